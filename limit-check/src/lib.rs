@@ -0,0 +1,835 @@
+/*!
+ * Limit Check
+ *
+ * The transfer limit math used by the Bridge Cards program to validate debits, without any
+ * Solana or Anchor dependencies. The program calls into this crate from
+ * `UserDelegateState::validate_debit_and_update`, so off-chain simulators can reuse the exact
+ * same rules.
+ *
+ * Checks, in order:
+ * - Validity window start (valid_from)
+ * - Validity window end (expires_at)
+ * - Minimum transfer amount
+ * - Per-transfer limit
+ * - Minimum slots since the last debit
+ * - Period limit, after resetting the period if it has elapsed (fixed window) or decaying
+ *   the period usage by the time since the last debit (sliding window). A strict boundary
+ *   anchors each new fixed-window period to the end of the previous one instead of the
+ *   time of the debit. Anchoring to the epoch aligns fixed-window periods to multiples of the
+ *   period since the unix epoch, e.g. UTC days for a one-day period
+ *
+ * A period limit of UNLIMITED_PERIOD_TRANSFER_LIMIT disables the period limit: the period
+ * math is skipped and the period usage is left untouched.
+ */
+#![no_std]
+
+/// Reason a debit was rejected
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LimitError {
+    /// The delegate's validity window has not started yet
+    DelegateNotYetValid,
+    /// The delegate's validity window has ended
+    DelegateExpired,
+    /// Amount is below the minimum transfer amount
+    BelowMinTransferAmount,
+    /// A usage counter would overflow
+    ArithmeticOverflow,
+    /// Amount exceeds the per-transfer limit
+    ExceedsMaxTransferLimit,
+    /// Too few slots have passed since the last debit
+    ExceedsMaxTransactionsPerSlot,
+    /// Amount exceeds what remains of the period limit
+    ExceedsTransferLimitPerPeriod,
+}
+
+/// How a delegate's period usage frees up over time
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LimitMode {
+    /// The whole period budget frees up at once when the period elapses
+    #[default]
+    FixedWindow = 0,
+    /// Period usage frees up linearly over the period following each debit
+    SlidingWindow = 1,
+}
+
+impl TryFrom<u8> for LimitMode {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(LimitMode::FixedWindow),
+            1 => Ok(LimitMode::SlidingWindow),
+            _ => Err(value),
+        }
+    }
+}
+
+/// Period transfer limit disabling the period limit, so debits skip the period math entirely
+pub const UNLIMITED_PERIOD_TRANSFER_LIMIT: u64 = u64::MAX;
+
+/// Limits configured on a delegate
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DelegateLimits {
+    // Maximum amount of tokens that can be transferred in a single transaction
+    pub per_transfer_limit: u64,
+    // Maximum amount of tokens that can be transferred within a period
+    pub period_transfer_limit: u64,
+    // Duration in seconds of the transfer limit period
+    pub transfer_limit_period_seconds: u32,
+    // Unix timestamp before which debits are rejected (0 = valid immediately)
+    pub valid_from: i64,
+    // Unix timestamp after which debits are rejected (0 = never expires)
+    pub expires_at: i64,
+    // Minimum amount of a single transfer (0 = no minimum)
+    pub min_transfer_amount: u64,
+    // How period usage frees up over time
+    pub limit_mode: LimitMode,
+    // Start fixed-window periods on fixed boundaries (the boundary second included) rather
+    // than at the first debit after the period elapsed
+    pub strict_boundary: bool,
+    // Align fixed-window periods to multiples of the period since the unix epoch (e.g. UTC
+    // midnight for a one-day period), starting a new period whenever that index changes
+    pub anchor_to_epoch: bool,
+}
+
+impl DelegateLimits {
+    /// Whether the period limit is disabled, see UNLIMITED_PERIOD_TRANSFER_LIMIT
+    pub fn period_unlimited(&self) -> bool {
+        self.period_transfer_limit == UNLIMITED_PERIOD_TRANSFER_LIMIT
+    }
+}
+
+/// Usage tracked on a delegate, updated by every successful debit
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DelegateUsage {
+    // Amount of tokens that have been transferred within the last period
+    pub period_transferred_amount: u64,
+    // Timestamp of when the period transferred amount was last reset (fixed window) or
+    // decayed (sliding window)
+    pub period_timestamp_last_reset: u64,
+    // Slot of the last transfer, if any
+    pub slot_last_transferred: u64,
+    // Amount of tokens transferred over the delegate's lifetime, never reset
+    pub total_transferred: u128,
+    // Number of successful debits over the delegate's lifetime, never reset
+    pub debit_nonce: u64,
+    // Index since the unix epoch of the last period a debit started, for epoch-anchored periods
+    pub last_period_index: u64,
+}
+
+/// Merchant-wide rules applied on top of a delegate's limits
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DebitRules {
+    // Minimum slots since the last debit (1 = one debit per slot)
+    pub min_slots_between_debits: u64,
+    // Whether a debit exactly at the end of the period starts a new period. When false
+    // (the default), the boundary second still belongs to the current period.
+    pub reset_at_period_boundary: bool,
+}
+
+impl Default for DebitRules {
+    fn default() -> Self {
+        Self {
+            min_slots_between_debits: 1,
+            reset_at_period_boundary: false,
+        }
+    }
+}
+
+/**
+ * Check a debit against a delegate's limits.
+ *
+ * @param limits Limits configured on the delegate
+ * @param usage Usage tracked on the delegate before this debit
+ * @param amount Number of tokens to debit
+ * @param current_time Current unix timestamp
+ * @param current_slot Current slot
+ * @param rules Merchant-wide rules applied to the debit
+ *
+ * @return The usage to store if the debit is allowed, or the reason it was rejected
+ */
+pub fn check_debit(
+    limits: &DelegateLimits,
+    usage: &DelegateUsage,
+    amount: u64,
+    current_time: u64,
+    current_slot: u64,
+    rules: &DebitRules,
+) -> Result<DelegateUsage, LimitError> {
+    if (current_time as i64) < limits.valid_from {
+        return Err(LimitError::DelegateNotYetValid);
+    }
+
+    if limits.expires_at != 0 && (current_time as i64) > limits.expires_at {
+        return Err(LimitError::DelegateExpired);
+    }
+
+    if amount < limits.min_transfer_amount {
+        return Err(LimitError::BelowMinTransferAmount);
+    }
+
+    if amount > limits.per_transfer_limit {
+        return Err(LimitError::ExceedsMaxTransferLimit);
+    }
+
+    if current_slot >= usage.slot_last_transferred
+        && current_slot - usage.slot_last_transferred < rules.min_slots_between_debits
+    {
+        return Err(LimitError::ExceedsMaxTransactionsPerSlot);
+    }
+
+    let mut updated = *usage;
+    // Without a period limit there is nothing to track, so skip the period math
+    if !limits.period_unlimited() {
+        match limits.limit_mode {
+            LimitMode::FixedWindow => {
+                if period_elapsed(limits, usage, current_time, rules) {
+                    updated.period_transferred_amount = 0;
+                    updated.period_timestamp_last_reset = period_start(limits, usage, current_time);
+                    if let Some(period_index) = epoch_period_index(limits, current_time) {
+                        updated.last_period_index = period_index;
+                    }
+                }
+            }
+            LimitMode::SlidingWindow => {
+                updated.period_transferred_amount =
+                    decayed_period_amount(limits, usage, current_time);
+                // Never move the decay reference back, e.g. on a clock behind the last debit
+                updated.period_timestamp_last_reset =
+                    current_time.max(usage.period_timestamp_last_reset);
+            }
+        }
+        // An overflowing total is necessarily above the limit
+        updated.period_transferred_amount = updated
+            .period_transferred_amount
+            .checked_add(amount)
+            .filter(|total| *total <= limits.period_transfer_limit)
+            .ok_or(LimitError::ExceedsTransferLimitPerPeriod)?;
+    }
+
+    updated.total_transferred = updated
+        .total_transferred
+        .checked_add(amount as u128)
+        .ok_or(LimitError::ArithmeticOverflow)?;
+    updated.debit_nonce = updated
+        .debit_nonce
+        .checked_add(1)
+        .ok_or(LimitError::ArithmeticOverflow)?;
+    updated.slot_last_transferred = current_slot;
+    Ok(updated)
+}
+
+/// Whether the delegate's period has elapsed, so the next debit starts a new one.
+/// Always false for sliding windows, which decay their usage instead of resetting it, and
+/// without a period limit.
+pub fn period_elapsed(
+    limits: &DelegateLimits,
+    usage: &DelegateUsage,
+    current_time: u64,
+    rules: &DebitRules,
+) -> bool {
+    if limits.limit_mode == LimitMode::SlidingWindow || limits.period_unlimited() {
+        return false;
+    }
+    // A clock behind the last period index (e.g. after a validator restart) is still in it
+    if let Some(period_index) = epoch_period_index(limits, current_time) {
+        return period_index > usage.last_period_index;
+    }
+    // A clock behind the period start (e.g. after a validator restart) is still in the period
+    let period = limits.transfer_limit_period_seconds as u64;
+    match current_time.checked_sub(usage.period_timestamp_last_reset) {
+        Some(elapsed) if limits.strict_boundary || rules.reset_at_period_boundary => {
+            elapsed >= period
+        }
+        Some(elapsed) => elapsed > period,
+        None => false,
+    }
+}
+
+/// Index since the unix epoch of the period containing current_time, if the delegate's periods
+/// are anchored to the epoch (None for a zero period, which can't be anchored)
+fn epoch_period_index(limits: &DelegateLimits, current_time: u64) -> Option<u64> {
+    let period = limits.transfer_limit_period_seconds as u64;
+    (limits.anchor_to_epoch && period != 0).then(|| current_time / period)
+}
+
+/// Start of the period a fixed-window debit at current_time opens once the previous period
+/// has elapsed: the current time, or under a strict boundary the last period boundary at or
+/// before it, so periods don't drift with the timing of debits. Epoch-anchored periods start
+/// at the last multiple of the period since the epoch.
+fn period_start(limits: &DelegateLimits, usage: &DelegateUsage, current_time: u64) -> u64 {
+    let period = limits.transfer_limit_period_seconds as u64;
+    if let Some(period_index) = epoch_period_index(limits, current_time) {
+        return period_index * period;
+    }
+    if !limits.strict_boundary || period == 0 {
+        return current_time;
+    }
+    let elapsed = current_time - usage.period_timestamp_last_reset;
+    usage.period_timestamp_last_reset + elapsed / period * period
+}
+
+/**
+ * Amount a delegate can still debit in the current period, accounting for a period reset.
+ *
+ * @param limits Limits configured on the delegate
+ * @param usage Usage tracked on the delegate
+ * @param current_time Current unix timestamp
+ * @param rules Merchant-wide rules applied to debits
+ *
+ * @return The full period limit if the period has elapsed or is unlimited, otherwise what
+ *         remains of it (after decay for sliding windows)
+ */
+pub fn remaining_period_allowance(
+    limits: &DelegateLimits,
+    usage: &DelegateUsage,
+    current_time: u64,
+    rules: &DebitRules,
+) -> u64 {
+    if limits.period_unlimited() {
+        return UNLIMITED_PERIOD_TRANSFER_LIMIT;
+    }
+    let period_transferred_amount = match limits.limit_mode {
+        LimitMode::FixedWindow if period_elapsed(limits, usage, current_time, rules) => 0,
+        LimitMode::FixedWindow => usage.period_transferred_amount,
+        LimitMode::SlidingWindow => decayed_period_amount(limits, usage, current_time),
+    };
+    limits
+        .period_transfer_limit
+        .saturating_sub(period_transferred_amount)
+}
+
+/**
+ * Period usage still counted by a sliding window, decayed linearly by the time since the
+ * last debit: a debit is fully released transfer_limit_period_seconds after it was made.
+ *
+ * @param limits Limits configured on the delegate
+ * @param usage Usage tracked on the delegate
+ * @param current_time Current unix timestamp
+ *
+ * @return The decayed period transferred amount, rounded up so usage never frees up early
+ */
+pub fn decayed_period_amount(
+    limits: &DelegateLimits,
+    usage: &DelegateUsage,
+    current_time: u64,
+) -> u64 {
+    let period = limits.transfer_limit_period_seconds as u128;
+    let elapsed = current_time.saturating_sub(usage.period_timestamp_last_reset) as u128;
+    if elapsed >= period {
+        return 0;
+    }
+    // amount * (period - elapsed) / period fits in a u64 since period - elapsed <= period
+    let remaining = period - elapsed;
+    (usage.period_transferred_amount as u128 * remaining).div_ceil(period) as u64
+}
+
+/**
+ * Seconds remaining until a delegate's period resets. For sliding windows, this is the time
+ * until the period usage has fully decayed.
+ *
+ * @param limits Limits configured on the delegate
+ * @param usage Usage tracked on the delegate
+ * @param current_time Current unix timestamp
+ *
+ * @return Seconds until the period resets, or 0 if it has already elapsed
+ */
+pub fn time_until_reset(limits: &DelegateLimits, usage: &DelegateUsage, current_time: u64) -> u64 {
+    usage
+        .period_timestamp_last_reset
+        .saturating_add(limits.transfer_limit_period_seconds as u64)
+        .saturating_sub(current_time)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Mirrors the UserDelegateState tests in the bridge_cards program
+    const LIMITS: DelegateLimits = DelegateLimits {
+        per_transfer_limit: 1000,
+        period_transfer_limit: 2000,
+        transfer_limit_period_seconds: 3600, // 1 hour
+        valid_from: 0,
+        expires_at: 0,
+        min_transfer_amount: 0,
+        limit_mode: LimitMode::FixedWindow,
+        strict_boundary: false,
+        anchor_to_epoch: false,
+    };
+
+    fn setup_usage() -> DelegateUsage {
+        DelegateUsage {
+            period_transferred_amount: 0,
+            period_timestamp_last_reset: 100,
+            slot_last_transferred: 0,
+            total_transferred: 0,
+            debit_nonce: 0,
+            last_period_index: 0,
+        }
+    }
+
+    fn debit(
+        usage: &mut DelegateUsage,
+        amount: u64,
+        time: u64,
+        slot: u64,
+    ) -> Result<(), LimitError> {
+        *usage = check_debit(&LIMITS, usage, amount, time, slot, &DebitRules::default())?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_successful_transfer() {
+        let mut usage = setup_usage();
+        assert!(debit(&mut usage, 500, 200, 1).is_ok());
+        assert_eq!(usage.period_transferred_amount, 500);
+    }
+
+    #[test]
+    fn test_exceeds_per_transfer_limit() {
+        let mut usage = setup_usage();
+        assert_eq!(
+            debit(&mut usage, 1500, 200, 1),
+            Err(LimitError::ExceedsMaxTransferLimit)
+        );
+    }
+
+    #[test]
+    fn test_exceeds_period_transfer_limit() {
+        let mut usage = setup_usage();
+        assert!(debit(&mut usage, 900, 200, 1).is_ok());
+        assert!(debit(&mut usage, 200, 300, 2).is_ok());
+
+        assert_eq!(
+            debit(&mut usage, 1000, 300, 1),
+            Err(LimitError::ExceedsTransferLimitPerPeriod)
+        );
+    }
+
+    #[test]
+    fn test_period_reset() {
+        let mut usage = setup_usage();
+        assert!(debit(&mut usage, 900, 200, 1).is_ok());
+        assert_eq!(usage.period_transferred_amount, 900);
+
+        assert!(debit(&mut usage, 900, 4000, 2).is_ok());
+        assert_eq!(usage.period_transferred_amount, 900);
+        assert_eq!(usage.period_timestamp_last_reset, 4000);
+    }
+
+    #[test]
+    fn test_multiple_transfers_within_period() {
+        let mut usage = setup_usage();
+        assert!(debit(&mut usage, 500, 200, 1).is_ok());
+        assert!(debit(&mut usage, 300, 300, 2).is_ok());
+        assert!(debit(&mut usage, 200, 400, 3).is_ok());
+        assert_eq!(usage.period_transferred_amount, 1000);
+    }
+
+    #[test]
+    fn test_exceeds_max_transactions_per_slot() {
+        let mut usage = setup_usage();
+        assert!(debit(&mut usage, 500, 200, 1).is_ok());
+        assert_eq!(usage.slot_last_transferred, 1);
+
+        assert_eq!(
+            debit(&mut usage, 300, 300, 1),
+            Err(LimitError::ExceedsMaxTransactionsPerSlot)
+        );
+
+        assert!(debit(&mut usage, 200, 400, 2).is_ok());
+        assert_eq!(usage.slot_last_transferred, 2);
+    }
+
+    #[test]
+    fn test_rejected_debit_leaves_usage_unchanged() {
+        let mut usage = setup_usage();
+        assert!(debit(&mut usage, 900, 200, 1).is_ok());
+        let before = usage;
+
+        assert!(debit(&mut usage, 1200, 300, 2).is_err());
+        assert_eq!(usage, before);
+    }
+
+    #[test]
+    fn test_min_slots_between_debits() {
+        let rules = DebitRules {
+            min_slots_between_debits: 10,
+            ..Default::default()
+        };
+        let usage = setup_usage();
+        let usage = check_debit(&LIMITS, &usage, 100, 200, 100, &rules).unwrap();
+
+        assert_eq!(
+            check_debit(&LIMITS, &usage, 100, 200, 109, &rules),
+            Err(LimitError::ExceedsMaxTransactionsPerSlot)
+        );
+        let usage = check_debit(&LIMITS, &usage, 100, 200, 110, &rules).unwrap();
+        assert_eq!(usage.slot_last_transferred, 110);
+    }
+
+    #[test]
+    fn test_min_slots_between_debits_gaps() {
+        // (min slots, earliest slot after a debit at slot 100 that is accepted)
+        for (min_slots_between_debits, next_allowed_slot) in [(0, 100), (1, 101), (25, 125)] {
+            let rules = DebitRules {
+                min_slots_between_debits,
+                ..Default::default()
+            };
+            let usage = check_debit(&LIMITS, &setup_usage(), 100, 200, 100, &rules).unwrap();
+
+            if next_allowed_slot > 100 {
+                assert_eq!(
+                    check_debit(&LIMITS, &usage, 100, 200, next_allowed_slot - 1, &rules),
+                    Err(LimitError::ExceedsMaxTransactionsPerSlot),
+                    "min_slots_between_debits = {min_slots_between_debits}"
+                );
+            }
+            let usage = check_debit(&LIMITS, &usage, 100, 200, next_allowed_slot, &rules).unwrap();
+            assert_eq!(usage.slot_last_transferred, next_allowed_slot);
+        }
+    }
+
+    #[test]
+    fn test_period_boundary() {
+        let mut usage = setup_usage();
+        usage.period_transferred_amount = 1500;
+        // Exactly one period after the last reset
+        let boundary = 100 + 3600;
+
+        // By default the boundary still belongs to the current period
+        assert_eq!(
+            check_debit(&LIMITS, &usage, 1000, boundary, 1, &DebitRules::default()),
+            Err(LimitError::ExceedsTransferLimitPerPeriod)
+        );
+
+        let rules = DebitRules {
+            reset_at_period_boundary: true,
+            ..Default::default()
+        };
+        let updated = check_debit(&LIMITS, &usage, 1000, boundary, 1, &rules).unwrap();
+        assert_eq!(updated.period_transferred_amount, 1000);
+        assert_eq!(updated.period_timestamp_last_reset, boundary);
+    }
+
+    #[test]
+    fn test_strict_boundary() {
+        let limits = DelegateLimits {
+            strict_boundary: true,
+            ..LIMITS
+        };
+        let rules = DebitRules::default();
+        let mut usage = setup_usage();
+        usage.period_transferred_amount = 2000;
+
+        // The boundary second starts the new period, even without reset_at_period_boundary
+        let boundary = 100 + 3600;
+        assert_eq!(
+            check_debit(&LIMITS, &usage, 1000, boundary, 1, &rules),
+            Err(LimitError::ExceedsTransferLimitPerPeriod)
+        );
+        let updated = check_debit(&limits, &usage, 1000, boundary, 1, &rules).unwrap();
+        assert_eq!(updated.period_timestamp_last_reset, boundary);
+
+        // A late debit opens the period at the boundary, not at the time of the debit
+        let late = boundary + 1800;
+        let updated = check_debit(&limits, &usage, 1000, late, 1, &rules).unwrap();
+        assert_eq!(updated.period_timestamp_last_reset, boundary);
+        assert_eq!(time_until_reset(&limits, &updated, late), 1800);
+        let drifted = check_debit(&LIMITS, &usage, 1000, late, 1, &rules).unwrap();
+        assert_eq!(drifted.period_timestamp_last_reset, late);
+
+        // The period's budget is shared by every debit until the next boundary
+        let updated = check_debit(&limits, &updated, 1000, late, 2, &rules).unwrap();
+        assert_eq!(
+            check_debit(&limits, &updated, 1, boundary + 3599, 3, &rules),
+            Err(LimitError::ExceedsTransferLimitPerPeriod)
+        );
+        assert!(check_debit(&limits, &updated, 1000, boundary + 3600, 3, &rules).is_ok());
+
+        // After several idle periods, the new period starts at the last boundary
+        let updated = check_debit(&limits, &usage, 1000, 100 + 3 * 3600 + 5, 1, &rules).unwrap();
+        assert_eq!(updated.period_timestamp_last_reset, 100 + 3 * 3600);
+    }
+
+    #[test]
+    fn test_anchor_to_epoch() {
+        const DAY: u64 = 86_400;
+        let limits = DelegateLimits {
+            transfer_limit_period_seconds: DAY as u32,
+            anchor_to_epoch: true,
+            ..LIMITS
+        };
+        let rules = DebitRules::default();
+        let usage = setup_usage();
+
+        // Two debits in the same UTC day share its budget, whatever the time since the first
+        let morning = 20_000 * DAY + 3600;
+        let updated = check_debit(&limits, &usage, 1000, morning, 1, &rules).unwrap();
+        assert_eq!(updated.last_period_index, 20_000);
+        assert_eq!(updated.period_timestamp_last_reset, 20_000 * DAY);
+        let updated = check_debit(&limits, &updated, 1000, morning + 3600, 2, &rules).unwrap();
+        assert_eq!(updated.period_transferred_amount, 2000);
+        assert_eq!(
+            check_debit(&limits, &updated, 1, 20_001 * DAY - 1, 3, &rules),
+            Err(LimitError::ExceedsTransferLimitPerPeriod)
+        );
+        assert_eq!(time_until_reset(&limits, &updated, morning), DAY - 3600);
+
+        // Midnight starts the next day's budget, less than 24 hours after the first debit
+        let midnight = 20_001 * DAY;
+        assert!(period_elapsed(&limits, &updated, midnight, &rules));
+        let next_day = check_debit(&limits, &updated, 1000, midnight, 3, &rules).unwrap();
+        assert_eq!(next_day.period_transferred_amount, 1000);
+        assert_eq!(next_day.last_period_index, 20_001);
+        assert_eq!(next_day.period_timestamp_last_reset, midnight);
+
+        // A clock behind the last period index stays in that period
+        assert!(!period_elapsed(&limits, &next_day, midnight - 1, &rules));
+
+        // Without anchoring, the period runs 24 hours from the first debit
+        let unanchored = DelegateLimits {
+            anchor_to_epoch: false,
+            ..limits
+        };
+        let updated = check_debit(&unanchored, &usage, 1000, morning, 1, &rules).unwrap();
+        let updated = check_debit(&unanchored, &updated, 1000, morning, 2, &rules).unwrap();
+        assert_eq!(
+            check_debit(&unanchored, &updated, 1000, midnight, 3, &rules),
+            Err(LimitError::ExceedsTransferLimitPerPeriod)
+        );
+    }
+
+    #[test]
+    fn test_time_until_reset() {
+        let usage = setup_usage();
+        assert_eq!(time_until_reset(&LIMITS, &usage, 100), 3600);
+        assert_eq!(time_until_reset(&LIMITS, &usage, 1900), 1800);
+        assert_eq!(time_until_reset(&LIMITS, &usage, 3700), 0);
+        assert_eq!(time_until_reset(&LIMITS, &usage, 10_000), 0);
+    }
+
+    #[test]
+    fn test_valid_from() {
+        let limits = DelegateLimits {
+            valid_from: 1000,
+            ..LIMITS
+        };
+        let usage = setup_usage();
+        let rules = DebitRules::default();
+
+        assert_eq!(
+            check_debit(&limits, &usage, 100, 999, 1, &rules),
+            Err(LimitError::DelegateNotYetValid)
+        );
+        assert!(check_debit(&limits, &usage, 100, 1000, 1, &rules).is_ok());
+    }
+
+    #[test]
+    fn test_expires_at() {
+        let limits = DelegateLimits {
+            expires_at: 5000,
+            ..LIMITS
+        };
+        let usage = setup_usage();
+        let rules = DebitRules::default();
+
+        assert!(check_debit(&limits, &usage, 100, 4999, 1, &rules).is_ok());
+        assert!(check_debit(&limits, &usage, 100, 5000, 1, &rules).is_ok());
+        assert_eq!(
+            check_debit(&limits, &usage, 100, 5001, 1, &rules),
+            Err(LimitError::DelegateExpired)
+        );
+    }
+
+    #[test]
+    fn test_clock_behind_period_start() {
+        let mut usage = setup_usage();
+        usage.period_timestamp_last_reset = 10_000;
+        usage.period_transferred_amount = 1500;
+
+        // Still within the period: the remaining allowance applies, nothing is reset
+        assert_eq!(debit(&mut usage, 500, 9000, 1), Ok(()));
+        assert_eq!(usage.period_transferred_amount, 2000);
+        assert_eq!(usage.period_timestamp_last_reset, 10_000);
+        assert_eq!(
+            debit(&mut usage, 1, 9000, 2),
+            Err(LimitError::ExceedsTransferLimitPerPeriod)
+        );
+    }
+
+    #[test]
+    fn test_period_total_overflow() {
+        let limits = DelegateLimits {
+            per_transfer_limit: u64::MAX,
+            period_transfer_limit: u64::MAX - 1,
+            ..LIMITS
+        };
+        let mut usage = setup_usage();
+        usage.period_transferred_amount = u64::MAX - 2;
+        let rules = DebitRules::default();
+
+        assert_eq!(
+            check_debit(&limits, &usage, 2, 200, 1, &rules),
+            Err(LimitError::ExceedsTransferLimitPerPeriod)
+        );
+        assert_eq!(
+            check_debit(&limits, &usage, u64::MAX, 200, 1, &rules),
+            Err(LimitError::ExceedsTransferLimitPerPeriod)
+        );
+        let updated = check_debit(&limits, &usage, 1, 200, 1, &rules).unwrap();
+        assert_eq!(updated.period_transferred_amount, u64::MAX - 1);
+    }
+
+    #[test]
+    fn test_unlimited_period() {
+        let limits = DelegateLimits {
+            period_transfer_limit: UNLIMITED_PERIOD_TRANSFER_LIMIT,
+            ..LIMITS
+        };
+        let mut usage = setup_usage();
+        usage.period_transferred_amount = u64::MAX - 1;
+        let rules = DebitRules::default();
+
+        // The per-transfer and per-slot limits still apply
+        assert_eq!(
+            check_debit(&limits, &usage, 1001, 200, 1, &rules),
+            Err(LimitError::ExceedsMaxTransferLimit)
+        );
+        let updated = check_debit(&limits, &usage, 1000, 200, 1, &rules).unwrap();
+        assert_eq!(
+            check_debit(&limits, &updated, 1000, 200, 1, &rules),
+            Err(LimitError::ExceedsMaxTransactionsPerSlot)
+        );
+
+        // The period usage is neither checked nor updated, even past the period
+        let updated = check_debit(&limits, &updated, 1000, 9000, 2, &rules).unwrap();
+        assert_eq!(updated.period_transferred_amount, u64::MAX - 1);
+        assert_eq!(updated.period_timestamp_last_reset, 100);
+        assert_eq!(updated.total_transferred, 2000);
+        assert_eq!(updated.debit_nonce, 2);
+        assert!(!period_elapsed(&limits, &updated, 9000, &rules));
+        assert_eq!(
+            remaining_period_allowance(&limits, &updated, 9000, &rules),
+            UNLIMITED_PERIOD_TRANSFER_LIMIT
+        );
+    }
+
+    #[test]
+    fn test_remaining_period_allowance() {
+        let mut usage = setup_usage();
+        let rules = DebitRules::default();
+        assert_eq!(
+            remaining_period_allowance(&LIMITS, &usage, 200, &rules),
+            2000
+        );
+
+        debit(&mut usage, 500, 200, 1).unwrap();
+        assert_eq!(
+            remaining_period_allowance(&LIMITS, &usage, 200, &rules),
+            1500
+        );
+
+        // The boundary second is still in the period unless configured otherwise
+        assert_eq!(
+            remaining_period_allowance(&LIMITS, &usage, 3700, &rules),
+            1500
+        );
+        let boundary_rules = DebitRules {
+            reset_at_period_boundary: true,
+            ..rules
+        };
+        assert_eq!(
+            remaining_period_allowance(&LIMITS, &usage, 3700, &boundary_rules),
+            2000
+        );
+        assert_eq!(
+            remaining_period_allowance(&LIMITS, &usage, 3701, &rules),
+            2000
+        );
+    }
+
+    #[test]
+    fn test_min_transfer_amount() {
+        let limits = DelegateLimits {
+            min_transfer_amount: 100,
+            ..LIMITS
+        };
+        let usage = setup_usage();
+        let rules = DebitRules::default();
+
+        assert_eq!(
+            check_debit(&limits, &usage, 99, 200, 1, &rules),
+            Err(LimitError::BelowMinTransferAmount)
+        );
+        assert!(check_debit(&limits, &usage, 100, 200, 1, &rules).is_ok());
+    }
+
+    #[test]
+    fn test_total_transferred() {
+        let mut usage = setup_usage();
+
+        // Accumulates across period resets
+        debit(&mut usage, 1000, 200, 1).unwrap();
+        debit(&mut usage, 1000, 300, 2).unwrap();
+        debit(&mut usage, 1000, 4000, 3).unwrap();
+        assert_eq!(usage.period_transferred_amount, 1000);
+        assert_eq!(usage.total_transferred, 3000);
+
+        // Rejected debits don't count
+        assert!(debit(&mut usage, 1001, 4000, 4).is_err());
+        assert_eq!(usage.total_transferred, 3000);
+
+        usage.total_transferred = u128::MAX;
+        assert_eq!(
+            debit(&mut usage, 1, 4000, 5),
+            Err(LimitError::ArithmeticOverflow)
+        );
+    }
+
+    #[test]
+    fn test_sliding_window_decay() {
+        let limits = DelegateLimits {
+            limit_mode: LimitMode::SlidingWindow,
+            ..LIMITS
+        };
+        let usage = DelegateUsage {
+            period_transferred_amount: 2000,
+            ..setup_usage()
+        };
+
+        assert_eq!(decayed_period_amount(&limits, &usage, 100), 2000);
+        // A quarter of the period releases a quarter of the usage
+        assert_eq!(decayed_period_amount(&limits, &usage, 1000), 1500);
+        // Rounded up, so usage never frees up early
+        assert_eq!(decayed_period_amount(&limits, &usage, 101), 2000);
+        assert_eq!(decayed_period_amount(&limits, &usage, 3700), 0);
+        assert_eq!(
+            remaining_period_allowance(&limits, &usage, 1000, &DebitRules::default()),
+            500
+        );
+        assert!(!period_elapsed(
+            &limits,
+            &usage,
+            10_000,
+            &DebitRules::default()
+        ));
+
+        // A debit stores the decayed usage and restarts the decay from now
+        let updated = check_debit(&limits, &usage, 500, 1000, 1, &DebitRules::default()).unwrap();
+        assert_eq!(updated.period_transferred_amount, 2000);
+        assert_eq!(updated.period_timestamp_last_reset, 1000);
+        assert_eq!(
+            check_debit(&limits, &updated, 1, 1000, 2, &DebitRules::default()),
+            Err(LimitError::ExceedsTransferLimitPerPeriod)
+        );
+    }
+
+    #[test]
+    fn test_limit_mode_from_u8() {
+        assert_eq!(LimitMode::try_from(0), Ok(LimitMode::FixedWindow));
+        assert_eq!(LimitMode::try_from(1), Ok(LimitMode::SlidingWindow));
+        assert_eq!(LimitMode::try_from(2), Err(2));
+    }
+}
@@ -57,8 +57,772 @@ pub enum ErrorCode {
      *
      * This error occurs when:
      * - A debit_user instruction is called
-     * - There has already been a transaction in the current slot
+     * - There has already been a transaction in the current slot, or fewer than the
+     *   configured min_slots_between_debits have passed since the last one
      */
     #[msg("Exceeds max transactions per slot")]
     ExceedsMaxTransactionsPerSlot,
+
+    /**
+     * The router program is not the one configured for the merchant.
+     *
+     * This error occurs when:
+     * - A debit_user_with_swap instruction is called
+     * - The provided router program does not match the merchant config's router_program
+     *   (including when no router is configured)
+     *
+     * How to handle:
+     * - Pass the router program stored in the merchant config
+     * - Request the admin to configure a router for the merchant
+     */
+    #[msg("Router not configured for merchant")]
+    RouterNotConfigured,
+
+    /**
+     * The router program has not been approved by the admin.
+     *
+     * This error occurs when:
+     * - A debit_user_with_swap instruction is called
+     * - The router's state PDA is not marked as allowed
+     *
+     * How to handle:
+     * - Request the admin to allowlist the router program
+     */
+    #[msg("Router not allowed")]
+    RouterNotAllowed,
+
+    /**
+     * The owner of the user token account is not a regular wallet.
+     *
+     * This error occurs when:
+     * - The merchant config has require_user_owner_system_owned enabled
+     * - The user token account's owner is owned by a program other than the system program
+     *   (e.g. a PDA of another program)
+     *
+     * How to handle:
+     * - Debit a token account owned by a regular wallet
+     * - Request the admin to disable the requirement for the merchant
+     */
+    #[msg("User token account owner is not system owned")]
+    UserOwnerNotSystemOwned,
+
+    /**
+     * The destination is not the merchant's primary destination.
+     *
+     * This error occurs when:
+     * - The merchant config has enforce_primary_destination enabled
+     * - The destination state is not marked as primary
+     * - The user delegate does not allow alternate destinations
+     *
+     * How to handle:
+     * - Debit to the merchant's primary destination
+     * - Request the merchant manager to allow alternate destinations on the delegate
+     */
+    #[msg("Destination is not the merchant's primary destination")]
+    NonPrimaryDestination,
+
+    /**
+     * Debits are paused program-wide.
+     *
+     * This error occurs when:
+     * - The admin has set the PAUSED feature flag
+     * - Any debit instruction is called
+     *
+     * How to handle:
+     * - Retry once the admin clears the PAUSED feature flag
+     */
+    #[msg("Program is paused")]
+    ProgramPaused,
+
+    /**
+     * Swaps are disabled program-wide.
+     *
+     * This error occurs when:
+     * - The admin has set the SWAPS_DISABLED feature flag
+     * - A debit_user_with_swap instruction is called
+     *
+     * How to handle:
+     * - Use debit_user without a swap
+     * - Retry once the admin clears the SWAPS_DISABLED feature flag
+     */
+    #[msg("Swaps are disabled")]
+    SwapsDisabled,
+
+    /**
+     * Raising a delegate's limits requires the admin's co-signature.
+     *
+     * This error occurs when:
+     * - The merchant config has managers_can_only_lower_limits enabled
+     * - add_or_update_user_delegate is called on an existing delegate with a higher
+     *   per-transfer or period limit, a shorter period, a later or removed expiry, a lifted
+     *   or changed destination restriction, a different limit mode, or epoch anchoring enabled
+     * - reissue_delegate is called with a higher limit, a shorter period, or carry_usage set
+     *   to false
+     *
+     * How to handle:
+     * - Raise the limits with admin_raise_delegate_limits, signed by the manager and admin
+     * - For other changes, ask the admin to lift managers_can_only_lower_limits in the
+     *   merchant config
+     */
+    #[msg("Raising delegate limits requires the admin")]
+    LimitRaiseRequiresAdmin,
+
+    /**
+     * The user and destination token accounts belong to different token programs.
+     *
+     * This error occurs when:
+     * - The user token account or destination token account is not owned by the
+     *   token program passed to the debit (e.g. one is SPL Token, the other Token-2022)
+     *
+     * How to handle:
+     * - Use token accounts of the same token program as the mint
+     */
+    #[msg("Token accounts belong to different token programs")]
+    CrossProgramTransferNotAllowed,
+
+    /**
+     * The delegate is not active yet.
+     *
+     * This error occurs when:
+     * - The delegate has a valid_from timestamp in the future
+     * - A debit is attempted before that time
+     *
+     * How to handle:
+     * - Retry once valid_from has passed
+     * - Request the merchant manager to move valid_from earlier
+     */
+    #[msg("Delegate is not valid yet")]
+    DelegateNotYetValid,
+
+    /**
+     * The destination did not receive the expected amount.
+     *
+     * This error occurs when:
+     * - The POST_CONDITION_CHECKS feature flag is set
+     * - After the transfer, the destination balance did not increase by exactly the debited
+     *   amount minus the mint's token-2022 transfer fee
+     *
+     * How to handle:
+     * - Investigate the mint's extensions (transfer hooks, fee configuration)
+     * - Contact the program admin
+     */
+    #[msg("Debit post-condition failed")]
+    PostConditionFailed,
+
+    /**
+     * The delegate has expired.
+     *
+     * This error occurs when:
+     * - The delegate has a non-zero expires_at timestamp
+     * - A debit is attempted after that time
+     *
+     * How to handle:
+     * - Request the merchant manager to re-issue the delegate with a later expiry
+     */
+    #[msg("Delegate has expired")]
+    DelegateExpired,
+
+    /**
+     * The account cannot be used as a merchant manager.
+     *
+     * This error occurs when:
+     * - The manager is the program state PDA or a token account, which can never sign
+     * - The REQUIRE_MANAGER_COSIGN feature flag is set and the manager did not sign
+     *
+     * How to handle:
+     * - Verify the manager public key
+     * - Have the new manager co-sign the transaction
+     */
+    #[msg("Invalid merchant manager")]
+    InvalidManager,
+
+    /**
+     * A token account does not hold the mint of the debit.
+     *
+     * This error occurs when:
+     * - The user token account's mint differs from the provided mint
+     * - The destination token account's mint differs from the provided mint
+     *
+     * How to handle:
+     * - Pass the mint of the user's token account
+     * - Use a destination token account for the same mint
+     */
+    #[msg("Token account mint does not match")]
+    MismatchedMint,
+
+    /**
+     * The accounts passed to a batch instruction do not match its amounts.
+     *
+     * This error occurs when:
+     * - No amounts are provided
+     * - The remaining accounts are not one (token account, state PDA) pair per amount
+     * - batch_add_destinations is given no destinations, an unpaired account or a repeated
+     *   destination
+     *
+     * How to handle:
+     * - Pass exactly one destination token account and destination state PDA per amount
+     * - Pass each destination once, followed by its destination state PDA
+     */
+    #[msg("Batch accounts do not match the amounts")]
+    InvalidBatchAccounts,
+
+    /**
+     * The destination is not authorized for the merchant.
+     *
+     * This error occurs when:
+     * - A debit or batch debit leg targets a destination whose destination_state.allowed is false
+     * - A debit targets a destination without a destination_state whose token account is not
+     *   owned by the merchant config's allow_any_destination_owner
+     * - Under the denylist destination policy, the destination_state.allowed is true
+     *
+     * How to handle:
+     * - Remove the destination from the batch
+     * - Request the program admin to allow the destination
+     */
+    #[msg("Destination is not allowed")]
+    DestinationNotAllowed,
+
+    /**
+     * Debits are paused for the merchant.
+     *
+     * This error occurs when:
+     * - The merchant config has paused set, e.g. during an incident
+     *
+     * How to handle:
+     * - Retry once the program admin unpauses the merchant
+     */
+    #[msg("Merchant is paused")]
+    MerchantPaused,
+
+    /**
+     * The transfer amount is below the delegate's minimum.
+     *
+     * This error occurs when:
+     * - The delegate has a non-zero min_transfer_amount
+     * - A debit is attempted for a smaller amount
+     *
+     * How to handle:
+     * - Debit at least min_transfer_amount
+     * - Request the merchant manager to lower the minimum
+     */
+    #[msg("Transfer amount is below the minimum")]
+    BelowMinTransferAmount,
+
+    /**
+     * A counter would overflow.
+     *
+     * This error occurs when:
+     * - A debit would overflow one of the delegate's usage counters
+     *
+     * How to handle:
+     * - Contact the program admin
+     */
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+
+    /**
+     * The limit mode is not supported.
+     *
+     * This error occurs when:
+     * - A delegate is added or updated with a limit_mode other than 0 (fixed window) or
+     *   1 (sliding window)
+     *
+     * How to handle:
+     * - Use one of the supported limit modes
+     */
+    #[msg("Invalid limit mode")]
+    InvalidLimitMode,
+
+    /**
+     * The user token account is not delegated to the user delegate PDA.
+     *
+     * This error occurs when:
+     * - The user has not approved the delegate PDA on their token account
+     * - The user has revoked the approval or approved another delegate
+     *
+     * How to handle:
+     * - Ask the user to approve the user delegate PDA on their token account
+     */
+    #[msg("Token account is not delegated to the user delegate")]
+    DelegateMismatch,
+
+    /**
+     * The user token account's approved amount is too small for the debit.
+     *
+     * This error occurs when:
+     * - The debit amount exceeds the delegated_amount approved for the delegate PDA
+     * - Previous debits have used up the approval
+     *
+     * How to handle:
+     * - Ask the user to approve a larger amount
+     * - Debit a smaller amount
+     */
+    #[msg("Delegated amount is insufficient")]
+    InsufficientDelegatedAmount,
+
+    /**
+     * The merchant-wide period limit would be exceeded.
+     *
+     * This error occurs when:
+     * - The merchant has a spend tracker
+     * - The amount would push the merchant's total for the period above its limit
+     *
+     * How to handle:
+     * - Wait for the merchant's period to reset
+     * - Request the program admin to raise the merchant's period limit
+     */
+    #[msg("Transfer exceeds the merchant period limit")]
+    ExceedsMerchantPeriodLimit,
+
+    /**
+     * The transfer limit period is invalid.
+     *
+     * This error occurs when:
+     * - A delegate is added, updated or re-issued with a transfer_limit_period of 0, which
+     *   would start a new period on every debit
+     * - A delegate is added, updated or re-issued with usage carried over with a period that,
+     *   added to the start of its current period, overflows u64
+     *
+     * How to handle:
+     * - Use a period of at least one second and at most the seconds left before u64::MAX
+     */
+    #[msg("Transfer limit period must be non-zero and must not overflow the period end")]
+    InvalidPeriod,
+
+    /**
+     * The delegate's transfer limits are inconsistent.
+     *
+     * This error occurs when:
+     * - A delegate is added, updated or re-issued with a period_transfer_limit below its
+     *   per_transfer_limit
+     *
+     * How to handle:
+     * - Set the period limit to at least the per-transfer limit
+     */
+    #[msg("Period transfer limit is below the per-transfer limit")]
+    InvalidTransferLimits,
+
+    /**
+     * A token account involved in the debit is frozen.
+     *
+     * This error occurs when:
+     * - The user's or the destination token account has been frozen by the mint's freeze
+     *   authority, or was created frozen by a token-2022 default account state
+     *
+     * How to handle:
+     * - Ask the mint's freeze authority to thaw the account
+     * - Debit to a different destination if the destination is frozen
+     */
+    #[msg("Token account is frozen")]
+    TokenAccountFrozen,
+
+    /**
+     * The account being closed is still the approved delegate of a token account.
+     *
+     * This error occurs when:
+     * - close_account is passed a user token account whose SPL delegate is the account being
+     *   closed, so closing it would leave the approval pointing at a dead PDA
+     *
+     * How to handle:
+     * - Have the user revoke the approval before closing the delegate
+     */
+    #[msg("Account is still the active delegate of the token account")]
+    DelegateStillActive,
+
+    /**
+     * The debitor's per-call limit would be exceeded.
+     *
+     * This error occurs when:
+     * - The debitor has a non-zero per_call_limit
+     * - The amount of a single debit is above it, even if the delegate's limits allow it
+     *
+     * How to handle:
+     * - Split the debit into smaller amounts
+     * - Request the merchant manager to raise the debitor's per-call limit
+     */
+    #[msg("Transfer exceeds the debitor per-call limit")]
+    ExceedsDebitorPerCallLimit,
+
+    /**
+     * The debitor's daily limit would be exceeded.
+     *
+     * This error occurs when:
+     * - The debitor has a non-zero daily_limit
+     * - The amount would push the debitor's total for the day above it, across all delegates
+     *
+     * How to handle:
+     * - Wait for the debitor's day to reset
+     * - Request the merchant manager to raise the debitor's daily limit
+     */
+    #[msg("Transfer exceeds the debitor daily limit")]
+    ExceedsDebitorDailyLimit,
+
+    /**
+     * The destination policy is not supported.
+     *
+     * This error occurs when:
+     * - A merchant config is added or updated with a destination_policy other than
+     *   0 (allowlist) or 1 (denylist)
+     *
+     * How to handle:
+     * - Use one of the supported destination policies
+     */
+    #[msg("Invalid destination policy")]
+    InvalidDestinationPolicy,
+
+    /**
+     * The account could not be closed.
+     *
+     * This error occurs when:
+     * - Releasing a closed account's data fails, before any lamports are moved
+     *
+     * How to handle:
+     * - Retry the close; the account and its rent are left unchanged
+     */
+    #[msg("Failed to close account")]
+    CloseFailed,
+
+    /**
+     * Users can only lower their own delegate limits.
+     *
+     * This error occurs when:
+     * - reduce_user_delegate_limits is called with a per-transfer or period limit above the
+     *   delegate's current value
+     *
+     * How to handle:
+     * - Request the merchant manager to raise the limits
+     */
+    #[msg("Cannot increase own delegate limit")]
+    CannotIncreaseOwnLimit,
+
+    /**
+     * The protocol fee is too high.
+     *
+     * This error occurs when:
+     * - set_protocol_fee is called with a fee above BridgeCardsState::MAX_PROTOCOL_FEE_BPS
+     *
+     * How to handle:
+     * - Use a fee of at most MAX_PROTOCOL_FEE_BPS basis points
+     */
+    #[msg("Protocol fee exceeds the maximum")]
+    FeeTooHigh,
+
+    /**
+     * The protocol fee account is missing or invalid.
+     *
+     * This error occurs when:
+     * - A debit owes a protocol fee but no fee_token_account is passed
+     * - The fee_token_account is not owned by the state's protocol_fee_owner
+     *
+     * How to handle:
+     * - Pass a token account of the protocol fee owner for the debited mint
+     */
+    #[msg("Invalid protocol fee account")]
+    InvalidProtocolFeeAccount,
+
+    /**
+     * The account cannot be migrated.
+     *
+     * This error occurs when:
+     * - migrate_account is called on an account whose discriminator is not a program state type
+     * - The account's size or version byte matches none of the account type's layout versions
+     *
+     * How to handle:
+     * - Only migrate program state accounts created by this program
+     */
+    #[msg("Unsupported account version")]
+    UnsupportedAccountVersion,
+
+    /**
+     * The account is already a manager of the merchant.
+     *
+     * This error occurs when:
+     * - add_merchant_manager is called with one of the merchant's current managers
+     *
+     * How to handle:
+     * - Check the merchant's managers before adding one
+     */
+    #[msg("Account is already a merchant manager")]
+    ManagerAlreadyAdded,
+
+    /**
+     * The merchant already has the maximum number of managers.
+     *
+     * This error occurs when:
+     * - add_merchant_manager is called on a merchant with MAX_MERCHANT_MANAGERS managers
+     *
+     * How to handle:
+     * - Remove a manager with remove_merchant_manager before adding another
+     */
+    #[msg("Merchant has too many managers")]
+    TooManyManagers,
+
+    /**
+     * The account is not a manager of the merchant.
+     *
+     * This error occurs when:
+     * - remove_merchant_manager is called with an account that is not one of the merchant's
+     *   managers
+     *
+     * How to handle:
+     * - Verify the manager public key and merchant id
+     */
+    #[msg("Account is not a merchant manager")]
+    ManagerNotFound,
+
+    /**
+     * The merchant's only manager cannot be removed.
+     *
+     * This error occurs when:
+     * - remove_merchant_manager is called with the merchant's last remaining manager
+     *
+     * How to handle:
+     * - Add the replacement manager first, or close the manager account with
+     *   close_merchant_manager to remove all managers
+     */
+    #[msg("Cannot remove the last merchant manager")]
+    CannotRemoveLastManager,
+
+    /**
+     * The debit's mint is not the native (wrapped SOL) mint.
+     *
+     * This error occurs when:
+     * - debit_user_sol is called with accounts of another mint
+     *
+     * How to handle:
+     * - Use debit_user for SPL token debits
+     * - Pass the native mint and the user's wrapped SOL token account
+     */
+    #[msg("Mint must be the native mint")]
+    NotNativeMint,
+
+    /**
+     * The account being closed is also the recipient of its lamports.
+     *
+     * This error occurs when:
+     * - close_account or close_accounts is passed the payer as an account to close
+     * - close_account is passed the account to close as its rent recipient
+     *
+     * How to handle:
+     * - Pass a different payer or rent recipient to receive the recovered rent
+     */
+    #[msg("Cannot close an account into itself")]
+    SelfClose,
+
+    /**
+     * The mint is not allowlisted for the merchant.
+     *
+     * This error occurs when:
+     * - The merchant config enforces the mint allowlist and the mint's merchant mint state
+     *   is missing, not passed or not allowed
+     *
+     * How to handle:
+     * - Pass the merchant mint PDA for the mint
+     * - Ask the program admin to allowlist the mint for the merchant
+     */
+    #[msg("Mint is not allowed for this merchant")]
+    MintNotAllowedForMerchant,
+
+    /**
+     * The memo attached to a debit is too long.
+     *
+     * This error occurs when:
+     * - debit_user_with_memo is called with a memo longer than MAX_MEMO_LENGTH bytes
+     *
+     * How to handle:
+     * - Shorten the memo, e.g. to an invoice number or a hash of the full reference
+     */
+    #[msg("Memo is too long")]
+    MemoTooLong,
+
+    /**
+     * The decimals of a transfer don't match its mint.
+     *
+     * This error occurs when:
+     * - A debit's transfer is made with decimals read from a different mint than the one it
+     *   transfers
+     *
+     * How to handle:
+     * - This indicates a program bug rather than a client error; report it
+     */
+    #[msg("Transfer decimals don't match the mint")]
+    DecimalsMismatch,
+
+    /**
+     * The destination of a debit is the user's own token account.
+     *
+     * This error occurs when:
+     * - debit_user or batch_debit_user is called with the user token account as a destination,
+     *   even if it has been allowlisted for the merchant
+     *
+     * How to handle:
+     * - Debit into a merchant-owned destination token account
+     */
+    #[msg("Destination is the user token account")]
+    SelfTransfer,
+
+    /**
+     * The destination is not the one the user delegate is restricted to.
+     *
+     * This error occurs when:
+     * - A user delegate with a restricted_destination is debited to another destination token
+     *   account, even if that account is allowed for the merchant
+     *
+     * How to handle:
+     * - Debit into the delegate's restricted destination
+     * - Have the merchant manager update the delegate's restricted_destination
+     */
+    #[msg("Destination is not allowed for this user delegate")]
+    DestinationNotAllowedForDelegate,
+
+    /**
+     * The user delegate is still in its creation cooldown.
+     *
+     * This error occurs when:
+     * - A debit is attempted before the delegate's active_after, i.e. less than the
+     *   cooldown_seconds it was created with have passed since its creation
+     *
+     * How to handle:
+     * - Retry the debit once the cooldown has passed
+     */
+    #[msg("User delegate is not active yet")]
+    DelegateNotYetActive,
+
+    /**
+     * The old and new user token accounts of a delegate migration are the same.
+     *
+     * This error occurs when:
+     * - migrate_user_delegate is called with the delegate's current token account as the new
+     *   token account
+     *
+     * How to handle:
+     * - Pass the user's new token account
+     */
+    #[msg("Old and new user token accounts are the same")]
+    SameUserTokenAccount,
+
+    /**
+     * The debitor is not authorized to debit the transfer's mint.
+     *
+     * This error occurs when:
+     * - The debitor state for the merchant, mint and debitor has not been created, e.g.
+     *   because the debitor was only authorized for another mint
+     * - The debitor state was closed or is not allowed
+     *
+     * How to handle:
+     * - Authorize the debitor for this mint with add_or_update_merchant_debitor
+     * - Debit with a debitor authorized for this mint
+     */
+    #[msg("Debitor is not authorized for this mint")]
+    DebitorNotAuthorizedForMint,
+
+    /**
+     * This error occurs when:
+     * - CLOCK_ANOMALY_CHECKS is enabled and the time elapsed since the delegate's last debit,
+     *   per the clock timestamp, differs from the time expected from the slots elapsed by
+     *   more than the merchant's clock skew plus the tolerated drift
+     *
+     * How to handle:
+     * - Retry once the cluster clock is back in line with the slot progression
+     * - Raise max_clock_skew_seconds in the merchant config if legitimate debits are rejected
+     */
+    #[msg("Clock timestamp is inconsistent with the slots elapsed since the last debit")]
+    ClockAnomaly,
+
+    /**
+     * This error occurs when:
+     * - create_user_delegate_from_template is called for a merchant whose config doesn't
+     *   exist or has no delegate limit template (default_period_seconds is 0)
+     *
+     * How to handle:
+     * - Set the template with add_or_update_merchant_config
+     * - Create the delegate with explicit limits via add_or_update_user_delegate
+     */
+    #[msg("Merchant has no delegate limit template")]
+    MerchantTemplateNotSet,
+
+    /**
+     * This error occurs when:
+     * - A manager sets a delegate's per-transfer limit above the merchant config's
+     *   max_allowed_per_transfer, or its period limit above max_allowed_period_limit
+     *
+     * How to handle:
+     * - Request limits within the merchant's caps
+     * - Ask the admin to raise the caps with add_or_update_merchant_config
+     */
+    #[msg("Delegate limit exceeds the merchant's cap")]
+    LimitExceedsMerchantCap,
+
+    /**
+     * This error occurs when:
+     * - close_program_state is called with confirm set to false
+     *
+     * How to handle:
+     * - Pass confirm = true to decommission the program
+     */
+    #[msg("Closing the program state must be confirmed")]
+    DecommissionNotConfirmed,
+
+    /**
+     * This error occurs when:
+     * - admin_raise_delegate_limits is called with a per-transfer or period limit below the
+     *   delegate's current value
+     *
+     * How to handle:
+     * - Lower limits with add_or_update_user_delegate or reduce_user_delegate_limits instead
+     */
+    #[msg("Admin raise cannot lower a delegate limit")]
+    LimitsNotRaised,
+
+    /**
+     * This error occurs when:
+     * - migrate_user_delegate is signed by an account that doesn't own the new user token
+     *   account
+     * - The old user token account still exists and has a different owner than the new one
+     *
+     * How to handle:
+     * - Have the owner of the new token account sign the migration
+     * - Only migrate a delegate between token accounts of the same user
+     */
+    #[msg("User token accounts of a delegate migration have different owners")]
+    UserTokenAccountOwnerMismatch,
+
+    /**
+     * This error occurs when:
+     * - batch_debit_user is given more amounts than the merchant config's max_splits, or
+     *   DEFAULT_MAX_SPLITS if the merchant doesn't set one
+     *
+     * How to handle:
+     * - Split the debit into fewer destinations, or have the admin raise max_splits
+     */
+    #[msg("Batch debit has too many splits")]
+    TooManySplits,
+
+    /**
+     * This error occurs when:
+     * - One of the amounts given to batch_debit_user is zero
+     *
+     * How to handle:
+     * - Leave destinations that receive nothing out of the batch
+     */
+    #[msg("Batch debit split amount is zero")]
+    ZeroSplitAmount,
+}
+
+impl From<limit_check::LimitError> for ErrorCode {
+    fn from(error: limit_check::LimitError) -> Self {
+        match error {
+            limit_check::LimitError::DelegateNotYetValid => ErrorCode::DelegateNotYetValid,
+            limit_check::LimitError::DelegateExpired => ErrorCode::DelegateExpired,
+            limit_check::LimitError::BelowMinTransferAmount => ErrorCode::BelowMinTransferAmount,
+            limit_check::LimitError::ArithmeticOverflow => ErrorCode::ArithmeticOverflow,
+            limit_check::LimitError::ExceedsMaxTransferLimit => ErrorCode::ExceedsMaxTransferLimit,
+            limit_check::LimitError::ExceedsMaxTransactionsPerSlot => {
+                ErrorCode::ExceedsMaxTransactionsPerSlot
+            }
+            limit_check::LimitError::ExceedsTransferLimitPerPeriod => {
+                ErrorCode::ExceedsTransferLimitPerPeriod
+            }
+        }
+    }
 }
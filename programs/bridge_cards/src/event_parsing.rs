@@ -0,0 +1,80 @@
+use crate::events::*;
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use base64::Engine;
+
+/// Prefix of the log lines through which emit! publishes events
+const PROGRAM_DATA_PREFIX: &str = "Program data: ";
+
+macro_rules! bridge_cards_events {
+    ($($event:ident),* $(,)?) => {
+        /// Any event emitted by the program
+        pub enum BridgeCardsEvent {
+            $($event($event),)*
+        }
+
+        /// Decode an event from its discriminator and serialized fields, returning None for
+        /// data that is not one of the program's events
+        fn decode_event(data: &[u8]) -> Option<BridgeCardsEvent> {
+            $(
+                if let Some(mut fields) = data.strip_prefix($event::DISCRIMINATOR) {
+                    return $event::deserialize(&mut fields)
+                        .ok()
+                        .map(BridgeCardsEvent::$event);
+                }
+            )*
+            None
+        }
+    };
+}
+
+bridge_cards_events!(
+    MerchantDebitorAddedOrUpdated,
+    MerchantDestinationAddedOrUpdated,
+    FeatureFlagsUpdated,
+    Heartbeat,
+    ProtocolFeeUpdated,
+    AdminUpdated,
+    UserDelegateAddedOrUpdated,
+    UserDelegateClosed,
+    UserDelegateMigrated,
+    UserDelegateLimitsRaised,
+    EmergencyRecovery,
+    UserDelegateReissued,
+    UserDelegateSnapshot,
+    UserDelegateStateSnapshot,
+    AccountClosed,
+    ProgramDecommissioned,
+    AccountMigrated,
+    MerchantManagerAddedOrUpdated,
+    MerchantManagerAdded,
+    MerchantManagerRemoved,
+    MerchantManagerClosed,
+    DebitSimulationResult,
+    UserDebited,
+    UserDelegatePeriodReset,
+    MerchantConfigAddedOrUpdated,
+    MerchantSpendTrackerAddedOrUpdated,
+    RouterAddedOrUpdated,
+    DebitRejected,
+    MerchantMintAddedOrUpdated,
+    MerchantRegistryInitialized,
+);
+
+/**
+ * Decode the program's events from a transaction's log messages, in emission order.
+ *
+ * Log lines that are not "Program data: " lines, not valid base64 or not one of the program's
+ * events are skipped, so the logs of a whole transaction (including other programs) can be
+ * passed as is.
+ *
+ * @param logs Log messages of a transaction
+ *
+ * @return The decoded events
+ */
+pub fn parse_events(logs: &[String]) -> Vec<BridgeCardsEvent> {
+    logs.iter()
+        .filter_map(|log| log.strip_prefix(PROGRAM_DATA_PREFIX))
+        .filter_map(|data| base64::engine::general_purpose::STANDARD.decode(data).ok())
+        .filter_map(|data| decode_event(&data))
+        .collect()
+}
@@ -1,3 +1,4 @@
+use crate::instructions::add_or_update_merchant_config::MerchantConfigParams;
 use anchor_lang::prelude::*;
 
 /**
@@ -10,6 +11,8 @@ use anchor_lang::prelude::*;
  * @field debitor - Public key of the debitor account
  * @field previous_state - Previous authorization state (true if was allowed)
  * @field new_state - New authorization state (true if now allowed)
+ * @field per_call_limit - Maximum amount per debit (0 for no debitor-level cap)
+ * @field daily_limit - Maximum amount per day (0 for no debitor-level cap)
  */
 #[event]
 pub struct MerchantDebitorAddedOrUpdated {
@@ -18,6 +21,8 @@ pub struct MerchantDebitorAddedOrUpdated {
     pub debitor: Pubkey,
     pub previous_state: bool,
     pub new_state: bool,
+    pub per_call_limit: u64,
+    pub daily_limit: u64,
 }
 
 /**
@@ -31,6 +36,7 @@ pub struct MerchantDebitorAddedOrUpdated {
  * @field state_pda - Public key of the destination state PDA
  * @field previous_state - Previous authorization state (true if was allowed)
  * @field new_state - New authorization state (true if now allowed)
+ * @field is_primary - Whether the destination is now the merchant's primary destination
  */
 #[event]
 pub struct MerchantDestinationAddedOrUpdated {
@@ -40,6 +46,55 @@ pub struct MerchantDestinationAddedOrUpdated {
     pub state_pda: Pubkey,
     pub previous_state: bool,
     pub new_state: bool,
+    pub is_primary: bool,
+}
+
+/**
+ * Event emitted when the global feature flags are set.
+ * This event is emitted by the set_feature_flags instruction.
+ *
+ * Fields:
+ * @field previous_flags - Feature flag bitmask before the update
+ * @field new_flags - Feature flag bitmask after the update
+ */
+#[event]
+pub struct FeatureFlagsUpdated {
+    pub previous_flags: u64,
+    pub new_flags: u64,
+}
+
+/**
+ * Event emitted as a liveness and configuration signal for monitoring.
+ * This event is emitted by the admin_heartbeat instruction.
+ *
+ * Fields:
+ * @field admin - Program admin who signed the heartbeat
+ * @field timestamp - Unix timestamp at which the heartbeat was processed
+ * @field feature_flags - Global feature flag bitmask
+ * @field fee_bps - Protocol fee in basis points
+ */
+#[event]
+pub struct Heartbeat {
+    pub admin: Pubkey,
+    pub timestamp: i64,
+    pub feature_flags: u64,
+    pub fee_bps: u16,
+}
+
+/**
+ * Event emitted when the protocol fee is set.
+ * This event is emitted by the set_protocol_fee instruction.
+ *
+ * Fields:
+ * @field previous_fee_bps - Protocol fee in basis points before the update
+ * @field fee_bps - Protocol fee in basis points after the update
+ * @field fee_owner - Owner of the token accounts receiving the fee
+ */
+#[event]
+pub struct ProtocolFeeUpdated {
+    pub previous_fee_bps: u16,
+    pub fee_bps: u16,
+    pub fee_owner: Pubkey,
 }
 
 /**
@@ -56,11 +111,17 @@ pub struct AdminUpdated {
 
 /**
  * Event emitted when a user delegate is added or updated for a merchant.
- * This event is emitted by the add_or_update_user_delegate instruction.
+ * This event is emitted by the add_or_update_user_delegate and reduce_user_delegate_limits
+ * instructions.
  *
  * Fields:
  * @field merchant_pda - Public key of the merchant's PDA
  * @field user_delegate - Public key of the delegate account being added/updated
+ * @field metadata_hash - Merchant-provided commitment linking the delegate to off-chain records
+ * @field previous_per_transfer_limit - Per-transfer limit before the change (0 if created)
+ * @field new_per_transfer_limit - Per-transfer limit after the change
+ * @field previous_period_transfer_limit - Period limit before the change (0 if created)
+ * @field new_period_transfer_limit - Period limit after the change
  */
 #[event]
 pub struct UserDelegateAddedOrUpdated {
@@ -68,18 +129,271 @@ pub struct UserDelegateAddedOrUpdated {
     pub mint: Pubkey,
     pub user_ata: Pubkey,
     pub user_delegate: Pubkey,
+    pub metadata_hash: [u8; 32],
+    pub previous_per_transfer_limit: u64,
+    pub new_per_transfer_limit: u64,
+    pub previous_period_transfer_limit: u64,
+    pub new_period_transfer_limit: u64,
+}
+
+/**
+ * Event emitted when a user delegate is revoked and its account closed.
+ * This event is emitted by the close_user_delegate instruction.
+ *
+ * Fields:
+ * @field merchant_id - Unique identifier of the merchant
+ * @field mint - Public key of the token mint of the delegation
+ * @field user_ata - Public key of the user's token account the delegate controlled
+ * @field user_delegate - Public key of the closed delegate account
+ */
+#[event]
+pub struct UserDelegateClosed {
+    pub merchant_id: u64,
+    pub mint: Pubkey,
+    pub user_ata: Pubkey,
+    pub user_delegate: Pubkey,
+}
+
+/**
+ * Event emitted when a user delegate is moved to a new user token account.
+ * This event is emitted by the migrate_user_delegate instruction.
+ *
+ * Fields:
+ * @field merchant_id - Unique identifier of the merchant
+ * @field mint - Public key of the token mint of the delegation
+ * @field old_user_ata - Public key of the user's token account the old delegate controlled
+ * @field new_user_ata - Public key of the user's token account the new delegate controls
+ * @field old_user_delegate - Public key of the closed delegate account
+ * @field new_user_delegate - Public key of the created delegate account
+ */
+#[event]
+pub struct UserDelegateMigrated {
+    pub merchant_id: u64,
+    pub mint: Pubkey,
+    pub old_user_ata: Pubkey,
+    pub new_user_ata: Pubkey,
+    pub old_user_delegate: Pubkey,
+    pub new_user_delegate: Pubkey,
+}
+
+/**
+ * Event emitted when a user delegate's limits are raised with the admin's co-signature.
+ * This event is emitted by the admin_raise_delegate_limits instruction.
+ *
+ * Fields:
+ * @field merchant_id - Unique identifier of the merchant
+ * @field user_delegate - Public key of the delegate account
+ * @field manager - Merchant manager who signed the raise
+ * @field admin - Program admin who co-signed the raise
+ * @field previous_per_transfer_limit - Per-transfer limit before the raise
+ * @field per_transfer_limit - Per-transfer limit after the raise
+ * @field previous_period_transfer_limit - Period limit before the raise
+ * @field period_transfer_limit - Period limit after the raise
+ */
+#[event]
+pub struct UserDelegateLimitsRaised {
+    pub merchant_id: u64,
+    pub user_delegate: Pubkey,
+    pub manager: Pubkey,
+    pub admin: Pubkey,
+    pub previous_per_transfer_limit: u64,
+    pub per_transfer_limit: u64,
+    pub previous_period_transfer_limit: u64,
+    pub period_transfer_limit: u64,
+}
+
+/**
+ * Event emitted when a user's tokens are moved to a recovery account in an emergency.
+ * This event is emitted by the admin_recover_user_funds instruction.
+ *
+ * Fields:
+ * @field admin - Program admin who signed the recovery
+ * @field owner - Owner of the user token account, who co-signed the recovery
+ * @field user_ata - Public key of the user's token account the tokens were moved from
+ * @field recovery_ata - Public key of the token account the tokens were moved to
+ * @field mint - Public key of the token mint
+ * @field amount - Number of tokens moved (in smallest units)
+ */
+#[event]
+pub struct EmergencyRecovery {
+    pub admin: Pubkey,
+    pub owner: Pubkey,
+    pub user_ata: Pubkey,
+    pub recovery_ata: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+/**
+ * Event emitted when a user delegate is re-issued with new limits.
+ * This event is emitted by the reissue_delegate instruction.
+ *
+ * Fields:
+ * @field merchant_id - Unique identifier of the merchant
+ * @field user_delegate - Public key of the delegate account
+ * @field per_transfer_limit - New maximum amount allowed in a single transfer
+ * @field period_transfer_limit - New maximum amount allowed within the time period
+ * @field transfer_limit_period_seconds - New duration of the transfer limit period
+ * @field carry_usage - Whether the current period's usage was preserved
+ * @field period_transferred_amount - Amount counted against the period after the re-issue
+ * @field reissue_count - Number of times the delegate has been re-issued, including this one
+ */
+#[event]
+pub struct UserDelegateReissued {
+    pub merchant_id: u64,
+    pub user_delegate: Pubkey,
+    pub per_transfer_limit: u64,
+    pub period_transfer_limit: u64,
+    pub transfer_limit_period_seconds: u32,
+    pub carry_usage: bool,
+    pub period_transferred_amount: u64,
+    pub reissue_count: u32,
+}
+
+/**
+ * Event emitted with the full state of a user delegate after it is added or updated.
+ * This event is emitted by the add_or_update_user_delegate instruction when the merchant
+ * config enables emit_full_snapshot, so indexers don't need to read the account.
+ *
+ * Fields:
+ * @field merchant_id - Unique identifier of the merchant
+ * @field mint - Public key of the token mint
+ * @field user_ata - Public key of the user's token account
+ * @field user_delegate - Public key of the delegate account
+ * @field per_transfer_limit - Maximum amount per transfer
+ * @field period_transfer_limit - Maximum amount per period
+ * @field period_transferred_amount - Amount transferred in the current period
+ * @field period_timestamp_last_reset - Timestamp the current period started
+ * @field transfer_limit_period_seconds - Duration of the period in seconds
+ * @field slot_last_transferred - Slot of the last transfer
+ * @field min_slots_between_debits - Delegate override of the minimum slots between debits
+ * @field allow_alternate_destination - Whether the delegate may debit to non-primary destinations
+ * @field min_transfer_amount - Minimum amount of a single debit
+ * @field total_transferred - Amount transferred over the delegate's lifetime
+ * @field debit_nonce - Sequence number of the last successful debit
+ * @field valid_from - Timestamp before which debits are rejected
+ * @field expires_at - Timestamp after which debits are rejected (0 = never)
+ * @field limit_mode - How period usage frees up: 0 = fixed window, 1 = sliding window
+ * @field strict_boundary - Whether fixed-window periods start at the end of the previous one
+ * @field anchor_to_epoch - Whether fixed-window periods are aligned to the unix epoch
+ * @field last_period_index - Index since the unix epoch of the last period, when anchored
+ * @field restricted_destination - Only destination allowed (default = any merchant destination)
+ * @field active_after - Timestamp before which the delegate's cooldown rejects debits
+ * @field timestamp_last_transferred - Timestamp of the last transfer
+ */
+#[event]
+pub struct UserDelegateSnapshot {
+    pub merchant_id: u64,
+    pub mint: Pubkey,
+    pub user_ata: Pubkey,
+    pub user_delegate: Pubkey,
+    pub per_transfer_limit: u64,
+    pub period_transfer_limit: u64,
+    pub period_transferred_amount: u64,
+    pub period_timestamp_last_reset: u64,
+    pub transfer_limit_period_seconds: u32,
+    pub slot_last_transferred: u64,
+    pub min_slots_between_debits: u64,
+    pub allow_alternate_destination: bool,
+    pub min_transfer_amount: u64,
+    pub total_transferred: u128,
+    pub debit_nonce: u64,
+    pub valid_from: i64,
+    pub expires_at: i64,
+    pub limit_mode: u8,
+    pub strict_boundary: bool,
+    pub anchor_to_epoch: bool,
+    pub last_period_index: u64,
+    pub restricted_destination: Pubkey,
+    pub active_after: i64,
+    pub timestamp_last_transferred: i64,
+}
+
+/**
+ * Event emitted with a user delegate's current limits, for clients that can't decode accounts.
+ * This event is emitted by the log_user_delegate_state instruction.
+ *
+ * Fields:
+ * @field merchant_id - Unique identifier of the merchant
+ * @field mint - Public key of the token mint
+ * @field user_ata - Public key of the user's token account
+ * @field user_delegate - Public key of the delegate account
+ * @field per_transfer_limit - Maximum amount per transfer
+ * @field period_transfer_limit - Maximum amount per period
+ * @field period_transferred_amount - Amount transferred in the current period
+ * @field period_timestamp_last_reset - Timestamp the current period started
+ * @field transfer_limit_period_seconds - Duration of the period in seconds
+ * @field min_slots_between_debits - Delegate override of the minimum slots between debits
+ * @field min_transfer_amount - Minimum amount of a single debit
+ * @field valid_from - Timestamp before which debits are rejected
+ * @field expires_at - Timestamp after which debits are rejected (0 = never)
+ * @field remaining_period_allowance - Amount that can still be debited in the period at timestamp
+ * @field timestamp - Unix timestamp the snapshot was taken at
+ */
+#[event]
+pub struct UserDelegateStateSnapshot {
+    pub merchant_id: u64,
+    pub mint: Pubkey,
+    pub user_ata: Pubkey,
+    pub user_delegate: Pubkey,
+    pub per_transfer_limit: u64,
+    pub period_transfer_limit: u64,
+    pub period_transferred_amount: u64,
+    pub period_timestamp_last_reset: u64,
+    pub transfer_limit_period_seconds: u32,
+    pub min_slots_between_debits: u64,
+    pub min_transfer_amount: u64,
+    pub valid_from: i64,
+    pub expires_at: i64,
+    pub remaining_period_allowance: u64,
+    pub timestamp: i64,
 }
 
 /**
  * Event emitted when a program account is closed.
- * This event is emitted by the close_account instruction.
+ * This event is emitted by the close_account, close_accounts (once per account),
+ * close_merchant_debitor and close_merchant_destination instructions.
  *
  * Fields:
  * @field account - Public key of the account that was closed
+ * @field lamports_recovered - Lamports moved to the recipient: the rent plus any balance above it
+ * @field recipient - Account that received the lamports
  */
 #[event]
 pub struct AccountClosed {
     pub account: Pubkey,
+    pub lamports_recovered: u64,
+    pub recipient: Pubkey,
+}
+
+/**
+ * Event emitted when the global state is closed and the program decommissioned.
+ * This event is emitted by the close_program_state instruction.
+ *
+ * Fields:
+ * @field admin - Program admin who closed the state and received its lamports
+ * @field lamports_recovered - Lamports moved from the state to the admin
+ */
+#[event]
+pub struct ProgramDecommissioned {
+    pub admin: Pubkey,
+    pub lamports_recovered: u64,
+}
+
+/**
+ * Event emitted when a program account is rewritten in the current layout.
+ * This event is emitted by the migrate_account instruction.
+ *
+ * Fields:
+ * @field account - Public key of the migrated account
+ * @field previous_version - Layout version of the account before the migration
+ * @field version - Layout version of the account after the migration
+ */
+#[event]
+pub struct AccountMigrated {
+    pub account: Pubkey,
+    pub previous_version: u8,
+    pub version: u8,
 }
 
 /**
@@ -96,9 +410,81 @@ pub struct MerchantManagerAddedOrUpdated {
     pub manager: Pubkey,
 }
 
+/**
+ * Event emitted when a manager is added alongside a merchant's existing managers.
+ * This event is emitted by the add_merchant_manager instruction.
+ *
+ * Fields:
+ * @field merchant_id - Unique identifier of the merchant
+ * @field manager - Public key of the added manager
+ * @field active_count - Number of managers after the addition
+ */
+#[event]
+pub struct MerchantManagerAdded {
+    pub merchant_id: u64,
+    pub manager: Pubkey,
+    pub active_count: u8,
+}
+
+/**
+ * Event emitted when one of a merchant's managers is removed.
+ * This event is emitted by the remove_merchant_manager instruction.
+ *
+ * Fields:
+ * @field merchant_id - Unique identifier of the merchant
+ * @field manager - Public key of the removed manager
+ * @field active_count - Number of managers after the removal
+ */
+#[event]
+pub struct MerchantManagerRemoved {
+    pub merchant_id: u64,
+    pub manager: Pubkey,
+    pub active_count: u8,
+}
+
+/**
+ * Event emitted when a merchant's managers are removed and its account closed.
+ * This event is emitted by the close_merchant_manager instruction.
+ *
+ * Fields:
+ * @field merchant_id - Unique identifier of the merchant
+ * @field managers - Public keys of the removed managers
+ */
+#[event]
+pub struct MerchantManagerClosed {
+    pub merchant_id: u64,
+    pub managers: Vec<Pubkey>,
+}
+
+/**
+ * Event emitted with the outcome of a simulated debit.
+ * This event is emitted by the simulate_debit_user instruction.
+ *
+ * Fields:
+ * @field merchant_id - Unique identifier of the merchant
+ * @field user_delegate - Public key of the user's delegate account that would authorize the debit
+ * @field debitor - Public key of the debitor that would initiate the debit
+ * @field destination_ata - Public key of the token account that would receive the funds
+ * @field amount - Amount of tokens that would be debited
+ * @field success - Whether debit_user would succeed with the same accounts and amount
+ * @field reason_code - Error code debit_user would fail with (e.g. 6000 + ErrorCode), 0 on
+ *   success
+ */
+#[event]
+pub struct DebitSimulationResult {
+    pub merchant_id: u64,
+    pub user_delegate: Pubkey,
+    pub debitor: Pubkey,
+    pub destination_ata: Pubkey,
+    pub amount: u64,
+    pub success: bool,
+    pub reason_code: u64,
+}
+
 /**
  * Event emitted when a user is debited by a merchant.
- * This event is emitted by the debit_user instruction.
+ * This event is emitted by the debit_user, debit_user_exact_out and debit_user_with_memo
+ * instructions, once per leg by batch_debit_user.
  *
  * Fields:
  * @field debitor - Public key of the merchant debitor account that initiated the debit
@@ -107,8 +493,15 @@ pub struct MerchantManagerAddedOrUpdated {
  * @field user_ata - Public key of the user's associated token account being debited
  * @field destination_ata - Public key of the destination associated token account receiving the funds
  * @field mint - Public key of the token mint being transferred
- * @field amount - Amount of tokens being transferred
- * @field user_nonce - Unique nonce to prevent replay attacks
+ * @field amount - Amount of tokens debited from the user (before any token-2022 transfer fee),
+ *   including the protocol fee
+ * @field slot - Slot in which the debit was processed
+ * @field timestamp - Unix timestamp at which the debit was processed
+ * @field debit_nonce - Per-delegate sequence number of the debit, shared by the legs of a batch
+ * @field protocol_fee - Part of the amount sent to the protocol fee account instead of the
+ *   destination
+ * @field memo - Merchant reference (e.g. invoice number) passed to debit_user_with_memo,
+ *   empty for other debits
  */
 #[event]
 pub struct UserDebited {
@@ -119,4 +512,136 @@ pub struct UserDebited {
     pub destination_ata: Pubkey,
     pub mint: Pubkey,
     pub amount: u64,
+    pub slot: u64,
+    pub timestamp: i64,
+    pub debit_nonce: u64,
+    pub protocol_fee: u64,
+    pub memo: Vec<u8>,
+}
+
+/**
+ * Event emitted when a debit starts a new transfer limit period for a delegate.
+ * This event is emitted by debit instructions before the UserDebited event of the debit
+ * that triggered the reset, so the debit counts towards the new period.
+ *
+ * Fields:
+ * @field merchant_id - Unique identifier of the merchant
+ * @field user_delegate - Public key of the delegate account
+ * @field period_timestamp_last_reset - Start of the new period (unix timestamp)
+ */
+#[event]
+pub struct UserDelegatePeriodReset {
+    pub merchant_id: u64,
+    pub user_delegate: Pubkey,
+    pub period_timestamp_last_reset: u64,
+}
+
+/**
+ * Event emitted when a merchant's config is added or updated.
+ * This event is emitted by the add_or_update_merchant_config instruction.
+ *
+ * Fields:
+ * @field merchant_id - Unique identifier of the merchant
+ * @field state_pda - Public key of the merchant config PDA
+ * @field config - Settings now stored in the merchant config
+ */
+#[event]
+pub struct MerchantConfigAddedOrUpdated {
+    pub merchant_id: u64,
+    pub state_pda: Pubkey,
+    pub config: MerchantConfigParams,
+}
+
+/**
+ * Event emitted when a merchant spend tracker is added or its limits are updated.
+ * This event is emitted by the add_or_update_merchant_spend_tracker instruction.
+ *
+ * Fields:
+ * @field merchant_id - Unique identifier of the merchant
+ * @field state_pda - Public key of the merchant spend tracker PDA
+ * @field period_transfer_limit - Maximum total amount per period across all delegates
+ * @field transfer_limit_period_seconds - Duration of the period in seconds
+ */
+#[event]
+pub struct MerchantSpendTrackerAddedOrUpdated {
+    pub merchant_id: u64,
+    pub state_pda: Pubkey,
+    pub period_transfer_limit: u64,
+    pub transfer_limit_period_seconds: u32,
+}
+
+/**
+ * Event emitted when a router program is added or its state is updated.
+ * This event is emitted by the add_or_update_router instruction.
+ *
+ * Fields:
+ * @field router_program - Public key of the router program
+ * @field state_pda - Public key of the router state PDA
+ * @field previous_state - Previous authorization state (true if was allowed)
+ * @field new_state - New authorization state (true if now allowed)
+ */
+#[event]
+pub struct RouterAddedOrUpdated {
+    pub router_program: Pubkey,
+    pub state_pda: Pubkey,
+    pub previous_state: bool,
+    pub new_state: bool,
+}
+
+/**
+ * Event emitted when a debit is rejected by a limit check instead of failing.
+ * This event is emitted by the debit_user_logged instruction, which then transfers nothing.
+ *
+ * Fields:
+ * @field merchant_id - Unique identifier of the merchant
+ * @field user_delegate - Public key of the user's delegate account
+ * @field debitor - Public key of the debitor that attempted the debit
+ * @field amount - Amount of tokens the debitor attempted to debit
+ * @field reason_code - Error code debit_user would fail with (6000 + ErrorCode)
+ * @field slot - Slot in which the debit was attempted
+ * @field timestamp - Unix timestamp at which the debit was attempted
+ */
+#[event]
+pub struct DebitRejected {
+    pub merchant_id: u64,
+    pub user_delegate: Pubkey,
+    pub debitor: Pubkey,
+    pub amount: u64,
+    pub reason_code: u64,
+    pub slot: u64,
+    pub timestamp: i64,
+}
+
+/**
+ * Event emitted when a mint is allowlisted for a merchant or its state is updated.
+ * This event is emitted by the add_or_update_merchant_mint instruction.
+ *
+ * Fields:
+ * @field merchant_id - Unique identifier of the merchant
+ * @field mint - Public key of the token mint
+ * @field state_pda - Public key of the merchant mint state PDA
+ * @field previous_state - Previous authorization state (true if was allowed)
+ * @field new_state - New authorization state (true if now allowed)
+ */
+#[event]
+pub struct MerchantMintAddedOrUpdated {
+    pub merchant_id: u64,
+    pub mint: Pubkey,
+    pub state_pda: Pubkey,
+    pub previous_state: bool,
+    pub new_state: bool,
+}
+
+/**
+ * Event emitted when a merchant registry is created.
+ * This event is emitted by the initialize_merchant_registry instruction.
+ *
+ * Fields:
+ * @field merchant_id - Unique identifier of the merchant
+ * @field state_pda - Public key of the merchant registry PDA
+ */
+#[event]
+pub struct MerchantRegistryInitialized {
+    pub merchant_id: u64,
+    pub state_pda: Pubkey,
 }
@@ -0,0 +1,95 @@
+use crate::events::MerchantManagerAdded;
+use crate::instructions::add_or_update_merchant_manager::check_manager;
+use crate::instructions::initialize::STATE_SEED;
+use crate::pda::merchant_id_seed;
+use crate::state::{BridgeCardsState, MerchantManagerState};
+use crate::{ID, MERCHANT_MANAGER_SEED};
+use anchor_lang::prelude::*;
+
+/**
+ * Add a manager alongside a merchant's existing managers.
+ *
+ * Every manager of a merchant has the same authority over its delegates and debitors, so a
+ * manager key can be rotated by adding the new key before removing the old one with
+ * remove_merchant_manager, without a window in which nobody can manage the merchant.
+ *
+ * Security Model:
+ * - Only the program admin can add managers
+ * - The merchant must already have a manager (set by add_or_update_merchant_manager)
+ * - The same checks as add_or_update_merchant_manager apply to the new manager, including
+ *   co-signing when the REQUIRE_MANAGER_COSIGN feature flag is set
+ * - A merchant has at most MAX_MERCHANT_MANAGERS managers, without duplicates
+ *
+ * Events Emitted:
+ * - MerchantManagerAdded: When the manager is added
+ *   Fields: merchant_id, manager, active_count
+ *
+ * Required Accounts:
+ * - admin: Program admin
+ * - state: Global program state storing admin pubkey
+ * - manager_state: PDA storing the merchant's managers
+ * - manager: Account to be added as a manager (not a signer)
+ */
+#[derive(Accounts)]
+#[instruction(merchant_id: u64)]
+pub struct AddMerchantManager<'info> {
+    /// The program admin account, must match admin stored in state
+    /// Required permissions: Signer
+    #[account(constraint = admin.key() == state.admin)]
+    pub admin: Signer<'info>,
+
+    /// Global program state storing the admin public key
+    /// Seeds: [STATE_SEED]
+    /// Required permissions: Read-only
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        seeds::program = ID
+    )]
+    pub state: Account<'info, BridgeCardsState>,
+
+    /// PDA storing the merchant's managers
+    /// Seeds: [MERCHANT_MANAGER_SEED, merchant_id]
+    /// Required permissions: Mutable
+    #[account(mut,
+        seeds = [MERCHANT_MANAGER_SEED, merchant_id_seed(merchant_id).as_ref()],
+        bump = manager_state.bump,
+        seeds::program = ID
+    )]
+    pub manager_state: Account<'info, MerchantManagerState>,
+
+    /// Account to be added as a merchant manager
+    /// Required permissions: None (Signer if REQUIRE_MANAGER_COSIGN is set)
+    /// CHECK: Account is only stored as a pubkey, no account data validation needed
+    pub manager: AccountInfo<'info>,
+}
+
+/**
+ * Process the addition of a merchant manager.
+ *
+ * @param ctx Context containing all required accounts
+ * @param merchant_id Unique identifier for the merchant
+ *
+ * Flow:
+ * 1. Verify admin signature and manager state PDA (done via account constraints)
+ * 2. Reject managers that can never sign, and unsigned managers if co-signing is required
+ * 3. Append the manager to the merchant's managers
+ * 4. Emit event with the new manager and manager count
+ *
+ * @return Result indicating success or containing an error
+ */
+pub fn handler(ctx: Context<AddMerchantManager>, merchant_id: u64) -> Result<()> {
+    check_manager(&ctx.accounts.state, &ctx.accounts.manager)?;
+
+    let manager_state = &mut ctx.accounts.manager_state;
+    manager_state.add_manager(ctx.accounts.manager.key())?;
+
+    // Emit event for indexing and notifications
+    emit!(MerchantManagerAdded {
+        merchant_id,
+        manager: ctx.accounts.manager.key(),
+        active_count: manager_state.active_count,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,194 @@
+use crate::errors::ErrorCode;
+use crate::events::MerchantConfigAddedOrUpdated;
+use crate::instructions::add_or_update_user_delegate::validate_delegate_limits;
+use crate::instructions::initialize::STATE_SEED;
+use crate::pda::merchant_id_seed;
+use crate::state::{
+    AccountSpace, BridgeCardsState, DestinationPolicy, MerchantConfigState, CURRENT_VERSION,
+};
+use crate::ID;
+use anchor_lang::prelude::*;
+
+/// Seed used to derive merchant config PDAs
+pub const MERCHANT_CONFIG_SEED: &[u8] = b"merchant_config";
+
+/**
+ * Settings applied by the add_or_update_merchant_config instruction.
+ *
+ * Every field overwrites the stored value, so callers updating a single setting
+ * should read the current config first and resend the remaining fields unchanged.
+ */
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, Debug, PartialEq)]
+pub struct MerchantConfigParams {
+    /// Router program invoked by debit_user_with_swap (Pubkey::default() disables routing)
+    pub router_program: Pubkey,
+    /// Reject debits from token accounts whose owner is not a system-owned wallet
+    pub require_user_owner_system_owned: bool,
+    /// Emit a UserDelegateSnapshot event with the full delegate state on delegate updates
+    pub emit_full_snapshot: bool,
+    /// Minimum slots between two debits of a delegate (0 = one debit per slot)
+    pub min_slots_between_debits: u64,
+    /// Reject debits to non-primary destinations unless the delegate allows alternates
+    pub enforce_primary_destination: bool,
+    /// Start a new period for debits exactly at the end of the period (default: still in period)
+    pub reset_at_period_boundary: bool,
+    /// Require an admin co-signature (admin_raise_delegate_limits) to raise delegate limits
+    pub managers_can_only_lower_limits: bool,
+    /// Expiry in seconds applied to delegates that set use_default_expiry (0 = no default)
+    pub default_delegate_expiry_seconds: u32,
+    /// Reject every debit of the merchant with MerchantPaused
+    pub paused: bool,
+    /// Owner whose token accounts debit_user accepts as destinations without allowlisting
+    pub allow_any_destination_owner: Option<Pubkey>,
+    /// How destination states are interpreted: 0 = allowlist (default), 1 = denylist
+    pub destination_policy: u8,
+    /// Only allow delegates and debits for mints allowlisted via add_or_update_merchant_mint
+    pub enforce_mint_allowlist: bool,
+    /// Clock skew in seconds tolerated when CLOCK_ANOMALY_CHECKS is enabled (0 = default)
+    pub max_clock_skew_seconds: u32,
+    /// Per-transfer limit applied by create_user_delegate_from_template
+    pub default_per_transfer_limit: u64,
+    /// Period limit applied by create_user_delegate_from_template
+    pub default_period_transfer_limit: u64,
+    /// Period in seconds applied by create_user_delegate_from_template (0 = no template)
+    pub default_period_seconds: u32,
+    /// Highest per-transfer limit managers can set on a delegate (0 = no cap)
+    pub max_allowed_per_transfer: u64,
+    /// Highest period limit managers can set on a delegate (0 = no cap)
+    pub max_allowed_period_limit: u64,
+    /// Most destinations batch_debit_user can split a debit into (0 = DEFAULT_MAX_SPLITS)
+    pub max_splits: u8,
+}
+
+/**
+ * Add or update the configuration of a merchant.
+ *
+ * This instruction allows the program admin to manage merchant-wide settings that apply
+ * to every delegate, debitor and destination of the merchant.
+ *
+ * Account Creation:
+ * - Creates a PDA to store the merchant config if it doesn't exist
+ * - PDA is derived using [MERCHANT_CONFIG_SEED, merchant_id]
+ * - Funded by the payer account
+ *
+ * Security Model:
+ * - Only the program admin can add/update merchant configs
+ * - A configured router must also be allowlisted via add_or_update_router before use
+ *
+ * Events Emitted:
+ * - MerchantConfigAddedOrUpdated: When a config is set or changed
+ *   Fields: merchant_id, state_pda, config
+ *
+ * Required Accounts:
+ * - admin: Program admin who can update merchant configs
+ * - payer: Account paying for PDA creation/rent
+ * - state: Global program state storing admin pubkey
+ * - merchant_config: PDA storing the merchant configuration
+ * - system_program: Required for account creation
+ */
+#[derive(Accounts)]
+#[instruction(merchant_id: u64)]
+pub struct AddOrUpdateMerchantConfig<'info> {
+    /// The program admin account, must match admin stored in state
+    /// Required permissions: Signer
+    #[account(constraint = admin.key() == state.admin)]
+    pub admin: Signer<'info>,
+
+    /// Account that will pay for PDA creation and rent
+    /// Required permissions: Signer, Mutable (for rent payment)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Global program state storing the admin public key
+    /// Seeds: [STATE_SEED]
+    /// Required permissions: Read-only
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        seeds::program = ID
+    )]
+    pub state: Account<'info, BridgeCardsState>,
+
+    /// PDA storing the merchant configuration
+    /// Seeds: [MERCHANT_CONFIG_SEED, merchant_id]
+    /// Space: Discriminator + Merchant config
+    /// Required permissions: Mutable
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = MerchantConfigState::SPACE,
+        seeds = [
+            MERCHANT_CONFIG_SEED,
+            merchant_id_seed(merchant_id).as_ref(),
+        ],
+        bump
+    )]
+    pub merchant_config: Account<'info, MerchantConfigState>,
+
+    /// Required for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/**
+ * Process the addition or update of a merchant config.
+ *
+ * @param ctx Context containing all required accounts
+ * @param merchant_id Unique identifier for the merchant
+ * @param params New settings for the merchant
+ *
+ * Flow:
+ * 1. Verify admin signature (done via account constraints)
+ * 2. Validate the destination policy and the delegate limit template, if set
+ * 3. Overwrite the merchant config PDA with the provided settings
+ * 4. Emit event with merchant_id and the new settings
+ *
+ * @return Result indicating success or containing an error
+ */
+pub fn handler(
+    ctx: Context<AddOrUpdateMerchantConfig>,
+    merchant_id: u64,
+    params: MerchantConfigParams,
+) -> Result<()> {
+    DestinationPolicy::try_from(params.destination_policy)
+        .map_err(|_| ErrorCode::InvalidDestinationPolicy)?;
+    // The template must hold limits a delegate could be created with
+    if params.default_period_seconds != 0 {
+        validate_delegate_limits(
+            params.default_per_transfer_limit,
+            params.default_period_transfer_limit,
+            params.default_period_seconds,
+        )?;
+    }
+
+    let merchant_config = &mut ctx.accounts.merchant_config;
+    merchant_config.router_program = params.router_program;
+    merchant_config.require_user_owner_system_owned = params.require_user_owner_system_owned;
+    merchant_config.emit_full_snapshot = params.emit_full_snapshot;
+    merchant_config.min_slots_between_debits = params.min_slots_between_debits;
+    merchant_config.enforce_primary_destination = params.enforce_primary_destination;
+    merchant_config.reset_at_period_boundary = params.reset_at_period_boundary;
+    merchant_config.managers_can_only_lower_limits = params.managers_can_only_lower_limits;
+    merchant_config.default_delegate_expiry_seconds = params.default_delegate_expiry_seconds;
+    merchant_config.paused = params.paused;
+    merchant_config.allow_any_destination_owner = params.allow_any_destination_owner;
+    merchant_config.destination_policy = params.destination_policy;
+    merchant_config.enforce_mint_allowlist = params.enforce_mint_allowlist;
+    merchant_config.max_clock_skew_seconds = params.max_clock_skew_seconds;
+    merchant_config.default_per_transfer_limit = params.default_per_transfer_limit;
+    merchant_config.default_period_transfer_limit = params.default_period_transfer_limit;
+    merchant_config.default_period_seconds = params.default_period_seconds;
+    merchant_config.max_allowed_per_transfer = params.max_allowed_per_transfer;
+    merchant_config.max_allowed_period_limit = params.max_allowed_period_limit;
+    merchant_config.max_splits = params.max_splits;
+    merchant_config.version = CURRENT_VERSION;
+    merchant_config.bump = ctx.bumps.merchant_config;
+
+    // Emit event for indexing and notifications
+    emit!(MerchantConfigAddedOrUpdated {
+        merchant_id,
+        state_pda: ctx.accounts.merchant_config.key(),
+        config: params,
+    });
+
+    Ok(())
+}
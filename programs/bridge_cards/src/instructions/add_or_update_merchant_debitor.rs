@@ -1,6 +1,10 @@
 use crate::events::MerchantDebitorAddedOrUpdated;
-use crate::state::{MerchantDebitorState, MerchantManagerState};
-use crate::{ID, MERCHANT_MANAGER_SEED};
+use crate::pda::merchant_id_seed;
+use crate::state::{
+    AccountSpace, MerchantDebitorState, MerchantManagerState, MerchantRegistryState,
+    CURRENT_VERSION,
+};
+use crate::{ID, MERCHANT_MANAGER_SEED, MERCHANT_REGISTRY_SEED};
 use anchor_lang::prelude::*;
 use anchor_spl::token_interface::Mint;
 
@@ -19,6 +23,8 @@ pub const MERCHANT_DEBITOR_SEED: &[u8] = b"merchant_debitor";
  * - Debitors can be enabled or disabled via the debitor_allowed parameter
  * - Multiple debitors can be configured per merchant
  * - Debitors must sign transactions but don't need to sign for revocation
+ * - Each debitor can be capped per debit and per day on top of the delegates' limits
+ *   (per_call_limit, daily_limit; 0 means no debitor-level cap)
  *
  * Account Creation:
  * - Creates a PDA to store the debitor's state if it doesn't exist
@@ -33,7 +39,8 @@ pub const MERCHANT_DEBITOR_SEED: &[u8] = b"merchant_debitor";
  *
  * Events Emitted:
  * - MerchantDebitorAddedOrUpdated: When a debitor is set or changed
- *   Fields: merchant_id, debitor, state_pda, previous_state, new_state
+ *   Fields: merchant_id, debitor, state_pda, previous_state, new_state, per_call_limit,
+ *   daily_limit
  *
  * Common Use Cases:
  * - Initial setup of merchant payment processors
@@ -49,13 +56,14 @@ pub const MERCHANT_DEBITOR_SEED: &[u8] = b"merchant_debitor";
  * - debitor: Account to be authorized as debitor
  * - mint: Token mint account that this debitor is authorized for
  * - system_program: Required for account creation
+ * - merchant_registry: Registry counting the merchant's accounts (optional)
  */
 #[derive(Accounts)]
 #[instruction(merchant_id: u64, allowed: bool)]
 pub struct AddOrUpdateMerchantDebitor<'info> {
-    /// The merchant manager account, must match manager in manager_state
+    /// The merchant manager account, must be one of the managers in manager_state
     /// Required permissions: Signer
-    #[account(constraint = manager.key() == manager_state.manager)]
+    #[account(constraint = manager_state.is_manager(&manager.key()))]
     pub manager: Signer<'info>,
 
     /// Account that will pay for PDA creation and rent
@@ -67,7 +75,7 @@ pub struct AddOrUpdateMerchantDebitor<'info> {
     /// Seeds: [MERCHANT_MANAGER_SEED, merchant_id]
     /// Required permissions: Read-only
     #[account(
-        seeds = [MERCHANT_MANAGER_SEED, &merchant_id.to_le_bytes()],
+        seeds = [MERCHANT_MANAGER_SEED, merchant_id_seed(merchant_id).as_ref()],
         bump = manager_state.bump,
         seeds::program = ID
     )]
@@ -75,15 +83,15 @@ pub struct AddOrUpdateMerchantDebitor<'info> {
 
     /// PDA storing the debitor's authorization state
     /// Seeds: [MERCHANT_DEBITOR_SEED, merchant_id, debitor]
-    /// Space: Discriminator + Boolean
+    /// Space: Discriminator + MerchantDebitorState
     /// Required permissions: Mutable if new, Read-only if existing
     #[account(
         init_if_needed,
         payer = payer,
-        space = MerchantDebitorState::DISCRIMINATOR.len() + MerchantDebitorState::INIT_SPACE,
+        space = MerchantDebitorState::SPACE,
         seeds = [
             MERCHANT_DEBITOR_SEED,
-            &merchant_id.to_le_bytes(),
+            merchant_id_seed(merchant_id).as_ref(),
             &mint.key().as_ref(),
             &debitor.key().as_ref(),
         ],
@@ -103,6 +111,16 @@ pub struct AddOrUpdateMerchantDebitor<'info> {
 
     /// Required for account creation
     pub system_program: Program<'info, System>,
+
+    /// Registry counting the merchant's accounts, incremented when the debitor state is created
+    /// Seeds: [MERCHANT_REGISTRY_SEED, merchant_id]
+    /// Required permissions: Mutable (counter update)
+    #[account(mut,
+        seeds = [MERCHANT_REGISTRY_SEED, merchant_id_seed(merchant_id).as_ref()],
+        bump = merchant_registry.bump,
+        seeds::program = ID
+    )]
+    pub merchant_registry: Option<Account<'info, MerchantRegistryState>>,
 }
 
 /**
@@ -111,11 +129,14 @@ pub struct AddOrUpdateMerchantDebitor<'info> {
  * @param ctx Context containing all required accounts
  * @param merchant_id Unique identifier for the merchant
  * @param allowed Whether the debitor should be allowed to initiate transfers
+ * @param per_call_limit Maximum amount the debitor can transfer in a single debit (0 for no cap)
+ * @param daily_limit Maximum amount the debitor can transfer within a day (0 for no cap)
  *
  * Flow:
  * 1. Verify manager signature (done via account constraints)
- * 2. Update debitor state PDA with new allowed status
- * 3. Emit event with merchant_id, debitor, and state change
+ * 2. Update debitor state PDA with new allowed status and limits
+ * 3. Count a newly created debitor in the merchant registry, if passed
+ * 4. Emit event with merchant_id, debitor, and state change
  *
  * @return Result indicating success or containing an error
  */
@@ -123,12 +144,23 @@ pub fn handler(
     ctx: Context<AddOrUpdateMerchantDebitor>,
     merchant_id: u64,
     allowed: bool,
+    per_call_limit: u64,
+    daily_limit: u64,
 ) -> Result<()> {
     let debitor_state = &mut ctx.accounts.debitor_state;
+    // A state just created by init_if_needed has no bump stored yet
+    let is_new = debitor_state.bump == 0;
     let previous_state = debitor_state.allowed;
     debitor_state.allowed = allowed;
+    debitor_state.per_call_limit = per_call_limit;
+    debitor_state.daily_limit = daily_limit;
+    debitor_state.version = CURRENT_VERSION;
     debitor_state.bump = ctx.bumps.debitor_state;
 
+    if let Some(merchant_registry) = ctx.accounts.merchant_registry.as_mut().filter(|_| is_new) {
+        merchant_registry.debitor_count = merchant_registry.debitor_count.saturating_add(1);
+    }
+
     // Emit event for indexing and notifications
     emit!(MerchantDebitorAddedOrUpdated {
         merchant_id,
@@ -136,6 +168,8 @@ pub fn handler(
         state_pda: ctx.accounts.debitor_state.key(),
         previous_state,
         new_state: allowed,
+        per_call_limit,
+        daily_limit,
     });
 
     Ok(())
@@ -1,7 +1,12 @@
 use crate::events::MerchantDestinationAddedOrUpdated;
 use crate::instructions::initialize::STATE_SEED;
-use crate::state::{BridgeCardsState, MerchantDestinationState};
+use crate::pda::merchant_id_seed;
+use crate::state::{
+    AccountSpace, BridgeCardsState, MerchantDestinationState, MerchantRegistryState,
+    CURRENT_VERSION,
+};
 use crate::ID;
+use crate::MERCHANT_REGISTRY_SEED;
 use anchor_lang::prelude::*;
 use anchor_spl::token_interface::{Mint, TokenAccount};
 
@@ -19,6 +24,8 @@ pub const MERCHANT_DESTINATION_SEED: &[u8] = b"merchant_destination";
  * - Each destination is specific to a merchant-mint combination
  * - Destinations can be enabled or disabled via the destination_allowed parameter
  * - Multiple destinations can be configured per merchant and mint
+ * - A destination can be marked primary via the is_primary parameter; debits must target a
+ *   primary destination when the merchant config enforces it
  *
  * Account Creation:
  * - Creates a PDA to store the destination's state if it doesn't exist
@@ -33,7 +40,7 @@ pub const MERCHANT_DESTINATION_SEED: &[u8] = b"merchant_destination";
  *
  * Events Emitted:
  * - MerchantDestinationAddedOrUpdated: When a destination is set or changed
- *   Fields: merchant_id, mint, destination, state_pda, previous_state, new_state
+ *   Fields: merchant_id, mint, destination, state_pda, previous_state, new_state, is_primary
  *
  * Required Accounts:
  * - admin: Program admin who can update destinations
@@ -43,6 +50,7 @@ pub const MERCHANT_DESTINATION_SEED: &[u8] = b"merchant_destination";
  * - destination_token_account: Token account to be allowlisted
  * - mint: Token mint for the destination account
  * - system_program: Required for account creation
+ * - merchant_registry: Registry counting the merchant's accounts (optional)
  */
 #[derive(Accounts)]
 #[instruction(merchant_id: u64)]
@@ -74,10 +82,10 @@ pub struct AddOrUpdateMerchantDestination<'info> {
     #[account(
         init_if_needed,
         payer = payer,
-        space = MerchantDestinationState::DISCRIMINATOR.len() + MerchantDestinationState::INIT_SPACE,
+        space = MerchantDestinationState::SPACE,
         seeds = [
             MERCHANT_DESTINATION_SEED,
-            &merchant_id.to_le_bytes(),
+            merchant_id_seed(merchant_id).as_ref(),
             mint.key().as_ref(),
             destination_token_account.key().as_ref(),
         ],
@@ -98,6 +106,16 @@ pub struct AddOrUpdateMerchantDestination<'info> {
 
     /// Required for account creation
     pub system_program: Program<'info, System>,
+
+    /// Registry counting the merchant's accounts, incremented when the destination state is created
+    /// Seeds: [MERCHANT_REGISTRY_SEED, merchant_id]
+    /// Required permissions: Mutable (counter update)
+    #[account(mut,
+        seeds = [MERCHANT_REGISTRY_SEED, merchant_id_seed(merchant_id).as_ref()],
+        bump = merchant_registry.bump,
+        seeds::program = ID
+    )]
+    pub merchant_registry: Option<Account<'info, MerchantRegistryState>>,
 }
 
 /**
@@ -106,11 +124,13 @@ pub struct AddOrUpdateMerchantDestination<'info> {
  * @param ctx Context containing all required accounts
  * @param merchant_id Unique identifier for the merchant
  * @param destination_allowed Whether the destination should be allowed to receive funds
+ * @param is_primary Whether the destination is the merchant's primary destination for the mint
  *
  * Flow:
  * 1. Verify admin signature (done via account constraints)
- * 2. Update destination state PDA with new allowed status
- * 3. Emit event with merchant_id, mint, destination, and state change
+ * 2. Update destination state PDA with new allowed and primary status
+ * 3. Count a newly created destination in the merchant registry, if passed
+ * 4. Emit event with merchant_id, mint, destination, and state change
  *
  * @return Result indicating success or containing an error
  */
@@ -118,12 +138,21 @@ pub fn handler(
     ctx: Context<AddOrUpdateMerchantDestination>,
     merchant_id: u64,
     destination_allowed: bool,
+    is_primary: bool,
 ) -> Result<()> {
     let destination_state = &mut ctx.accounts.destination_state;
+    // A state just created by init_if_needed has no bump stored yet
+    let is_new = destination_state.bump == 0;
     let previous_state = destination_state.allowed;
     destination_state.allowed = destination_allowed;
+    destination_state.is_primary = is_primary;
+    destination_state.version = CURRENT_VERSION;
     destination_state.bump = ctx.bumps.destination_state;
 
+    if let Some(merchant_registry) = ctx.accounts.merchant_registry.as_mut().filter(|_| is_new) {
+        merchant_registry.destination_count = merchant_registry.destination_count.saturating_add(1);
+    }
+
     // Emit event for indexing and notifications
     emit!(MerchantDestinationAddedOrUpdated {
         merchant_id,
@@ -132,6 +161,7 @@ pub fn handler(
         state_pda: ctx.accounts.destination_state.key(),
         previous_state,
         new_state: destination_allowed,
+        is_primary,
     });
 
     Ok(())
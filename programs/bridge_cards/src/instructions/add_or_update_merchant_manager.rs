@@ -1,8 +1,13 @@
+use crate::errors::ErrorCode;
 use crate::events::MerchantManagerAddedOrUpdated;
 use crate::instructions::initialize::STATE_SEED;
-use crate::state::{BridgeCardsState, MerchantManagerState};
+use crate::pda::merchant_id_seed;
+use crate::state::{
+    feature_flags, AccountSpace, BridgeCardsState, MerchantManagerState, CURRENT_VERSION,
+};
 use crate::ID;
 use anchor_lang::prelude::*;
+use anchor_spl::{token, token_2022};
 
 /// Seed used to derive merchant manager PDAs
 pub const MERCHANT_MANAGER_SEED: &[u8] = b"merchant_manager";
@@ -10,8 +15,9 @@ pub const MERCHANT_MANAGER_SEED: &[u8] = b"merchant_manager";
 /**
  * Add or update a merchant manager account.
  *
- * This instruction allows the program admin to designate an account as a manager for a specific
- * merchant. Merchant managers can:
+ * This instruction allows the program admin to designate an account as the manager for a specific
+ * merchant, replacing any existing managers. Further managers can then be added with
+ * add_merchant_manager. Merchant managers can:
  * - Add/update user delegates for their merchant
  * - Configure delegate transfer limits
  * - Add/update debitor accounts for their merchant
@@ -26,12 +32,14 @@ pub const MERCHANT_MANAGER_SEED: &[u8] = b"merchant_manager";
  *
  * Security Model:
  * - Only the program admin can add/update managers
- * - Manager account does not need to sign (allows admin to revoke access)
- * - Each merchant can have one active manager at a time
+ * - Manager account does not need to sign (allows admin to revoke access), unless the
+ *   REQUIRE_MANAGER_COSIGN feature flag is set
+ * - The manager cannot be the program state PDA or a token account, which can never sign
+ * - Each merchant can have up to MAX_MERCHANT_MANAGERS managers, this instruction leaves one
  * - Manager state is stored in a PDA unique to the merchant
  *
  * Events Emitted:
- * - MerchantManagerAddedOrUpdated: When a manager is set or changed
+ * - MerchantManagerAddedOrUpdated: When the manager is set or changed
  *   Fields: merchant_id, manager pubkey
  *
  * Common Use Cases:
@@ -72,15 +80,15 @@ pub struct AddOrUpdateMerchantManager<'info> {
 
     /// PDA storing the merchant manager's authorization state
     /// Seeds: [MERCHANT_MANAGER_SEED, merchant_id]
-    /// Space: Discriminator + Pubkey
+    /// Space: Discriminator + MerchantManagerState::INIT_SPACE
     /// Required permissions: Mutable if new, Read-only if existing
     #[account(
         init_if_needed,
         payer = payer,
-        space = MerchantManagerState::DISCRIMINATOR.len() + MerchantManagerState::INIT_SPACE,
+        space = MerchantManagerState::SPACE,
         seeds = [
             MERCHANT_MANAGER_SEED,
-            &merchant_id.to_le_bytes(),
+            merchant_id_seed(merchant_id).as_ref(),
         ],
         bump
     )]
@@ -88,7 +96,7 @@ pub struct AddOrUpdateMerchantManager<'info> {
 
     /// Account to be set as the merchant manager
     /// Not a signer to allow admin to revoke access without manager cooperation
-    /// Required permissions: None
+    /// Required permissions: None (Signer if REQUIRE_MANAGER_COSIGN is set)
     /// CHECK: Account is only stored as a pubkey, no account data validation needed
     pub manager: AccountInfo<'info>,
 
@@ -104,14 +112,18 @@ pub struct AddOrUpdateMerchantManager<'info> {
  *
  * Flow:
  * 1. Verify admin signature (done via account constraints)
- * 2. Update manager state PDA with new manager pubkey
- * 3. Emit event with merchant_id and new manager
+ * 2. Reject managers that can never sign, and unsigned managers if co-signing is required
+ * 3. Replace the managers in the manager state PDA with the new manager
+ * 4. Emit event with merchant_id and new manager
  *
  * @return Result indicating success or containing an error
  */
 pub fn handler(ctx: Context<AddOrUpdateMerchantManager>, merchant_id: u64) -> Result<()> {
+    check_manager(&ctx.accounts.state, &ctx.accounts.manager)?;
+
     let manager_state = &mut ctx.accounts.manager_state;
-    manager_state.manager = ctx.accounts.manager.key();
+    manager_state.set_manager(ctx.accounts.manager.key());
+    manager_state.version = CURRENT_VERSION;
     manager_state.bump = ctx.bumps.manager_state;
 
     // Emit event for indexing and notifications
@@ -122,3 +134,25 @@ pub fn handler(ctx: Context<AddOrUpdateMerchantManager>, merchant_id: u64) -> Re
 
     Ok(())
 }
+
+/**
+ * Check that an account can be made a merchant manager.
+ *
+ * @param state Global program state, for the program address and feature flags
+ * @param manager Account to be made a manager
+ *
+ * @return Ok if the account can sign as a manager, InvalidManager otherwise
+ */
+pub(crate) fn check_manager(
+    state: &Account<BridgeCardsState>,
+    manager: &AccountInfo,
+) -> Result<()> {
+    let is_token_account = manager.owner == &token::ID || manager.owner == &token_2022::ID;
+    if manager.key() == state.key() || is_token_account {
+        return Err(ErrorCode::InvalidManager.into());
+    }
+    if state.has_feature_flag(feature_flags::REQUIRE_MANAGER_COSIGN) && !manager.is_signer {
+        return Err(ErrorCode::InvalidManager.into());
+    }
+    Ok(())
+}
@@ -0,0 +1,129 @@
+use crate::events::MerchantMintAddedOrUpdated;
+use crate::instructions::initialize::STATE_SEED;
+use crate::pda::merchant_id_seed;
+use crate::state::{AccountSpace, BridgeCardsState, MerchantMintState, CURRENT_VERSION};
+use crate::ID;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+/// Seed used to derive merchant mint PDAs
+pub const MERCHANT_MINT_SEED: &[u8] = b"merchant_mint";
+
+/**
+ * Add or update a mint allowlisted for a merchant.
+ *
+ * This instruction allows the program admin to choose which mints a merchant supports.
+ * When the merchant config enforces the mint allowlist, managers can only create delegates
+ * and debitors can only debit for mints with an allowed merchant mint state.
+ *
+ * Mint Configuration:
+ * - Each state is specific to a merchant-mint combination
+ * - Mints can be enabled or disabled via the mint_allowed parameter
+ * - States are ignored until enforce_mint_allowlist is set in the merchant config
+ *
+ * Account Creation:
+ * - Creates a PDA to store the mint's state if it doesn't exist
+ * - PDA is derived using [MERCHANT_MINT_SEED, merchant_id, mint]
+ * - Funded by the payer account
+ *
+ * Security Model:
+ * - Only the program admin can add/update merchant mints
+ * - State is stored in a PDA unique to the merchant-mint combination
+ *
+ * Events Emitted:
+ * - MerchantMintAddedOrUpdated: When a mint is set or changed
+ *   Fields: merchant_id, mint, state_pda, previous_state, new_state
+ *
+ * Required Accounts:
+ * - admin: Program admin who can update merchant mints
+ * - payer: Account paying for PDA creation/rent
+ * - state: Global program state storing admin pubkey
+ * - mint_state: PDA storing the mint's authorization
+ * - mint: Token mint to be allowlisted
+ * - system_program: Required for account creation
+ */
+#[derive(Accounts)]
+#[instruction(merchant_id: u64)]
+pub struct AddOrUpdateMerchantMint<'info> {
+    /// The program admin account, must match admin stored in state
+    /// Required permissions: Signer
+    #[account(constraint = admin.key() == state.admin)]
+    pub admin: Signer<'info>,
+
+    /// Account that will pay for PDA creation and rent
+    /// Required permissions: Signer, Mutable (for rent payment)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Global program state storing the admin public key
+    /// Seeds: [STATE_SEED]
+    /// Required permissions: Read-only
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        seeds::program = ID
+    )]
+    pub state: Account<'info, BridgeCardsState>,
+
+    /// PDA storing the mint's authorization state
+    /// Seeds: [MERCHANT_MINT_SEED, merchant_id, mint]
+    /// Space: Discriminator + Boolean
+    /// Required permissions: Mutable
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = MerchantMintState::SPACE,
+        seeds = [
+            MERCHANT_MINT_SEED,
+            merchant_id_seed(merchant_id).as_ref(),
+            mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub mint_state: Account<'info, MerchantMintState>,
+
+    /// Mint to be allowlisted for the merchant
+    /// Used for PDA derivation
+    /// Required permissions: None (read-only validation)
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Required for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/**
+ * Process the addition or update of a merchant mint.
+ *
+ * @param ctx Context containing all required accounts
+ * @param merchant_id Unique identifier for the merchant
+ * @param mint_allowed Whether the merchant may use the mint
+ *
+ * Flow:
+ * 1. Verify admin signature (done via account constraints)
+ * 2. Update mint state PDA with the new allowed status
+ * 3. Emit event with merchant_id, mint, and state change
+ *
+ * @return Result indicating success or containing an error
+ */
+pub fn handler(
+    ctx: Context<AddOrUpdateMerchantMint>,
+    merchant_id: u64,
+    mint_allowed: bool,
+) -> Result<()> {
+    let mint_state = &mut ctx.accounts.mint_state;
+    let previous_state = mint_state.allowed;
+    mint_state.allowed = mint_allowed;
+    mint_state.version = CURRENT_VERSION;
+    mint_state.bump = ctx.bumps.mint_state;
+
+    // Emit event for indexing and notifications
+    emit!(MerchantMintAddedOrUpdated {
+        merchant_id,
+        mint: ctx.accounts.mint.key(),
+        state_pda: ctx.accounts.mint_state.key(),
+        previous_state,
+        new_state: mint_allowed,
+    });
+
+    Ok(())
+}
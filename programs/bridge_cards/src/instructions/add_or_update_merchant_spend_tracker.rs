@@ -0,0 +1,118 @@
+use crate::events::MerchantSpendTrackerAddedOrUpdated;
+use crate::instructions::initialize::STATE_SEED;
+use crate::pda::merchant_id_seed;
+use crate::state::{AccountSpace, BridgeCardsState, MerchantSpendTrackerState, CURRENT_VERSION};
+use crate::ID;
+use anchor_lang::prelude::*;
+
+/// Seed used to derive merchant spend tracker PDAs
+pub const MERCHANT_SPEND_SEED: &[u8] = b"merchant_spend";
+
+/**
+ * Add or update the merchant-wide spend limit of a merchant.
+ *
+ * Per-delegate limits don't cap a merchant's total outflow when it has many delegates. This
+ * instruction allows the program admin to create a spend tracker holding a period limit
+ * across all of the merchant's delegates. Once it exists, every debit of the merchant
+ * (debit_user and batch_debit_user) counts against it in addition to its delegate's limits.
+ *
+ * Account Creation:
+ * - Creates a PDA to store the tracker if it doesn't exist
+ * - PDA is derived using [MERCHANT_SPEND_SEED, merchant_id]
+ * - Funded by the payer account
+ *
+ * Security Model:
+ * - Only the program admin can add/update spend trackers
+ * - Updates only change the limits; the current period's usage is kept
+ *
+ * Events Emitted:
+ * - MerchantSpendTrackerAddedOrUpdated: When a tracker is set or changed
+ *   Fields: merchant_id, state_pda, period_transfer_limit, transfer_limit_period_seconds
+ *
+ * Required Accounts:
+ * - admin: Program admin who can update spend trackers
+ * - payer: Account paying for PDA creation/rent
+ * - state: Global program state storing admin pubkey
+ * - merchant_spend_tracker: PDA storing the merchant's limit and usage
+ * - system_program: Required for account creation
+ */
+#[derive(Accounts)]
+#[instruction(merchant_id: u64)]
+pub struct AddOrUpdateMerchantSpendTracker<'info> {
+    /// The program admin account, must match admin stored in state
+    /// Required permissions: Signer
+    #[account(constraint = admin.key() == state.admin)]
+    pub admin: Signer<'info>,
+
+    /// Account that will pay for PDA creation and rent
+    /// Required permissions: Signer, Mutable (for rent payment)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Global program state storing the admin public key
+    /// Seeds: [STATE_SEED]
+    /// Required permissions: Read-only
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        seeds::program = ID
+    )]
+    pub state: Account<'info, BridgeCardsState>,
+
+    /// PDA storing the merchant's spend limit and usage
+    /// Seeds: [MERCHANT_SPEND_SEED, merchant_id]
+    /// Space: Discriminator + Merchant spend tracker
+    /// Required permissions: Mutable
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = MerchantSpendTrackerState::SPACE,
+        seeds = [
+            MERCHANT_SPEND_SEED,
+            merchant_id_seed(merchant_id).as_ref(),
+        ],
+        bump
+    )]
+    pub merchant_spend_tracker: Account<'info, MerchantSpendTrackerState>,
+
+    /// Required for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/**
+ * Process the addition or update of a merchant spend tracker.
+ *
+ * @param ctx Context containing all required accounts
+ * @param merchant_id Unique identifier for the merchant
+ * @param period_transfer_limit Maximum total amount per period across all delegates
+ * @param transfer_limit_period Duration of the period in seconds
+ *
+ * Flow:
+ * 1. Verify admin signature (done via account constraints)
+ * 2. Set the tracker's limits, keeping the current period's usage
+ * 3. Emit event with merchant_id and the new limits
+ *
+ * @return Result indicating success or containing an error
+ */
+pub fn handler(
+    ctx: Context<AddOrUpdateMerchantSpendTracker>,
+    merchant_id: u64,
+    period_transfer_limit: u64,
+    transfer_limit_period: u32,
+) -> Result<()> {
+    let merchant_spend_tracker = &mut ctx.accounts.merchant_spend_tracker;
+    merchant_spend_tracker.period_transfer_limit = period_transfer_limit;
+    merchant_spend_tracker.transfer_limit_period_seconds = transfer_limit_period;
+    merchant_spend_tracker.version = CURRENT_VERSION;
+    merchant_spend_tracker.bump = ctx.bumps.merchant_spend_tracker;
+
+    // Emit event for indexing and notifications
+    emit!(MerchantSpendTrackerAddedOrUpdated {
+        merchant_id,
+        state_pda: ctx.accounts.merchant_spend_tracker.key(),
+        period_transfer_limit,
+        transfer_limit_period_seconds: transfer_limit_period,
+    });
+
+    Ok(())
+}
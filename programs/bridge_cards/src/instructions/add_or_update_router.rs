@@ -0,0 +1,116 @@
+use crate::events::RouterAddedOrUpdated;
+use crate::instructions::initialize::STATE_SEED;
+use crate::state::{AccountSpace, BridgeCardsState, RouterState, CURRENT_VERSION};
+use crate::ID;
+use anchor_lang::prelude::*;
+
+/// Seed used to derive router PDAs
+pub const ROUTER_SEED: &[u8] = b"router";
+
+/**
+ * Add or update an allowed router program.
+ *
+ * This instruction allows the program admin to allowlist external programs that may be
+ * invoked after a debit to swap the tokens received by a merchant destination. A merchant
+ * config can only route through programs that are allowed here.
+ *
+ * Account Creation:
+ * - Creates a PDA to store the router's state if it doesn't exist
+ * - PDA is derived using [ROUTER_SEED, router_program]
+ * - Funded by the payer account
+ *
+ * Security Model:
+ * - Only the program admin can add/update routers
+ * - Router must be an executable program account
+ * - Revoking a router immediately blocks every merchant configured to use it
+ *
+ * Events Emitted:
+ * - RouterAddedOrUpdated: When a router is set or changed
+ *   Fields: router_program, state_pda, previous_state, new_state
+ *
+ * Required Accounts:
+ * - admin: Program admin who can update routers
+ * - payer: Account paying for PDA creation/rent
+ * - state: Global program state storing admin pubkey
+ * - router_state: PDA storing router authorization
+ * - router_program: Program to be allowlisted as a router
+ * - system_program: Required for account creation
+ */
+#[derive(Accounts)]
+pub struct AddOrUpdateRouter<'info> {
+    /// The program admin account, must match admin stored in state
+    /// Required permissions: Signer
+    #[account(constraint = admin.key() == state.admin)]
+    pub admin: Signer<'info>,
+
+    /// Account that will pay for PDA creation and rent
+    /// Required permissions: Signer, Mutable (for rent payment)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Global program state storing the admin public key
+    /// Seeds: [STATE_SEED]
+    /// Required permissions: Read-only
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        seeds::program = ID
+    )]
+    pub state: Account<'info, BridgeCardsState>,
+
+    /// PDA storing the router's authorization state
+    /// Seeds: [ROUTER_SEED, router_program]
+    /// Space: Discriminator + Boolean
+    /// Required permissions: Mutable if new, Read-only if existing
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = RouterState::SPACE,
+        seeds = [
+            ROUTER_SEED,
+            router_program.key().as_ref(),
+        ],
+        bump
+    )]
+    pub router_state: Account<'info, RouterState>,
+
+    /// Program to be allowlisted as a router
+    /// Required permissions: None (read-only validation)
+    /// CHECK: Only the key is stored; the account must be an executable program
+    #[account(executable)]
+    pub router_program: UncheckedAccount<'info>,
+
+    /// Required for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/**
+ * Process the addition or update of a router.
+ *
+ * @param ctx Context containing all required accounts
+ * @param router_allowed Whether the router should be allowed to be invoked
+ *
+ * Flow:
+ * 1. Verify admin signature (done via account constraints)
+ * 2. Update router state PDA with new allowed status
+ * 3. Emit event with router and state change
+ *
+ * @return Result indicating success or containing an error
+ */
+pub fn handler(ctx: Context<AddOrUpdateRouter>, router_allowed: bool) -> Result<()> {
+    let router_state = &mut ctx.accounts.router_state;
+    let previous_state = router_state.allowed;
+    router_state.allowed = router_allowed;
+    router_state.version = CURRENT_VERSION;
+    router_state.bump = ctx.bumps.router_state;
+
+    // Emit event for indexing and notifications
+    emit!(RouterAddedOrUpdated {
+        router_program: ctx.accounts.router_program.key(),
+        state_pda: ctx.accounts.router_state.key(),
+        previous_state,
+        new_state: router_allowed,
+    });
+
+    Ok(())
+}
@@ -1,12 +1,60 @@
-use crate::events::UserDelegateAddedOrUpdated;
-use crate::state::{MerchantManagerState, UserDelegateState};
-use crate::{ID, MERCHANT_MANAGER_SEED};
+use crate::errors::ErrorCode;
+use crate::events::{UserDelegateAddedOrUpdated, UserDelegateSnapshot};
+use crate::pda::merchant_id_seed;
+use crate::state::{
+    AccountSpace, MerchantConfigState, MerchantManagerState, MerchantRegistryState,
+    UserDelegateState, CURRENT_VERSION,
+};
+use crate::{
+    ID, MERCHANT_CONFIG_SEED, MERCHANT_MANAGER_SEED, MERCHANT_MINT_SEED, MERCHANT_REGISTRY_SEED,
+};
 use anchor_lang::prelude::*;
 use anchor_spl::token_interface::{Mint, TokenAccount};
+use limit_check::LimitMode;
 
 /// Seed used to derive user delegate PDAs
 pub const USER_DELEGATE_SEED: &[u8] = b"user_delegate";
 
+/**
+ * Optional delegate settings applied by the add_or_update_user_delegate instruction.
+ *
+ * Zero values mean "not overridden" and fall back to the merchant config.
+ */
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, Debug, PartialEq)]
+pub struct UserDelegateOptions {
+    /// Minimum slots between two debits, overriding the merchant config (0 = not overridden)
+    pub min_slots_between_debits: u64,
+    /// Allow debits to non-primary destinations when the merchant enforces primary destinations
+    pub allow_alternate_destination: bool,
+    /// Commitment linking the delegate to an off-chain record, e.g. a hash of a card identifier
+    pub metadata_hash: [u8; 32],
+    /// Unix timestamp before which debits are rejected, e.g. for pre-issued cards (0 = now)
+    pub valid_from: i64,
+    /// Unix timestamp after which debits are rejected (0 = never, or the merchant default)
+    pub expires_at: i64,
+    /// Apply the merchant's default_delegate_expiry_seconds when expires_at is 0
+    pub use_default_expiry: bool,
+    /// Minimum amount of a single debit, e.g. to reject dust transfers (0 = no minimum)
+    pub min_transfer_amount: u64,
+    /// How period usage frees up: 0 = fixed window (default), 1 = sliding window
+    pub limit_mode: u8,
+    /// Start each fixed-window period at the end of the previous one, so periods don't drift
+    /// with the timing of debits
+    pub strict_boundary: bool,
+    /// Align fixed-window periods to multiples of the period since the unix epoch, e.g. to
+    /// reset at UTC midnight with a one-day period. The first debit after enabling it starts a
+    /// new period
+    pub anchor_to_epoch: bool,
+    /// Only destination token account the delegate can be debited to, e.g. a single
+    /// subscription. None keeps the delegate's current restriction, Some(None) lifts it so any
+    /// destination allowed for the merchant can be debited to
+    pub restricted_destination: Option<Option<Pubkey>>,
+    /// Seconds after creation before the delegate can debit, so a compromised manager can't
+    /// drain a user through a delegate it just created (0 = no cooldown). Only applied when
+    /// the delegate is created
+    pub cooldown_seconds: u32,
+}
+
 /**
  * Add or update a user delegate account for token transfers.
  *
@@ -18,6 +66,8 @@ pub const USER_DELEGATE_SEED: &[u8] = b"user_delegate";
  * - Per-transaction limit: Maximum amount for a single transfer
  * - Period limit: Maximum amount within a time window
  * - Period duration: Length of the time window in seconds
+ * - Options: Optional overrides of merchant-wide settings and the limit mode (fixed or
+ *   sliding window, see UserDelegateOptions)
  *
  * Account Creation:
  * - Creates a PDA to store delegate parameters if it doesn't exist
@@ -26,6 +76,16 @@ pub const USER_DELEGATE_SEED: &[u8] = b"user_delegate";
  *
  * Security Model:
  * - Only merchant managers can create/update delegates
+ * - If the merchant config sets managers_can_only_lower_limits, existing delegates' limits
+ *   can only be lowered here; raises go through admin_raise_delegate_limits. Loosening the
+ *   delegate's other controls (a shorter period, a later or removed expiry, a lifted or
+ *   changed destination restriction, a new limit mode or epoch anchoring) counts as a raise
+ *   too
+ * - If the merchant config caps delegate limits (max_allowed_per_transfer,
+ *   max_allowed_period_limit), the limits can't exceed the caps; without a config managers
+ *   are not capped
+ * - If the merchant config sets enforce_mint_allowlist, the mint must be allowlisted for the
+ *   merchant (merchant_mint)
  * - Each delegate is specific to a merchant-user-mint combination
  * - Transfer limits provide spending controls
  * - Period tracking prevents excessive transfers
@@ -37,7 +97,9 @@ pub const USER_DELEGATE_SEED: &[u8] = b"user_delegate";
  *
  * Events Emitted:
  * - UserDelegateAddedOrUpdated: When a delegate is created or updated
- *   Fields: merchant_pda, user_delegate
+ *   Fields: merchant_pda, user_delegate, metadata_hash, previous and new per-transfer and
+ *   period limits
+ * - UserDelegateSnapshot: Full delegate state, if the merchant config enables emit_full_snapshot
  *
  * Common Use Cases:
  * - Setting up new merchant-user relationships
@@ -48,17 +110,20 @@ pub const USER_DELEGATE_SEED: &[u8] = b"user_delegate";
  * - manager: Merchant manager who can create delegates
  * - payer: Account paying for PDA creation/rent
  * - manager_state: PDA verifying manager authority
+ * - merchant_config: PDA storing the merchant configuration (may not exist)
  * - user_token_account: Token account to delegate
  * - mint: Token mint for the delegation
  * - user_delegate_account: PDA storing delegate parameters
  * - system_program: Required for account creation
+ * - merchant_mint: PDA allowlisting the mint (optional, required by enforce_mint_allowlist)
+ * - merchant_registry: Registry counting the merchant's accounts (optional)
  */
 #[derive(Accounts)]
 #[instruction(merchant_id: u64)]
 pub struct AddOrUpdateUserDelegate<'info> {
-    /// Merchant manager account, must match manager in manager_state
+    /// Merchant manager account, must be one of the managers in manager_state
     /// Required permissions: Signer
-    #[account( constraint = manager.key() == manager_state.manager)]
+    #[account(constraint = manager_state.is_manager(&manager.key()))]
     pub manager: Signer<'info>,
 
     /// Account that will pay for PDA creation and rent
@@ -70,12 +135,23 @@ pub struct AddOrUpdateUserDelegate<'info> {
     /// Seeds: [MERCHANT_MANAGER_SEED, merchant_id]
     /// Required permissions: Read-only
     #[account(
-        seeds = [MERCHANT_MANAGER_SEED, &merchant_id.to_le_bytes()],
+        seeds = [MERCHANT_MANAGER_SEED, merchant_id_seed(merchant_id).as_ref()],
         bump = manager_state.bump,
         seeds::program = ID
     )]
     pub manager_state: Account<'info, MerchantManagerState>,
 
+    /// PDA storing the merchant configuration
+    /// Seeds: [MERCHANT_CONFIG_SEED, merchant_id]
+    /// Required permissions: Read-only
+    /// CHECK: Address is verified by seeds; default settings apply if it has not been created
+    #[account(
+        seeds = [MERCHANT_CONFIG_SEED, merchant_id_seed(merchant_id).as_ref()],
+        bump,
+        seeds::program = ID
+    )]
+    pub merchant_config: UncheckedAccount<'info>,
+
     /// Token account that will be controlled by the delegate
     /// Required permissions: Read-only
     pub user_token_account: InterfaceAccount<'info, TokenAccount>,
@@ -90,10 +166,10 @@ pub struct AddOrUpdateUserDelegate<'info> {
     /// Required permissions: Mutable if new, Read-only if existing
     #[account(init_if_needed,
         payer=payer,
-        space = UserDelegateState::DISCRIMINATOR.len() + UserDelegateState::INIT_SPACE,
+        space = UserDelegateState::SPACE,
         seeds = [
             USER_DELEGATE_SEED,
-            merchant_id.to_le_bytes().as_ref(),
+            merchant_id_seed(merchant_id).as_ref(),
             mint.key().as_ref(),
             user_token_account.key().as_ref(),
         ],
@@ -103,6 +179,28 @@ pub struct AddOrUpdateUserDelegate<'info> {
 
     /// Required for account creation
     pub system_program: Program<'info, System>,
+
+    /// PDA allowlisting the mint for the merchant
+    /// Seeds: [MERCHANT_MINT_SEED, merchant_id, mint]
+    /// Required when the merchant config enforces the mint allowlist
+    /// Required permissions: Read-only
+    /// CHECK: Address is verified by seeds; the mint is not allowlisted if it has not been created
+    #[account(
+        seeds = [MERCHANT_MINT_SEED, merchant_id_seed(merchant_id).as_ref(), mint.key().as_ref()],
+        bump,
+        seeds::program = ID
+    )]
+    pub merchant_mint: Option<UncheckedAccount<'info>>,
+
+    /// Registry counting the merchant's accounts, incremented when the delegate is created
+    /// Seeds: [MERCHANT_REGISTRY_SEED, merchant_id]
+    /// Required permissions: Mutable (counter update)
+    #[account(mut,
+        seeds = [MERCHANT_REGISTRY_SEED, merchant_id_seed(merchant_id).as_ref()],
+        bump = merchant_registry.bump,
+        seeds::program = ID
+    )]
+    pub merchant_registry: Option<Account<'info, MerchantRegistryState>>,
 }
 
 /**
@@ -111,13 +209,21 @@ pub struct AddOrUpdateUserDelegate<'info> {
  * @param ctx Context containing all required accounts
  * @param _merchant_id Unique identifier for the merchant (used in constraints)
  * @param max_transfer_limit Maximum amount allowed in a single transfer
- * @param period_transfer_limit Maximum amount allowed within the time period
+ * @param period_transfer_limit Maximum amount allowed within the time period, or
+ *        UNLIMITED_PERIOD_TRANSFER_LIMIT to skip period tracking
  * @param transfer_limit_period Duration of the transfer limit period in seconds
+ * @param options Optional overrides of merchant-wide settings
  *
  * Flow:
  * 1. Verify manager signature (done via account constraints)
- * 2. Update delegate parameters in PDA
- * 3. Emit event with delegate information
+ * 2. Reject mints not allowlisted for the merchant, limit raises if the merchant only allows managers to lower limits, limits
+ *    above the merchant's caps, inconsistent limits (zero period, period limit below the
+ *    per-transfer limit), periods whose end would overflow and unknown limit modes
+ * 3. Update delegate parameters in PDA, applying the merchant's default expiry if requested
+ *    and, for a new delegate, starting its cooldown
+ * 4. Count a newly created delegate in the merchant registry, if passed
+ * 5. Emit event with delegate information and the limits it replaced (and a full snapshot if
+ *    configured)
  *
  * Note: Period tracking (transferred amount and reset timestamp) is managed
  * during the debit_user instruction, not during setup.
@@ -130,9 +236,112 @@ pub fn handler(
     max_transfer_limit: u64,
     period_transfer_limit: u64,
     transfer_limit_period: u32,
+    options: UserDelegateOptions,
 ) -> Result<()> {
+    let merchant_config = MerchantConfigState::load_or_default(&ctx.accounts.merchant_config)?;
+    merchant_config.check_mint(ctx.accounts.merchant_mint.as_deref())?;
     let user_delegate_account = &mut ctx.accounts.user_delegate_account;
 
+    // A delegate just created by init_if_needed has no bump stored yet
+    let is_existing = user_delegate_account.bump != 0;
+
+    // Read the limits being replaced for the event (zero for a new delegate)
+    let previous_per_transfer_limit = user_delegate_account.per_transfer_limit;
+    let previous_period_transfer_limit = user_delegate_account.period_transfer_limit;
+
+    apply_user_delegate_settings(
+        user_delegate_account,
+        &merchant_config,
+        max_transfer_limit,
+        period_transfer_limit,
+        transfer_limit_period,
+        &options,
+    )?;
+    user_delegate_account.bump = ctx.bumps.user_delegate_account;
+
+    if let Some(merchant_registry) = ctx
+        .accounts
+        .merchant_registry
+        .as_mut()
+        .filter(|_| !is_existing)
+    {
+        merchant_registry.delegate_count = merchant_registry.delegate_count.saturating_add(1);
+    }
+
+    // Emit event for indexing and notifications
+    emit!(UserDelegateAddedOrUpdated {
+        merchant_id,
+        mint: ctx.accounts.mint.key(),
+        user_ata: ctx.accounts.user_token_account.key(),
+        user_delegate: ctx.accounts.user_delegate_account.key(),
+        metadata_hash: ctx.accounts.user_delegate_account.metadata_hash,
+        previous_per_transfer_limit,
+        new_per_transfer_limit: max_transfer_limit,
+        previous_period_transfer_limit,
+        new_period_transfer_limit: period_transfer_limit,
+    });
+
+    if merchant_config.emit_full_snapshot {
+        emit_user_delegate_snapshot(
+            merchant_id,
+            &ctx.accounts.mint.key(),
+            &ctx.accounts.user_token_account.key(),
+            &ctx.accounts.user_delegate_account,
+        );
+    }
+
+    Ok(())
+}
+
+/**
+ * Write the limits and options of a delegate being created or updated.
+ *
+ * A delegate without a bump stored yet is being created: it starts its cooldown and is not
+ * subject to managers_can_only_lower_limits. An existing delegate under that setting can't
+ * have its limits raised or its other controls loosened. The caller stores the bump
+ * afterwards.
+ *
+ * @param user_delegate_account The delegate state to write
+ * @param merchant_config The merchant's config, or the default settings
+ * @param max_transfer_limit Maximum amount allowed in a single transfer
+ * @param period_transfer_limit Maximum amount allowed within the time period
+ * @param transfer_limit_period Duration of the transfer limit period in seconds
+ * @param options Optional overrides of merchant-wide settings
+ *
+ * @return Result indicating success, or an error if the limits or options are rejected
+ */
+pub(crate) fn apply_user_delegate_settings(
+    user_delegate_account: &mut UserDelegateState,
+    merchant_config: &MerchantConfigState,
+    max_transfer_limit: u64,
+    period_transfer_limit: u64,
+    transfer_limit_period: u32,
+    options: &UserDelegateOptions,
+) -> Result<()> {
+    let is_existing = user_delegate_account.bump != 0;
+    let expires_at = resolve_expires_at(merchant_config, options)?;
+    if merchant_config.managers_can_only_lower_limits
+        && is_existing
+        && (max_transfer_limit > user_delegate_account.per_transfer_limit
+            || period_transfer_limit > user_delegate_account.period_transfer_limit
+            || loosens_delegate_controls(
+                user_delegate_account,
+                transfer_limit_period,
+                expires_at,
+                options,
+            ))
+    {
+        return Err(ErrorCode::LimitRaiseRequiresAdmin.into());
+    }
+    merchant_config.check_limit_caps(max_transfer_limit, period_transfer_limit)?;
+    validate_delegate_limits(
+        max_transfer_limit,
+        period_transfer_limit,
+        transfer_limit_period,
+    )?;
+    user_delegate_account.check_period_end(transfer_limit_period)?;
+    LimitMode::try_from(options.limit_mode).map_err(|_| ErrorCode::InvalidLimitMode)?;
+
     // Set the maximum amount allowed per transaction
     user_delegate_account.per_transfer_limit = max_transfer_limit;
 
@@ -142,15 +351,118 @@ pub fn handler(
     // Set the duration of the transfer limit period in seconds
     user_delegate_account.transfer_limit_period_seconds = transfer_limit_period;
 
-    user_delegate_account.bump = ctx.bumps.user_delegate_account;
+    // Set the delegate's overrides of merchant-wide settings
+    user_delegate_account.min_slots_between_debits = options.min_slots_between_debits;
+    user_delegate_account.allow_alternate_destination = options.allow_alternate_destination;
+    user_delegate_account.metadata_hash = options.metadata_hash;
+    user_delegate_account.valid_from = options.valid_from;
+    user_delegate_account.expires_at = expires_at;
+    user_delegate_account.min_transfer_amount = options.min_transfer_amount;
+    user_delegate_account.limit_mode = options.limit_mode;
+    user_delegate_account.strict_boundary = options.strict_boundary;
+    user_delegate_account.anchor_to_epoch = options.anchor_to_epoch;
+    // An update that doesn't mention the restriction keeps it
+    if let Some(restricted_destination) = options.restricted_destination {
+        user_delegate_account.restricted_destination = restricted_destination.unwrap_or_default();
+    }
+    // Updates don't restart the cooldown, a delegate already active stays active
+    if !is_existing {
+        user_delegate_account.active_after = Clock::get()?
+            .unix_timestamp
+            .saturating_add(i64::from(options.cooldown_seconds));
+    }
 
-    // Emit event for indexing and notifications
-    emit!(UserDelegateAddedOrUpdated {
+    user_delegate_account.version = CURRENT_VERSION;
+
+    Ok(())
+}
+
+/// Emit a UserDelegateSnapshot event with the full state of a delegate.
+pub(crate) fn emit_user_delegate_snapshot(
+    merchant_id: u64,
+    mint: &Pubkey,
+    user_ata: &Pubkey,
+    user_delegate_account: &Account<UserDelegateState>,
+) {
+    emit!(UserDelegateSnapshot {
         merchant_id,
-        mint: ctx.accounts.mint.key(),
-        user_ata: ctx.accounts.user_token_account.key(),
-        user_delegate: ctx.accounts.user_delegate_account.key(),
+        mint: *mint,
+        user_ata: *user_ata,
+        user_delegate: user_delegate_account.key(),
+        per_transfer_limit: user_delegate_account.per_transfer_limit,
+        period_transfer_limit: user_delegate_account.period_transfer_limit,
+        period_transferred_amount: user_delegate_account.period_transferred_amount,
+        period_timestamp_last_reset: user_delegate_account.period_timestamp_last_reset,
+        transfer_limit_period_seconds: user_delegate_account.transfer_limit_period_seconds,
+        slot_last_transferred: user_delegate_account.slot_last_transferred,
+        min_slots_between_debits: user_delegate_account.min_slots_between_debits,
+        allow_alternate_destination: user_delegate_account.allow_alternate_destination,
+        min_transfer_amount: user_delegate_account.min_transfer_amount,
+        total_transferred: user_delegate_account.total_transferred,
+        debit_nonce: user_delegate_account.debit_nonce,
+        valid_from: user_delegate_account.valid_from,
+        expires_at: user_delegate_account.expires_at,
+        limit_mode: user_delegate_account.limit_mode,
+        strict_boundary: user_delegate_account.strict_boundary,
+        anchor_to_epoch: user_delegate_account.anchor_to_epoch,
+        last_period_index: user_delegate_account.last_period_index,
+        restricted_destination: user_delegate_account.restricted_destination,
+        active_after: user_delegate_account.active_after,
+        timestamp_last_transferred: user_delegate_account.timestamp_last_transferred,
     });
+}
 
+/// Reject a zero period, which would start a new period on every debit and disable the period
+/// limit, and a period limit below the per-transfer limit.
+pub(crate) fn validate_delegate_limits(
+    per_transfer_limit: u64,
+    period_transfer_limit: u64,
+    transfer_limit_period: u32,
+) -> Result<()> {
+    if transfer_limit_period == 0 {
+        return Err(ErrorCode::InvalidPeriod.into());
+    }
+    if period_transfer_limit < per_transfer_limit {
+        return Err(ErrorCode::InvalidTransferLimits.into());
+    }
     Ok(())
 }
+
+/// Whether new settings loosen an existing delegate's controls other than its limits: a
+/// shorter period, a later or removed expiry, a lifted or changed destination restriction, a
+/// different limit mode, or enabling epoch anchoring, which starts a new period.
+fn loosens_delegate_controls(
+    user_delegate_account: &UserDelegateState,
+    transfer_limit_period: u32,
+    expires_at: i64,
+    options: &UserDelegateOptions,
+) -> bool {
+    let current_expires_at = user_delegate_account.expires_at;
+    transfer_limit_period < user_delegate_account.transfer_limit_period_seconds
+        || (current_expires_at != 0 && (expires_at == 0 || expires_at > current_expires_at))
+        || (user_delegate_account.restricted_destination != Pubkey::default()
+            && options
+                .restricted_destination
+                .is_some_and(|restricted_destination| {
+                    restricted_destination.unwrap_or_default()
+                        != user_delegate_account.restricted_destination
+                }))
+        || options.limit_mode != user_delegate_account.limit_mode
+        || (options.anchor_to_epoch && !user_delegate_account.anchor_to_epoch)
+}
+
+/// Expiry to store on the delegate: the explicit expires_at, else the merchant default if
+/// requested, else 0 (never expires).
+fn resolve_expires_at(
+    merchant_config: &MerchantConfigState,
+    options: &UserDelegateOptions,
+) -> Result<i64> {
+    if options.expires_at != 0
+        || !options.use_default_expiry
+        || merchant_config.default_delegate_expiry_seconds == 0
+    {
+        return Ok(options.expires_at);
+    }
+    let now = Clock::get()?.unix_timestamp;
+    Ok(now.saturating_add(merchant_config.default_delegate_expiry_seconds as i64))
+}
@@ -0,0 +1,65 @@
+use crate::events::Heartbeat;
+use crate::instructions::initialize::STATE_SEED;
+use crate::state::BridgeCardsState;
+use crate::ID;
+use anchor_lang::prelude::*;
+
+/**
+ * Emit a timestamped heartbeat signed by the program admin.
+ *
+ * External monitors can send this periodically and watch for the event to confirm that
+ * the admin key still controls the program and that the global configuration has not
+ * changed unexpectedly. No state is modified.
+ *
+ * Security Model:
+ * - Only the program admin can emit a heartbeat
+ *
+ * Events Emitted:
+ * - Heartbeat: Every time the instruction succeeds
+ *   Fields: admin, timestamp, feature_flags, fee_bps
+ *
+ * Required Accounts:
+ * - admin: Program admin
+ * - state: Global program state PDA
+ */
+#[derive(Accounts)]
+pub struct AdminHeartbeat<'info> {
+    /// The program admin account, must match admin stored in state
+    /// Required permissions: Signer
+    #[account(constraint = admin.key() == state.admin)]
+    pub admin: Signer<'info>,
+
+    /// Global program state storing the admin, feature flags and protocol fee
+    /// Seeds: [STATE_SEED]
+    /// Required permissions: Read-only
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        seeds::program = ID
+    )]
+    pub state: Account<'info, BridgeCardsState>,
+}
+
+/**
+ * Process a heartbeat.
+ *
+ * @param ctx Context containing all required accounts
+ *
+ * Flow:
+ * 1. Verify admin signature (done via account constraints)
+ * 2. Emit event with the admin, current time and global configuration
+ *
+ * @return Result indicating success or containing an error
+ */
+pub fn handler(ctx: Context<AdminHeartbeat>) -> Result<()> {
+    let clock = Clock::get()?;
+
+    emit!(Heartbeat {
+        admin: ctx.accounts.admin.key(),
+        timestamp: clock.unix_timestamp,
+        feature_flags: ctx.accounts.state.feature_flags,
+        fee_bps: ctx.accounts.state.protocol_fee_bps,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,147 @@
+use crate::errors::ErrorCode;
+use crate::events::UserDelegateLimitsRaised;
+use crate::instructions::add_or_update_user_delegate::{
+    validate_delegate_limits, USER_DELEGATE_SEED,
+};
+use crate::pda::merchant_id_seed;
+use crate::state::{BridgeCardsState, MerchantManagerState, UserDelegateState};
+use crate::{ID, MERCHANT_MANAGER_SEED, STATE_SEED};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+/**
+ * Set a user delegate's limits with both the merchant manager's and the admin's signatures.
+ *
+ * When a merchant config enables managers_can_only_lower_limits, add_or_update_user_delegate
+ * rejects limit raises on existing delegates. This instruction is the raise path: it requires
+ * the merchant manager and the program admin to co-sign, so a compromised manager key alone
+ * cannot loosen spending controls.
+ *
+ * Security Model:
+ * - A merchant manager must sign (one of the managers in manager_state)
+ * - Program admin must sign (state.admin)
+ * - Only the per-transfer and period limits are changed, and neither can be lowered
+ * - The new limits must be consistent with each other and the delegate's current period
+ *
+ * Events Emitted:
+ * - UserDelegateLimitsRaised: When the limits are set
+ *   Fields: merchant_id, user_delegate, manager, admin, previous and new limits
+ *
+ * Common Errors:
+ * - LimitsNotRaised: A new limit is below the delegate's current one
+ * - InvalidTransferLimits: The period limit is below the per-transfer limit
+ *
+ * Required Accounts:
+ * - manager: Merchant manager
+ * - admin: Program admin
+ * - manager_state: PDA verifying manager authority
+ * - state: Global program state PDA
+ * - user_token_account: Token account the delegate controls
+ * - mint: Token mint for the delegation
+ * - user_delegate_account: PDA storing delegate parameters
+ */
+#[derive(Accounts)]
+#[instruction(merchant_id: u64)]
+pub struct AdminRaiseDelegateLimits<'info> {
+    /// Merchant manager account, must be one of the managers in manager_state
+    /// Required permissions: Signer
+    #[account(constraint = manager_state.is_manager(&manager.key()))]
+    pub manager: Signer<'info>,
+
+    /// The program admin account, must match admin stored in state
+    /// Required permissions: Signer
+    #[account(constraint = admin.key() == state.admin)]
+    pub admin: Signer<'info>,
+
+    /// PDA storing the merchant manager's authorization
+    /// Seeds: [MERCHANT_MANAGER_SEED, merchant_id]
+    /// Required permissions: Read-only
+    #[account(
+        seeds = [MERCHANT_MANAGER_SEED, merchant_id_seed(merchant_id).as_ref()],
+        bump = manager_state.bump,
+        seeds::program = ID
+    )]
+    pub manager_state: Account<'info, MerchantManagerState>,
+
+    /// Global program state storing the admin public key
+    /// Seeds: [STATE_SEED]
+    /// Required permissions: Read-only
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        seeds::program = ID
+    )]
+    pub state: Account<'info, BridgeCardsState>,
+
+    /// Token account controlled by the delegate
+    /// Required permissions: Read-only
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Mint of the tokens that can be transferred by this delegate
+    /// Required permissions: Read-only
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// PDA storing the delegate's transfer limits and state
+    /// Seeds: [USER_DELEGATE_SEED, merchant_id, mint, user_token_account]
+    /// Required permissions: Mutable
+    #[account(mut,
+        seeds = [USER_DELEGATE_SEED, merchant_id_seed(merchant_id).as_ref(), mint.key().as_ref(), user_token_account.key().as_ref()],
+        bump = user_delegate_account.bump,
+        seeds::program = ID
+    )]
+    pub user_delegate_account: Account<'info, UserDelegateState>,
+}
+
+/**
+ * Process an admin co-signed update of a delegate's limits.
+ *
+ * @param ctx Context containing all required accounts
+ * @param merchant_id Unique identifier for the merchant
+ * @param per_transfer_limit New maximum amount allowed in a single transfer
+ * @param period_transfer_limit New maximum amount allowed within the time period
+ *
+ * Flow:
+ * 1. Verify manager and admin signatures (done via account constraints)
+ * 2. Reject lowered and inconsistent limits
+ * 3. Update the delegate's limits
+ * 4. Emit event recording both signers and the limit change
+ *
+ * @return Result indicating success or containing an error
+ */
+pub fn handler(
+    ctx: Context<AdminRaiseDelegateLimits>,
+    merchant_id: u64,
+    per_transfer_limit: u64,
+    period_transfer_limit: u64,
+) -> Result<()> {
+    let user_delegate_account = &mut ctx.accounts.user_delegate_account;
+    let previous_per_transfer_limit = user_delegate_account.per_transfer_limit;
+    let previous_period_transfer_limit = user_delegate_account.period_transfer_limit;
+    if per_transfer_limit < previous_per_transfer_limit
+        || period_transfer_limit < previous_period_transfer_limit
+    {
+        return Err(ErrorCode::LimitsNotRaised.into());
+    }
+    validate_delegate_limits(
+        per_transfer_limit,
+        period_transfer_limit,
+        user_delegate_account.transfer_limit_period_seconds,
+    )?;
+
+    user_delegate_account.per_transfer_limit = per_transfer_limit;
+    user_delegate_account.period_transfer_limit = period_transfer_limit;
+
+    // Emit event for indexing and notifications
+    emit!(UserDelegateLimitsRaised {
+        merchant_id,
+        user_delegate: ctx.accounts.user_delegate_account.key(),
+        manager: ctx.accounts.manager.key(),
+        admin: ctx.accounts.admin.key(),
+        previous_per_transfer_limit,
+        per_transfer_limit,
+        previous_period_transfer_limit,
+        period_transfer_limit,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,125 @@
+use crate::errors::ErrorCode;
+use crate::events::EmergencyRecovery;
+use crate::state::BridgeCardsState;
+use crate::{ID, STATE_SEED};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+/**
+ * Move a user's tokens to a recovery token account in an emergency.
+ *
+ * When a user's delegate is compromised, the tokens it can debit are at risk until the user
+ * revokes the approval. This instruction lets the admin move them to a safe account right
+ * away, with the user's consent. The transfer is signed by the user as owner of the token
+ * account, so it bypasses every delegate limit and doesn't touch any delegate state.
+ *
+ * Security Model:
+ * - Program admin must sign (state.admin)
+ * - The owner of the user token account must co-sign, so the admin alone can't move user funds
+ * - The recovery token account must hold the same mint and differ from the user token account
+ * - At most the user's balance is moved
+ *
+ * Events Emitted:
+ * - EmergencyRecovery: When the tokens are moved
+ *   Fields: admin, owner, user_ata, recovery_ata, mint, amount
+ *
+ * Required Accounts:
+ * - admin: Program admin
+ * - owner: Owner of the user token account
+ * - state: Global program state PDA
+ * - user_token_account: Token account the tokens are moved from
+ * - recovery_token_account: Token account the tokens are moved to
+ * - mint: Token mint of both token accounts
+ * - token_program: Token program of the mint
+ */
+#[derive(Accounts)]
+pub struct AdminRecoverUserFunds<'info> {
+    /// The program admin account, must match admin stored in state
+    /// Required permissions: Signer
+    #[account(constraint = admin.key() == state.admin)]
+    pub admin: Signer<'info>,
+
+    /// Owner of the user token account, consenting to the recovery
+    /// Required permissions: Signer
+    #[account(constraint = owner.key() == user_token_account.owner)]
+    pub owner: Signer<'info>,
+
+    /// Global program state storing the admin public key
+    /// Seeds: [STATE_SEED]
+    /// Required permissions: Read-only
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        seeds::program = ID
+    )]
+    pub state: Account<'info, BridgeCardsState>,
+
+    /// Token account the tokens are moved from, owned by the owner
+    /// Required permissions: Mutable
+    #[account(
+        mut,
+        constraint = user_token_account.mint == mint.key() @ ErrorCode::MismatchedMint
+    )]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Token account receiving the recovered tokens
+    /// Required permissions: Mutable
+    #[account(
+        mut,
+        constraint = recovery_token_account.mint == mint.key() @ ErrorCode::MismatchedMint,
+        constraint = recovery_token_account.key() != user_token_account.key() @ ErrorCode::SelfTransfer
+    )]
+    pub recovery_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Mint of the recovered tokens
+    /// Required permissions: Read-only
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Token program of the mint (SPL Token or Token-2022)
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/**
+ * Process an emergency recovery of a user's tokens.
+ *
+ * @param ctx Context containing all required accounts
+ * @param amount Maximum number of tokens to move; u64::MAX moves the whole balance
+ *
+ * Flow:
+ * 1. Verify admin and owner signatures and the token accounts (done via account constraints)
+ * 2. Cap the amount at the user's balance
+ * 3. Transfer the tokens with the owner's signature
+ * 4. Emit event recording both signers and the amount moved
+ *
+ * @return Result indicating success or containing an error
+ */
+pub fn handler(ctx: Context<AdminRecoverUserFunds>, amount: u64) -> Result<()> {
+    let accounts = ctx.accounts;
+    let amount = amount.min(accounts.user_token_account.amount);
+
+    token_interface::transfer_checked(
+        CpiContext::new(
+            accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: accounts.user_token_account.to_account_info(),
+                to: accounts.recovery_token_account.to_account_info(),
+                mint: accounts.mint.to_account_info(),
+                authority: accounts.owner.to_account_info(),
+            },
+        ),
+        amount,
+        accounts.mint.decimals,
+    )?;
+
+    // Emit event for indexing and notifications
+    emit!(EmergencyRecovery {
+        admin: accounts.admin.key(),
+        owner: accounts.owner.key(),
+        user_ata: accounts.user_token_account.key(),
+        recovery_ata: accounts.recovery_token_account.key(),
+        mint: accounts.mint.key(),
+        amount,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,246 @@
+use crate::errors::ErrorCode;
+use crate::events::MerchantDestinationAddedOrUpdated;
+use crate::instructions::add_or_update_merchant_destination::MERCHANT_DESTINATION_SEED;
+use crate::instructions::initialize::STATE_SEED;
+use crate::pda::{find_merchant_destination, merchant_id_seed};
+use crate::state::{
+    AccountSpace, BridgeCardsState, MerchantDestinationState, MerchantRegistryState,
+    CURRENT_VERSION,
+};
+use crate::ID;
+use crate::MERCHANT_REGISTRY_SEED;
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+/**
+ * Add or update several allowed destination token accounts for a merchant in one transaction.
+ *
+ * This is the bulk form of add_or_update_merchant_destination for onboarding merchants with
+ * many destinations. Every destination is validated before any state is written, so a single
+ * bad entry refuses the whole batch. All destinations receive the same allowed status; the
+ * primary status of existing destinations is kept and new destinations are not primary.
+ *
+ * Account Creation:
+ * - Creates each destination's state PDA if it doesn't exist
+ * - PDA is derived using [MERCHANT_DESTINATION_SEED, merchant_id, mint, destination_token_account]
+ * - Funded by the payer account
+ *
+ * Security Model:
+ * - Only the program admin can add/update destinations
+ * - Each destination is validated to use the specified mint
+ * - Each state account must be the destination's PDA
+ * - No destination may appear twice
+ *
+ * Remaining Accounts (one pair per destination, in order):
+ * - destination_token_account: Token account to be allowlisted (Read-only)
+ * - destination_state: PDA storing the destination's authorization (Mutable)
+ *
+ * Events Emitted:
+ * - MerchantDestinationAddedOrUpdated: One per destination
+ *   Fields: merchant_id, mint, destination, state_pda, previous_state, new_state, is_primary
+ *
+ * Required Accounts:
+ * - admin: Program admin who can update destinations
+ * - payer: Account paying for PDA creation/rent
+ * - state: Global program state storing admin pubkey
+ * - mint: Token mint of every destination account
+ * - system_program: Required for account creation
+ * - merchant_registry: Registry counting the merchant's accounts (optional)
+ */
+#[derive(Accounts)]
+#[instruction(merchant_id: u64)]
+pub struct BatchAddDestinations<'info> {
+    /// The program admin account, must match admin stored in state
+    /// Required permissions: Signer
+    #[account(constraint = admin.key() == state.admin)]
+    pub admin: Signer<'info>,
+
+    /// Account that will pay for PDA creation and rent
+    /// Required permissions: Signer, Mutable (for rent payment)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Global program state storing the admin public key
+    /// Seeds: [STATE_SEED]
+    /// Required permissions: Read-only
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        seeds::program = ID
+    )]
+    pub state: Account<'info, BridgeCardsState>,
+
+    /// Mint of every destination token account
+    /// Used for PDA derivation and account validation
+    /// Required permissions: None (read-only validation)
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Required for account creation
+    pub system_program: Program<'info, System>,
+
+    /// Registry counting the merchant's accounts, incremented when the destination state is created
+    /// Seeds: [MERCHANT_REGISTRY_SEED, merchant_id]
+    /// Required permissions: Mutable (counter update)
+    #[account(mut,
+        seeds = [MERCHANT_REGISTRY_SEED, merchant_id_seed(merchant_id).as_ref()],
+        bump = merchant_registry.bump,
+        seeds::program = ID
+    )]
+    pub merchant_registry: Option<Account<'info, MerchantRegistryState>>,
+}
+
+/**
+ * Process the addition or update of several merchant destinations.
+ *
+ * @param ctx Context containing all required accounts; remaining accounts are one
+ * (destination token account, destination state) pair per destination
+ * @param merchant_id Unique identifier for the merchant
+ * @param destination_allowed Whether the destinations should be allowed to receive funds
+ *
+ * Flow:
+ * 1. Verify admin signature (done via account constraints)
+ * 2. Validate every destination token account and state PDA
+ * 3. Create missing state PDAs, counting them in the merchant registry if passed, and update
+ *    each with the allowed status
+ * 4. Emit one event per destination
+ *
+ * Error Handling:
+ * - Returns InvalidBatchAccounts if the remaining accounts are not pairs, or a destination
+ *   is repeated
+ * - Returns MismatchedMint if a destination token account does not use the mint
+ * - Returns InvalidPda if a state account is not its destination's PDA
+ *
+ * @return Result indicating success or containing an error
+ */
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, BatchAddDestinations<'info>>,
+    merchant_id: u64,
+    destination_allowed: bool,
+) -> Result<()> {
+    let remaining_accounts = ctx.remaining_accounts;
+    if remaining_accounts.is_empty() || remaining_accounts.len() % 2 != 0 {
+        return Err(ErrorCode::InvalidBatchAccounts.into());
+    }
+
+    // Validate every destination before any state is written
+    let mint = ctx.accounts.mint.key();
+    let mut bumps = Vec::with_capacity(remaining_accounts.len() / 2);
+    for (index, pair) in remaining_accounts.chunks(2).enumerate() {
+        let (destination_info, destination_state_info) = (&pair[0], &pair[1]);
+        if remaining_accounts[..index * 2]
+            .chunks(2)
+            .any(|previous| previous[0].key == destination_info.key)
+        {
+            return Err(ErrorCode::InvalidBatchAccounts.into());
+        }
+        let destination_token_account =
+            InterfaceAccount::<TokenAccount>::try_from(destination_info)?;
+        if destination_token_account.mint != mint {
+            return Err(ErrorCode::MismatchedMint.into());
+        }
+
+        let (destination_state_pda, bump) =
+            find_merchant_destination(merchant_id, &mint, destination_info.key);
+        if destination_state_info.key() != destination_state_pda {
+            return Err(ErrorCode::InvalidPda.into());
+        }
+        bumps.push(bump);
+    }
+
+    let merchant_id_bytes = merchant_id_seed(merchant_id);
+    for (pair, bump) in remaining_accounts.chunks(2).zip(bumps) {
+        let (destination_info, destination_state_info) = (&pair[0], &pair[1]);
+        let existing = MerchantDestinationState::load_optional(destination_state_info)?;
+        if existing.is_none() {
+            let seeds = [
+                MERCHANT_DESTINATION_SEED,
+                merchant_id_bytes.as_ref(),
+                mint.as_ref(),
+                destination_info.key.as_ref(),
+                &[bump],
+            ];
+            create_destination_state(ctx.accounts, destination_state_info, &seeds)?;
+            if let Some(merchant_registry) = &mut ctx.accounts.merchant_registry {
+                merchant_registry.destination_count =
+                    merchant_registry.destination_count.saturating_add(1);
+            }
+        }
+
+        let previous_state = existing.as_ref().is_some_and(|state| state.allowed);
+        let is_primary = existing.as_ref().is_some_and(|state| state.is_primary);
+        // Updating the authorization keeps the record of the last debit received
+        let destination_state = MerchantDestinationState {
+            version: CURRENT_VERSION,
+            allowed: destination_allowed,
+            is_primary,
+            bump,
+            last_received_amount: existing
+                .as_ref()
+                .map_or(0, |state| state.last_received_amount),
+            last_received_timestamp: existing
+                .as_ref()
+                .map_or(0, |state| state.last_received_timestamp),
+        };
+        destination_state.try_serialize(&mut &mut destination_state_info.data.borrow_mut()[..])?;
+
+        // Emit event for indexing and notifications
+        emit!(MerchantDestinationAddedOrUpdated {
+            merchant_id,
+            mint,
+            destination: destination_info.key(),
+            state_pda: destination_state_info.key(),
+            previous_state,
+            new_state: destination_allowed,
+            is_primary,
+        });
+    }
+
+    Ok(())
+}
+
+/// Create a destination state account at its PDA, funding any missing rent from the payer
+fn create_destination_state<'info>(
+    accounts: &BatchAddDestinations<'info>,
+    destination_state_info: &AccountInfo<'info>,
+    seeds: &[&[u8]],
+) -> Result<()> {
+    let system_program = accounts.system_program.to_account_info();
+    let space = MerchantDestinationState::SPACE;
+    let rent = Rent::get()?.minimum_balance(space);
+
+    // Lamports sent to the address beforehand must not prevent its creation
+    let shortfall = rent.saturating_sub(destination_state_info.lamports());
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: accounts.payer.to_account_info(),
+                    to: destination_state_info.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    system_program::allocate(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            system_program::Allocate {
+                account_to_allocate: destination_state_info.clone(),
+            },
+            &[seeds],
+        ),
+        space as u64,
+    )?;
+    system_program::assign(
+        CpiContext::new_with_signer(
+            system_program,
+            system_program::Assign {
+                account_to_assign: destination_state_info.clone(),
+            },
+            &[seeds],
+        ),
+        &ID,
+    )
+}
@@ -0,0 +1,414 @@
+use crate::errors::ErrorCode;
+use crate::events::{UserDebited, UserDelegatePeriodReset};
+use crate::instructions::add_or_update_user_delegate::USER_DELEGATE_SEED;
+use crate::instructions::debit_user::{
+    check_debit_destination, check_debit_token_accounts, check_merchant_debit, mint_decimals,
+    transfer_checked_verified, verify_delegate_approval, verify_fee_token_account,
+};
+use crate::pda::find_merchant_destination;
+use crate::pda::merchant_id_seed;
+use crate::state::{
+    feature_flags, BridgeCardsState, DebitPolicy, MerchantConfigState, MerchantDebitorState,
+    MerchantDestinationState, MerchantSpendTrackerState, UserDelegateState,
+};
+use crate::ID;
+use crate::{
+    MERCHANT_CONFIG_SEED, MERCHANT_DEBITOR_SEED, MERCHANT_MINT_SEED, MERCHANT_SPEND_SEED,
+    STATE_SEED,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+/**
+ * Debit a user's token account once and split the amount across several merchant destinations.
+ *
+ * This instruction lets a merchant settle a single authorization into multiple allowed
+ * destinations atomically (e.g. principal and fee accounts). The legs are treated as one
+ * debit: their total is checked against the delegate's per-transfer and period limits, and
+ * every destination is validated before any tokens move, so either every leg succeeds or
+ * the whole instruction reverts.
+ *
+ * Security Checks:
+ * - All debit_user checks apply, with the delegate, debitor and merchant spend tracker
+ *   limits applied to the sum of the amounts
+ * - Every destination must be authorized for the merchant and mint, under the same
+ *   destination policy as debit_user
+ * - Every destination must hold the debited mint and belong to the same token program
+ * - The debit splits into at most the merchant's max_splits legs, none of them zero
+ * - Each leg pays the protocol fee on its amount, sent in one transfer to the fee token account
+ * - The updated delegate, debitor and spend tracker usage, and each destination's receipt,
+ *   is written to the accounts before any transfer CPI
+ *
+ * Remaining Accounts (one pair per amount, in order):
+ * - destination_token_account: Token account receiving the leg (Mutable)
+ * - destination_state: PDA [MERCHANT_DESTINATION_SEED, merchant_id, mint, destination_token_account]
+ *   (Mutable, records the leg received)
+ *
+ * Events Emitted:
+ * - UserDebited: One per leg, all sharing the batch's debit_nonce
+ *
+ * Common Errors:
+ * - InvalidBatchAccounts: Remaining accounts are not one pair per amount
+ * - TooManySplits: More amounts than the merchant's max_splits
+ * - ZeroSplitAmount: One of the amounts is zero
+ * - DestinationNotAllowed: A destination is not authorized for the merchant
+ * - InvalidPda: A destination state is not the PDA of its destination token account
+ * - ExceedsMaxTransferLimit: The total exceeds the per-transfer limit
+ * - ExceedsTransferLimitPerPeriod: The total exceeds the remaining period limit
+ * - ExceedsMerchantPeriodLimit: The total exceeds the merchant's remaining period limit
+ * - InsufficientDelegatedAmount: The approved amount is below the total
+ * - TokenAccountFrozen: The user token account or a destination is frozen
+ * - SelfTransfer: A destination is the user token account itself
+ * - DestinationNotAllowedForDelegate: The delegate is restricted to a single destination and
+ *   a leg targets another one
+ * - DelegateNotYetActive: The delegate was created less than its cooldown ago
+ * - ClockAnomaly: The clock timestamp is inconsistent with the slots elapsed since the
+ *   delegate's last debit
+ */
+#[derive(Accounts)]
+#[instruction(merchant_id: u64)]
+pub struct BatchDebitUser<'info> {
+    /// Account that pays for the transaction fees
+    /// CHECK: Can be any account with sufficient SOL
+    pub payer: Signer<'info>,
+
+    /// PDA that stores the delegate's transfer limits and state
+    /// This account acts as the authority for the user's token account
+    ///
+    /// Seeds: [USER_DELEGATE_SEED, merchant_id, mint, user_token_account]
+    /// Required permissions: Mutable (updates period tracking)
+    #[account(mut,
+        seeds = [USER_DELEGATE_SEED, merchant_id_seed(merchant_id).as_ref(), mint.key().as_ref(), user_token_account.key().as_ref()],
+        bump = user_delegate_account.bump,
+        seeds::program = ID
+    )]
+    pub user_delegate_account: Account<'info, UserDelegateState>,
+
+    /// Account initiating the debit operation
+    /// Must be an authorized debitor for the merchant and mint
+    /// Required permissions: Signer
+    pub debitor: Signer<'info>,
+
+    /// PDA storing the debitor's authorization state and limits for this merchant and mint
+    /// Seeds: [MERCHANT_DEBITOR_SEED, merchant_id, mint, debitor]
+    /// Required permissions: Mutable (updates the debitor's daily tracking)
+    /// CHECK: Address is verified by seeds; the debitor is not authorized for the mint if it
+    /// has not been created
+    #[account(mut, seeds = [MERCHANT_DEBITOR_SEED, merchant_id_seed(merchant_id).as_ref(), mint.key().as_ref(), debitor.key().as_ref()], bump, seeds::program = ID)]
+    pub debitor_state: UncheckedAccount<'info>,
+
+    /// PDA storing the merchant configuration
+    /// Seeds: [MERCHANT_CONFIG_SEED, merchant_id]
+    /// Required permissions: Read-only
+    /// CHECK: Address is verified by seeds; default settings apply if it has not been created
+    #[account(
+        seeds = [MERCHANT_CONFIG_SEED, merchant_id_seed(merchant_id).as_ref()],
+        bump,
+        seeds::program = ID
+    )]
+    pub merchant_config: UncheckedAccount<'info>,
+
+    /// Global program state storing the feature flags
+    /// Seeds: [STATE_SEED]
+    /// Required permissions: Read-only
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        seeds::program = ID
+    )]
+    pub state: Account<'info, BridgeCardsState>,
+
+    /// User's token account from which tokens will be transferred
+    /// Required permissions: Mutable
+    #[account(mut, constraint = user_token_account.mint.key() == mint.key() @ ErrorCode::MismatchedMint)]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Owner of the user's token account
+    /// Required permissions: Read-only
+    /// CHECK: Must be the owner recorded in the user token account
+    #[account(address = user_token_account.owner)]
+    pub user_owner: UncheckedAccount<'info>,
+
+    /// The mint of the tokens being transferred
+    /// Required permissions: Read-only
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Required Solana system programs
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// PDA tracking the merchant's spend across all of its delegates
+    /// Seeds: [MERCHANT_SPEND_SEED, merchant_id]
+    /// Required permissions: Mutable (updates period tracking)
+    /// CHECK: Address is verified by seeds; no merchant-wide limit applies if it has not been
+    /// created
+    #[account(mut,
+        seeds = [MERCHANT_SPEND_SEED, merchant_id_seed(merchant_id).as_ref()],
+        bump,
+        seeds::program = ID
+    )]
+    pub merchant_spend_tracker: UncheckedAccount<'info>,
+
+    /// Token account of the protocol fee owner receiving the protocol fee
+    /// Required when the program's protocol fee is set
+    /// Required permissions: Mutable
+    #[account(mut, constraint = fee_token_account.mint == mint.key() @ ErrorCode::MismatchedMint)]
+    pub fee_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// PDA allowlisting the mint for the merchant
+    /// Seeds: [MERCHANT_MINT_SEED, merchant_id, mint]
+    /// Required when the merchant config enforces the mint allowlist
+    /// Required permissions: Read-only
+    /// CHECK: Address is verified by seeds; the mint is not allowlisted if it has not been created
+    #[account(
+        seeds = [MERCHANT_MINT_SEED, merchant_id_seed(merchant_id).as_ref(), mint.key().as_ref()],
+        bump,
+        seeds::program = ID
+    )]
+    pub merchant_mint: Option<UncheckedAccount<'info>>,
+}
+
+/**
+ * Process a debit split across several destinations.
+ *
+ * @param ctx The instruction context containing all required accounts
+ * @param merchant_id Unique identifier for the merchant
+ * @param amounts Number of tokens to transfer to each destination, in remaining-account order
+ *
+ * Flow:
+ * 1. Validate the split count and amounts, every destination and the merchant config checks
+ * 2. Check the total against the transfer limits and update period tracking
+ * 3. Record each leg's net amount and time on its destination state, if it exists
+ * 4. Transfer each leg using the delegate PDA as authority and emit UserDebited
+ *
+ * @return Result indicating success or containing an error
+ */
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, BatchDebitUser<'info>>,
+    merchant_id: u64,
+    amounts: Vec<u64>,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+    let mut debitor_state = MerchantDebitorState::load_authorized(&accounts.debitor_state)?;
+    if accounts.state.has_feature_flag(feature_flags::PAUSED) {
+        return Err(ErrorCode::ProgramPaused.into());
+    }
+    if amounts.is_empty() || ctx.remaining_accounts.len() != amounts.len() * 2 {
+        return Err(ErrorCode::InvalidBatchAccounts.into());
+    }
+
+    // transfer_checked cannot move tokens between accounts of different token programs
+    let token_program = accounts.token_program.key();
+    if accounts.user_token_account.to_account_info().owner != &token_program {
+        return Err(ErrorCode::CrossProgramTransferNotAllowed.into());
+    }
+    if accounts.user_token_account.is_frozen() {
+        return Err(ErrorCode::TokenAccountFrozen.into());
+    }
+
+    let merchant_config = MerchantConfigState::load_or_default(&accounts.merchant_config)?;
+    if amounts.len() > merchant_config.max_splits() {
+        return Err(ErrorCode::TooManySplits.into());
+    }
+    // A zero leg moves nothing and would only emit an empty UserDebited event
+    if amounts.contains(&0) {
+        return Err(ErrorCode::ZeroSplitAmount.into());
+    }
+    check_merchant_debit(
+        &merchant_config,
+        accounts.merchant_mint.as_deref(),
+        &accounts.user_owner,
+    )?;
+
+    // Validate every destination before any tokens move
+    let mint = accounts.mint.key();
+    let mut destinations = Vec::with_capacity(amounts.len());
+    for pair in ctx.remaining_accounts.chunks(2) {
+        let (destination_info, destination_state_info) = (&pair[0], &pair[1]);
+        if destination_info.owner != &token_program {
+            return Err(ErrorCode::CrossProgramTransferNotAllowed.into());
+        }
+        let destination_token_account =
+            InterfaceAccount::<TokenAccount>::try_from(destination_info)?;
+        if destination_token_account.mint != mint {
+            return Err(ErrorCode::MismatchedMint.into());
+        }
+        check_debit_token_accounts(
+            &token_program,
+            &accounts.user_token_account,
+            &destination_token_account,
+        )?;
+
+        let (destination_state_pda, _) =
+            find_merchant_destination(merchant_id, &mint, destination_info.key);
+        if destination_state_info.key() != destination_state_pda {
+            return Err(ErrorCode::InvalidPda.into());
+        }
+        let destination_state = MerchantDestinationState::load_optional(destination_state_info)?;
+        check_debit_destination(
+            &merchant_config,
+            &accounts.user_delegate_account,
+            destination_state.as_ref(),
+            &destination_token_account,
+        )?;
+        destinations.push((
+            destination_token_account,
+            destination_state_info,
+            destination_state,
+        ));
+    }
+
+    // The legs form one debit, so their total is what the limits apply to
+    let total = amounts
+        .iter()
+        .try_fold(0u64, |total, amount| total.checked_add(*amount))
+        .ok_or(ErrorCode::ExceedsMaxTransferLimit)?;
+    // Each leg pays its own protocol fee, collected in a single transfer
+    let protocol_fees = amounts
+        .iter()
+        .map(|amount| accounts.state.protocol_fee(*amount))
+        .collect::<Result<Vec<u64>>>()?;
+    let total_protocol_fee: u64 = protocol_fees.iter().sum();
+    if total_protocol_fee > 0 {
+        verify_fee_token_account(
+            &accounts.state,
+            accounts.fee_token_account.as_ref(),
+            &token_program,
+        )?;
+    }
+    let policy = DebitPolicy::resolve(&merchant_config, &accounts.user_delegate_account);
+    let clock = Clock::get()?;
+    accounts
+        .user_delegate_account
+        .check_active(clock.unix_timestamp)?;
+    if accounts
+        .state
+        .has_feature_flag(feature_flags::CLOCK_ANOMALY_CHECKS)
+    {
+        accounts.user_delegate_account.check_clock(
+            clock.unix_timestamp,
+            clock.slot,
+            merchant_config.max_clock_skew_seconds(),
+        )?;
+    }
+    let period_reset = accounts
+        .user_delegate_account
+        .validate_debit_and_update_with_policy(
+            total,
+            clock.unix_timestamp as u64,
+            clock.slot,
+            &policy,
+        )?;
+    debitor_state.record_debit(total, clock.unix_timestamp as u64)?;
+    let mut merchant_spend_tracker =
+        MerchantSpendTrackerState::load_optional(&accounts.merchant_spend_tracker)?;
+    if let Some(merchant_spend_tracker) = &mut merchant_spend_tracker {
+        merchant_spend_tracker.record_debit(total, clock.unix_timestamp as u64)?;
+    }
+    if period_reset {
+        emit!(UserDelegatePeriodReset {
+            merchant_id,
+            user_delegate: accounts.user_delegate_account.key(),
+            period_timestamp_last_reset: accounts.user_delegate_account.period_timestamp_last_reset,
+        });
+    }
+
+    verify_delegate_approval(
+        &accounts.user_token_account,
+        &accounts.user_delegate_account.key(),
+        total,
+    )?;
+
+    // Write the recorded usage before the transfer CPIs, as debit_user does
+    accounts.user_delegate_account.exit(&ID)?;
+    debitor_state.store(&accounts.debitor_state)?;
+    if let Some(merchant_spend_tracker) = &merchant_spend_tracker {
+        merchant_spend_tracker.store(&accounts.merchant_spend_tracker)?;
+    }
+    // Each destination receives its leg net of the protocol fee
+    for ((_, destination_state_info, destination_state), (amount, protocol_fee)) in destinations
+        .iter_mut()
+        .zip(amounts.iter().zip(&protocol_fees))
+    {
+        if let Some(destination_state) = destination_state {
+            destination_state.record_receipt(amount - protocol_fee, clock.unix_timestamp);
+            destination_state.store(destination_state_info)?;
+        }
+    }
+
+    // Derive the PDA signer seeds for the delegate account
+    let merchant_id_bytes = merchant_id_seed(merchant_id);
+    let seeds = [
+        USER_DELEGATE_SEED,
+        merchant_id_bytes.as_ref(),
+        mint.as_ref(),
+        accounts.user_token_account.to_account_info().key.as_ref(),
+        &[accounts.user_delegate_account.bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    // Read the decimals once so every destination and the fee leg use the same validated value
+    let decimals = mint_decimals(&accounts.mint.to_account_info())?;
+    let post_condition_epoch = accounts
+        .state
+        .has_feature_flag(feature_flags::POST_CONDITION_CHECKS)
+        .then_some(clock.epoch);
+    for (((destination_token_account, _, _), amount), protocol_fee) in
+        destinations.iter().zip(amounts).zip(protocol_fees)
+    {
+        transfer_checked_verified(
+            CpiContext::new_with_signer(
+                accounts.token_program.to_account_info(),
+                token_interface::TransferChecked {
+                    from: accounts.user_token_account.to_account_info(),
+                    to: destination_token_account.to_account_info(),
+                    mint: accounts.mint.to_account_info(),
+                    authority: accounts.user_delegate_account.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount - protocol_fee,
+            decimals,
+            post_condition_epoch,
+        )?;
+
+        emit!(UserDebited {
+            debitor: accounts.debitor.key(),
+            user_delegate: accounts.user_delegate_account.key(),
+            merchant_id,
+            user_ata: accounts.user_token_account.key(),
+            destination_ata: destination_token_account.key(),
+            mint,
+            amount,
+            slot: clock.slot,
+            timestamp: clock.unix_timestamp,
+            debit_nonce: accounts.user_delegate_account.debit_nonce,
+            protocol_fee,
+            memo: Vec::new(),
+        });
+    }
+    if let Some(fee_token_account) = accounts
+        .fee_token_account
+        .as_ref()
+        .filter(|_| total_protocol_fee > 0)
+    {
+        transfer_checked_verified(
+            CpiContext::new_with_signer(
+                accounts.token_program.to_account_info(),
+                token_interface::TransferChecked {
+                    from: accounts.user_token_account.to_account_info(),
+                    to: fee_token_account.to_account_info(),
+                    mint: accounts.mint.to_account_info(),
+                    authority: accounts.user_delegate_account.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            total_protocol_fee,
+            decimals,
+            post_condition_epoch,
+        )?;
+    }
+
+    Ok(())
+}
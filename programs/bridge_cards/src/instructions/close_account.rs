@@ -1,5 +1,9 @@
 use crate::{errors::ErrorCode, events::AccountClosed, state::BridgeCardsState, ID, STATE_SEED};
-use anchor_lang::{prelude::*, solana_program::system_program};
+use anchor_lang::{
+    prelude::*,
+    solana_program::{program_option::COption, system_program},
+};
+use anchor_spl::token_interface::TokenAccount;
 
 /**
  * Close a program-derived account and reclaim its rent.
@@ -12,21 +16,27 @@ use anchor_lang::{prelude::*, solana_program::system_program};
  * - Verifies the account is a valid PDA of this program
  * - Checks that the account is not the program state account
  * - Validates PDA derivation using provided seeds
+ * - Uses the caller-supplied bump when given, skipping the costly canonical bump search
+ * - If a user token account is passed, checks it does not still approve the account as its
+ *   delegate (optional, so generic closes are unaffected)
  *
  * Security Model:
  * - Only the program admin can close accounts
  * - Admin authority verified through state PDA
  * - Prevents closing of critical program state
+ * - Refuses to close an account into itself
+ * - The admin chooses the rent recipient, e.g. the merchant that originally paid the rent
  * - Atomic closure and rent recovery
  *
  * Rent Recovery:
- * - Transfers all lamports from closed account to payer
+ * - Transfers all lamports from closed account to rent_recipient if passed, otherwise to payer
+ * - The balance is read after the data is released, so lamports topped up beyond the
+ *   rent-exempt minimum are recovered too
  * - Account data is zeroed by runtime after instruction
- * - Rent exempt SOL is fully recovered
  *
  * Events Emitted:
  * - AccountClosed: When an account is successfully closed
- *   Fields: account (pubkey of closed account)
+ *   Fields: account (pubkey of closed account), lamports_recovered, recipient
  *
  * Common Use Cases:
  * - Cleaning up unused merchant destinations
@@ -36,11 +46,15 @@ use anchor_lang::{prelude::*, solana_program::system_program};
  *
  * Required Accounts:
  * - admin: Program admin with closure authority
- * - payer: Account to receive recovered rent
+ * - payer: Account to receive recovered rent, unless rent_recipient is passed
  * - account_to_close: PDA to be closed
  * - state: Global program state PDA
+ * - user_token_account: Token account a closed user delegate controlled (optional)
+ * - rent_recipient: Account to receive recovered rent instead of the payer (optional)
  *
- * @param input_seeds Seeds used to derive and validate the PDA
+ * @param input_seeds Seeds used to derive and validate the PDA, as returned by the pda::*_seeds
+ *   helpers
+ * @param bump Bump of the PDA, or None to search for the canonical bump
  */
 #[derive(Accounts)]
 pub struct CloseAccount<'info> {
@@ -55,9 +69,10 @@ pub struct CloseAccount<'info> {
     pub payer: Signer<'info>,
 
     /// Program-derived account to be closed
+    /// Must not be the payer
     /// Required permissions: Mutable (for closure)
     /// CHECK: Account validity is verified through PDA derivation
-    #[account(mut)]
+    #[account(mut, constraint = account_to_close.key() != payer.key() @ ErrorCode::SelfClose)]
     pub account_to_close: AccountInfo<'info>,
 
     /// Global program state storing the admin public key
@@ -65,71 +80,246 @@ pub struct CloseAccount<'info> {
     /// Required permissions: Read-only
     #[account(
         seeds = [STATE_SEED],
-        bump,
+        bump = state.bump,
         seeds::program = ID
     )]
     pub state: Account<'info, BridgeCardsState>,
+
+    /// Token account controlled by the user delegate being closed, if any
+    /// Required permissions: Read-only
+    pub user_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Account receiving the recovered rent instead of the payer, e.g. the original rent payer
+    /// Must not be the account being closed
+    /// Required permissions: Mutable (for rent receipt)
+    /// CHECK: Any account can receive lamports
+    #[account(
+        mut,
+        constraint = rent_recipient.key() != account_to_close.key() @ ErrorCode::SelfClose
+    )]
+    pub rent_recipient: Option<UncheckedAccount<'info>>,
 }
 
 /**
  * Process the closure of a program-derived account.
  *
  * @param ctx Context containing all required accounts
- * @param input_seeds Seeds used to derive and validate the PDA being closed
+ * @param input_seeds Seeds used to derive and validate the PDA being closed (without the bump)
+ * @param bump Bump of the PDA, or None to search for the canonical bump
  *
  * Flow:
  * 1. Verify admin signature (done via account constraints)
- * 2. Validate account is a valid PDA using input seeds
- * 3. Verify account is not program state
- * 4. Transfer rent lamports to payer
- * 5. Emit closure event
+ * 2. Validate account is a valid PDA using input seeds and bump
+ * 3. Verify account is not the canonical program state PDA
+ * 4. If a user token account is passed, verify it no longer delegates to the account
+ * 5. Transfer rent lamports to the rent recipient if passed, otherwise to the payer
+ * 6. Emit closure event
  *
  * Error Handling:
  * - Returns InvalidPda if account is not a valid PDA
+ * - Returns InvalidPda if the provided bump does not derive the account
  * - Returns InvalidPda if attempting to close state account
+ * - Returns DelegateStillActive if the user token account still delegates to the account
+ * - Returns SelfClose if the account to close is the payer or the rent recipient
  *
  * @return Result indicating success or containing an error
  */
-pub fn handler(ctx: Context<CloseAccount>, input_seeds: Vec<Vec<u8>>) -> Result<()> {
+pub fn handler(
+    ctx: Context<CloseAccount>,
+    input_seeds: Vec<Vec<u8>>,
+    bump: Option<u8>,
+) -> Result<()> {
     let account_to_close = &ctx.accounts.account_to_close;
-    let payer = &ctx.accounts.payer;
-    let seeds_slices: Vec<&[u8]> = input_seeds.iter().map(|s| s.as_slice()).collect();
-
-    // Derive the PDA from the seeds and validate it matches
-    let pda = Pubkey::find_program_address(&seeds_slices, ctx.program_id).0;
-    if pda != account_to_close.key() {
-        return Err(ErrorCode::InvalidPda.into());
-    }
+    let recipient = match &ctx.accounts.rent_recipient {
+        Some(rent_recipient) => rent_recipient.to_account_info(),
+        None => ctx.accounts.payer.to_account_info(),
+    };
+    validate_closable_pda(
+        &account_to_close.key(),
+        &input_seeds,
+        bump,
+        &ctx.accounts.state.key(),
+    )?;
 
-    // Prevent closing of program state account
-    if pda == ctx.accounts.state.key() {
-        return Err(ErrorCode::InvalidPda.into());
+    // Closing a delegate that is still approved would strand the approval on a dead PDA
+    if let Some(user_token_account) = &ctx.accounts.user_token_account {
+        if user_token_account.delegate == COption::Some(account_to_close.key()) {
+            return Err(ErrorCode::DelegateStillActive.into());
+        }
     }
 
     // Close account and transfer lamports
-    close_account_and_transfer_lamports(account_to_close, payer)?;
+    let lamports_recovered = close_account_and_transfer_lamports(account_to_close, &recipient)?;
 
     // Emit event for indexing and notifications
     emit!(AccountClosed {
         account: account_to_close.key(),
+        lamports_recovered,
+        recipient: recipient.key(),
     });
 
     Ok(())
 }
 
-/// Schedule an account for closure by transferring its rent-exempt balance to the recipient
+/**
+ * Check that an account is the PDA derived from the seeds and is not the program state.
+ *
+ * @param account Address of the account to close
+ * @param input_seeds Seeds used to derive the PDA (without the bump)
+ * @param bump Bump of the PDA, or None to search for the canonical bump
+ * @param state Address of the program state PDA, which must never be closed
+ *
+ * @return Result indicating success or InvalidPda
+ */
+pub(crate) fn validate_closable_pda(
+    account: &Pubkey,
+    input_seeds: &[Vec<u8>],
+    bump: Option<u8>,
+    state: &Pubkey,
+) -> Result<()> {
+    let mut seeds_slices: Vec<&[u8]> = input_seeds.iter().map(|s| s.as_slice()).collect();
+
+    // Derive the PDA from the seeds and validate it matches
+    let pda = match bump {
+        Some(bump) => {
+            let bump_seed = [bump];
+            seeds_slices.push(&bump_seed);
+            Pubkey::create_program_address(&seeds_slices, &ID).map_err(|_| ErrorCode::InvalidPda)?
+        }
+        None => Pubkey::find_program_address(&seeds_slices, &ID).0,
+    };
+    if pda != *account {
+        return Err(ErrorCode::InvalidPda.into());
+    }
+
+    // Prevent closing of program state account, whose address is verified by the state constraint
+    if account == state {
+        return Err(ErrorCode::InvalidPda.into());
+    }
+    Ok(())
+}
+
+/**
+ * Schedule an account for closure by transferring its whole balance to the recipient.
+ *
+ * The balance is read after the data is released, so any lamports above the rent-exempt
+ * minimum (e.g. top-ups sent to the address) are recovered along with the rent.
+ *
+ * @return The lamports moved to the recipient
+ */
 pub fn close_account_and_transfer_lamports<'info>(
     account_to_close: &AccountInfo<'info>,
     recipient: &AccountInfo<'info>,
-) -> Result<()> {
-    // Transfer all lamports from the account to the recipient
-    let lamports = account_to_close.lamports();
-    **account_to_close.try_borrow_mut_lamports()? = 0;
-    **recipient.try_borrow_mut_lamports()? += lamports;
-
-    // realloc the account to 0 bytes
+) -> Result<u64> {
+    // Release the data first so a failure leaves the lamports untouched
+    account_to_close
+        .realloc(0, false)
+        .map_err(|_| ErrorCode::CloseFailed)?;
     account_to_close.assign(&system_program::ID);
-    account_to_close.realloc(0, false).unwrap();
 
-    Ok(())
+    sweep_lamports(account_to_close, recipient)
+}
+
+/**
+ * Move an account's entire balance to the recipient.
+ *
+ * The account is left with zero lamports, so sweeping it again (e.g. an account closed
+ * twice in one transaction) recovers nothing rather than failing.
+ *
+ * @return The lamports moved to the recipient
+ */
+fn sweep_lamports(account: &AccountInfo, recipient: &AccountInfo) -> Result<u64> {
+    let lamports = account.lamports();
+    if lamports == 0 {
+        return Ok(0);
+    }
+    let recipient_lamports = recipient
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    **account.try_borrow_mut_lamports()? = 0;
+    **recipient.try_borrow_mut_lamports()? = recipient_lamports;
+
+    Ok(lamports)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_close_failure_leaves_lamports() {
+        let (account_key, recipient_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (mut account_lamports, mut recipient_lamports) = (1_000, 500);
+        let (mut account_data, mut recipient_data) = (vec![1u8; 16], vec![]);
+        let account = AccountInfo::new(
+            &account_key,
+            false,
+            true,
+            &mut account_lamports,
+            &mut account_data,
+            &ID,
+            false,
+            0,
+        );
+        let recipient = AccountInfo::new(
+            &recipient_key,
+            false,
+            true,
+            &mut recipient_lamports,
+            &mut recipient_data,
+            &system_program::ID,
+            false,
+            0,
+        );
+
+        // An outstanding borrow of the data makes the realloc fail. The successful path resizes
+        // the runtime's input buffer in place, so only the close_account integration tests
+        // cover it.
+        let data = account.try_borrow_data().unwrap();
+        let result = close_account_and_transfer_lamports(&account, &recipient);
+        assert_eq!(result.unwrap_err(), ErrorCode::CloseFailed.into());
+        drop(data);
+
+        // Nothing was moved or reassigned
+        assert_eq!(account.lamports(), 1_000);
+        assert_eq!(recipient.lamports(), 500);
+        assert_eq!(account.owner, &ID);
+        assert_eq!(account.data_len(), 16);
+    }
+
+    #[test]
+    fn test_sweep_moves_entire_balance_once() {
+        let (account_key, recipient_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (mut account_lamports, mut recipient_lamports) = (1_500, 500);
+        let (mut account_data, mut recipient_data) = (vec![], vec![]);
+        let account = AccountInfo::new(
+            &account_key,
+            false,
+            true,
+            &mut account_lamports,
+            &mut account_data,
+            &system_program::ID,
+            false,
+            0,
+        );
+        let recipient = AccountInfo::new(
+            &recipient_key,
+            false,
+            true,
+            &mut recipient_lamports,
+            &mut recipient_data,
+            &system_program::ID,
+            false,
+            0,
+        );
+
+        assert_eq!(sweep_lamports(&account, &recipient).unwrap(), 1_500);
+        assert_eq!(account.lamports(), 0);
+        assert_eq!(recipient.lamports(), 2_000);
+
+        // A second sweep finds nothing left
+        assert_eq!(sweep_lamports(&account, &recipient).unwrap(), 0);
+        assert_eq!(recipient.lamports(), 2_000);
+    }
 }
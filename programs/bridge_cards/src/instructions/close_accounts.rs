@@ -0,0 +1,120 @@
+use crate::instructions::close_account::{
+    close_account_and_transfer_lamports, validate_closable_pda,
+};
+use crate::{errors::ErrorCode, events::AccountClosed, state::BridgeCardsState, ID, STATE_SEED};
+use anchor_lang::prelude::*;
+
+/**
+ * Close several program-derived accounts in one transaction and reclaim their rent.
+ *
+ * This is the bulk form of close_account for program maintenance. Each remaining account is
+ * validated exactly as close_account validates its account_to_close, using the seeds at the
+ * same position in input_seeds. Every account is validated before any is closed, so a single
+ * bad entry refuses the whole batch.
+ *
+ * Account Validation:
+ * - One seed list per remaining account
+ * - Every account must be the PDA derived from its seeds (canonical bump)
+ * - No account may be the program state account
+ * - No account may appear twice
+ * - No account may be the payer
+ *
+ * Security Model:
+ * - Only the program admin can close accounts
+ * - Admin authority verified through state PDA
+ *
+ * Remaining Accounts (one per seed list, in order):
+ * - account_to_close: PDA to be closed (Mutable)
+ *
+ * Events Emitted:
+ * - AccountClosed: One per closed account
+ *   Fields: account (pubkey of closed account), lamports_recovered
+ *
+ * Required Accounts:
+ * - admin: Program admin with closure authority
+ * - payer: Account to receive recovered rent
+ * - state: Global program state PDA
+ */
+#[derive(Accounts)]
+pub struct CloseAccounts<'info> {
+    /// Program admin account, must match admin stored in state
+    /// Required permissions: Signer
+    #[account(constraint = admin.key() == state.admin)]
+    pub admin: Signer<'info>,
+
+    /// Account that will receive the recovered rent
+    /// Required permissions: Signer, Mutable (for rent receipt)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Global program state storing the admin public key
+    /// Seeds: [STATE_SEED]
+    /// Required permissions: Read-only
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        seeds::program = ID
+    )]
+    pub state: Account<'info, BridgeCardsState>,
+}
+
+/**
+ * Process the closure of several program-derived accounts.
+ *
+ * @param ctx Context containing all required accounts; remaining accounts are closed
+ * @param input_seeds Seeds of each remaining account's PDA (without the bump)
+ *
+ * Flow:
+ * 1. Verify admin signature (done via account constraints)
+ * 2. Validate every remaining account against its seeds
+ * 3. Transfer each account's rent lamports to the payer
+ * 4. Emit one closure event per account
+ *
+ * Error Handling:
+ * - Returns InvalidBatchAccounts if the accounts and seed lists differ in number, or an
+ *   account is repeated
+ * - Returns InvalidPda if any account is not its PDA or is the state account
+ * - Returns SelfClose if any account is the payer
+ *
+ * @return Result indicating success or containing an error
+ */
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CloseAccounts<'info>>,
+    input_seeds: Vec<Vec<Vec<u8>>>,
+) -> Result<()> {
+    if input_seeds.is_empty() || ctx.remaining_accounts.len() != input_seeds.len() {
+        return Err(ErrorCode::InvalidBatchAccounts.into());
+    }
+
+    // Validate every account before any is closed
+    let state = ctx.accounts.state.key();
+    let payer = ctx.accounts.payer.key();
+    for (index, (account_to_close, seeds)) in
+        ctx.remaining_accounts.iter().zip(&input_seeds).enumerate()
+    {
+        if *account_to_close.key == payer {
+            return Err(ErrorCode::SelfClose.into());
+        }
+        if ctx.remaining_accounts[..index]
+            .iter()
+            .any(|account| account.key == account_to_close.key)
+        {
+            return Err(ErrorCode::InvalidBatchAccounts.into());
+        }
+        validate_closable_pda(account_to_close.key, seeds, None, &state)?;
+    }
+
+    let payer = ctx.accounts.payer.to_account_info();
+    for account_to_close in ctx.remaining_accounts.iter() {
+        let lamports_recovered = close_account_and_transfer_lamports(account_to_close, &payer)?;
+
+        // Emit event for indexing and notifications
+        emit!(AccountClosed {
+            account: account_to_close.key(),
+            lamports_recovered,
+            recipient: payer.key(),
+        });
+    }
+
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use crate::events::AccountClosed;
+use crate::pda::merchant_id_seed;
+use crate::state::{MerchantDebitorState, MerchantManagerState};
+use crate::{ID, MERCHANT_DEBITOR_SEED, MERCHANT_MANAGER_SEED};
+use anchor_lang::prelude::*;
+
+/**
+ * Remove a merchant debitor and close its account.
+ *
+ * This instruction lets a merchant manager tear down a debitor without going through the
+ * admin-only close_account and hand-assembled seeds. The debitor state PDA is derived from the
+ * typed mint and debitor arguments with the same seeds as add_or_update_merchant_debitor, so a
+ * mismatched account is rejected by the seeds constraint.
+ *
+ * Rent Recovery:
+ * - The debitor state account is closed and its lamports are sent to the payer
+ *
+ * Security Model:
+ * - Only merchant managers can close their merchant's debitors
+ * - The debitor does not need to sign, so a compromised debitor can be removed
+ *
+ * Events Emitted:
+ * - AccountClosed: When the debitor state is closed
+ *   Fields: account (pubkey of the closed debitor state), lamports_recovered
+ *
+ * Required Accounts:
+ * - manager: Merchant manager
+ * - payer: Account receiving the debitor state's rent
+ * - manager_state: PDA verifying manager authority
+ * - debitor_state: PDA to be closed
+ */
+#[derive(Accounts)]
+#[instruction(merchant_id: u64, mint: Pubkey, debitor: Pubkey)]
+pub struct CloseMerchantDebitor<'info> {
+    /// Merchant manager account, must be one of the managers in manager_state
+    /// Required permissions: Signer
+    #[account(constraint = manager_state.is_manager(&manager.key()))]
+    pub manager: Signer<'info>,
+
+    /// Account that will receive the recovered rent
+    /// Required permissions: Signer, Mutable (for rent receipt)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// PDA storing the merchant manager's authorization
+    /// Seeds: [MERCHANT_MANAGER_SEED, merchant_id]
+    /// Required permissions: Read-only
+    #[account(
+        seeds = [MERCHANT_MANAGER_SEED, merchant_id_seed(merchant_id).as_ref()],
+        bump = manager_state.bump,
+        seeds::program = ID
+    )]
+    pub manager_state: Account<'info, MerchantManagerState>,
+
+    /// PDA storing the debitor's authorization state
+    /// Seeds: [MERCHANT_DEBITOR_SEED, merchant_id, mint, debitor]
+    /// Required permissions: Mutable (for closure)
+    #[account(mut,
+        close = payer,
+        seeds = [MERCHANT_DEBITOR_SEED, merchant_id_seed(merchant_id).as_ref(), mint.as_ref(), debitor.as_ref()],
+        bump = debitor_state.bump,
+        seeds::program = ID
+    )]
+    pub debitor_state: Account<'info, MerchantDebitorState>,
+}
+
+/**
+ * Process the closure of a merchant debitor.
+ *
+ * @param ctx Context containing all required accounts
+ * @param _merchant_id Unique identifier for the merchant (used for PDA derivation)
+ * @param _mint Token mint the debitor was authorized for (used for PDA derivation)
+ * @param _debitor Debitor whose state is closed (used for PDA derivation)
+ *
+ * Flow:
+ * 1. Verify manager signature and debitor state PDA (done via account constraints)
+ * 2. Emit closure event
+ * 3. Close the debitor state account, sending its rent to the payer (done by Anchor on exit)
+ *
+ * @return Result indicating success or containing an error
+ */
+pub fn handler(
+    ctx: Context<CloseMerchantDebitor>,
+    _merchant_id: u64,
+    _mint: Pubkey,
+    _debitor: Pubkey,
+) -> Result<()> {
+    // Emit event for indexing and notifications
+    // The account is closed to the payer after the handler, with its whole balance
+    emit!(AccountClosed {
+        account: ctx.accounts.debitor_state.key(),
+        lamports_recovered: ctx.accounts.debitor_state.to_account_info().lamports(),
+        recipient: ctx.accounts.payer.key(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use crate::events::AccountClosed;
+use crate::instructions::initialize::STATE_SEED;
+use crate::pda::merchant_id_seed;
+use crate::state::{BridgeCardsState, MerchantDestinationState};
+use crate::{ID, MERCHANT_DESTINATION_SEED};
+use anchor_lang::prelude::*;
+
+/**
+ * Remove a merchant destination and close its account.
+ *
+ * This instruction lets the program admin tear down a destination without hand-assembling
+ * close_account seeds. The destination state PDA is derived from the typed mint and
+ * destination arguments with the same seeds as add_or_update_merchant_destination, so a
+ * mismatched account is rejected by the seeds constraint.
+ *
+ * Rent Recovery:
+ * - The destination state account is closed and its lamports are sent to the payer
+ *
+ * Security Model:
+ * - Only the program admin can close destinations, as only it can add them
+ * - The destination token account is only used for PDA derivation, so the destination can be
+ *   closed even after its token account has been closed
+ *
+ * Events Emitted:
+ * - AccountClosed: When the destination state is closed
+ *   Fields: account (pubkey of the closed destination state), lamports_recovered
+ *
+ * Required Accounts:
+ * - admin: Program admin
+ * - payer: Account receiving the destination state's rent
+ * - state: Global program state storing admin pubkey
+ * - destination_state: PDA to be closed
+ */
+#[derive(Accounts)]
+#[instruction(merchant_id: u64, mint: Pubkey, destination: Pubkey)]
+pub struct CloseMerchantDestination<'info> {
+    /// The program admin account, must match admin stored in state
+    /// Required permissions: Signer
+    #[account(constraint = admin.key() == state.admin)]
+    pub admin: Signer<'info>,
+
+    /// Account that will receive the recovered rent
+    /// Required permissions: Signer, Mutable (for rent receipt)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Global program state storing the admin public key
+    /// Seeds: [STATE_SEED]
+    /// Required permissions: Read-only
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        seeds::program = ID
+    )]
+    pub state: Account<'info, BridgeCardsState>,
+
+    /// PDA storing the destination's authorization state
+    /// Seeds: [MERCHANT_DESTINATION_SEED, merchant_id, mint, destination]
+    /// Required permissions: Mutable (for closure)
+    #[account(mut,
+        close = payer,
+        seeds = [MERCHANT_DESTINATION_SEED, merchant_id_seed(merchant_id).as_ref(), mint.as_ref(), destination.as_ref()],
+        bump = destination_state.bump,
+        seeds::program = ID
+    )]
+    pub destination_state: Account<'info, MerchantDestinationState>,
+}
+
+/**
+ * Process the closure of a merchant destination.
+ *
+ * @param ctx Context containing all required accounts
+ * @param _merchant_id Unique identifier for the merchant (used for PDA derivation)
+ * @param _mint Token mint of the destination (used for PDA derivation)
+ * @param _destination Destination token account whose state is closed (used for PDA derivation)
+ *
+ * Flow:
+ * 1. Verify admin signature and destination state PDA (done via account constraints)
+ * 2. Emit closure event
+ * 3. Close the destination state account, sending its rent to the payer (done by Anchor on exit)
+ *
+ * @return Result indicating success or containing an error
+ */
+pub fn handler(
+    ctx: Context<CloseMerchantDestination>,
+    _merchant_id: u64,
+    _mint: Pubkey,
+    _destination: Pubkey,
+) -> Result<()> {
+    // Emit event for indexing and notifications
+    // The account is closed to the payer after the handler, with its whole balance
+    emit!(AccountClosed {
+        account: ctx.accounts.destination_state.key(),
+        lamports_recovered: ctx.accounts.destination_state.to_account_info().lamports(),
+        recipient: ctx.accounts.payer.key(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use crate::events::MerchantManagerClosed;
+use crate::instructions::initialize::STATE_SEED;
+use crate::pda::merchant_id_seed;
+use crate::state::{BridgeCardsState, MerchantManagerState};
+use crate::{ID, MERCHANT_MANAGER_SEED};
+use anchor_lang::prelude::*;
+
+/**
+ * Remove all of a merchant's managers and close its account.
+ *
+ * add_or_update_merchant_manager and remove_merchant_manager always leave the merchant with a
+ * manager. This instruction removes the managers entirely: once the manager state is closed,
+ * every manager-gated instruction of the merchant (add_or_update_user_delegate,
+ * add_or_update_merchant_debitor, ...) fails until the admin sets a new manager.
+ *
+ * Rent Recovery:
+ * - The manager state account is closed and its lamports are sent to the payer
+ *
+ * Security Model:
+ * - Only the program admin can close manager accounts
+ * - The managers do not need to sign, so lost or compromised keys can be removed
+ *
+ * Events Emitted:
+ * - MerchantManagerClosed: When the manager state is closed
+ *   Fields: merchant_id, managers
+ *
+ * Required Accounts:
+ * - admin: Program admin
+ * - payer: Account receiving the manager state's rent
+ * - state: Global program state storing admin pubkey
+ * - manager_state: PDA to be closed
+ */
+#[derive(Accounts)]
+#[instruction(merchant_id: u64)]
+pub struct CloseMerchantManager<'info> {
+    /// The program admin account, must match admin stored in state
+    /// Required permissions: Signer
+    #[account(constraint = admin.key() == state.admin)]
+    pub admin: Signer<'info>,
+
+    /// Account that will receive the recovered rent
+    /// Required permissions: Signer, Mutable (for rent receipt)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Global program state storing the admin public key
+    /// Seeds: [STATE_SEED]
+    /// Required permissions: Read-only
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        seeds::program = ID
+    )]
+    pub state: Account<'info, BridgeCardsState>,
+
+    /// PDA storing the merchant manager's authorization
+    /// Seeds: [MERCHANT_MANAGER_SEED, merchant_id]
+    /// Required permissions: Mutable (for closure)
+    #[account(mut,
+        close = payer,
+        seeds = [MERCHANT_MANAGER_SEED, merchant_id_seed(merchant_id).as_ref()],
+        bump = manager_state.bump,
+        seeds::program = ID
+    )]
+    pub manager_state: Account<'info, MerchantManagerState>,
+}
+
+/**
+ * Process the closure of a merchant manager.
+ *
+ * @param ctx Context containing all required accounts
+ * @param merchant_id Unique identifier for the merchant
+ *
+ * Flow:
+ * 1. Verify admin signature and manager state PDA (done via account constraints)
+ * 2. Emit event identifying the removed managers
+ * 3. Close the manager state account, sending its rent to the payer (done by Anchor on exit)
+ *
+ * @return Result indicating success or containing an error
+ */
+pub fn handler(ctx: Context<CloseMerchantManager>, merchant_id: u64) -> Result<()> {
+    // Emit event for indexing and notifications
+    emit!(MerchantManagerClosed {
+        merchant_id,
+        managers: ctx.accounts.manager_state.active_managers().to_vec(),
+    });
+
+    Ok(())
+}
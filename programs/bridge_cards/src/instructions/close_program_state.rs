@@ -0,0 +1,81 @@
+use crate::errors::ErrorCode;
+use crate::events::ProgramDecommissioned;
+use crate::instructions::initialize::STATE_SEED;
+use crate::state::BridgeCardsState;
+use crate::ID;
+use anchor_lang::prelude::*;
+
+/**
+ * Close the global state of the BridgeCards program, decommissioning it.
+ *
+ * close_account refuses to close the state PDA, as every admin instruction depends on it.
+ * This instruction closes it on purpose, e.g. when the program is being retired. Once the
+ * state is closed no admin instruction can run, and initialize can run again to start
+ * fresh with a new admin.
+ *
+ * Account Closure:
+ * - Closes the global state PDA, derived using [STATE_SEED]
+ * - Transfers all its lamports to the admin
+ * - Other program accounts are left as they are; close them with close_account first
+ *
+ * Security Model:
+ * - Only the program admin can close the state
+ * - The admin must pass confirm = true, so the state isn't closed by a mistaken call
+ *
+ * Events Emitted:
+ * - ProgramDecommissioned: When the state is closed
+ *   Fields: admin, lamports_recovered
+ *
+ * Common Errors:
+ * - DecommissionNotConfirmed: confirm is false
+ *
+ * Required Accounts:
+ * - admin: Program admin, receiving the state's rent
+ * - state: Global program state PDA
+ */
+#[derive(Accounts)]
+pub struct CloseProgramState<'info> {
+    /// The program admin account, must match admin stored in state
+    /// Required permissions: Signer, Mutable (for rent receipt)
+    #[account(mut, constraint = admin.key() == state.admin)]
+    pub admin: Signer<'info>,
+
+    /// Global program state to close
+    /// Seeds: [STATE_SEED]
+    /// Required permissions: Mutable (for closure)
+    #[account(mut,
+        close = admin,
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        seeds::program = ID
+    )]
+    pub state: Account<'info, BridgeCardsState>,
+}
+
+/**
+ * Process the closure of the global state.
+ *
+ * @param ctx Context containing all required accounts
+ * @param confirm Must be true to close the state
+ *
+ * Flow:
+ * 1. Verify admin signature (done via account constraints)
+ * 2. Reject the call unless it is confirmed
+ * 3. Emit event with the lamports returned to the admin
+ * 4. Close the state PDA into the admin (done via account constraints)
+ *
+ * @return Result indicating success or containing an error
+ */
+pub fn handler(ctx: Context<CloseProgramState>, confirm: bool) -> Result<()> {
+    if !confirm {
+        return Err(ErrorCode::DecommissionNotConfirmed.into());
+    }
+
+    // Emit event for indexing and notifications
+    emit!(ProgramDecommissioned {
+        admin: ctx.accounts.admin.key(),
+        lamports_recovered: ctx.accounts.state.to_account_info().lamports(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use crate::events::UserDelegateClosed;
+use crate::instructions::add_or_update_user_delegate::USER_DELEGATE_SEED;
+use crate::pda::merchant_id_seed;
+use crate::state::{MerchantManagerState, UserDelegateState};
+use crate::{ID, MERCHANT_MANAGER_SEED};
+use anchor_lang::prelude::*;
+
+/**
+ * Revoke a user delegate and close its account.
+ *
+ * This instruction allows a merchant manager to tear down a delegate when a user offboards,
+ * without going through the admin-only close_account and hand-assembled seeds. The delegate
+ * PDA is derived from the same seeds as add_or_update_user_delegate.
+ *
+ * Rent Recovery:
+ * - The delegate account is closed and its lamports are sent to the payer
+ *
+ * Security Model:
+ * - Only merchant managers can close their merchant's delegates
+ * - The user token account and mint are only used for PDA derivation, so the delegate can
+ *   be closed even after the user has closed their token account
+ *
+ * Events Emitted:
+ * - UserDelegateClosed: When the delegate is closed
+ *   Fields: merchant_id, mint, user_ata, user_delegate
+ *
+ * Required Accounts:
+ * - manager: Merchant manager
+ * - payer: Account receiving the delegate's rent
+ * - manager_state: PDA verifying manager authority
+ * - user_token_account: Token account the delegate controlled
+ * - mint: Token mint of the delegation
+ * - user_delegate_account: PDA to be closed
+ */
+#[derive(Accounts)]
+#[instruction(merchant_id: u64)]
+pub struct CloseUserDelegate<'info> {
+    /// Merchant manager account, must be one of the managers in manager_state
+    /// Required permissions: Signer
+    #[account(constraint = manager_state.is_manager(&manager.key()))]
+    pub manager: Signer<'info>,
+
+    /// Account that will receive the recovered rent
+    /// Required permissions: Signer, Mutable (for rent receipt)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// PDA storing the merchant manager's authorization
+    /// Seeds: [MERCHANT_MANAGER_SEED, merchant_id]
+    /// Required permissions: Read-only
+    #[account(
+        seeds = [MERCHANT_MANAGER_SEED, merchant_id_seed(merchant_id).as_ref()],
+        bump = manager_state.bump,
+        seeds::program = ID
+    )]
+    pub manager_state: Account<'info, MerchantManagerState>,
+
+    /// Token account the delegate controlled
+    /// Required permissions: None (used for PDA derivation)
+    /// CHECK: Only the key is used, to derive the delegate PDA
+    pub user_token_account: UncheckedAccount<'info>,
+
+    /// Mint of the delegation
+    /// Required permissions: None (used for PDA derivation)
+    /// CHECK: Only the key is used, to derive the delegate PDA
+    pub mint: UncheckedAccount<'info>,
+
+    /// PDA storing the delegate's transfer limits and state
+    /// Seeds: [USER_DELEGATE_SEED, merchant_id, mint, user_token_account]
+    /// Required permissions: Mutable (for closure)
+    #[account(mut,
+        close = payer,
+        seeds = [USER_DELEGATE_SEED, merchant_id_seed(merchant_id).as_ref(), mint.key().as_ref(), user_token_account.key().as_ref()],
+        bump = user_delegate_account.bump,
+        seeds::program = ID
+    )]
+    pub user_delegate_account: Account<'info, UserDelegateState>,
+}
+
+/**
+ * Process the closure of a user delegate.
+ *
+ * @param ctx Context containing all required accounts
+ * @param merchant_id Unique identifier for the merchant
+ *
+ * Flow:
+ * 1. Verify manager signature and delegate PDA (done via account constraints)
+ * 2. Emit event identifying the closed delegate
+ * 3. Close the delegate account, sending its rent to the payer (done by Anchor on exit)
+ *
+ * @return Result indicating success or containing an error
+ */
+pub fn handler(ctx: Context<CloseUserDelegate>, merchant_id: u64) -> Result<()> {
+    // Emit event for indexing and notifications
+    emit!(UserDelegateClosed {
+        merchant_id,
+        mint: ctx.accounts.mint.key(),
+        user_ata: ctx.accounts.user_token_account.key(),
+        user_delegate: ctx.accounts.user_delegate_account.key(),
+    });
+
+    Ok(())
+}
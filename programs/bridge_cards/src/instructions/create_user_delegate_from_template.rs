@@ -0,0 +1,211 @@
+use crate::errors::ErrorCode;
+use crate::events::UserDelegateAddedOrUpdated;
+use crate::instructions::add_or_update_user_delegate::{
+    apply_user_delegate_settings, emit_user_delegate_snapshot, UserDelegateOptions,
+    USER_DELEGATE_SEED,
+};
+use crate::pda::merchant_id_seed;
+use crate::state::{
+    AccountSpace, MerchantConfigState, MerchantManagerState, MerchantRegistryState,
+    UserDelegateState,
+};
+use crate::{
+    ID, MERCHANT_CONFIG_SEED, MERCHANT_MANAGER_SEED, MERCHANT_MINT_SEED, MERCHANT_REGISTRY_SEED,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+/**
+ * Create a user delegate with the limits of the merchant's template.
+ *
+ * Managers creating many delegates with the same limits can store them once in the merchant
+ * config (default_per_transfer_limit, default_period_transfer_limit, default_period_seconds)
+ * and create each delegate without passing them. The delegate is created exactly as
+ * add_or_update_user_delegate would with those limits, and later updates go through
+ * add_or_update_user_delegate.
+ *
+ * Account Creation:
+ * - Creates a PDA to store delegate parameters, failing if it already exists
+ * - PDA is derived using [USER_DELEGATE_SEED, merchant_id, mint, user_token_account]
+ * - Funded by the payer account
+ *
+ * Security Model:
+ * - Only merchant managers can create delegates
+ * - The limits come from the merchant config, which only the admin can set
+ * - If the merchant config sets enforce_mint_allowlist, the mint must be allowlisted for the
+ *   merchant (merchant_mint)
+ *
+ * Events Emitted:
+ * - UserDelegateAddedOrUpdated: When the delegate is created, with zero previous limits
+ * - UserDelegateSnapshot: Full delegate state, if the merchant config enables emit_full_snapshot
+ *
+ * Common Errors:
+ * - MerchantTemplateNotSet: The merchant config doesn't exist or has no template
+ * - MintNotAllowedForMerchant: The merchant enforces its mint allowlist and the mint is not on it
+ * - LimitExceedsMerchantCap: The template's limits are above the merchant's caps
+ * - InvalidLimitMode: The options request an unknown limit mode
+ *
+ * Required Accounts:
+ * - manager: Merchant manager who can create delegates
+ * - payer: Account paying for PDA creation/rent
+ * - manager_state: PDA verifying manager authority
+ * - merchant_config: PDA storing the merchant configuration and its template
+ * - user_token_account: Token account to delegate
+ * - mint: Token mint for the delegation
+ * - user_delegate_account: PDA storing delegate parameters
+ * - system_program: Required for account creation
+ * - merchant_mint: PDA allowlisting the mint (optional, required by enforce_mint_allowlist)
+ * - merchant_registry: Registry counting the merchant's accounts (optional)
+ */
+#[derive(Accounts)]
+#[instruction(merchant_id: u64)]
+pub struct CreateUserDelegateFromTemplate<'info> {
+    /// Merchant manager account, must be one of the managers in manager_state
+    /// Required permissions: Signer
+    #[account(constraint = manager_state.is_manager(&manager.key()))]
+    pub manager: Signer<'info>,
+
+    /// Account that will pay for PDA creation and rent
+    /// Required permissions: Signer, Mutable (for rent payment)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// PDA storing the merchant manager's authorization
+    /// Seeds: [MERCHANT_MANAGER_SEED, merchant_id]
+    /// Required permissions: Read-only
+    #[account(
+        seeds = [MERCHANT_MANAGER_SEED, merchant_id_seed(merchant_id).as_ref()],
+        bump = manager_state.bump,
+        seeds::program = ID
+    )]
+    pub manager_state: Account<'info, MerchantManagerState>,
+
+    /// PDA storing the merchant configuration and its delegate limit template
+    /// Seeds: [MERCHANT_CONFIG_SEED, merchant_id]
+    /// Required permissions: Read-only
+    /// CHECK: Address is verified by seeds; a missing config has no template
+    #[account(
+        seeds = [MERCHANT_CONFIG_SEED, merchant_id_seed(merchant_id).as_ref()],
+        bump,
+        seeds::program = ID
+    )]
+    pub merchant_config: UncheckedAccount<'info>,
+
+    /// Token account that will be controlled by the delegate
+    /// Required permissions: Read-only
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Mint of the tokens that can be transferred by this delegate
+    /// Required permissions: Read-only
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// PDA storing the delegate's transfer limits and state
+    /// Seeds: [USER_DELEGATE_SEED, merchant_id, mint, user_token_account]
+    /// Space: Discriminator + Delegate parameters
+    /// Required permissions: Mutable
+    #[account(init,
+        payer = payer,
+        space = UserDelegateState::SPACE,
+        seeds = [
+            USER_DELEGATE_SEED,
+            merchant_id_seed(merchant_id).as_ref(),
+            mint.key().as_ref(),
+            user_token_account.key().as_ref(),
+        ],
+        bump
+    )]
+    pub user_delegate_account: Account<'info, UserDelegateState>,
+
+    /// Required for account creation
+    pub system_program: Program<'info, System>,
+
+    /// PDA allowlisting the mint for the merchant
+    /// Seeds: [MERCHANT_MINT_SEED, merchant_id, mint]
+    /// Required when the merchant config enforces the mint allowlist
+    /// Required permissions: Read-only
+    /// CHECK: Address is verified by seeds; the mint is not allowlisted if it has not been created
+    #[account(
+        seeds = [MERCHANT_MINT_SEED, merchant_id_seed(merchant_id).as_ref(), mint.key().as_ref()],
+        bump,
+        seeds::program = ID
+    )]
+    pub merchant_mint: Option<UncheckedAccount<'info>>,
+
+    /// Registry counting the merchant's accounts, incremented when the delegate is created
+    /// Seeds: [MERCHANT_REGISTRY_SEED, merchant_id]
+    /// Required permissions: Mutable (counter update)
+    #[account(mut,
+        seeds = [MERCHANT_REGISTRY_SEED, merchant_id_seed(merchant_id).as_ref()],
+        bump = merchant_registry.bump,
+        seeds::program = ID
+    )]
+    pub merchant_registry: Option<Account<'info, MerchantRegistryState>>,
+}
+
+/**
+ * Process the creation of a user delegate from the merchant's template.
+ *
+ * @param ctx Context containing all required accounts
+ * @param merchant_id Unique identifier for the merchant
+ * @param options Optional overrides of merchant-wide settings, as for add_or_update_user_delegate
+ *
+ * Flow:
+ * 1. Verify manager signature (done via account constraints)
+ * 2. Read the template from the merchant config, rejecting merchants without one
+ * 3. Reject mints not allowlisted for the merchant
+ * 4. Write the template limits and the options to the new delegate, starting its cooldown
+ * 5. Count the delegate in the merchant registry, if passed
+ * 6. Emit event with delegate information (and a full snapshot if configured)
+ *
+ * @return Result indicating success or containing an error
+ */
+pub fn handler(
+    ctx: Context<CreateUserDelegateFromTemplate>,
+    merchant_id: u64,
+    options: UserDelegateOptions,
+) -> Result<()> {
+    let merchant_config = MerchantConfigState::load_or_default(&ctx.accounts.merchant_config)?;
+    if merchant_config.default_period_seconds == 0 {
+        return Err(ErrorCode::MerchantTemplateNotSet.into());
+    }
+    merchant_config.check_mint(ctx.accounts.merchant_mint.as_deref())?;
+
+    let user_delegate_account = &mut ctx.accounts.user_delegate_account;
+    apply_user_delegate_settings(
+        user_delegate_account,
+        &merchant_config,
+        merchant_config.default_per_transfer_limit,
+        merchant_config.default_period_transfer_limit,
+        merchant_config.default_period_seconds,
+        &options,
+    )?;
+    user_delegate_account.bump = ctx.bumps.user_delegate_account;
+
+    if let Some(merchant_registry) = &mut ctx.accounts.merchant_registry {
+        merchant_registry.delegate_count = merchant_registry.delegate_count.saturating_add(1);
+    }
+
+    // Emit event for indexing and notifications
+    emit!(UserDelegateAddedOrUpdated {
+        merchant_id,
+        mint: ctx.accounts.mint.key(),
+        user_ata: ctx.accounts.user_token_account.key(),
+        user_delegate: ctx.accounts.user_delegate_account.key(),
+        metadata_hash: ctx.accounts.user_delegate_account.metadata_hash,
+        previous_per_transfer_limit: 0,
+        new_per_transfer_limit: merchant_config.default_per_transfer_limit,
+        previous_period_transfer_limit: 0,
+        new_period_transfer_limit: merchant_config.default_period_transfer_limit,
+    });
+
+    if merchant_config.emit_full_snapshot {
+        emit_user_delegate_snapshot(
+            merchant_id,
+            &ctx.accounts.mint.key(),
+            &ctx.accounts.user_token_account.key(),
+            &ctx.accounts.user_delegate_account,
+        );
+    }
+
+    Ok(())
+}
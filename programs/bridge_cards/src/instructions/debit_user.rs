@@ -1,12 +1,31 @@
-use crate::events::UserDebited;
+use crate::errors::ErrorCode;
+use crate::events::{UserDebited, UserDelegatePeriodReset};
 use crate::instructions::add_or_update_user_delegate::USER_DELEGATE_SEED;
-use crate::state::{MerchantDebitorState, MerchantDestinationState, UserDelegateState};
+use crate::pda::{find_debit_receipt, merchant_id_seed};
+use crate::state::{
+    feature_flags, AccountSpace, BridgeCardsState, DebitPolicy, DebitReceiptState,
+    MerchantConfigState, MerchantDebitorState, MerchantDestinationState, MerchantSpendTrackerState,
+    UserDelegateState, CURRENT_VERSION,
+};
 use crate::ID;
-use crate::{MERCHANT_DEBITOR_SEED, MERCHANT_DESTINATION_SEED};
+use crate::{
+    MERCHANT_CONFIG_SEED, MERCHANT_DEBITOR_SEED, MERCHANT_DESTINATION_SEED, MERCHANT_MINT_SEED,
+    MERCHANT_SPEND_SEED, STATE_SEED,
+};
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_lang::system_program;
+use anchor_spl::token_2022::spl_token_2022;
+use anchor_spl::token_2022::spl_token_2022::extension::{
+    transfer_fee::{TransferFee, TransferFeeConfig},
+    BaseStateWithExtensions, StateWithExtensions,
+};
 use anchor_spl::token_interface;
 use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
+/// Seed used to derive debit receipt PDAs
+pub const DEBIT_RECEIPT_SEED: &[u8] = b"debit_receipt";
+
 /**
  * Debit tokens from a user's token account via their UserDelegate.
  *
@@ -14,32 +33,99 @@ use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
  * to an authorized destination token account. The transfer must satisfy multiple security checks:
  *
  * Security Checks:
- * - Debitor must be authorized for the merchant (debitor_state.allowed == true)
- * - Destination must be authorized for the merchant (destination_state.allowed == true)
+ * - Debits must not be paused (PAUSED feature flag, or the merchant config's paused)
+ * - Debitor must be authorized for the merchant and mint (debitor_state exists and
+ *   debitor_state.allowed == true)
+ * - If the merchant config enforces the mint allowlist, the mint must be allowlisted for the
+ *   merchant (merchant_mint.allowed == true)
+ * - Destination must be authorized for the merchant (destination_state.allowed == true), or
+ *   be owned by the merchant config's allow_any_destination_owner when no destination_state
+ *   exists. Under the denylist destination policy, destinations are accepted unless their
+ *   destination_state is allowed
+ * - Delegate must be active (current time at or after valid_from and at or before expires_at)
+ * - The delegate's creation cooldown must have passed (current time at or after active_after)
+ * - If CLOCK_ANOMALY_CHECKS is enabled, the clock timestamp must be consistent with the slots
+ *   elapsed since the delegate's last debit, within the merchant's clock skew
+ * - Transfer amount must not be below delegate's minimum transfer amount
  * - Transfer amount must not exceed delegate's per-transfer limit
  * - Transfer amount must not exceed delegate's remaining period limit
+ * - Transfer amount must not exceed the debitor's own per-call and daily limits, if set
+ * - If the merchant spend tracker exists, the amount must not exceed the merchant's
+ *   remaining period limit across all delegates
+ * - Enough slots must have passed since the delegate's last debit (delegate override,
+ *   else merchant config, else one debit per slot)
  * - Source and destination token accounts must use the same mint
+ * - Source and destination token accounts must be owned by the passed token program
+ * - Source and destination token accounts must not be frozen
+ * - If the program's protocol fee is set, the fee token account must be owned by the protocol
+ *   fee owner, use the same mint and token program, and not be frozen
+ * - If the merchant config requires it, the user token account owner must be system owned
+ * - If the merchant config enforces it, the destination must be primary unless the delegate
+ *   allows alternate destinations
+ * - If the delegate is restricted to a destination, the destination must be that one
+ * - The updated delegate, debitor and spend tracker usage is written to the accounts before
+ *   the transfer CPI, so no program invoked during the transfer can observe the limits as
+ *   they were before this debit
  *
  * Account Derivation:
  * - User delegate PDA: [USER_DELEGATE_SEED, merchant_id, mint, user_token_account]
  * - Debitor state PDA: [MERCHANT_DEBITOR_SEED, merchant_id, debitor]
  * - Destination state PDA: [MERCHANT_DESTINATION_SEED, merchant_id, mint, destination_token_account]
+ *   (may not exist)
+ * - Merchant config PDA: [MERCHANT_CONFIG_SEED, merchant_id] (may not exist)
+ * - Merchant spend tracker PDA: [MERCHANT_SPEND_SEED, merchant_id] (may not exist)
  *
  * Transaction Flow:
  * 1. Validate debitor and destination are authorized
- * 2. Check transfer limits and update period tracking
- * 3. Execute token transfer using the delegate PDA as authority
+ * 2. Apply merchant config checks
+ * 3. Check transfer limits and update period tracking (delegate, debitor and, if its spend
+ *    tracker exists, merchant)
+ * 4. Record the net amount and time on the destination state, if it exists
+ * 5. Execute token transfer using the delegate PDA as authority, sending the protocol fee
+ *    (rounded down) to the fee token account and the rest to the destination
+ * 6. Write the debit receipt, if its account was passed
+ *
+ * Debit Receipt:
+ * - Optional, so compute-sensitive callers can rely on the UserDebited event alone
+ * - Created at [DEBIT_RECEIPT_SEED, user_delegate, debit_nonce], the nonce of this debit
+ * - Records the amount, destination, mint and timestamp of the debit
+ * - Rent is paid by the payer
  *
  * Common Errors:
  * - ExceedsMaxTransferLimit: Amount exceeds per-transfer limit
  * - ExceedsTransferLimitPerPeriod: Amount exceeds remaining period limit
+ * - ExceedsMerchantPeriodLimit: Amount exceeds the merchant's remaining period limit
+ * - ExceedsDebitorPerCallLimit: Amount exceeds the debitor's per-call limit
+ * - ExceedsDebitorDailyLimit: Amount exceeds the debitor's remaining daily limit
  * - MismatchedMint: Source and destination token accounts have different mints
+ * - DelegateMismatch: The user token account does not approve the delegate PDA
+ * - InsufficientDelegatedAmount: The approved amount is below the debit amount
+ * - DestinationNotAllowed: Destination is neither allowlisted nor owned by the allowed owner,
+ *   or is denylisted
+ * - UserOwnerNotSystemOwned: User token account owner is not a regular wallet
+ * - NonPrimaryDestination: Destination is not primary while the merchant enforces it
+ * - DestinationNotAllowedForDelegate: The delegate is restricted to another destination
+ * - DelegateNotYetActive: The delegate was created less than its cooldown ago
+ * - ClockAnomaly: The clock timestamp is inconsistent with the slots elapsed since the
+ *   delegate's last debit
+ * - ProgramPaused: The admin has paused debits
+ * - MerchantPaused: The admin has paused the merchant's debits
+ * - SelfTransfer: The destination is the user token account itself
+ * - MintNotAllowedForMerchant: The merchant enforces its mint allowlist and the mint is not on it
+ * - CrossProgramTransferNotAllowed: Token accounts belong to different token programs
+ * - TokenAccountFrozen: The user, destination or fee token account is frozen
+ * - InvalidProtocolFeeAccount: A protocol fee is owed but the fee token account is missing or
+ *   not owned by the protocol fee owner
+ * - PostConditionFailed: Destination balance did not increase by the expected net amount
+ * - InvalidPda: The debit receipt is not the PDA of this debit's nonce
  */
 #[derive(Accounts)]
 #[instruction(merchant_id: u64)]
 pub struct DebitUser<'info> {
     /// Account that pays for the transaction fees and rent
     /// CHECK: Can be any account with sufficient SOL
+    /// Required permissions: Signer, Mutable (for the debit receipt's rent)
+    #[account(mut)]
     pub payer: Signer<'info>,
 
     /// Program Derived Address (PDA) that stores the delegate's transfer limits and state
@@ -48,48 +134,79 @@ pub struct DebitUser<'info> {
     /// Seeds: [USER_DELEGATE_SEED, merchant_id, mint, user_token_account]
     /// Required permissions: Mutable (updates period tracking)
     #[account(mut,
-        seeds = [USER_DELEGATE_SEED, merchant_id.to_le_bytes().as_ref(), mint.key().as_ref(), user_token_account.key().as_ref()],
+        seeds = [USER_DELEGATE_SEED, merchant_id_seed(merchant_id).as_ref(), mint.key().as_ref(), user_token_account.key().as_ref()],
         bump = user_delegate_account.bump,
         seeds::program = ID
     )]
     pub user_delegate_account: Account<'info, UserDelegateState>,
 
     /// Account initiating the debit operation
-    /// Must be an authorized debitor for the merchant
+    /// Must be an authorized debitor for the merchant and mint
     /// Required permissions: Signer
-    #[account( constraint = debitor_state.allowed)]
     pub debitor: Signer<'info>,
 
-    /// PDA storing the debitor's authorization state for this merchant
-    /// Seeds: [MERCHANT_DEBITOR_SEED, merchant_id, debitor]
-    /// Required permissions: Read-only
-    #[account(seeds = [MERCHANT_DEBITOR_SEED, &merchant_id.to_le_bytes().as_ref(), mint.key().as_ref(), debitor.key().as_ref()], bump = debitor_state.bump, seeds::program = ID)]
-    pub debitor_state: Account<'info, MerchantDebitorState>,
+    /// PDA storing the debitor's authorization state and limits for this merchant and mint
+    /// Seeds: [MERCHANT_DEBITOR_SEED, merchant_id, mint, debitor]
+    /// Required permissions: Mutable (updates the debitor's daily tracking)
+    /// CHECK: Address is verified by seeds; the debitor is not authorized for the mint if it
+    /// has not been created
+    #[account(mut, seeds = [MERCHANT_DEBITOR_SEED, merchant_id_seed(merchant_id).as_ref(), mint.key().as_ref(), debitor.key().as_ref()], bump, seeds::program = ID)]
+    pub debitor_state: UncheckedAccount<'info>,
 
     /// Token account that will receive the transferred tokens
-    /// Must be an authorized destination for the merchant
+    /// Must be an authorized destination for the merchant and hold the delegate's mint, checked
+    /// here rather than relying on the mint in the destination state's seeds
     /// Required permissions: Mutable
     #[account(
         mut,
-        constraint = destination_state.allowed
+        constraint = destination_token_account.mint == mint.key() @ ErrorCode::MismatchedMint
     )]
     pub destination_token_account: InterfaceAccount<'info, TokenAccount>,
 
     /// PDA storing the destination's authorization state for this merchant and mint
     /// Seeds: [MERCHANT_DESTINATION_SEED, merchant_id, mint, destination_token_account]
-    /// Required permissions: Read-only
+    /// Required permissions: Mutable (records the last amount received)
+    /// CHECK: Address is verified by seeds; treated as unlisted if it has not been created
     #[account(
-        seeds = [MERCHANT_DESTINATION_SEED, &merchant_id.to_le_bytes().as_ref(), mint.key().as_ref(), destination_token_account.key().as_ref()],
-        bump = destination_state.bump,
+        mut,
+        seeds = [MERCHANT_DESTINATION_SEED, merchant_id_seed(merchant_id).as_ref(), mint.key().as_ref(), destination_token_account.key().as_ref()],
+        bump,
         seeds::program = ID)]
-    pub destination_state: Account<'info, MerchantDestinationState>,
+    pub destination_state: UncheckedAccount<'info>,
+
+    /// PDA storing the merchant configuration
+    /// Seeds: [MERCHANT_CONFIG_SEED, merchant_id]
+    /// Required permissions: Read-only
+    /// CHECK: Address is verified by seeds; default settings apply if it has not been created
+    #[account(
+        seeds = [MERCHANT_CONFIG_SEED, merchant_id_seed(merchant_id).as_ref()],
+        bump,
+        seeds::program = ID
+    )]
+    pub merchant_config: UncheckedAccount<'info>,
+
+    /// Global program state storing the feature flags
+    /// Seeds: [STATE_SEED]
+    /// Required permissions: Read-only
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        seeds::program = ID
+    )]
+    pub state: Account<'info, BridgeCardsState>,
 
     /// User's token account from which tokens will be transferred
     /// Must have the same mint as the destination account
     /// Required permissions: Mutable
-    #[account(mut, constraint = user_token_account.mint.key() == mint.key())]
+    #[account(mut, constraint = user_token_account.mint.key() == mint.key() @ ErrorCode::MismatchedMint)]
     pub user_token_account: InterfaceAccount<'info, TokenAccount>,
 
+    /// Owner of the user's token account
+    /// Required permissions: Read-only
+    /// CHECK: Must be the owner recorded in the user token account
+    #[account(address = user_token_account.owner)]
+    pub user_owner: UncheckedAccount<'info>,
+
     /// The mint of the tokens being transferred
     /// Used to verify token account compatibility and for PDA derivation
     /// Required permissions: Read-only
@@ -98,6 +215,43 @@ pub struct DebitUser<'info> {
     /// Required Solana system programs
     pub system_program: Program<'info, System>,
     pub token_program: Interface<'info, TokenInterface>,
+
+    /// PDA tracking the merchant's spend across all of its delegates
+    /// Seeds: [MERCHANT_SPEND_SEED, merchant_id]
+    /// Required permissions: Mutable (updates period tracking)
+    /// CHECK: Address is verified by seeds; no merchant-wide limit applies if it has not been
+    /// created
+    #[account(mut,
+        seeds = [MERCHANT_SPEND_SEED, merchant_id_seed(merchant_id).as_ref()],
+        bump,
+        seeds::program = ID
+    )]
+    pub merchant_spend_tracker: UncheckedAccount<'info>,
+
+    /// Token account of the protocol fee owner receiving the protocol fee
+    /// Required when the program's protocol fee is set
+    /// Required permissions: Mutable
+    #[account(mut, constraint = fee_token_account.mint == mint.key() @ ErrorCode::MismatchedMint)]
+    pub fee_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// PDA allowlisting the mint for the merchant
+    /// Seeds: [MERCHANT_MINT_SEED, merchant_id, mint]
+    /// Required when the merchant config enforces the mint allowlist
+    /// Required permissions: Read-only
+    /// CHECK: Address is verified by seeds; the mint is not allowlisted if it has not been created
+    #[account(
+        seeds = [MERCHANT_MINT_SEED, merchant_id_seed(merchant_id).as_ref(), mint.key().as_ref()],
+        bump,
+        seeds::program = ID
+    )]
+    pub merchant_mint: Option<UncheckedAccount<'info>>,
+
+    /// PDA receiving the receipt of this debit
+    /// Seeds: [DEBIT_RECEIPT_SEED, user_delegate_account, debit_nonce of this debit]
+    /// Required permissions: Mutable (account is being created)
+    /// CHECK: Address is verified against the debit's nonce once the debit is validated
+    #[account(mut)]
+    pub debit_receipt: Option<UncheckedAccount<'info>>,
 }
 
 /**
@@ -115,52 +269,734 @@ pub struct DebitUser<'info> {
  * @return Result indicating success or containing an error
  */
 pub fn handler(ctx: Context<DebitUser>, merchant_id: u64, amount: u64) -> Result<()> {
-    // Validate transfer limits and update period tracking
-    let clock = Clock::get()?;
-    ctx.accounts
-        .user_delegate_account
-        .validate_debit_and_update(amount, clock.unix_timestamp as u64, clock.slot)?;
+    process_debit(ctx.accounts, merchant_id, amount)
+}
+
+/**
+ * Validate limits, transfer the tokens and emit the UserDebited event.
+ *
+ * Shared by every instruction that debits a user into the destination token account of the
+ * DebitUser accounts.
+ *
+ * @param accounts The validated DebitUser accounts
+ * @param merchant_id Unique identifier for the merchant
+ * @param amount Number of tokens to transfer (in smallest units)
+ *
+ * @return Result indicating success or containing an error
+ */
+pub(crate) fn process_debit(accounts: &mut DebitUser, merchant_id: u64, amount: u64) -> Result<()> {
+    process_debit_with_memo(accounts, merchant_id, amount, &[])
+}
+
+/**
+ * Debit a user as process_debit does, attaching a merchant reference to the UserDebited event.
+ *
+ * @param accounts The validated DebitUser accounts
+ * @param merchant_id Unique identifier for the merchant
+ * @param amount Number of tokens to transfer (in smallest units)
+ * @param memo Merchant reference recorded in the event, empty for none
+ *
+ * @return Result indicating success or containing an error
+ */
+pub(crate) fn process_debit_with_memo(
+    accounts: &mut DebitUser,
+    merchant_id: u64,
+    amount: u64,
+    memo: &[u8],
+) -> Result<()> {
+    let protocol_fee = validate_debit(accounts, merchant_id, amount)?;
 
     // Derive the PDA signer seeds for the delegate account
-    let merchant_id_bytes = merchant_id.to_le_bytes();
+    let merchant_id_bytes = merchant_id_seed(merchant_id);
     let seeds = [
         USER_DELEGATE_SEED,
         merchant_id_bytes.as_ref(),
-        ctx.accounts.mint.to_account_info().key.as_ref(),
-        ctx.accounts
-            .user_token_account
-            .to_account_info()
-            .key
-            .as_ref(),
-        &[ctx.accounts.user_delegate_account.bump],
+        accounts.mint.to_account_info().key.as_ref(),
+        accounts.user_token_account.to_account_info().key.as_ref(),
+        &[accounts.user_delegate_account.bump],
     ];
     let signer_seeds = &[&seeds[..]];
 
+    // Read the decimals once so the destination and fee legs use the same validated value
+    let decimals = mint_decimals(&accounts.mint.to_account_info())?;
+
     // Execute the token transfer with amount and decimal validation
-    token_interface::transfer_checked(
+    let clock = Clock::get()?;
+    let post_condition_epoch = accounts
+        .state
+        .has_feature_flag(feature_flags::POST_CONDITION_CHECKS)
+        .then_some(clock.epoch);
+    transfer_checked_verified(
         CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
+            accounts.token_program.to_account_info(),
             token_interface::TransferChecked {
-                from: ctx.accounts.user_token_account.to_account_info(),
-                to: ctx.accounts.destination_token_account.to_account_info(),
-                mint: ctx.accounts.mint.to_account_info(),
-                authority: ctx.accounts.user_delegate_account.to_account_info(),
+                from: accounts.user_token_account.to_account_info(),
+                to: accounts.destination_token_account.to_account_info(),
+                mint: accounts.mint.to_account_info(),
+                authority: accounts.user_delegate_account.to_account_info(),
             },
             signer_seeds,
         ),
-        amount,
-        ctx.accounts.mint.decimals,
+        amount - protocol_fee,
+        decimals,
+        post_condition_epoch,
     )?;
+    transfer_protocol_fee(
+        accounts,
+        protocol_fee,
+        decimals,
+        signer_seeds,
+        post_condition_epoch,
+    )?;
+    write_debit_receipt(accounts, amount, clock.unix_timestamp)?;
 
     emit!(UserDebited {
-        debitor: ctx.accounts.debitor.key(),
-        user_delegate: ctx.accounts.user_delegate_account.key(),
+        debitor: accounts.debitor.key(),
+        user_delegate: accounts.user_delegate_account.key(),
         merchant_id,
-        user_ata: ctx.accounts.user_token_account.key(),
-        destination_ata: ctx.accounts.destination_token_account.key(),
-        mint: ctx.accounts.mint.key(),
+        user_ata: accounts.user_token_account.key(),
+        destination_ata: accounts.destination_token_account.key(),
+        mint: accounts.mint.key(),
         amount,
+        slot: clock.slot,
+        timestamp: clock.unix_timestamp,
+        debit_nonce: accounts.user_delegate_account.debit_nonce,
+        protocol_fee,
+        memo: memo.to_vec(),
     });
 
     Ok(())
 }
+
+/**
+ * Run every check of a debit and record it against the delegate, debitor and merchant limits,
+ * without moving any tokens.
+ *
+ * @param accounts The validated DebitUser accounts
+ * @param merchant_id Unique identifier for the merchant
+ * @param amount Number of tokens to transfer (in smallest units)
+ *
+ * @return The protocol fee owed on the amount, or an error if the debit is not allowed
+ */
+pub(crate) fn validate_debit(
+    accounts: &mut DebitUser,
+    merchant_id: u64,
+    amount: u64,
+) -> Result<u64> {
+    // The debitor state's address includes the mint, so it only exists for the mints the
+    // debitor was authorized for
+    let mut debitor_state = MerchantDebitorState::load_authorized(&accounts.debitor_state)?;
+    if accounts.state.has_feature_flag(feature_flags::PAUSED) {
+        return Err(ErrorCode::ProgramPaused.into());
+    }
+
+    let token_program = accounts.token_program.key();
+    check_debit_token_accounts(
+        &token_program,
+        &accounts.user_token_account,
+        &accounts.destination_token_account,
+    )?;
+
+    // The protocol fee is part of the debit: the limits apply to the amount, fee included
+    let protocol_fee = accounts.state.protocol_fee(amount)?;
+    if protocol_fee > 0 {
+        verify_fee_token_account(
+            &accounts.state,
+            accounts.fee_token_account.as_ref(),
+            &token_program,
+        )?;
+    }
+
+    let merchant_config = MerchantConfigState::load_or_default(&accounts.merchant_config)?;
+    check_merchant_debit(
+        &merchant_config,
+        accounts.merchant_mint.as_deref(),
+        &accounts.user_owner,
+    )?;
+    // The destination state's address is checked by seeds, so it can't be omitted to dodge
+    // the denylist
+    let mut destination_state =
+        MerchantDestinationState::load_optional(&accounts.destination_state)?;
+    check_debit_destination(
+        &merchant_config,
+        &accounts.user_delegate_account,
+        destination_state.as_ref(),
+        &accounts.destination_token_account,
+    )?;
+
+    // Validate transfer limits and update period tracking
+    let policy = DebitPolicy::resolve(&merchant_config, &accounts.user_delegate_account);
+    let clock = Clock::get()?;
+    accounts
+        .user_delegate_account
+        .check_active(clock.unix_timestamp)?;
+    if accounts
+        .state
+        .has_feature_flag(feature_flags::CLOCK_ANOMALY_CHECKS)
+    {
+        accounts.user_delegate_account.check_clock(
+            clock.unix_timestamp,
+            clock.slot,
+            merchant_config.max_clock_skew_seconds(),
+        )?;
+    }
+    let period_reset = accounts
+        .user_delegate_account
+        .validate_debit_and_update_with_policy(
+            amount,
+            clock.unix_timestamp as u64,
+            clock.slot,
+            &policy,
+        )?;
+    // The debitor's own caps apply on top of the delegate's limits
+    debitor_state.record_debit(amount, clock.unix_timestamp as u64)?;
+    // The spend tracker's address is checked by seeds, so it can't be omitted to dodge the
+    // merchant's limit
+    let mut merchant_spend_tracker =
+        MerchantSpendTrackerState::load_optional(&accounts.merchant_spend_tracker)?;
+    if let Some(merchant_spend_tracker) = &mut merchant_spend_tracker {
+        merchant_spend_tracker.record_debit(amount, clock.unix_timestamp as u64)?;
+    }
+    if period_reset {
+        emit!(UserDelegatePeriodReset {
+            merchant_id,
+            user_delegate: accounts.user_delegate_account.key(),
+            period_timestamp_last_reset: accounts.user_delegate_account.period_timestamp_last_reset,
+        });
+    }
+
+    verify_delegate_approval(
+        &accounts.user_token_account,
+        &accounts.user_delegate_account.key(),
+        amount,
+    )?;
+
+    // The destination receives the amount net of the protocol fee
+    if let Some(destination_state) = &mut destination_state {
+        destination_state.record_receipt(amount - protocol_fee, clock.unix_timestamp);
+    }
+
+    persist_debit_usage(
+        accounts,
+        &debitor_state,
+        destination_state.as_ref(),
+        merchant_spend_tracker.as_ref(),
+    )?;
+
+    Ok(protocol_fee)
+}
+
+/**
+ * Check the token accounts of a debit, without updating any state.
+ *
+ * Shared by every debit path, including simulate_debit_user, so a simulation rejects exactly
+ * what the debit would.
+ *
+ * @param token_program The token program of the debit
+ * @param user_token_account The token account being debited
+ * @param destination_token_account The token account receiving the debit
+ *
+ * @return Result indicating success or containing an error
+ */
+pub(crate) fn check_debit_token_accounts(
+    token_program: &Pubkey,
+    user_token_account: &InterfaceAccount<TokenAccount>,
+    destination_token_account: &InterfaceAccount<TokenAccount>,
+) -> Result<()> {
+    // transfer_checked cannot move tokens between accounts of different token programs
+    if user_token_account.to_account_info().owner != token_program
+        || destination_token_account.to_account_info().owner != token_program
+    {
+        return Err(ErrorCode::CrossProgramTransferNotAllowed.into());
+    }
+    // A frozen account would otherwise only fail inside the transfer CPI
+    if user_token_account.is_frozen() || destination_token_account.is_frozen() {
+        return Err(ErrorCode::TokenAccountFrozen.into());
+    }
+    // Even if allowlisted, the user's own account is never a destination
+    if destination_token_account.key() == user_token_account.key() {
+        return Err(ErrorCode::SelfTransfer.into());
+    }
+    Ok(())
+}
+
+/**
+ * Check a debit against the merchant-wide settings of its merchant config, without updating
+ * any state.
+ *
+ * @param merchant_config The merchant's config, or the default settings
+ * @param merchant_mint The merchant mint PDA passed with the debit, if any
+ * @param user_owner The owner of the user token account
+ *
+ * @return Result indicating success or containing an error
+ */
+pub(crate) fn check_merchant_debit(
+    merchant_config: &MerchantConfigState,
+    merchant_mint: Option<&AccountInfo>,
+    user_owner: &AccountInfo,
+) -> Result<()> {
+    if merchant_config.paused {
+        return Err(ErrorCode::MerchantPaused.into());
+    }
+    merchant_config.check_mint(merchant_mint)?;
+    if merchant_config.require_user_owner_system_owned
+        && user_owner.owner != &anchor_lang::system_program::ID
+    {
+        return Err(ErrorCode::UserOwnerNotSystemOwned.into());
+    }
+    Ok(())
+}
+
+/**
+ * Check that a debit's destination is allowed for the merchant and the delegate, without
+ * updating any state.
+ *
+ * @param merchant_config The merchant's config, or the default settings
+ * @param user_delegate The delegate being debited
+ * @param destination_state The destination's state, if it exists
+ * @param destination_token_account The token account receiving the debit
+ *
+ * @return Result indicating success or containing an error
+ */
+pub(crate) fn check_debit_destination(
+    merchant_config: &MerchantConfigState,
+    user_delegate: &UserDelegateState,
+    destination_state: Option<&MerchantDestinationState>,
+    destination_token_account: &InterfaceAccount<TokenAccount>,
+) -> Result<()> {
+    let is_primary =
+        merchant_config.check_destination(destination_state, &destination_token_account.owner)?;
+    if merchant_config.enforce_primary_destination
+        && !is_primary
+        && !user_delegate.allow_alternate_destination
+    {
+        return Err(ErrorCode::NonPrimaryDestination.into());
+    }
+    user_delegate.check_destination(&destination_token_account.key())
+}
+
+/**
+ * Write the usage recorded by validate_debit to the account data before any CPI.
+ *
+ * Anchor only serializes accounts when the instruction exits, so until then the account data
+ * still holds the usage from before the debit. The token program can't call back into this
+ * program, but writing the usage first keeps the limits sound regardless of what the
+ * transfer invokes. Anchor serializes the same state again on exit. The debitor, destination
+ * and spend tracker states are loaded by validate_debit rather than Anchor, so this is their
+ * only write.
+ *
+ * @param accounts The validated DebitUser accounts
+ * @param debitor_state The debitor state with the debit recorded
+ * @param destination_state The destination state with the receipt recorded, if it exists
+ * @param merchant_spend_tracker The spend tracker with the debit recorded, if it exists
+ *
+ * @return Result indicating success or containing an error
+ */
+pub(crate) fn persist_debit_usage(
+    accounts: &DebitUser,
+    debitor_state: &MerchantDebitorState,
+    destination_state: Option<&MerchantDestinationState>,
+    merchant_spend_tracker: Option<&MerchantSpendTrackerState>,
+) -> Result<()> {
+    accounts.user_delegate_account.exit(&ID)?;
+    debitor_state.store(&accounts.debitor_state)?;
+    if let Some(destination_state) = destination_state {
+        destination_state.store(&accounts.destination_state)?;
+    }
+    if let Some(merchant_spend_tracker) = merchant_spend_tracker {
+        merchant_spend_tracker.store(&accounts.merchant_spend_tracker)?;
+    }
+    Ok(())
+}
+
+/**
+ * Create the receipt of a validated debit, if the debit_receipt account was passed.
+ *
+ * The receipt is derived from the delegate's debit_nonce, which validate_debit has already
+ * advanced to this debit's nonce.
+ *
+ * @param accounts The validated DebitUser accounts
+ * @param amount Number of tokens debited, protocol fee included
+ * @param timestamp Unix timestamp of the debit
+ *
+ * @return Result indicating success, or InvalidPda if the receipt is not this debit's PDA
+ */
+pub(crate) fn write_debit_receipt(accounts: &DebitUser, amount: u64, timestamp: i64) -> Result<()> {
+    let Some(debit_receipt) = &accounts.debit_receipt else {
+        return Ok(());
+    };
+    let user_delegate = accounts.user_delegate_account.key();
+    let debit_nonce = accounts.user_delegate_account.debit_nonce;
+    let (debit_receipt_pda, bump) = find_debit_receipt(&user_delegate, debit_nonce);
+    if debit_receipt.key() != debit_receipt_pda {
+        return Err(ErrorCode::InvalidPda.into());
+    }
+
+    let debit_nonce_bytes = debit_nonce.to_le_bytes();
+    let seeds = [
+        DEBIT_RECEIPT_SEED,
+        user_delegate.as_ref(),
+        debit_nonce_bytes.as_ref(),
+        &[bump],
+    ];
+    let receipt_info = debit_receipt.to_account_info();
+    create_debit_receipt(accounts, &receipt_info, &seeds)?;
+
+    let receipt = DebitReceiptState {
+        version: CURRENT_VERSION,
+        user_delegate,
+        debit_nonce,
+        amount,
+        destination: accounts.destination_token_account.key(),
+        mint: accounts.mint.key(),
+        timestamp,
+        bump,
+    };
+    receipt.try_serialize(&mut &mut receipt_info.data.borrow_mut()[..])?;
+    Ok(())
+}
+
+/// Create the debit receipt account at its PDA, funding any missing rent from the payer
+fn create_debit_receipt<'info>(
+    accounts: &DebitUser<'info>,
+    receipt_info: &AccountInfo<'info>,
+    seeds: &[&[u8]],
+) -> Result<()> {
+    let system_program = accounts.system_program.to_account_info();
+    let space = DebitReceiptState::SPACE;
+    let rent = Rent::get()?.minimum_balance(space);
+
+    // Lamports sent to the address beforehand must not prevent its creation
+    let shortfall = rent.saturating_sub(receipt_info.lamports());
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: accounts.payer.to_account_info(),
+                    to: receipt_info.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    system_program::allocate(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            system_program::Allocate {
+                account_to_allocate: receipt_info.clone(),
+            },
+            &[seeds],
+        ),
+        space as u64,
+    )?;
+    system_program::assign(
+        CpiContext::new_with_signer(
+            system_program,
+            system_program::Assign {
+                account_to_assign: receipt_info.clone(),
+            },
+            &[seeds],
+        ),
+        &ID,
+    )
+}
+
+/**
+ * Transfer the protocol fee of a debit to the fee token account, signed by the delegate PDA.
+ *
+ * @param accounts The validated DebitUser accounts
+ * @param protocol_fee Protocol fee owed on the debit (nothing is transferred if 0)
+ * @param decimals Decimals of the mint, as returned by mint_decimals
+ * @param signer_seeds Signer seeds of the delegate PDA
+ * @param post_condition_epoch Epoch used to verify the transfer, or None to skip the check
+ *
+ * @return Result indicating success or containing an error
+ */
+pub(crate) fn transfer_protocol_fee(
+    accounts: &mut DebitUser,
+    protocol_fee: u64,
+    decimals: u8,
+    signer_seeds: &[&[&[u8]]],
+    post_condition_epoch: Option<u64>,
+) -> Result<()> {
+    if let Some(fee_token_account) = accounts
+        .fee_token_account
+        .as_ref()
+        .filter(|_| protocol_fee > 0)
+    {
+        transfer_checked_verified(
+            CpiContext::new_with_signer(
+                accounts.token_program.to_account_info(),
+                token_interface::TransferChecked {
+                    from: accounts.user_token_account.to_account_info(),
+                    to: fee_token_account.to_account_info(),
+                    mint: accounts.mint.to_account_info(),
+                    authority: accounts.user_delegate_account.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            protocol_fee,
+            decimals,
+            post_condition_epoch,
+        )?;
+    }
+    Ok(())
+}
+
+/**
+ * Check that the user token account approves the delegate PDA for at least the amount, so a
+ * missing or spent approval surfaces as a program error rather than a raw token error.
+ *
+ * @param user_token_account The token account being debited
+ * @param user_delegate The delegate PDA signing the transfer
+ * @param amount Number of tokens to transfer (in smallest units)
+ *
+ * @return Result indicating success or containing an error
+ */
+pub(crate) fn verify_delegate_approval(
+    user_token_account: &InterfaceAccount<TokenAccount>,
+    user_delegate: &Pubkey,
+    amount: u64,
+) -> Result<()> {
+    if user_token_account.delegate != COption::Some(*user_delegate) {
+        return Err(ErrorCode::DelegateMismatch.into());
+    }
+    if user_token_account.delegated_amount < amount {
+        return Err(ErrorCode::InsufficientDelegatedAmount.into());
+    }
+    Ok(())
+}
+
+/**
+ * Check that a debit owing a protocol fee can pay it into the fee token account.
+ *
+ * @param state The global program state holding the protocol fee owner
+ * @param fee_token_account The fee token account passed with the debit, if any
+ * @param token_program The token program of the debit
+ *
+ * @return Result indicating success or containing an error
+ */
+pub(crate) fn verify_fee_token_account(
+    state: &BridgeCardsState,
+    fee_token_account: Option<&InterfaceAccount<TokenAccount>>,
+    token_program: &Pubkey,
+) -> Result<()> {
+    let fee_token_account = fee_token_account.ok_or(ErrorCode::InvalidProtocolFeeAccount)?;
+    if fee_token_account.owner != state.protocol_fee_owner {
+        return Err(ErrorCode::InvalidProtocolFeeAccount.into());
+    }
+    if fee_token_account.to_account_info().owner != token_program {
+        return Err(ErrorCode::CrossProgramTransferNotAllowed.into());
+    }
+    if fee_token_account.is_frozen() {
+        return Err(ErrorCode::TokenAccountFrozen.into());
+    }
+    Ok(())
+}
+
+/**
+ * Read the decimals of a mint from its account data.
+ *
+ * Debits read the decimals once with this helper and pass the value to every transfer leg,
+ * which checks it against the mint of its own CPI with verify_decimals.
+ *
+ * @param mint The mint of the tokens being transferred (token or token-2022)
+ *
+ * @return The decimals of the mint
+ */
+pub(crate) fn mint_decimals(mint: &AccountInfo) -> Result<u8> {
+    let data = mint.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    Ok(mint_state.base.decimals)
+}
+
+/**
+ * Check that the decimals passed to a transfer match the mint it transfers.
+ *
+ * transfer_checked would reject a mismatch with a token program error; checking first surfaces
+ * it as DecimalsMismatch, e.g. if a transfer leg is ever given a different mint than the one
+ * its decimals were read from.
+ *
+ * @param mint The mint of the transfer's CPI
+ * @param decimals Decimals the transfer will be made with
+ *
+ * @return Result indicating success or containing an error
+ */
+pub(crate) fn verify_decimals(mint: &AccountInfo, decimals: u8) -> Result<()> {
+    if mint_decimals(mint)? != decimals {
+        return Err(ErrorCode::DecimalsMismatch.into());
+    }
+    Ok(())
+}
+
+/**
+ * Execute transfer_checked and, if post_condition_epoch is set, verify that the destination
+ * received exactly the amount net of the mint's token-2022 transfer fee for that epoch.
+ * The decimals are checked against the mint with verify_decimals first.
+ *
+ * The destination's balance is read from its account data around the transfer, so earlier
+ * transfers to the same account in this instruction are counted, and token accounts created
+ * within the instruction can be checked too.
+ *
+ * @param ctx CPI context of the transfer, signed by the delegate PDA
+ * @param amount Number of tokens to transfer (in smallest units)
+ * @param decimals Decimals of the mint, as returned by mint_decimals
+ * @param post_condition_epoch Epoch used to compute the fee, or None to skip the check
+ *
+ * @return Result indicating success or containing an error
+ */
+pub(crate) fn transfer_checked_verified<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, token_interface::TransferChecked<'info>>,
+    amount: u64,
+    decimals: u8,
+    post_condition_epoch: Option<u64>,
+) -> Result<()> {
+    verify_decimals(&ctx.accounts.mint, decimals)?;
+
+    let Some(epoch) = post_condition_epoch else {
+        return token_interface::transfer_checked(ctx, amount, decimals);
+    };
+
+    let destination = ctx.accounts.to.clone();
+    let destination_balance_before = token_account_amount(&destination)?;
+    let expected_fee = expected_transfer_fee(&ctx.accounts.mint, amount, epoch)?;
+
+    token_interface::transfer_checked(ctx, amount, decimals)?;
+
+    let received = token_account_amount(&destination)?.checked_sub(destination_balance_before);
+    if received != amount.checked_sub(expected_fee) {
+        return Err(ErrorCode::PostConditionFailed.into());
+    }
+    Ok(())
+}
+
+/// Current balance of a token account of either token program
+fn token_account_amount(token_account: &AccountInfo) -> Result<u64> {
+    let token_account = TokenAccount::try_deserialize(&mut &token_account.try_borrow_data()?[..])?;
+    Ok(token_account.amount)
+}
+
+/// Transfer fee withheld by a token-2022 mint for the given amount (0 for other mints)
+fn expected_transfer_fee(mint: &AccountInfo, amount: u64, epoch: u64) -> Result<u64> {
+    match epoch_transfer_fee(mint, epoch)? {
+        Some(transfer_fee) => transfer_fee
+            .calculate_fee(amount)
+            .ok_or_else(|| ErrorCode::PostConditionFailed.into()),
+        None => Ok(0),
+    }
+}
+
+/**
+ * Compute the amount to debit so that the destination receives exactly net_amount after the
+ * mint's token-2022 transfer fee for the given epoch. Mints without the transfer fee extension
+ * return net_amount unchanged.
+ *
+ * @param mint The mint of the tokens being transferred
+ * @param net_amount Number of tokens the destination must receive (in smallest units)
+ * @param epoch Epoch used to select the transfer fee
+ *
+ * @return The smallest gross amount whose transfer nets net_amount
+ */
+pub(crate) fn gross_transfer_amount(
+    mint: &AccountInfo,
+    net_amount: u64,
+    epoch: u64,
+) -> Result<u64> {
+    match epoch_transfer_fee(mint, epoch)? {
+        Some(transfer_fee) => transfer_fee
+            .calculate_pre_fee_amount(net_amount)
+            .ok_or_else(|| ErrorCode::ArithmeticOverflow.into()),
+        None => Ok(net_amount),
+    }
+}
+
+/// Transfer fee in effect for a token-2022 mint at the given epoch (None for other mints)
+fn epoch_transfer_fee(mint: &AccountInfo, epoch: u64) -> Result<Option<TransferFee>> {
+    if mint.owner != &spl_token_2022::ID {
+        return Ok(None);
+    }
+    let data = mint.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    Ok(mint_state
+        .get_extension::<TransferFeeConfig>()
+        .ok()
+        .map(|transfer_fee_config| *transfer_fee_config.get_epoch_fee(epoch)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use anchor_spl::token::spl_token;
+
+    fn mint_data(decimals: u8) -> Vec<u8> {
+        let mut data = vec![0u8; spl_token_2022::state::Mint::LEN];
+        spl_token_2022::state::Mint {
+            mint_authority: COption::None,
+            supply: 0,
+            decimals,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        }
+        .pack_into_slice(&mut data);
+        data
+    }
+
+    #[test]
+    fn test_mint_decimals_validated() {
+        for decimals in [0u8, 9] {
+            let mint_key = Pubkey::new_unique();
+            let mut lamports = 1_000;
+            let mut data = mint_data(decimals);
+            let mint = AccountInfo::new(
+                &mint_key,
+                false,
+                false,
+                &mut lamports,
+                &mut data,
+                &spl_token_2022::ID,
+                false,
+                0,
+            );
+
+            assert_eq!(mint_decimals(&mint).unwrap(), decimals);
+            assert!(verify_decimals(&mint, decimals).is_ok());
+            for other in [0u8, 6, 9].into_iter().filter(|other| *other != decimals) {
+                assert_eq!(
+                    verify_decimals(&mint, other).unwrap_err(),
+                    ErrorCode::DecimalsMismatch.into()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_token_account_amount() {
+        for program_id in [spl_token::ID, spl_token_2022::ID] {
+            let token_account_key = Pubkey::new_unique();
+            let mut lamports = 1_000;
+            let mut data = vec![0u8; spl_token::state::Account::LEN];
+            spl_token::state::Account {
+                mint: Pubkey::new_unique(),
+                owner: Pubkey::new_unique(),
+                amount: 42,
+                state: spl_token::state::AccountState::Initialized,
+                ..Default::default()
+            }
+            .pack_into_slice(&mut data);
+            let token_account = AccountInfo::new(
+                &token_account_key,
+                false,
+                true,
+                &mut lamports,
+                &mut data,
+                &program_id,
+                false,
+                0,
+            );
+
+            assert_eq!(token_account_amount(&token_account).unwrap(), 42);
+        }
+    }
+}
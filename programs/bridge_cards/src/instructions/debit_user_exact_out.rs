@@ -0,0 +1,47 @@
+use crate::instructions::debit_user::*;
+use anchor_lang::prelude::*;
+
+/**
+ * Debit a user so that the destination receives exactly the requested amount.
+ *
+ * With the token-2022 transfer fee extension the amount leaving the user's token account and
+ * the amount arriving at the destination differ by the fee withheld on the destination. This
+ * instruction takes the amount the destination must receive, grosses it up by the mint's
+ * transfer fee for the current epoch, and debits the grossed-up amount. Mints without the
+ * extension debit the amount unchanged, exactly as debit_user does. When the program's
+ * protocol fee is set, the amount is also grossed up by the fee so the destination still
+ * receives the requested amount.
+ *
+ * Security Checks:
+ * - All debit_user checks apply, to the grossed-up amount
+ * - The grossed-up amount is what counts against the delegate's per-transfer and period
+ *   limits, the delegate approval and the merchant spend tracker
+ *
+ * Accounts:
+ * - Identical to debit_user (DebitUser)
+ *
+ * Events Emitted:
+ * - UserDebited: amount is the grossed-up amount debited from the user
+ *
+ * Common Errors:
+ * - ArithmeticOverflow: The grossed-up amount does not fit in a u64
+ * - Every debit_user error, evaluated against the grossed-up amount
+ *
+ * @param ctx The instruction context containing all required accounts
+ * @param merchant_id Unique identifier for the merchant
+ * @param amount Number of tokens the destination must receive (in smallest units)
+ *
+ * Flow:
+ * 1. Look up the mint's transfer fee for the current epoch
+ * 2. Compute the smallest gross amount that nets the requested amount
+ * 3. Gross that amount up by the protocol fee
+ * 4. Debit the gross amount exactly as debit_user does
+ *
+ * @return Result indicating success or containing an error
+ */
+pub fn handler(ctx: Context<DebitUser>, merchant_id: u64, amount: u64) -> Result<()> {
+    let epoch = Clock::get()?.epoch;
+    let gross_amount = gross_transfer_amount(&ctx.accounts.mint.to_account_info(), amount, epoch)?;
+    let gross_amount = ctx.accounts.state.gross_of_protocol_fee(gross_amount)?;
+    process_debit(ctx.accounts, merchant_id, gross_amount)
+}
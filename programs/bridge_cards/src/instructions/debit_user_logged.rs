@@ -0,0 +1,93 @@
+use crate::errors::ErrorCode;
+use crate::events::DebitRejected;
+use crate::instructions::debit_user::*;
+use crate::instructions::simulate_debit_user::reason_code;
+use anchor_lang::prelude::*;
+
+/**
+ * Debit a user, recording a rejection by the limit checks as an event instead of failing.
+ *
+ * debit_user reverts when a limit check fails, so the attempt never reaches indexers. This
+ * instruction debits exactly as debit_user does, but when the debit exceeds a limit it emits
+ * a DebitRejected event and succeeds without transferring anything, so failed attempts can
+ * be monitored for fraud.
+ *
+ * Security Checks:
+ * - All debit_user checks apply; only limit failures are recorded, every other failure
+ *   (authorization, destination, pause, approval, ...) still reverts
+ * - Limit checks run before any transfer, so a rejected debit moves no tokens
+ * - A rejected debit leaves the delegate, debitor and merchant spend tracker states
+ *   unchanged, as if debit_user had reverted
+ * - Success does not mean the user was debited: integrators must check for the UserDebited
+ *   event (or the absence of DebitRejected) before treating the debit as paid
+ *
+ * Recorded Limit Failures:
+ * - DelegateNotYetValid, DelegateExpired
+ * - BelowMinTransferAmount, ExceedsMaxTransferLimit, ExceedsTransferLimitPerPeriod
+ * - ExceedsMaxTransactionsPerSlot
+ * - ExceedsDebitorPerCallLimit, ExceedsDebitorDailyLimit
+ * - ExceedsMerchantPeriodLimit
+ *
+ * Accounts:
+ * - Identical to debit_user (DebitUser)
+ *
+ * Events Emitted:
+ * - UserDebited: When the debit is within the limits, as for debit_user
+ * - DebitRejected: When a limit check fails
+ *   Fields: merchant_id, user_delegate, debitor, amount, reason_code, slot, timestamp
+ *
+ * @param ctx The instruction context containing all required accounts
+ * @param merchant_id Unique identifier for the merchant
+ * @param amount Number of tokens to transfer (in smallest units)
+ *
+ * Flow:
+ * 1. Debit the user exactly as debit_user does
+ * 2. On a limit failure, restore the tracked states and emit DebitRejected
+ *
+ * @return Result indicating success or containing an error; a debit rejected by a limit
+ * check still succeeds
+ */
+pub fn handler(ctx: Context<DebitUser>, merchant_id: u64, amount: u64) -> Result<()> {
+    let accounts = ctx.accounts;
+    // The limit checks update the delegate before the later checks run, so a late failure
+    // would leave the earlier updates in place. The debitor, destination and spend tracker
+    // states are only written once every check has passed.
+    let user_delegate = (*accounts.user_delegate_account).clone();
+
+    match process_debit(accounts, merchant_id, amount) {
+        Err(error) if is_limit_error(&error) => {
+            *accounts.user_delegate_account = user_delegate;
+
+            let clock = Clock::get()?;
+            emit!(DebitRejected {
+                merchant_id,
+                user_delegate: accounts.user_delegate_account.key(),
+                debitor: accounts.debitor.key(),
+                amount,
+                reason_code: reason_code(&error),
+                slot: clock.slot,
+                timestamp: clock.unix_timestamp,
+            });
+            Ok(())
+        }
+        result => result,
+    }
+}
+
+/// Whether the error comes from a limit check, which runs before any transfer
+fn is_limit_error(error: &Error) -> bool {
+    const LIMIT_ERRORS: [ErrorCode; 9] = [
+        ErrorCode::DelegateNotYetValid,
+        ErrorCode::DelegateExpired,
+        ErrorCode::BelowMinTransferAmount,
+        ErrorCode::ExceedsMaxTransferLimit,
+        ErrorCode::ExceedsTransferLimitPerPeriod,
+        ErrorCode::ExceedsMaxTransactionsPerSlot,
+        ErrorCode::ExceedsDebitorPerCallLimit,
+        ErrorCode::ExceedsDebitorDailyLimit,
+        ErrorCode::ExceedsMerchantPeriodLimit,
+    ];
+    LIMIT_ERRORS
+        .iter()
+        .any(|limit_error| reason_code(&(*limit_error).into()) == reason_code(error))
+}
@@ -0,0 +1,257 @@
+use crate::errors::ErrorCode;
+use crate::events::UserDebited;
+use crate::instructions::add_or_update_user_delegate::USER_DELEGATE_SEED;
+use crate::instructions::debit_user::*;
+use crate::pda::merchant_id_seed;
+use crate::state::feature_flags;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::system_program;
+use anchor_spl::token::spl_token::{self, state::Account as SplTokenAccount};
+use anchor_spl::token_2022::spl_token_2022;
+use anchor_spl::token_interface;
+
+pub const SOL_DEBIT_SEED: &[u8] = b"sol_debit";
+
+/**
+ * Debit wrapped SOL from a user's token account and pay the merchant in native SOL.
+ *
+ * The delegate PDA cannot move lamports out of a user's wallet: the system program only
+ * debits accounts that sign. Users therefore authorize SOL debits the same way as any other
+ * token, by wrapping SOL into a token account of the native mint and approving the delegate
+ * PDA on it. Limits are those of the user delegate for the native mint.
+ *
+ * The debit is checked and recorded exactly as debit_user does, with the merchant's allowlisted
+ * wrapped SOL token account as the destination. The net amount is then moved into a temporary
+ * token account owned by the delegate PDA, which is closed to unwrap it, and the unwrapped SOL
+ * is sent to the owner of the destination token account. The temporary account's rent is
+ * refunded to the rent payer within the instruction.
+ *
+ * Security Checks:
+ * - All debit_user checks apply, with the destination token account as the destination
+ * - The mint must be the native mint of the Token or Token-2022 program
+ * - The SOL recipient must be the owner of the destination token account
+ *
+ * Account Derivation:
+ * - Temporary token account PDA: [SOL_DEBIT_SEED, user_delegate_account]
+ * - Other PDAs as in debit_user (passed in debit)
+ *
+ * Events Emitted:
+ * - UserDebited: As for debit_user; destination_ata is the allowlisted destination token
+ *   account whose owner received the SOL
+ *
+ * Common Errors:
+ * - NotNativeMint: The debit's mint is not the native mint
+ * - The debit_user errors (e.g. ExceedsMaxTransferLimit)
+ */
+#[derive(Accounts)]
+pub struct DebitUserSol<'info> {
+    /// Accounts required by the underlying debit, on the native mint
+    pub debit: DebitUser<'info>,
+
+    /// Account funding the temporary token account, refunded within the instruction
+    /// Required permissions: Signer, Mutable
+    #[account(mut)]
+    pub rent_payer: Signer<'info>,
+
+    /// Wallet receiving the unwrapped SOL
+    /// Must be the owner of the destination token account
+    /// Required permissions: Mutable
+    #[account(
+        mut,
+        address = debit.destination_token_account.owner,
+        constraint = is_native_mint(&debit.mint.key()) @ ErrorCode::NotNativeMint
+    )]
+    pub destination_wallet: SystemAccount<'info>,
+
+    /// Temporary wrapped SOL token account owned by the delegate PDA, created and closed by the
+    /// instruction
+    /// Seeds: [SOL_DEBIT_SEED, user_delegate_account]
+    /// Required permissions: Mutable
+    /// CHECK: Address is verified by seeds; created as a token account in the handler
+    #[account(
+        mut,
+        seeds = [SOL_DEBIT_SEED, debit.user_delegate_account.key().as_ref()],
+        bump
+    )]
+    pub temporary_token_account: UncheckedAccount<'info>,
+
+    /// Required Solana system program
+    pub system_program: Program<'info, System>,
+}
+
+/**
+ * Process a wrapped SOL debit paid out in native SOL.
+ *
+ * @param ctx The instruction context containing all required accounts
+ * @param merchant_id Unique identifier for the merchant
+ * @param amount Number of lamports to debit
+ *
+ * Flow:
+ * 1. Verify the native mint and SOL recipient (done via account constraints)
+ * 2. Check the debit and update period tracking exactly as debit_user does
+ * 3. Create the temporary token account, owned by the delegate PDA
+ * 4. Transfer the net amount to the temporary token account and the protocol fee to the
+ *    fee token account
+ * 5. Close the temporary token account to the rent payer, unwrapping the SOL
+ * 6. Send the net amount from the rent payer to the destination wallet
+ * 7. Write the debit receipt, if its account was passed
+ * 8. Emit UserDebited
+ *
+ * @return Result indicating success or containing an error
+ */
+pub fn handler(ctx: Context<DebitUserSol>, merchant_id: u64, amount: u64) -> Result<()> {
+    let temporary_bump = ctx.bumps.temporary_token_account;
+    let accounts = ctx.accounts;
+    let protocol_fee = validate_debit(&mut accounts.debit, merchant_id, amount)?;
+    let net_amount = amount - protocol_fee;
+
+    // Derive the PDA signer seeds for the delegate account
+    let merchant_id_bytes = merchant_id_seed(merchant_id);
+    let seeds = [
+        USER_DELEGATE_SEED,
+        merchant_id_bytes.as_ref(),
+        accounts.debit.mint.to_account_info().key.as_ref(),
+        accounts
+            .debit
+            .user_token_account
+            .to_account_info()
+            .key
+            .as_ref(),
+        &[accounts.debit.user_delegate_account.bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    let delegate_key = accounts.debit.user_delegate_account.key();
+    let temporary_seeds = [SOL_DEBIT_SEED, delegate_key.as_ref(), &[temporary_bump]];
+    create_temporary_token_account(accounts, &temporary_seeds)?;
+
+    // Read the decimals once so the temporary account and fee legs use the same validated value
+    let decimals = mint_decimals(&accounts.debit.mint.to_account_info())?;
+
+    let clock = Clock::get()?;
+    let post_condition_epoch = accounts
+        .debit
+        .state
+        .has_feature_flag(feature_flags::POST_CONDITION_CHECKS)
+        .then_some(clock.epoch);
+    // The native mint has no transfer fee, so the temporary account receives the full amount
+    transfer_checked_verified(
+        CpiContext::new_with_signer(
+            accounts.debit.token_program.to_account_info(),
+            token_interface::TransferChecked {
+                from: accounts.debit.user_token_account.to_account_info(),
+                to: accounts.temporary_token_account.to_account_info(),
+                mint: accounts.debit.mint.to_account_info(),
+                authority: accounts.debit.user_delegate_account.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        net_amount,
+        decimals,
+        post_condition_epoch,
+    )?;
+    transfer_protocol_fee(
+        &mut accounts.debit,
+        protocol_fee,
+        decimals,
+        signer_seeds,
+        post_condition_epoch,
+    )?;
+
+    // Closing the wrapped SOL account releases its rent and the net amount as lamports
+    token_interface::close_account(CpiContext::new_with_signer(
+        accounts.debit.token_program.to_account_info(),
+        token_interface::CloseAccount {
+            account: accounts.temporary_token_account.to_account_info(),
+            destination: accounts.rent_payer.to_account_info(),
+            authority: accounts.debit.user_delegate_account.to_account_info(),
+        },
+        signer_seeds,
+    ))?;
+    system_program::transfer(
+        CpiContext::new(
+            accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: accounts.rent_payer.to_account_info(),
+                to: accounts.destination_wallet.to_account_info(),
+            },
+        ),
+        net_amount,
+    )?;
+    write_debit_receipt(&accounts.debit, amount, clock.unix_timestamp)?;
+
+    emit!(UserDebited {
+        debitor: accounts.debit.debitor.key(),
+        user_delegate: accounts.debit.user_delegate_account.key(),
+        merchant_id,
+        user_ata: accounts.debit.user_token_account.key(),
+        destination_ata: accounts.debit.destination_token_account.key(),
+        mint: accounts.debit.mint.key(),
+        amount,
+        slot: clock.slot,
+        timestamp: clock.unix_timestamp,
+        debit_nonce: accounts.debit.user_delegate_account.debit_nonce,
+        protocol_fee,
+        memo: Vec::new(),
+    });
+
+    Ok(())
+}
+
+/// Whether the mint is the native (wrapped SOL) mint of either token program
+fn is_native_mint(mint: &Pubkey) -> bool {
+    *mint == spl_token::native_mint::ID || *mint == spl_token_2022::native_mint::ID
+}
+
+/// Create the temporary token account at its PDA, funding any missing rent from the rent payer
+fn create_temporary_token_account(accounts: &DebitUserSol, seeds: &[&[u8]]) -> Result<()> {
+    let temporary_token_account = accounts.temporary_token_account.to_account_info();
+    let system_program = accounts.system_program.to_account_info();
+    let space = SplTokenAccount::LEN;
+    let rent = Rent::get()?.minimum_balance(space);
+
+    // Lamports sent to the address beforehand must not prevent its creation
+    let shortfall = rent.saturating_sub(temporary_token_account.lamports());
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: accounts.rent_payer.to_account_info(),
+                    to: temporary_token_account.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    system_program::allocate(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            system_program::Allocate {
+                account_to_allocate: temporary_token_account.clone(),
+            },
+            &[seeds],
+        ),
+        space as u64,
+    )?;
+    system_program::assign(
+        CpiContext::new_with_signer(
+            system_program,
+            system_program::Assign {
+                account_to_assign: temporary_token_account.clone(),
+            },
+            &[seeds],
+        ),
+        accounts.debit.token_program.key,
+    )?;
+
+    token_interface::initialize_account3(CpiContext::new(
+        accounts.debit.token_program.to_account_info(),
+        token_interface::InitializeAccount3 {
+            account: temporary_token_account,
+            mint: accounts.debit.mint.to_account_info(),
+            authority: accounts.debit.user_delegate_account.to_account_info(),
+        },
+    ))
+}
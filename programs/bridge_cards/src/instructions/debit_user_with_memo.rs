@@ -0,0 +1,79 @@
+use crate::errors::ErrorCode;
+use crate::instructions::debit_user::*;
+use anchor_lang::prelude::*;
+use anchor_spl::memo::{self, BuildMemo, Memo};
+
+/// Maximum length in bytes of a memo attached to a debit
+pub const MAX_MEMO_LENGTH: usize = 128;
+
+/**
+ * Debit a user and attach a merchant reference to the debit for reconciliation.
+ *
+ * This instruction debits exactly as debit_user does and records the memo (e.g. an invoice
+ * number) in the UserDebited event. When the SPL Memo program is passed, the memo is also
+ * logged through a Memo CPI, so it shows up in explorers and wallets that display memos.
+ *
+ * Security Checks:
+ * - All debit_user checks apply to the transfer itself
+ * - The memo must be at most MAX_MEMO_LENGTH bytes
+ * - The memo is only recorded, the program never interprets it
+ *
+ * Memo CPI:
+ * - Only made when memo_program is passed and the memo is not empty
+ * - The Memo program requires valid UTF-8, so binary memos must be event-only
+ *
+ * Events Emitted:
+ * - UserDebited: As for debit_user, with the memo
+ *
+ * Common Errors:
+ * - MemoTooLong: The memo is longer than MAX_MEMO_LENGTH bytes
+ * - Every debit_user error
+ */
+#[derive(Accounts)]
+pub struct DebitUserWithMemo<'info> {
+    /// Accounts required by the underlying debit
+    pub debit: DebitUser<'info>,
+
+    /// SPL Memo program, logs the memo through a CPI when provided
+    /// Required permissions: None
+    pub memo_program: Option<Program<'info, Memo>>,
+}
+
+/**
+ * Process a debit with a memo.
+ *
+ * @param ctx The instruction context containing all required accounts
+ * @param merchant_id Unique identifier for the merchant
+ * @param amount Number of tokens to transfer (in smallest units)
+ * @param memo Merchant reference recorded with the debit
+ *
+ * Flow:
+ * 1. Verify the memo length
+ * 2. Debit the user exactly as debit_user does, recording the memo in the event
+ * 3. Log the memo through the Memo program if it was passed
+ *
+ * @return Result indicating success or containing an error
+ */
+pub fn handler(
+    ctx: Context<DebitUserWithMemo>,
+    merchant_id: u64,
+    amount: u64,
+    memo: Vec<u8>,
+) -> Result<()> {
+    if memo.len() > MAX_MEMO_LENGTH {
+        return Err(ErrorCode::MemoTooLong.into());
+    }
+
+    process_debit_with_memo(&mut ctx.accounts.debit, merchant_id, amount, &memo)?;
+
+    if let Some(memo_program) = &ctx.accounts.memo_program {
+        if !memo.is_empty() {
+            memo::build_memo(
+                CpiContext::new(memo_program.to_account_info(), BuildMemo {}),
+                &memo,
+            )?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,126 @@
+use crate::errors::ErrorCode;
+use crate::instructions::debit_user::*;
+use crate::state::{feature_flags, MerchantConfigState, RouterState};
+use crate::ID;
+use crate::ROUTER_SEED;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program::invoke;
+
+/**
+ * Debit tokens from a user's token account and hand the proceeds to the merchant's router.
+ *
+ * This instruction performs a regular debit_user transfer and then invokes the router program
+ * configured on the merchant config, letting it swap the tokens received by the destination
+ * (e.g. accept token A from the user but settle the merchant in token B).
+ *
+ * Security Checks:
+ * - Swaps must not be disabled (SWAPS_DISABLED feature flag)
+ * - All debit_user checks apply to the transfer itself
+ * - Router program must match the merchant config's router_program
+ * - Router program must be allowlisted by the admin (router_state.allowed == true)
+ * - The router is invoked without the delegate PDA's signature, so it cannot move user funds
+ *
+ * Router Invocation:
+ * - Accounts: [destination_token_account, ...remaining_accounts]
+ * - Data: route_data, passed through unchanged
+ * - Signer and writable flags of remaining accounts are forwarded as provided
+ *
+ * Account Derivation:
+ * - Merchant config PDA: [MERCHANT_CONFIG_SEED, merchant_id] (passed as debit.merchant_config)
+ * - Router state PDA: [ROUTER_SEED, router_program]
+ *
+ * Common Errors:
+ * - SwapsDisabled: The admin has disabled swaps
+ * - RouterNotConfigured: Router program differs from the merchant config
+ * - RouterNotAllowed: Router program is not allowlisted
+ */
+#[derive(Accounts)]
+pub struct DebitUserWithSwap<'info> {
+    /// Accounts required by the underlying debit
+    pub debit: DebitUser<'info>,
+
+    /// PDA storing the router's authorization state
+    /// Seeds: [ROUTER_SEED, router_program]
+    /// Required permissions: Read-only
+    #[account(
+        seeds = [ROUTER_SEED, router_program.key().as_ref()],
+        bump = router_state.bump,
+        seeds::program = ID,
+        constraint = router_state.allowed @ ErrorCode::RouterNotAllowed
+    )]
+    pub router_state: Account<'info, RouterState>,
+
+    /// Router program invoked after the transfer
+    /// CHECK: Validated against the merchant config (in the handler) and router allowlist
+    #[account(executable)]
+    pub router_program: UncheckedAccount<'info>,
+}
+
+/**
+ * Process a debit followed by a router invocation.
+ *
+ * @param ctx The instruction context containing all required accounts
+ * @param merchant_id Unique identifier for the merchant
+ * @param amount Number of tokens to transfer (in smallest units)
+ * @param route_data Instruction data forwarded to the router program
+ *
+ * Flow:
+ * 1. Verify router allowlisting (done via account constraints) and merchant configuration
+ * 2. Debit the user exactly as debit_user does
+ * 3. Invoke the router with the destination account and remaining accounts
+ *
+ * @return Result indicating success or containing an error
+ */
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, DebitUserWithSwap<'info>>,
+    merchant_id: u64,
+    amount: u64,
+    route_data: Vec<u8>,
+) -> Result<()> {
+    if ctx
+        .accounts
+        .debit
+        .state
+        .has_feature_flag(feature_flags::SWAPS_DISABLED)
+    {
+        return Err(ErrorCode::SwapsDisabled.into());
+    }
+
+    let merchant_config =
+        MerchantConfigState::load_or_default(&ctx.accounts.debit.merchant_config)?;
+    if merchant_config.router_program != ctx.accounts.router_program.key() {
+        return Err(ErrorCode::RouterNotConfigured.into());
+    }
+
+    process_debit(&mut ctx.accounts.debit, merchant_id, amount)?;
+
+    let destination = ctx
+        .accounts
+        .debit
+        .destination_token_account
+        .to_account_info();
+    let mut account_metas = vec![AccountMeta::new(destination.key(), false)];
+    let mut account_infos = vec![destination];
+    for account in ctx.remaining_accounts.iter() {
+        account_metas.push(if account.is_writable {
+            AccountMeta::new(account.key(), account.is_signer)
+        } else {
+            AccountMeta::new_readonly(account.key(), account.is_signer)
+        });
+        account_infos.push(account.clone());
+    }
+    account_infos.push(ctx.accounts.router_program.to_account_info());
+
+    // Plain invoke: the delegate PDA never signs for the router
+    invoke(
+        &Instruction {
+            program_id: ctx.accounts.router_program.key(),
+            accounts: account_metas,
+            data: route_data,
+        },
+        &account_infos,
+    )?;
+
+    Ok(())
+}
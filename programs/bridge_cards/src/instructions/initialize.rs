@@ -1,4 +1,4 @@
-use crate::state::BridgeCardsState;
+use crate::state::{AccountSpace, BridgeCardsState, CURRENT_VERSION};
 use anchor_lang::prelude::*;
 
 /// Seed used to derive the global state PDA
@@ -25,7 +25,7 @@ pub const STATE_SEED: &[u8] = b"state";
  * Security Considerations:
  * - The payer becomes the admin and should be a secure, controlled account
  * - Admin authority is critical and should be managed carefully
- * - State PDA can only be initialized once
+ * - State PDA can only be initialized once, unless close_program_state decommissions it
  *
  * Required Accounts:
  * - payer: Account that will pay for state account creation and become admin
@@ -46,7 +46,7 @@ pub struct Initialize<'info> {
     #[account(
         init,
         payer = payer,
-        space = BridgeCardsState::DISCRIMINATOR.len() + BridgeCardsState::INIT_SPACE,
+        space = BridgeCardsState::SPACE,
         seeds = [STATE_SEED],
         bump,
     )]
@@ -68,18 +68,19 @@ pub struct Initialize<'info> {
 pub fn handler(ctx: Context<Initialize>) -> Result<()> {
     let state = &mut ctx.accounts.state;
     state.admin = ctx.accounts.payer.key();
+    state.version = CURRENT_VERSION;
     state.bump = ctx.bumps.state;
     Ok(())
 }
 
 #[cfg(feature = "local")]
-fn auth_initialize(account: Pubkey) -> bool {
+pub(crate) fn auth_initialize(account: Pubkey) -> bool {
     use crate::ID;
 
     account == ID
 }
 
 #[cfg(not(feature = "local"))]
-fn auth_initialize(_: Pubkey) -> bool {
+pub(crate) fn auth_initialize(_: Pubkey) -> bool {
     true
 }
@@ -0,0 +1,75 @@
+use crate::instructions::initialize::{auth_initialize, STATE_SEED};
+use crate::state::{AccountSpace, BridgeCardsState, CURRENT_VERSION};
+use anchor_lang::prelude::*;
+
+/**
+ * Initialize the BridgeCards program if it hasn't been initialized yet.
+ *
+ * This instruction behaves as initialize on the first call. Once the state exists it is a
+ * no-op, so deployment scripts can run it unconditionally without checking is_initialized
+ * first or failing on a re-run.
+ *
+ * State Account:
+ * - Created as a PDA with seed [STATE_SEED] if it doesn't exist
+ * - Left untouched if it already exists
+ *
+ * Security Considerations:
+ * - The payer only becomes the admin when the state is created
+ * - An existing admin, fee configuration or feature flags are never overwritten
+ *
+ * Required Accounts:
+ * - payer: Account that pays for state account creation and becomes admin if it is created
+ * - state: PDA storing global program state
+ * - system_program: Required for account creation
+ */
+#[derive(Accounts)]
+pub struct InitializeIdempotent<'info> {
+    /// Account that will pay for state account creation and become the admin
+    /// Required permissions: Signer, Mutable (for rent payment)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Program Derived Address storing global program state
+    /// Seeds: [STATE_SEED]
+    /// Space: Discriminator + Admin Pubkey
+    /// Required permissions: Mutable (account may be created)
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = BridgeCardsState::SPACE,
+        seeds = [STATE_SEED],
+        bump,
+    )]
+    pub state: Account<'info, BridgeCardsState>,
+
+    #[account(constraint = auth_initialize(program_account.key()))]
+    pub program_account: Signer<'info>,
+
+    /// Required for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/**
+ * Initialize the program state and set the admin, unless already initialized.
+ *
+ * @param ctx Context containing the payer (future admin) and state accounts
+ *
+ * Flow:
+ * 1. Create the state account if needed (done via account constraints)
+ * 2. Return early if the state was already initialized
+ * 3. Otherwise set the admin, version and bump as initialize does
+ *
+ * @return Result indicating success or containing an error
+ */
+pub fn handler(ctx: Context<InitializeIdempotent>) -> Result<()> {
+    let state = &mut ctx.accounts.state;
+    // A freshly created state is zeroed, an initialized one always has an admin
+    if state.admin != Pubkey::default() {
+        return Ok(());
+    }
+
+    state.admin = ctx.accounts.payer.key();
+    state.version = CURRENT_VERSION;
+    state.bump = ctx.bumps.state;
+    Ok(())
+}
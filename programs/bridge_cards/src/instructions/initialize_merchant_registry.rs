@@ -0,0 +1,106 @@
+use crate::events::MerchantRegistryInitialized;
+use crate::instructions::initialize::STATE_SEED;
+use crate::pda::merchant_id_seed;
+use crate::state::{AccountSpace, BridgeCardsState, MerchantRegistryState, CURRENT_VERSION};
+use crate::ID;
+use anchor_lang::prelude::*;
+
+/// Seed used to derive merchant registry PDAs
+pub const MERCHANT_REGISTRY_SEED: &[u8] = b"merchant_registry";
+
+/**
+ * Create the registry counting a merchant's destinations, debitors and delegates.
+ *
+ * The registry is optional: instructions creating merchant accounts work without it and
+ * only increment its counters when it is passed. Counters start at zero, so accounts created
+ * before the registry are not counted.
+ *
+ * Account Creation:
+ * - Creates a PDA to store the registry
+ * - PDA is derived using [MERCHANT_REGISTRY_SEED, merchant_id]
+ * - Funded by the payer account
+ *
+ * Security Model:
+ * - Only the program admin can create merchant registries
+ * - A registry can only be created once, so its counters can't be reset
+ *
+ * Events Emitted:
+ * - MerchantRegistryInitialized: When the registry is created
+ *   Fields: merchant_id, state_pda
+ *
+ * Required Accounts:
+ * - admin: Program admin who can create merchant registries
+ * - payer: Account paying for PDA creation/rent
+ * - state: Global program state storing admin pubkey
+ * - merchant_registry: PDA storing the merchant's counters
+ * - system_program: Required for account creation
+ */
+#[derive(Accounts)]
+#[instruction(merchant_id: u64)]
+pub struct InitializeMerchantRegistry<'info> {
+    /// The program admin account, must match admin stored in state
+    /// Required permissions: Signer
+    #[account(constraint = admin.key() == state.admin)]
+    pub admin: Signer<'info>,
+
+    /// Account that will pay for PDA creation and rent
+    /// Required permissions: Signer, Mutable (for rent payment)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Global program state storing the admin public key
+    /// Seeds: [STATE_SEED]
+    /// Required permissions: Read-only
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        seeds::program = ID
+    )]
+    pub state: Account<'info, BridgeCardsState>,
+
+    /// PDA storing the merchant's counters
+    /// Seeds: [MERCHANT_REGISTRY_SEED, merchant_id]
+    /// Space: Discriminator + Merchant registry
+    /// Required permissions: Mutable
+    #[account(
+        init,
+        payer = payer,
+        space = MerchantRegistryState::SPACE,
+        seeds = [
+            MERCHANT_REGISTRY_SEED,
+            merchant_id_seed(merchant_id).as_ref(),
+        ],
+        bump
+    )]
+    pub merchant_registry: Account<'info, MerchantRegistryState>,
+
+    /// Required for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/**
+ * Process the creation of a merchant registry.
+ *
+ * @param ctx Context containing all required accounts
+ * @param merchant_id Unique identifier for the merchant
+ *
+ * Flow:
+ * 1. Verify admin signature (done via account constraints)
+ * 2. Create the registry with zero counters
+ * 3. Emit event with merchant_id
+ *
+ * @return Result indicating success or containing an error
+ */
+pub fn handler(ctx: Context<InitializeMerchantRegistry>, merchant_id: u64) -> Result<()> {
+    let merchant_registry = &mut ctx.accounts.merchant_registry;
+    merchant_registry.version = CURRENT_VERSION;
+    merchant_registry.bump = ctx.bumps.merchant_registry;
+
+    // Emit event for indexing and notifications
+    emit!(MerchantRegistryInitialized {
+        merchant_id,
+        state_pda: ctx.accounts.merchant_registry.key(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use crate::instructions::initialize::STATE_SEED;
+use crate::state::BridgeCardsState;
+use crate::ID;
+use anchor_lang::prelude::*;
+
+/**
+ * Return whether the BridgeCards program has been initialized.
+ *
+ * This read-only instruction gives SDKs a single explicit check instead of fetching the
+ * state PDA and handling the not-found case. The value is returned through the transaction's
+ * return data (set_return_data) as a borsh-encoded bool.
+ *
+ * Initialized means the state PDA is owned by this program and starts with the
+ * BridgeCardsState discriminator.
+ *
+ * Required Accounts:
+ * - state: Global program state PDA (may not exist)
+ */
+#[derive(Accounts)]
+pub struct IsInitialized<'info> {
+    /// Global program state PDA
+    /// Seeds: [STATE_SEED]
+    /// Required permissions: Read-only
+    /// CHECK: Address is verified by seeds; the account may not exist yet
+    #[account(
+        seeds = [STATE_SEED],
+        bump,
+        seeds::program = ID
+    )]
+    pub state: UncheckedAccount<'info>,
+}
+
+/**
+ * Check whether the state PDA holds an initialized BridgeCardsState.
+ *
+ * @param ctx The instruction context containing the state account
+ *
+ * @return true if the program is initialized, false otherwise
+ */
+pub fn handler(ctx: Context<IsInitialized>) -> Result<bool> {
+    let state = &ctx.accounts.state;
+    Ok(state.owner == &ID
+        && state
+            .try_borrow_data()?
+            .starts_with(BridgeCardsState::DISCRIMINATOR))
+}
@@ -0,0 +1,99 @@
+use crate::events::UserDelegateStateSnapshot;
+use crate::instructions::add_or_update_user_delegate::USER_DELEGATE_SEED;
+use crate::pda::merchant_id_seed;
+use crate::state::{DebitPolicy, MerchantConfigState, UserDelegateState};
+use crate::{ID, MERCHANT_CONFIG_SEED};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+/**
+ * Emit a user delegate's current limits and remaining period allowance as an event.
+ *
+ * This read-only instruction serves lightweight clients that can't deserialize account data:
+ * they simulate or send it and decode the UserDelegateStateSnapshot event from the logs.
+ * No state is modified and no authority is required beyond passing the correct PDA.
+ *
+ * Account Derivation:
+ * - User delegate PDA: [USER_DELEGATE_SEED, merchant_id, mint, user_token_account]
+ * - Merchant config PDA: [MERCHANT_CONFIG_SEED, merchant_id] (may not exist)
+ *
+ * Events Emitted:
+ * - UserDelegateStateSnapshot: The delegate's limits, period usage and remaining allowance
+ *
+ * Required Accounts:
+ * - user_delegate_account: PDA storing the delegate's limits and period tracking
+ * - merchant_config: PDA storing the merchant configuration, whose period rules apply to the
+ *   remaining allowance
+ * - mint: Token mint of the user token account
+ * - user_token_account: Token account the delegate can debit
+ */
+#[derive(Accounts)]
+#[instruction(merchant_id: u64)]
+pub struct LogUserDelegateState<'info> {
+    /// PDA storing the delegate's transfer limits and period tracking
+    /// Seeds: [USER_DELEGATE_SEED, merchant_id, mint, user_token_account]
+    /// Required permissions: Read-only
+    #[account(
+        seeds = [USER_DELEGATE_SEED, merchant_id_seed(merchant_id).as_ref(), mint.key().as_ref(), user_token_account.key().as_ref()],
+        bump = user_delegate_account.bump,
+        seeds::program = ID
+    )]
+    pub user_delegate_account: Account<'info, UserDelegateState>,
+
+    /// PDA storing the merchant configuration
+    /// Seeds: [MERCHANT_CONFIG_SEED, merchant_id]
+    /// Required permissions: Read-only
+    /// CHECK: Address is verified by seeds; default settings apply if it has not been created
+    #[account(
+        seeds = [MERCHANT_CONFIG_SEED, merchant_id_seed(merchant_id).as_ref()],
+        bump,
+        seeds::program = ID
+    )]
+    pub merchant_config: UncheckedAccount<'info>,
+
+    /// Token mint of the user token account
+    /// Required permissions: None (used for PDA derivation)
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Token account the delegate can debit
+    /// Required permissions: None (used for PDA derivation)
+    #[account(constraint = user_token_account.mint == mint.key())]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+}
+
+/**
+ * Emit a snapshot of the delegate's limits at the current clock.
+ *
+ * @param ctx The instruction context containing all required accounts
+ * @param merchant_id Unique identifier for the merchant
+ *
+ * @return Result indicating success or containing an error
+ */
+pub fn handler(ctx: Context<LogUserDelegateState>, merchant_id: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    let user_delegate_account = &ctx.accounts.user_delegate_account;
+    // The allowance follows the same period rules as a debit would
+    let merchant_config = MerchantConfigState::load_or_default(&ctx.accounts.merchant_config)?;
+    let policy = DebitPolicy::resolve(&merchant_config, user_delegate_account);
+
+    emit!(UserDelegateStateSnapshot {
+        merchant_id,
+        mint: ctx.accounts.mint.key(),
+        user_ata: ctx.accounts.user_token_account.key(),
+        user_delegate: user_delegate_account.key(),
+        per_transfer_limit: user_delegate_account.per_transfer_limit,
+        period_transfer_limit: user_delegate_account.period_transfer_limit,
+        period_transferred_amount: user_delegate_account.period_transferred_amount,
+        period_timestamp_last_reset: user_delegate_account.period_timestamp_last_reset,
+        transfer_limit_period_seconds: user_delegate_account.transfer_limit_period_seconds,
+        min_slots_between_debits: user_delegate_account.min_slots_between_debits,
+        min_transfer_amount: user_delegate_account.min_transfer_amount,
+        valid_from: user_delegate_account.valid_from,
+        expires_at: user_delegate_account.expires_at,
+        remaining_period_allowance: user_delegate_account
+            .remaining_period_allowance(clock.unix_timestamp as u64, &policy),
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
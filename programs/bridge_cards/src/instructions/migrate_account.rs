@@ -0,0 +1,492 @@
+use crate::errors::ErrorCode;
+use crate::events::AccountMigrated;
+use crate::instructions::initialize::STATE_SEED;
+use crate::state::{
+    BridgeCardsState, DebitReceiptState, MerchantConfigState, MerchantDebitorState,
+    MerchantDestinationState, MerchantManagerState, MerchantMintState, MerchantRegistryState,
+    MerchantSpendTrackerState, RouterState, UserDelegateState, CURRENT_VERSION,
+    MAX_MERCHANT_MANAGERS,
+};
+use crate::ID;
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+/**
+ * Rewrite a program account of an older layout version in the current layout.
+ *
+ * Accounts start with the 8-byte discriminator followed by a version byte (see
+ * state::CURRENT_VERSION). Each version is upgraded in turn:
+ * - Version 0: accounts created before versioning hold only the original program's fields and
+ *   lack the version byte, which is inserted after the discriminator. The fields added before
+ *   versioning are filled in with their defaults: no feature flags or protocol fee on the
+ *   state, no delegate options and zero counters on user delegates, non-primary
+ *   destinations, and debitors without limits or usage. The destination and debitor bumps
+ *   move after the fields inserted before them
+ * - Version 1: merchant manager accounts hold a single manager, which becomes the only entry
+ *   of the managers list
+ * - Version 2: user delegate accounts lack the trailing strict_boundary, left disabled
+ * - Version 3: merchant config accounts lack the trailing enforce_mint_allowlist, left disabled
+ * - Version 4: user delegate accounts lack the trailing anchor_to_epoch and last_period_index,
+ *   left disabled and zero
+ * - Version 5: user delegate accounts lack the trailing restricted_destination, left
+ *   unrestricted
+ * - Version 6: user delegate accounts lack the trailing active_after, left zero so they stay
+ *   active
+ * - Version 7: user delegate accounts lack the trailing timestamp_last_transferred, left zero
+ *   so the clock anomaly check waits for their next debit, and merchant config accounts lack
+ *   the trailing max_clock_skew_seconds, left zero for the default
+ * - Version 8: merchant destination accounts lack the trailing last_received_amount and
+ *   last_received_timestamp, left zero until their next debit
+ * - Version 9: merchant config accounts lack the trailing default_per_transfer_limit,
+ *   default_period_transfer_limit and default_period_seconds, left zero so no template is set
+ * - Version 10: merchant config accounts lack the trailing max_allowed_per_transfer and
+ *   max_allowed_period_limit, left zero so managers stay uncapped
+ * - Version 11: merchant config accounts lack the trailing max_splits, left zero so batch
+ *   debits use the default maximum
+ *
+ * The account is grown to the current layout size and the version byte is set, so the
+ * account deserializes in the current layout again.
+ *
+ * Migrating an account that already has the current version does nothing, so the
+ * instruction can safely be run over every program account.
+ *
+ * Account Updates:
+ * - Grows older accounts to the current layout size and writes CURRENT_VERSION after the
+ *   discriminator
+ * - The payer funds any rent needed for the extra space
+ *
+ * Security Model:
+ * - Only the program admin can migrate accounts
+ * - The admin is read from the state in either layout, so the state itself can be migrated
+ * - Only program-owned accounts of a known type and layout size are rewritten
+ *
+ * Events Emitted:
+ * - AccountMigrated: When an account is rewritten
+ *   Fields: account, previous_version, version
+ *
+ * Required Accounts:
+ * - admin: Program admin
+ * - payer: Account funding the extra rent
+ * - state: Global program state PDA, in any layout version
+ * - account: Program account to migrate (may be the state itself)
+ * - system_program: Required for the rent transfer
+ */
+#[derive(Accounts)]
+pub struct MigrateAccount<'info> {
+    /// The program admin account, checked against the admin stored in state by the handler
+    /// Required permissions: Signer
+    pub admin: Signer<'info>,
+
+    /// Account that will pay for the extra rent
+    /// Required permissions: Signer, Mutable (for rent payment)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Global program state storing the admin public key
+    /// Seeds: [STATE_SEED]
+    /// Required permissions: Read-only
+    /// CHECK: Address is verified by seeds; read in either layout version by the handler
+    #[account(
+        seeds = [STATE_SEED],
+        bump,
+        seeds::program = ID
+    )]
+    pub state: UncheckedAccount<'info>,
+
+    /// Program account to migrate
+    /// Required permissions: Mutable
+    /// CHECK: Must be owned by the program; its type is identified by the discriminator
+    #[account(mut, owner = ID)]
+    pub account: UncheckedAccount<'info>,
+
+    /// Required for the rent transfer
+    pub system_program: Program<'info, System>,
+}
+
+/**
+ * Process the migration of a program account.
+ *
+ * @param ctx Context containing all required accounts
+ *
+ * Flow:
+ * 1. Verify the admin signature against the state, in either layout
+ * 2. Identify the account type by its discriminator and read its version
+ * 3. Return if the account already has the current version
+ * 4. Fund the extra rent and grow the account
+ * 5. Upgrade the fields one version at a time and write the current version
+ * 6. Emit event with the previous and new version
+ *
+ * @return Result indicating success or containing an error
+ */
+pub fn handler(ctx: Context<MigrateAccount>) -> Result<()> {
+    // The admin follows the version byte, absent from unversioned states
+    let admin = {
+        let data = ctx.accounts.state.try_borrow_data()?;
+        let state_version = stored_version(&data)?;
+        let admin_offset = BridgeCardsState::DISCRIMINATOR.len() + usize::from(state_version > 0);
+        Pubkey::try_from(&data[admin_offset..admin_offset + 32])
+            .map_err(|_| ErrorCode::UnsupportedAccountVersion)?
+    };
+    require_keys_eq!(
+        admin,
+        ctx.accounts.admin.key(),
+        anchor_lang::error::ErrorCode::ConstraintRaw
+    );
+
+    let account = ctx.accounts.account.to_account_info();
+    let (previous_size, previous_version, size, is_manager_state) = {
+        let data = account.try_borrow_data()?;
+        let previous_version = stored_version(&data)?;
+        let discriminator = &data[..8];
+        (
+            data.len(),
+            previous_version,
+            layout_size(discriminator, CURRENT_VERSION),
+            discriminator == MerchantManagerState::DISCRIMINATOR,
+        )
+    };
+    if previous_version == CURRENT_VERSION {
+        return Ok(());
+    }
+
+    // Fund the rent of the extra space before growing the account
+    let rent_due = Rent::get()?
+        .minimum_balance(size)
+        .saturating_sub(account.lamports());
+    if rent_due > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: account.clone(),
+                },
+            ),
+            rent_due,
+        )?;
+    }
+    account.realloc(size, false)?;
+
+    let mut data = account.try_borrow_mut_data()?;
+    if previous_version < 1 {
+        upgrade_unversioned(&mut data, previous_size);
+    }
+    if previous_version < 2 && is_manager_state {
+        // The single manager becomes the first of the managers, followed by empty entries
+        let bump = data[9 + 32];
+        let managers_end = 9 + 32 * MAX_MERCHANT_MANAGERS;
+        data[9 + 32..managers_end].fill(0);
+        data[managers_end] = 1;
+        data[managers_end + 1] = bump;
+    }
+    data[8] = CURRENT_VERSION;
+
+    // Emit event for indexing and notifications
+    emit!(AccountMigrated {
+        account: account.key(),
+        previous_version,
+        version: CURRENT_VERSION,
+    });
+
+    Ok(())
+}
+
+/// Rewrite an account of the original, unversioned layout in the version 1 layout, with the
+/// fields added before versioning set to their defaults
+fn upgrade_unversioned(data: &mut [u8], previous_size: usize) {
+    // Shift the original fields after the discriminator to make room for the version byte
+    data.copy_within(8..previous_size, 9);
+    // Fields appended before versioning (state fee settings, delegate options and counters)
+    data[previous_size + 1..].fill(0);
+
+    if data[..8] == *MerchantDestinationState::DISCRIMINATOR {
+        // is_primary was inserted between allowed and bump: not primary
+        let bump = data[10];
+        data[10] = 0;
+        data[11] = bump;
+    } else if data[..8] == *MerchantDebitorState::DISCRIMINATOR {
+        // per_call_limit, daily_limit and the daily usage were inserted between allowed and
+        // bump: no caps and no usage
+        let bump = data[10];
+        let bump_offset = 10 + 4 * 8;
+        data[10..bump_offset].fill(0);
+        data[bump_offset] = bump;
+    }
+}
+
+/// Size of an account created before versioning, in the original program's layout (0 if the
+/// account type did not exist then)
+fn unversioned_size(discriminator: &[u8]) -> usize {
+    let layouts: [(&[u8], usize); 5] = [
+        // admin, bump
+        (BridgeCardsState::DISCRIMINATOR, 32 + 1),
+        // per_transfer_limit, period_transfer_limit, period_transferred_amount,
+        // period_timestamp_last_reset, transfer_limit_period_seconds, slot_last_transferred,
+        // bump
+        (UserDelegateState::DISCRIMINATOR, 4 * 8 + 4 + 8 + 1),
+        // allowed, bump
+        (MerchantDestinationState::DISCRIMINATOR, 1 + 1),
+        // allowed, bump
+        (MerchantDebitorState::DISCRIMINATOR, 1 + 1),
+        // manager, bump
+        (MerchantManagerState::DISCRIMINATOR, 32 + 1),
+    ];
+    layouts
+        .iter()
+        .find(|(layout_discriminator, _)| *layout_discriminator == discriminator)
+        .map_or(0, |(layout_discriminator, space)| {
+            layout_discriminator.len() + space
+        })
+}
+
+/// Size of an account in the given layout version, identified by its discriminator (0 if
+/// unknown)
+fn layout_size(discriminator: &[u8], version: u8) -> usize {
+    if version < 1 {
+        return unversioned_size(discriminator);
+    }
+    let layouts: [(&[u8], usize); 11] = [
+        (
+            BridgeCardsState::DISCRIMINATOR,
+            BridgeCardsState::INIT_SPACE,
+        ),
+        (
+            UserDelegateState::DISCRIMINATOR,
+            UserDelegateState::INIT_SPACE,
+        ),
+        (
+            MerchantDestinationState::DISCRIMINATOR,
+            MerchantDestinationState::INIT_SPACE,
+        ),
+        (
+            MerchantDebitorState::DISCRIMINATOR,
+            MerchantDebitorState::INIT_SPACE,
+        ),
+        (
+            MerchantManagerState::DISCRIMINATOR,
+            MerchantManagerState::INIT_SPACE,
+        ),
+        (
+            MerchantConfigState::DISCRIMINATOR,
+            MerchantConfigState::INIT_SPACE,
+        ),
+        (
+            MerchantSpendTrackerState::DISCRIMINATOR,
+            MerchantSpendTrackerState::INIT_SPACE,
+        ),
+        (RouterState::DISCRIMINATOR, RouterState::INIT_SPACE),
+        (
+            MerchantMintState::DISCRIMINATOR,
+            MerchantMintState::INIT_SPACE,
+        ),
+        (
+            MerchantRegistryState::DISCRIMINATOR,
+            MerchantRegistryState::INIT_SPACE,
+        ),
+        (
+            DebitReceiptState::DISCRIMINATOR,
+            DebitReceiptState::INIT_SPACE,
+        ),
+    ];
+    let Some(size) = layouts
+        .iter()
+        .find(|(layout_discriminator, _)| *layout_discriminator == discriminator)
+        .map(|(layout_discriminator, space)| layout_discriminator.len() + space)
+    else {
+        return 0;
+    };
+    // Version 2 replaced the manager with MAX_MERCHANT_MANAGERS managers and a count
+    let size = if version < 2 && discriminator == MerchantManagerState::DISCRIMINATOR {
+        size - 32 * (MAX_MERCHANT_MANAGERS - 1) - 1
+    } else {
+        size
+    };
+    // Version 3 appended strict_boundary to user delegates
+    let size = if version < 3 && discriminator == UserDelegateState::DISCRIMINATOR {
+        size - 1
+    } else {
+        size
+    };
+    // Version 4 appended enforce_mint_allowlist to merchant configs
+    let size = if version < 4 && discriminator == MerchantConfigState::DISCRIMINATOR {
+        size - 1
+    } else {
+        size
+    };
+    // Version 5 appended anchor_to_epoch and last_period_index to user delegates
+    let size = if version < 5 && discriminator == UserDelegateState::DISCRIMINATOR {
+        size - 9
+    } else {
+        size
+    };
+    // Version 6 appended restricted_destination to user delegates
+    let size = if version < 6 && discriminator == UserDelegateState::DISCRIMINATOR {
+        size - 32
+    } else {
+        size
+    };
+    // Version 7 appended active_after to user delegates
+    let size = if version < 7 && discriminator == UserDelegateState::DISCRIMINATOR {
+        size - 8
+    } else {
+        size
+    };
+    // Version 8 appended timestamp_last_transferred to user delegates and
+    // max_clock_skew_seconds to merchant configs
+    let size = if version < 8 && discriminator == UserDelegateState::DISCRIMINATOR {
+        size - 8
+    } else if version < 8 && discriminator == MerchantConfigState::DISCRIMINATOR {
+        size - 4
+    } else {
+        size
+    };
+    // Version 9 appended last_received_amount and last_received_timestamp to merchant
+    // destinations
+    let size = if version < 9 && discriminator == MerchantDestinationState::DISCRIMINATOR {
+        size - 16
+    } else {
+        size
+    };
+    // Version 10 appended the delegate limit template to merchant configs
+    let size = if version < 10 && discriminator == MerchantConfigState::DISCRIMINATOR {
+        size - 20
+    } else {
+        size
+    };
+    // Version 11 appended the manager limit caps to merchant configs
+    let size = if version < 11 && discriminator == MerchantConfigState::DISCRIMINATOR {
+        size - 16
+    } else {
+        size
+    };
+    // Version 12 appended max_splits to merchant configs
+    if version < 12 && discriminator == MerchantConfigState::DISCRIMINATOR {
+        size - 1
+    } else {
+        size
+    }
+}
+
+/// Version of an account: the stored version byte if the account has that version's size,
+/// or 0 for an account of the original program's size, created before versioning
+fn stored_version(data: &[u8]) -> Result<u8> {
+    let discriminator = data.get(..8).ok_or(ErrorCode::UnsupportedAccountVersion)?;
+    if layout_size(discriminator, CURRENT_VERSION) == 0 {
+        return Err(ErrorCode::UnsupportedAccountVersion.into());
+    }
+    match data.get(8) {
+        Some(&version)
+            if (1..=CURRENT_VERSION).contains(&version)
+                && data.len() == layout_size(discriminator, version) =>
+        {
+            Ok(version)
+        }
+        _ if data.len() == layout_size(discriminator, 0) => Ok(0),
+        _ => Err(ErrorCode::UnsupportedAccountVersion.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Migrate unversioned data as the handler does, with garbage in the grown space
+    fn migrate_unversioned(unversioned: &[u8]) -> Vec<u8> {
+        assert_eq!(stored_version(unversioned).unwrap(), 0);
+        let size = layout_size(&unversioned[..8], CURRENT_VERSION);
+        let mut data = unversioned.to_vec();
+        data.resize(size, 0xAA);
+        upgrade_unversioned(&mut data, unversioned.len());
+        data[8] = CURRENT_VERSION;
+        data
+    }
+
+    #[test]
+    fn test_migrate_unversioned_state() {
+        let admin = Pubkey::new_unique();
+        let mut unversioned = BridgeCardsState::DISCRIMINATOR.to_vec();
+        unversioned.extend_from_slice(admin.as_ref());
+        unversioned.push(254);
+        assert_eq!(unversioned.len(), 41);
+
+        let data = migrate_unversioned(&unversioned);
+        let state = BridgeCardsState::try_deserialize(&mut data.as_slice()).unwrap();
+        assert_eq!(state.version, CURRENT_VERSION);
+        assert_eq!(state.admin, admin);
+        assert_eq!(state.bump, 254);
+        assert_eq!(state.feature_flags, 0);
+        assert_eq!(state.protocol_fee_bps, 0);
+        assert_eq!(state.protocol_fee_owner, Pubkey::default());
+    }
+
+    #[test]
+    fn test_migrate_unversioned_user_delegate() {
+        let mut unversioned = UserDelegateState::DISCRIMINATOR.to_vec();
+        for value in [100u64, 2_000, 300, 1_700_000_000] {
+            unversioned.extend_from_slice(&value.to_le_bytes());
+        }
+        unversioned.extend_from_slice(&86_400u32.to_le_bytes());
+        unversioned.extend_from_slice(&42u64.to_le_bytes());
+        unversioned.push(253);
+        assert_eq!(unversioned.len(), 53);
+
+        let data = migrate_unversioned(&unversioned);
+        let delegate = UserDelegateState::try_deserialize(&mut data.as_slice()).unwrap();
+        assert_eq!(delegate.version, CURRENT_VERSION);
+        assert_eq!(delegate.per_transfer_limit, 100);
+        assert_eq!(delegate.period_transfer_limit, 2_000);
+        assert_eq!(delegate.period_transferred_amount, 300);
+        assert_eq!(delegate.period_timestamp_last_reset, 1_700_000_000);
+        assert_eq!(delegate.transfer_limit_period_seconds, 86_400);
+        assert_eq!(delegate.slot_last_transferred, 42);
+        assert_eq!(delegate.bump, 253);
+        // Fields added since the original program are left at their defaults
+        assert_eq!(delegate.min_slots_between_debits, 0);
+        assert!(!delegate.allow_alternate_destination);
+        assert_eq!(delegate.metadata_hash, [0; 32]);
+        assert_eq!(delegate.valid_from, 0);
+        assert_eq!(delegate.expires_at, 0);
+        assert_eq!(delegate.total_transferred, 0);
+        assert_eq!(delegate.debit_nonce, 0);
+        assert_eq!(delegate.limit_mode, 0);
+        assert_eq!(delegate.restricted_destination, Pubkey::default());
+        assert_eq!(delegate.active_after, 0);
+    }
+
+    #[test]
+    fn test_migrate_unversioned_destination_and_debitor() {
+        let mut unversioned = MerchantDestinationState::DISCRIMINATOR.to_vec();
+        unversioned.extend_from_slice(&[1, 252]);
+        let data = migrate_unversioned(&unversioned);
+        let destination = MerchantDestinationState::try_deserialize(&mut data.as_slice()).unwrap();
+        assert!(destination.allowed);
+        assert!(!destination.is_primary);
+        assert_eq!(destination.bump, 252);
+        assert_eq!(destination.last_received_amount, 0);
+
+        let mut unversioned = MerchantDebitorState::DISCRIMINATOR.to_vec();
+        unversioned.extend_from_slice(&[1, 251]);
+        let data = migrate_unversioned(&unversioned);
+        let debitor = MerchantDebitorState::try_deserialize(&mut data.as_slice()).unwrap();
+        assert!(debitor.allowed);
+        assert_eq!(debitor.per_call_limit, 0);
+        assert_eq!(debitor.daily_limit, 0);
+        assert_eq!(debitor.daily_transferred_amount, 0);
+        assert_eq!(debitor.daily_timestamp_last_reset, 0);
+        assert_eq!(debitor.bump, 251);
+    }
+
+    #[test]
+    fn test_unversioned_layout_of_newer_types_is_unsupported() {
+        // Merchant configs were added after versioning, so have no unversioned layout
+        let mut data = MerchantConfigState::DISCRIMINATOR.to_vec();
+        data.resize(
+            layout_size(MerchantConfigState::DISCRIMINATOR, CURRENT_VERSION) - 1,
+            0,
+        );
+        assert_eq!(
+            stored_version(&data).unwrap_err(),
+            ErrorCode::UnsupportedAccountVersion.into()
+        );
+    }
+}
@@ -0,0 +1,176 @@
+use crate::errors::ErrorCode;
+use crate::events::UserDelegateMigrated;
+use crate::instructions::add_or_update_user_delegate::USER_DELEGATE_SEED;
+use crate::pda::merchant_id_seed;
+use crate::state::{AccountSpace, MerchantManagerState, UserDelegateState, CURRENT_VERSION};
+use crate::{ID, MERCHANT_MANAGER_SEED};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+/**
+ * Move a user delegate to a new token account of the same user and mint.
+ *
+ * A delegate PDA is derived from the user token account, so it becomes useless when the user
+ * moves to a new token account (e.g. after recreating a Token-2022 account). This instruction
+ * closes the old delegate and creates the delegate of the new token account with the same
+ * limits, settings and period tracking, so the spend already recorded keeps counting.
+ *
+ * Account Changes:
+ * - Creates the delegate PDA [USER_DELEGATE_SEED, merchant_id, mint, new_user_token_account],
+ *   funded by the payer
+ * - Closes the old delegate PDA, sending its rent to the payer
+ *
+ * Security Model:
+ * - Only merchant managers can migrate their merchant's delegates
+ * - The new token account must hold the delegation's mint and differ from the old one
+ * - The owner of the new token account must sign, so a delegate's limits and spend can't be
+ *   moved to another user's token account
+ * - If the old token account still exists, it must have the same owner as the new one
+ * - The new delegate must not exist yet, so an existing delegate is never overwritten
+ * - The old token account is only required to exist for the owner check, so the delegate can
+ *   be migrated after the user has closed it
+ * - The user still has to approve the new delegate PDA on the new token account before it
+ *   can debit
+ *
+ * Events Emitted:
+ * - UserDelegateMigrated: When the delegate is migrated
+ *   Fields: merchant_id, mint, old_user_ata, new_user_ata, old_user_delegate,
+ *   new_user_delegate
+ *
+ * Required Accounts:
+ * - manager: Merchant manager
+ * - payer: Account paying for the new delegate and receiving the old delegate's rent
+ * - manager_state: PDA verifying manager authority
+ * - old_user_token_account: Token account the old delegate controlled
+ * - new_user_token_account: Token account the new delegate controls
+ * - user_owner: Owner of the new token account
+ * - mint: Token mint of the delegation
+ * - old_user_delegate_account: PDA to be closed
+ * - new_user_delegate_account: PDA to be created
+ * - system_program: Required for account creation
+ */
+#[derive(Accounts)]
+#[instruction(merchant_id: u64)]
+pub struct MigrateUserDelegate<'info> {
+    /// Merchant manager account, must be one of the managers in manager_state
+    /// Required permissions: Signer
+    #[account(constraint = manager_state.is_manager(&manager.key()))]
+    pub manager: Signer<'info>,
+
+    /// Account paying for the new delegate and receiving the old delegate's rent
+    /// Required permissions: Signer, Mutable (for rent payment)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// PDA storing the merchant manager's authorization
+    /// Seeds: [MERCHANT_MANAGER_SEED, merchant_id]
+    /// Required permissions: Read-only
+    #[account(
+        seeds = [MERCHANT_MANAGER_SEED, merchant_id_seed(merchant_id).as_ref()],
+        bump = manager_state.bump,
+        seeds::program = ID
+    )]
+    pub manager_state: Account<'info, MerchantManagerState>,
+
+    /// Token account the old delegate controlled
+    /// Required permissions: Read-only
+    /// CHECK: Address is verified by the old delegate's seeds; its owner is checked in the
+    /// handler if it still exists
+    pub old_user_token_account: UncheckedAccount<'info>,
+
+    /// Token account the new delegate controls
+    /// Must hold the delegation's mint
+    /// Required permissions: Read-only
+    #[account(
+        constraint = new_user_token_account.mint == mint.key() @ ErrorCode::MismatchedMint,
+        constraint = new_user_token_account.key() != old_user_token_account.key() @ ErrorCode::SameUserTokenAccount
+    )]
+    pub new_user_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Owner of the new token account
+    /// Required permissions: Signer
+    #[account(address = new_user_token_account.owner @ ErrorCode::UserTokenAccountOwnerMismatch)]
+    pub user_owner: Signer<'info>,
+
+    /// Mint of the delegation
+    /// Required permissions: Read-only
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// PDA of the old token account's delegate
+    /// Seeds: [USER_DELEGATE_SEED, merchant_id, mint, old_user_token_account]
+    /// Required permissions: Mutable (for closure)
+    #[account(mut,
+        close = payer,
+        seeds = [USER_DELEGATE_SEED, merchant_id_seed(merchant_id).as_ref(), mint.key().as_ref(), old_user_token_account.key().as_ref()],
+        bump = old_user_delegate_account.bump,
+        seeds::program = ID
+    )]
+    pub old_user_delegate_account: Account<'info, UserDelegateState>,
+
+    /// PDA of the new token account's delegate
+    /// Seeds: [USER_DELEGATE_SEED, merchant_id, mint, new_user_token_account]
+    /// Space: Discriminator + UserDelegateState::INIT_SPACE
+    /// Required permissions: Mutable (account is being created)
+    #[account(
+        init,
+        payer = payer,
+        space = UserDelegateState::SPACE,
+        seeds = [USER_DELEGATE_SEED, merchant_id_seed(merchant_id).as_ref(), mint.key().as_ref(), new_user_token_account.key().as_ref()],
+        bump
+    )]
+    pub new_user_delegate_account: Account<'info, UserDelegateState>,
+
+    /// Required for account creation
+    pub system_program: Program<'info, System>,
+}
+
+/**
+ * Process the migration of a user delegate.
+ *
+ * @param ctx Context containing all required accounts
+ * @param merchant_id Unique identifier for the merchant
+ *
+ * Flow:
+ * 1. Verify manager and user signatures, the token accounts and both delegate PDAs (done via
+ *    account constraints)
+ * 2. Check that the old token account, if it still exists, has the same owner as the new one
+ * 3. Copy the old delegate's limits, settings and period tracking to the new delegate
+ * 4. Emit event linking the old and new delegates
+ * 5. Close the old delegate account, sending its rent to the payer (done by Anchor on exit)
+ *
+ * @return Result indicating success or containing an error
+ */
+pub fn handler(ctx: Context<MigrateUserDelegate>, merchant_id: u64) -> Result<()> {
+    // A closed token account no longer belongs to the token program
+    let old_user_token_account = &ctx.accounts.old_user_token_account;
+    let token_program = ctx.accounts.new_user_token_account.to_account_info().owner;
+    if old_user_token_account.owner == token_program && !old_user_token_account.data_is_empty() {
+        let old_owner =
+            TokenAccount::try_deserialize(&mut &old_user_token_account.try_borrow_data()?[..])?
+                .owner;
+        if old_owner != ctx.accounts.new_user_token_account.owner {
+            return Err(ErrorCode::UserTokenAccountOwnerMismatch.into());
+        }
+    }
+
+    let new_user_delegate = UserDelegateState {
+        version: CURRENT_VERSION,
+        bump: ctx.bumps.new_user_delegate_account,
+        ..(*ctx.accounts.old_user_delegate_account).clone()
+    };
+    ctx.accounts
+        .new_user_delegate_account
+        .set_inner(new_user_delegate);
+
+    // Emit event for indexing and notifications
+    emit!(UserDelegateMigrated {
+        merchant_id,
+        mint: ctx.accounts.mint.key(),
+        old_user_ata: ctx.accounts.old_user_token_account.key(),
+        new_user_ata: ctx.accounts.new_user_token_account.key(),
+        old_user_delegate: ctx.accounts.old_user_delegate_account.key(),
+        new_user_delegate: ctx.accounts.new_user_delegate_account.key(),
+    });
+
+    Ok(())
+}
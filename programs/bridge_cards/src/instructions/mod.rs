@@ -19,5 +19,108 @@ pub mod update_admin;
 pub mod close_account;
 pub use close_account::*;
 
+pub mod close_accounts;
+pub use close_accounts::*;
+
 pub mod add_or_update_merchant_manager;
 pub use add_or_update_merchant_manager::*;
+
+pub mod add_merchant_manager;
+pub use add_merchant_manager::*;
+
+pub mod remove_merchant_manager;
+pub use remove_merchant_manager::*;
+
+pub mod add_or_update_merchant_config;
+pub use add_or_update_merchant_config::*;
+
+pub mod add_or_update_router;
+pub use add_or_update_router::*;
+
+pub mod debit_user_with_swap;
+pub use debit_user_with_swap::*;
+
+pub mod time_until_reset;
+pub use time_until_reset::*;
+
+pub mod set_feature_flags;
+pub use set_feature_flags::*;
+
+pub mod admin_raise_delegate_limits;
+pub use admin_raise_delegate_limits::*;
+
+pub mod is_initialized;
+pub use is_initialized::*;
+
+pub mod reissue_delegate;
+pub use reissue_delegate::*;
+
+pub mod admin_heartbeat;
+pub use admin_heartbeat::*;
+
+pub mod batch_debit_user;
+pub use batch_debit_user::*;
+
+pub mod close_user_delegate;
+pub use close_user_delegate::*;
+
+pub mod log_user_delegate_state;
+pub use log_user_delegate_state::*;
+
+pub mod close_merchant_manager;
+pub use close_merchant_manager::*;
+
+pub mod add_or_update_merchant_spend_tracker;
+pub use add_or_update_merchant_spend_tracker::*;
+
+pub mod debit_user_exact_out;
+
+pub mod close_merchant_debitor;
+pub use close_merchant_debitor::*;
+
+pub mod close_merchant_destination;
+pub use close_merchant_destination::*;
+
+pub mod reduce_user_delegate_limits;
+pub use reduce_user_delegate_limits::*;
+
+pub mod set_protocol_fee;
+pub use set_protocol_fee::*;
+
+pub mod migrate_account;
+pub use migrate_account::*;
+
+pub mod simulate_debit_user;
+pub use simulate_debit_user::*;
+
+pub mod debit_user_sol;
+pub use debit_user_sol::*;
+
+pub mod debit_user_logged;
+
+pub mod batch_add_destinations;
+pub use batch_add_destinations::*;
+
+pub mod add_or_update_merchant_mint;
+pub use add_or_update_merchant_mint::*;
+
+pub mod debit_user_with_memo;
+pub use debit_user_with_memo::*;
+
+pub mod initialize_merchant_registry;
+pub use initialize_merchant_registry::*;
+
+pub mod initialize_idempotent;
+pub use initialize_idempotent::*;
+
+pub mod migrate_user_delegate;
+pub use migrate_user_delegate::*;
+
+pub mod admin_recover_user_funds;
+pub use admin_recover_user_funds::*;
+
+pub mod create_user_delegate_from_template;
+pub use create_user_delegate_from_template::*;
+
+pub mod close_program_state;
+pub use close_program_state::*;
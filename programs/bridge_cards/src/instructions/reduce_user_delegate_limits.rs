@@ -0,0 +1,116 @@
+use crate::errors::ErrorCode;
+use crate::events::UserDelegateAddedOrUpdated;
+use crate::instructions::add_or_update_user_delegate::{
+    validate_delegate_limits, USER_DELEGATE_SEED,
+};
+use crate::pda::merchant_id_seed;
+use crate::state::UserDelegateState;
+use crate::ID;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+/**
+ * Lower a user delegate's limits, signed by the owner of the delegated token account.
+ *
+ * Only merchant managers can otherwise change delegate limits. This instruction lets users
+ * tighten their own spending caps without involving the merchant. Limits can only go down:
+ * any increase is rejected, so the user cannot grant the merchant more than it was given.
+ *
+ * Security Model:
+ * - The owner of user_token_account must sign
+ * - Neither the per-transfer nor the period limit may increase
+ * - The period limit must stay at or above the per-transfer limit
+ *
+ * Events Emitted:
+ * - UserDelegateAddedOrUpdated: When the limits are lowered
+ *   Fields: merchant_id, mint, user_ata, user_delegate, metadata_hash, previous and new
+ *   per-transfer and period limits
+ *
+ * Required Accounts:
+ * - user: Owner of the user token account
+ * - user_token_account: Token account the delegate controls
+ * - mint: Token mint for the delegation
+ * - user_delegate_account: PDA storing delegate parameters
+ */
+#[derive(Accounts)]
+#[instruction(merchant_id: u64)]
+pub struct ReduceUserDelegateLimits<'info> {
+    /// Owner of the user token account
+    /// Required permissions: Signer
+    #[account(constraint = user.key() == user_token_account.owner)]
+    pub user: Signer<'info>,
+
+    /// Token account controlled by the delegate
+    /// Required permissions: Read-only
+    #[account(constraint = user_token_account.mint == mint.key() @ ErrorCode::MismatchedMint)]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Mint of the tokens that can be transferred by this delegate
+    /// Required permissions: Read-only
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// PDA storing the delegate's transfer limits and state
+    /// Seeds: [USER_DELEGATE_SEED, merchant_id, mint, user_token_account]
+    /// Required permissions: Mutable
+    #[account(mut,
+        seeds = [USER_DELEGATE_SEED, merchant_id_seed(merchant_id).as_ref(), mint.key().as_ref(), user_token_account.key().as_ref()],
+        bump = user_delegate_account.bump,
+        seeds::program = ID
+    )]
+    pub user_delegate_account: Account<'info, UserDelegateState>,
+}
+
+/**
+ * Process a user's reduction of their delegate's limits.
+ *
+ * @param ctx Context containing all required accounts
+ * @param merchant_id Unique identifier for the merchant
+ * @param per_transfer_limit New maximum amount allowed in a single transfer
+ * @param period_transfer_limit New maximum amount allowed within the time period
+ *
+ * Flow:
+ * 1. Verify the token account owner's signature (done via account constraints)
+ * 2. Reject any limit above its current value
+ * 3. Update the delegate's limits
+ * 4. Emit event for the updated delegate
+ *
+ * @return Result indicating success or containing an error
+ */
+pub fn handler(
+    ctx: Context<ReduceUserDelegateLimits>,
+    merchant_id: u64,
+    per_transfer_limit: u64,
+    period_transfer_limit: u64,
+) -> Result<()> {
+    let user_delegate_account = &mut ctx.accounts.user_delegate_account;
+    if per_transfer_limit > user_delegate_account.per_transfer_limit
+        || period_transfer_limit > user_delegate_account.period_transfer_limit
+    {
+        return Err(ErrorCode::CannotIncreaseOwnLimit.into());
+    }
+    validate_delegate_limits(
+        per_transfer_limit,
+        period_transfer_limit,
+        user_delegate_account.transfer_limit_period_seconds,
+    )?;
+
+    let previous_per_transfer_limit = user_delegate_account.per_transfer_limit;
+    let previous_period_transfer_limit = user_delegate_account.period_transfer_limit;
+    user_delegate_account.per_transfer_limit = per_transfer_limit;
+    user_delegate_account.period_transfer_limit = period_transfer_limit;
+
+    // Emit event for indexing and notifications
+    emit!(UserDelegateAddedOrUpdated {
+        merchant_id,
+        mint: ctx.accounts.mint.key(),
+        user_ata: ctx.accounts.user_token_account.key(),
+        user_delegate: ctx.accounts.user_delegate_account.key(),
+        metadata_hash: ctx.accounts.user_delegate_account.metadata_hash,
+        previous_per_transfer_limit,
+        new_per_transfer_limit: per_transfer_limit,
+        previous_period_transfer_limit,
+        new_period_transfer_limit: period_transfer_limit,
+    });
+
+    Ok(())
+}
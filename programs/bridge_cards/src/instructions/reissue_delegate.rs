@@ -0,0 +1,167 @@
+use crate::errors::ErrorCode;
+use crate::events::UserDelegateReissued;
+use crate::instructions::add_or_update_user_delegate::{
+    validate_delegate_limits, USER_DELEGATE_SEED,
+};
+use crate::pda::merchant_id_seed;
+use crate::state::{MerchantConfigState, MerchantManagerState, UserDelegateState};
+use crate::{ID, MERCHANT_CONFIG_SEED, MERCHANT_MANAGER_SEED};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+/**
+ * Re-issue an existing user delegate with new limits.
+ *
+ * Used when a card is re-issued: the delegate keeps its address and settings, gets new
+ * limits, and the merchant manager chooses whether the spend already recorded for the
+ * current period carries over to the new limits or starts from zero.
+ *
+ * Usage Handling:
+ * - carry_usage = true: period_transferred_amount and the period start are preserved, so
+ *   the new period limit applies to what was already spent this period
+ * - carry_usage = false: usage is cleared and the next debit starts a new period
+ *
+ * Security Model:
+ * - Only merchant managers can re-issue delegates
+ * - The delegate must already exist
+ * - If the merchant config sets managers_can_only_lower_limits, limits can only be lowered,
+ *   the period can't be shortened and usage must be carried over, as clearing it frees up
+ *   the whole period limit
+ * - The new limits must be within the merchant config's caps, as for add_or_update_user_delegate
+ *
+ * Events Emitted:
+ * - UserDelegateReissued: When the delegate is re-issued
+ *   Fields: merchant_id, user_delegate, new limits, carry_usage, reissue_count
+ *
+ * Required Accounts:
+ * - manager: Merchant manager who can update delegates
+ * - manager_state: PDA verifying manager authority
+ * - merchant_config: PDA storing the merchant configuration (may not exist)
+ * - user_token_account: Token account the delegate controls
+ * - mint: Token mint for the delegation
+ * - user_delegate_account: PDA storing delegate parameters
+ */
+#[derive(Accounts)]
+#[instruction(merchant_id: u64)]
+pub struct ReissueDelegate<'info> {
+    /// Merchant manager account, must be one of the managers in manager_state
+    /// Required permissions: Signer
+    #[account(constraint = manager_state.is_manager(&manager.key()))]
+    pub manager: Signer<'info>,
+
+    /// PDA storing the merchant manager's authorization
+    /// Seeds: [MERCHANT_MANAGER_SEED, merchant_id]
+    /// Required permissions: Read-only
+    #[account(
+        seeds = [MERCHANT_MANAGER_SEED, merchant_id_seed(merchant_id).as_ref()],
+        bump = manager_state.bump,
+        seeds::program = ID
+    )]
+    pub manager_state: Account<'info, MerchantManagerState>,
+
+    /// PDA storing the merchant configuration
+    /// Seeds: [MERCHANT_CONFIG_SEED, merchant_id]
+    /// Required permissions: Read-only
+    /// CHECK: Address is verified by seeds; default settings apply if it has not been created
+    #[account(
+        seeds = [MERCHANT_CONFIG_SEED, merchant_id_seed(merchant_id).as_ref()],
+        bump,
+        seeds::program = ID
+    )]
+    pub merchant_config: UncheckedAccount<'info>,
+
+    /// Token account controlled by the delegate
+    /// Required permissions: Read-only
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Mint of the tokens that can be transferred by this delegate
+    /// Required permissions: Read-only
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// PDA storing the delegate's transfer limits and state
+    /// Seeds: [USER_DELEGATE_SEED, merchant_id, mint, user_token_account]
+    /// Required permissions: Mutable
+    #[account(mut,
+        seeds = [USER_DELEGATE_SEED, merchant_id_seed(merchant_id).as_ref(), mint.key().as_ref(), user_token_account.key().as_ref()],
+        bump = user_delegate_account.bump,
+        seeds::program = ID
+    )]
+    pub user_delegate_account: Account<'info, UserDelegateState>,
+}
+
+/**
+ * Process the re-issue of a user delegate.
+ *
+ * @param ctx Context containing all required accounts
+ * @param merchant_id Unique identifier for the merchant
+ * @param per_transfer_limit New maximum amount allowed in a single transfer
+ * @param period_transfer_limit New maximum amount allowed within the time period
+ * @param transfer_limit_period New duration of the transfer limit period in seconds
+ * @param carry_usage Whether the spend recorded for the current period is preserved
+ *
+ * Flow:
+ * 1. Verify manager signature (done via account constraints)
+ * 2. Reject limit raises, shorter periods and cleared usage if the merchant only allows
+ *    managers to lower limits, limits above
+ *    the merchant's caps, inconsistent limits, and a carried period whose end overflows
+ * 3. Set the new limits, preserve or clear usage, and increment reissue_count
+ * 4. Emit event recording the re-issue
+ *
+ * @return Result indicating success or containing an error
+ */
+pub fn handler(
+    ctx: Context<ReissueDelegate>,
+    merchant_id: u64,
+    per_transfer_limit: u64,
+    period_transfer_limit: u64,
+    transfer_limit_period: u32,
+    carry_usage: bool,
+) -> Result<()> {
+    let merchant_config = MerchantConfigState::load_or_default(&ctx.accounts.merchant_config)?;
+    let user_delegate_account = &mut ctx.accounts.user_delegate_account;
+
+    if merchant_config.managers_can_only_lower_limits
+        && (per_transfer_limit > user_delegate_account.per_transfer_limit
+            || period_transfer_limit > user_delegate_account.period_transfer_limit
+            || transfer_limit_period < user_delegate_account.transfer_limit_period_seconds
+            || !carry_usage)
+    {
+        return Err(ErrorCode::LimitRaiseRequiresAdmin.into());
+    }
+    merchant_config.check_limit_caps(per_transfer_limit, period_transfer_limit)?;
+    validate_delegate_limits(
+        per_transfer_limit,
+        period_transfer_limit,
+        transfer_limit_period,
+    )?;
+    // Without carry_usage the period restarts from zero, which any period can follow
+    if carry_usage {
+        user_delegate_account.check_period_end(transfer_limit_period)?;
+    }
+
+    user_delegate_account.per_transfer_limit = per_transfer_limit;
+    user_delegate_account.period_transfer_limit = period_transfer_limit;
+    user_delegate_account.transfer_limit_period_seconds = transfer_limit_period;
+
+    if !carry_usage {
+        // A zero period start makes the next debit begin a new period
+        user_delegate_account.period_transferred_amount = 0;
+        user_delegate_account.period_timestamp_last_reset = 0;
+    }
+
+    user_delegate_account.reissue_count = user_delegate_account.reissue_count.saturating_add(1);
+
+    // Emit event for indexing and notifications
+    emit!(UserDelegateReissued {
+        merchant_id,
+        user_delegate: ctx.accounts.user_delegate_account.key(),
+        per_transfer_limit,
+        period_transfer_limit,
+        transfer_limit_period_seconds: transfer_limit_period,
+        carry_usage,
+        period_transferred_amount: ctx.accounts.user_delegate_account.period_transferred_amount,
+        reissue_count: ctx.accounts.user_delegate_account.reissue_count,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use crate::events::MerchantManagerRemoved;
+use crate::instructions::initialize::STATE_SEED;
+use crate::pda::merchant_id_seed;
+use crate::state::{BridgeCardsState, MerchantManagerState};
+use crate::{ID, MERCHANT_MANAGER_SEED};
+use anchor_lang::prelude::*;
+
+/**
+ * Remove one of a merchant's managers.
+ *
+ * The remaining managers keep their authority, so a lost or compromised manager key can be
+ * revoked without interrupting the merchant. The last manager cannot be removed; use
+ * close_merchant_manager to remove all of a merchant's managers.
+ *
+ * Security Model:
+ * - Only the program admin can remove managers
+ * - The removed manager does not need to sign, so a lost or compromised key can be removed
+ *
+ * Events Emitted:
+ * - MerchantManagerRemoved: When the manager is removed
+ *   Fields: merchant_id, manager, active_count
+ *
+ * Required Accounts:
+ * - admin: Program admin
+ * - state: Global program state storing admin pubkey
+ * - manager_state: PDA storing the merchant's managers
+ */
+#[derive(Accounts)]
+#[instruction(merchant_id: u64)]
+pub struct RemoveMerchantManager<'info> {
+    /// The program admin account, must match admin stored in state
+    /// Required permissions: Signer
+    #[account(constraint = admin.key() == state.admin)]
+    pub admin: Signer<'info>,
+
+    /// Global program state storing the admin public key
+    /// Seeds: [STATE_SEED]
+    /// Required permissions: Read-only
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        seeds::program = ID
+    )]
+    pub state: Account<'info, BridgeCardsState>,
+
+    /// PDA storing the merchant's managers
+    /// Seeds: [MERCHANT_MANAGER_SEED, merchant_id]
+    /// Required permissions: Mutable
+    #[account(mut,
+        seeds = [MERCHANT_MANAGER_SEED, merchant_id_seed(merchant_id).as_ref()],
+        bump = manager_state.bump,
+        seeds::program = ID
+    )]
+    pub manager_state: Account<'info, MerchantManagerState>,
+}
+
+/**
+ * Process the removal of a merchant manager.
+ *
+ * @param ctx Context containing all required accounts
+ * @param merchant_id Unique identifier for the merchant
+ * @param manager Manager to remove
+ *
+ * Flow:
+ * 1. Verify admin signature and manager state PDA (done via account constraints)
+ * 2. Remove the manager, rejecting unknown managers and the last manager
+ * 3. Emit event with the removed manager and manager count
+ *
+ * @return Result indicating success or containing an error
+ */
+pub fn handler(
+    ctx: Context<RemoveMerchantManager>,
+    merchant_id: u64,
+    manager: Pubkey,
+) -> Result<()> {
+    let manager_state = &mut ctx.accounts.manager_state;
+    manager_state.remove_manager(&manager)?;
+
+    // Emit event for indexing and notifications
+    emit!(MerchantManagerRemoved {
+        merchant_id,
+        manager,
+        active_count: manager_state.active_count,
+    });
+
+    Ok(())
+}
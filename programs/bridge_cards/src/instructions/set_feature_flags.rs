@@ -0,0 +1,81 @@
+use crate::events::FeatureFlagsUpdated;
+use crate::instructions::initialize::STATE_SEED;
+use crate::state::BridgeCardsState;
+use crate::ID;
+use anchor_lang::prelude::*;
+
+/**
+ * Set the global feature flag bitmask of the BridgeCards program.
+ *
+ * Program-wide switches are stored as bits of a single u64 rather than one field each,
+ * keeping the state account a fixed size as features accumulate.
+ *
+ * Feature Flags (see state::feature_flags):
+ * - Bit 0 PAUSED: Every debit is rejected with ProgramPaused; admin instructions such as
+ *   set_feature_flags and update_admin never check it, so the admin can always unpause
+ * - Bit 1 SWAPS_DISABLED: debit_user_with_swap is rejected with SwapsDisabled
+ * - Bit 2 POST_CONDITION_CHECKS: Debits verify the destination's net balance increase
+ * - Bit 3 REQUIRE_MANAGER_COSIGN: New merchant managers must co-sign their appointment
+ * - Bit 4 CLOCK_ANOMALY_CHECKS: Debits reject clock timestamps inconsistent with the slots
+ *   elapsed since the delegate's last debit
+ * - Unassigned bits are stored but ignored
+ *
+ * Account Updates:
+ * - Replaces the whole bitmask in the global state PDA
+ * - State PDA is derived using [STATE_SEED]
+ *
+ * Security Model:
+ * - Only the program admin can set feature flags
+ *
+ * Events Emitted:
+ * - FeatureFlagsUpdated: When the flags are set
+ *   Fields: previous_flags, new_flags
+ *
+ * Required Accounts:
+ * - admin: Program admin
+ * - state: Global program state PDA
+ */
+#[derive(Accounts)]
+pub struct SetFeatureFlags<'info> {
+    /// The program admin account, must match admin stored in state
+    /// Required permissions: Signer
+    #[account(constraint = admin.key() == state.admin)]
+    pub admin: Signer<'info>,
+
+    /// Global program state storing the feature flags
+    /// Seeds: [STATE_SEED]
+    /// Required permissions: Mutable (for flag update)
+    #[account(mut,
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        seeds::program = ID
+    )]
+    pub state: Account<'info, BridgeCardsState>,
+}
+
+/**
+ * Process the update of the feature flags.
+ *
+ * @param ctx Context containing all required accounts
+ * @param flags New feature flag bitmask
+ *
+ * Flow:
+ * 1. Verify admin signature (done via account constraints)
+ * 2. Replace the feature flags in the state PDA
+ * 3. Emit event with the previous and new flags
+ *
+ * @return Result indicating success or containing an error
+ */
+pub fn handler(ctx: Context<SetFeatureFlags>, flags: u64) -> Result<()> {
+    let state = &mut ctx.accounts.state;
+    let previous_flags = state.feature_flags;
+    state.feature_flags = flags;
+
+    // Emit event for indexing and notifications
+    emit!(FeatureFlagsUpdated {
+        previous_flags,
+        new_flags: flags,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use crate::errors::ErrorCode;
+use crate::events::ProtocolFeeUpdated;
+use crate::instructions::initialize::STATE_SEED;
+use crate::state::BridgeCardsState;
+use crate::ID;
+use anchor_lang::prelude::*;
+
+/**
+ * Set the protocol fee skimmed from every debit of the BridgeCards program.
+ *
+ * debit_user splits each debit into the amount sent to the destination and a fee of
+ * protocol_fee_bps basis points, rounded down, sent to a token account of the fee owner.
+ * The delegate and debitor limits apply to the whole debit, fee included.
+ *
+ * Account Updates:
+ * - Replaces the fee and fee owner in the global state PDA
+ * - State PDA is derived using [STATE_SEED]
+ *
+ * Security Model:
+ * - Only the program admin can set the protocol fee
+ * - The fee cannot exceed BridgeCardsState::MAX_PROTOCOL_FEE_BPS
+ *
+ * Events Emitted:
+ * - ProtocolFeeUpdated: When the fee is set
+ *   Fields: previous_fee_bps, fee_bps, fee_owner
+ *
+ * Common Errors:
+ * - FeeTooHigh: fee_bps is above MAX_PROTOCOL_FEE_BPS
+ *
+ * Required Accounts:
+ * - admin: Program admin
+ * - state: Global program state PDA
+ */
+#[derive(Accounts)]
+pub struct SetProtocolFee<'info> {
+    /// The program admin account, must match admin stored in state
+    /// Required permissions: Signer
+    #[account(constraint = admin.key() == state.admin)]
+    pub admin: Signer<'info>,
+
+    /// Global program state storing the protocol fee
+    /// Seeds: [STATE_SEED]
+    /// Required permissions: Mutable (for fee update)
+    #[account(mut,
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        seeds::program = ID
+    )]
+    pub state: Account<'info, BridgeCardsState>,
+}
+
+/**
+ * Process the update of the protocol fee.
+ *
+ * @param ctx Context containing all required accounts
+ * @param fee_bps New protocol fee in basis points (0 disables the fee)
+ * @param fee_owner Owner of the token accounts receiving the fee
+ *
+ * Flow:
+ * 1. Verify admin signature (done via account constraints)
+ * 2. Validate the fee is at most MAX_PROTOCOL_FEE_BPS
+ * 3. Replace the fee and fee owner in the state PDA
+ * 4. Emit event with the previous and new fee
+ *
+ * @return Result indicating success or containing an error
+ */
+pub fn handler(ctx: Context<SetProtocolFee>, fee_bps: u16, fee_owner: Pubkey) -> Result<()> {
+    if fee_bps > BridgeCardsState::MAX_PROTOCOL_FEE_BPS {
+        return Err(ErrorCode::FeeTooHigh.into());
+    }
+
+    let state = &mut ctx.accounts.state;
+    let previous_fee_bps = state.protocol_fee_bps;
+    state.protocol_fee_bps = fee_bps;
+    state.protocol_fee_owner = fee_owner;
+
+    // Emit event for indexing and notifications
+    emit!(ProtocolFeeUpdated {
+        previous_fee_bps,
+        fee_bps,
+        fee_owner,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,276 @@
+use crate::errors::ErrorCode;
+use crate::events::DebitSimulationResult;
+use crate::instructions::add_or_update_user_delegate::USER_DELEGATE_SEED;
+use crate::instructions::debit_user::{
+    check_debit_destination, check_debit_token_accounts, check_merchant_debit,
+    verify_delegate_approval, verify_fee_token_account,
+};
+use crate::pda::merchant_id_seed;
+use crate::state::{
+    feature_flags, BridgeCardsState, DebitPolicy, MerchantConfigState, MerchantDebitorState,
+    MerchantDestinationState, MerchantSpendTrackerState, UserDelegateState,
+};
+use crate::ID;
+use crate::{
+    MERCHANT_CONFIG_SEED, MERCHANT_DEBITOR_SEED, MERCHANT_DESTINATION_SEED, MERCHANT_MINT_SEED,
+    MERCHANT_SPEND_SEED, STATE_SEED,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+/**
+ * Check whether a debit_user call would succeed, without moving tokens or updating any state.
+ *
+ * Integrators use this dry run to pre-validate a debit (limits, allowlists, mints) before
+ * asking the debitor to sign it. The instruction runs the checks of debit_user on copies of
+ * the delegate, debitor and merchant spend tracker states, then reports the outcome in a
+ * DebitSimulationResult event instead of failing, so the event can be read from a simulated
+ * or sent transaction alike.
+ *
+ * Checks:
+ * - The same checks as debit_user, in the same order, except the token transfer itself. The
+ *   checks that don't update state are the functions debit_user runs itself
+ * - The token accounts must use the debit's mint; debit_user enforces this as account
+ *   constraints, here it is reported in the event
+ *
+ * Account Updates:
+ * - None: every account is read-only and the limit checks run on copies of the states
+ *
+ * Security Model:
+ * - The debitor does not need to sign, as nothing is debited
+ *
+ * Events Emitted:
+ * - DebitSimulationResult: The outcome of the simulated debit
+ *   Fields: merchant_id, user_delegate, debitor, destination_ata, amount, success, reason_code
+ *
+ * Required Accounts:
+ * - The accounts of debit_user, without the payer and the system program
+ */
+#[derive(Accounts)]
+#[instruction(merchant_id: u64)]
+pub struct SimulateDebitUser<'info> {
+    /// PDA storing the delegate's transfer limits and state
+    /// Seeds: [USER_DELEGATE_SEED, merchant_id, mint, user_token_account]
+    /// Required permissions: Read-only
+    #[account(
+        seeds = [USER_DELEGATE_SEED, merchant_id_seed(merchant_id).as_ref(), mint.key().as_ref(), user_token_account.key().as_ref()],
+        bump = user_delegate_account.bump,
+        seeds::program = ID
+    )]
+    pub user_delegate_account: Account<'info, UserDelegateState>,
+
+    /// Account that would initiate the debit
+    /// Required permissions: None
+    /// CHECK: Only used to derive the debitor state PDA
+    pub debitor: UncheckedAccount<'info>,
+
+    /// PDA storing the debitor's authorization state and limits for this merchant and mint
+    /// Seeds: [MERCHANT_DEBITOR_SEED, merchant_id, mint, debitor]
+    /// Required permissions: Read-only
+    /// CHECK: Address is verified by seeds; the debitor is not authorized for the mint if it
+    /// has not been created
+    #[account(seeds = [MERCHANT_DEBITOR_SEED, merchant_id_seed(merchant_id).as_ref(), mint.key().as_ref(), debitor.key().as_ref()], bump, seeds::program = ID)]
+    pub debitor_state: UncheckedAccount<'info>,
+
+    /// Token account that would receive the debited tokens
+    /// Required permissions: Read-only
+    pub destination_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// PDA storing the destination's authorization state for this merchant and mint
+    /// Seeds: [MERCHANT_DESTINATION_SEED, merchant_id, mint, destination_token_account]
+    /// Required permissions: Read-only
+    /// CHECK: Address is verified by seeds; treated as unlisted if it has not been created
+    #[account(
+        seeds = [MERCHANT_DESTINATION_SEED, merchant_id_seed(merchant_id).as_ref(), mint.key().as_ref(), destination_token_account.key().as_ref()],
+        bump,
+        seeds::program = ID)]
+    pub destination_state: UncheckedAccount<'info>,
+
+    /// PDA storing the merchant configuration
+    /// Seeds: [MERCHANT_CONFIG_SEED, merchant_id]
+    /// Required permissions: Read-only
+    /// CHECK: Address is verified by seeds; default settings apply if it has not been created
+    #[account(
+        seeds = [MERCHANT_CONFIG_SEED, merchant_id_seed(merchant_id).as_ref()],
+        bump,
+        seeds::program = ID
+    )]
+    pub merchant_config: UncheckedAccount<'info>,
+
+    /// Global program state storing the feature flags and protocol fee
+    /// Seeds: [STATE_SEED]
+    /// Required permissions: Read-only
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        seeds::program = ID
+    )]
+    pub state: Account<'info, BridgeCardsState>,
+
+    /// User's token account that would be debited
+    /// Required permissions: Read-only
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Owner of the user's token account
+    /// Required permissions: Read-only
+    /// CHECK: Must be the owner recorded in the user token account
+    #[account(address = user_token_account.owner)]
+    pub user_owner: UncheckedAccount<'info>,
+
+    /// The mint of the tokens that would be transferred
+    /// Required permissions: Read-only
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Token program of the debit
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// PDA tracking the merchant's spend across all of its delegates
+    /// Seeds: [MERCHANT_SPEND_SEED, merchant_id]
+    /// Required permissions: Read-only
+    /// CHECK: Address is verified by seeds; no merchant-wide limit applies if it has not been
+    /// created
+    #[account(
+        seeds = [MERCHANT_SPEND_SEED, merchant_id_seed(merchant_id).as_ref()],
+        bump,
+        seeds::program = ID
+    )]
+    pub merchant_spend_tracker: UncheckedAccount<'info>,
+
+    /// Token account of the protocol fee owner that would receive the protocol fee
+    /// Required when the program's protocol fee is set
+    /// Required permissions: Read-only
+    pub fee_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// PDA allowlisting the mint for the merchant
+    /// Seeds: [MERCHANT_MINT_SEED, merchant_id, mint]
+    /// Required when the merchant config enforces the mint allowlist
+    /// Required permissions: Read-only
+    /// CHECK: Address is verified by seeds; the mint is not allowlisted if it has not been created
+    #[account(
+        seeds = [MERCHANT_MINT_SEED, merchant_id_seed(merchant_id).as_ref(), mint.key().as_ref()],
+        bump,
+        seeds::program = ID
+    )]
+    pub merchant_mint: Option<UncheckedAccount<'info>>,
+}
+
+/**
+ * Process a debit simulation.
+ *
+ * @param ctx The instruction context containing all required accounts
+ * @param merchant_id Unique identifier for the merchant
+ * @param amount Number of tokens that would be transferred (in smallest units)
+ *
+ * Flow:
+ * 1. Run the debit checks on copies of the states
+ * 2. Emit the outcome, with the error code the debit would fail with
+ *
+ * @return Result indicating success or containing an error; a failing simulated debit still
+ * succeeds
+ */
+pub fn handler(ctx: Context<SimulateDebitUser>, merchant_id: u64, amount: u64) -> Result<()> {
+    let outcome = simulate_debit(ctx.accounts, amount);
+
+    emit!(DebitSimulationResult {
+        merchant_id,
+        user_delegate: ctx.accounts.user_delegate_account.key(),
+        debitor: ctx.accounts.debitor.key(),
+        destination_ata: ctx.accounts.destination_token_account.key(),
+        amount,
+        success: outcome.is_ok(),
+        reason_code: outcome.err().map_or(0, |error| reason_code(&error)),
+    });
+
+    Ok(())
+}
+
+/// Run the checks of debit_user against copies of the mutable states
+fn simulate_debit(accounts: &SimulateDebitUser, amount: u64) -> Result<()> {
+    // debit_user checks these through account constraints, before its handler runs
+    let mint = accounts.mint.key();
+    if accounts.user_token_account.mint != mint
+        || accounts.destination_token_account.mint != mint
+        || accounts
+            .fee_token_account
+            .as_ref()
+            .is_some_and(|fee_token_account| fee_token_account.mint != mint)
+    {
+        return Err(ErrorCode::MismatchedMint.into());
+    }
+    let mut debitor_state = MerchantDebitorState::load_authorized(&accounts.debitor_state)?;
+
+    if accounts.state.has_feature_flag(feature_flags::PAUSED) {
+        return Err(ErrorCode::ProgramPaused.into());
+    }
+
+    let token_program = accounts.token_program.key();
+    check_debit_token_accounts(
+        &token_program,
+        &accounts.user_token_account,
+        &accounts.destination_token_account,
+    )?;
+
+    if accounts.state.protocol_fee(amount)? > 0 {
+        verify_fee_token_account(
+            &accounts.state,
+            accounts.fee_token_account.as_ref(),
+            &token_program,
+        )?;
+    }
+
+    let merchant_config = MerchantConfigState::load_or_default(&accounts.merchant_config)?;
+    check_merchant_debit(
+        &merchant_config,
+        accounts.merchant_mint.as_deref(),
+        &accounts.user_owner,
+    )?;
+    let destination_state = MerchantDestinationState::load_optional(&accounts.destination_state)?;
+    check_debit_destination(
+        &merchant_config,
+        &accounts.user_delegate_account,
+        destination_state.as_ref(),
+        &accounts.destination_token_account,
+    )?;
+
+    // The limit checks update period tracking, so they run on copies that are dropped
+    let mut user_delegate = (*accounts.user_delegate_account).clone();
+    let policy = DebitPolicy::resolve(&merchant_config, &user_delegate);
+    let clock = Clock::get()?;
+    user_delegate.check_active(clock.unix_timestamp)?;
+    if accounts
+        .state
+        .has_feature_flag(feature_flags::CLOCK_ANOMALY_CHECKS)
+    {
+        user_delegate.check_clock(
+            clock.unix_timestamp,
+            clock.slot,
+            merchant_config.max_clock_skew_seconds(),
+        )?;
+    }
+    user_delegate.validate_debit_and_update_with_policy(
+        amount,
+        clock.unix_timestamp as u64,
+        clock.slot,
+        &policy,
+    )?;
+    debitor_state.record_debit(amount, clock.unix_timestamp as u64)?;
+    if let Some(mut merchant_spend_tracker) =
+        MerchantSpendTrackerState::load_optional(&accounts.merchant_spend_tracker)?
+    {
+        merchant_spend_tracker.record_debit(amount, clock.unix_timestamp as u64)?;
+    }
+
+    verify_delegate_approval(
+        &accounts.user_token_account,
+        &accounts.user_delegate_account.key(),
+        amount,
+    )
+}
+
+/// Error code a failed transaction would report for the error
+pub(crate) fn reason_code(error: &Error) -> u64 {
+    match error {
+        Error::AnchorError(anchor_error) => u64::from(anchor_error.error_code_number),
+        Error::ProgramError(program_error) => u64::from(program_error.program_error.clone()),
+    }
+}
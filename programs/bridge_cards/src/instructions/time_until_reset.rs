@@ -0,0 +1,64 @@
+use crate::instructions::add_or_update_user_delegate::USER_DELEGATE_SEED;
+use crate::pda::merchant_id_seed;
+use crate::state::UserDelegateState;
+use crate::ID;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+/**
+ * Return the number of seconds until a user delegate's transfer limit period resets.
+ *
+ * This read-only instruction lets clients display when a delegate's period limit frees up
+ * without reimplementing the reset math. The value is returned through the transaction's
+ * return data (set_return_data) as a little-endian u64.
+ *
+ * Computation:
+ * - period_timestamp_last_reset + transfer_limit_period_seconds - now
+ * - Returns 0 if the period has already elapsed
+ *
+ * Account Derivation:
+ * - User delegate PDA: [USER_DELEGATE_SEED, merchant_id, mint, user_token_account]
+ *
+ * Required Accounts:
+ * - user_delegate_account: PDA storing the delegate's limits and period tracking
+ * - mint: Token mint of the user token account
+ * - user_token_account: Token account the delegate can debit
+ */
+#[derive(Accounts)]
+#[instruction(merchant_id: u64)]
+pub struct TimeUntilReset<'info> {
+    /// PDA storing the delegate's transfer limits and period tracking
+    /// Seeds: [USER_DELEGATE_SEED, merchant_id, mint, user_token_account]
+    /// Required permissions: Read-only
+    #[account(
+        seeds = [USER_DELEGATE_SEED, merchant_id_seed(merchant_id).as_ref(), mint.key().as_ref(), user_token_account.key().as_ref()],
+        bump = user_delegate_account.bump,
+        seeds::program = ID
+    )]
+    pub user_delegate_account: Account<'info, UserDelegateState>,
+
+    /// Token mint of the user token account
+    /// Required permissions: None (used for PDA derivation)
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Token account the delegate can debit
+    /// Required permissions: None (used for PDA derivation)
+    #[account(constraint = user_token_account.mint == mint.key())]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+}
+
+/**
+ * Compute the time remaining until the delegate's period resets.
+ *
+ * @param ctx The instruction context containing all required accounts
+ * @param _merchant_id Unique identifier for the merchant (used for PDA derivation)
+ *
+ * @return Seconds until the period resets, or 0 if it has already elapsed
+ */
+pub fn handler(ctx: Context<TimeUntilReset>, _merchant_id: u64) -> Result<u64> {
+    let clock = Clock::get()?;
+    Ok(ctx
+        .accounts
+        .user_delegate_account
+        .time_until_reset(clock.unix_timestamp as u64))
+}
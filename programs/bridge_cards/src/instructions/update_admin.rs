@@ -1,6 +1,6 @@
 use crate::events::AdminUpdated;
 use crate::instructions::initialize::STATE_SEED;
-use crate::state::BridgeCardsState;
+use crate::state::{BridgeCardsState, CURRENT_VERSION};
 use crate::ID;
 use anchor_lang::prelude::*;
 
@@ -86,6 +86,7 @@ pub struct UpdateAdmin<'info> {
 pub fn handler(ctx: Context<UpdateAdmin>) -> Result<()> {
     let state = &mut ctx.accounts.state;
     state.admin = ctx.accounts.new_admin.key();
+    state.version = CURRENT_VERSION;
     state.bump = ctx.bumps.state;
 
     // Emit event for indexing and notifications
@@ -19,12 +19,24 @@
  * - Debitors can only initiate transfers within a delegate's configured limits
  */
 pub mod errors;
+/**
+ * Decoding of the program's events from transaction logs, for tests and indexers.
+ * Only available off-chain, with the event-parsing feature.
+ */
+#[cfg(all(feature = "event-parsing", not(target_os = "solana")))]
+pub mod event_parsing;
 pub mod events;
 pub mod instructions;
+/**
+ * Canonical PDA derivations built from the program's own seed constants and ID, so off-chain
+ * clients can derive account addresses without re-implementing the seed layouts.
+ */
+pub mod pda;
 pub mod state;
 
 use anchor_lang::prelude::*;
 pub use instructions::*;
+pub use limit_check;
 #[cfg(not(feature = "no-entrypoint"))]
 use solana_security_txt::security_txt;
 
@@ -60,6 +72,30 @@ pub mod bridge_cards {
         instructions::initialize::handler(ctx)
     }
 
+    /**
+     * Initialize the Bridge Cards program state if it doesn't exist yet.
+     * Behaves as initialize on the first call and is a no-op afterwards,
+     * leaving the existing admin untouched.
+     *
+     * @param ctx Context containing:
+     *   - The system program for account creation
+     *   - The signer who will become the admin if the state is created
+     *   - The state account to initialize
+     */
+    pub fn initialize_idempotent(ctx: Context<InitializeIdempotent>) -> Result<()> {
+        instructions::initialize_idempotent::handler(ctx)
+    }
+
+    /**
+     * Return whether the program has been initialized.
+     * The value is returned via return data.
+     *
+     * @param ctx Context containing required accounts
+     */
+    pub fn is_initialized(ctx: Context<IsInitialized>) -> Result<bool> {
+        instructions::is_initialized::handler(ctx)
+    }
+
     /**
      * Add or update a user delegate for a specific merchant.
      * Delegates can initiate transfers on behalf of users within configured limits.
@@ -69,6 +105,7 @@ pub mod bridge_cards {
      * @param max_transfer_limit Maximum amount allowed in a single transfer
      * @param period_transfer_limit Maximum amount allowed within the time period
      * @param transfer_limit_period Duration of the transfer limit period in seconds
+     * @param options Optional overrides of merchant-wide settings
      */
     pub fn add_or_update_user_delegate(
         ctx: Context<AddOrUpdateUserDelegate>,
@@ -76,6 +113,7 @@ pub mod bridge_cards {
         max_transfer_limit: u64,
         period_transfer_limit: u64,
         transfer_limit_period: u32,
+        options: UserDelegateOptions,
     ) -> Result<()> {
         instructions::add_or_update_user_delegate::handler(
             ctx,
@@ -83,6 +121,139 @@ pub mod bridge_cards {
             max_transfer_limit,
             period_transfer_limit,
             transfer_limit_period,
+            options,
+        )
+    }
+
+    /**
+     * Create a user delegate with the limits of the merchant's template.
+     * Only a merchant manager can execute this instruction.
+     *
+     * @param ctx Context containing required accounts
+     * @param merchant_id Unique identifier for the merchant
+     * @param options Optional overrides of merchant-wide settings
+     */
+    pub fn create_user_delegate_from_template(
+        ctx: Context<CreateUserDelegateFromTemplate>,
+        merchant_id: u64,
+        options: UserDelegateOptions,
+    ) -> Result<()> {
+        instructions::create_user_delegate_from_template::handler(ctx, merchant_id, options)
+    }
+
+    /**
+     * Revoke a user delegate and close its account, returning the rent to the payer.
+     * Only a merchant manager can execute this instruction.
+     *
+     * @param ctx Context containing required accounts
+     * @param merchant_id Unique identifier for the merchant
+     */
+    pub fn close_user_delegate(ctx: Context<CloseUserDelegate>, merchant_id: u64) -> Result<()> {
+        instructions::close_user_delegate::handler(ctx, merchant_id)
+    }
+
+    /**
+     * Move a user delegate to a new token account of the same user and mint.
+     * Closes the old delegate and creates the new one with the same limits and period tracking.
+     * Only a merchant manager can execute this instruction.
+     *
+     * @param ctx Context containing required accounts
+     * @param merchant_id Unique identifier for the merchant
+     */
+    pub fn migrate_user_delegate(
+        ctx: Context<MigrateUserDelegate>,
+        merchant_id: u64,
+    ) -> Result<()> {
+        instructions::migrate_user_delegate::handler(ctx, merchant_id)
+    }
+
+    /**
+     * Set a user delegate's limits with both the merchant manager and admin signing.
+     * This is the only way to raise limits when the merchant only lets managers lower them.
+     *
+     * @param ctx Context containing required accounts
+     * @param merchant_id Unique identifier for the merchant
+     * @param per_transfer_limit New maximum amount allowed in a single transfer
+     * @param period_transfer_limit New maximum amount allowed within the time period
+     */
+    pub fn admin_raise_delegate_limits(
+        ctx: Context<AdminRaiseDelegateLimits>,
+        merchant_id: u64,
+        per_transfer_limit: u64,
+        period_transfer_limit: u64,
+    ) -> Result<()> {
+        instructions::admin_raise_delegate_limits::handler(
+            ctx,
+            merchant_id,
+            per_transfer_limit,
+            period_transfer_limit,
+        )
+    }
+
+    /**
+     * Move a user's tokens to a recovery token account, e.g. when a delegate is compromised.
+     * Requires both the admin and the owner of the user token account to sign, and bypasses
+     * delegate limits.
+     *
+     * @param ctx Context containing required accounts
+     * @param amount Maximum number of tokens to move, capped at the user's balance
+     */
+    pub fn admin_recover_user_funds(
+        ctx: Context<AdminRecoverUserFunds>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::admin_recover_user_funds::handler(ctx, amount)
+    }
+
+    /**
+     * Lower a user delegate's limits. Signed by the owner of the delegated token account,
+     * who can only tighten the limits, never raise them.
+     *
+     * @param ctx Context containing required accounts
+     * @param merchant_id Unique identifier for the merchant
+     * @param per_transfer_limit New maximum amount allowed in a single transfer
+     * @param period_transfer_limit New maximum amount allowed within the time period
+     */
+    pub fn reduce_user_delegate_limits(
+        ctx: Context<ReduceUserDelegateLimits>,
+        merchant_id: u64,
+        per_transfer_limit: u64,
+        period_transfer_limit: u64,
+    ) -> Result<()> {
+        instructions::reduce_user_delegate_limits::handler(
+            ctx,
+            merchant_id,
+            per_transfer_limit,
+            period_transfer_limit,
+        )
+    }
+
+    /**
+     * Re-issue an existing user delegate with new limits, optionally carrying over the
+     * spend already recorded for the current period.
+     *
+     * @param ctx Context containing required accounts
+     * @param merchant_id Unique identifier for the merchant
+     * @param per_transfer_limit New maximum amount allowed in a single transfer
+     * @param period_transfer_limit New maximum amount allowed within the time period
+     * @param transfer_limit_period New duration of the transfer limit period in seconds
+     * @param carry_usage Whether the current period's usage is preserved (true) or reset
+     */
+    pub fn reissue_delegate(
+        ctx: Context<ReissueDelegate>,
+        merchant_id: u64,
+        per_transfer_limit: u64,
+        period_transfer_limit: u64,
+        transfer_limit_period: u32,
+        carry_usage: bool,
+    ) -> Result<()> {
+        instructions::reissue_delegate::handler(
+            ctx,
+            merchant_id,
+            per_transfer_limit,
+            period_transfer_limit,
+            transfer_limit_period,
+            carry_usage,
         )
     }
 
@@ -93,21 +264,75 @@ pub mod bridge_cards {
      * @param ctx Context containing required accounts
      * @param merchant_id Unique identifier for the merchant
      * @param destination_allowed Whether the destination account should be allowed to receive funds
+     * @param is_primary Whether the destination is the merchant's primary destination for the mint
      */
     pub fn add_or_update_merchant_destination(
         ctx: Context<AddOrUpdateMerchantDestination>,
         merchant_id: u64,
         destination_allowed: bool,
+        is_primary: bool,
     ) -> Result<()> {
         instructions::add_or_update_merchant_destination::handler(
             ctx,
             merchant_id,
             destination_allowed,
+            is_primary,
         )
     }
 
     /**
-     * Add or update a merchant manager.
+     * Add or update several merchant destination accounts in one transaction.
+     * Every destination must use the mint; otherwise no destination is updated.
+     *
+     * @param ctx Context containing required accounts; remaining accounts are one
+     * (destination token account, destination state) pair per destination
+     * @param merchant_id Unique identifier for the merchant
+     * @param destination_allowed Whether the destination accounts should be allowed to receive funds
+     */
+    pub fn batch_add_destinations<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchAddDestinations<'info>>,
+        merchant_id: u64,
+        destination_allowed: bool,
+    ) -> Result<()> {
+        instructions::batch_add_destinations::handler(ctx, merchant_id, destination_allowed)
+    }
+
+    /**
+     * Add or update a mint allowlisted for a merchant.
+     * Allowlisted mints are enforced when the merchant config sets enforce_mint_allowlist.
+     *
+     * @param ctx Context containing required accounts
+     * @param merchant_id Unique identifier for the merchant
+     * @param mint_allowed Whether the merchant may use the mint
+     */
+    pub fn add_or_update_merchant_mint(
+        ctx: Context<AddOrUpdateMerchantMint>,
+        merchant_id: u64,
+        mint_allowed: bool,
+    ) -> Result<()> {
+        instructions::add_or_update_merchant_mint::handler(ctx, merchant_id, mint_allowed)
+    }
+
+    /**
+     * Remove a merchant destination and close its account, returning the rent to the payer.
+     * Only the admin can execute this instruction.
+     *
+     * @param ctx Context containing required accounts
+     * @param merchant_id Unique identifier for the merchant
+     * @param mint Token mint of the destination
+     * @param destination Destination token account whose state is closed
+     */
+    pub fn close_merchant_destination(
+        ctx: Context<CloseMerchantDestination>,
+        merchant_id: u64,
+        mint: Pubkey,
+        destination: Pubkey,
+    ) -> Result<()> {
+        instructions::close_merchant_destination::handler(ctx, merchant_id, mint, destination)
+    }
+
+    /**
+     * Set a merchant's manager, replacing any existing managers.
      * Managers can configure delegate and destination settings for their merchant.
      *
      * @param ctx Context containing required accounts
@@ -120,6 +345,47 @@ pub mod bridge_cards {
         instructions::add_or_update_merchant_manager::handler(ctx, merchant_id)
     }
 
+    /**
+     * Add a manager alongside a merchant's existing managers.
+     * Only the admin can execute this instruction.
+     *
+     * @param ctx Context containing required accounts
+     * @param merchant_id Unique identifier for the merchant
+     */
+    pub fn add_merchant_manager(ctx: Context<AddMerchantManager>, merchant_id: u64) -> Result<()> {
+        instructions::add_merchant_manager::handler(ctx, merchant_id)
+    }
+
+    /**
+     * Remove one of a merchant's managers, keeping at least one.
+     * Only the admin can execute this instruction.
+     *
+     * @param ctx Context containing required accounts
+     * @param merchant_id Unique identifier for the merchant
+     * @param manager Manager to remove
+     */
+    pub fn remove_merchant_manager(
+        ctx: Context<RemoveMerchantManager>,
+        merchant_id: u64,
+        manager: Pubkey,
+    ) -> Result<()> {
+        instructions::remove_merchant_manager::handler(ctx, merchant_id, manager)
+    }
+
+    /**
+     * Remove a merchant manager and close its account, returning the rent to the payer.
+     * Only the admin can execute this instruction.
+     *
+     * @param ctx Context containing required accounts
+     * @param merchant_id Unique identifier for the merchant
+     */
+    pub fn close_merchant_manager(
+        ctx: Context<CloseMerchantManager>,
+        merchant_id: u64,
+    ) -> Result<()> {
+        instructions::close_merchant_manager::handler(ctx, merchant_id)
+    }
+
     /**
      * Add or update a merchant debitor account.
      * Debitor accounts are authorized to initiate transfers from user delegates.
@@ -127,18 +393,47 @@ pub mod bridge_cards {
      * @param ctx Context containing required accounts
      * @param merchant_id Unique identifier for the merchant
      * @param debitor_allowed Whether the debitor account should be allowed to initiate transfers
+     * @param per_call_limit Maximum amount the debitor can transfer per debit (0 for no cap)
+     * @param daily_limit Maximum amount the debitor can transfer per day (0 for no cap)
      */
     pub fn add_or_update_merchant_debitor(
         ctx: Context<AddOrUpdateMerchantDebitor>,
         merchant_id: u64,
         debitor_allowed: bool,
+        per_call_limit: u64,
+        daily_limit: u64,
+    ) -> Result<()> {
+        instructions::add_or_update_merchant_debitor::handler(
+            ctx,
+            merchant_id,
+            debitor_allowed,
+            per_call_limit,
+            daily_limit,
+        )
+    }
+
+    /**
+     * Remove a merchant debitor and close its account, returning the rent to the payer.
+     * Only a merchant manager can execute this instruction.
+     *
+     * @param ctx Context containing required accounts
+     * @param merchant_id Unique identifier for the merchant
+     * @param mint Token mint the debitor was authorized for
+     * @param debitor Debitor whose state is closed
+     */
+    pub fn close_merchant_debitor(
+        ctx: Context<CloseMerchantDebitor>,
+        merchant_id: u64,
+        mint: Pubkey,
+        debitor: Pubkey,
     ) -> Result<()> {
-        instructions::add_or_update_merchant_debitor::handler(ctx, merchant_id, debitor_allowed)
+        instructions::close_merchant_debitor::handler(ctx, merchant_id, mint, debitor)
     }
 
     /**
      * Debit tokens from a user's account via their delegate.
      * The transfer must be within the delegate's configured limits.
+     * An on-chain receipt of the debit is written if the debit_receipt account is passed.
      *
      * @param ctx Context containing required accounts
      * @param merchant_id Unique identifier for the merchant
@@ -148,6 +443,260 @@ pub mod bridge_cards {
         instructions::debit_user::handler(ctx, merchant_id, amount)
     }
 
+    /**
+     * Debit tokens from a user's account via their delegate, recording a debit that exceeds
+     * the delegate's limits in a DebitRejected event instead of failing.
+     *
+     * @param ctx Context containing required accounts (same as debit_user)
+     * @param merchant_id Unique identifier for the merchant
+     * @param amount Amount of tokens to transfer
+     */
+    pub fn debit_user_logged(ctx: Context<DebitUser>, merchant_id: u64, amount: u64) -> Result<()> {
+        instructions::debit_user_logged::handler(ctx, merchant_id, amount)
+    }
+
+    /**
+     * Check whether a debit_user call would succeed, without moving tokens or updating state.
+     * The outcome is reported in a DebitSimulationResult event.
+     *
+     * @param ctx Context containing required accounts (debit_user's, without payer and system program)
+     * @param merchant_id Unique identifier for the merchant
+     * @param amount Amount of tokens that would be transferred
+     */
+    pub fn simulate_debit_user(
+        ctx: Context<SimulateDebitUser>,
+        merchant_id: u64,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::simulate_debit_user::handler(ctx, merchant_id, amount)
+    }
+
+    /**
+     * Debit a user so that the destination receives exactly the given amount.
+     * The amount is grossed up by the mint's token-2022 transfer fee, and the grossed-up
+     * amount must be within the delegate's configured limits.
+     *
+     * @param ctx Context containing required accounts (same as debit_user)
+     * @param merchant_id Unique identifier for the merchant
+     * @param amount Amount of tokens the destination must receive
+     */
+    pub fn debit_user_exact_out(
+        ctx: Context<DebitUser>,
+        merchant_id: u64,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::debit_user_exact_out::handler(ctx, merchant_id, amount)
+    }
+
+    /**
+     * Debit tokens from a user's account and invoke the merchant's router to swap them.
+     * The transfer must be within the delegate's configured limits and the router must be
+     * configured for the merchant and allowlisted by the admin.
+     *
+     * @param ctx Context containing required accounts; remaining accounts are forwarded to the router
+     * @param merchant_id Unique identifier for the merchant
+     * @param amount Amount of tokens to transfer
+     * @param route_data Instruction data forwarded to the router program
+     */
+    pub fn debit_user_with_swap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, DebitUserWithSwap<'info>>,
+        merchant_id: u64,
+        amount: u64,
+        route_data: Vec<u8>,
+    ) -> Result<()> {
+        instructions::debit_user_with_swap::handler(ctx, merchant_id, amount, route_data)
+    }
+
+    /**
+     * Debit tokens from a user's account via their delegate, attaching a merchant reference
+     * (e.g. an invoice number) to the UserDebited event and, when the Memo program is passed,
+     * logging it through a Memo CPI.
+     *
+     * @param ctx Context containing required accounts (debit_user's, plus the optional Memo program)
+     * @param merchant_id Unique identifier for the merchant
+     * @param amount Amount of tokens to transfer
+     * @param memo Merchant reference of at most MAX_MEMO_LENGTH bytes
+     */
+    pub fn debit_user_with_memo(
+        ctx: Context<DebitUserWithMemo>,
+        merchant_id: u64,
+        amount: u64,
+        memo: Vec<u8>,
+    ) -> Result<()> {
+        instructions::debit_user_with_memo::handler(ctx, merchant_id, amount, memo)
+    }
+
+    /**
+     * Debit wrapped SOL from a user's account via their delegate and pay the merchant in SOL.
+     * The transfer must be within the delegate's configured limits for the native mint.
+     *
+     * @param ctx Context containing required accounts (debit_user's on the native mint, plus the SOL recipient)
+     * @param merchant_id Unique identifier for the merchant
+     * @param amount Amount of lamports to transfer
+     */
+    pub fn debit_user_sol(ctx: Context<DebitUserSol>, merchant_id: u64, amount: u64) -> Result<()> {
+        instructions::debit_user_sol::handler(ctx, merchant_id, amount)
+    }
+
+    /**
+     * Debit tokens from a user's account and split them across several merchant destinations.
+     * The total of the amounts must be within the delegate's configured limits and every
+     * destination must be allowed; otherwise nothing is transferred.
+     *
+     * @param ctx Context containing required accounts; remaining accounts are one
+     * (destination token account, destination state) pair per amount
+     * @param merchant_id Unique identifier for the merchant
+     * @param amounts Amount of tokens to transfer to each destination
+     */
+    pub fn batch_debit_user<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchDebitUser<'info>>,
+        merchant_id: u64,
+        amounts: Vec<u64>,
+    ) -> Result<()> {
+        instructions::batch_debit_user::handler(ctx, merchant_id, amounts)
+    }
+
+    /**
+     * Add or update the configuration of a merchant.
+     * Only the admin can execute this instruction.
+     *
+     * @param ctx Context containing required accounts
+     * @param merchant_id Unique identifier for the merchant
+     * @param params New settings for the merchant
+     */
+    pub fn add_or_update_merchant_config(
+        ctx: Context<AddOrUpdateMerchantConfig>,
+        merchant_id: u64,
+        params: MerchantConfigParams,
+    ) -> Result<()> {
+        instructions::add_or_update_merchant_config::handler(ctx, merchant_id, params)
+    }
+
+    /**
+     * Add or update a merchant's spend limit across all of its delegates.
+     * Only the admin can execute this instruction.
+     *
+     * @param ctx Context containing required accounts
+     * @param merchant_id Unique identifier for the merchant
+     * @param period_transfer_limit Maximum total amount per period across all delegates
+     * @param transfer_limit_period Duration of the period in seconds
+     */
+    pub fn add_or_update_merchant_spend_tracker(
+        ctx: Context<AddOrUpdateMerchantSpendTracker>,
+        merchant_id: u64,
+        period_transfer_limit: u64,
+        transfer_limit_period: u32,
+    ) -> Result<()> {
+        instructions::add_or_update_merchant_spend_tracker::handler(
+            ctx,
+            merchant_id,
+            period_transfer_limit,
+            transfer_limit_period,
+        )
+    }
+
+    /**
+     * Create a merchant's registry, counting the destinations, debitors and delegates created
+     * while it is passed to their add/update instructions.
+     * Only the admin can execute this instruction.
+     *
+     * @param ctx Context containing required accounts
+     * @param merchant_id Unique identifier for the merchant
+     */
+    pub fn initialize_merchant_registry(
+        ctx: Context<InitializeMerchantRegistry>,
+        merchant_id: u64,
+    ) -> Result<()> {
+        instructions::initialize_merchant_registry::handler(ctx, merchant_id)
+    }
+
+    /**
+     * Add or update a router program.
+     * Routers are external programs merchants may use to swap debited funds.
+     *
+     * @param ctx Context containing required accounts
+     * @param router_allowed Whether the router program should be allowed to be invoked
+     */
+    pub fn add_or_update_router(
+        ctx: Context<AddOrUpdateRouter>,
+        router_allowed: bool,
+    ) -> Result<()> {
+        instructions::add_or_update_router::handler(ctx, router_allowed)
+    }
+
+    /**
+     * Return the seconds remaining until a user delegate's transfer limit period resets.
+     * The value is returned via return data; 0 means the period has already elapsed.
+     *
+     * @param ctx Context containing required accounts
+     * @param merchant_id Unique identifier for the merchant
+     */
+    pub fn time_until_reset(ctx: Context<TimeUntilReset>, merchant_id: u64) -> Result<u64> {
+        instructions::time_until_reset::handler(ctx, merchant_id)
+    }
+
+    /**
+     * Emit a UserDelegateStateSnapshot event with a user delegate's current limits.
+     * Read-only; requires no authority beyond the delegate PDA.
+     *
+     * @param ctx Context containing required accounts
+     * @param merchant_id Unique identifier for the merchant
+     */
+    pub fn log_user_delegate_state(
+        ctx: Context<LogUserDelegateState>,
+        merchant_id: u64,
+    ) -> Result<()> {
+        instructions::log_user_delegate_state::handler(ctx, merchant_id)
+    }
+
+    /**
+     * Set the global feature flag bitmask.
+     * Only the admin can execute this instruction.
+     *
+     * @param ctx Context containing required accounts
+     * @param flags New feature flag bitmask (see state::feature_flags)
+     */
+    pub fn set_feature_flags(ctx: Context<SetFeatureFlags>, flags: u64) -> Result<()> {
+        instructions::set_feature_flags::handler(ctx, flags)
+    }
+
+    /**
+     * Rewrite a program account created before the current layout version in the current
+     * layout. Accounts already at the current version are left unchanged.
+     * Only the admin can execute this instruction.
+     *
+     * @param ctx Context containing required accounts
+     */
+    pub fn migrate_account(ctx: Context<MigrateAccount>) -> Result<()> {
+        instructions::migrate_account::handler(ctx)
+    }
+
+    /**
+     * Set the protocol fee skimmed from every debit and the owner receiving it.
+     * Only the admin can execute this instruction.
+     *
+     * @param ctx Context containing required accounts
+     * @param fee_bps Protocol fee in basis points (0 disables the fee)
+     * @param fee_owner Owner of the token accounts receiving the fee
+     */
+    pub fn set_protocol_fee(
+        ctx: Context<SetProtocolFee>,
+        fee_bps: u16,
+        fee_owner: Pubkey,
+    ) -> Result<()> {
+        instructions::set_protocol_fee::handler(ctx, fee_bps, fee_owner)
+    }
+
+    /**
+     * Emit a Heartbeat event with the admin, current time, feature flags and protocol fee.
+     * Only the admin can execute this instruction; no state is modified.
+     *
+     * @param ctx Context containing required accounts
+     */
+    pub fn admin_heartbeat(ctx: Context<AdminHeartbeat>) -> Result<()> {
+        instructions::admin_heartbeat::handler(ctx)
+    }
+
     /**
      * Update the program admin.
      * Only the current admin can execute this instruction.
@@ -159,13 +708,44 @@ pub mod bridge_cards {
     }
 
     /**
-     * Close a program account and recover its rent.
+     * Close a program account and recover its rent, to the payer or the optional rent recipient.
      * Only the admin can execute this instruction.
      *
      * @param ctx Context containing required accounts
      * @param input_seeds Seeds used to derive the PDA being closed
+     * @param bump Bump of the PDA, or None to search for the canonical bump
+     */
+    pub fn close_account(
+        ctx: Context<CloseAccount>,
+        input_seeds: Vec<Vec<u8>>,
+        bump: Option<u8>,
+    ) -> Result<()> {
+        instructions::close_account::handler(ctx, input_seeds, bump)
+    }
+
+    /**
+     * Close several program accounts and recover their rent.
+     * Only the admin can execute this instruction; if any account fails validation, none are
+     * closed.
+     *
+     * @param ctx Context containing required accounts; remaining accounts are the PDAs to close
+     * @param input_seeds Seeds used to derive each remaining account's PDA, in order
+     */
+    pub fn close_accounts<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CloseAccounts<'info>>,
+        input_seeds: Vec<Vec<Vec<u8>>>,
+    ) -> Result<()> {
+        instructions::close_accounts::handler(ctx, input_seeds)
+    }
+
+    /**
+     * Close the global state and return its rent to the admin, decommissioning the program.
+     * Only the admin can execute this instruction; initialize can run again afterwards.
+     *
+     * @param ctx Context containing required accounts
+     * @param confirm Must be true to close the state
      */
-    pub fn close_account(ctx: Context<CloseAccount>, input_seeds: Vec<Vec<u8>>) -> Result<()> {
-        instructions::close_account::handler(ctx, input_seeds)
+    pub fn close_program_state(ctx: Context<CloseProgramState>, confirm: bool) -> Result<()> {
+        instructions::close_program_state::handler(ctx, confirm)
     }
 }
@@ -0,0 +1,277 @@
+use crate::{
+    DEBIT_RECEIPT_SEED, ID, MERCHANT_CONFIG_SEED, MERCHANT_DEBITOR_SEED, MERCHANT_DESTINATION_SEED,
+    MERCHANT_MANAGER_SEED, MERCHANT_MINT_SEED, MERCHANT_SPEND_SEED, ROUTER_SEED, STATE_SEED,
+    USER_DELEGATE_SEED,
+};
+use anchor_lang::prelude::*;
+
+/**
+ * Encode a merchant id as a PDA seed.
+ *
+ * Every PDA scoped to a merchant uses this encoding (little-endian u64), in account
+ * constraints and handlers alike, so derivations cannot drift apart.
+ *
+ * @param merchant_id Unique identifier for the merchant
+ */
+pub const fn merchant_id_seed(merchant_id: u64) -> [u8; 8] {
+    merchant_id.to_le_bytes()
+}
+
+// The *_seeds helpers return the seeds of each PDA without the bump, in the Vec<Vec<u8>>
+// form close_account takes, so clients don't have to rebuild them by hand.
+
+/**
+ * Derive the global program state PDA.
+ *
+ * Seeds: [STATE_SEED]
+ */
+pub fn find_state() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STATE_SEED], &ID)
+}
+
+/// Seeds of the global program state PDA, see find_state
+pub fn state_seeds() -> Vec<Vec<u8>> {
+    vec![STATE_SEED.to_vec()]
+}
+
+/**
+ * Derive the merchant manager PDA for a merchant.
+ *
+ * Seeds: [MERCHANT_MANAGER_SEED, merchant_id]
+ *
+ * @param merchant_id Unique identifier for the merchant
+ */
+pub fn find_merchant_manager(merchant_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            MERCHANT_MANAGER_SEED,
+            merchant_id_seed(merchant_id).as_ref(),
+        ],
+        &ID,
+    )
+}
+
+/// Seeds of the merchant manager PDA, see find_merchant_manager
+pub fn merchant_manager_seeds(merchant_id: u64) -> Vec<Vec<u8>> {
+    vec![
+        MERCHANT_MANAGER_SEED.to_vec(),
+        merchant_id_seed(merchant_id).to_vec(),
+    ]
+}
+
+/**
+ * Derive the merchant config PDA for a merchant.
+ *
+ * Seeds: [MERCHANT_CONFIG_SEED, merchant_id]
+ *
+ * @param merchant_id Unique identifier for the merchant
+ */
+pub fn find_merchant_config(merchant_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[MERCHANT_CONFIG_SEED, merchant_id_seed(merchant_id).as_ref()],
+        &ID,
+    )
+}
+
+/// Seeds of the merchant config PDA, see find_merchant_config
+pub fn merchant_config_seeds(merchant_id: u64) -> Vec<Vec<u8>> {
+    vec![
+        MERCHANT_CONFIG_SEED.to_vec(),
+        merchant_id_seed(merchant_id).to_vec(),
+    ]
+}
+
+/**
+ * Derive the merchant spend tracker PDA for a merchant.
+ *
+ * Seeds: [MERCHANT_SPEND_SEED, merchant_id]
+ *
+ * @param merchant_id Unique identifier for the merchant
+ */
+pub fn find_merchant_spend_tracker(merchant_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[MERCHANT_SPEND_SEED, merchant_id_seed(merchant_id).as_ref()],
+        &ID,
+    )
+}
+
+/// Seeds of the merchant spend tracker PDA, see find_merchant_spend_tracker
+pub fn merchant_spend_tracker_seeds(merchant_id: u64) -> Vec<Vec<u8>> {
+    vec![
+        MERCHANT_SPEND_SEED.to_vec(),
+        merchant_id_seed(merchant_id).to_vec(),
+    ]
+}
+
+/**
+ * Derive the merchant debitor PDA authorizing a debitor for a mint.
+ *
+ * Seeds: [MERCHANT_DEBITOR_SEED, merchant_id, mint, debitor]
+ *
+ * @param merchant_id Unique identifier for the merchant
+ * @param mint Token mint the debitor may debit
+ * @param debitor Account authorized to initiate debits
+ */
+pub fn find_merchant_debitor(merchant_id: u64, mint: &Pubkey, debitor: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            MERCHANT_DEBITOR_SEED,
+            merchant_id_seed(merchant_id).as_ref(),
+            mint.as_ref(),
+            debitor.as_ref(),
+        ],
+        &ID,
+    )
+}
+
+/// Seeds of the merchant debitor PDA, see find_merchant_debitor
+pub fn merchant_debitor_seeds(merchant_id: u64, mint: &Pubkey, debitor: &Pubkey) -> Vec<Vec<u8>> {
+    vec![
+        MERCHANT_DEBITOR_SEED.to_vec(),
+        merchant_id_seed(merchant_id).to_vec(),
+        mint.to_bytes().to_vec(),
+        debitor.to_bytes().to_vec(),
+    ]
+}
+
+/**
+ * Derive the merchant destination PDA allowlisting a destination token account.
+ *
+ * Seeds: [MERCHANT_DESTINATION_SEED, merchant_id, mint, destination]
+ *
+ * @param merchant_id Unique identifier for the merchant
+ * @param mint Token mint of the destination token account
+ * @param destination Destination token account receiving debited funds
+ */
+pub fn find_merchant_destination(
+    merchant_id: u64,
+    mint: &Pubkey,
+    destination: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            MERCHANT_DESTINATION_SEED,
+            merchant_id_seed(merchant_id).as_ref(),
+            mint.as_ref(),
+            destination.as_ref(),
+        ],
+        &ID,
+    )
+}
+
+/// Seeds of the merchant destination PDA, see find_merchant_destination
+pub fn merchant_destination_seeds(
+    merchant_id: u64,
+    mint: &Pubkey,
+    destination: &Pubkey,
+) -> Vec<Vec<u8>> {
+    vec![
+        MERCHANT_DESTINATION_SEED.to_vec(),
+        merchant_id_seed(merchant_id).to_vec(),
+        mint.to_bytes().to_vec(),
+        destination.to_bytes().to_vec(),
+    ]
+}
+
+/**
+ * Derive the user delegate PDA storing a user's limits for a merchant.
+ *
+ * Seeds: [USER_DELEGATE_SEED, merchant_id, mint, user_ata]
+ *
+ * @param merchant_id Unique identifier for the merchant
+ * @param mint Token mint of the user's token account
+ * @param user_ata Token account the delegate can debit
+ */
+pub fn find_user_delegate(merchant_id: u64, mint: &Pubkey, user_ata: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            USER_DELEGATE_SEED,
+            merchant_id_seed(merchant_id).as_ref(),
+            mint.as_ref(),
+            user_ata.as_ref(),
+        ],
+        &ID,
+    )
+}
+
+/// Seeds of the user delegate PDA, see find_user_delegate
+pub fn user_delegate_seeds(merchant_id: u64, mint: &Pubkey, user_ata: &Pubkey) -> Vec<Vec<u8>> {
+    vec![
+        USER_DELEGATE_SEED.to_vec(),
+        merchant_id_seed(merchant_id).to_vec(),
+        mint.to_bytes().to_vec(),
+        user_ata.to_bytes().to_vec(),
+    ]
+}
+
+/**
+ * Derive the merchant mint PDA allowlisting a mint for a merchant.
+ *
+ * Seeds: [MERCHANT_MINT_SEED, merchant_id, mint]
+ *
+ * @param merchant_id Unique identifier for the merchant
+ * @param mint Token mint the merchant may use
+ */
+pub fn find_merchant_mint(merchant_id: u64, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            MERCHANT_MINT_SEED,
+            merchant_id_seed(merchant_id).as_ref(),
+            mint.as_ref(),
+        ],
+        &ID,
+    )
+}
+
+/// Seeds of the merchant mint PDA, see find_merchant_mint
+pub fn merchant_mint_seeds(merchant_id: u64, mint: &Pubkey) -> Vec<Vec<u8>> {
+    vec![
+        MERCHANT_MINT_SEED.to_vec(),
+        merchant_id_seed(merchant_id).to_vec(),
+        mint.to_bytes().to_vec(),
+    ]
+}
+
+/**
+ * Derive the router PDA allowlisting a router program.
+ *
+ * Seeds: [ROUTER_SEED, router_program]
+ *
+ * @param router_program Program invoked by debit_user_with_swap
+ */
+pub fn find_router(router_program: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ROUTER_SEED, router_program.as_ref()], &ID)
+}
+
+/// Seeds of the router PDA, see find_router
+pub fn router_seeds(router_program: &Pubkey) -> Vec<Vec<u8>> {
+    vec![ROUTER_SEED.to_vec(), router_program.to_bytes().to_vec()]
+}
+
+/**
+ * Derive the debit receipt PDA of a debit.
+ *
+ * Seeds: [DEBIT_RECEIPT_SEED, user_delegate, debit_nonce]
+ *
+ * @param user_delegate User delegate the debit was made through
+ * @param debit_nonce Nonce of the debit on the delegate (little-endian u64 seed)
+ */
+pub fn find_debit_receipt(user_delegate: &Pubkey, debit_nonce: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            DEBIT_RECEIPT_SEED,
+            user_delegate.as_ref(),
+            debit_nonce.to_le_bytes().as_ref(),
+        ],
+        &ID,
+    )
+}
+
+/// Seeds of the debit receipt PDA, see find_debit_receipt
+pub fn debit_receipt_seeds(user_delegate: &Pubkey, debit_nonce: u64) -> Vec<Vec<u8>> {
+    vec![
+        DEBIT_RECEIPT_SEED.to_vec(),
+        user_delegate.to_bytes().to_vec(),
+        debit_nonce.to_le_bytes().to_vec(),
+    ]
+}
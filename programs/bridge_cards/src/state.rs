@@ -2,6 +2,51 @@ use crate::errors::ErrorCode;
 use account_data_macro_derive::AccountData;
 use account_data_trait::AccountData;
 use anchor_lang::prelude::*;
+use limit_check::{DebitRules, DelegateLimits, DelegateUsage, LimitMode};
+
+/**
+ * Layout version written to every account created or updated by the program.
+ *
+ * Every account starts with its 8-byte Anchor discriminator, which identifies the account
+ * type, followed by a one-byte version, which identifies the layout of the fields after it.
+ * The version is bumped whenever a field is added to any account, and migrate_account
+ * rewrites accounts of an older version in the current layout. Accounts created before
+ * versioning are treated as version 0: they have no version byte and only the fields of the
+ * original program's BridgeCardsState, UserDelegateState, MerchantDestinationState,
+ * MerchantDebitorState and MerchantManagerState.
+ *
+ * Version history:
+ * - 1: Version byte added after the discriminator. Also holds every field added before
+ *   versioning: the feature flags and protocol fee of BridgeCardsState, the delegate options
+ *   and counters of UserDelegateState, is_primary of MerchantDestinationState and the limits
+ *   of MerchantDebitorState
+ * - 2: MerchantManagerState holds a list of managers instead of a single manager
+ * - 3: UserDelegateState gains strict_boundary
+ * - 4: MerchantConfigState gains enforce_mint_allowlist
+ * - 5: UserDelegateState gains anchor_to_epoch and last_period_index
+ * - 6: UserDelegateState gains restricted_destination
+ * - 7: UserDelegateState gains active_after
+ * - 8: UserDelegateState gains timestamp_last_transferred, MerchantConfigState gains
+ *   max_clock_skew_seconds
+ * - 9: MerchantDestinationState gains last_received_amount and last_received_timestamp
+ * - 10: MerchantConfigState gains default_per_transfer_limit, default_period_transfer_limit
+ *   and default_period_seconds
+ * - 11: MerchantConfigState gains max_allowed_per_transfer and max_allowed_period_limit
+ * - 12: MerchantConfigState gains max_splits
+ */
+pub const CURRENT_VERSION: u8 = 12;
+
+/**
+ * Bytes allocated for a program account: its discriminator followed by its fields.
+ *
+ * Instructions creating accounts allocate SPACE of the type they create, so the allocation
+ * can't fall behind a field added to the type.
+ */
+pub trait AccountSpace: Discriminator + Space {
+    const SPACE: usize = Self::DISCRIMINATOR.len() + Self::INIT_SPACE;
+}
+
+impl<T: Discriminator + Space> AccountSpace for T {}
 
 /**
  * The global state of the BridgeCards program.
@@ -11,16 +56,82 @@ use anchor_lang::prelude::*;
  * - Add/update merchant managers
  * - Close accounts
  * - Update the admin
+ * - Set the global feature flags (see feature_flags for bit meanings)
+ * - Set the protocol fee skimmed from every debit
  *
  * The bump field stores the PDA bump seed to avoid recalculation.
  */
 #[account]
 #[derive(InitSpace, AccountData)]
 pub struct BridgeCardsState {
+    // Layout version of the account, see CURRENT_VERSION
+    pub version: u8,
     // Signer allowed to change BridgeCardsState
     pub admin: Pubkey,
     // Bump seed used in PDA derivation
     pub bump: u8,
+    // Bitmask of program-wide features, see feature_flags
+    pub feature_flags: u64,
+    // Share of every debit sent to the protocol fee owner, in basis points (0 = no fee)
+    pub protocol_fee_bps: u16,
+    // Owner of the token accounts receiving the protocol fee
+    pub protocol_fee_owner: Pubkey,
+}
+
+impl BridgeCardsState {
+    /// Highest protocol fee the admin can set, in basis points (10%)
+    pub const MAX_PROTOCOL_FEE_BPS: u16 = 1_000;
+
+    /// Protocol fee skimmed from a debit of the given amount, rounded down
+    pub fn protocol_fee(&self, amount: u64) -> Result<u64> {
+        let fee = (amount as u128)
+            .checked_mul(self.protocol_fee_bps as u128)
+            .ok_or(ErrorCode::ArithmeticOverflow)?
+            / 10_000;
+        Ok(u64::try_from(fee).map_err(|_| ErrorCode::ArithmeticOverflow)?)
+    }
+
+    /// Smallest debit amount that leaves exactly net_amount after the protocol fee
+    pub fn gross_of_protocol_fee(&self, net_amount: u64) -> Result<u64> {
+        // The fee is at most a tenth of the amount, so this converges in a few steps
+        let mut gross_amount = net_amount;
+        loop {
+            let next = net_amount
+                .checked_add(self.protocol_fee(gross_amount)?)
+                .ok_or(ErrorCode::ArithmeticOverflow)?;
+            if next == gross_amount {
+                return Ok(gross_amount);
+            }
+            gross_amount = next;
+        }
+    }
+
+    /// Whether every bit of the given feature flag is set
+    pub fn has_feature_flag(&self, flag: u64) -> bool {
+        self.feature_flags & flag == flag
+    }
+}
+
+/**
+ * Bits of BridgeCardsState::feature_flags.
+ *
+ * New program-wide switches get a bit here instead of a new BridgeCardsState field.
+ * Unassigned bits are ignored.
+ */
+pub mod feature_flags {
+    /// Reject every debit with ProgramPaused
+    pub const PAUSED: u64 = 1 << 0;
+    /// Reject debit_user_with_swap with SwapsDisabled
+    pub const SWAPS_DISABLED: u64 = 1 << 1;
+    /// Verify after each debit that the destination received the amount net of token-2022
+    /// transfer fees, failing with PostConditionFailed otherwise
+    pub const POST_CONDITION_CHECKS: u64 = 1 << 2;
+    /// Require a new merchant manager to co-sign add_or_update_merchant_manager, proving it
+    /// can sign, failing with InvalidManager otherwise
+    pub const REQUIRE_MANAGER_COSIGN: u64 = 1 << 3;
+    /// Reject debits whose clock timestamp is implausible for the slots elapsed since the
+    /// delegate's last debit, failing with ClockAnomaly otherwise
+    pub const CLOCK_ANOMALY_CHECKS: u64 = 1 << 4;
 }
 
 /**
@@ -32,19 +143,29 @@ pub struct BridgeCardsState {
  * - Amount transferred in current period
  * - Period reset timestamp
  * - Period duration
+ * - Limit mode: whether period usage resets at once or decays over a sliding window
+ * - Strict boundary: whether fixed-window periods start on fixed boundaries
+ * - Epoch anchoring: whether fixed-window periods are aligned to the unix epoch, e.g. to reset
+ *   at UTC midnight
+ * - Optional restriction to a single destination token account
+ * - Optional cooldown after creation before the first debit
+ * - Optional override of the merchant's minimum slots between debits
  *
  * The bump field stores the PDA bump seed to avoid recalculation.
  */
 #[account]
 #[derive(InitSpace, AccountData)]
 pub struct UserDelegateState {
+    // Layout version of the account, see CURRENT_VERSION
+    pub version: u8,
     // Maximum amount of tokens that can be transferred in a single transaction
     pub per_transfer_limit: u64,
     // Maximum amount of tokens that can be transferred within a period
+    // (UNLIMITED_PERIOD_TRANSFER_LIMIT = no period limit, period usage not tracked)
     pub period_transfer_limit: u64,
     // Amount of tokens that have been transferred within the last period
     pub period_transferred_amount: u64,
-    // Timestamp of when the period transferred amount was last reset
+    // Timestamp of when the period transferred amount was last reset (decayed in sliding mode)
     pub period_timestamp_last_reset: u64,
     // Duration in seconds of the transfer limit period
     pub transfer_limit_period_seconds: u32,
@@ -52,39 +173,263 @@ pub struct UserDelegateState {
     pub slot_last_transferred: u64,
     // Bump seed used in PDA derivation
     pub bump: u8,
+    // Minimum slots between two debits, overriding the merchant config (0 = not overridden)
+    pub min_slots_between_debits: u64,
+    // Whether debits may target non-primary destinations when the merchant enforces primary
+    pub allow_alternate_destination: bool,
+    // Merchant-provided commitment (e.g. hash of an off-chain card identifier), never PII
+    pub metadata_hash: [u8; 32],
+    // Unix timestamp before which debits are rejected (0 = valid immediately)
+    pub valid_from: i64,
+    // Unix timestamp after which debits are rejected (0 = never expires)
+    pub expires_at: i64,
+    // Number of times the delegate has been re-issued via reissue_delegate
+    pub reissue_count: u32,
+    // Minimum amount of a single debit, e.g. to reject dust transfers (0 = no minimum)
+    pub min_transfer_amount: u64,
+    // Amount transferred over the delegate's lifetime, never reset (for reconciliation)
+    pub total_transferred: u128,
+    // Sequence number of the last successful debit, never reset (for off-chain deduplication)
+    pub debit_nonce: u64,
+    // How period usage frees up: 0 = fixed window (reset at once), 1 = sliding window (decays)
+    pub limit_mode: u8,
+    // Whether a fixed-window period starts at the end of the previous one, the boundary second
+    // included, instead of at the first debit after it elapsed
+    pub strict_boundary: bool,
+    // Whether fixed-window periods are aligned to multiples of the period since the unix epoch
+    // (e.g. UTC days), resetting whenever that index changes
+    pub anchor_to_epoch: bool,
+    // Index since the unix epoch of the last period started by a debit, when anchor_to_epoch
+    pub last_period_index: u64,
+    // Only destination token account the delegate can be debited to, on top of the merchant's
+    // destination checks (default = any destination allowed for the merchant)
+    pub restricted_destination: Pubkey,
+    // Unix timestamp before which debits are rejected, set once when the delegate is created
+    // to its creation time plus the cooldown
+    pub active_after: i64,
+    // Unix timestamp of the last transfer, paired with slot_last_transferred (0 = none yet)
+    pub timestamp_last_transferred: i64,
 }
 
-impl UserDelegateState {
-    pub fn validate_debit_and_update(
-        &mut self,
-        amount: u64,
-        current_time: u64,
-        current_slot: u64,
-    ) -> Result<()> {
-        if amount > self.per_transfer_limit {
-            return Err(ErrorCode::ExceedsMaxTransferLimit.into());
+/// Minimum slots between debits when neither the delegate nor the merchant config sets one
+pub const DEFAULT_MIN_SLOTS_BETWEEN_DEBITS: u64 = 1;
+
+/// Clock skew tolerated by the clock anomaly check when the merchant config doesn't set one
+pub const DEFAULT_MAX_CLOCK_SKEW_SECONDS: u32 = 3_600;
+
+/// Legs a batch debit can split into when the merchant config doesn't set a maximum
+pub const DEFAULT_MAX_SPLITS: u8 = 8;
+
+/// Target duration of a slot, used to derive the time expected to elapse over a number of slots
+pub const MS_PER_SLOT: u64 = 400;
+
+/// Drift tolerated by the clock anomaly check on top of the skew, in percent of the expected
+/// elapsed time. Real slots run slower than the target and the cluster clock itself may drift
+/// from it, so the tolerance has to grow with the gap between debits.
+pub const CLOCK_DRIFT_PERCENT: u64 = 50;
+
+/**
+ * Merchant-level rules applied when validating a debit.
+ *
+ * Resolved from the delegate's overrides and the merchant config before validation,
+ * so the limit math in UserDelegateState doesn't need to know where a rule came from.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DebitPolicy {
+    // Minimum number of slots between two debits of the same delegate
+    pub min_slots_between_debits: u64,
+    // Whether a debit exactly at the end of the period starts a new period
+    pub reset_at_period_boundary: bool,
+}
+
+impl Default for DebitPolicy {
+    fn default() -> Self {
+        Self {
+            min_slots_between_debits: DEFAULT_MIN_SLOTS_BETWEEN_DEBITS,
+            reset_at_period_boundary: false,
         }
+    }
+}
 
-        if self.slot_last_transferred == current_slot {
-            return Err(ErrorCode::ExceedsMaxTransactionsPerSlot.into());
+impl DebitPolicy {
+    /// Resolve the effective policy: delegate override, else merchant config, else default.
+    pub fn resolve(merchant_config: &MerchantConfigState, delegate: &UserDelegateState) -> Self {
+        let min_slots_between_debits = if delegate.min_slots_between_debits != 0 {
+            delegate.min_slots_between_debits
+        } else if merchant_config.min_slots_between_debits != 0 {
+            merchant_config.min_slots_between_debits
+        } else {
+            DEFAULT_MIN_SLOTS_BETWEEN_DEBITS
+        };
+        Self {
+            min_slots_between_debits,
+            reset_at_period_boundary: merchant_config.reset_at_period_boundary,
         }
+    }
 
-        if current_time - self.period_timestamp_last_reset
-            > self.transfer_limit_period_seconds as u64
-        {
-            self.period_transferred_amount = 0;
-            self.period_timestamp_last_reset = current_time;
+    fn rules(&self) -> DebitRules {
+        DebitRules {
+            min_slots_between_debits: self.min_slots_between_debits,
+            reset_at_period_boundary: self.reset_at_period_boundary,
         }
-        if self.period_transferred_amount.checked_add(amount).unwrap() > self.period_transfer_limit
+    }
+}
+
+impl UserDelegateState {
+    /// Check that a debit to the destination token account is allowed by the delegate's
+    /// destination restriction, if any.
+    pub fn check_destination(&self, destination: &Pubkey) -> Result<()> {
+        if self.restricted_destination != Pubkey::default()
+            && self.restricted_destination != *destination
         {
-            return Err(ErrorCode::ExceedsTransferLimitPerPeriod.into());
+            return Err(ErrorCode::DestinationNotAllowedForDelegate.into());
+        }
+        Ok(())
+    }
+
+    /// Check that the delegate's creation cooldown has passed.
+    pub fn check_active(&self, current_time: i64) -> Result<()> {
+        if current_time < self.active_after {
+            return Err(ErrorCode::DelegateNotYetActive.into());
         }
+        Ok(())
+    }
+
+    /**
+     * Check that the clock timestamp is consistent with the slots elapsed since the
+     * delegate's last debit.
+     *
+     * The period limits are based on unix_timestamp, so a clock warped forward would reset
+     * periods early. The time expected since the last debit is derived from the slots elapsed
+     * at MS_PER_SLOT, and the actual time may differ from it by max_clock_skew_seconds plus
+     * CLOCK_DRIFT_PERCENT of the expected time. Delegates without a recorded debit have no
+     * reference and always pass.
+     *
+     * @param current_time Unix timestamp of the debit
+     * @param current_slot Slot of the debit
+     * @param max_clock_skew_seconds Tolerated difference in seconds on top of the drift
+     */
+    pub fn check_clock(
+        &self,
+        current_time: i64,
+        current_slot: u64,
+        max_clock_skew_seconds: u32,
+    ) -> Result<()> {
+        if self.timestamp_last_transferred == 0 {
+            return Ok(());
+        }
+        let elapsed_slots = current_slot.saturating_sub(self.slot_last_transferred);
+        let expected_seconds = i128::from(elapsed_slots) * i128::from(MS_PER_SLOT) / 1_000;
+        let tolerance = i128::from(max_clock_skew_seconds)
+            + expected_seconds * i128::from(CLOCK_DRIFT_PERCENT) / 100;
+        let elapsed_seconds =
+            i128::from(current_time) - i128::from(self.timestamp_last_transferred);
+        if (elapsed_seconds - expected_seconds).abs() > tolerance {
+            return Err(ErrorCode::ClockAnomaly.into());
+        }
+        Ok(())
+    }
+
+    /// Validate a debit and update period tracking.
+    /// Returns whether the debit started a new period, resetting the transferred amount.
+    pub fn validate_debit_and_update(
+        &mut self,
+        amount: u64,
+        current_time: u64,
+        current_slot: u64,
+    ) -> Result<bool> {
+        self.validate_debit_and_update_with_policy(
+            amount,
+            current_time,
+            current_slot,
+            &DebitPolicy::default(),
+        )
+    }
+
+    pub fn validate_debit_and_update_with_policy(
+        &mut self,
+        amount: u64,
+        current_time: u64,
+        current_slot: u64,
+        policy: &DebitPolicy,
+    ) -> Result<bool> {
+        let limits = self.limits();
+        let usage = self.usage();
+        let rules = policy.rules();
+        let updated =
+            limit_check::check_debit(&limits, &usage, amount, current_time, current_slot, &rules)
+                .map_err(ErrorCode::from)?;
+        let period_reset = limit_check::period_elapsed(&limits, &usage, current_time, &rules);
 
         // Only update state after all validations pass
-        self.slot_last_transferred = current_slot;
-        self.period_transferred_amount += amount;
+        self.period_transferred_amount = updated.period_transferred_amount;
+        self.period_timestamp_last_reset = updated.period_timestamp_last_reset;
+        self.slot_last_transferred = updated.slot_last_transferred;
+        self.total_transferred = updated.total_transferred;
+        self.debit_nonce = updated.debit_nonce;
+        self.last_period_index = updated.last_period_index;
+        self.timestamp_last_transferred = current_time as i64;
+        Ok(period_reset)
+    }
+
+    /// Seconds until the transfer limit period resets, or 0 if it has already elapsed
+    pub fn time_until_reset(&self, current_time: u64) -> u64 {
+        limit_check::time_until_reset(&self.limits(), &self.usage(), current_time)
+    }
+
+    /// Timestamp at which the current transfer limit period resets, for countdown displays.
+    /// Saturates at u64::MAX rather than overflowing.
+    pub fn next_period_reset_timestamp(&self) -> u64 {
+        self.period_timestamp_last_reset
+            .checked_add(u64::from(self.transfer_limit_period_seconds))
+            .unwrap_or(u64::MAX)
+    }
+
+    /// Check that a period of `transfer_limit_period_seconds` can follow the current period's
+    /// start without overflowing, so the period end can always be computed exactly.
+    pub fn check_period_end(&self, transfer_limit_period_seconds: u32) -> Result<()> {
+        self.period_timestamp_last_reset
+            .checked_add(u64::from(transfer_limit_period_seconds))
+            .ok_or(ErrorCode::InvalidPeriod)?;
         Ok(())
     }
+
+    /// Amount that can still be debited in the current period under the resolved policy,
+    /// without updating any state. Returns the full period limit if the period has elapsed.
+    pub fn remaining_period_allowance(&self, current_time: u64, policy: &DebitPolicy) -> u64 {
+        limit_check::remaining_period_allowance(
+            &self.limits(),
+            &self.usage(),
+            current_time,
+            &policy.rules(),
+        )
+    }
+
+    fn limits(&self) -> DelegateLimits {
+        DelegateLimits {
+            per_transfer_limit: self.per_transfer_limit,
+            period_transfer_limit: self.period_transfer_limit,
+            transfer_limit_period_seconds: self.transfer_limit_period_seconds,
+            valid_from: self.valid_from,
+            expires_at: self.expires_at,
+            min_transfer_amount: self.min_transfer_amount,
+            // Validated when set, unknown values fall back to the fixed window
+            limit_mode: LimitMode::try_from(self.limit_mode).unwrap_or_default(),
+            strict_boundary: self.strict_boundary,
+            anchor_to_epoch: self.anchor_to_epoch,
+        }
+    }
+
+    fn usage(&self) -> DelegateUsage {
+        DelegateUsage {
+            period_transferred_amount: self.period_transferred_amount,
+            period_timestamp_last_reset: self.period_timestamp_last_reset,
+            slot_last_transferred: self.slot_last_transferred,
+            total_transferred: self.total_transferred,
+            debit_nonce: self.debit_nonce,
+            last_period_index: self.last_period_index,
+        }
+    }
 }
 
 /**
@@ -92,16 +437,50 @@ impl UserDelegateState {
  *
  * When allowed is true, this token account can receive transfers from user delegates
  * associated with this merchant. This is used to control which token accounts
- * can receive funds on behalf of the merchant.
+ * can receive funds on behalf of the merchant. Under a merchant's denylist destination
+ * policy the meaning is inverted: an allowed destination state blocks the token account.
+ *
+ * Each debit to the destination records the amount received and its time, so a dashboard
+ * can poll the destination account without an indexer.
  *
  * The bump field stores the PDA bump seed to avoid recalculation.
  */
 #[account]
 #[derive(InitSpace, AccountData)]
 pub struct MerchantDestinationState {
+    // Layout version of the account, see CURRENT_VERSION
+    pub version: u8,
     pub allowed: bool,
+    // Whether this is the merchant's primary destination for the mint
+    pub is_primary: bool,
     // Bump seed used in PDA derivation
     pub bump: u8,
+    // Amount received by the last debit, net of the protocol fee (0 = none yet)
+    pub last_received_amount: u64,
+    // Unix timestamp of the last debit received (0 = none yet)
+    pub last_received_timestamp: i64,
+}
+
+impl MerchantDestinationState {
+    /// Load the destination state from an account whose address was already verified as the
+    /// destination's PDA, returning None if it has not been created.
+    pub fn load_optional(account: &AccountInfo) -> Result<Option<Self>> {
+        if account.owner != &crate::ID || account.data_is_empty() {
+            return Ok(None);
+        }
+        Self::try_deserialize(&mut &account.data.borrow()[..]).map(Some)
+    }
+
+    /// Write the state back to the account it was loaded from.
+    pub fn store(&self, account: &AccountInfo) -> Result<()> {
+        self.try_serialize(&mut &mut account.try_borrow_mut_data()?[..])
+    }
+
+    /// Record a debit received by the destination.
+    pub fn record_receipt(&mut self, amount: u64, timestamp: i64) {
+        self.last_received_amount = amount;
+        self.last_received_timestamp = timestamp;
+    }
 }
 
 /**
@@ -111,156 +490,703 @@ pub struct MerchantDestinationState {
  * associated with this merchant. This is used to control which accounts can
  * debit funds from users on behalf of the merchant.
  *
+ * The debitor's own per-call and daily limits apply on top of each delegate's limits, so a
+ * compromised debitor cannot drain every delegate up to its full limit. A zero limit means
+ * no debitor-level cap.
+ *
  * The bump field stores the PDA bump seed to avoid recalculation.
  */
 #[account]
 #[derive(InitSpace, AccountData)]
 pub struct MerchantDebitorState {
+    // Layout version of the account, see CURRENT_VERSION
+    pub version: u8,
     pub allowed: bool,
+    // Maximum amount the debitor can transfer in a single debit (0 for no cap)
+    pub per_call_limit: u64,
+    // Maximum amount the debitor can transfer within a day (0 for no cap)
+    pub daily_limit: u64,
+    // Amount transferred by the debitor within the current day
+    pub daily_transferred_amount: u64,
+    // Timestamp of when the daily transferred amount was last reset
+    pub daily_timestamp_last_reset: u64,
     // Bump seed used in PDA derivation
     pub bump: u8,
 }
 
+impl MerchantDebitorState {
+    /// Duration in seconds of the debitor's daily limit period
+    pub const DAILY_LIMIT_PERIOD_SECONDS: u64 = 86_400;
+
+    /// Load the debitor state from an account whose address was already verified as the
+    /// debitor's PDA for the debit's mint. A debitor state is per mint, so a debitor allowed
+    /// for another mint has no state here.
+    pub fn load_authorized(account: &AccountInfo) -> Result<Self> {
+        if account.owner != &crate::ID || account.data_is_empty() {
+            return Err(ErrorCode::DebitorNotAuthorizedForMint.into());
+        }
+        let debitor_state = Self::try_deserialize(&mut &account.data.borrow()[..])?;
+        if !debitor_state.allowed {
+            return Err(ErrorCode::DebitorNotAuthorizedForMint.into());
+        }
+        Ok(debitor_state)
+    }
+
+    /// Write the state back to the account it was loaded from.
+    pub fn store(&self, account: &AccountInfo) -> Result<()> {
+        self.try_serialize(&mut &mut account.try_borrow_mut_data()?[..])
+    }
+
+    /// Check a debit against the debitor's limits and add it to the day's total, starting a
+    /// new day if the last one has elapsed.
+    pub fn record_debit(&mut self, amount: u64, current_time: u64) -> Result<()> {
+        if self.per_call_limit != 0 && amount > self.per_call_limit {
+            return Err(ErrorCode::ExceedsDebitorPerCallLimit.into());
+        }
+
+        // A clock behind the day's start is still in the day
+        let day_elapsed = current_time
+            .checked_sub(self.daily_timestamp_last_reset)
+            .is_some_and(|elapsed| elapsed > Self::DAILY_LIMIT_PERIOD_SECONDS);
+        let daily_transferred_amount = if day_elapsed {
+            0
+        } else {
+            self.daily_transferred_amount
+        };
+        // An overflowing total is necessarily above the limit
+        let daily_transferred_amount = daily_transferred_amount
+            .checked_add(amount)
+            .filter(|total| self.daily_limit == 0 || *total <= self.daily_limit)
+            .ok_or(ErrorCode::ExceedsDebitorDailyLimit)?;
+
+        if day_elapsed {
+            self.daily_timestamp_last_reset = current_time;
+        }
+        self.daily_transferred_amount = daily_transferred_amount;
+        Ok(())
+    }
+}
+
+/// Maximum number of managers a merchant can have at once
+pub const MAX_MERCHANT_MANAGERS: usize = 4;
+
 /**
  * State for a merchant's manager account.
  *
- * The manager pubkey stored here has authority to:
+ * Each of the first active_count manager pubkeys stored here has authority to:
  * - Add/update user delegates for this merchant
  * - Add/update debitors for this merchant
  *
+ * Having several managers lets a merchant rotate a manager key without a window in which
+ * nobody can manage it. Unused entries are Pubkey::default().
+ *
  * The bump field stores the PDA bump seed to avoid recalculation.
  */
 #[account]
 #[derive(InitSpace, AccountData)]
 pub struct MerchantManagerState {
-    pub manager: Pubkey,
+    // Layout version of the account, see CURRENT_VERSION
+    pub version: u8,
+    // Managers of the merchant, only the first active_count entries are in use
+    pub managers: [Pubkey; MAX_MERCHANT_MANAGERS],
+    // Number of managers in use
+    pub active_count: u8,
     // Bump seed used in PDA derivation
     pub bump: u8,
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+impl MerchantManagerState {
+    /// Managers currently in use.
+    pub fn active_managers(&self) -> &[Pubkey] {
+        let active_count = usize::from(self.active_count).min(MAX_MERCHANT_MANAGERS);
+        &self.managers[..active_count]
+    }
 
-    fn setup_delegate_state() -> UserDelegateState {
-        UserDelegateState {
-            per_transfer_limit: 1000,
-            period_transfer_limit: 2000,
-            period_transferred_amount: 0,
-            period_timestamp_last_reset: 100,
-            transfer_limit_period_seconds: 3600, // 1 hour
-            slot_last_transferred: 0,
-            bump: 0,
-        }
+    /// Whether the key is one of the merchant's managers.
+    pub fn is_manager(&self, key: &Pubkey) -> bool {
+        self.active_managers().contains(key)
     }
 
-    #[test]
-    fn test_successful_transfer() {
-        let mut state = setup_delegate_state();
-        assert!(state.validate_debit_and_update(500, 200, 1).is_ok());
-        assert_eq!(state.period_transferred_amount, 500);
+    /// Make the manager the merchant's only manager, replacing any others.
+    pub fn set_manager(&mut self, manager: Pubkey) {
+        self.managers = [Pubkey::default(); MAX_MERCHANT_MANAGERS];
+        self.managers[0] = manager;
+        self.active_count = 1;
     }
 
-    #[test]
-    fn test_exceeds_per_transfer_limit() {
-        let mut state = setup_delegate_state();
-        let result = state.validate_debit_and_update(1500, 200, 1);
-        assert!(result.is_err());
-        if let Err(error) = result {
-            assert_eq!(error, ErrorCode::ExceedsMaxTransferLimit.into());
+    /// Add a manager alongside the existing ones.
+    pub fn add_manager(&mut self, manager: Pubkey) -> Result<()> {
+        if self.is_manager(&manager) {
+            return Err(ErrorCode::ManagerAlreadyAdded.into());
         }
+        let active_count = self.active_managers().len();
+        if active_count == MAX_MERCHANT_MANAGERS {
+            return Err(ErrorCode::TooManyManagers.into());
+        }
+        self.managers[active_count] = manager;
+        self.active_count = active_count as u8 + 1;
+        Ok(())
     }
 
-    #[test]
-    fn test_exceeds_period_transfer_limit() {
-        let mut state = setup_delegate_state();
-        // First transfer
-        assert!(state.validate_debit_and_update(900, 200, 1).is_ok());
-        assert!(state.validate_debit_and_update(200, 300, 2).is_ok());
-
-        // Second transfer that would exceed period limit
-        let result = state.validate_debit_and_update(1000, 300, 1);
-        assert!(result.is_err());
-        if let Err(error) = result {
-            assert_eq!(error, ErrorCode::ExceedsTransferLimitPerPeriod.into());
+    /// Remove a manager, keeping the remaining managers in order. The last manager cannot be
+    /// removed, close the manager account instead.
+    pub fn remove_manager(&mut self, manager: &Pubkey) -> Result<()> {
+        let active_count = self.active_managers().len();
+        let index = self
+            .active_managers()
+            .iter()
+            .position(|key| key == manager)
+            .ok_or(ErrorCode::ManagerNotFound)?;
+        if active_count == 1 {
+            return Err(ErrorCode::CannotRemoveLastManager.into());
         }
+        self.managers.copy_within(index + 1..active_count, index);
+        self.managers[active_count - 1] = Pubkey::default();
+        self.active_count = active_count as u8 - 1;
+        Ok(())
     }
+}
 
-    #[test]
-    fn test_period_reset() {
-        let mut state = setup_delegate_state();
-        // Initial transfer
-        assert!(state.validate_debit_and_update(900, 200, 1).is_ok());
-        assert_eq!(state.period_transferred_amount, 900);
+/**
+ * Configuration for a merchant.
+ *
+ * This account stores merchant-wide settings managed by the program admin:
+ * - Router program invoked to swap settled funds (Pubkey::default() disables routing)
+ * - Whether user token accounts must be owned by a regular (system-owned) wallet
+ * - Whether delegate updates emit a full snapshot of the delegate state
+ * - Minimum slots between debits for delegates that don't override it
+ * - Whether delegates and debits are limited to the mints allowlisted for the merchant
+ * - Maximum number of destinations a batch debit can split into
+ *
+ * Merchants without a config account use the default settings.
+ *
+ * The bump field stores the PDA bump seed to avoid recalculation.
+ */
+#[account]
+#[derive(InitSpace, AccountData, Default)]
+pub struct MerchantConfigState {
+    // Layout version of the account, see CURRENT_VERSION
+    pub version: u8,
+    // Router program allowed to swap funds received by this merchant's destinations
+    pub router_program: Pubkey,
+    // Whether the owner of a debited user token account must be owned by the system program
+    pub require_user_owner_system_owned: bool,
+    // Whether add_or_update_user_delegate also emits a UserDelegateSnapshot event
+    pub emit_full_snapshot: bool,
+    // Minimum slots between two debits of a delegate (0 = DEFAULT_MIN_SLOTS_BETWEEN_DEBITS)
+    pub min_slots_between_debits: u64,
+    // Whether debits must target a primary destination unless the delegate allows alternates
+    pub enforce_primary_destination: bool,
+    // Whether a debit exactly at the end of the period starts a new period. Defaults to false:
+    // the boundary second is still in the period and counts against its limit.
+    pub reset_at_period_boundary: bool,
+    // Whether managers may only lower delegate limits; raises need admin_raise_delegate_limits
+    pub managers_can_only_lower_limits: bool,
+    // Expiry applied to delegates that request the merchant default, in seconds from creation
+    pub default_delegate_expiry_seconds: u32,
+    // Whether every debit of the merchant is rejected, e.g. during an incident
+    pub paused: bool,
+    // Owner whose token accounts may receive debits without a destination allowlist entry
+    pub allow_any_destination_owner: Option<Pubkey>,
+    // How destination states are interpreted (DestinationPolicy, default allowlist)
+    pub destination_policy: u8,
+    // Bump seed used in PDA derivation
+    pub bump: u8,
+    // Whether only mints with an allowed merchant mint state can be used by the merchant
+    pub enforce_mint_allowlist: bool,
+    // Clock skew tolerated by the clock anomaly check (0 = DEFAULT_MAX_CLOCK_SKEW_SECONDS)
+    pub max_clock_skew_seconds: u32,
+    // Per-transfer limit of delegates created from the merchant's template
+    pub default_per_transfer_limit: u64,
+    // Period limit of delegates created from the merchant's template
+    pub default_period_transfer_limit: u64,
+    // Period in seconds of delegates created from the merchant's template (0 = no template)
+    pub default_period_seconds: u32,
+    // Highest per-transfer limit managers can set on a delegate (0 = no cap)
+    pub max_allowed_per_transfer: u64,
+    // Highest period limit managers can set on a delegate (0 = no cap)
+    pub max_allowed_period_limit: u64,
+    // Most legs a batch debit can split into (0 = DEFAULT_MAX_SPLITS)
+    pub max_splits: u8,
+}
 
-        // Transfer after period reset (3600 seconds + initial timestamp)
-        assert!(state.validate_debit_and_update(900, 4000, 2).is_ok());
-        assert_eq!(state.period_transferred_amount, 900);
-        assert_eq!(state.period_timestamp_last_reset, 4000);
+/// How a merchant's destination states decide which destinations can receive debits
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DestinationPolicy {
+    /// Only destinations with an allowed destination state can receive debits
+    #[default]
+    Allowlist = 0,
+    /// Every destination can receive debits except those with an allowed destination state
+    Denylist = 1,
+}
+
+impl TryFrom<u8> for DestinationPolicy {
+    type Error = u8;
+
+    fn try_from(value: u8) -> core::result::Result<Self, Self::Error> {
+        match value {
+            0 => Ok(DestinationPolicy::Allowlist),
+            1 => Ok(DestinationPolicy::Denylist),
+            _ => Err(value),
+        }
     }
+}
 
-    #[test]
-    fn test_multiple_transfers_within_period() {
-        let mut state = setup_delegate_state();
-        // First transfer
-        assert!(state.validate_debit_and_update(500, 200, 1).is_ok());
-        assert_eq!(state.period_transferred_amount, 500);
+impl MerchantConfigState {
+    /// Load the config from an account whose address was already verified as the merchant's
+    /// config PDA, falling back to the default settings if it has not been created.
+    pub fn load_or_default(account: &AccountInfo) -> Result<Self> {
+        if account.owner != &crate::ID || account.data_is_empty() {
+            return Ok(Self::default());
+        }
+        Self::try_deserialize(&mut &account.data.borrow()[..])
+    }
 
-        // Second transfer
-        assert!(state.validate_debit_and_update(300, 300, 2).is_ok());
-        assert_eq!(state.period_transferred_amount, 800);
+    /**
+     * Check that a destination can receive debits under the merchant's destination policy.
+     *
+     * With the allowlist policy the destination state must exist and be allowed, unless the
+     * destination is owned by allow_any_destination_owner. With the denylist policy an allowed
+     * destination state blocks the destination, and destinations without one are accepted.
+     *
+     * @param destination_state The destination's state, if it has been created
+     * @param destination_owner Owner of the destination token account
+     *
+     * @return Whether the destination is the merchant's primary destination
+     */
+    pub fn check_destination(
+        &self,
+        destination_state: Option<&MerchantDestinationState>,
+        destination_owner: &Pubkey,
+    ) -> Result<bool> {
+        let policy = DestinationPolicy::try_from(self.destination_policy).unwrap_or_default();
+        match (policy, destination_state) {
+            (DestinationPolicy::Allowlist, Some(destination_state)) => {
+                if !destination_state.allowed {
+                    return Err(ErrorCode::DestinationNotAllowed.into());
+                }
+                Ok(destination_state.is_primary)
+            }
+            (DestinationPolicy::Allowlist, None) => {
+                if self.allow_any_destination_owner != Some(*destination_owner) {
+                    return Err(ErrorCode::DestinationNotAllowed.into());
+                }
+                Ok(false)
+            }
+            (DestinationPolicy::Denylist, Some(destination_state)) => {
+                if destination_state.allowed {
+                    return Err(ErrorCode::DestinationNotAllowed.into());
+                }
+                Ok(destination_state.is_primary)
+            }
+            (DestinationPolicy::Denylist, None) => Ok(false),
+        }
+    }
 
-        // Third transfer
-        assert!(state.validate_debit_and_update(200, 400, 3).is_ok());
-        assert_eq!(state.period_transferred_amount, 1000);
+    /// Clock skew tolerated by the clock anomaly check, falling back to the default when unset
+    pub fn max_clock_skew_seconds(&self) -> u32 {
+        if self.max_clock_skew_seconds == 0 {
+            DEFAULT_MAX_CLOCK_SKEW_SECONDS
+        } else {
+            self.max_clock_skew_seconds
+        }
     }
 
-    #[test]
-    fn test_exceeds_max_transactions_per_slot() {
-        let mut state = setup_delegate_state();
-        // First transfer in slot 1
-        assert!(state.validate_debit_and_update(500, 200, 1).is_ok());
-        assert_eq!(state.slot_last_transferred, 1);
+    /// Legs allowed in a batch debit, falling back to the default when unset
+    pub fn max_splits(&self) -> usize {
+        if self.max_splits == 0 {
+            DEFAULT_MAX_SPLITS as usize
+        } else {
+            self.max_splits as usize
+        }
+    }
 
-        // Second transfer in same slot should fail
-        let result = state.validate_debit_and_update(300, 300, 1);
-        assert!(result.is_err());
-        if let Err(error) = result {
-            assert_eq!(error, ErrorCode::ExceedsMaxTransactionsPerSlot.into());
+    /// Check that delegate limits set by a manager are within the caps set by the admin.
+    pub fn check_limit_caps(
+        &self,
+        per_transfer_limit: u64,
+        period_transfer_limit: u64,
+    ) -> Result<()> {
+        if (self.max_allowed_per_transfer != 0
+            && per_transfer_limit > self.max_allowed_per_transfer)
+            || (self.max_allowed_period_limit != 0
+                && period_transfer_limit > self.max_allowed_period_limit)
+        {
+            return Err(ErrorCode::LimitExceedsMerchantCap.into());
         }
+        Ok(())
+    }
 
-        // Transfer in different slot should succeed
-        assert!(state.validate_debit_and_update(200, 400, 2).is_ok());
-        assert_eq!(state.slot_last_transferred, 2);
+    /**
+     * Check that a mint can be used by the merchant under its mint allowlist.
+     *
+     * Without enforce_mint_allowlist every mint is accepted. Otherwise the mint's merchant mint
+     * state must be passed, exist and be allowed.
+     *
+     * @param merchant_mint Account whose address was already verified as the merchant mint
+     * PDA, if passed
+     */
+    pub fn check_mint(&self, merchant_mint: Option<&AccountInfo>) -> Result<()> {
+        if !self.enforce_mint_allowlist {
+            return Ok(());
+        }
+        let mint_state = match merchant_mint {
+            Some(account) => MerchantMintState::load_optional(account)?,
+            None => None,
+        };
+        if !mint_state.is_some_and(|mint_state| mint_state.allowed) {
+            return Err(ErrorCode::MintNotAllowedForMerchant.into());
+        }
+        Ok(())
     }
+}
 
-    #[test]
-    fn test_state_updates() {
-        let DEFAULT_STATE: UserDelegateState = setup_delegate_state();
-        let mut state = setup_delegate_state();
+/**
+ * State for a mint allowlisted for a merchant.
+ *
+ * When the merchant config enforces the mint allowlist, delegates can only be created and
+ * debited for mints whose state exists and is allowed. The state is ignored otherwise.
+ *
+ * The bump field stores the PDA bump seed to avoid recalculation.
+ */
+#[account]
+#[derive(InitSpace, AccountData)]
+pub struct MerchantMintState {
+    // Layout version of the account, see CURRENT_VERSION
+    pub version: u8,
+    pub allowed: bool,
+    // Bump seed used in PDA derivation
+    pub bump: u8,
+}
 
-        // First transfer
-        assert!(state.validate_debit_and_update(500, 200, 1).is_ok());
+impl MerchantMintState {
+    /// Load the mint state from an account whose address was already verified as the merchant
+    /// mint PDA, returning None if it has not been created.
+    pub fn load_optional(account: &AccountInfo) -> Result<Option<Self>> {
+        if account.owner != &crate::ID || account.data_is_empty() {
+            return Ok(None);
+        }
+        Self::try_deserialize(&mut &account.data.borrow()[..]).map(Some)
+    }
+}
 
-        // Verify all state updates after first transfer
-        assert_eq!(
-            state.per_transfer_limit, DEFAULT_STATE.per_transfer_limit,
-            "per_transfer_limit should remain unchanged"
-        );
-        assert_eq!(
-            state.period_transfer_limit, DEFAULT_STATE.period_transfer_limit,
-            "period_transfer_limit should remain unchanged"
-        );
-        assert_eq!(
-            state.period_transferred_amount, 500,
-            "period_transferred_amount should be updated"
-        );
+/**
+ * Summary counters of a merchant's accounts, for indexers.
+ *
+ * Destinations, debitors and delegates can otherwise only be enumerated by scanning for their
+ * PDAs. When the registry exists and is passed to the instructions creating those accounts,
+ * each creation increments the matching counter. Updating an existing account doesn't count,
+ * and closing an account doesn't decrement its counter, so the counters are the number of
+ * accounts created since the registry was initialized.
+ *
+ * The bump field stores the PDA bump seed to avoid recalculation.
+ */
+#[account]
+#[derive(InitSpace, AccountData)]
+pub struct MerchantRegistryState {
+    // Layout version of the account, see CURRENT_VERSION
+    pub version: u8,
+    // Number of merchant destination states created
+    pub destination_count: u64,
+    // Number of merchant debitor states created
+    pub debitor_count: u64,
+    // Number of user delegates created
+    pub delegate_count: u64,
+    // Bump seed used in PDA derivation
+    pub bump: u8,
+}
+
+/**
+ * Merchant-wide spend tracking across all of a merchant's delegates.
+ *
+ * Once it exists, every debit of the merchant also counts against this period limit, capping
+ * the merchant's total outflow regardless of how many delegates it has. Periods follow the same
+ * rules as delegate periods: the boundary second still belongs to the current period.
+ *
+ * The bump field stores the PDA bump seed to avoid recalculation.
+ */
+#[account]
+#[derive(InitSpace, AccountData)]
+pub struct MerchantSpendTrackerState {
+    // Layout version of the account, see CURRENT_VERSION
+    pub version: u8,
+    // Maximum amount the merchant's delegates can transfer in total within a period
+    pub period_transfer_limit: u64,
+    // Amount transferred by the merchant's delegates within the last period
+    pub period_transferred_amount: u64,
+    // Timestamp of when the period transferred amount was last reset
+    pub period_timestamp_last_reset: u64,
+    // Duration in seconds of the transfer limit period
+    pub transfer_limit_period_seconds: u32,
+    // Bump seed used in PDA derivation
+    pub bump: u8,
+}
+
+impl MerchantSpendTrackerState {
+    /// Load the spend tracker from an account whose address was already verified as the
+    /// merchant's spend tracker PDA, returning None if it has not been created.
+    pub fn load_optional(account: &AccountInfo) -> Result<Option<Self>> {
+        if account.owner != &crate::ID || account.data_is_empty() {
+            return Ok(None);
+        }
+        Self::try_deserialize(&mut &account.data.borrow()[..]).map(Some)
+    }
+
+    /// Write the state back to the account it was loaded from.
+    pub fn store(&self, account: &AccountInfo) -> Result<()> {
+        self.try_serialize(&mut &mut account.try_borrow_mut_data()?[..])
+    }
+
+    /// Add a debit to the merchant's period total, starting a new period if it has elapsed.
+    pub fn record_debit(&mut self, amount: u64, current_time: u64) -> Result<()> {
+        // A clock behind the period start is still in the period
+        let period_elapsed = current_time
+            .checked_sub(self.period_timestamp_last_reset)
+            .is_some_and(|elapsed| elapsed > self.transfer_limit_period_seconds as u64);
+        let period_transferred_amount = if period_elapsed {
+            0
+        } else {
+            self.period_transferred_amount
+        };
+        // An overflowing total is necessarily above the limit
+        let period_transferred_amount = period_transferred_amount
+            .checked_add(amount)
+            .filter(|total| *total <= self.period_transfer_limit)
+            .ok_or(ErrorCode::ExceedsMerchantPeriodLimit)?;
+
+        if period_elapsed {
+            self.period_timestamp_last_reset = current_time;
+        }
+        self.period_transferred_amount = period_transferred_amount;
+        Ok(())
+    }
+}
+
+/**
+ * State for a router program.
+ *
+ * When allowed is true, this program may be configured as a merchant's router and
+ * invoked after a debit to swap the received tokens. This is used to control which
+ * external programs the Bridge Cards program will call into.
+ *
+ * The bump field stores the PDA bump seed to avoid recalculation.
+ */
+#[account]
+#[derive(InitSpace, AccountData)]
+pub struct RouterState {
+    // Layout version of the account, see CURRENT_VERSION
+    pub version: u8,
+    pub allowed: bool,
+    // Bump seed used in PDA derivation
+    pub bump: u8,
+}
+
+/**
+ * Receipt of a single debit, kept on-chain as a durable proof of payment.
+ *
+ * Debits only write a receipt when the debit_receipt account is passed. The receipt is
+ * derived from the delegate and the debit's nonce, so every debit has its own address and an
+ * existing receipt is never overwritten. Receipts can be closed with close_account once they
+ * are no longer needed.
+ *
+ * The bump field stores the PDA bump seed to avoid recalculation.
+ */
+#[account]
+#[derive(InitSpace, AccountData)]
+pub struct DebitReceiptState {
+    // Layout version of the account, see CURRENT_VERSION
+    pub version: u8,
+    // User delegate the debit was made through
+    pub user_delegate: Pubkey,
+    // Sequence number of the debit on the delegate, see UserDelegateState::debit_nonce
+    pub debit_nonce: u64,
+    // Amount debited from the user, protocol fee included
+    pub amount: u64,
+    // Token account the debit was sent to
+    pub destination: Pubkey,
+    // Mint of the debited tokens
+    pub mint: Pubkey,
+    // Unix timestamp of the debit
+    pub timestamp: i64,
+    // Bump seed used in PDA derivation
+    pub bump: u8,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An account of type T with every field zeroed, as created by init
+    fn zeroed_account<T: AccountSpace + AccountDeserialize>() -> T {
+        let mut data = T::DISCRIMINATOR.to_vec();
+        data.resize(T::SPACE, 0);
+        T::try_deserialize(&mut data.as_slice()).unwrap()
+    }
+
+    /// The space allocated for T must fit the account exactly, given at its largest
+    fn assert_account_space<T: AccountSpace + AccountSerialize>(account: T) {
+        assert_eq!(T::SPACE, T::DISCRIMINATOR.len() + T::INIT_SPACE);
+
+        let mut serialized = Vec::new();
+        account.try_serialize(&mut serialized).unwrap();
+        assert_eq!(serialized.len(), T::SPACE);
+    }
+
+    #[test]
+    fn test_account_space() {
+        assert_account_space(zeroed_account::<BridgeCardsState>());
+        assert_account_space(zeroed_account::<UserDelegateState>());
+        assert_account_space(zeroed_account::<MerchantDestinationState>());
+        assert_account_space(zeroed_account::<MerchantDebitorState>());
+        assert_account_space(zeroed_account::<MerchantManagerState>());
+        // Options serialize to a single byte when None
+        assert_account_space(MerchantConfigState {
+            allow_any_destination_owner: Some(Pubkey::default()),
+            ..zeroed_account()
+        });
+        assert_account_space(zeroed_account::<MerchantMintState>());
+        assert_account_space(zeroed_account::<MerchantRegistryState>());
+        assert_account_space(zeroed_account::<MerchantSpendTrackerState>());
+        assert_account_space(zeroed_account::<RouterState>());
+        assert_account_space(zeroed_account::<DebitReceiptState>());
+    }
+
+    fn setup_delegate_state() -> UserDelegateState {
+        UserDelegateState {
+            version: CURRENT_VERSION,
+            per_transfer_limit: 1000,
+            period_transfer_limit: 2000,
+            period_transferred_amount: 0,
+            period_timestamp_last_reset: 100,
+            transfer_limit_period_seconds: 3600, // 1 hour
+            slot_last_transferred: 0,
+            bump: 0,
+            min_slots_between_debits: 0,
+            allow_alternate_destination: false,
+            metadata_hash: [0; 32],
+            valid_from: 0,
+            expires_at: 0,
+            reissue_count: 0,
+            min_transfer_amount: 0,
+            total_transferred: 0,
+            debit_nonce: 0,
+            limit_mode: 0,
+            strict_boundary: false,
+            anchor_to_epoch: false,
+            last_period_index: 0,
+            restricted_destination: Pubkey::default(),
+            active_after: 0,
+            timestamp_last_transferred: 0,
+        }
+    }
+
+    #[test]
+    fn test_successful_transfer() {
+        let mut state = setup_delegate_state();
+        assert!(state.validate_debit_and_update(500, 200, 1).is_ok());
+        assert_eq!(state.period_transferred_amount, 500);
+    }
+
+    #[test]
+    fn test_exceeds_per_transfer_limit() {
+        let mut state = setup_delegate_state();
+        let result = state.validate_debit_and_update(1500, 200, 1);
+        assert!(result.is_err());
+        if let Err(error) = result {
+            assert_eq!(error, ErrorCode::ExceedsMaxTransferLimit.into());
+        }
+    }
+
+    #[test]
+    fn test_exceeds_period_transfer_limit() {
+        let mut state = setup_delegate_state();
+        // First transfer
+        assert!(state.validate_debit_and_update(900, 200, 1).is_ok());
+        assert!(state.validate_debit_and_update(200, 300, 2).is_ok());
+
+        // Second transfer that would exceed period limit
+        let result = state.validate_debit_and_update(1000, 300, 1);
+        assert!(result.is_err());
+        if let Err(error) = result {
+            assert_eq!(error, ErrorCode::ExceedsTransferLimitPerPeriod.into());
+        }
+    }
+
+    #[test]
+    fn test_period_reset() {
+        let mut state = setup_delegate_state();
+        // Initial transfer
+        assert!(state.validate_debit_and_update(900, 200, 1).is_ok());
+        assert_eq!(state.period_transferred_amount, 900);
+
+        // Transfer after period reset (3600 seconds + initial timestamp)
+        assert!(state.validate_debit_and_update(900, 4000, 2).is_ok());
+        assert_eq!(state.period_transferred_amount, 900);
+        assert_eq!(state.period_timestamp_last_reset, 4000);
+    }
+
+    #[test]
+    fn test_multiple_transfers_within_period() {
+        let mut state = setup_delegate_state();
+        // First transfer
+        assert!(state.validate_debit_and_update(500, 200, 1).is_ok());
+        assert_eq!(state.period_transferred_amount, 500);
+
+        // Second transfer
+        assert!(state.validate_debit_and_update(300, 300, 2).is_ok());
+        assert_eq!(state.period_transferred_amount, 800);
+
+        // Third transfer
+        assert!(state.validate_debit_and_update(200, 400, 3).is_ok());
+        assert_eq!(state.period_transferred_amount, 1000);
+    }
+
+    #[test]
+    fn test_exceeds_max_transactions_per_slot() {
+        let mut state = setup_delegate_state();
+        // First transfer in slot 1
+        assert!(state.validate_debit_and_update(500, 200, 1).is_ok());
+        assert_eq!(state.slot_last_transferred, 1);
+
+        // Second transfer in same slot should fail
+        let result = state.validate_debit_and_update(300, 300, 1);
+        assert!(result.is_err());
+        if let Err(error) = result {
+            assert_eq!(error, ErrorCode::ExceedsMaxTransactionsPerSlot.into());
+        }
+
+        // Transfer in different slot should succeed
+        assert!(state.validate_debit_and_update(200, 400, 2).is_ok());
+        assert_eq!(state.slot_last_transferred, 2);
+    }
+
+    #[test]
+    fn test_state_updates() {
+        let default_state: UserDelegateState = setup_delegate_state();
+        let mut state = setup_delegate_state();
+
+        // First transfer
+        assert!(state.validate_debit_and_update(500, 200, 1).is_ok());
+
+        // Verify all state updates after first transfer
+        assert_eq!(
+            state.per_transfer_limit, default_state.per_transfer_limit,
+            "per_transfer_limit should remain unchanged"
+        );
+        assert_eq!(
+            state.period_transfer_limit, default_state.period_transfer_limit,
+            "period_transfer_limit should remain unchanged"
+        );
+        assert_eq!(
+            state.period_transferred_amount, 500,
+            "period_transferred_amount should be updated"
+        );
         assert_eq!(
             state.period_timestamp_last_reset, 100,
             "period_timestamp should remain unchanged as period not elapsed"
         );
         assert_eq!(
-            state.transfer_limit_period_seconds, DEFAULT_STATE.transfer_limit_period_seconds,
+            state.transfer_limit_period_seconds, default_state.transfer_limit_period_seconds,
             "transfer_limit_period should remain unchanged"
         );
         assert_eq!(
@@ -334,11 +1260,11 @@ mod tests {
 
         // Verify state unchanged after failed transfer
         assert_eq!(
-            state.per_transfer_limit, DEFAULT_STATE.per_transfer_limit,
+            state.per_transfer_limit, default_state.per_transfer_limit,
             "per_transfer_limit should remain unchanged after failed transfer"
         );
         assert_eq!(
-            state.period_transfer_limit, DEFAULT_STATE.period_transfer_limit,
+            state.period_transfer_limit, default_state.period_transfer_limit,
             "period_transfer_limit should remain unchanged after failed transfer"
         );
         assert_eq!(
@@ -350,7 +1276,7 @@ mod tests {
             "period_timestamp should remain unchanged after failed transfer"
         );
         assert_eq!(
-            state.transfer_limit_period_seconds, DEFAULT_STATE.transfer_limit_period_seconds,
+            state.transfer_limit_period_seconds, default_state.transfer_limit_period_seconds,
             "transfer_limit_period should remain unchanged after failed transfer"
         );
         assert_eq!(
@@ -369,4 +1295,832 @@ mod tests {
             "slot_last_transferred should be updated"
         );
     }
+
+    #[test]
+    fn test_min_slots_between_debits_policy() {
+        let mut state = setup_delegate_state();
+        let policy = DebitPolicy {
+            min_slots_between_debits: 10,
+            ..Default::default()
+        };
+        assert!(state
+            .validate_debit_and_update_with_policy(100, 200, 100, &policy)
+            .is_ok());
+
+        // Within the window the debit is rejected
+        let result = state.validate_debit_and_update_with_policy(100, 200, 109, &policy);
+        assert_eq!(
+            result.unwrap_err(),
+            ErrorCode::ExceedsMaxTransactionsPerSlot.into()
+        );
+        assert_eq!(state.slot_last_transferred, 100);
+
+        // Exactly at the end of the window the debit succeeds
+        assert!(state
+            .validate_debit_and_update_with_policy(100, 200, 110, &policy)
+            .is_ok());
+        assert_eq!(state.slot_last_transferred, 110);
+    }
+
+    #[test]
+    fn test_debit_policy_resolution() {
+        let mut delegate = setup_delegate_state();
+        let mut merchant_config = MerchantConfigState::default();
+
+        // Neither sets a value: default of one debit per slot
+        assert_eq!(
+            DebitPolicy::resolve(&merchant_config, &delegate).min_slots_between_debits,
+            DEFAULT_MIN_SLOTS_BETWEEN_DEBITS
+        );
+
+        // Merchant config applies when the delegate doesn't override it
+        merchant_config.min_slots_between_debits = 5;
+        assert_eq!(
+            DebitPolicy::resolve(&merchant_config, &delegate).min_slots_between_debits,
+            5
+        );
+
+        // Delegate override takes precedence over the merchant config
+        delegate.min_slots_between_debits = 2;
+        assert_eq!(
+            DebitPolicy::resolve(&merchant_config, &delegate).min_slots_between_debits,
+            2
+        );
+    }
+
+    #[test]
+    fn test_period_boundary_exclusive_by_default() {
+        let mut state = setup_delegate_state();
+        assert!(state.validate_debit_and_update(900, 200, 1).is_ok());
+        assert!(state.validate_debit_and_update(900, 300, 2).is_ok());
+
+        // Exactly one period after the reset: still in the period, so the limit applies
+        let boundary = 100 + 3600;
+        let result = state.validate_debit_and_update(1000, boundary, 3);
+        assert_eq!(
+            result.unwrap_err(),
+            ErrorCode::ExceedsTransferLimitPerPeriod.into()
+        );
+        assert_eq!(state.period_timestamp_last_reset, 100);
+
+        // One second later the period has elapsed
+        assert!(state
+            .validate_debit_and_update(1000, boundary + 1, 3)
+            .is_ok());
+        assert_eq!(state.period_transferred_amount, 1000);
+    }
+
+    #[test]
+    fn test_period_boundary_reset_when_configured() {
+        let policy = DebitPolicy {
+            reset_at_period_boundary: true,
+            ..Default::default()
+        };
+        let mut state = setup_delegate_state();
+        assert!(state
+            .validate_debit_and_update_with_policy(900, 200, 1, &policy)
+            .is_ok());
+        assert!(state
+            .validate_debit_and_update_with_policy(900, 300, 2, &policy)
+            .is_ok());
+
+        // Exactly one period after the reset: a new period starts
+        let boundary = 100 + 3600;
+        assert!(state
+            .validate_debit_and_update_with_policy(1000, boundary, 3, &policy)
+            .is_ok());
+        assert_eq!(state.period_transferred_amount, 1000);
+        assert_eq!(state.period_timestamp_last_reset, boundary);
+    }
+
+    #[test]
+    fn test_strict_boundary_anchors_period() {
+        let mut state = setup_delegate_state();
+        state.strict_boundary = true;
+        assert!(state.validate_debit_and_update(1000, 200, 1).is_ok());
+        assert!(state.validate_debit_and_update(1000, 300, 2).is_ok());
+
+        // A debit late in the next period starts it at the boundary and reports the reset
+        let boundary = 100 + 3600;
+        assert_eq!(
+            state.validate_debit_and_update(1000, boundary + 1000, 3),
+            Ok(true)
+        );
+        assert_eq!(state.period_timestamp_last_reset, boundary);
+        assert!(state
+            .validate_debit_and_update(1000, boundary + 1000, 4)
+            .is_ok());
+        let result = state.validate_debit_and_update(1, boundary + 3599, 5);
+        assert_eq!(
+            result.unwrap_err(),
+            ErrorCode::ExceedsTransferLimitPerPeriod.into()
+        );
+        assert_eq!(
+            state.validate_debit_and_update(1, boundary + 3600, 5),
+            Ok(true)
+        );
+    }
+
+    #[test]
+    fn test_anchor_to_epoch_resets_on_period_index() {
+        let mut state = setup_delegate_state();
+        state.anchor_to_epoch = true;
+
+        // Debits within the same hour since the epoch share its budget
+        assert_eq!(
+            state.validate_debit_and_update(1000, 7200 + 1800, 1),
+            Ok(true)
+        );
+        assert_eq!(state.last_period_index, 2);
+        assert_eq!(state.period_timestamp_last_reset, 7200);
+        assert_eq!(
+            state.validate_debit_and_update(1000, 7200 + 3599, 2),
+            Ok(false)
+        );
+        let result = state.validate_debit_and_update(1, 7200 + 3599, 3);
+        assert_eq!(
+            result.unwrap_err(),
+            ErrorCode::ExceedsTransferLimitPerPeriod.into()
+        );
+        assert_eq!(state.next_period_reset_timestamp(), 10_800);
+
+        // The next hour resets the budget, only half an hour after the first debit
+        assert_eq!(state.validate_debit_and_update(1000, 10_800, 3), Ok(true));
+        assert_eq!(state.period_transferred_amount, 1000);
+        assert_eq!(state.last_period_index, 3);
+    }
+
+    #[test]
+    fn test_restricted_destination() {
+        let mut state = setup_delegate_state();
+        let destination = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+
+        // Unrestricted delegates accept any destination
+        assert_eq!(state.check_destination(&other), Ok(()));
+
+        state.restricted_destination = destination;
+        assert_eq!(state.check_destination(&destination), Ok(()));
+        assert_eq!(
+            state.check_destination(&other).unwrap_err(),
+            ErrorCode::DestinationNotAllowedForDelegate.into()
+        );
+    }
+
+    #[test]
+    fn test_active_after() {
+        let mut state = setup_delegate_state();
+        assert_eq!(state.check_active(0), Ok(()));
+
+        state.active_after = 1000;
+        assert_eq!(
+            state.check_active(999).unwrap_err(),
+            ErrorCode::DelegateNotYetActive.into()
+        );
+        // active_after is the first second the delegate can debit
+        assert_eq!(state.check_active(1000), Ok(()));
+    }
+
+    #[test]
+    fn test_check_clock() {
+        let mut state = setup_delegate_state();
+        // No debit recorded yet, so any clock passes
+        assert_eq!(state.check_clock(i64::MAX, 0, 0), Ok(()));
+
+        state.validate_debit_and_update(500, 10_000, 1_000).unwrap();
+        assert_eq!(state.timestamp_last_transferred, 10_000);
+
+        // 9000 slots are expected to take an hour, with 30 minutes of drift tolerated
+        let skew = 60;
+        assert_eq!(state.check_clock(13_600, 10_000, skew), Ok(()));
+        assert_eq!(state.check_clock(13_600 + 1_800 + 60, 10_000, skew), Ok(()));
+        assert_eq!(state.check_clock(13_600 - 1_800 - 60, 10_000, skew), Ok(()));
+
+        // Clock warped a day ahead, as if to reset the period early
+        assert_eq!(
+            state
+                .check_clock(10_000 + 86_400, 10_000, skew)
+                .unwrap_err(),
+            ErrorCode::ClockAnomaly.into()
+        );
+        assert_eq!(
+            state
+                .check_clock(13_600 + 1_800 + 61, 10_000, skew)
+                .unwrap_err(),
+            ErrorCode::ClockAnomaly.into()
+        );
+        // Clock warped backwards
+        assert_eq!(
+            state
+                .check_clock(13_600 - 1_800 - 61, 10_000, skew)
+                .unwrap_err(),
+            ErrorCode::ClockAnomaly.into()
+        );
+        assert_eq!(
+            state.check_clock(0, 10_000, skew).unwrap_err(),
+            ErrorCode::ClockAnomaly.into()
+        );
+
+        // Within the same slot only the skew is tolerated
+        assert_eq!(state.check_clock(10_060, 1_000, skew), Ok(()));
+        assert_eq!(
+            state.check_clock(10_061, 1_000, skew).unwrap_err(),
+            ErrorCode::ClockAnomaly.into()
+        );
+    }
+
+    #[test]
+    fn test_max_clock_skew_defaults() {
+        let mut merchant_config = MerchantConfigState::default();
+        assert_eq!(
+            merchant_config.max_clock_skew_seconds(),
+            DEFAULT_MAX_CLOCK_SKEW_SECONDS
+        );
+        merchant_config.max_clock_skew_seconds = 30;
+        assert_eq!(merchant_config.max_clock_skew_seconds(), 30);
+    }
+
+    #[test]
+    fn test_max_splits_defaults() {
+        let mut merchant_config = MerchantConfigState::default();
+        assert_eq!(merchant_config.max_splits(), DEFAULT_MAX_SPLITS as usize);
+        merchant_config.max_splits = 3;
+        assert_eq!(merchant_config.max_splits(), 3);
+    }
+
+    #[test]
+    fn test_check_period_end() {
+        let mut state = setup_delegate_state();
+        state.period_timestamp_last_reset = u64::MAX - 3600;
+        assert!(state.check_period_end(3600).is_ok());
+        assert_eq!(
+            state.check_period_end(3601).unwrap_err(),
+            ErrorCode::InvalidPeriod.into()
+        );
+
+        state.period_timestamp_last_reset = u64::MAX;
+        assert_eq!(
+            state.check_period_end(1).unwrap_err(),
+            ErrorCode::InvalidPeriod.into()
+        );
+    }
+
+    #[test]
+    fn test_check_limit_caps() {
+        let mut merchant_config = MerchantConfigState::default();
+        assert!(merchant_config.check_limit_caps(u64::MAX, u64::MAX).is_ok());
+
+        merchant_config.max_allowed_per_transfer = 100;
+        merchant_config.max_allowed_period_limit = 1000;
+        assert!(merchant_config.check_limit_caps(100, 1000).is_ok());
+        assert_eq!(
+            merchant_config.check_limit_caps(101, 1000).unwrap_err(),
+            ErrorCode::LimitExceedsMerchantCap.into()
+        );
+        assert_eq!(
+            merchant_config.check_limit_caps(100, 1001).unwrap_err(),
+            ErrorCode::LimitExceedsMerchantCap.into()
+        );
+    }
+
+    #[test]
+    fn test_debit_policy_resolves_period_boundary() {
+        let delegate = setup_delegate_state();
+        let mut merchant_config = MerchantConfigState::default();
+        assert!(!DebitPolicy::resolve(&merchant_config, &delegate).reset_at_period_boundary);
+
+        merchant_config.reset_at_period_boundary = true;
+        assert!(DebitPolicy::resolve(&merchant_config, &delegate).reset_at_period_boundary);
+    }
+
+    #[test]
+    fn test_validity_window_start() {
+        let mut state = setup_delegate_state();
+        state.valid_from = 1000;
+
+        // Before the window starts
+        let result = state.validate_debit_and_update(100, 999, 1);
+        assert_eq!(result.unwrap_err(), ErrorCode::DelegateNotYetValid.into());
+        assert_eq!(state.period_transferred_amount, 0);
+
+        // At the start of the window
+        assert!(state.validate_debit_and_update(100, 1000, 2).is_ok());
+
+        // Well into the window
+        assert!(state.validate_debit_and_update(100, 50_000, 3).is_ok());
+        assert_eq!(state.period_transferred_amount, 100);
+    }
+
+    #[test]
+    fn test_validity_window_end() {
+        let mut state = setup_delegate_state();
+        state.expires_at = 5000;
+
+        // Before and at expiry
+        assert!(state.validate_debit_and_update(100, 4999, 1).is_ok());
+        assert!(state.validate_debit_and_update(100, 5000, 2).is_ok());
+
+        // After expiry
+        let result = state.validate_debit_and_update(100, 5001, 3);
+        assert_eq!(result.unwrap_err(), ErrorCode::DelegateExpired.into());
+        let result = state.validate_debit_and_update(100, 50_000, 4);
+        assert_eq!(result.unwrap_err(), ErrorCode::DelegateExpired.into());
+        assert_eq!(state.period_transferred_amount, 200);
+    }
+
+    #[test]
+    fn test_clock_behind_period_start() {
+        let mut state = setup_delegate_state();
+        state.period_timestamp_last_reset = 10_000;
+        state.period_transferred_amount = 1500;
+
+        // A clock earlier than the period start is treated as within the current period
+        assert!(state.validate_debit_and_update(500, 9000, 1).is_ok());
+        assert_eq!(state.period_transferred_amount, 2000);
+        assert_eq!(state.period_timestamp_last_reset, 10_000);
+
+        let result = state.validate_debit_and_update(1, 9000, 2);
+        assert_eq!(
+            result.unwrap_err(),
+            ErrorCode::ExceedsTransferLimitPerPeriod.into()
+        );
+    }
+
+    #[test]
+    fn test_period_total_overflow() {
+        let mut state = setup_delegate_state();
+        state.per_transfer_limit = u64::MAX;
+        state.period_transfer_limit = u64::MAX - 1;
+        state.period_transferred_amount = u64::MAX - 2;
+
+        let result = state.validate_debit_and_update(u64::MAX, 200, 1);
+        assert_eq!(
+            result.unwrap_err(),
+            ErrorCode::ExceedsTransferLimitPerPeriod.into()
+        );
+        assert_eq!(state.period_transferred_amount, u64::MAX - 2);
+
+        assert!(state.validate_debit_and_update(1, 200, 2).is_ok());
+        assert_eq!(state.period_transferred_amount, u64::MAX - 1);
+    }
+
+    #[test]
+    fn test_unlimited_period() {
+        let mut state = setup_delegate_state();
+        state.period_transfer_limit = limit_check::UNLIMITED_PERIOD_TRANSFER_LIMIT;
+
+        // Over-limit single transfers are still rejected
+        let result = state.validate_debit_and_update(1001, 200, 1);
+        assert_eq!(
+            result.unwrap_err(),
+            ErrorCode::ExceedsMaxTransferLimit.into()
+        );
+
+        // Debits are only bounded by the per-transfer and per-slot limits, and never reset
+        for slot in 1..=5 {
+            assert!(!state
+                .validate_debit_and_update(1000, 200 * slot, slot)
+                .unwrap());
+        }
+        let result = state.validate_debit_and_update(1000, 1000, 5);
+        assert_eq!(
+            result.unwrap_err(),
+            ErrorCode::ExceedsMaxTransactionsPerSlot.into()
+        );
+        assert_eq!(state.period_transferred_amount, 0);
+        assert_eq!(state.total_transferred, 5000);
+        assert_eq!(
+            state.remaining_period_allowance(1000, &DebitPolicy::default()),
+            limit_check::UNLIMITED_PERIOD_TRANSFER_LIMIT
+        );
+    }
+
+    #[test]
+    fn test_remaining_period_allowance() {
+        let mut state = setup_delegate_state();
+
+        // Before any debit
+        assert_eq!(
+            state.remaining_period_allowance(200, &DebitPolicy::default()),
+            2000
+        );
+
+        // Before the reset
+        state.validate_debit_and_update(800, 200, 1).unwrap();
+        assert_eq!(
+            state.remaining_period_allowance(200, &DebitPolicy::default()),
+            1200
+        );
+        assert_eq!(
+            state.remaining_period_allowance(3700, &DebitPolicy::default()),
+            1200
+        );
+
+        // After the reset
+        assert_eq!(
+            state.remaining_period_allowance(3701, &DebitPolicy::default()),
+            2000
+        );
+
+        // Fully consumed
+        state.validate_debit_and_update(1000, 300, 2).unwrap();
+        state.validate_debit_and_update(200, 400, 3).unwrap();
+        assert_eq!(
+            state.remaining_period_allowance(400, &DebitPolicy::default()),
+            0
+        );
+        assert_eq!(state.period_transferred_amount, 2000);
+    }
+
+    #[test]
+    fn test_remaining_period_allowance_at_period_boundary() {
+        let mut state = setup_delegate_state();
+        state.validate_debit_and_update(800, 200, 1).unwrap();
+        let boundary = 100 + 3600;
+
+        // The boundary second still belongs to the period by default
+        assert_eq!(
+            state.remaining_period_allowance(boundary, &DebitPolicy::default()),
+            1200
+        );
+
+        // A merchant resetting at the boundary frees up the whole limit there
+        let policy = DebitPolicy {
+            reset_at_period_boundary: true,
+            ..DebitPolicy::default()
+        };
+        assert_eq!(state.remaining_period_allowance(boundary, &policy), 2000);
+        assert_eq!(
+            state.remaining_period_allowance(boundary - 1, &policy),
+            1200
+        );
+    }
+
+    #[test]
+    fn test_period_reset_reported() {
+        let mut state = setup_delegate_state();
+
+        // Within the period
+        assert!(!state.validate_debit_and_update(100, 200, 1).unwrap());
+        assert!(!state.validate_debit_and_update(100, 3700, 2).unwrap());
+
+        // First debit after the period elapsed
+        assert!(state.validate_debit_and_update(100, 3701, 3).unwrap());
+        assert_eq!(state.period_timestamp_last_reset, 3701);
+        assert!(!state.validate_debit_and_update(100, 3702, 4).unwrap());
+    }
+
+    #[test]
+    fn test_min_transfer_amount() {
+        let mut state = setup_delegate_state();
+
+        // No minimum configured
+        assert!(state.validate_debit_and_update(1, 200, 1).is_ok());
+
+        state.min_transfer_amount = 100;
+
+        // One below the minimum
+        let result = state.validate_debit_and_update(99, 200, 2);
+        assert_eq!(
+            result.unwrap_err(),
+            ErrorCode::BelowMinTransferAmount.into()
+        );
+        assert_eq!(state.period_transferred_amount, 1);
+
+        // Exactly the minimum
+        assert!(state.validate_debit_and_update(100, 200, 3).is_ok());
+        assert_eq!(state.period_transferred_amount, 101);
+    }
+
+    #[test]
+    fn test_total_transferred_across_periods() {
+        let mut state = setup_delegate_state();
+
+        assert!(state.validate_debit_and_update(1000, 200, 1).is_ok());
+        assert!(state.validate_debit_and_update(1000, 300, 2).is_ok());
+        // New period
+        assert!(state.validate_debit_and_update(500, 4000, 3).is_ok());
+        // And another
+        assert!(state.validate_debit_and_update(700, 8000, 4).is_ok());
+
+        assert_eq!(state.period_transferred_amount, 700);
+        assert_eq!(state.total_transferred, 3200);
+    }
+
+    #[test]
+    fn test_debit_nonce() {
+        let mut state = setup_delegate_state();
+
+        assert!(state.validate_debit_and_update(100, 200, 1).is_ok());
+        assert_eq!(state.debit_nonce, 1);
+        assert!(state.validate_debit_and_update(100, 300, 2).is_ok());
+        assert_eq!(state.debit_nonce, 2);
+
+        // Rejected debits don't consume a nonce
+        assert!(state.validate_debit_and_update(5000, 300, 3).is_err());
+        assert_eq!(state.debit_nonce, 2);
+
+        // Persists across period resets
+        assert!(state.validate_debit_and_update(100, 4000, 4).is_ok());
+        assert_eq!(state.debit_nonce, 3);
+    }
+
+    #[test]
+    fn test_fixed_window_allows_double_spend_across_boundary() {
+        let mut state = setup_delegate_state();
+        assert!(state.validate_debit_and_update(1000, 3000, 1).is_ok());
+        assert!(state.validate_debit_and_update(1000, 3650, 2).is_ok());
+
+        // Right after the boundary the whole budget is available again
+        let after_boundary = 100 + 3600 + 1;
+        assert!(state
+            .validate_debit_and_update(1000, after_boundary, 3)
+            .unwrap());
+        assert!(state
+            .validate_debit_and_update(1000, after_boundary + 1, 4)
+            .is_ok());
+        assert_eq!(state.total_transferred, 4000);
+    }
+
+    #[test]
+    fn test_sliding_window_decays_across_boundary() {
+        let mut state = UserDelegateState {
+            version: CURRENT_VERSION,
+            limit_mode: LimitMode::SlidingWindow as u8,
+            ..setup_delegate_state()
+        };
+        assert!(state.validate_debit_and_update(1000, 3000, 1).is_ok());
+        // 650 seconds after the first debit, 820 of it still counts
+        assert!(!state.validate_debit_and_update(1000, 3650, 2).unwrap());
+        assert_eq!(state.period_transferred_amount, 1820);
+        assert_eq!(state.period_timestamp_last_reset, 3650);
+
+        // Right after the fixed window's boundary, only a little has freed up
+        let after_boundary = 100 + 3600 + 1;
+        assert_eq!(
+            state.remaining_period_allowance(after_boundary, &DebitPolicy::default()),
+            205
+        );
+        let result = state.validate_debit_and_update(1000, after_boundary, 3);
+        assert_eq!(
+            result.unwrap_err(),
+            ErrorCode::ExceedsTransferLimitPerPeriod.into()
+        );
+
+        // A full period after the last debit, everything has freed up
+        assert_eq!(
+            state.remaining_period_allowance(3650 + 3600, &DebitPolicy::default()),
+            2000
+        );
+        assert!(state
+            .validate_debit_and_update(1000, 3650 + 3600, 3)
+            .is_ok());
+        assert_eq!(state.period_transferred_amount, 1000);
+    }
+
+    #[test]
+    fn test_merchant_debitor_limits() {
+        let mut debitor = MerchantDebitorState {
+            version: CURRENT_VERSION,
+            allowed: true,
+            per_call_limit: 1000,
+            daily_limit: 1500,
+            daily_transferred_amount: 0,
+            daily_timestamp_last_reset: 100,
+            bump: 0,
+        };
+
+        let result = debitor.record_debit(1001, 200);
+        assert_eq!(
+            result.unwrap_err(),
+            ErrorCode::ExceedsDebitorPerCallLimit.into()
+        );
+        assert!(debitor.record_debit(1000, 200).is_ok());
+        assert!(debitor.record_debit(500, 300).is_ok());
+
+        // The daily limit applies until the end of the day, boundary included
+        let end_of_day = 100 + MerchantDebitorState::DAILY_LIMIT_PERIOD_SECONDS;
+        let result = debitor.record_debit(1, end_of_day);
+        assert_eq!(
+            result.unwrap_err(),
+            ErrorCode::ExceedsDebitorDailyLimit.into()
+        );
+        assert_eq!(debitor.daily_transferred_amount, 1500);
+
+        // A new day starts right after
+        assert!(debitor.record_debit(1000, end_of_day + 1).is_ok());
+        assert_eq!(debitor.daily_transferred_amount, 1000);
+        assert_eq!(debitor.daily_timestamp_last_reset, end_of_day + 1);
+
+        // Zero limits don't cap the debitor
+        debitor.per_call_limit = 0;
+        debitor.daily_limit = 0;
+        assert!(debitor
+            .record_debit(u64::MAX - 1000, end_of_day + 2)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_merchant_spend_tracker() {
+        let mut tracker = MerchantSpendTrackerState {
+            version: CURRENT_VERSION,
+            period_transfer_limit: 2000,
+            period_transferred_amount: 0,
+            period_timestamp_last_reset: 100,
+            transfer_limit_period_seconds: 3600,
+            bump: 0,
+        };
+
+        assert!(tracker.record_debit(1500, 200).is_ok());
+        assert!(tracker.record_debit(500, 300).is_ok());
+        assert_eq!(tracker.period_transferred_amount, 2000);
+
+        // The limit applies until the end of the period, boundary included
+        let result = tracker.record_debit(1, 3700);
+        assert_eq!(
+            result.unwrap_err(),
+            ErrorCode::ExceedsMerchantPeriodLimit.into()
+        );
+        assert_eq!(tracker.period_transferred_amount, 2000);
+
+        // Then a new period starts
+        assert!(tracker.record_debit(1000, 3701).is_ok());
+        assert_eq!(tracker.period_transferred_amount, 1000);
+        assert_eq!(tracker.period_timestamp_last_reset, 3701);
+
+        // Overflowing totals are rejected like any total above the limit
+        let result = tracker.record_debit(u64::MAX, 3702);
+        assert_eq!(
+            result.unwrap_err(),
+            ErrorCode::ExceedsMerchantPeriodLimit.into()
+        );
+    }
+
+    #[test]
+    fn test_protocol_fee() {
+        let mut state = BridgeCardsState {
+            version: CURRENT_VERSION,
+            admin: Pubkey::new_unique(),
+            bump: 0,
+            feature_flags: 0,
+            protocol_fee_bps: 0,
+            protocol_fee_owner: Pubkey::new_unique(),
+        };
+        assert_eq!(state.protocol_fee(1_000_000).unwrap(), 0);
+        assert_eq!(state.gross_of_protocol_fee(1_000_000).unwrap(), 1_000_000);
+
+        // 2.5%, rounded down
+        state.protocol_fee_bps = 250;
+        assert_eq!(state.protocol_fee(1_000_000).unwrap(), 25_000);
+        assert_eq!(state.protocol_fee(39).unwrap(), 0);
+        assert_eq!(state.protocol_fee(41).unwrap(), 1);
+        assert_eq!(state.protocol_fee(u64::MAX).unwrap(), u64::MAX / 40);
+
+        // The gross amount leaves exactly the net amount after the fee
+        for net_amount in [0, 1, 39, 40, 975_000, 1_000_000] {
+            let gross_amount = state.gross_of_protocol_fee(net_amount).unwrap();
+            let fee = state.protocol_fee(gross_amount).unwrap();
+            assert_eq!(gross_amount - fee, net_amount);
+            let smaller_fee = state.protocol_fee(gross_amount.saturating_sub(1)).unwrap();
+            assert!(gross_amount == 0 || gross_amount - 1 - smaller_fee < net_amount);
+        }
+        assert!(state.gross_of_protocol_fee(u64::MAX).is_err());
+    }
+
+    #[test]
+    fn test_check_destination_policies() {
+        let owner = Pubkey::new_unique();
+        let listed = MerchantDestinationState {
+            version: CURRENT_VERSION,
+            allowed: true,
+            is_primary: true,
+            bump: 0,
+            last_received_amount: 0,
+            last_received_timestamp: 0,
+        };
+        let unlisted = MerchantDestinationState {
+            version: CURRENT_VERSION,
+            allowed: false,
+            is_primary: false,
+            bump: 0,
+            last_received_amount: 0,
+            last_received_timestamp: 0,
+        };
+
+        // Allowlist: only allowed destination states pass
+        let mut merchant_config = MerchantConfigState::default();
+        assert!(merchant_config
+            .check_destination(Some(&listed), &owner)
+            .unwrap());
+        assert_eq!(
+            merchant_config
+                .check_destination(Some(&unlisted), &owner)
+                .unwrap_err(),
+            ErrorCode::DestinationNotAllowed.into()
+        );
+        assert_eq!(
+            merchant_config.check_destination(None, &owner).unwrap_err(),
+            ErrorCode::DestinationNotAllowed.into()
+        );
+        merchant_config.allow_any_destination_owner = Some(owner);
+        assert!(!merchant_config.check_destination(None, &owner).unwrap());
+
+        // Denylist: allowed destination states block the destination
+        merchant_config.allow_any_destination_owner = None;
+        merchant_config.destination_policy = DestinationPolicy::Denylist as u8;
+        assert_eq!(
+            merchant_config
+                .check_destination(Some(&listed), &owner)
+                .unwrap_err(),
+            ErrorCode::DestinationNotAllowed.into()
+        );
+        assert!(!merchant_config
+            .check_destination(Some(&unlisted), &owner)
+            .unwrap());
+        assert!(!merchant_config.check_destination(None, &owner).unwrap());
+    }
+
+    #[test]
+    fn test_merchant_managers() {
+        let managers: Vec<Pubkey> = (0..=MAX_MERCHANT_MANAGERS)
+            .map(|_| Pubkey::new_unique())
+            .collect();
+        let mut manager_state = MerchantManagerState {
+            version: CURRENT_VERSION,
+            managers: [Pubkey::default(); MAX_MERCHANT_MANAGERS],
+            active_count: 0,
+            bump: 0,
+        };
+        manager_state.set_manager(managers[0]);
+        assert_eq!(manager_state.active_managers(), &managers[..1]);
+        assert!(!manager_state.is_manager(&Pubkey::default()));
+
+        // Managers are added up to the maximum, without duplicates
+        assert_eq!(
+            manager_state.add_manager(managers[0]).unwrap_err(),
+            ErrorCode::ManagerAlreadyAdded.into()
+        );
+        for manager in &managers[1..MAX_MERCHANT_MANAGERS] {
+            manager_state.add_manager(*manager).unwrap();
+        }
+        assert_eq!(
+            manager_state
+                .add_manager(managers[MAX_MERCHANT_MANAGERS])
+                .unwrap_err(),
+            ErrorCode::TooManyManagers.into()
+        );
+        assert!(managers[..MAX_MERCHANT_MANAGERS]
+            .iter()
+            .all(|manager| manager_state.is_manager(manager)));
+
+        // Removing a manager keeps the others in order
+        manager_state.remove_manager(&managers[1]).unwrap();
+        assert!(!manager_state.is_manager(&managers[1]));
+        assert_eq!(
+            manager_state.active_managers(),
+            &[managers[0], managers[2], managers[3]]
+        );
+        assert_eq!(
+            manager_state.managers[MAX_MERCHANT_MANAGERS - 1],
+            Pubkey::default()
+        );
+        assert_eq!(
+            manager_state.remove_manager(&managers[1]).unwrap_err(),
+            ErrorCode::ManagerNotFound.into()
+        );
+
+        // The last manager cannot be removed
+        manager_state.remove_manager(&managers[0]).unwrap();
+        manager_state.remove_manager(&managers[3]).unwrap();
+        assert_eq!(
+            manager_state.remove_manager(&managers[2]).unwrap_err(),
+            ErrorCode::CannotRemoveLastManager.into()
+        );
+
+        // Setting a manager replaces all the others
+        manager_state.add_manager(managers[4]).unwrap();
+        manager_state.set_manager(managers[1]);
+        assert_eq!(manager_state.active_managers(), &managers[1..2]);
+        assert_eq!(
+            manager_state.managers[1..],
+            [Pubkey::default(); MAX_MERCHANT_MANAGERS - 1]
+        );
+    }
+
+    #[test]
+    fn test_next_period_reset_timestamp() {
+        let state = setup_delegate_state();
+        assert_eq!(state.next_period_reset_timestamp(), 100 + 3600);
+        assert_eq!(
+            state.time_until_reset(state.next_period_reset_timestamp()),
+            0
+        );
+    }
+
+    #[test]
+    fn test_next_period_reset_timestamp_saturates() {
+        let mut state = setup_delegate_state();
+        state.period_timestamp_last_reset = u64::MAX - 10;
+        assert_eq!(state.next_period_reset_timestamp(), u64::MAX);
+    }
 }
@@ -0,0 +1,199 @@
+use crate::common::*;
+use anchor_lang::error::ErrorCode as AnchorErrorCode;
+use anchor_lang::prelude::*;
+use bridge_cards::accounts::AddMerchantManager;
+use bridge_cards::errors::ErrorCode;
+use bridge_cards::event_parsing::{parse_events, BridgeCardsEvent};
+use bridge_cards::events::MerchantManagerAdded;
+use bridge_cards::state::{MerchantDebitorState, MerchantManagerState, MAX_MERCHANT_MANAGERS};
+use litesvm::types::TransactionResult;
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer};
+
+type TestContext = crate::common::Context;
+
+fn find_merchant_manager_added_event(logs: &[String]) -> Option<MerchantManagerAdded> {
+    parse_events(logs)
+        .into_iter()
+        .find_map(|event| match event {
+            BridgeCardsEvent::MerchantManagerAdded(event) => Some(event),
+            _ => None,
+        })
+}
+
+#[allow(clippy::result_large_err)]
+fn add_manager(ctx: &mut TestContext, admin: &Keypair, manager: Pubkey) -> TransactionResult {
+    ctx.svm.expire_blockhash();
+    let accounts = AddMerchantManager {
+        admin: admin.pubkey(),
+        state: ctx.bridge_cards_state.pubkey,
+        manager_state: ctx.merchant_manager_state.pubkey,
+        manager,
+    };
+    let ix = create_add_merchant_manager_instruction(ctx, &accounts, TEST_MERCHANT_ID);
+    let tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, admin],
+    );
+    submit_transaction(ctx, tx)
+}
+
+fn assert_error(result: TransactionResult, expected_message: String) {
+    let err = result.expect_err("Transaction should fail");
+    assert!(
+        err.meta
+            .logs
+            .iter()
+            .any(|log| log.contains(&expected_message)),
+        "Error should contain the expected error message {}, got {}",
+        expected_message,
+        err.meta.logs.join("\n")
+    );
+}
+
+fn read_manager_state(ctx: &TestContext) -> MerchantManagerState {
+    let account = ctx
+        .svm
+        .get_account(&ctx.merchant_manager_state.pubkey)
+        .unwrap();
+    MerchantManagerState::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+fn read_debitor_allowed(ctx: &TestContext, mint: &Pubkey, debitor: &Pubkey) -> bool {
+    let debitor_pda = make_merchant_debitor_pda(TEST_MERCHANT_ID, debitor, mint, &ctx.program_id);
+    let account = ctx.svm.get_account(&debitor_pda.pubkey).unwrap();
+    MerchantDebitorState::try_deserialize(&mut account.data.as_slice())
+        .unwrap()
+        .allowed
+}
+
+#[tokio::test]
+async fn test_add_merchant_manager() {
+    let mut ctx = setup_and_initialize();
+    let admin = ctx.payer_kp.insecure_clone();
+    let second_manager = Keypair::new();
+
+    let result = add_manager(&mut ctx, &admin, second_manager.pubkey());
+    assert!(
+        result.is_ok(),
+        "Failed to add merchant manager: {:?}",
+        result.err()
+    );
+
+    let event = find_merchant_manager_added_event(&result.unwrap().logs)
+        .expect("MerchantManagerAdded event not found");
+    assert_eq!(event.merchant_id, TEST_MERCHANT_ID);
+    assert_eq!(event.manager, second_manager.pubkey());
+    assert_eq!(event.active_count, 2);
+
+    let manager_state = read_manager_state(&ctx);
+    assert_eq!(
+        manager_state.active_managers(),
+        &[ctx.merchant_manager_kp.pubkey(), second_manager.pubkey()]
+    );
+}
+
+#[tokio::test]
+async fn test_two_managers_configure_debitors_independently() {
+    let mut ctx = setup_and_initialize();
+    let second_manager = Keypair::new();
+    add_merchant_manager(&mut ctx, TEST_MERCHANT_ID, second_manager.pubkey());
+
+    let mint_pk = setup_mint(&mut ctx);
+    let first_debitor = Pubkey::new_unique();
+    let second_debitor = Pubkey::new_unique();
+
+    // Each manager adds its own debitor
+    let first_manager = ctx.merchant_manager_kp.insecure_clone();
+    let result = configure_merchant_debitor(&mut ctx, &first_manager, mint_pk, first_debitor, true);
+    assert!(
+        result.is_ok(),
+        "First manager failed to add a debitor: {:?}",
+        result.err()
+    );
+    let result =
+        configure_merchant_debitor(&mut ctx, &second_manager, mint_pk, second_debitor, true);
+    assert!(
+        result.is_ok(),
+        "Second manager failed to add a debitor: {:?}",
+        result.err()
+    );
+    assert!(read_debitor_allowed(&ctx, &mint_pk, &first_debitor));
+    assert!(read_debitor_allowed(&ctx, &mint_pk, &second_debitor));
+
+    // Either manager can update a debitor added by the other
+    let result =
+        configure_merchant_debitor(&mut ctx, &second_manager, mint_pk, first_debitor, false);
+    assert!(
+        result.is_ok(),
+        "Second manager failed to update a debitor: {:?}",
+        result.err()
+    );
+    assert!(!read_debitor_allowed(&ctx, &mint_pk, &first_debitor));
+    assert!(read_debitor_allowed(&ctx, &mint_pk, &second_debitor));
+
+    // Accounts that are not managers are still rejected
+    let non_manager = Keypair::new();
+    assert_error(
+        configure_merchant_debitor(&mut ctx, &non_manager, mint_pk, second_debitor, false),
+        AnchorErrorCode::ConstraintRaw.to_string(),
+    );
+}
+
+#[tokio::test]
+async fn test_add_merchant_manager_rejects_duplicates() {
+    let mut ctx = setup_and_initialize();
+    let admin = ctx.payer_kp.insecure_clone();
+
+    let manager = ctx.merchant_manager_kp.pubkey();
+    assert_error(
+        add_manager(&mut ctx, &admin, manager),
+        ErrorCode::ManagerAlreadyAdded.to_string(),
+    );
+    assert_eq!(read_manager_state(&ctx).active_count, 1);
+}
+
+#[tokio::test]
+async fn test_add_merchant_manager_rejects_too_many_managers() {
+    let mut ctx = setup_and_initialize();
+    let admin = ctx.payer_kp.insecure_clone();
+
+    for _ in 1..MAX_MERCHANT_MANAGERS {
+        add_merchant_manager(&mut ctx, TEST_MERCHANT_ID, Pubkey::new_unique());
+    }
+    assert_error(
+        add_manager(&mut ctx, &admin, Pubkey::new_unique()),
+        ErrorCode::TooManyManagers.to_string(),
+    );
+    assert_eq!(
+        read_manager_state(&ctx).active_count as usize,
+        MAX_MERCHANT_MANAGERS
+    );
+}
+
+#[tokio::test]
+async fn test_add_merchant_manager_rejects_invalid_manager() {
+    let mut ctx = setup_and_initialize();
+    let admin = ctx.payer_kp.insecure_clone();
+
+    let state = ctx.bridge_cards_state.pubkey;
+    assert_error(
+        add_manager(&mut ctx, &admin, state),
+        ErrorCode::InvalidManager.to_string(),
+    );
+}
+
+#[tokio::test]
+async fn test_non_admin_cannot_add_merchant_manager() {
+    let mut ctx = setup_and_initialize();
+
+    let non_admin = Keypair::new();
+    let result = add_manager(&mut ctx, &non_admin, non_admin.pubkey());
+    assert!(
+        result.is_err(),
+        "Non-admin should not be able to add a merchant manager"
+    );
+    assert_eq!(read_manager_state(&ctx).active_count, 1);
+}
@@ -0,0 +1,203 @@
+use crate::common::*;
+use account_data_trait::AccountData;
+use anchor_lang::prelude::*;
+use bridge_cards::{
+    accounts::AddOrUpdateMerchantConfig,
+    errors::ErrorCode,
+    event_parsing::{parse_events, BridgeCardsEvent},
+    events::MerchantConfigAddedOrUpdated,
+    instructions::add_or_update_merchant_config::MerchantConfigParams,
+    state::{MerchantConfigState, CURRENT_VERSION},
+};
+use solana_program_test::tokio;
+
+fn find_merchant_config_event(logs: &[String]) -> Option<MerchantConfigAddedOrUpdated> {
+    parse_events(logs)
+        .into_iter()
+        .find_map(|event| match event {
+            BridgeCardsEvent::MerchantConfigAddedOrUpdated(event) => Some(event),
+            _ => None,
+        })
+}
+
+#[tokio::test]
+async fn test_create_merchant_config() {
+    let mut ctx = setup_and_initialize();
+    let merchant_config_pda = make_merchant_config_pda(TEST_MERCHANT_ID, &ctx.program_id);
+
+    let accounts = AddOrUpdateMerchantConfig {
+        admin: ctx.payer_pk,
+        payer: ctx.payer_pk,
+        state: ctx.bridge_cards_state.pubkey,
+        merchant_config: merchant_config_pda.pubkey,
+        system_program: System::id(),
+    };
+    let params = MerchantConfigParams {
+        router_program: MOCK_ROUTER_PROGRAM_ID,
+        ..Default::default()
+    };
+    let ix =
+        create_add_or_update_merchant_config_instruction(&ctx, &accounts, TEST_MERCHANT_ID, params);
+    let tx = create_transaction(&ctx, &[ix]);
+
+    let result = submit_transaction(&mut ctx, tx);
+    assert!(
+        result.is_ok(),
+        "Failed to create merchant config: {:?}",
+        result.err()
+    );
+
+    // Verify the MerchantConfigAddedOrUpdated event
+    let meta = result.unwrap();
+    let event = find_merchant_config_event(&meta.logs)
+        .expect("MerchantConfigAddedOrUpdated event not found");
+    assert_eq!(event.merchant_id, TEST_MERCHANT_ID);
+    assert_eq!(event.state_pda, merchant_config_pda.pubkey);
+    assert_eq!(event.config.router_program, MOCK_ROUTER_PROGRAM_ID);
+
+    // Verify the merchant config state
+    let expected_config_data = MerchantConfigState {
+        version: CURRENT_VERSION,
+        router_program: MOCK_ROUTER_PROGRAM_ID,
+        require_user_owner_system_owned: false,
+        emit_full_snapshot: false,
+        min_slots_between_debits: 0,
+        enforce_primary_destination: false,
+        reset_at_period_boundary: false,
+        managers_can_only_lower_limits: false,
+        default_delegate_expiry_seconds: 0,
+        paused: false,
+        allow_any_destination_owner: None,
+        destination_policy: 0,
+        bump: merchant_config_pda.bump,
+        enforce_mint_allowlist: false,
+        max_clock_skew_seconds: 0,
+        default_per_transfer_limit: 0,
+        default_period_transfer_limit: 0,
+        default_period_seconds: 0,
+        max_allowed_per_transfer: 0,
+        max_allowed_period_limit: 0,
+        max_splits: 0,
+    }
+    .account_data();
+    let config_account = ctx.svm.get_account(&merchant_config_pda.pubkey).unwrap();
+    assert_eq!(
+        config_account.data, expected_config_data,
+        "Merchant config data doesn't match expected data"
+    );
+}
+
+#[tokio::test]
+async fn test_update_merchant_config() {
+    let mut ctx = setup_and_initialize();
+    let merchant_config_pda = setup_merchant_config(
+        &mut ctx,
+        TEST_MERCHANT_ID,
+        MerchantConfigParams {
+            router_program: MOCK_ROUTER_PROGRAM_ID,
+            ..Default::default()
+        },
+    );
+
+    // Clear the router to disable swaps for the merchant
+    let accounts = AddOrUpdateMerchantConfig {
+        admin: ctx.payer_pk,
+        payer: ctx.payer_pk,
+        state: ctx.bridge_cards_state.pubkey,
+        merchant_config: merchant_config_pda,
+        system_program: System::id(),
+    };
+    let ix = create_add_or_update_merchant_config_instruction(
+        &ctx,
+        &accounts,
+        TEST_MERCHANT_ID,
+        MerchantConfigParams::default(),
+    );
+    let tx = create_transaction(&ctx, &[ix]);
+
+    let result = submit_transaction(&mut ctx, tx);
+    assert!(
+        result.is_ok(),
+        "Failed to update merchant config: {:?}",
+        result.err()
+    );
+
+    let config_account = ctx.svm.get_account(&merchant_config_pda).unwrap();
+    let config_state =
+        MerchantConfigState::try_deserialize(&mut config_account.data.as_slice()).unwrap();
+    assert_eq!(config_state.router_program, Pubkey::default());
+}
+
+#[tokio::test]
+async fn test_non_admin_cannot_create_merchant_config() {
+    let mut ctx = setup_and_initialize();
+    let (non_admin_kp, non_admin_pk) = setup_keypair(&mut ctx);
+    let merchant_config_pda = make_merchant_config_pda(TEST_MERCHANT_ID, &ctx.program_id);
+
+    let accounts = AddOrUpdateMerchantConfig {
+        admin: non_admin_pk,
+        payer: non_admin_pk,
+        state: ctx.bridge_cards_state.pubkey,
+        merchant_config: merchant_config_pda.pubkey,
+        system_program: System::id(),
+    };
+    let params = MerchantConfigParams {
+        router_program: MOCK_ROUTER_PROGRAM_ID,
+        ..Default::default()
+    };
+    let ix =
+        create_add_or_update_merchant_config_instruction(&ctx, &accounts, TEST_MERCHANT_ID, params);
+    let tx = create_transaction_with_payer_and_signers(
+        &ctx,
+        &[ix],
+        Some(&non_admin_pk),
+        &[&non_admin_kp],
+    );
+
+    let result = submit_transaction(&mut ctx, tx);
+    assert!(
+        result.is_err(),
+        "Non-admin should not be able to create a merchant config"
+    );
+    assert!(
+        ctx.svm.get_account(&merchant_config_pda.pubkey).is_none(),
+        "Merchant config should not be created"
+    );
+}
+
+#[tokio::test]
+async fn test_invalid_destination_policy_rejected() {
+    let mut ctx = setup_and_initialize();
+    let merchant_config_pda = make_merchant_config_pda(TEST_MERCHANT_ID, &ctx.program_id);
+
+    let accounts = AddOrUpdateMerchantConfig {
+        admin: ctx.payer_pk,
+        payer: ctx.payer_pk,
+        state: ctx.bridge_cards_state.pubkey,
+        merchant_config: merchant_config_pda.pubkey,
+        system_program: System::id(),
+    };
+    let params = MerchantConfigParams {
+        destination_policy: 2,
+        ..Default::default()
+    };
+    let ix =
+        create_add_or_update_merchant_config_instruction(&ctx, &accounts, TEST_MERCHANT_ID, params);
+    let tx = create_transaction(&ctx, &[ix]);
+
+    let err = submit_transaction(&mut ctx, tx).expect_err("Invalid destination policy accepted");
+    let expected_message = ErrorCode::InvalidDestinationPolicy.to_string();
+    assert!(
+        err.meta
+            .logs
+            .iter()
+            .any(|log| log.contains(&expected_message)),
+        "Error should contain the expected error message {}, got {}",
+        expected_message,
+        err.meta.logs.join("\n")
+    );
+    assert!(
+        ctx.svm.get_account(&merchant_config_pda.pubkey).is_none(),
+        "Merchant config should not be created"
+    );
+}
@@ -1,10 +1,10 @@
 use crate::common::*;
 use account_data_trait::AccountData;
-use anchor_lang::{prelude::*, Event};
-use base64;
+use anchor_lang::prelude::*;
 use bridge_cards::{
-    accounts::AddOrUpdateMerchantDebitor, events::MerchantDebitorAddedOrUpdated,
-    state::MerchantDebitorState,
+    accounts::AddOrUpdateMerchantDebitor,
+    event_parsing::{parse_events, BridgeCardsEvent},
+    state::{MerchantDebitorState, CURRENT_VERSION},
 };
 use solana_program_test::tokio;
 use solana_sdk::signature::Signer;
@@ -32,6 +32,7 @@ async fn test_add_merchant_debitor() {
         debitor_state: debitor_pda.pubkey,
         mint: mint_pk,
         system_program: System::id(),
+        merchant_registry: None,
     };
 
     let ix =
@@ -54,23 +55,14 @@ async fn test_add_merchant_debitor() {
     // Verify the MerchantDebitorAddedOrUpdated event
     let meta = result.unwrap();
     let mut event_found = false;
-    for log in meta.logs.iter() {
-        if let Some(data_str) = log.strip_prefix("Program data: ") {
-            if let Ok(log_bytes) = base64::decode(data_str) {
-                if log_bytes.len() > 8 {
-                    let event_data = &log_bytes[8..];
-                    if let Ok(parsed_event) =
-                        MerchantDebitorAddedOrUpdated::try_from_slice(event_data)
-                    {
-                        assert_eq!(parsed_event.merchant_id, TEST_MERCHANT_ID);
-                        assert_eq!(parsed_event.debitor, debitor_pk);
-                        assert_eq!(parsed_event.state_pda, debitor_pda.pubkey);
-                        assert!(parsed_event.new_state);
-                        event_found = true;
-                        break;
-                    }
-                }
-            }
+    for event in parse_events(&meta.logs) {
+        if let BridgeCardsEvent::MerchantDebitorAddedOrUpdated(parsed_event) = event {
+            assert_eq!(parsed_event.merchant_id, TEST_MERCHANT_ID);
+            assert_eq!(parsed_event.debitor, debitor_pk);
+            assert_eq!(parsed_event.state_pda, debitor_pda.pubkey);
+            assert!(parsed_event.new_state);
+            event_found = true;
+            break;
         }
     }
     assert!(
@@ -81,7 +73,12 @@ async fn test_add_merchant_debitor() {
 
     // Step 5: Verify the merchant state
     let expected_merchant_data = MerchantDebitorState {
+        version: CURRENT_VERSION,
         allowed: true,
+        per_call_limit: 0,
+        daily_limit: 0,
+        daily_transferred_amount: 0,
+        daily_timestamp_last_reset: 0,
         bump: debitor_pda.bump,
     }
     .account_data();
@@ -114,6 +111,7 @@ async fn test_add_second_debitor() {
         debitor_state: debitor_pda.pubkey,
         mint: mint_pk,
         system_program: System::id(),
+        merchant_registry: None,
     };
 
     let ix =
@@ -134,20 +132,9 @@ async fn test_add_second_debitor() {
 
     // Verify the event for the first creation (optional, could be removed)
     let meta1 = result.unwrap();
-    let mut event1_found = false;
-    for log in meta1.logs.iter() {
-        if let Some(data_str) = log.strip_prefix("Program data: ") {
-            if let Ok(log_bytes) = base64::decode(data_str) {
-                if log_bytes.len() > 8 {
-                    let event_data = &log_bytes[8..];
-                    if MerchantDebitorAddedOrUpdated::try_from_slice(event_data).is_ok() {
-                        event1_found = true;
-                        break;
-                    }
-                }
-            }
-        }
-    }
+    let event1_found = parse_events(&meta1.logs)
+        .iter()
+        .any(|event| matches!(event, BridgeCardsEvent::MerchantDebitorAddedOrUpdated(_)));
     assert!(
         event1_found,
         "MerchantDebitorAddedOrUpdated event not found for first creation: {}",
@@ -171,6 +158,7 @@ async fn test_add_second_debitor() {
         debitor_state: new_debitor_pda.pubkey,
         mint: new_mint_pk,
         system_program: System::id(),
+        merchant_registry: None,
     };
 
     let update_ix = create_add_or_update_merchant_debitor_instruction(
@@ -197,23 +185,14 @@ async fn test_add_second_debitor() {
     // Verify the MerchantDebitorAddedOrUpdated event for the second debitor
     let meta2 = update_result.unwrap();
     let mut event2_found = false;
-    for log in meta2.logs.iter() {
-        if let Some(data_str) = log.strip_prefix("Program data: ") {
-            if let Ok(log_bytes) = base64::decode(data_str) {
-                if log_bytes.len() > 8 {
-                    let event_data = &log_bytes[8..];
-                    if let Ok(parsed_event) =
-                        MerchantDebitorAddedOrUpdated::try_from_slice(event_data)
-                    {
-                        assert_eq!(parsed_event.merchant_id, TEST_MERCHANT_ID);
-                        assert_eq!(parsed_event.debitor, new_debitor_pk);
-                        assert_eq!(parsed_event.state_pda, new_debitor_pda.pubkey);
-                        assert!(parsed_event.new_state);
-                        event2_found = true;
-                        break;
-                    }
-                }
-            }
+    for event in parse_events(&meta2.logs) {
+        if let BridgeCardsEvent::MerchantDebitorAddedOrUpdated(parsed_event) = event {
+            assert_eq!(parsed_event.merchant_id, TEST_MERCHANT_ID);
+            assert_eq!(parsed_event.debitor, new_debitor_pk);
+            assert_eq!(parsed_event.state_pda, new_debitor_pda.pubkey);
+            assert!(parsed_event.new_state);
+            event2_found = true;
+            break;
         }
     }
     assert!(
@@ -224,7 +203,12 @@ async fn test_add_second_debitor() {
 
     // Step 5: Verify the merchant state has been updated
     let expected_updated_merchant_data = MerchantDebitorState {
+        version: CURRENT_VERSION,
         allowed: true,
+        per_call_limit: 0,
+        daily_limit: 0,
+        daily_transferred_amount: 0,
+        daily_timestamp_last_reset: 0,
         bump: debitor_pda.bump,
     }
     .account_data();
@@ -254,6 +238,7 @@ async fn test_update_debitor_to_false_then_back() {
         debitor_state: debitor_pda.pubkey,
         mint: mint_pk,
         system_program: System::id(),
+        merchant_registry: None,
     };
 
     let ix =
@@ -274,20 +259,9 @@ async fn test_update_debitor_to_false_then_back() {
 
     // Verify the event for the initial creation (optional)
     let meta1 = result.unwrap();
-    let mut event1_found = false;
-    for log in meta1.logs.iter() {
-        if let Some(data_str) = log.strip_prefix("Program data: ") {
-            if let Ok(log_bytes) = base64::decode(data_str) {
-                if log_bytes.len() > 8 {
-                    let event_data = &log_bytes[8..];
-                    if MerchantDebitorAddedOrUpdated::try_from_slice(event_data).is_ok() {
-                        event1_found = true;
-                        break;
-                    }
-                }
-            }
-        }
-    }
+    let event1_found = parse_events(&meta1.logs)
+        .iter()
+        .any(|event| matches!(event, BridgeCardsEvent::MerchantDebitorAddedOrUpdated(_)));
     assert!(
         event1_found,
         "MerchantDebitorAddedOrUpdated event not found for initial creation: {}",
@@ -295,7 +269,12 @@ async fn test_update_debitor_to_false_then_back() {
     );
 
     let expected_merchant_data = MerchantDebitorState {
+        version: CURRENT_VERSION,
         allowed: true,
+        per_call_limit: 0,
+        daily_limit: 0,
+        daily_transferred_amount: 0,
+        daily_timestamp_last_reset: 0,
         bump: debitor_pda.bump,
     }
     .account_data();
@@ -326,24 +305,15 @@ async fn test_update_debitor_to_false_then_back() {
     // Verify the MerchantDebitorAddedOrUpdated event for the update to false
     let meta2 = result.unwrap();
     let mut event2_found = false;
-    for log in meta2.logs.iter() {
-        if let Some(data_str) = log.strip_prefix("Program data: ") {
-            if let Ok(log_bytes) = base64::decode(data_str) {
-                if log_bytes.len() > 8 {
-                    let event_data = &log_bytes[8..];
-                    if let Ok(parsed_event) =
-                        MerchantDebitorAddedOrUpdated::try_from_slice(event_data)
-                    {
-                        assert_eq!(parsed_event.merchant_id, TEST_MERCHANT_ID);
-                        assert_eq!(parsed_event.debitor, debitor_pk);
-                        assert_eq!(parsed_event.state_pda, debitor_pda.pubkey);
-                        assert!(parsed_event.previous_state);
-                        assert!(!parsed_event.new_state);
-                        event2_found = true;
-                        break;
-                    }
-                }
-            }
+    for event in parse_events(&meta2.logs) {
+        if let BridgeCardsEvent::MerchantDebitorAddedOrUpdated(parsed_event) = event {
+            assert_eq!(parsed_event.merchant_id, TEST_MERCHANT_ID);
+            assert_eq!(parsed_event.debitor, debitor_pk);
+            assert_eq!(parsed_event.state_pda, debitor_pda.pubkey);
+            assert!(parsed_event.previous_state);
+            assert!(!parsed_event.new_state);
+            event2_found = true;
+            break;
         }
     }
     assert!(
@@ -353,7 +323,12 @@ async fn test_update_debitor_to_false_then_back() {
     );
 
     let expected_merchant_data = MerchantDebitorState {
+        version: CURRENT_VERSION,
         allowed: false,
+        per_call_limit: 0,
+        daily_limit: 0,
+        daily_transferred_amount: 0,
+        daily_timestamp_last_reset: 0,
         bump: debitor_pda.bump,
     }
     .account_data();
@@ -388,6 +363,7 @@ async fn test_non_manager_cannot_add_debitor() {
         debitor_state: debitor_pda.pubkey,
         mint: mint_pk,
         system_program: System::id(),
+        merchant_registry: None,
     };
 
     let ix =
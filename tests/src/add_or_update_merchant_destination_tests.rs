@@ -1,10 +1,10 @@
 use crate::common::*;
 use account_data_trait::AccountData;
-use anchor_lang::{prelude::*, Event};
-use base64;
+use anchor_lang::prelude::*;
 use bridge_cards::{
-    accounts::AddOrUpdateMerchantDestination, events::MerchantDestinationAddedOrUpdated,
-    state::MerchantDestinationState,
+    accounts::AddOrUpdateMerchantDestination,
+    event_parsing::{parse_events, BridgeCardsEvent},
+    state::{MerchantDestinationState, CURRENT_VERSION},
 };
 use litesvm_token::CreateAssociatedTokenAccountIdempotent;
 
@@ -46,6 +46,7 @@ async fn test_create_merchant_destination() {
         destination_token_account,
         mint: mint_pk,
         system_program: System::id(),
+        merchant_registry: None,
     };
 
     let ix =
@@ -68,24 +69,16 @@ async fn test_create_merchant_destination() {
     // Verify the MerchantDestinationAddedOrUpdated event
     let meta = result.unwrap();
     let mut event_found = false;
-    for log in meta.logs.iter() {
-        if let Some(data_str) = log.strip_prefix("Program data: ") {
-            if let Ok(log_bytes) = base64::decode(data_str) {
-                if log_bytes.len() > 8 {
-                    let event_data = &log_bytes[8..];
-                    if let Ok(parsed_event) =
-                        MerchantDestinationAddedOrUpdated::try_from_slice(event_data)
-                    {
-                        assert_eq!(parsed_event.merchant_id, merchant_id);
-                        assert_eq!(parsed_event.mint, mint_pk);
-                        assert_eq!(parsed_event.destination, destination_token_account_key);
-                        assert_eq!(parsed_event.state_pda, merchant_destination_pda.pubkey);
-                        assert!(parsed_event.new_state);
-                        event_found = true;
-                        break;
-                    }
-                }
-            }
+    for event in parse_events(&meta.logs) {
+        if let BridgeCardsEvent::MerchantDestinationAddedOrUpdated(parsed_event) = event {
+            assert_eq!(parsed_event.merchant_id, merchant_id);
+            assert_eq!(parsed_event.mint, mint_pk);
+            assert_eq!(parsed_event.destination, destination_token_account_key);
+            assert_eq!(parsed_event.state_pda, merchant_destination_pda.pubkey);
+            assert!(parsed_event.new_state);
+            assert!(!parsed_event.is_primary);
+            event_found = true;
+            break;
         }
     }
     assert!(
@@ -96,8 +89,12 @@ async fn test_create_merchant_destination() {
 
     // Step 5: Verify the merchant state
     let expected_merchant_data = MerchantDestinationState {
+        version: CURRENT_VERSION,
         allowed: true,
+        is_primary: false,
         bump: merchant_destination_pda.bump,
+        last_received_amount: 0,
+        last_received_timestamp: 0,
     }
     .account_data();
 
@@ -146,6 +143,7 @@ async fn test_update_merchant_destination() {
         destination_token_account,
         mint: mint_pk,
         system_program: System::id(),
+        merchant_registry: None,
     };
 
     let ix =
@@ -166,20 +164,12 @@ async fn test_update_merchant_destination() {
 
     // Verify the event for the initial creation
     let meta1 = result.unwrap();
-    let mut event1_found = false;
-    for log in meta1.logs.iter() {
-        if let Some(data_str) = log.strip_prefix("Program data: ") {
-            if let Ok(log_bytes) = base64::decode(data_str) {
-                if log_bytes.len() > 8 {
-                    let event_data = &log_bytes[8..];
-                    if MerchantDestinationAddedOrUpdated::try_from_slice(event_data).is_ok() {
-                        event1_found = true;
-                        break;
-                    }
-                }
-            }
-        }
-    }
+    let event1_found = parse_events(&meta1.logs).iter().any(|event| {
+        matches!(
+            event,
+            BridgeCardsEvent::MerchantDestinationAddedOrUpdated(_)
+        )
+    });
     assert!(
         event1_found,
         "MerchantDestinationAddedOrUpdated event not found for initial creation: {}",
@@ -194,6 +184,7 @@ async fn test_update_merchant_destination() {
         mint: mint_pk,
         destination_token_account,
         system_program: System::id(),
+        merchant_registry: None,
     };
 
     let update_ix = create_add_or_update_merchant_destination_instruction(
@@ -220,25 +211,16 @@ async fn test_update_merchant_destination() {
     // Verify the event for the update
     let meta2 = update_result.unwrap();
     let mut event2_found = false;
-    for log in meta2.logs.iter() {
-        if let Some(data_str) = log.strip_prefix("Program data: ") {
-            if let Ok(log_bytes) = base64::decode(data_str) {
-                if log_bytes.len() > 8 {
-                    let event_data = &log_bytes[8..];
-                    if let Ok(parsed_event) =
-                        MerchantDestinationAddedOrUpdated::try_from_slice(event_data)
-                    {
-                        assert_eq!(parsed_event.merchant_id, merchant_id);
-                        assert_eq!(parsed_event.mint, mint_pk);
-                        assert_eq!(parsed_event.destination, destination_token_account_key);
-                        assert_eq!(parsed_event.state_pda, merchant_destination_pda.pubkey);
-                        assert!(parsed_event.previous_state);
-                        assert!(!parsed_event.new_state);
-                        event2_found = true;
-                        break;
-                    }
-                }
-            }
+    for event in parse_events(&meta2.logs) {
+        if let BridgeCardsEvent::MerchantDestinationAddedOrUpdated(parsed_event) = event {
+            assert_eq!(parsed_event.merchant_id, merchant_id);
+            assert_eq!(parsed_event.mint, mint_pk);
+            assert_eq!(parsed_event.destination, destination_token_account_key);
+            assert_eq!(parsed_event.state_pda, merchant_destination_pda.pubkey);
+            assert!(parsed_event.previous_state);
+            assert!(!parsed_event.new_state);
+            event2_found = true;
+            break;
         }
     }
     assert!(
@@ -249,8 +231,12 @@ async fn test_update_merchant_destination() {
 
     // Step 5: Verify the merchant state has been updated
     let expected_updated_merchant_data = MerchantDestinationState {
+        version: CURRENT_VERSION,
         allowed: false,
+        is_primary: false,
         bump: merchant_destination_pda.bump,
+        last_received_amount: 0,
+        last_received_timestamp: 0,
     }
     .account_data();
 
@@ -300,6 +286,7 @@ async fn test_non_admin_cannot_create_merchant() {
         mint: mint_pk,
         destination_token_account,
         system_program: System::id(),
+        merchant_registry: None,
     };
 
     let ix =
@@ -358,6 +345,7 @@ async fn test_two_merchant_destinations() {
         mint: mint_pk,
         destination_token_account,
         system_program: System::id(),
+        merchant_registry: None,
     };
 
     let ix =
@@ -398,6 +386,7 @@ async fn test_two_merchant_destinations() {
         mint: mint_pk,
         destination_token_account: destination_token_account2,
         system_program: System::id(),
+        merchant_registry: None,
     };
 
     let ix2 =
@@ -417,8 +406,12 @@ async fn test_two_merchant_destinations() {
     );
 
     let expected_merchant_data = MerchantDestinationState {
+        version: CURRENT_VERSION,
         allowed: true,
+        is_primary: false,
         bump: merchant_destination_pda2.bump,
+        last_received_amount: 0,
+        last_received_timestamp: 0,
     }
     .account_data();
 
@@ -436,8 +429,12 @@ async fn test_two_merchant_destinations() {
         .get_account(&merchant_destination_pda.pubkey)
         .unwrap();
     let expected_merchant_data = MerchantDestinationState {
+        version: CURRENT_VERSION,
         allowed: true,
+        is_primary: false,
         bump: merchant_destination_pda.bump,
+        last_received_amount: 0,
+        last_received_timestamp: 0,
     }
     .account_data();
     assert_eq!(
@@ -1,12 +1,13 @@
 use crate::common::*;
 use account_data_trait::AccountData;
-use anchor_lang::{prelude::*, Event};
-use base64;
+use anchor_lang::prelude::*;
 use bridge_cards::{
-    events::MerchantManagerAddedOrUpdated,
+    errors::ErrorCode,
+    event_parsing::{parse_events, BridgeCardsEvent},
     instructions::add_or_update_merchant_manager::MERCHANT_MANAGER_SEED,
-    state::MerchantManagerState,
+    state::{feature_flags, MerchantManagerState, CURRENT_VERSION},
 };
+use litesvm_token::CreateAssociatedTokenAccountIdempotent;
 use solana_program_test::tokio;
 use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
 
@@ -40,7 +41,14 @@ async fn test_add_merchant_manager_success() {
     // Verify manager state was created and updated correctly
     let manager_state_account = ctx.svm.get_account(&manager_state.pubkey).unwrap();
     let expected_manager_data = MerchantManagerState {
-        manager: manager.pubkey(),
+        version: CURRENT_VERSION,
+        managers: [
+            manager.pubkey(),
+            Pubkey::default(),
+            Pubkey::default(),
+            Pubkey::default(),
+        ],
+        active_count: 1,
         bump: manager_state.bump,
     }
     .account_data();
@@ -53,21 +61,12 @@ async fn test_add_merchant_manager_success() {
     // Verify the MerchantManagerAddedOrUpdated event
     let meta = result.unwrap();
     let mut event_found = false;
-    for log in meta.logs.iter() {
-        if let Some(data_str) = log.strip_prefix("Program data: ") {
-            if let Ok(log_bytes) = base64::decode(data_str) {
-                if log_bytes.len() > 8 {
-                    let event_data = &log_bytes[8..];
-                    if let Ok(parsed_event) =
-                        MerchantManagerAddedOrUpdated::try_from_slice(event_data)
-                    {
-                        assert_eq!(parsed_event.merchant_id, merchant_id);
-                        assert_eq!(parsed_event.manager, manager.pubkey());
-                        event_found = true;
-                        break;
-                    }
-                }
-            }
+    for event in parse_events(&meta.logs) {
+        if let BridgeCardsEvent::MerchantManagerAddedOrUpdated(parsed_event) = event {
+            assert_eq!(parsed_event.merchant_id, merchant_id);
+            assert_eq!(parsed_event.manager, manager.pubkey());
+            event_found = true;
+            break;
         }
     }
     assert!(
@@ -106,21 +105,12 @@ async fn test_update_existing_manager() {
     // Verify first event
     let meta1 = result.unwrap();
     let mut event1_found = false;
-    for log in meta1.logs.iter() {
-        if let Some(data_str) = log.strip_prefix("Program data: ") {
-            if let Ok(log_bytes) = base64::decode(data_str) {
-                if log_bytes.len() > 8 {
-                    let event_data = &log_bytes[8..];
-                    if let Ok(parsed_event) =
-                        MerchantManagerAddedOrUpdated::try_from_slice(event_data)
-                    {
-                        assert_eq!(parsed_event.merchant_id, merchant_id);
-                        assert_eq!(parsed_event.manager, old_manager.pubkey());
-                        event1_found = true;
-                        break;
-                    }
-                }
-            }
+    for event in parse_events(&meta1.logs) {
+        if let BridgeCardsEvent::MerchantManagerAddedOrUpdated(parsed_event) = event {
+            assert_eq!(parsed_event.merchant_id, merchant_id);
+            assert_eq!(parsed_event.manager, old_manager.pubkey());
+            event1_found = true;
+            break;
         }
     }
     assert!(
@@ -147,7 +137,14 @@ async fn test_update_existing_manager() {
     // Verify manager was updated
     let manager_state_account = ctx.svm.get_account(&manager_state.pubkey).unwrap();
     let expected_manager_data = MerchantManagerState {
-        manager: new_manager.pubkey(),
+        version: CURRENT_VERSION,
+        managers: [
+            new_manager.pubkey(),
+            Pubkey::default(),
+            Pubkey::default(),
+            Pubkey::default(),
+        ],
+        active_count: 1,
         bump: manager_state.bump,
     }
     .account_data();
@@ -160,21 +157,12 @@ async fn test_update_existing_manager() {
     // Verify second event
     let meta2 = result.unwrap();
     let mut event2_found = false;
-    for log in meta2.logs.iter() {
-        if let Some(data_str) = log.strip_prefix("Program data: ") {
-            if let Ok(log_bytes) = base64::decode(data_str) {
-                if log_bytes.len() > 8 {
-                    let event_data = &log_bytes[8..];
-                    if let Ok(parsed_event) =
-                        MerchantManagerAddedOrUpdated::try_from_slice(event_data)
-                    {
-                        assert_eq!(parsed_event.merchant_id, merchant_id);
-                        assert_eq!(parsed_event.manager, new_manager.pubkey());
-                        event2_found = true;
-                        break;
-                    }
-                }
-            }
+    for event in parse_events(&meta2.logs) {
+        if let BridgeCardsEvent::MerchantManagerAddedOrUpdated(parsed_event) = event {
+            assert_eq!(parsed_event.merchant_id, merchant_id);
+            assert_eq!(parsed_event.manager, new_manager.pubkey());
+            event2_found = true;
+            break;
         }
     }
     assert!(
@@ -253,21 +241,12 @@ async fn test_manager_signature_not_required() {
     // Verify event was emitted
     let meta = result.unwrap();
     let mut event_found = false;
-    for log in meta.logs.iter() {
-        if let Some(data_str) = log.strip_prefix("Program data: ") {
-            if let Ok(log_bytes) = base64::decode(data_str) {
-                if log_bytes.len() > 8 {
-                    let event_data = &log_bytes[8..];
-                    if let Ok(parsed_event) =
-                        MerchantManagerAddedOrUpdated::try_from_slice(event_data)
-                    {
-                        assert_eq!(parsed_event.merchant_id, merchant_id);
-                        assert_eq!(parsed_event.manager, manager.pubkey());
-                        event_found = true;
-                        break;
-                    }
-                }
-            }
+    for event in parse_events(&meta.logs) {
+        if let BridgeCardsEvent::MerchantManagerAddedOrUpdated(parsed_event) = event {
+            assert_eq!(parsed_event.merchant_id, merchant_id);
+            assert_eq!(parsed_event.manager, manager.pubkey());
+            event_found = true;
+            break;
         }
     }
     assert!(
@@ -276,3 +255,102 @@ async fn test_manager_signature_not_required() {
         meta.logs.join("\n")
     );
 }
+
+fn assert_invalid_manager(result: litesvm::types::TransactionResult) {
+    let err = result.expect_err("Setting an invalid manager should fail");
+    let expected_message = ErrorCode::InvalidManager.to_string();
+    assert!(
+        err.meta
+            .logs
+            .iter()
+            .any(|log| log.contains(&expected_message)),
+        "Error should contain the expected error message {}, got {}",
+        expected_message,
+        err.meta.logs.join("\n")
+    );
+}
+
+fn make_manager_accounts(
+    ctx: &crate::common::Context,
+    merchant_id: u64,
+    manager: Pubkey,
+) -> bridge_cards::accounts::AddOrUpdateMerchantManager {
+    bridge_cards::accounts::AddOrUpdateMerchantManager {
+        admin: ctx.payer_pk,
+        payer: ctx.payer_pk,
+        state: ctx.bridge_cards_state.pubkey,
+        manager_state: make_manager_pda(merchant_id, &ctx.program_id).pubkey,
+        manager,
+        system_program: anchor_lang::system_program::ID,
+    }
+}
+
+#[tokio::test]
+async fn test_manager_cannot_be_state_or_token_account() {
+    let mut ctx = setup();
+    initialize_bridge_cards(&mut ctx);
+
+    let merchant_id = 42u64;
+
+    // The program state PDA
+    let accounts = make_manager_accounts(&ctx, merchant_id, ctx.bridge_cards_state.pubkey);
+    let ix = create_add_or_update_merchant_manager_instruction(&ctx, &accounts, merchant_id);
+    let tx = create_transaction(&ctx, &[ix]);
+    assert_invalid_manager(submit_transaction(&mut ctx, tx));
+
+    // A token account
+    let mint_pk = setup_mint(&mut ctx);
+    let token_account =
+        CreateAssociatedTokenAccountIdempotent::new(&mut ctx.svm, &ctx.payer_kp, &mint_pk)
+            .owner(&Keypair::new().pubkey())
+            .send()
+            .unwrap();
+    let accounts = make_manager_accounts(&ctx, merchant_id, token_account);
+    let ix = create_add_or_update_merchant_manager_instruction(&ctx, &accounts, merchant_id);
+    let tx = create_transaction(&ctx, &[ix]);
+    assert_invalid_manager(submit_transaction(&mut ctx, tx));
+
+    let manager_state = make_manager_pda(merchant_id, &ctx.program_id);
+    assert!(ctx.svm.get_account(&manager_state.pubkey).is_none());
+}
+
+#[tokio::test]
+async fn test_manager_cosign_required_when_flag_set() {
+    let mut ctx = setup();
+    initialize_bridge_cards(&mut ctx);
+    set_feature_flags(&mut ctx, feature_flags::REQUIRE_MANAGER_COSIGN);
+
+    let merchant_id = 42u64;
+    let manager = Keypair::new();
+    let accounts = make_manager_accounts(&ctx, merchant_id, manager.pubkey());
+
+    // Without the manager's signature
+    let ix = create_add_or_update_merchant_manager_instruction(&ctx, &accounts, merchant_id);
+    let tx = create_transaction(&ctx, &[ix]);
+    assert_invalid_manager(submit_transaction(&mut ctx, tx));
+
+    // With the manager co-signing
+    let mut ix = create_add_or_update_merchant_manager_instruction(&ctx, &accounts, merchant_id);
+    for meta in ix.accounts.iter_mut() {
+        if meta.pubkey == manager.pubkey() {
+            meta.is_signer = true;
+        }
+    }
+    let tx = create_transaction_with_payer_and_signers(
+        &ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &manager],
+    );
+    let result = submit_transaction(&mut ctx, tx);
+    assert!(
+        result.is_ok(),
+        "Co-signed manager should be accepted: {:?}",
+        result.err()
+    );
+
+    let manager_state = make_manager_pda(merchant_id, &ctx.program_id);
+    let account = ctx.svm.get_account(&manager_state.pubkey).unwrap();
+    let state = MerchantManagerState::try_deserialize(&mut account.data.as_slice()).unwrap();
+    assert_eq!(state.active_managers(), &[manager.pubkey()]);
+}
@@ -0,0 +1,242 @@
+use crate::common::*;
+use account_data_trait::AccountData;
+use anchor_lang::prelude::*;
+use bridge_cards::accounts::{AddOrUpdateUserDelegate, DebitUser};
+use bridge_cards::errors::ErrorCode;
+use bridge_cards::event_parsing::{parse_events, BridgeCardsEvent};
+use bridge_cards::events::MerchantMintAddedOrUpdated;
+use bridge_cards::instructions::add_or_update_merchant_config::MerchantConfigParams;
+use bridge_cards::state::{MerchantMintState, CURRENT_VERSION};
+use litesvm::types::TransactionResult;
+use solana_program_test::tokio;
+use solana_sdk::signature::Signer;
+
+const MAX_TRANSFER_LIMIT: u64 = 100_000_000; // $100 per transaction
+const PERIOD_TRANSFER_LIMIT: u64 = 2_000_000_000; // $2000 per day
+const DEBIT_AMOUNT: u64 = 50_000_000; // $50 debit amount
+
+type TestContext = crate::common::Context;
+
+fn find_merchant_mint_event(logs: &[String]) -> Option<MerchantMintAddedOrUpdated> {
+    parse_events(logs)
+        .into_iter()
+        .find_map(|event| match event {
+            BridgeCardsEvent::MerchantMintAddedOrUpdated(event) => Some(event),
+            _ => None,
+        })
+}
+
+fn enforce_mint_allowlist(ctx: &mut TestContext) {
+    setup_merchant_config(
+        ctx,
+        TEST_MERCHANT_ID,
+        MerchantConfigParams {
+            enforce_mint_allowlist: true,
+            ..Default::default()
+        },
+    );
+}
+
+#[allow(clippy::result_large_err)]
+fn update_user_delegate(
+    ctx: &mut TestContext,
+    debit_context: &DebitUserContext,
+    merchant_mint: Option<Pubkey>,
+) -> TransactionResult {
+    ctx.svm.expire_blockhash();
+    let accounts = AddOrUpdateUserDelegate {
+        manager: ctx.merchant_manager_kp.pubkey(),
+        manager_state: ctx.merchant_manager_state.pubkey,
+        merchant_config: make_merchant_config_pda(TEST_MERCHANT_ID, &ctx.program_id).pubkey,
+        payer: ctx.payer_pk,
+        user_token_account: debit_context.user_token_account,
+        mint: debit_context.mint_pk,
+        user_delegate_account: debit_context.user_delegate_pda,
+        system_program: anchor_lang::system_program::ID,
+        merchant_mint,
+        merchant_registry: None,
+    };
+    let ix = create_add_or_update_user_delegate_instruction(
+        ctx,
+        &accounts,
+        TEST_MERCHANT_ID,
+        MAX_TRANSFER_LIMIT,
+        PERIOD_TRANSFER_LIMIT,
+        LIMIT_PERIOD,
+    );
+    let tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &ctx.merchant_manager_kp],
+    );
+    submit_transaction(ctx, tx)
+}
+
+#[allow(clippy::result_large_err)]
+fn debit_user_with_merchant_mint(
+    ctx: &mut TestContext,
+    debit_context: &DebitUserContext,
+    merchant_mint: Option<Pubkey>,
+) -> TransactionResult {
+    ctx.svm.expire_blockhash();
+    let accounts = DebitUser {
+        merchant_mint,
+        ..make_debit_user_accounts(ctx, debit_context)
+    };
+    let ix = create_debit_user_instruction_with_program(
+        ctx,
+        &accounts,
+        TEST_MERCHANT_ID,
+        DEBIT_AMOUNT,
+        debit_context.token_program,
+    );
+    let tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &debit_context.debitor_kp],
+    );
+    submit_transaction(ctx, tx)
+}
+
+fn assert_mint_not_allowed(result: TransactionResult) {
+    let err = result.expect_err("Transaction should fail");
+    let expected_message = ErrorCode::MintNotAllowedForMerchant.to_string();
+    assert!(
+        err.meta
+            .logs
+            .iter()
+            .any(|log| log.contains(&expected_message)),
+        "Error should contain the expected error message {}, got {}",
+        expected_message,
+        err.meta.logs.join("\n")
+    );
+}
+
+#[tokio::test]
+async fn test_add_or_update_merchant_mint() {
+    let mut ctx = setup_and_initialize();
+    let mint_pk = setup_mint(&mut ctx);
+    let merchant_mint_pda = make_merchant_mint_pda(TEST_MERCHANT_ID, &mint_pk, &ctx.program_id);
+
+    for (previous_state, new_state) in [(false, true), (true, false)] {
+        ctx.svm.expire_blockhash();
+        let accounts = bridge_cards::accounts::AddOrUpdateMerchantMint {
+            admin: ctx.payer_pk,
+            payer: ctx.payer_pk,
+            state: ctx.bridge_cards_state.pubkey,
+            mint_state: merchant_mint_pda.pubkey,
+            mint: mint_pk,
+            system_program: anchor_lang::system_program::ID,
+        };
+        let ix = create_add_or_update_merchant_mint_instruction(
+            &ctx,
+            &accounts,
+            TEST_MERCHANT_ID,
+            new_state,
+        );
+        let tx = create_transaction(&ctx, &[ix]);
+        let meta = submit_transaction(&mut ctx, tx).expect("Merchant mint update failed");
+
+        let event =
+            find_merchant_mint_event(&meta.logs).expect("MerchantMintAddedOrUpdated not found");
+        assert_eq!(event.merchant_id, TEST_MERCHANT_ID);
+        assert_eq!(event.mint, mint_pk);
+        assert_eq!(event.state_pda, merchant_mint_pda.pubkey);
+        assert_eq!(event.previous_state, previous_state);
+        assert_eq!(event.new_state, new_state);
+
+        let expected_data = MerchantMintState {
+            version: CURRENT_VERSION,
+            allowed: new_state,
+            bump: merchant_mint_pda.bump,
+        }
+        .account_data();
+        let account = ctx.svm.get_account(&merchant_mint_pda.pubkey).unwrap();
+        assert_eq!(account.owner, ctx.program_id);
+        assert_eq!(account.data, expected_data);
+    }
+}
+
+#[tokio::test]
+async fn test_mint_allowlist_enforced_for_user_delegate() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    enforce_mint_allowlist(&mut ctx);
+    let merchant_mint_pda =
+        make_merchant_mint_pda(TEST_MERCHANT_ID, &debit_context.mint_pk, &ctx.program_id).pubkey;
+
+    // Without an allowlist entry the delegate can't be updated
+    assert_mint_not_allowed(update_user_delegate(&mut ctx, &debit_context, None));
+    assert_mint_not_allowed(update_user_delegate(
+        &mut ctx,
+        &debit_context,
+        Some(merchant_mint_pda),
+    ));
+
+    // A disallowed entry doesn't help either
+    setup_merchant_mint(&mut ctx, TEST_MERCHANT_ID, &debit_context.mint_pk, false);
+    assert_mint_not_allowed(update_user_delegate(
+        &mut ctx,
+        &debit_context,
+        Some(merchant_mint_pda),
+    ));
+
+    setup_merchant_mint(&mut ctx, TEST_MERCHANT_ID, &debit_context.mint_pk, true);
+    let result = update_user_delegate(&mut ctx, &debit_context, Some(merchant_mint_pda));
+    assert!(result.is_ok(), "Update failed: {:?}", result.err());
+
+    // The merchant mint account is still required once allowed
+    assert_mint_not_allowed(update_user_delegate(&mut ctx, &debit_context, None));
+}
+
+#[tokio::test]
+async fn test_mint_allowlist_enforced_for_debits() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    enforce_mint_allowlist(&mut ctx);
+    let merchant_mint_pda =
+        make_merchant_mint_pda(TEST_MERCHANT_ID, &debit_context.mint_pk, &ctx.program_id).pubkey;
+
+    assert_mint_not_allowed(debit_user_with_merchant_mint(
+        &mut ctx,
+        &debit_context,
+        None,
+    ));
+
+    setup_merchant_mint(&mut ctx, TEST_MERCHANT_ID, &debit_context.mint_pk, true);
+    let result = debit_user_with_merchant_mint(&mut ctx, &debit_context, Some(merchant_mint_pda));
+    assert!(result.is_ok(), "Debit failed: {:?}", result.err());
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.user_token_account,
+        INITIAL_BALANCE - DEBIT_AMOUNT,
+        debit_context.token_program,
+        "User should be debited",
+    );
+
+    // Disallowing the mint stops further debits
+    setup_merchant_mint(&mut ctx, TEST_MERCHANT_ID, &debit_context.mint_pk, false);
+    assert_mint_not_allowed(debit_user_with_merchant_mint(
+        &mut ctx,
+        &debit_context,
+        Some(merchant_mint_pda),
+    ));
+}
+
+#[tokio::test]
+async fn test_mint_allowlist_ignored_when_not_enforced() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    let merchant_mint_pda =
+        setup_merchant_mint(&mut ctx, TEST_MERCHANT_ID, &debit_context.mint_pk, false);
+
+    let result = update_user_delegate(&mut ctx, &debit_context, Some(merchant_mint_pda));
+    assert!(result.is_ok(), "Update failed: {:?}", result.err());
+    let result = debit_user_with_merchant_mint(&mut ctx, &debit_context, None);
+    assert!(result.is_ok(), "Debit failed: {:?}", result.err());
+}
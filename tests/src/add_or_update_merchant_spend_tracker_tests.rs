@@ -0,0 +1,189 @@
+use crate::common::*;
+use account_data_trait::AccountData;
+use anchor_lang::prelude::*;
+use bridge_cards::accounts::DebitUser;
+use bridge_cards::errors::ErrorCode;
+use bridge_cards::state::{MerchantSpendTrackerState, CURRENT_VERSION};
+use solana_program_test::tokio;
+use solana_sdk::signature::Keypair;
+
+const MAX_TRANSFER_LIMIT: u64 = 100_000_000; // $100 per transaction
+const PERIOD_TRANSFER_LIMIT: u64 = 2_000_000_000; // $2000 per day
+const DEBIT_AMOUNT: u64 = 50_000_000; // $50 debit amount
+const MERCHANT_PERIOD_LIMIT: u64 = 120_000_000; // $120 per day across all delegates
+
+type TestContext = crate::common::Context;
+
+fn get_merchant_spend_tracker(ctx: &TestContext) -> MerchantSpendTrackerState {
+    let account = ctx
+        .svm
+        .get_account(&make_merchant_spend_tracker_pda(TEST_MERCHANT_ID, &ctx.program_id).pubkey)
+        .unwrap();
+    MerchantSpendTrackerState::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+#[tokio::test]
+async fn test_add_merchant_spend_tracker() {
+    let mut ctx = setup_and_initialize();
+    let admin = ctx.payer_kp.insecure_clone();
+
+    let result = add_or_update_merchant_spend_tracker(&mut ctx, &admin, MERCHANT_PERIOD_LIMIT);
+    assert!(
+        result.is_ok(),
+        "Failed to add merchant spend tracker: {:?}",
+        result.err()
+    );
+
+    let tracker_pda = make_merchant_spend_tracker_pda(TEST_MERCHANT_ID, &ctx.program_id);
+    let expected_tracker_data = MerchantSpendTrackerState {
+        version: CURRENT_VERSION,
+        period_transfer_limit: MERCHANT_PERIOD_LIMIT,
+        period_transferred_amount: 0,
+        period_timestamp_last_reset: 0,
+        transfer_limit_period_seconds: LIMIT_PERIOD,
+        bump: tracker_pda.bump,
+    }
+    .account_data();
+    let tracker_account = ctx.svm.get_account(&tracker_pda.pubkey).unwrap();
+    assert_eq!(tracker_account.data, expected_tracker_data);
+}
+
+#[tokio::test]
+async fn test_non_admin_cannot_add_merchant_spend_tracker() {
+    let mut ctx = setup_and_initialize();
+
+    let non_admin = Keypair::new();
+    let result = add_or_update_merchant_spend_tracker(&mut ctx, &non_admin, MERCHANT_PERIOD_LIMIT);
+    assert!(
+        result.is_err(),
+        "Non-admin should not be able to add a merchant spend tracker"
+    );
+}
+
+#[tokio::test]
+async fn test_merchant_spend_tracker_caps_all_delegates() {
+    let mut ctx = setup_and_initialize();
+    let first_delegate =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    let second_delegate =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    let admin = ctx.payer_kp.insecure_clone();
+    add_or_update_merchant_spend_tracker(&mut ctx, &admin, MERCHANT_PERIOD_LIMIT).unwrap();
+
+    // Both delegates count against the same merchant total
+    let result = debit_user(&mut ctx, &first_delegate, DEBIT_AMOUNT);
+    assert!(result.is_ok(), "First debit failed: {:?}", result.err());
+    let result = debit_user(&mut ctx, &second_delegate, DEBIT_AMOUNT);
+    assert!(result.is_ok(), "Second debit failed: {:?}", result.err());
+    assert_eq!(
+        get_merchant_spend_tracker(&ctx).period_transferred_amount,
+        2 * DEBIT_AMOUNT
+    );
+
+    // Each delegate is well within its own limits, but the merchant total is not
+    advance_slots(&mut ctx, 1);
+    let err = debit_user(&mut ctx, &first_delegate, DEBIT_AMOUNT)
+        .expect_err("Debit above the merchant limit should fail");
+    let expected_message = ErrorCode::ExceedsMerchantPeriodLimit.to_string();
+    assert!(
+        err.meta
+            .logs
+            .iter()
+            .any(|log| log.contains(&expected_message)),
+        "Error should contain the expected error message {}, got {}",
+        expected_message,
+        err.meta.logs.join("\n")
+    );
+    assert_eq!(
+        get_merchant_spend_tracker(&ctx).period_transferred_amount,
+        2 * DEBIT_AMOUNT
+    );
+
+    // What remains of the merchant limit can still be debited
+    let remaining = MERCHANT_PERIOD_LIMIT - 2 * DEBIT_AMOUNT;
+    let result = debit_user(&mut ctx, &second_delegate, remaining);
+    assert!(result.is_ok(), "Remaining debit failed: {:?}", result.err());
+
+    // A new merchant period frees up the limit again
+    let mut clock = ctx.svm.get_sysvar::<Clock>();
+    clock.unix_timestamp += LIMIT_PERIOD as i64 + 1;
+    clock.slot += 1;
+    ctx.svm.set_sysvar(&clock);
+    let result = debit_user(&mut ctx, &first_delegate, DEBIT_AMOUNT);
+    assert!(
+        result.is_ok(),
+        "Debit in a new merchant period failed: {:?}",
+        result.err()
+    );
+    assert_eq!(
+        get_merchant_spend_tracker(&ctx).period_transferred_amount,
+        DEBIT_AMOUNT
+    );
+}
+
+#[tokio::test]
+async fn test_debit_requires_merchant_spend_tracker() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    let admin = ctx.payer_kp.insecure_clone();
+    add_or_update_merchant_spend_tracker(&mut ctx, &admin, DEBIT_AMOUNT - 1).unwrap();
+
+    // Leaving the tracker out, as an optional account would be, doesn't dodge the limit
+    ctx.svm.expire_blockhash();
+    let debit_accounts = DebitUser {
+        merchant_spend_tracker: ctx.program_id,
+        ..make_debit_user_accounts(&ctx, &debit_context)
+    };
+    let ix = create_debit_user_instruction(&ctx, &debit_accounts, TEST_MERCHANT_ID, DEBIT_AMOUNT);
+    let tx = create_transaction_with_payer_and_signers(
+        &ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &debit_context.debitor_kp],
+    );
+    let err = submit_transaction(&mut ctx, tx).expect_err("Debit without the tracker should fail");
+    let expected_message = anchor_lang::error::ErrorCode::ConstraintSeeds.to_string();
+    assert!(
+        err.meta
+            .logs
+            .iter()
+            .any(|log| log.contains(&expected_message)),
+        "Error should contain the expected error message {}, got {}",
+        expected_message,
+        err.meta.logs.join("\n")
+    );
+
+    // With the tracker, the debit is held to the merchant limit
+    let err = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT)
+        .expect_err("Debit above the merchant limit should fail");
+    let expected_message = ErrorCode::ExceedsMerchantPeriodLimit.to_string();
+    assert!(
+        err.meta
+            .logs
+            .iter()
+            .any(|log| log.contains(&expected_message)),
+        "Error should contain the expected error message {}, got {}",
+        expected_message,
+        err.meta.logs.join("\n")
+    );
+    assert_eq!(
+        get_merchant_spend_tracker(&ctx).period_transferred_amount,
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_debit_without_merchant_spend_tracker_created() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+
+    // No merchant-wide limit applies until the tracker is created
+    let result = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT);
+    assert!(result.is_ok(), "Debit failed: {:?}", result.err());
+    assert!(ctx
+        .svm
+        .get_account(&make_merchant_spend_tracker_pda(TEST_MERCHANT_ID, &ctx.program_id).pubkey)
+        .is_none_or(|account| account.data.is_empty()));
+}
@@ -0,0 +1,150 @@
+use crate::common::*;
+use account_data_trait::AccountData;
+use anchor_lang::prelude::*;
+use bridge_cards::{
+    accounts::AddOrUpdateRouter,
+    event_parsing::{parse_events, BridgeCardsEvent},
+    events::RouterAddedOrUpdated,
+    state::{RouterState, CURRENT_VERSION},
+};
+use solana_program_test::tokio;
+
+fn find_router_event(logs: &[String]) -> Option<RouterAddedOrUpdated> {
+    parse_events(logs)
+        .into_iter()
+        .find_map(|event| match event {
+            BridgeCardsEvent::RouterAddedOrUpdated(event) => Some(event),
+            _ => None,
+        })
+}
+
+#[tokio::test]
+async fn test_add_router() {
+    let mut ctx = setup_and_initialize();
+    let router_pda = make_router_pda(&MOCK_ROUTER_PROGRAM_ID, &ctx.program_id);
+
+    let accounts = AddOrUpdateRouter {
+        admin: ctx.payer_pk,
+        payer: ctx.payer_pk,
+        state: ctx.bridge_cards_state.pubkey,
+        router_state: router_pda.pubkey,
+        router_program: MOCK_ROUTER_PROGRAM_ID,
+        system_program: System::id(),
+    };
+    let ix = create_add_or_update_router_instruction(&ctx, &accounts, true);
+    let tx = create_transaction(&ctx, &[ix]);
+
+    let result = submit_transaction(&mut ctx, tx);
+    assert!(result.is_ok(), "Failed to add router: {:?}", result.err());
+
+    // Verify the RouterAddedOrUpdated event
+    let meta = result.unwrap();
+    let event = find_router_event(&meta.logs).expect("RouterAddedOrUpdated event not found");
+    assert_eq!(event.router_program, MOCK_ROUTER_PROGRAM_ID);
+    assert_eq!(event.state_pda, router_pda.pubkey);
+    assert!(!event.previous_state);
+    assert!(event.new_state);
+
+    // Verify the router state
+    let expected_router_data = RouterState {
+        version: CURRENT_VERSION,
+        allowed: true,
+        bump: router_pda.bump,
+    }
+    .account_data();
+    let router_account = ctx.svm.get_account(&router_pda.pubkey).unwrap();
+    assert_eq!(
+        router_account.data, expected_router_data,
+        "Router account data doesn't match expected data"
+    );
+}
+
+#[tokio::test]
+async fn test_revoke_router() {
+    let mut ctx = setup_and_initialize();
+    let router_pda = setup_router(&mut ctx, &MOCK_ROUTER_PROGRAM_ID, true);
+
+    let accounts = AddOrUpdateRouter {
+        admin: ctx.payer_pk,
+        payer: ctx.payer_pk,
+        state: ctx.bridge_cards_state.pubkey,
+        router_state: router_pda,
+        router_program: MOCK_ROUTER_PROGRAM_ID,
+        system_program: System::id(),
+    };
+    let ix = create_add_or_update_router_instruction(&ctx, &accounts, false);
+    let tx = create_transaction(&ctx, &[ix]);
+
+    let result = submit_transaction(&mut ctx, tx);
+    assert!(
+        result.is_ok(),
+        "Failed to revoke router: {:?}",
+        result.err()
+    );
+
+    let meta = result.unwrap();
+    let event = find_router_event(&meta.logs).expect("RouterAddedOrUpdated event not found");
+    assert!(event.previous_state);
+    assert!(!event.new_state);
+
+    let router_account = ctx.svm.get_account(&router_pda).unwrap();
+    let router_state = RouterState::try_deserialize(&mut router_account.data.as_slice()).unwrap();
+    assert!(!router_state.allowed, "Router should be revoked");
+}
+
+#[tokio::test]
+async fn test_non_admin_cannot_add_router() {
+    let mut ctx = setup_and_initialize();
+    let (non_admin_kp, non_admin_pk) = setup_keypair(&mut ctx);
+    let router_pda = make_router_pda(&MOCK_ROUTER_PROGRAM_ID, &ctx.program_id);
+
+    let accounts = AddOrUpdateRouter {
+        admin: non_admin_pk,
+        payer: non_admin_pk,
+        state: ctx.bridge_cards_state.pubkey,
+        router_state: router_pda.pubkey,
+        router_program: MOCK_ROUTER_PROGRAM_ID,
+        system_program: System::id(),
+    };
+    let ix = create_add_or_update_router_instruction(&ctx, &accounts, true);
+    let tx = create_transaction_with_payer_and_signers(
+        &ctx,
+        &[ix],
+        Some(&non_admin_pk),
+        &[&non_admin_kp],
+    );
+
+    let result = submit_transaction(&mut ctx, tx);
+    assert!(
+        result.is_err(),
+        "Non-admin should not be able to add router"
+    );
+    assert!(
+        ctx.svm.get_account(&router_pda.pubkey).is_none(),
+        "Router state should not be created"
+    );
+}
+
+#[tokio::test]
+async fn test_cannot_add_non_executable_router() {
+    let mut ctx = setup_and_initialize();
+    let (_, not_a_program) = setup_keypair(&mut ctx);
+    let router_pda = make_router_pda(&not_a_program, &ctx.program_id);
+
+    let accounts = AddOrUpdateRouter {
+        admin: ctx.payer_pk,
+        payer: ctx.payer_pk,
+        state: ctx.bridge_cards_state.pubkey,
+        router_state: router_pda.pubkey,
+        router_program: not_a_program,
+        system_program: System::id(),
+    };
+    let ix = create_add_or_update_router_instruction(&ctx, &accounts, true);
+    let tx = create_transaction(&ctx, &[ix]);
+
+    let result = submit_transaction(&mut ctx, tx);
+    assert!(
+        result.is_err(),
+        "Non-executable account should not be allowed as a router"
+    );
+}
@@ -1,8 +1,14 @@
 use crate::common::*;
 use account_data_trait::AccountData;
-use anchor_lang::{prelude::*, Event};
-use base64;
-use bridge_cards::{events::UserDelegateAddedOrUpdated, state::UserDelegateState};
+use anchor_lang::prelude::*;
+use bridge_cards::{
+    errors::ErrorCode,
+    event_parsing::{parse_events, BridgeCardsEvent},
+    events::{UserDelegateAddedOrUpdated, UserDelegateSnapshot},
+    instructions::add_or_update_merchant_config::MerchantConfigParams,
+    instructions::add_or_update_user_delegate::UserDelegateOptions,
+    state::{UserDelegateState, CURRENT_VERSION},
+};
 use litesvm_token::CreateAssociatedTokenAccountIdempotent;
 use solana_program_test::tokio;
 use solana_sdk::signature::Signer;
@@ -41,11 +47,14 @@ async fn test_create_user_delegate() {
     let accounts = bridge_cards::accounts::AddOrUpdateUserDelegate {
         manager: ctx.merchant_manager_kp.pubkey(),
         manager_state: ctx.merchant_manager_state.pubkey,
+        merchant_config: make_merchant_config_pda(TEST_MERCHANT_ID, &ctx.program_id).pubkey,
         payer: ctx.payer_pk,
         user_token_account,
         mint: mint_pk,
         user_delegate_account: user_delegate_pda.pubkey,
         system_program: System::id(),
+        merchant_mint: None,
+        merchant_registry: None,
     };
 
     let ix = create_add_or_update_user_delegate_instruction(
@@ -79,23 +88,37 @@ async fn test_create_user_delegate() {
         mint: mint_pk,
         user_ata: user_token_account,
         user_delegate: user_delegate_pda.pubkey,
+        metadata_hash: [0; 32],
+        previous_per_transfer_limit: 0,
+        new_per_transfer_limit: DEFAULT_MAX_TRANSFER_LIMIT,
+        previous_period_transfer_limit: 0,
+        new_period_transfer_limit: DEFAULT_PERIOD_TRANSFER_LIMIT,
     };
-    for log in meta.logs.iter() {
-        if let Some(data_str) = log.strip_prefix("Program data: ") {
-            if let Ok(log_bytes) = base64::decode(data_str) {
-                if log_bytes.len() > 8 {
-                    let event_data = &log_bytes[8..];
-                    if let Ok(parsed_event) = UserDelegateAddedOrUpdated::try_from_slice(event_data)
-                    {
-                        assert_eq!(parsed_event.merchant_id, expected_event.merchant_id);
-                        assert_eq!(parsed_event.mint, expected_event.mint);
-                        assert_eq!(parsed_event.user_ata, expected_event.user_ata);
-                        assert_eq!(parsed_event.user_delegate, expected_event.user_delegate);
-                        event_found = true;
-                        break;
-                    }
-                }
-            }
+    for event in parse_events(&meta.logs) {
+        if let BridgeCardsEvent::UserDelegateAddedOrUpdated(parsed_event) = event {
+            assert_eq!(parsed_event.merchant_id, expected_event.merchant_id);
+            assert_eq!(parsed_event.mint, expected_event.mint);
+            assert_eq!(parsed_event.user_ata, expected_event.user_ata);
+            assert_eq!(parsed_event.user_delegate, expected_event.user_delegate);
+            assert_eq!(parsed_event.metadata_hash, expected_event.metadata_hash);
+            assert_eq!(
+                parsed_event.previous_per_transfer_limit,
+                expected_event.previous_per_transfer_limit
+            );
+            assert_eq!(
+                parsed_event.new_per_transfer_limit,
+                expected_event.new_per_transfer_limit
+            );
+            assert_eq!(
+                parsed_event.previous_period_transfer_limit,
+                expected_event.previous_period_transfer_limit
+            );
+            assert_eq!(
+                parsed_event.new_period_transfer_limit,
+                expected_event.new_period_transfer_limit
+            );
+            event_found = true;
+            break;
         }
     }
     assert!(
@@ -110,6 +133,7 @@ async fn test_create_user_delegate() {
     // Verify the PDA was created with the expected settings
     let data = user_delegate_account.data.clone();
     let user_delegate_state = UserDelegateState {
+        version: CURRENT_VERSION,
         per_transfer_limit: 100_000_000,      // $100 per transaction
         period_transfer_limit: 2_000_000_000, // $2000 per day
         period_transferred_amount: 0,
@@ -117,6 +141,23 @@ async fn test_create_user_delegate() {
         slot_last_transferred: 0,
         transfer_limit_period_seconds: anchor_lang::solana_program::clock::SECONDS_PER_DAY as u32,
         bump: user_delegate_pda.bump,
+        min_slots_between_debits: 0,
+        allow_alternate_destination: false,
+        metadata_hash: [0; 32],
+        valid_from: 0,
+        expires_at: 0,
+        reissue_count: 0,
+        min_transfer_amount: 0,
+        total_transferred: 0,
+        debit_nonce: 0,
+        limit_mode: 0,
+        strict_boundary: false,
+        anchor_to_epoch: false,
+        last_period_index: 0,
+        restricted_destination: Pubkey::default(),
+        // No cooldown, active from creation
+        active_after: ctx.svm.get_sysvar::<Clock>().unix_timestamp,
+        timestamp_last_transferred: 0,
     };
     let expected_data = user_delegate_state.account_data();
 
@@ -155,11 +196,14 @@ async fn test_non_manager_cannot_create_user_delegate() {
     let accounts = bridge_cards::accounts::AddOrUpdateUserDelegate {
         manager: non_manager_pk,
         manager_state: ctx.merchant_manager_state.pubkey,
+        merchant_config: make_merchant_config_pda(TEST_MERCHANT_ID, &ctx.program_id).pubkey,
         payer: ctx.payer_pk,
         user_token_account,
         mint: mint_pk,
         user_delegate_account: user_delegate_pda.pubkey,
         system_program: System::id(),
+        merchant_mint: None,
+        merchant_registry: None,
     };
 
     let ix = create_add_or_update_user_delegate_instruction(
@@ -224,11 +268,14 @@ async fn test_update_user_delegate() {
     let accounts = bridge_cards::accounts::AddOrUpdateUserDelegate {
         manager: ctx.merchant_manager_kp.pubkey(),
         manager_state: ctx.merchant_manager_state.pubkey,
+        merchant_config: make_merchant_config_pda(TEST_MERCHANT_ID, &ctx.program_id).pubkey,
         payer: ctx.payer_pk,
         user_token_account,
         mint: mint_pk,
         user_delegate_account: user_delegate_pda.pubkey,
         system_program: System::id(),
+        merchant_mint: None,
+        merchant_registry: None,
     };
 
     // Create initial user delegate account
@@ -255,20 +302,9 @@ async fn test_update_user_delegate() {
 
     // Verify the event for the initial creation (optional)
     let meta1 = result.unwrap();
-    let mut event1_found = false;
-    for log in meta1.logs.iter() {
-        if let Some(data_str) = log.strip_prefix("Program data: ") {
-            if let Ok(log_bytes) = base64::decode(data_str) {
-                if log_bytes.len() > 8 {
-                    let event_data = &log_bytes[8..];
-                    if UserDelegateAddedOrUpdated::try_from_slice(event_data).is_ok() {
-                        event1_found = true;
-                        break;
-                    }
-                }
-            }
-        }
-    }
+    let event1_found = parse_events(&meta1.logs)
+        .iter()
+        .any(|event| matches!(event, BridgeCardsEvent::UserDelegateAddedOrUpdated(_)));
     assert!(
         event1_found,
         "UserDelegateAddedOrUpdated event not found for initial creation: {}",
@@ -279,6 +315,7 @@ async fn test_update_user_delegate() {
     let initial_user_delegate_account = ctx.svm.get_account(&user_delegate_pda.pubkey).unwrap();
     let initial_data = initial_user_delegate_account.data.clone();
     let initial_state = UserDelegateState {
+        version: CURRENT_VERSION,
         per_transfer_limit: 100_000_000,      // $100 per transaction
         period_transfer_limit: 2_000_000_000, // $2000 per day
         period_transferred_amount: 0,
@@ -286,6 +323,23 @@ async fn test_update_user_delegate() {
         slot_last_transferred: 0,
         transfer_limit_period_seconds: anchor_lang::solana_program::clock::SECONDS_PER_DAY as u32,
         bump: user_delegate_pda.bump,
+        min_slots_between_debits: 0,
+        allow_alternate_destination: false,
+        metadata_hash: [0; 32],
+        valid_from: 0,
+        expires_at: 0,
+        reissue_count: 0,
+        min_transfer_amount: 0,
+        total_transferred: 0,
+        debit_nonce: 0,
+        limit_mode: 0,
+        strict_boundary: false,
+        anchor_to_epoch: false,
+        last_period_index: 0,
+        restricted_destination: Pubkey::default(),
+        // No cooldown, active from creation
+        active_after: ctx.svm.get_sysvar::<Clock>().unix_timestamp,
+        timestamp_last_transferred: 0,
     };
     let expected_initial_data = initial_state.account_data();
     assert_eq!(
@@ -297,11 +351,14 @@ async fn test_update_user_delegate() {
     let update_accounts = bridge_cards::accounts::AddOrUpdateUserDelegate {
         manager: ctx.merchant_manager_kp.pubkey(),
         manager_state: ctx.merchant_manager_state.pubkey,
+        merchant_config: make_merchant_config_pda(TEST_MERCHANT_ID, &ctx.program_id).pubkey,
         payer: ctx.payer_pk,
         user_token_account,
         mint: mint_pk,
         user_delegate_account: user_delegate_pda.pubkey,
         system_program: System::id(),
+        merchant_mint: None,
+        merchant_registry: None,
     };
 
     let update_ix = create_add_or_update_user_delegate_instruction(
@@ -330,19 +387,28 @@ async fn test_update_user_delegate() {
     // Verify the UserDelegateAddedOrUpdated event for the update
     let meta2 = update_result.unwrap();
     let mut event2_found = false;
-    for log in meta2.logs.iter() {
-        if let Some(data_str) = log.strip_prefix("Program data: ") {
-            if let Ok(log_bytes) = base64::decode(data_str) {
-                if log_bytes.len() > 8 {
-                    let event_data = &log_bytes[8..];
-                    if let Ok(parsed_event) = UserDelegateAddedOrUpdated::try_from_slice(event_data)
-                    {
-                        assert_eq!(parsed_event.user_delegate, user_delegate_pda.pubkey);
-                        event2_found = true;
-                        break;
-                    }
-                }
-            }
+    for event in parse_events(&meta2.logs) {
+        if let BridgeCardsEvent::UserDelegateAddedOrUpdated(parsed_event) = event {
+            assert_eq!(parsed_event.user_delegate, user_delegate_pda.pubkey);
+            // The event carries the limits before and after the update
+            assert_eq!(
+                parsed_event.previous_per_transfer_limit,
+                DEFAULT_MAX_TRANSFER_LIMIT
+            );
+            assert_eq!(
+                parsed_event.new_per_transfer_limit,
+                DEFAULT_MAX_TRANSFER_LIMIT * 2
+            );
+            assert_eq!(
+                parsed_event.previous_period_transfer_limit,
+                DEFAULT_PERIOD_TRANSFER_LIMIT
+            );
+            assert_eq!(
+                parsed_event.new_period_transfer_limit,
+                DEFAULT_PERIOD_TRANSFER_LIMIT
+            );
+            event2_found = true;
+            break;
         }
     }
     assert!(
@@ -357,6 +423,7 @@ async fn test_update_user_delegate() {
 
     // The account should be reinitialized with the values from the handler
     let expected_updated_state = UserDelegateState {
+        version: CURRENT_VERSION,
         per_transfer_limit: 200_000_000,      // $200 per transaction
         period_transfer_limit: 2_000_000_000, // $2000 per day
         period_transferred_amount: 0,         // Reset to 0
@@ -364,6 +431,23 @@ async fn test_update_user_delegate() {
         slot_last_transferred: 0,
         transfer_limit_period_seconds: anchor_lang::solana_program::clock::SECONDS_PER_DAY as u32,
         bump: user_delegate_pda.bump,
+        min_slots_between_debits: 0,
+        allow_alternate_destination: false,
+        metadata_hash: [0; 32],
+        valid_from: 0,
+        expires_at: 0,
+        reissue_count: 0,
+        min_transfer_amount: 0,
+        total_transferred: 0,
+        debit_nonce: 0,
+        limit_mode: 0,
+        strict_boundary: false,
+        anchor_to_epoch: false,
+        last_period_index: 0,
+        restricted_destination: Pubkey::default(),
+        // No cooldown, active from creation
+        active_after: ctx.svm.get_sysvar::<Clock>().unix_timestamp,
+        timestamp_last_transferred: 0,
     };
     let expected_updated_data = expected_updated_state.account_data();
 
@@ -372,3 +456,623 @@ async fn test_update_user_delegate() {
         "Updated user delegate state data mismatch"
     );
 }
+
+fn find_user_delegate_snapshot(logs: &[String]) -> Option<UserDelegateSnapshot> {
+    parse_events(logs)
+        .into_iter()
+        .find_map(|event| match event {
+            BridgeCardsEvent::UserDelegateSnapshot(event) => Some(event),
+            _ => None,
+        })
+}
+
+#[tokio::test]
+async fn test_user_delegate_snapshot_emitted_when_enabled() {
+    let mut ctx = setup_and_initialize();
+    setup_merchant_config(
+        &mut ctx,
+        TEST_MERCHANT_ID,
+        MerchantConfigParams {
+            emit_full_snapshot: true,
+            ..Default::default()
+        },
+    );
+
+    let mint_pk = setup_mint(&mut ctx);
+    let (_, user_pk) = setup_keypair(&mut ctx);
+    let user_token_account =
+        CreateAssociatedTokenAccountIdempotent::new(&mut ctx.svm, &ctx.payer_kp, &mint_pk)
+            .owner(&user_pk)
+            .send()
+            .unwrap();
+    let user_delegate_pda = make_user_delegate_pda(
+        TEST_MERCHANT_ID,
+        &mint_pk,
+        &user_token_account,
+        &ctx.program_id,
+    );
+
+    let accounts = bridge_cards::accounts::AddOrUpdateUserDelegate {
+        manager: ctx.merchant_manager_kp.pubkey(),
+        manager_state: ctx.merchant_manager_state.pubkey,
+        merchant_config: make_merchant_config_pda(TEST_MERCHANT_ID, &ctx.program_id).pubkey,
+        payer: ctx.payer_pk,
+        user_token_account,
+        mint: mint_pk,
+        user_delegate_account: user_delegate_pda.pubkey,
+        system_program: System::id(),
+        merchant_mint: None,
+        merchant_registry: None,
+    };
+
+    // Create the delegate and verify the snapshot
+    let ix = create_add_or_update_user_delegate_instruction(
+        &ctx,
+        &accounts,
+        TEST_MERCHANT_ID,
+        DEFAULT_MAX_TRANSFER_LIMIT,
+        DEFAULT_PERIOD_TRANSFER_LIMIT,
+        LIMIT_PERIOD,
+    );
+    let tx = create_transaction_with_payer_and_signers(
+        &ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &ctx.merchant_manager_kp],
+    );
+    let result = submit_transaction(&mut ctx, tx);
+    assert!(
+        result.is_ok(),
+        "Failed to create user delegate: {:?}",
+        result.err()
+    );
+
+    let snapshot = find_user_delegate_snapshot(&result.unwrap().logs)
+        .expect("UserDelegateSnapshot event not found for creation");
+    assert_eq!(snapshot.merchant_id, TEST_MERCHANT_ID);
+    assert_eq!(snapshot.mint, mint_pk);
+    assert_eq!(snapshot.user_ata, user_token_account);
+    assert_eq!(snapshot.user_delegate, user_delegate_pda.pubkey);
+    assert_eq!(snapshot.per_transfer_limit, DEFAULT_MAX_TRANSFER_LIMIT);
+    assert_eq!(
+        snapshot.period_transfer_limit,
+        DEFAULT_PERIOD_TRANSFER_LIMIT
+    );
+    assert_eq!(snapshot.period_transferred_amount, 0);
+    assert_eq!(snapshot.period_timestamp_last_reset, 0);
+    assert_eq!(snapshot.transfer_limit_period_seconds, LIMIT_PERIOD);
+    assert_eq!(snapshot.slot_last_transferred, 0);
+    assert_eq!(snapshot.total_transferred, 0);
+    assert_eq!(snapshot.debit_nonce, 0);
+    assert_eq!(snapshot.last_period_index, 0);
+    assert_eq!(snapshot.timestamp_last_transferred, 0);
+    assert_eq!(
+        snapshot.active_after,
+        ctx.svm.get_sysvar::<Clock>().unix_timestamp
+    );
+
+    // Update the limits and options and verify the snapshot reflects them
+    let now = ctx.svm.get_sysvar::<Clock>().unix_timestamp;
+    let restricted_destination = Pubkey::new_unique();
+    let options = UserDelegateOptions {
+        min_transfer_amount: 1_000_000,
+        valid_from: now + 60,
+        expires_at: now + 3600,
+        limit_mode: 1,
+        strict_boundary: true,
+        anchor_to_epoch: true,
+        restricted_destination: Some(Some(restricted_destination)),
+        ..Default::default()
+    };
+    let ix = create_add_or_update_user_delegate_instruction_with_options(
+        &ctx,
+        &accounts,
+        TEST_MERCHANT_ID,
+        DEFAULT_MAX_TRANSFER_LIMIT * 2,
+        DEFAULT_PERIOD_TRANSFER_LIMIT * 2,
+        LIMIT_PERIOD * 7,
+        options,
+    );
+    let tx = create_transaction_with_payer_and_signers(
+        &ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &ctx.merchant_manager_kp],
+    );
+    let result = submit_transaction(&mut ctx, tx);
+    assert!(
+        result.is_ok(),
+        "Failed to update user delegate: {:?}",
+        result.err()
+    );
+
+    let snapshot = find_user_delegate_snapshot(&result.unwrap().logs)
+        .expect("UserDelegateSnapshot event not found for update");
+    assert_eq!(snapshot.per_transfer_limit, DEFAULT_MAX_TRANSFER_LIMIT * 2);
+    assert_eq!(
+        snapshot.period_transfer_limit,
+        DEFAULT_PERIOD_TRANSFER_LIMIT * 2
+    );
+    assert_eq!(snapshot.transfer_limit_period_seconds, LIMIT_PERIOD * 7);
+    assert_eq!(snapshot.period_transferred_amount, 0);
+    assert_eq!(snapshot.min_transfer_amount, 1_000_000);
+    assert_eq!(snapshot.valid_from, now + 60);
+    assert_eq!(snapshot.expires_at, now + 3600);
+    assert_eq!(snapshot.limit_mode, 1);
+    assert!(snapshot.strict_boundary);
+    assert!(snapshot.anchor_to_epoch);
+    assert_eq!(snapshot.restricted_destination, restricted_destination);
+
+    // Every field matches the stored delegate
+    let account = ctx.svm.get_account(&user_delegate_pda.pubkey).unwrap();
+    let state = UserDelegateState::try_deserialize(&mut account.data.as_slice()).unwrap();
+    assert_eq!(snapshot.total_transferred, state.total_transferred);
+    assert_eq!(snapshot.debit_nonce, state.debit_nonce);
+    assert_eq!(snapshot.last_period_index, state.last_period_index);
+    assert_eq!(snapshot.active_after, state.active_after);
+    assert_eq!(
+        snapshot.timestamp_last_transferred,
+        state.timestamp_last_transferred
+    );
+}
+
+#[tokio::test]
+async fn test_user_delegate_snapshot_not_emitted_by_default() {
+    let mut ctx = setup_and_initialize();
+
+    let mint_pk = setup_mint(&mut ctx);
+    let (_, user_pk) = setup_keypair(&mut ctx);
+    let user_token_account =
+        CreateAssociatedTokenAccountIdempotent::new(&mut ctx.svm, &ctx.payer_kp, &mint_pk)
+            .owner(&user_pk)
+            .send()
+            .unwrap();
+    let user_delegate_pda = make_user_delegate_pda(
+        TEST_MERCHANT_ID,
+        &mint_pk,
+        &user_token_account,
+        &ctx.program_id,
+    );
+
+    let accounts = bridge_cards::accounts::AddOrUpdateUserDelegate {
+        manager: ctx.merchant_manager_kp.pubkey(),
+        manager_state: ctx.merchant_manager_state.pubkey,
+        merchant_config: make_merchant_config_pda(TEST_MERCHANT_ID, &ctx.program_id).pubkey,
+        payer: ctx.payer_pk,
+        user_token_account,
+        mint: mint_pk,
+        user_delegate_account: user_delegate_pda.pubkey,
+        system_program: System::id(),
+        merchant_mint: None,
+        merchant_registry: None,
+    };
+    let ix = create_add_or_update_user_delegate_instruction(
+        &ctx,
+        &accounts,
+        TEST_MERCHANT_ID,
+        DEFAULT_MAX_TRANSFER_LIMIT,
+        DEFAULT_PERIOD_TRANSFER_LIMIT,
+        LIMIT_PERIOD,
+    );
+    let tx = create_transaction_with_payer_and_signers(
+        &ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &ctx.merchant_manager_kp],
+    );
+    let result = submit_transaction(&mut ctx, tx);
+    assert!(
+        result.is_ok(),
+        "Failed to create user delegate: {:?}",
+        result.err()
+    );
+
+    assert!(
+        find_user_delegate_snapshot(&result.unwrap().logs).is_none(),
+        "UserDelegateSnapshot should only be emitted when enabled in the merchant config"
+    );
+}
+
+fn find_user_delegate_event(logs: &[String]) -> Option<UserDelegateAddedOrUpdated> {
+    parse_events(logs)
+        .into_iter()
+        .find_map(|event| match event {
+            BridgeCardsEvent::UserDelegateAddedOrUpdated(event) => Some(event),
+            _ => None,
+        })
+}
+
+#[tokio::test]
+async fn test_user_delegate_metadata_hash() {
+    let mut ctx = setup_and_initialize();
+
+    let mint_pk = setup_mint(&mut ctx);
+    let (_, user_pk) = setup_keypair(&mut ctx);
+    let user_token_account =
+        CreateAssociatedTokenAccountIdempotent::new(&mut ctx.svm, &ctx.payer_kp, &mint_pk)
+            .owner(&user_pk)
+            .send()
+            .unwrap();
+    let user_delegate_pda = make_user_delegate_pda(
+        TEST_MERCHANT_ID,
+        &mint_pk,
+        &user_token_account,
+        &ctx.program_id,
+    );
+
+    let accounts = bridge_cards::accounts::AddOrUpdateUserDelegate {
+        manager: ctx.merchant_manager_kp.pubkey(),
+        manager_state: ctx.merchant_manager_state.pubkey,
+        merchant_config: make_merchant_config_pda(TEST_MERCHANT_ID, &ctx.program_id).pubkey,
+        payer: ctx.payer_pk,
+        user_token_account,
+        mint: mint_pk,
+        user_delegate_account: user_delegate_pda.pubkey,
+        system_program: System::id(),
+        merchant_mint: None,
+        merchant_registry: None,
+    };
+
+    // Hash of an off-chain card identifier
+    let metadata_hash = anchor_lang::solana_program::hash::hash(b"card-1234").to_bytes();
+    let ix = create_add_or_update_user_delegate_instruction_with_options(
+        &ctx,
+        &accounts,
+        TEST_MERCHANT_ID,
+        DEFAULT_MAX_TRANSFER_LIMIT,
+        DEFAULT_PERIOD_TRANSFER_LIMIT,
+        LIMIT_PERIOD,
+        UserDelegateOptions {
+            metadata_hash,
+            ..Default::default()
+        },
+    );
+    let tx = create_transaction_with_payer_and_signers(
+        &ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &ctx.merchant_manager_kp],
+    );
+    let result = submit_transaction(&mut ctx, tx);
+    assert!(
+        result.is_ok(),
+        "Failed to create user delegate: {:?}",
+        result.err()
+    );
+
+    // Verify the hash is emitted and stored
+    let event = find_user_delegate_event(&result.unwrap().logs)
+        .expect("UserDelegateAddedOrUpdated event not found");
+    assert_eq!(event.metadata_hash, metadata_hash);
+
+    let user_delegate_account = ctx.svm.get_account(&user_delegate_pda.pubkey).unwrap();
+    let user_delegate_state =
+        UserDelegateState::try_deserialize(&mut user_delegate_account.data.as_slice()).unwrap();
+    assert_eq!(user_delegate_state.metadata_hash, metadata_hash);
+}
+
+const DEFAULT_DELEGATE_EXPIRY_SECONDS: u32 = 365 * 24 * 60 * 60;
+
+fn setup_delegate_with_default_expiry(
+    ctx: &mut crate::common::Context,
+    options: UserDelegateOptions,
+) -> UserDelegateState {
+    setup_merchant_config(
+        ctx,
+        TEST_MERCHANT_ID,
+        MerchantConfigParams {
+            default_delegate_expiry_seconds: DEFAULT_DELEGATE_EXPIRY_SECONDS,
+            ..Default::default()
+        },
+    );
+    let debit_context = setup_merchant_and_user_delegate_with_options(
+        ctx,
+        DEFAULT_MAX_TRANSFER_LIMIT,
+        DEFAULT_PERIOD_TRANSFER_LIMIT,
+        TokenProgram::Token,
+        options,
+    );
+    let account = ctx
+        .svm
+        .get_account(&debit_context.user_delegate_pda)
+        .unwrap();
+    UserDelegateState::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+#[tokio::test]
+async fn test_user_delegate_default_expiry_applied() {
+    let mut ctx = setup_and_initialize();
+    let now = ctx.svm.get_sysvar::<Clock>().unix_timestamp;
+
+    let user_delegate_state = setup_delegate_with_default_expiry(
+        &mut ctx,
+        UserDelegateOptions {
+            use_default_expiry: true,
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(
+        user_delegate_state.expires_at,
+        now + DEFAULT_DELEGATE_EXPIRY_SECONDS as i64
+    );
+}
+
+#[tokio::test]
+async fn test_user_delegate_explicit_expiry_overrides_default() {
+    let mut ctx = setup_and_initialize();
+    let now = ctx.svm.get_sysvar::<Clock>().unix_timestamp;
+    let expires_at = now + 3600;
+
+    let user_delegate_state = setup_delegate_with_default_expiry(
+        &mut ctx,
+        UserDelegateOptions {
+            expires_at,
+            use_default_expiry: true,
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(user_delegate_state.expires_at, expires_at);
+}
+
+#[tokio::test]
+async fn test_user_delegate_default_expiry_not_requested() {
+    let mut ctx = setup_and_initialize();
+
+    let user_delegate_state =
+        setup_delegate_with_default_expiry(&mut ctx, UserDelegateOptions::default());
+
+    assert_eq!(user_delegate_state.expires_at, 0);
+}
+
+#[tokio::test]
+async fn test_user_delegate_sliding_window_mode() {
+    let mut ctx = setup_and_initialize();
+
+    let user_delegate_state = setup_delegate_with_default_expiry(
+        &mut ctx,
+        UserDelegateOptions {
+            limit_mode: 1,
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(user_delegate_state.limit_mode, 1);
+}
+
+#[tokio::test]
+async fn test_user_delegate_strict_boundary() {
+    let mut ctx = setup_and_initialize();
+
+    let user_delegate_state = setup_delegate_with_default_expiry(
+        &mut ctx,
+        UserDelegateOptions {
+            strict_boundary: true,
+            ..Default::default()
+        },
+    );
+
+    assert!(user_delegate_state.strict_boundary);
+}
+
+#[tokio::test]
+async fn test_user_delegate_anchor_to_epoch() {
+    let mut ctx = setup_and_initialize();
+
+    let user_delegate_state = setup_delegate_with_default_expiry(
+        &mut ctx,
+        UserDelegateOptions {
+            anchor_to_epoch: true,
+            ..Default::default()
+        },
+    );
+
+    assert!(user_delegate_state.anchor_to_epoch);
+    assert_eq!(user_delegate_state.last_period_index, 0);
+}
+
+/// Create a user delegate with the given limits, returning the transaction result
+#[allow(clippy::result_large_err)]
+fn add_user_delegate(
+    ctx: &mut crate::common::Context,
+    max_transfer_limit: u64,
+    period_transfer_limit: u64,
+    transfer_limit_period: u32,
+    options: UserDelegateOptions,
+) -> litesvm::types::TransactionResult {
+    let mint_pk = setup_mint(ctx);
+    let (_, user_pk) = setup_keypair(ctx);
+    let user_token_account =
+        CreateAssociatedTokenAccountIdempotent::new(&mut ctx.svm, &ctx.payer_kp, &mint_pk)
+            .owner(&user_pk)
+            .send()
+            .unwrap();
+    let user_delegate_pda = make_user_delegate_pda(
+        TEST_MERCHANT_ID,
+        &mint_pk,
+        &user_token_account,
+        &ctx.program_id,
+    );
+
+    let accounts = bridge_cards::accounts::AddOrUpdateUserDelegate {
+        manager: ctx.merchant_manager_kp.pubkey(),
+        manager_state: ctx.merchant_manager_state.pubkey,
+        merchant_config: make_merchant_config_pda(TEST_MERCHANT_ID, &ctx.program_id).pubkey,
+        payer: ctx.payer_pk,
+        user_token_account,
+        mint: mint_pk,
+        user_delegate_account: user_delegate_pda.pubkey,
+        system_program: System::id(),
+        merchant_mint: None,
+        merchant_registry: None,
+    };
+    let ix = create_add_or_update_user_delegate_instruction_with_options(
+        ctx,
+        &accounts,
+        TEST_MERCHANT_ID,
+        max_transfer_limit,
+        period_transfer_limit,
+        transfer_limit_period,
+        options,
+    );
+    let tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &ctx.merchant_manager_kp],
+    );
+    submit_transaction(ctx, tx)
+}
+
+fn assert_user_delegate_error(result: litesvm::types::TransactionResult, error: ErrorCode) {
+    let err = result.expect_err("Adding the user delegate should fail");
+    let expected_message = error.to_string();
+    assert!(
+        err.meta
+            .logs
+            .iter()
+            .any(|log| log.contains(&expected_message)),
+        "Error should contain the expected error message {}, got {}",
+        expected_message,
+        err.meta.logs.join("\n")
+    );
+}
+
+#[tokio::test]
+async fn test_user_delegate_invalid_limit_mode() {
+    let mut ctx = setup_and_initialize();
+
+    let result = add_user_delegate(
+        &mut ctx,
+        DEFAULT_MAX_TRANSFER_LIMIT,
+        DEFAULT_PERIOD_TRANSFER_LIMIT,
+        LIMIT_PERIOD,
+        UserDelegateOptions {
+            limit_mode: 2,
+            ..Default::default()
+        },
+    );
+    assert_user_delegate_error(result, ErrorCode::InvalidLimitMode);
+}
+
+#[tokio::test]
+async fn test_user_delegate_zero_period_rejected() {
+    let mut ctx = setup_and_initialize();
+
+    let result = add_user_delegate(
+        &mut ctx,
+        DEFAULT_MAX_TRANSFER_LIMIT,
+        DEFAULT_PERIOD_TRANSFER_LIMIT,
+        0,
+        UserDelegateOptions::default(),
+    );
+    assert_user_delegate_error(result, ErrorCode::InvalidPeriod);
+}
+
+#[tokio::test]
+async fn test_user_delegate_period_limit_below_per_transfer_limit_rejected() {
+    let mut ctx = setup_and_initialize();
+
+    let result = add_user_delegate(
+        &mut ctx,
+        DEFAULT_MAX_TRANSFER_LIMIT,
+        DEFAULT_MAX_TRANSFER_LIMIT - 1,
+        LIMIT_PERIOD,
+        UserDelegateOptions::default(),
+    );
+    assert_user_delegate_error(result, ErrorCode::InvalidTransferLimits);
+}
+
+#[tokio::test]
+async fn test_user_delegate_consistent_limits_accepted() {
+    let mut ctx = setup_and_initialize();
+
+    // A period limit equal to the per-transfer limit and a one second period are valid
+    let result = add_user_delegate(
+        &mut ctx,
+        DEFAULT_MAX_TRANSFER_LIMIT,
+        DEFAULT_MAX_TRANSFER_LIMIT,
+        1,
+        UserDelegateOptions::default(),
+    );
+    assert!(
+        result.is_ok(),
+        "Failed to create user delegate: {:?}",
+        result.err()
+    );
+}
+
+fn setup_limit_caps(ctx: &mut crate::common::Context) {
+    setup_merchant_config(
+        ctx,
+        TEST_MERCHANT_ID,
+        MerchantConfigParams {
+            max_allowed_per_transfer: DEFAULT_MAX_TRANSFER_LIMIT,
+            max_allowed_period_limit: DEFAULT_PERIOD_TRANSFER_LIMIT,
+            ..Default::default()
+        },
+    );
+}
+
+#[tokio::test]
+async fn test_user_delegate_limits_above_merchant_cap_rejected() {
+    let mut ctx = setup_and_initialize();
+    setup_limit_caps(&mut ctx);
+
+    let result = add_user_delegate(
+        &mut ctx,
+        DEFAULT_MAX_TRANSFER_LIMIT + 1,
+        DEFAULT_PERIOD_TRANSFER_LIMIT,
+        LIMIT_PERIOD,
+        UserDelegateOptions::default(),
+    );
+    assert_user_delegate_error(result, ErrorCode::LimitExceedsMerchantCap);
+
+    let result = add_user_delegate(
+        &mut ctx,
+        DEFAULT_MAX_TRANSFER_LIMIT,
+        DEFAULT_PERIOD_TRANSFER_LIMIT + 1,
+        LIMIT_PERIOD,
+        UserDelegateOptions::default(),
+    );
+    assert_user_delegate_error(result, ErrorCode::LimitExceedsMerchantCap);
+}
+
+#[tokio::test]
+async fn test_user_delegate_limits_at_merchant_cap_accepted() {
+    let mut ctx = setup_and_initialize();
+    setup_limit_caps(&mut ctx);
+
+    let result = add_user_delegate(
+        &mut ctx,
+        DEFAULT_MAX_TRANSFER_LIMIT,
+        DEFAULT_PERIOD_TRANSFER_LIMIT,
+        LIMIT_PERIOD,
+        UserDelegateOptions::default(),
+    );
+    assert!(
+        result.is_ok(),
+        "Failed to create user delegate: {:?}",
+        result.err()
+    );
+}
+
+#[tokio::test]
+async fn test_user_delegate_limits_uncapped_without_merchant_config() {
+    let mut ctx = setup_and_initialize();
+
+    // Without a merchant config managers can set any consistent limits
+    let result = add_user_delegate(
+        &mut ctx,
+        u64::MAX,
+        u64::MAX,
+        LIMIT_PERIOD,
+        UserDelegateOptions::default(),
+    );
+    assert!(
+        result.is_ok(),
+        "Failed to create user delegate: {:?}",
+        result.err()
+    );
+}
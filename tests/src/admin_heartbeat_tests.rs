@@ -0,0 +1,79 @@
+use crate::common::*;
+use anchor_lang::prelude::*;
+use bridge_cards::accounts::AdminHeartbeat;
+use bridge_cards::event_parsing::{parse_events, BridgeCardsEvent};
+use bridge_cards::events::Heartbeat;
+use bridge_cards::state::{feature_flags, BridgeCardsState};
+use solana_program_test::tokio;
+
+fn find_heartbeat_event(logs: &[String]) -> Option<Heartbeat> {
+    parse_events(logs)
+        .into_iter()
+        .find_map(|event| match event {
+            BridgeCardsEvent::Heartbeat(event) => Some(event),
+            _ => None,
+        })
+}
+
+#[tokio::test]
+async fn test_admin_heartbeat() {
+    let mut ctx = setup_and_initialize();
+    set_feature_flags(&mut ctx, feature_flags::SWAPS_DISABLED);
+    let (_, fee_owner) = setup_keypair(&mut ctx);
+    set_protocol_fee(&mut ctx, 250, fee_owner);
+    let state_before = ctx.svm.get_account(&ctx.bridge_cards_state.pubkey).unwrap();
+
+    let accounts = AdminHeartbeat {
+        admin: ctx.payer_pk,
+        state: ctx.bridge_cards_state.pubkey,
+    };
+    let ix = create_admin_heartbeat_instruction(&ctx, &accounts);
+    let tx = create_transaction(&ctx, &[ix]);
+
+    let result = submit_transaction(&mut ctx, tx);
+    assert!(
+        result.is_ok(),
+        "Failed to emit heartbeat: {:?}",
+        result.err()
+    );
+
+    // Verify the Heartbeat event
+    let event = find_heartbeat_event(&result.unwrap().logs).expect("Heartbeat event not found");
+    assert_eq!(event.admin, ctx.payer_pk);
+    assert_eq!(
+        event.timestamp,
+        ctx.svm.get_sysvar::<Clock>().unix_timestamp
+    );
+    assert_eq!(event.feature_flags, feature_flags::SWAPS_DISABLED);
+    assert_eq!(event.fee_bps, 250);
+
+    // Verify the state is unchanged
+    let state_after = ctx.svm.get_account(&ctx.bridge_cards_state.pubkey).unwrap();
+    assert_eq!(state_after.data, state_before.data);
+    let state = BridgeCardsState::try_deserialize(&mut state_after.data.as_slice()).unwrap();
+    assert_eq!(state.feature_flags, feature_flags::SWAPS_DISABLED);
+}
+
+#[tokio::test]
+async fn test_non_admin_cannot_emit_heartbeat() {
+    let mut ctx = setup_and_initialize();
+    let (non_admin_kp, non_admin_pk) = setup_keypair(&mut ctx);
+
+    let accounts = AdminHeartbeat {
+        admin: non_admin_pk,
+        state: ctx.bridge_cards_state.pubkey,
+    };
+    let ix = create_admin_heartbeat_instruction(&ctx, &accounts);
+    let tx = create_transaction_with_payer_and_signers(
+        &ctx,
+        &[ix],
+        Some(&non_admin_pk),
+        &[&non_admin_kp],
+    );
+
+    let result = submit_transaction(&mut ctx, tx);
+    assert!(
+        result.is_err(),
+        "Non-admin should not be able to emit a heartbeat"
+    );
+}
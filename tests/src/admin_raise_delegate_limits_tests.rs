@@ -0,0 +1,424 @@
+use crate::common::*;
+use anchor_lang::prelude::*;
+use bridge_cards::accounts::{AddOrUpdateUserDelegate, AdminRaiseDelegateLimits};
+use bridge_cards::errors::ErrorCode;
+use bridge_cards::event_parsing::{parse_events, BridgeCardsEvent};
+use bridge_cards::events::UserDelegateLimitsRaised;
+use bridge_cards::instructions::add_or_update_merchant_config::MerchantConfigParams;
+use bridge_cards::instructions::add_or_update_user_delegate::UserDelegateOptions;
+use bridge_cards::state::UserDelegateState;
+use solana_program_test::tokio;
+use solana_sdk::signature::Signer;
+
+const MAX_TRANSFER_LIMIT: u64 = 100_000_000; // $100 per transaction
+const PERIOD_TRANSFER_LIMIT: u64 = 2_000_000_000; // $2000 per day
+
+type TestContext = crate::common::Context;
+
+fn find_limits_raised_event(logs: &[String]) -> Option<UserDelegateLimitsRaised> {
+    parse_events(logs)
+        .into_iter()
+        .find_map(|event| match event {
+            BridgeCardsEvent::UserDelegateLimitsRaised(event) => Some(event),
+            _ => None,
+        })
+}
+
+fn get_user_delegate_state(
+    ctx: &TestContext,
+    debit_context: &DebitUserContext,
+) -> UserDelegateState {
+    let account = ctx
+        .svm
+        .get_account(&debit_context.user_delegate_pda)
+        .unwrap();
+    UserDelegateState::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+fn setup_lower_only_merchant(ctx: &mut TestContext) -> DebitUserContext {
+    let debit_context =
+        setup_merchant_and_user_delegate(ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    setup_merchant_config(
+        ctx,
+        TEST_MERCHANT_ID,
+        MerchantConfigParams {
+            managers_can_only_lower_limits: true,
+            ..Default::default()
+        },
+    );
+    debit_context
+}
+
+fn make_admin_raise_accounts(
+    ctx: &TestContext,
+    debit_context: &DebitUserContext,
+    admin: Pubkey,
+) -> AdminRaiseDelegateLimits {
+    AdminRaiseDelegateLimits {
+        manager: ctx.merchant_manager_kp.pubkey(),
+        admin,
+        manager_state: ctx.merchant_manager_state.pubkey,
+        state: ctx.bridge_cards_state.pubkey,
+        user_token_account: debit_context.user_token_account,
+        mint: debit_context.mint_pk,
+        user_delegate_account: debit_context.user_delegate_pda,
+    }
+}
+
+#[allow(clippy::result_large_err)]
+fn update_user_delegate_as_manager(
+    ctx: &mut TestContext,
+    debit_context: &DebitUserContext,
+    max_transfer_limit: u64,
+    period_transfer_limit: u64,
+    transfer_limit_period: u32,
+    options: UserDelegateOptions,
+) -> litesvm::types::TransactionResult {
+    ctx.svm.expire_blockhash();
+    let accounts = AddOrUpdateUserDelegate {
+        manager: ctx.merchant_manager_kp.pubkey(),
+        manager_state: ctx.merchant_manager_state.pubkey,
+        merchant_config: make_merchant_config_pda(TEST_MERCHANT_ID, &ctx.program_id).pubkey,
+        payer: ctx.payer_pk,
+        user_token_account: debit_context.user_token_account,
+        mint: debit_context.mint_pk,
+        user_delegate_account: debit_context.user_delegate_pda,
+        system_program: System::id(),
+        merchant_mint: None,
+        merchant_registry: None,
+    };
+    let ix = create_add_or_update_user_delegate_instruction_with_options(
+        ctx,
+        &accounts,
+        TEST_MERCHANT_ID,
+        max_transfer_limit,
+        period_transfer_limit,
+        transfer_limit_period,
+        options,
+    );
+    let tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &ctx.merchant_manager_kp],
+    );
+    submit_transaction(ctx, tx)
+}
+
+#[allow(clippy::result_large_err)]
+fn admin_raise_delegate_limits(
+    ctx: &mut TestContext,
+    debit_context: &DebitUserContext,
+    per_transfer_limit: u64,
+    period_transfer_limit: u64,
+) -> litesvm::types::TransactionResult {
+    // The payer is the program admin
+    let accounts = make_admin_raise_accounts(ctx, debit_context, ctx.payer_pk);
+    let ix = create_admin_raise_delegate_limits_instruction(
+        ctx,
+        &accounts,
+        TEST_MERCHANT_ID,
+        per_transfer_limit,
+        period_transfer_limit,
+    );
+    let tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &ctx.merchant_manager_kp],
+    );
+    submit_transaction(ctx, tx)
+}
+
+fn assert_raise_error(result: litesvm::types::TransactionResult, error: ErrorCode) {
+    let err = result.expect_err("Raise should fail");
+    let expected_message = error.to_string();
+    assert!(
+        err.meta
+            .logs
+            .iter()
+            .any(|log| log.contains(&expected_message)),
+        "Error should contain the expected error message {}, got {}",
+        expected_message,
+        err.meta.logs.join("\n")
+    );
+}
+
+#[tokio::test]
+async fn test_admin_raise_delegate_limits() {
+    let mut ctx = setup_and_initialize();
+    let debit_context = setup_lower_only_merchant(&mut ctx);
+
+    // The payer is the program admin
+    let accounts = make_admin_raise_accounts(&ctx, &debit_context, ctx.payer_pk);
+    let ix = create_admin_raise_delegate_limits_instruction(
+        &ctx,
+        &accounts,
+        TEST_MERCHANT_ID,
+        MAX_TRANSFER_LIMIT * 2,
+        PERIOD_TRANSFER_LIMIT * 2,
+    );
+    let tx = create_transaction_with_payer_and_signers(
+        &ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &ctx.merchant_manager_kp],
+    );
+
+    let result = submit_transaction(&mut ctx, tx);
+    assert!(
+        result.is_ok(),
+        "Failed to raise delegate limits: {:?}",
+        result.err()
+    );
+
+    // Verify the event records both signers and the change
+    let event = find_limits_raised_event(&result.unwrap().logs)
+        .expect("UserDelegateLimitsRaised event not found");
+    assert_eq!(event.merchant_id, TEST_MERCHANT_ID);
+    assert_eq!(event.user_delegate, debit_context.user_delegate_pda);
+    assert_eq!(event.manager, ctx.merchant_manager_kp.pubkey());
+    assert_eq!(event.admin, ctx.payer_pk);
+    assert_eq!(event.previous_per_transfer_limit, MAX_TRANSFER_LIMIT);
+    assert_eq!(event.per_transfer_limit, MAX_TRANSFER_LIMIT * 2);
+    assert_eq!(event.previous_period_transfer_limit, PERIOD_TRANSFER_LIMIT);
+    assert_eq!(event.period_transfer_limit, PERIOD_TRANSFER_LIMIT * 2);
+
+    let state = get_user_delegate_state(&ctx, &debit_context);
+    assert_eq!(state.per_transfer_limit, MAX_TRANSFER_LIMIT * 2);
+    assert_eq!(state.period_transfer_limit, PERIOD_TRANSFER_LIMIT * 2);
+}
+
+#[tokio::test]
+async fn test_admin_raise_delegate_limits_requires_admin() {
+    let mut ctx = setup_and_initialize();
+    let debit_context = setup_lower_only_merchant(&mut ctx);
+
+    // Only the manager signs, posing as the admin
+    let accounts =
+        make_admin_raise_accounts(&ctx, &debit_context, ctx.merchant_manager_kp.pubkey());
+    let ix = create_admin_raise_delegate_limits_instruction(
+        &ctx,
+        &accounts,
+        TEST_MERCHANT_ID,
+        MAX_TRANSFER_LIMIT * 2,
+        PERIOD_TRANSFER_LIMIT * 2,
+    );
+    let tx = create_transaction_with_payer_and_signers(
+        &ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &ctx.merchant_manager_kp],
+    );
+
+    let result = submit_transaction(&mut ctx, tx);
+    assert!(
+        result.is_err(),
+        "Raise without the admin signature should fail"
+    );
+
+    let state = get_user_delegate_state(&ctx, &debit_context);
+    assert_eq!(state.per_transfer_limit, MAX_TRANSFER_LIMIT);
+    assert_eq!(state.period_transfer_limit, PERIOD_TRANSFER_LIMIT);
+}
+
+#[tokio::test]
+async fn test_manager_cannot_raise_limits_when_lower_only() {
+    let mut ctx = setup_and_initialize();
+    let debit_context = setup_lower_only_merchant(&mut ctx);
+
+    let result = update_user_delegate_as_manager(
+        &mut ctx,
+        &debit_context,
+        MAX_TRANSFER_LIMIT * 2,
+        PERIOD_TRANSFER_LIMIT,
+        LIMIT_PERIOD,
+        UserDelegateOptions::default(),
+    );
+    let err = result.expect_err("Manager-only raise should fail");
+    let expected_message = ErrorCode::LimitRaiseRequiresAdmin.to_string();
+    assert!(
+        err.meta
+            .logs
+            .iter()
+            .any(|log| log.contains(&expected_message)),
+        "Error should contain the expected error message {}, got {}",
+        expected_message,
+        err.meta.logs.join("\n")
+    );
+
+    // Lowering is still allowed
+    let result = update_user_delegate_as_manager(
+        &mut ctx,
+        &debit_context,
+        MAX_TRANSFER_LIMIT / 2,
+        PERIOD_TRANSFER_LIMIT / 2,
+        LIMIT_PERIOD,
+        UserDelegateOptions::default(),
+    );
+    assert!(
+        result.is_ok(),
+        "Manager should be able to lower limits: {:?}",
+        result.err()
+    );
+    let state = get_user_delegate_state(&ctx, &debit_context);
+    assert_eq!(state.per_transfer_limit, MAX_TRANSFER_LIMIT / 2);
+}
+
+#[tokio::test]
+async fn test_admin_raise_delegate_limits_rejects_lowering() {
+    let mut ctx = setup_and_initialize();
+    let debit_context = setup_lower_only_merchant(&mut ctx);
+
+    // Raising one limit doesn't allow lowering the other
+    let result = admin_raise_delegate_limits(
+        &mut ctx,
+        &debit_context,
+        MAX_TRANSFER_LIMIT * 2,
+        PERIOD_TRANSFER_LIMIT - 1,
+    );
+    assert_raise_error(result, ErrorCode::LimitsNotRaised);
+
+    let state = get_user_delegate_state(&ctx, &debit_context);
+    assert_eq!(state.per_transfer_limit, MAX_TRANSFER_LIMIT);
+    assert_eq!(state.period_transfer_limit, PERIOD_TRANSFER_LIMIT);
+}
+
+#[tokio::test]
+async fn test_admin_raise_delegate_limits_rejects_inconsistent_limits() {
+    let mut ctx = setup_and_initialize();
+    let debit_context = setup_lower_only_merchant(&mut ctx);
+
+    // The per-transfer limit can't be raised above the period limit
+    let result = admin_raise_delegate_limits(
+        &mut ctx,
+        &debit_context,
+        PERIOD_TRANSFER_LIMIT + 1,
+        PERIOD_TRANSFER_LIMIT,
+    );
+    assert_raise_error(result, ErrorCode::InvalidTransferLimits);
+
+    let state = get_user_delegate_state(&ctx, &debit_context);
+    assert_eq!(state.per_transfer_limit, MAX_TRANSFER_LIMIT);
+}
+
+#[tokio::test]
+async fn test_manager_cannot_loosen_controls_when_lower_only() {
+    let mut ctx = setup_and_initialize();
+    let debit_context = setup_lower_only_merchant(&mut ctx);
+
+    // Tightening the delegate's controls is allowed
+    let expires_at = ctx.svm.get_sysvar::<Clock>().unix_timestamp + 86400;
+    let restricted = UserDelegateOptions {
+        expires_at,
+        restricted_destination: Some(Some(debit_context.destination_token_account)),
+        ..Default::default()
+    };
+    let result = update_user_delegate_as_manager(
+        &mut ctx,
+        &debit_context,
+        MAX_TRANSFER_LIMIT,
+        PERIOD_TRANSFER_LIMIT,
+        LIMIT_PERIOD,
+        restricted.clone(),
+    );
+    assert!(
+        result.is_ok(),
+        "Manager should be able to tighten controls: {:?}",
+        result.err()
+    );
+
+    // Each loosening of the controls needs the admin
+    let loosened = [
+        (LIMIT_PERIOD - 1, restricted.clone()),
+        (
+            LIMIT_PERIOD,
+            UserDelegateOptions {
+                expires_at: 0,
+                ..restricted.clone()
+            },
+        ),
+        (
+            LIMIT_PERIOD,
+            UserDelegateOptions {
+                expires_at: expires_at + 1,
+                ..restricted.clone()
+            },
+        ),
+        (
+            LIMIT_PERIOD,
+            UserDelegateOptions {
+                restricted_destination: Some(None),
+                ..restricted.clone()
+            },
+        ),
+        (
+            LIMIT_PERIOD,
+            UserDelegateOptions {
+                restricted_destination: Some(Some(Pubkey::new_unique())),
+                ..restricted.clone()
+            },
+        ),
+        (
+            LIMIT_PERIOD,
+            UserDelegateOptions {
+                limit_mode: 1,
+                ..restricted.clone()
+            },
+        ),
+        (
+            LIMIT_PERIOD,
+            UserDelegateOptions {
+                anchor_to_epoch: true,
+                ..restricted.clone()
+            },
+        ),
+    ];
+    for (transfer_limit_period, options) in loosened {
+        let result = update_user_delegate_as_manager(
+            &mut ctx,
+            &debit_context,
+            MAX_TRANSFER_LIMIT,
+            PERIOD_TRANSFER_LIMIT,
+            transfer_limit_period,
+            options.clone(),
+        );
+        let err = result.expect_err(&format!("Loosening {options:?} should fail"));
+        let expected_message = ErrorCode::LimitRaiseRequiresAdmin.to_string();
+        assert!(
+            err.meta
+                .logs
+                .iter()
+                .any(|log| log.contains(&expected_message)),
+            "Error should contain the expected error message {}, got {}",
+            expected_message,
+            err.meta.logs.join("\n")
+        );
+    }
+
+    // Leaving the restriction out of an update keeps it, so it isn't a loosening
+    let result = update_user_delegate_as_manager(
+        &mut ctx,
+        &debit_context,
+        MAX_TRANSFER_LIMIT,
+        PERIOD_TRANSFER_LIMIT,
+        LIMIT_PERIOD,
+        UserDelegateOptions {
+            restricted_destination: None,
+            ..restricted.clone()
+        },
+    );
+    assert!(
+        result.is_ok(),
+        "Manager should be able to update without repeating the restriction: {:?}",
+        result.err()
+    );
+
+    let state = get_user_delegate_state(&ctx, &debit_context);
+    assert_eq!(state.transfer_limit_period_seconds, LIMIT_PERIOD);
+    assert_eq!(state.expires_at, expires_at);
+    assert_eq!(
+        state.restricted_destination,
+        debit_context.destination_token_account
+    );
+}
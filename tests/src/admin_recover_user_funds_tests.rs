@@ -0,0 +1,182 @@
+use crate::common::*;
+use anchor_lang::prelude::*;
+use bridge_cards::accounts::AdminRecoverUserFunds;
+use bridge_cards::event_parsing::{parse_events, BridgeCardsEvent};
+use bridge_cards::events::EmergencyRecovery;
+use litesvm::types::TransactionResult;
+use litesvm_token::CreateAssociatedTokenAccountIdempotent;
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer};
+
+const MAX_TRANSFER_LIMIT: u64 = 100_000_000; // $100 per transaction
+const PERIOD_TRANSFER_LIMIT: u64 = 2_000_000_000; // $2000 per day
+const PARTIAL_AMOUNT: u64 = 300_000_000; // $300 recovered first
+
+type TestContext = crate::common::Context;
+
+fn find_emergency_recovery_event(logs: &[String]) -> Option<EmergencyRecovery> {
+    parse_events(logs)
+        .into_iter()
+        .find_map(|event| match event {
+            BridgeCardsEvent::EmergencyRecovery(event) => Some(event),
+            _ => None,
+        })
+}
+
+fn setup_recovery_token_account(ctx: &mut TestContext, debit_context: &DebitUserContext) -> Pubkey {
+    let (_, recovery_owner) = setup_keypair(ctx);
+    CreateAssociatedTokenAccountIdempotent::new(&mut ctx.svm, &ctx.payer_kp, &debit_context.mint_pk)
+        .owner(&recovery_owner)
+        .token_program_id(&debit_context.token_program.program_id())
+        .send()
+        .unwrap()
+}
+
+fn make_recover_accounts(
+    ctx: &TestContext,
+    debit_context: &DebitUserContext,
+    admin: Pubkey,
+    recovery_token_account: Pubkey,
+) -> AdminRecoverUserFunds {
+    AdminRecoverUserFunds {
+        admin,
+        owner: debit_context.user_pk,
+        state: ctx.bridge_cards_state.pubkey,
+        user_token_account: debit_context.user_token_account,
+        recovery_token_account,
+        mint: debit_context.mint_pk,
+        token_program: debit_context.token_program.program_id(),
+    }
+}
+
+#[allow(clippy::result_large_err)]
+fn recover_user_funds(
+    ctx: &mut TestContext,
+    debit_context: &DebitUserContext,
+    recovery_token_account: Pubkey,
+    amount: u64,
+) -> TransactionResult {
+    ctx.svm.expire_blockhash();
+    let accounts = make_recover_accounts(ctx, debit_context, ctx.payer_pk, recovery_token_account);
+    let ix = create_admin_recover_user_funds_instruction(ctx, &accounts, amount);
+    let tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &debit_context.user_kp],
+    );
+    submit_transaction(ctx, tx)
+}
+
+#[tokio::test]
+async fn test_admin_recover_user_funds() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    let recovery_token_account = setup_recovery_token_account(&mut ctx, &debit_context);
+
+    // Part of the balance
+    let meta = recover_user_funds(
+        &mut ctx,
+        &debit_context,
+        recovery_token_account,
+        PARTIAL_AMOUNT,
+    )
+    .expect("Partial recovery failed");
+    let event =
+        find_emergency_recovery_event(&meta.logs).expect("EmergencyRecovery event not found");
+    assert_eq!(event.admin, ctx.payer_pk);
+    assert_eq!(event.owner, debit_context.user_pk);
+    assert_eq!(event.user_ata, debit_context.user_token_account);
+    assert_eq!(event.recovery_ata, recovery_token_account);
+    assert_eq!(event.mint, debit_context.mint_pk);
+    assert_eq!(event.amount, PARTIAL_AMOUNT);
+
+    // An amount above the balance moves whatever is left
+    let meta = recover_user_funds(&mut ctx, &debit_context, recovery_token_account, u64::MAX)
+        .expect("Full recovery failed");
+    let event =
+        find_emergency_recovery_event(&meta.logs).expect("EmergencyRecovery event not found");
+    assert_eq!(event.amount, INITIAL_BALANCE - PARTIAL_AMOUNT);
+
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.user_token_account,
+        0,
+        debit_context.token_program,
+        "User token account should be drained",
+    );
+    verify_token_account_balance(
+        &ctx,
+        &recovery_token_account,
+        INITIAL_BALANCE,
+        debit_context.token_program,
+        "Recovery token account should hold the whole balance",
+    );
+}
+
+#[tokio::test]
+async fn test_admin_recover_user_funds_requires_owner_signature() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    let recovery_token_account = setup_recovery_token_account(&mut ctx, &debit_context);
+
+    let accounts =
+        make_recover_accounts(&ctx, &debit_context, ctx.payer_pk, recovery_token_account);
+    let mut ix = create_admin_recover_user_funds_instruction(&ctx, &accounts, u64::MAX);
+    for meta in ix.accounts.iter_mut() {
+        if meta.pubkey == debit_context.user_pk {
+            meta.is_signer = false;
+        }
+    }
+    let tx = create_transaction_with_payer_and_signers(
+        &ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp],
+    );
+    let result = submit_transaction(&mut ctx, tx);
+    assert!(result.is_err(), "Recovery without the owner should fail");
+
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.user_token_account,
+        INITIAL_BALANCE,
+        debit_context.token_program,
+        "User balance should be unchanged",
+    );
+}
+
+#[tokio::test]
+async fn test_admin_recover_user_funds_requires_admin() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    let recovery_token_account = setup_recovery_token_account(&mut ctx, &debit_context);
+
+    let non_admin = Keypair::new();
+    let accounts = make_recover_accounts(
+        &ctx,
+        &debit_context,
+        non_admin.pubkey(),
+        recovery_token_account,
+    );
+    let ix = create_admin_recover_user_funds_instruction(&ctx, &accounts, u64::MAX);
+    let tx = create_transaction_with_payer_and_signers(
+        &ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &non_admin, &debit_context.user_kp],
+    );
+    let result = submit_transaction(&mut ctx, tx);
+    assert!(result.is_err(), "Recovery by a non-admin should fail");
+
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.user_token_account,
+        INITIAL_BALANCE,
+        debit_context.token_program,
+        "User balance should be unchanged",
+    );
+}
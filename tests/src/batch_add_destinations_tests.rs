@@ -0,0 +1,229 @@
+use crate::common::*;
+use account_data_trait::AccountData;
+use anchor_lang::prelude::*;
+use bridge_cards::accounts::{AddOrUpdateMerchantDestination, BatchAddDestinations};
+use bridge_cards::errors::ErrorCode;
+use bridge_cards::event_parsing::{parse_events, BridgeCardsEvent};
+use bridge_cards::events::MerchantDestinationAddedOrUpdated;
+use bridge_cards::state::{MerchantDestinationState, CURRENT_VERSION};
+use litesvm::types::TransactionResult;
+use litesvm_token::CreateAssociatedTokenAccountIdempotent;
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer};
+
+const MERCHANT_ID: u64 = 1;
+
+type TestContext = crate::common::Context;
+
+fn find_destination_events(logs: &[String]) -> Vec<MerchantDestinationAddedOrUpdated> {
+    parse_events(logs)
+        .into_iter()
+        .filter_map(|event| match event {
+            BridgeCardsEvent::MerchantDestinationAddedOrUpdated(event) => Some(event),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Create a token account for the mint, owned by a new wallet
+fn create_destination(ctx: &mut TestContext, mint_pk: &Pubkey) -> Pubkey {
+    CreateAssociatedTokenAccountIdempotent::new(&mut ctx.svm, &ctx.payer_kp, mint_pk)
+        .owner(&Keypair::new().pubkey())
+        .send()
+        .unwrap()
+}
+
+/// Pair each destination token account with its destination state PDA
+fn with_state_pdas(
+    ctx: &TestContext,
+    mint_pk: &Pubkey,
+    destinations: &[Pubkey],
+) -> Vec<(Pubkey, Pubkey)> {
+    destinations
+        .iter()
+        .map(|destination| {
+            let destination_state =
+                make_merchant_destination_pda(MERCHANT_ID, mint_pk, destination, &ctx.program_id);
+            (*destination, destination_state.pubkey)
+        })
+        .collect()
+}
+
+#[allow(clippy::result_large_err)]
+fn batch_add_destinations(
+    ctx: &mut TestContext,
+    mint_pk: &Pubkey,
+    destination_allowed: bool,
+    destinations: &[(Pubkey, Pubkey)],
+) -> TransactionResult {
+    ctx.svm.expire_blockhash();
+    let accounts = BatchAddDestinations {
+        admin: ctx.payer_pk,
+        payer: ctx.payer_pk,
+        state: ctx.bridge_cards_state.pubkey,
+        mint: *mint_pk,
+        system_program: System::id(),
+        merchant_registry: None,
+    };
+    let ix = create_batch_add_destinations_instruction(
+        ctx,
+        &accounts,
+        MERCHANT_ID,
+        destination_allowed,
+        destinations,
+    );
+    let tx = create_transaction(ctx, &[ix]);
+    submit_transaction(ctx, tx)
+}
+
+fn assert_error(result: TransactionResult, expected_message: String) {
+    let err = result.expect_err("Transaction should fail");
+    assert!(
+        err.meta
+            .logs
+            .iter()
+            .any(|log| log.contains(&expected_message)),
+        "Error should contain the expected error message {}, got {}",
+        expected_message,
+        err.meta.logs.join("\n")
+    );
+}
+
+#[tokio::test]
+async fn test_batch_add_destinations() {
+    let mut ctx = setup_and_initialize();
+    let mint_pk = setup_mint(&mut ctx);
+    let destinations: Vec<Pubkey> = (0..3)
+        .map(|_| create_destination(&mut ctx, &mint_pk))
+        .collect();
+    let destinations = with_state_pdas(&ctx, &mint_pk, &destinations);
+
+    let result = batch_add_destinations(&mut ctx, &mint_pk, true, &destinations);
+    assert!(result.is_ok(), "Batch add failed: {:?}", result.err());
+
+    // One event per destination, in order
+    let events = find_destination_events(&result.unwrap().logs);
+    assert_eq!(events.len(), destinations.len());
+    for (event, (destination, destination_state)) in events.iter().zip(&destinations) {
+        assert_eq!(event.merchant_id, MERCHANT_ID);
+        assert_eq!(event.mint, mint_pk);
+        assert_eq!(event.destination, *destination);
+        assert_eq!(event.state_pda, *destination_state);
+        assert!(!event.previous_state);
+        assert!(event.new_state);
+        assert!(!event.is_primary);
+    }
+
+    // Every state PDA matches what add_or_update_merchant_destination would create
+    for (destination, destination_state) in &destinations {
+        let bump =
+            make_merchant_destination_pda(MERCHANT_ID, &mint_pk, destination, &ctx.program_id).bump;
+        let expected_data = MerchantDestinationState {
+            version: CURRENT_VERSION,
+            allowed: true,
+            is_primary: false,
+            bump,
+            last_received_amount: 0,
+            last_received_timestamp: 0,
+        }
+        .account_data();
+        let account = ctx.svm.get_account(destination_state).unwrap();
+        assert_eq!(account.owner, ctx.program_id);
+        assert_eq!(account.data, expected_data);
+    }
+}
+
+#[tokio::test]
+async fn test_batch_add_destinations_updates_existing() {
+    let mut ctx = setup_and_initialize();
+    let mint_pk = setup_mint(&mut ctx);
+    let destinations: Vec<Pubkey> = (0..2)
+        .map(|_| create_destination(&mut ctx, &mint_pk))
+        .collect();
+    let destinations = with_state_pdas(&ctx, &mint_pk, &destinations);
+
+    // The first destination already exists as the primary destination
+    let (primary_destination, primary_state) = destinations[0];
+    let accounts = AddOrUpdateMerchantDestination {
+        admin: ctx.payer_pk,
+        payer: ctx.payer_pk,
+        state: ctx.bridge_cards_state.pubkey,
+        destination_state: primary_state,
+        destination_token_account: primary_destination,
+        mint: mint_pk,
+        system_program: System::id(),
+        merchant_registry: None,
+    };
+    let ix = create_add_or_update_merchant_destination_instruction_with_primary(
+        &ctx,
+        &accounts,
+        MERCHANT_ID,
+        true,
+        true,
+    );
+    let tx = create_transaction(&ctx, &[ix]);
+    submit_transaction(&mut ctx, tx).unwrap();
+
+    let result = batch_add_destinations(&mut ctx, &mint_pk, false, &destinations);
+    assert!(result.is_ok(), "Batch update failed: {:?}", result.err());
+
+    let events = find_destination_events(&result.unwrap().logs);
+    assert_eq!(events.len(), 2);
+    assert!(events[0].previous_state);
+    assert!(events[0].is_primary);
+    assert!(!events[1].previous_state);
+    assert!(!events[1].is_primary);
+    for (_, destination_state) in &destinations {
+        let account = ctx.svm.get_account(destination_state).unwrap();
+        let state = MerchantDestinationState::try_deserialize(&mut &account.data[..]).unwrap();
+        assert!(!state.allowed);
+    }
+    let account = ctx.svm.get_account(&primary_state).unwrap();
+    let state = MerchantDestinationState::try_deserialize(&mut &account.data[..]).unwrap();
+    assert!(state.is_primary, "Primary status should be kept");
+}
+
+#[tokio::test]
+async fn test_batch_add_destinations_rejects_mismatched_mint() {
+    let mut ctx = setup_and_initialize();
+    let mint_pk = setup_mint(&mut ctx);
+    let other_mint_pk = setup_mint(&mut ctx);
+    let destinations = [
+        create_destination(&mut ctx, &mint_pk),
+        create_destination(&mut ctx, &other_mint_pk),
+    ];
+    let destinations = with_state_pdas(&ctx, &mint_pk, &destinations);
+
+    assert_error(
+        batch_add_destinations(&mut ctx, &mint_pk, true, &destinations),
+        ErrorCode::MismatchedMint.to_string(),
+    );
+
+    // The valid destination was not added either
+    assert!(ctx.svm.get_account(&destinations[0].1).is_none());
+}
+
+#[tokio::test]
+async fn test_batch_add_destinations_rejects_invalid_accounts() {
+    let mut ctx = setup_and_initialize();
+    let mint_pk = setup_mint(&mut ctx);
+    let destination = create_destination(&mut ctx, &mint_pk);
+    let destinations = with_state_pdas(&ctx, &mint_pk, &[destination, destination]);
+
+    assert_error(
+        batch_add_destinations(&mut ctx, &mint_pk, true, &destinations),
+        ErrorCode::InvalidBatchAccounts.to_string(),
+    );
+    assert_error(
+        batch_add_destinations(&mut ctx, &mint_pk, true, &[]),
+        ErrorCode::InvalidBatchAccounts.to_string(),
+    );
+
+    // A state account that is not the destination's PDA
+    let other_destination = create_destination(&mut ctx, &mint_pk);
+    let wrong_state = with_state_pdas(&ctx, &mint_pk, &[other_destination])[0].1;
+    assert_error(
+        batch_add_destinations(&mut ctx, &mint_pk, true, &[(destination, wrong_state)]),
+        ErrorCode::InvalidPda.to_string(),
+    );
+}
@@ -0,0 +1,411 @@
+use crate::common::*;
+use anchor_lang::prelude::*;
+use bridge_cards::accounts::{AddOrUpdateMerchantDestination, BatchDebitUser};
+use bridge_cards::errors::ErrorCode;
+use bridge_cards::event_parsing::{parse_events, BridgeCardsEvent};
+use bridge_cards::events::UserDebited;
+use bridge_cards::instructions::add_or_update_merchant_config::MerchantConfigParams;
+use bridge_cards::state::MerchantDestinationState;
+use litesvm_token::CreateAssociatedTokenAccountIdempotent;
+use solana_program_test::tokio;
+
+const MAX_TRANSFER_LIMIT: u64 = 100_000_000; // $100 per transaction
+const PERIOD_TRANSFER_LIMIT: u64 = 2_000_000_000; // $2000 per day
+const PRINCIPAL_AMOUNT: u64 = 45_000_000; // $45
+const FEE_AMOUNT: u64 = 5_000_000; // $5
+
+type TestContext = crate::common::Context;
+
+fn find_user_debited_events(logs: &[String]) -> Vec<UserDebited> {
+    parse_events(logs)
+        .into_iter()
+        .filter_map(|event| match event {
+            BridgeCardsEvent::UserDebited(event) => Some(event),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Add a second destination for the merchant, returning (token account, destination state)
+fn setup_fee_destination(
+    ctx: &mut TestContext,
+    debit_context: &DebitUserContext,
+    destination_allowed: bool,
+) -> (Pubkey, Pubkey) {
+    let (_, fee_owner_pk) = setup_keypair(ctx);
+    let destination_token_account = CreateAssociatedTokenAccountIdempotent::new(
+        &mut ctx.svm,
+        &ctx.payer_kp,
+        &debit_context.mint_pk,
+    )
+    .owner(&fee_owner_pk)
+    .token_program_id(&debit_context.token_program.program_id())
+    .send()
+    .unwrap();
+    let destination_state = make_merchant_destination_pda(
+        TEST_MERCHANT_ID,
+        &debit_context.mint_pk,
+        &destination_token_account,
+        &ctx.program_id,
+    );
+    let accounts = AddOrUpdateMerchantDestination {
+        admin: ctx.payer_pk,
+        payer: ctx.payer_pk,
+        state: ctx.bridge_cards_state.pubkey,
+        destination_state: destination_state.pubkey,
+        destination_token_account,
+        mint: debit_context.mint_pk,
+        system_program: System::id(),
+        merchant_registry: None,
+    };
+    let ix = create_add_or_update_merchant_destination_instruction(
+        ctx,
+        &accounts,
+        TEST_MERCHANT_ID,
+        destination_allowed,
+    );
+    let tx = create_transaction(ctx, &[ix]);
+    submit_transaction(ctx, tx).unwrap();
+    (destination_token_account, destination_state.pubkey)
+}
+
+#[allow(clippy::result_large_err)]
+fn batch_debit_user(
+    ctx: &mut TestContext,
+    debit_context: &DebitUserContext,
+    amounts: Vec<u64>,
+    destinations: &[(Pubkey, Pubkey)],
+) -> litesvm::types::TransactionResult {
+    let accounts = BatchDebitUser {
+        payer: ctx.payer_pk,
+        user_delegate_account: debit_context.user_delegate_pda,
+        debitor: debit_context.debitor_pk,
+        debitor_state: debit_context.debitor_state_pda,
+        merchant_config: make_merchant_config_pda(TEST_MERCHANT_ID, &ctx.program_id).pubkey,
+        state: ctx.bridge_cards_state.pubkey,
+        user_token_account: debit_context.user_token_account,
+        user_owner: debit_context.user_pk,
+        mint: debit_context.mint_pk,
+        system_program: System::id(),
+        token_program: debit_context.token_program.program_id(),
+        merchant_spend_tracker: make_merchant_spend_tracker_pda(TEST_MERCHANT_ID, &ctx.program_id)
+            .pubkey,
+        fee_token_account: None,
+        merchant_mint: None,
+    };
+    let ix = create_batch_debit_user_instruction(
+        ctx,
+        &accounts,
+        TEST_MERCHANT_ID,
+        amounts,
+        destinations,
+    );
+    let tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &debit_context.debitor_kp],
+    );
+    submit_transaction(ctx, tx)
+}
+
+fn assert_error(result: litesvm::types::TransactionResult, error: ErrorCode) {
+    let err = result.expect_err("Batch debit should fail");
+    let expected_message = error.to_string();
+    assert!(
+        err.meta
+            .logs
+            .iter()
+            .any(|log| log.contains(&expected_message)),
+        "Error should contain the expected error message {}, got {}",
+        expected_message,
+        err.meta.logs.join("\n")
+    );
+}
+
+#[tokio::test]
+async fn test_batch_debit_user_splits_across_destinations() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    let fee_destination = setup_fee_destination(&mut ctx, &debit_context, true);
+    let destinations = [
+        (
+            debit_context.destination_token_account,
+            debit_context.destination_state_pda,
+        ),
+        fee_destination,
+    ];
+
+    let result = batch_debit_user(
+        &mut ctx,
+        &debit_context,
+        vec![PRINCIPAL_AMOUNT, FEE_AMOUNT],
+        &destinations,
+    );
+    assert!(result.is_ok(), "Batch debit failed: {:?}", result.err());
+
+    // One UserDebited event per leg
+    let events = find_user_debited_events(&result.unwrap().logs);
+    assert_eq!(events.len(), 2);
+    assert_eq!(
+        events[0].destination_ata,
+        debit_context.destination_token_account
+    );
+    assert_eq!(events[0].amount, PRINCIPAL_AMOUNT);
+    assert_eq!(events[1].destination_ata, fee_destination.0);
+    assert_eq!(events[1].amount, FEE_AMOUNT);
+
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.user_token_account,
+        INITIAL_BALANCE - PRINCIPAL_AMOUNT - FEE_AMOUNT,
+        TokenProgram::Token,
+        "User token account balance incorrect",
+    );
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.destination_token_account,
+        PRINCIPAL_AMOUNT,
+        TokenProgram::Token,
+        "Principal destination balance incorrect",
+    );
+    verify_token_account_balance(
+        &ctx,
+        &fee_destination.0,
+        FEE_AMOUNT,
+        TokenProgram::Token,
+        "Fee destination balance incorrect",
+    );
+}
+
+#[tokio::test]
+async fn test_batch_debit_user_records_last_received_on_destinations() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    let fee_destination = setup_fee_destination(&mut ctx, &debit_context, true);
+    let destinations = [
+        (
+            debit_context.destination_token_account,
+            debit_context.destination_state_pda,
+        ),
+        fee_destination,
+    ];
+
+    let result = batch_debit_user(
+        &mut ctx,
+        &debit_context,
+        vec![PRINCIPAL_AMOUNT, FEE_AMOUNT],
+        &destinations,
+    );
+    assert!(result.is_ok(), "Batch debit failed: {:?}", result.err());
+
+    // Each destination records its own leg
+    let timestamp = ctx.svm.get_sysvar::<Clock>().unix_timestamp;
+    for ((_, destination_state_pda), amount) in
+        destinations.iter().zip([PRINCIPAL_AMOUNT, FEE_AMOUNT])
+    {
+        let account = ctx.svm.get_account(destination_state_pda).unwrap();
+        let destination_state =
+            MerchantDestinationState::try_deserialize(&mut account.data.as_slice()).unwrap();
+        assert_eq!(destination_state.last_received_amount, amount);
+        assert_eq!(destination_state.last_received_timestamp, timestamp);
+    }
+}
+
+#[tokio::test]
+async fn test_batch_debit_user_reverts_on_disallowed_destination() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    let fee_destination = setup_fee_destination(&mut ctx, &debit_context, false);
+    let destinations = [
+        (
+            debit_context.destination_token_account,
+            debit_context.destination_state_pda,
+        ),
+        fee_destination,
+    ];
+
+    let result = batch_debit_user(
+        &mut ctx,
+        &debit_context,
+        vec![PRINCIPAL_AMOUNT, FEE_AMOUNT],
+        &destinations,
+    );
+    assert_error(result, ErrorCode::DestinationNotAllowed);
+
+    // No leg was transferred
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.user_token_account,
+        INITIAL_BALANCE,
+        TokenProgram::Token,
+        "User token account balance should remain unchanged",
+    );
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.destination_token_account,
+        0,
+        TokenProgram::Token,
+        "Allowed destination should not receive its leg",
+    );
+}
+
+#[tokio::test]
+async fn test_batch_debit_user_total_checked_against_limit() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    let fee_destination = setup_fee_destination(&mut ctx, &debit_context, true);
+    let destinations = [
+        (
+            debit_context.destination_token_account,
+            debit_context.destination_state_pda,
+        ),
+        fee_destination,
+    ];
+
+    // Each leg is within the per-transfer limit, but their total is not
+    let result = batch_debit_user(
+        &mut ctx,
+        &debit_context,
+        vec![MAX_TRANSFER_LIMIT, FEE_AMOUNT],
+        &destinations,
+    );
+    assert_error(result, ErrorCode::ExceedsMaxTransferLimit);
+}
+
+#[tokio::test]
+async fn test_batch_debit_user_requires_account_per_amount() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    let destinations = [(
+        debit_context.destination_token_account,
+        debit_context.destination_state_pda,
+    )];
+
+    let result = batch_debit_user(
+        &mut ctx,
+        &debit_context,
+        vec![PRINCIPAL_AMOUNT, FEE_AMOUNT],
+        &destinations,
+    );
+    assert_error(result, ErrorCode::InvalidBatchAccounts);
+}
+
+#[tokio::test]
+async fn test_batch_debit_user_total_checked_against_merchant_spend_tracker() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    let fee_destination = setup_fee_destination(&mut ctx, &debit_context, true);
+    let destinations = [
+        (
+            debit_context.destination_token_account,
+            debit_context.destination_state_pda,
+        ),
+        fee_destination,
+    ];
+    let admin = ctx.payer_kp.insecure_clone();
+    add_or_update_merchant_spend_tracker(&mut ctx, &admin, PRINCIPAL_AMOUNT).unwrap();
+
+    // Each leg is within the merchant limit, but their total is not
+    let result = batch_debit_user(
+        &mut ctx,
+        &debit_context,
+        vec![PRINCIPAL_AMOUNT, FEE_AMOUNT],
+        &destinations,
+    );
+    assert_error(result, ErrorCode::ExceedsMerchantPeriodLimit);
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.user_token_account,
+        INITIAL_BALANCE,
+        TokenProgram::Token,
+        "User token account balance should remain unchanged",
+    );
+}
+
+#[tokio::test]
+async fn test_batch_debit_user_split_count_checked_against_max_splits() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    setup_merchant_config(
+        &mut ctx,
+        TEST_MERCHANT_ID,
+        MerchantConfigParams {
+            max_splits: 2,
+            ..Default::default()
+        },
+    );
+    let fee_destination = setup_fee_destination(&mut ctx, &debit_context, true);
+    let extra_destination = setup_fee_destination(&mut ctx, &debit_context, true);
+    let destinations = [
+        (
+            debit_context.destination_token_account,
+            debit_context.destination_state_pda,
+        ),
+        fee_destination,
+        extra_destination,
+    ];
+
+    // One split more than the merchant allows
+    let result = batch_debit_user(
+        &mut ctx,
+        &debit_context,
+        vec![PRINCIPAL_AMOUNT, FEE_AMOUNT, FEE_AMOUNT],
+        &destinations,
+    );
+    assert_error(result, ErrorCode::TooManySplits);
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.user_token_account,
+        INITIAL_BALANCE,
+        TokenProgram::Token,
+        "User token account balance should remain unchanged",
+    );
+
+    // Exactly the merchant's maximum
+    let result = batch_debit_user(
+        &mut ctx,
+        &debit_context,
+        vec![PRINCIPAL_AMOUNT, FEE_AMOUNT],
+        &destinations[..2],
+    );
+    assert!(result.is_ok(), "Batch debit failed: {:?}", result.err());
+    assert_eq!(find_user_debited_events(&result.unwrap().logs).len(), 2);
+}
+
+#[tokio::test]
+async fn test_batch_debit_user_rejects_zero_split() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    let fee_destination = setup_fee_destination(&mut ctx, &debit_context, true);
+    let destinations = [
+        (
+            debit_context.destination_token_account,
+            debit_context.destination_state_pda,
+        ),
+        fee_destination,
+    ];
+
+    let result = batch_debit_user(
+        &mut ctx,
+        &debit_context,
+        vec![PRINCIPAL_AMOUNT, 0],
+        &destinations,
+    );
+    assert_error(result, ErrorCode::ZeroSplitAmount);
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.user_token_account,
+        INITIAL_BALANCE,
+        TokenProgram::Token,
+        "User token account balance should remain unchanged",
+    );
+}
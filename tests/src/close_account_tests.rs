@@ -1,24 +1,29 @@
 use crate::common::*;
 use anchor_lang::prelude::*;
 use anchor_lang::{error::ErrorCode, system_program};
-use base64;
 use bridge_cards::{
-    accounts::CloseAccount, errors::ErrorCode as BridgeErrorCode, events::AccountClosed,
-    instructions::add_or_update_merchant_debitor::MERCHANT_DEBITOR_SEED,
+    accounts::CloseAccount,
+    errors::ErrorCode as BridgeErrorCode,
+    event_parsing::{parse_events, BridgeCardsEvent},
+    pda,
 };
+use litesvm_token::Revoke;
 use solana_account::ReadableAccount;
 use solana_program_test::tokio;
 use solana_sdk::signature::Signer;
+use solana_sdk::transaction::Transaction;
 
 const TEST_MERCHANT_ID: u64 = 1;
 
+type TestContext = crate::common::Context;
+
 #[tokio::test]
 async fn test_close_account_success() {
     // Step 1: Setup the test environment and create an account to close
     let mut ctx = setup_and_initialize();
 
     // Create a merchant debitor account to close
-    let (debitor_kp, debitor_pk) = setup_keypair(&mut ctx);
+    let (_, debitor_pk) = setup_keypair(&mut ctx);
     let mint_pk = setup_mint(&mut ctx);
     let debitor_pda =
         make_merchant_debitor_pda(TEST_MERCHANT_ID, &debitor_pk, &mint_pk, &ctx.program_id);
@@ -32,6 +37,7 @@ async fn test_close_account_success() {
         debitor_state: debitor_pda.pubkey,
         mint: mint_pk,
         system_program: anchor_lang::system_program::ID,
+        merchant_registry: None,
     };
     let ix = create_add_or_update_merchant_debitor_instruction(
         &ctx,
@@ -57,17 +63,14 @@ async fn test_close_account_success() {
         payer: ctx.payer_pk,
         account_to_close: debitor_pda.pubkey,
         state: ctx.bridge_cards_state.pubkey,
+        user_token_account: None,
+        rent_recipient: None,
     };
 
     // Prepare the seeds for the debitor PDA
-    let input_seeds = vec![
-        MERCHANT_DEBITOR_SEED.to_vec(),
-        TEST_MERCHANT_ID.to_le_bytes().to_vec(),
-        mint_pk.to_bytes().to_vec(),
-        debitor_pk.to_bytes().to_vec(),
-    ];
-
-    let ix = create_close_account_instruction(&ctx, &close_accounts, input_seeds);
+    let input_seeds = pda::merchant_debitor_seeds(TEST_MERCHANT_ID, &mint_pk, &debitor_pk);
+
+    let ix = create_close_account_instruction(&ctx, &close_accounts, input_seeds, None);
     let tx = create_transaction_with_payer_and_signers(
         &ctx,
         &[ix],
@@ -87,22 +90,15 @@ async fn test_close_account_success() {
     let meta = result.unwrap(); // Get the TransactionMetadata directly
 
     let mut event_found = false;
-    for log in meta.logs.iter() {
-        if let Some(data_str) = log.strip_prefix("Program data: ") {
-            if let Ok(log_bytes) = base64::decode(data_str) {
-                if log_bytes.len() > 8 {
-                    let event_data = &log_bytes[8..]; // Skip the 8-byte discriminator
-                    if let Ok(parsed_event) = AccountClosed::try_from_slice(event_data) {
-                        // Assert specific event fields if needed
-                        assert_eq!(
-                            parsed_event.account, debitor_pda.pubkey,
-                            "Closed account does not match"
-                        );
-                        event_found = true;
-                        break;
-                    }
-                }
-            }
+    for event in parse_events(&meta.logs) {
+        if let BridgeCardsEvent::AccountClosed(parsed_event) = event {
+            // Assert specific event fields if needed
+            assert_eq!(
+                parsed_event.account, debitor_pda.pubkey,
+                "Closed account does not match"
+            );
+            event_found = true;
+            break;
         }
     }
 
@@ -135,7 +131,7 @@ async fn test_close_account_not_admin() {
     let mut ctx = setup_and_initialize();
 
     // Create a merchant debitor account to close
-    let (debitor_kp, debitor_pk) = setup_keypair(&mut ctx);
+    let (_, debitor_pk) = setup_keypair(&mut ctx);
     let mint_pk = setup_mint(&mut ctx);
     let debitor_pda =
         make_merchant_debitor_pda(TEST_MERCHANT_ID, &debitor_pk, &mint_pk, &ctx.program_id);
@@ -149,6 +145,7 @@ async fn test_close_account_not_admin() {
         debitor_state: debitor_pda.pubkey,
         mint: mint_pk,
         system_program: anchor_lang::system_program::ID,
+        merchant_registry: None,
     };
     let ix = create_add_or_update_merchant_debitor_instruction(
         &ctx,
@@ -173,16 +170,14 @@ async fn test_close_account_not_admin() {
         payer: not_admin_pk,
         account_to_close: debitor_pda.pubkey,
         state: ctx.bridge_cards_state.pubkey,
+        user_token_account: None,
+        rent_recipient: None,
     };
 
     // Prepare the seeds for the debitor PDA
-    let input_seeds = vec![
-        MERCHANT_DEBITOR_SEED.to_vec(),
-        TEST_MERCHANT_ID.to_le_bytes().to_vec(),
-        debitor_pk.to_bytes().to_vec(),
-    ];
+    let input_seeds = pda::merchant_debitor_seeds(TEST_MERCHANT_ID, &mint_pk, &debitor_pk);
 
-    let ix = create_close_account_instruction(&ctx, &close_accounts, input_seeds);
+    let ix = create_close_account_instruction(&ctx, &close_accounts, input_seeds, None);
     let tx = create_transaction_with_payer_and_signers(
         &ctx,
         &[ix],
@@ -218,7 +213,7 @@ async fn test_close_account_invalid_pda() {
     let mut ctx = setup_and_initialize();
 
     // Create a merchant debitor account to close
-    let (debitor_kp, debitor_pk) = setup_keypair(&mut ctx);
+    let (_, debitor_pk) = setup_keypair(&mut ctx);
     let mint_pk = setup_mint(&mut ctx);
     let debitor_pda =
         make_merchant_debitor_pda(TEST_MERCHANT_ID, &debitor_pk, &mint_pk, &ctx.program_id);
@@ -232,6 +227,7 @@ async fn test_close_account_invalid_pda() {
         debitor_state: debitor_pda.pubkey,
         mint: mint_pk,
         system_program: anchor_lang::system_program::ID,
+        merchant_registry: None,
     };
     let ix = create_add_or_update_merchant_debitor_instruction(
         &ctx,
@@ -253,17 +249,15 @@ async fn test_close_account_invalid_pda() {
         payer: ctx.payer_pk,
         account_to_close: debitor_pda.pubkey,
         state: ctx.bridge_cards_state.pubkey,
+        user_token_account: None,
+        rent_recipient: None,
     };
 
     // Prepare incorrect seeds (using wrong merchant ID)
     let wrong_merchant_id = 54321u64; // Different from the actual merchant ID
-    let input_seeds = vec![
-        MERCHANT_DEBITOR_SEED.to_vec(),
-        wrong_merchant_id.to_le_bytes().to_vec(), // Wrong merchant ID
-        debitor_pk.to_bytes().to_vec(),
-    ];
+    let input_seeds = pda::merchant_debitor_seeds(wrong_merchant_id, &mint_pk, &debitor_pk);
 
-    let ix = create_close_account_instruction(&ctx, &close_accounts, input_seeds);
+    let ix = create_close_account_instruction(&ctx, &close_accounts, input_seeds, None);
     let tx = create_transaction_with_payer_and_signers(
         &ctx,
         &[ix],
@@ -290,3 +284,377 @@ async fn test_close_account_invalid_pda() {
         err.meta.logs.join(", ")
     );
 }
+
+#[tokio::test]
+async fn test_close_account_cannot_close_state() {
+    let mut ctx = setup_and_initialize();
+
+    // Seeds crafted to derive the program state PDA itself
+    let close_accounts = CloseAccount {
+        admin: ctx.payer_pk,
+        payer: ctx.payer_pk,
+        account_to_close: ctx.bridge_cards_state.pubkey,
+        state: ctx.bridge_cards_state.pubkey,
+        user_token_account: None,
+        rent_recipient: None,
+    };
+    let input_seeds = pda::state_seeds();
+
+    let ix = create_close_account_instruction(&ctx, &close_accounts, input_seeds, None);
+    let tx = create_transaction_with_payer_and_signers(
+        &ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp],
+    );
+
+    let result = submit_transaction(&mut ctx, tx);
+    assert!(result.is_err(), "Should not be able to close the state PDA");
+
+    let err = result.err().unwrap();
+    let expected_error = BridgeErrorCode::InvalidPda.to_string();
+    assert!(
+        err.meta
+            .logs
+            .iter()
+            .any(|log| log.contains(&expected_error)),
+        "Error should contain InvalidPda, got {}",
+        err.meta.logs.join(", ")
+    );
+
+    // The state account must be untouched
+    let state_account = ctx.svm.get_account(&ctx.bridge_cards_state.pubkey).unwrap();
+    assert!(state_account.lamports() > 0);
+}
+
+/// Create a merchant debitor account and return its PDA along with the seeds to close it
+fn setup_debitor_to_close(ctx: &mut TestContext) -> (PDAWithBump, Vec<Vec<u8>>) {
+    let (_, debitor_pk) = setup_keypair(ctx);
+    let mint_pk = setup_mint(ctx);
+    let debitor_pda =
+        make_merchant_debitor_pda(TEST_MERCHANT_ID, &debitor_pk, &mint_pk, &ctx.program_id);
+
+    let debitor_accounts = bridge_cards::accounts::AddOrUpdateMerchantDebitor {
+        manager: ctx.merchant_manager_kp.pubkey(),
+        manager_state: ctx.merchant_manager_state.pubkey,
+        payer: ctx.payer_pk,
+        debitor: debitor_pk,
+        debitor_state: debitor_pda.pubkey,
+        mint: mint_pk,
+        system_program: anchor_lang::system_program::ID,
+        merchant_registry: None,
+    };
+    let ix = create_add_or_update_merchant_debitor_instruction(
+        ctx,
+        &debitor_accounts,
+        TEST_MERCHANT_ID,
+        true,
+    );
+    let tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &ctx.merchant_manager_kp],
+    );
+    submit_transaction(ctx, tx).unwrap();
+
+    let input_seeds = pda::merchant_debitor_seeds(TEST_MERCHANT_ID, &mint_pk, &debitor_pk);
+    (debitor_pda, input_seeds)
+}
+
+#[allow(clippy::result_large_err)]
+fn close_account(
+    ctx: &mut TestContext,
+    account_to_close: Pubkey,
+    input_seeds: Vec<Vec<u8>>,
+    bump: Option<u8>,
+) -> litesvm::types::TransactionResult {
+    let close_accounts = CloseAccount {
+        admin: ctx.payer_pk,
+        payer: ctx.payer_pk,
+        account_to_close,
+        state: ctx.bridge_cards_state.pubkey,
+        user_token_account: None,
+        rent_recipient: None,
+    };
+    let ix = create_close_account_instruction(ctx, &close_accounts, input_seeds, bump);
+    let tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp],
+    );
+    submit_transaction(ctx, tx)
+}
+
+#[tokio::test]
+async fn test_close_account_with_bump_uses_less_compute() {
+    let mut ctx = setup_and_initialize();
+    let (searched_pda, searched_seeds) = setup_debitor_to_close(&mut ctx);
+    let (bumped_pda, bumped_seeds) = setup_debitor_to_close(&mut ctx);
+
+    let searched = close_account(&mut ctx, searched_pda.pubkey, searched_seeds, None).unwrap();
+    let bumped = close_account(
+        &mut ctx,
+        bumped_pda.pubkey,
+        bumped_seeds,
+        Some(bumped_pda.bump),
+    )
+    .unwrap();
+
+    assert!(
+        bumped.compute_units_consumed < searched.compute_units_consumed,
+        "Closing with a bump should use fewer compute units: {} (bump) vs {} (search)",
+        bumped.compute_units_consumed,
+        searched.compute_units_consumed
+    );
+    let account = ctx.svm.get_account(&bumped_pda.pubkey);
+    assert!(account.is_none_or(|account| account.lamports() == 0));
+}
+
+#[tokio::test]
+async fn test_close_account_wrong_bump() {
+    let mut ctx = setup_and_initialize();
+    let (debitor_pda, input_seeds) = setup_debitor_to_close(&mut ctx);
+
+    let result = close_account(
+        &mut ctx,
+        debitor_pda.pubkey,
+        input_seeds,
+        Some(debitor_pda.bump.wrapping_sub(1)),
+    );
+    let err = result.expect_err("Closing with a wrong bump should fail");
+    let expected_error = BridgeErrorCode::InvalidPda.to_string();
+    assert!(
+        err.meta
+            .logs
+            .iter()
+            .any(|log| log.contains(&expected_error)),
+        "Error should contain InvalidPda, got {}",
+        err.meta.logs.join(", ")
+    );
+
+    // The account must be untouched
+    let account = ctx.svm.get_account(&debitor_pda.pubkey).unwrap();
+    assert!(account.lamports() > 0);
+}
+
+#[tokio::test]
+async fn test_close_account_recovers_balance_above_rent() {
+    let mut ctx = setup_and_initialize();
+    let (debitor_pda, input_seeds) = setup_debitor_to_close(&mut ctx);
+
+    // Lamports sent to the account after creation sit above its rent-exempt minimum
+    ctx.svm.airdrop(&debitor_pda.pubkey, 1_000_000_000).unwrap();
+    let balance_before = ctx.svm.get_account(&debitor_pda.pubkey).unwrap().lamports();
+
+    let meta = close_account(&mut ctx, debitor_pda.pubkey, input_seeds, None).unwrap();
+    let event = parse_events(&meta.logs)
+        .into_iter()
+        .find_map(|event| match event {
+            BridgeCardsEvent::AccountClosed(event) => Some(event),
+            _ => None,
+        })
+        .expect("AccountClosed event not found");
+    assert_eq!(event.account, debitor_pda.pubkey);
+    assert_eq!(event.lamports_recovered, balance_before);
+    assert_eq!(event.recipient, ctx.payer_pk);
+
+    let account = ctx.svm.get_account(&debitor_pda.pubkey);
+    assert!(account.is_none_or(|account| account.lamports() == 0));
+}
+
+#[tokio::test]
+async fn test_close_account_into_itself() {
+    let mut ctx = setup_and_initialize();
+    let (debitor_pda, input_seeds) = setup_debitor_to_close(&mut ctx);
+    let balance_before = ctx.svm.get_account(&debitor_pda.pubkey).unwrap().lamports();
+
+    // A PDA cannot sign a transaction, so skip signature checks to reach the program with the
+    // account to close as the payer
+    ctx.svm = std::mem::take(&mut ctx.svm).with_sigverify(false);
+    let close_accounts = CloseAccount {
+        admin: ctx.payer_pk,
+        payer: debitor_pda.pubkey,
+        account_to_close: debitor_pda.pubkey,
+        state: ctx.bridge_cards_state.pubkey,
+        user_token_account: None,
+        rent_recipient: None,
+    };
+    let ix = create_close_account_instruction(&ctx, &close_accounts, input_seeds, None);
+    let mut tx = Transaction::new_with_payer(&[ix], Some(&ctx.payer_pk));
+    tx.partial_sign(&[&ctx.payer_kp], ctx.svm.latest_blockhash());
+
+    let err = submit_transaction(&mut ctx, tx).expect_err("Closing into itself should fail");
+    let expected_error = BridgeErrorCode::SelfClose.to_string();
+    assert!(
+        err.meta
+            .logs
+            .iter()
+            .any(|log| log.contains(&expected_error)),
+        "Error should contain SelfClose, got {}",
+        err.meta.logs.join(", ")
+    );
+
+    // The account must be untouched
+    let account = ctx.svm.get_account(&debitor_pda.pubkey).unwrap();
+    assert_eq!(account.lamports(), balance_before);
+    assert_eq!(account.owner, ctx.program_id);
+}
+
+#[allow(clippy::result_large_err)]
+fn close_user_delegate_account(
+    ctx: &mut TestContext,
+    debit_context: &DebitUserContext,
+) -> litesvm::types::TransactionResult {
+    ctx.svm.expire_blockhash();
+    let close_accounts = CloseAccount {
+        admin: ctx.payer_pk,
+        payer: ctx.payer_pk,
+        account_to_close: debit_context.user_delegate_pda,
+        state: ctx.bridge_cards_state.pubkey,
+        user_token_account: Some(debit_context.user_token_account),
+        rent_recipient: None,
+    };
+    let input_seeds = pda::user_delegate_seeds(
+        TEST_MERCHANT_ID,
+        &debit_context.mint_pk,
+        &debit_context.user_token_account,
+    );
+    let ix = create_close_account_instruction(ctx, &close_accounts, input_seeds, None);
+    let tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp],
+    );
+    submit_transaction(ctx, tx)
+}
+
+#[tokio::test]
+async fn test_close_account_rejects_active_delegate() {
+    let mut ctx = setup_and_initialize();
+    let debit_context = setup_merchant_and_user_delegate(&mut ctx, 100_000_000, 2_000_000_000);
+
+    // The user token account still approves the delegate PDA
+    let result = close_user_delegate_account(&mut ctx, &debit_context);
+    let err = result.expect_err("Closing an approved delegate should fail");
+    let expected_error = BridgeErrorCode::DelegateStillActive.to_string();
+    assert!(
+        err.meta
+            .logs
+            .iter()
+            .any(|log| log.contains(&expected_error)),
+        "Error should contain DelegateStillActive, got {}",
+        err.meta.logs.join(", ")
+    );
+    assert!(ctx
+        .svm
+        .get_account(&debit_context.user_delegate_pda)
+        .is_some());
+
+    // Once the user revokes the approval the delegate can be closed
+    Revoke::new(
+        &mut ctx.svm,
+        &ctx.payer_kp,
+        &debit_context.user_token_account,
+    )
+    .owner(&debit_context.user_kp)
+    .send()
+    .unwrap();
+    let result = close_user_delegate_account(&mut ctx, &debit_context);
+    assert!(
+        result.is_ok(),
+        "Failed to close revoked delegate: {:?}",
+        result.err()
+    );
+    let closed = ctx.svm.get_account(&debit_context.user_delegate_pda);
+    assert!(closed.is_none() || closed.unwrap().lamports == 0);
+}
+
+#[allow(clippy::result_large_err)]
+fn close_account_to_recipient(
+    ctx: &mut TestContext,
+    account_to_close: Pubkey,
+    input_seeds: Vec<Vec<u8>>,
+    rent_recipient: Pubkey,
+) -> litesvm::types::TransactionResult {
+    ctx.svm.expire_blockhash();
+    let close_accounts = CloseAccount {
+        admin: ctx.payer_pk,
+        payer: ctx.payer_pk,
+        account_to_close,
+        state: ctx.bridge_cards_state.pubkey,
+        user_token_account: None,
+        rent_recipient: Some(rent_recipient),
+    };
+    let ix = create_close_account_instruction(ctx, &close_accounts, input_seeds, None);
+    let tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp],
+    );
+    submit_transaction(ctx, tx)
+}
+
+#[tokio::test]
+async fn test_close_account_to_rent_recipient() {
+    let mut ctx = setup_and_initialize();
+    let (debitor_pda, input_seeds) = setup_debitor_to_close(&mut ctx);
+    // The recipient doesn't sign, e.g. the merchant that paid the rent
+    let (_, recipient_pk) = setup_keypair(&mut ctx);
+    let rent = ctx.svm.get_account(&debitor_pda.pubkey).unwrap().lamports();
+    let recipient_before = ctx.svm.get_account(&recipient_pk).unwrap().lamports();
+    let payer_before = ctx.svm.get_account(&ctx.payer_pk).unwrap().lamports();
+
+    let meta = close_account_to_recipient(&mut ctx, debitor_pda.pubkey, input_seeds, recipient_pk)
+        .expect("Closing to a rent recipient failed");
+    let event = parse_events(&meta.logs)
+        .into_iter()
+        .find_map(|event| match event {
+            BridgeCardsEvent::AccountClosed(event) => Some(event),
+            _ => None,
+        })
+        .expect("AccountClosed event not found");
+    assert_eq!(event.account, debitor_pda.pubkey);
+    assert_eq!(event.lamports_recovered, rent);
+    assert_eq!(event.recipient, recipient_pk);
+
+    // The rent goes to the recipient, the payer only pays the transaction fee
+    assert_eq!(
+        ctx.svm.get_account(&recipient_pk).unwrap().lamports(),
+        recipient_before + rent
+    );
+    assert!(ctx.svm.get_account(&ctx.payer_pk).unwrap().lamports() < payer_before);
+    let account = ctx.svm.get_account(&debitor_pda.pubkey);
+    assert!(account.is_none_or(|account| account.lamports() == 0));
+}
+
+#[tokio::test]
+async fn test_close_account_to_itself_as_rent_recipient() {
+    let mut ctx = setup_and_initialize();
+    let (debitor_pda, input_seeds) = setup_debitor_to_close(&mut ctx);
+    let balance_before = ctx.svm.get_account(&debitor_pda.pubkey).unwrap().lamports();
+
+    let err = close_account_to_recipient(
+        &mut ctx,
+        debitor_pda.pubkey,
+        input_seeds,
+        debitor_pda.pubkey,
+    )
+    .expect_err("Closing an account into itself should fail");
+    let expected_error = BridgeErrorCode::SelfClose.to_string();
+    assert!(
+        err.meta
+            .logs
+            .iter()
+            .any(|log| log.contains(&expected_error)),
+        "Error should contain SelfClose, got {}",
+        err.meta.logs.join(", ")
+    );
+    assert_eq!(
+        ctx.svm.get_account(&debitor_pda.pubkey).unwrap().lamports(),
+        balance_before
+    );
+}
@@ -0,0 +1,193 @@
+use crate::common::*;
+use anchor_lang::prelude::*;
+use bridge_cards::{
+    accounts::CloseAccounts,
+    errors::ErrorCode,
+    event_parsing::{parse_events, BridgeCardsEvent},
+    events::AccountClosed,
+    instructions::add_or_update_merchant_debitor::MERCHANT_DEBITOR_SEED,
+    STATE_SEED,
+};
+use litesvm::types::TransactionResult;
+use solana_program_test::tokio;
+use solana_sdk::signature::Signer;
+
+type TestContext = crate::common::Context;
+
+fn find_account_closed_events(logs: &[String]) -> Vec<AccountClosed> {
+    parse_events(logs)
+        .into_iter()
+        .filter_map(|event| match event {
+            BridgeCardsEvent::AccountClosed(event) => Some(event),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Create an allowed debitor for the mint, returning its state PDA and seeds
+fn setup_debitor(ctx: &mut TestContext, mint_pk: &Pubkey) -> (Pubkey, Vec<Vec<u8>>) {
+    let (_, debitor_pk) = setup_keypair(ctx);
+    let debitor_pda =
+        make_merchant_debitor_pda(TEST_MERCHANT_ID, &debitor_pk, mint_pk, &ctx.program_id);
+    let accounts = bridge_cards::accounts::AddOrUpdateMerchantDebitor {
+        manager: ctx.merchant_manager_kp.pubkey(),
+        manager_state: ctx.merchant_manager_state.pubkey,
+        payer: ctx.payer_pk,
+        debitor: debitor_pk,
+        debitor_state: debitor_pda.pubkey,
+        mint: *mint_pk,
+        system_program: anchor_lang::system_program::ID,
+        merchant_registry: None,
+    };
+    let ix =
+        create_add_or_update_merchant_debitor_instruction(ctx, &accounts, TEST_MERCHANT_ID, true);
+    let tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &ctx.merchant_manager_kp],
+    );
+    submit_transaction(ctx, tx).unwrap();
+
+    let seeds = vec![
+        MERCHANT_DEBITOR_SEED.to_vec(),
+        TEST_MERCHANT_ID.to_le_bytes().to_vec(),
+        mint_pk.to_bytes().to_vec(),
+        debitor_pk.to_bytes().to_vec(),
+    ];
+    (debitor_pda.pubkey, seeds)
+}
+
+/// Create three debitors for one mint, returning their state PDAs and seeds
+fn setup_debitors(ctx: &mut TestContext) -> (Vec<Pubkey>, Vec<Vec<Vec<u8>>>) {
+    let mint_pk = setup_mint(ctx);
+    (0..3).map(|_| setup_debitor(ctx, &mint_pk)).unzip()
+}
+
+#[allow(clippy::result_large_err)]
+fn close_accounts(
+    ctx: &mut TestContext,
+    input_seeds: Vec<Vec<Vec<u8>>>,
+    accounts_to_close: &[Pubkey],
+) -> TransactionResult {
+    let accounts = CloseAccounts {
+        admin: ctx.payer_pk,
+        payer: ctx.payer_pk,
+        state: ctx.bridge_cards_state.pubkey,
+    };
+    let ix = create_close_accounts_instruction(ctx, &accounts, input_seeds, accounts_to_close);
+    let tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp],
+    );
+    submit_transaction(ctx, tx)
+}
+
+fn assert_close_error(result: TransactionResult, error: ErrorCode) {
+    let err = result.expect_err("Closing the accounts should fail");
+    let expected_message = error.to_string();
+    assert!(
+        err.meta
+            .logs
+            .iter()
+            .any(|log| log.contains(&expected_message)),
+        "Error should contain the expected error message {}, got {}",
+        expected_message,
+        err.meta.logs.join("\n")
+    );
+}
+
+fn assert_accounts_open(ctx: &TestContext, accounts: &[Pubkey]) {
+    for account in accounts {
+        let account = ctx.svm.get_account(account);
+        assert!(
+            account.is_some_and(|account| account.lamports > 0),
+            "Account should not have been closed"
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_close_accounts_success() {
+    let mut ctx = setup_and_initialize();
+    let (debitor_pdas, input_seeds) = setup_debitors(&mut ctx);
+    let rent: u64 = debitor_pdas
+        .iter()
+        .map(|pda| ctx.svm.get_account(pda).unwrap().lamports)
+        .sum();
+    let payer_lamports_before = ctx.svm.get_balance(&ctx.payer_pk).unwrap();
+
+    let result = close_accounts(&mut ctx, input_seeds, &debitor_pdas);
+    assert!(
+        result.is_ok(),
+        "Failed to close accounts: {:?}",
+        result.err()
+    );
+
+    // One event per closed account, in order
+    let events = find_account_closed_events(&result.unwrap().logs);
+    let closed: Vec<Pubkey> = events.iter().map(|event| event.account).collect();
+    assert_eq!(closed, debitor_pdas);
+    let recovered: u64 = events.iter().map(|event| event.lamports_recovered).sum();
+    assert_eq!(recovered, rent);
+
+    for pda in &debitor_pdas {
+        let account = ctx.svm.get_account(pda);
+        assert!(account.is_none() || account.unwrap().lamports == 0);
+    }
+    // The rent went to the payer, net of the transaction fee
+    let payer_lamports_after = ctx.svm.get_balance(&ctx.payer_pk).unwrap();
+    assert!(payer_lamports_after > payer_lamports_before);
+    assert!(payer_lamports_after <= payer_lamports_before + rent);
+}
+
+#[tokio::test]
+async fn test_close_accounts_bad_entry_reverts_batch() {
+    let mut ctx = setup_and_initialize();
+    let (debitor_pdas, mut input_seeds) = setup_debitors(&mut ctx);
+
+    // The last seeds derive a different PDA than the account passed at that position
+    input_seeds[2][3] = Pubkey::new_unique().to_bytes().to_vec();
+
+    let result = close_accounts(&mut ctx, input_seeds, &debitor_pdas);
+    assert_close_error(result, ErrorCode::InvalidPda);
+    assert_accounts_open(&ctx, &debitor_pdas);
+}
+
+#[tokio::test]
+async fn test_close_accounts_rejects_state_account() {
+    let mut ctx = setup_and_initialize();
+    let (mut debitor_pdas, mut input_seeds) = setup_debitors(&mut ctx);
+    debitor_pdas.push(ctx.bridge_cards_state.pubkey);
+    input_seeds.push(vec![STATE_SEED.to_vec()]);
+
+    let result = close_accounts(&mut ctx, input_seeds, &debitor_pdas);
+    assert_close_error(result, ErrorCode::InvalidPda);
+    assert_accounts_open(&ctx, &debitor_pdas);
+}
+
+#[tokio::test]
+async fn test_close_accounts_rejects_mismatched_lengths() {
+    let mut ctx = setup_and_initialize();
+    let (debitor_pdas, input_seeds) = setup_debitors(&mut ctx);
+
+    let result = close_accounts(&mut ctx, input_seeds, &debitor_pdas[..2]);
+    assert_close_error(result, ErrorCode::InvalidBatchAccounts);
+    assert_accounts_open(&ctx, &debitor_pdas);
+}
+
+#[tokio::test]
+async fn test_close_accounts_rejects_duplicate_account() {
+    let mut ctx = setup_and_initialize();
+    let (debitor_pdas, input_seeds) = setup_debitors(&mut ctx);
+
+    let result = close_accounts(
+        &mut ctx,
+        vec![input_seeds[0].clone(), input_seeds[0].clone()],
+        &[debitor_pdas[0], debitor_pdas[0]],
+    );
+    assert_close_error(result, ErrorCode::InvalidBatchAccounts);
+    assert_accounts_open(&ctx, &debitor_pdas);
+}
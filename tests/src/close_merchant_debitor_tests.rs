@@ -0,0 +1,146 @@
+use crate::common::*;
+use anchor_lang::error::ErrorCode as AnchorErrorCode;
+use anchor_lang::prelude::*;
+use bridge_cards::accounts::CloseMerchantDebitor;
+use bridge_cards::event_parsing::{parse_events, BridgeCardsEvent};
+use bridge_cards::events::AccountClosed;
+use litesvm::types::TransactionResult;
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer};
+
+const MAX_TRANSFER_LIMIT: u64 = 100_000_000; // $100 per transaction
+const PERIOD_TRANSFER_LIMIT: u64 = 2_000_000_000; // $2000 per day
+
+type TestContext = crate::common::Context;
+
+fn find_account_closed_event(logs: &[String]) -> Option<AccountClosed> {
+    parse_events(logs)
+        .into_iter()
+        .find_map(|event| match event {
+            BridgeCardsEvent::AccountClosed(event) => Some(event),
+            _ => None,
+        })
+}
+
+#[allow(clippy::result_large_err)]
+fn close_merchant_debitor(
+    ctx: &mut TestContext,
+    manager: &Keypair,
+    debitor_state: Pubkey,
+    mint: Pubkey,
+    debitor: Pubkey,
+) -> TransactionResult {
+    let accounts = CloseMerchantDebitor {
+        manager: manager.pubkey(),
+        payer: ctx.payer_pk,
+        manager_state: ctx.merchant_manager_state.pubkey,
+        debitor_state,
+    };
+    let ix =
+        create_close_merchant_debitor_instruction(ctx, &accounts, TEST_MERCHANT_ID, mint, debitor);
+    let tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, manager],
+    );
+    submit_transaction(ctx, tx)
+}
+
+#[tokio::test]
+async fn test_close_merchant_debitor() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    let debitor_state_lamports = ctx
+        .svm
+        .get_account(&debit_context.debitor_state_pda)
+        .unwrap()
+        .lamports;
+    let payer_lamports_before = ctx.svm.get_balance(&ctx.payer_pk).unwrap();
+
+    let manager = ctx.merchant_manager_kp.insecure_clone();
+    let result = close_merchant_debitor(
+        &mut ctx,
+        &manager,
+        debit_context.debitor_state_pda,
+        debit_context.mint_pk,
+        debit_context.debitor_pk,
+    );
+    assert!(
+        result.is_ok(),
+        "Failed to close merchant debitor: {:?}",
+        result.err()
+    );
+
+    let event =
+        find_account_closed_event(&result.unwrap().logs).expect("AccountClosed event not found");
+    assert_eq!(event.account, debit_context.debitor_state_pda);
+    assert_eq!(event.recipient, ctx.payer_pk);
+
+    // The debitor state is gone and its rent went to the payer, net of the transaction fee
+    let closed = ctx.svm.get_account(&debit_context.debitor_state_pda);
+    assert!(closed.is_none() || closed.unwrap().lamports == 0);
+    let payer_lamports_after = ctx.svm.get_balance(&ctx.payer_pk).unwrap();
+    assert!(payer_lamports_after > payer_lamports_before);
+    assert!(payer_lamports_after <= payer_lamports_before + debitor_state_lamports);
+
+    // The debitor can no longer debit
+    assert!(debit_user(&mut ctx, &debit_context, 1).is_err());
+}
+
+#[tokio::test]
+async fn test_close_merchant_debitor_rejects_mismatched_account() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+
+    // The typed debitor derives a different PDA than the debitor state passed in
+    let manager = ctx.merchant_manager_kp.insecure_clone();
+    let result = close_merchant_debitor(
+        &mut ctx,
+        &manager,
+        debit_context.debitor_state_pda,
+        debit_context.mint_pk,
+        Pubkey::new_unique(),
+    );
+    let err = result.expect_err("A mismatched debitor state should be rejected");
+    let expected_message = AnchorErrorCode::ConstraintSeeds.to_string();
+    assert!(
+        err.meta
+            .logs
+            .iter()
+            .any(|log| log.contains(&expected_message)),
+        "Error should contain the expected error message {}, got {}",
+        expected_message,
+        err.meta.logs.join("\n")
+    );
+    assert!(ctx
+        .svm
+        .get_account(&debit_context.debitor_state_pda)
+        .is_some());
+}
+
+#[tokio::test]
+async fn test_non_manager_cannot_close_merchant_debitor() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+
+    let non_manager = Keypair::new();
+    let result = close_merchant_debitor(
+        &mut ctx,
+        &non_manager,
+        debit_context.debitor_state_pda,
+        debit_context.mint_pk,
+        debit_context.debitor_pk,
+    );
+    assert!(
+        result.is_err(),
+        "Non-manager should not be able to close a merchant debitor"
+    );
+    assert!(ctx
+        .svm
+        .get_account(&debit_context.debitor_state_pda)
+        .is_some());
+}
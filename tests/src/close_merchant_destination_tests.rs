@@ -0,0 +1,152 @@
+use crate::common::*;
+use anchor_lang::error::ErrorCode as AnchorErrorCode;
+use anchor_lang::prelude::*;
+use bridge_cards::accounts::CloseMerchantDestination;
+use bridge_cards::event_parsing::{parse_events, BridgeCardsEvent};
+use bridge_cards::events::AccountClosed;
+use litesvm::types::TransactionResult;
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer};
+
+const MAX_TRANSFER_LIMIT: u64 = 100_000_000; // $100 per transaction
+const PERIOD_TRANSFER_LIMIT: u64 = 2_000_000_000; // $2000 per day
+
+type TestContext = crate::common::Context;
+
+fn find_account_closed_event(logs: &[String]) -> Option<AccountClosed> {
+    parse_events(logs)
+        .into_iter()
+        .find_map(|event| match event {
+            BridgeCardsEvent::AccountClosed(event) => Some(event),
+            _ => None,
+        })
+}
+
+#[allow(clippy::result_large_err)]
+fn close_merchant_destination(
+    ctx: &mut TestContext,
+    admin: &Keypair,
+    destination_state: Pubkey,
+    mint: Pubkey,
+    destination: Pubkey,
+) -> TransactionResult {
+    let accounts = CloseMerchantDestination {
+        admin: admin.pubkey(),
+        payer: ctx.payer_pk,
+        state: ctx.bridge_cards_state.pubkey,
+        destination_state,
+    };
+    let ix = create_close_merchant_destination_instruction(
+        ctx,
+        &accounts,
+        TEST_MERCHANT_ID,
+        mint,
+        destination,
+    );
+    let tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, admin],
+    );
+    submit_transaction(ctx, tx)
+}
+
+#[tokio::test]
+async fn test_close_merchant_destination() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    let destination_state_lamports = ctx
+        .svm
+        .get_account(&debit_context.destination_state_pda)
+        .unwrap()
+        .lamports;
+    let payer_lamports_before = ctx.svm.get_balance(&ctx.payer_pk).unwrap();
+
+    let admin = ctx.payer_kp.insecure_clone();
+    let result = close_merchant_destination(
+        &mut ctx,
+        &admin,
+        debit_context.destination_state_pda,
+        debit_context.mint_pk,
+        debit_context.destination_token_account,
+    );
+    assert!(
+        result.is_ok(),
+        "Failed to close merchant destination: {:?}",
+        result.err()
+    );
+
+    let event =
+        find_account_closed_event(&result.unwrap().logs).expect("AccountClosed event not found");
+    assert_eq!(event.account, debit_context.destination_state_pda);
+    assert_eq!(event.lamports_recovered, destination_state_lamports);
+    assert_eq!(event.recipient, ctx.payer_pk);
+
+    // The destination state is gone and its rent went to the payer, net of the transaction fee
+    let closed = ctx.svm.get_account(&debit_context.destination_state_pda);
+    assert!(closed.is_none() || closed.unwrap().lamports == 0);
+    let payer_lamports_after = ctx.svm.get_balance(&ctx.payer_pk).unwrap();
+    assert!(payer_lamports_after > payer_lamports_before);
+    assert!(payer_lamports_after <= payer_lamports_before + destination_state_lamports);
+
+    // Debits to the destination no longer go through
+    assert!(debit_user(&mut ctx, &debit_context, 1).is_err());
+}
+
+#[tokio::test]
+async fn test_close_merchant_destination_rejects_mismatched_account() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+
+    // The typed destination derives a different PDA than the destination state passed in
+    let admin = ctx.payer_kp.insecure_clone();
+    let result = close_merchant_destination(
+        &mut ctx,
+        &admin,
+        debit_context.destination_state_pda,
+        debit_context.mint_pk,
+        Pubkey::new_unique(),
+    );
+    let err = result.expect_err("A mismatched destination state should be rejected");
+    let expected_message = AnchorErrorCode::ConstraintSeeds.to_string();
+    assert!(
+        err.meta
+            .logs
+            .iter()
+            .any(|log| log.contains(&expected_message)),
+        "Error should contain the expected error message {}, got {}",
+        expected_message,
+        err.meta.logs.join("\n")
+    );
+    assert!(ctx
+        .svm
+        .get_account(&debit_context.destination_state_pda)
+        .is_some());
+}
+
+#[tokio::test]
+async fn test_non_admin_cannot_close_merchant_destination() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+
+    let non_admin = Keypair::new();
+    let result = close_merchant_destination(
+        &mut ctx,
+        &non_admin,
+        debit_context.destination_state_pda,
+        debit_context.mint_pk,
+        debit_context.destination_token_account,
+    );
+    assert!(
+        result.is_err(),
+        "Non-admin should not be able to close a merchant destination"
+    );
+    assert!(ctx
+        .svm
+        .get_account(&debit_context.destination_state_pda)
+        .is_some());
+}
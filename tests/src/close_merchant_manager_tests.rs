@@ -0,0 +1,188 @@
+use crate::common::*;
+use anchor_lang::error::ErrorCode as AnchorErrorCode;
+use anchor_lang::prelude::*;
+use bridge_cards::accounts::{
+    AddOrUpdateMerchantDebitor, AddOrUpdateUserDelegate, CloseMerchantManager,
+};
+use bridge_cards::event_parsing::{parse_events, BridgeCardsEvent};
+use bridge_cards::events::MerchantManagerClosed;
+use litesvm::types::TransactionResult;
+use litesvm_token::CreateAssociatedTokenAccountIdempotent;
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer};
+
+const MAX_TRANSFER_LIMIT: u64 = 100_000_000; // $100 per transaction
+const PERIOD_TRANSFER_LIMIT: u64 = 2_000_000_000; // $2000 per day
+
+type TestContext = crate::common::Context;
+
+fn find_merchant_manager_closed_event(logs: &[String]) -> Option<MerchantManagerClosed> {
+    parse_events(logs)
+        .into_iter()
+        .find_map(|event| match event {
+            BridgeCardsEvent::MerchantManagerClosed(event) => Some(event),
+            _ => None,
+        })
+}
+
+#[allow(clippy::result_large_err)]
+fn close_merchant_manager(ctx: &mut TestContext, admin: &Keypair) -> TransactionResult {
+    let accounts = CloseMerchantManager {
+        admin: admin.pubkey(),
+        payer: ctx.payer_pk,
+        state: ctx.bridge_cards_state.pubkey,
+        manager_state: ctx.merchant_manager_state.pubkey,
+    };
+    let ix = create_close_merchant_manager_instruction(ctx, &accounts, TEST_MERCHANT_ID);
+    let tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, admin],
+    );
+    submit_transaction(ctx, tx)
+}
+
+fn assert_manager_state_missing(result: TransactionResult) {
+    let err = result.expect_err("Manager-gated instructions should fail without a manager");
+    let expected_message = AnchorErrorCode::AccountNotInitialized.to_string();
+    assert!(
+        err.meta
+            .logs
+            .iter()
+            .any(|log| log.contains(&expected_message) && log.contains("manager_state")),
+        "Error should contain the expected error message {}, got {}",
+        expected_message,
+        err.meta.logs.join("\n")
+    );
+}
+
+#[tokio::test]
+async fn test_close_merchant_manager() {
+    let mut ctx = setup_and_initialize();
+    let manager_state_lamports = ctx
+        .svm
+        .get_account(&ctx.merchant_manager_state.pubkey)
+        .unwrap()
+        .lamports;
+    let payer_lamports_before = ctx.svm.get_balance(&ctx.payer_pk).unwrap();
+
+    let admin = ctx.payer_kp.insecure_clone();
+    let result = close_merchant_manager(&mut ctx, &admin);
+    assert!(
+        result.is_ok(),
+        "Failed to close merchant manager: {:?}",
+        result.err()
+    );
+
+    let event = find_merchant_manager_closed_event(&result.unwrap().logs)
+        .expect("MerchantManagerClosed event not found");
+    assert_eq!(event.merchant_id, TEST_MERCHANT_ID);
+    assert_eq!(event.managers, vec![ctx.merchant_manager_kp.pubkey()]);
+
+    // The manager state is gone and its rent went to the payer, net of the transaction fee
+    let closed = ctx.svm.get_account(&ctx.merchant_manager_state.pubkey);
+    assert!(closed.is_none() || closed.unwrap().lamports == 0);
+    let payer_lamports_after = ctx.svm.get_balance(&ctx.payer_pk).unwrap();
+    assert!(payer_lamports_after > payer_lamports_before);
+    assert!(payer_lamports_after <= payer_lamports_before + manager_state_lamports);
+}
+
+#[tokio::test]
+async fn test_non_admin_cannot_close_merchant_manager() {
+    let mut ctx = setup_and_initialize();
+
+    let non_admin = Keypair::new();
+    let result = close_merchant_manager(&mut ctx, &non_admin);
+    assert!(
+        result.is_err(),
+        "Non-admin should not be able to close a merchant manager"
+    );
+    assert!(ctx
+        .svm
+        .get_account(&ctx.merchant_manager_state.pubkey)
+        .is_some());
+}
+
+#[tokio::test]
+async fn test_closed_merchant_manager_cannot_add_user_delegate() {
+    let mut ctx = setup_and_initialize();
+    let admin = ctx.payer_kp.insecure_clone();
+    close_merchant_manager(&mut ctx, &admin).unwrap();
+
+    let mint_pk = setup_mint(&mut ctx);
+    let (_, user_pk) = setup_keypair(&mut ctx);
+    let user_token_account =
+        CreateAssociatedTokenAccountIdempotent::new(&mut ctx.svm, &ctx.payer_kp, &mint_pk)
+            .owner(&user_pk)
+            .send()
+            .unwrap();
+    let accounts = AddOrUpdateUserDelegate {
+        manager: ctx.merchant_manager_kp.pubkey(),
+        manager_state: ctx.merchant_manager_state.pubkey,
+        merchant_config: make_merchant_config_pda(TEST_MERCHANT_ID, &ctx.program_id).pubkey,
+        payer: ctx.payer_pk,
+        user_token_account,
+        mint: mint_pk,
+        user_delegate_account: make_user_delegate_pda(
+            TEST_MERCHANT_ID,
+            &mint_pk,
+            &user_token_account,
+            &ctx.program_id,
+        )
+        .pubkey,
+        system_program: System::id(),
+        merchant_mint: None,
+        merchant_registry: None,
+    };
+    let ix = create_add_or_update_user_delegate_instruction(
+        &ctx,
+        &accounts,
+        TEST_MERCHANT_ID,
+        MAX_TRANSFER_LIMIT,
+        PERIOD_TRANSFER_LIMIT,
+        LIMIT_PERIOD,
+    );
+    let tx = create_transaction_with_payer_and_signers(
+        &ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &ctx.merchant_manager_kp],
+    );
+    assert_manager_state_missing(submit_transaction(&mut ctx, tx));
+}
+
+#[tokio::test]
+async fn test_closed_merchant_manager_cannot_add_debitor() {
+    let mut ctx = setup_and_initialize();
+    let admin = ctx.payer_kp.insecure_clone();
+    close_merchant_manager(&mut ctx, &admin).unwrap();
+
+    let (_, debitor_pk) = setup_keypair(&mut ctx);
+    let mint_pk = setup_mint(&mut ctx);
+    let accounts = AddOrUpdateMerchantDebitor {
+        manager: ctx.merchant_manager_kp.pubkey(),
+        manager_state: ctx.merchant_manager_state.pubkey,
+        payer: ctx.payer_pk,
+        debitor: debitor_pk,
+        debitor_state: make_merchant_debitor_pda(
+            TEST_MERCHANT_ID,
+            &debitor_pk,
+            &mint_pk,
+            &ctx.program_id,
+        )
+        .pubkey,
+        mint: mint_pk,
+        system_program: System::id(),
+        merchant_registry: None,
+    };
+    let ix =
+        create_add_or_update_merchant_debitor_instruction(&ctx, &accounts, TEST_MERCHANT_ID, true);
+    let tx = create_transaction_with_payer_and_signers(
+        &ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &ctx.merchant_manager_kp],
+    );
+    assert_manager_state_missing(submit_transaction(&mut ctx, tx));
+}
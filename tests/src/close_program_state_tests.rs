@@ -0,0 +1,119 @@
+use crate::common::*;
+use anchor_lang::prelude::*;
+use bridge_cards::accounts::{CloseAccount, CloseProgramState};
+use bridge_cards::errors::ErrorCode;
+use bridge_cards::event_parsing::{parse_events, BridgeCardsEvent};
+use bridge_cards::events::ProgramDecommissioned;
+use bridge_cards::pda;
+use bridge_cards::state::{BridgeCardsState, CURRENT_VERSION};
+use litesvm::types::TransactionResult;
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer};
+
+type TestContext = crate::common::Context;
+
+fn find_program_decommissioned_event(logs: &[String]) -> Option<ProgramDecommissioned> {
+    parse_events(logs)
+        .into_iter()
+        .find_map(|event| match event {
+            BridgeCardsEvent::ProgramDecommissioned(event) => Some(event),
+            _ => None,
+        })
+}
+
+#[allow(clippy::result_large_err)]
+fn close_program_state(ctx: &mut TestContext, admin: &Keypair, confirm: bool) -> TransactionResult {
+    ctx.svm.expire_blockhash();
+    let accounts = CloseProgramState {
+        admin: admin.pubkey(),
+        state: ctx.bridge_cards_state.pubkey,
+    };
+    let ix = create_close_program_state_instruction(ctx, &accounts, confirm);
+    let tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, admin],
+    );
+    submit_transaction(ctx, tx)
+}
+
+#[tokio::test]
+async fn test_close_program_state() {
+    let mut ctx = setup_and_initialize();
+
+    // close_account still refuses the state
+    let accounts = CloseAccount {
+        admin: ctx.payer_pk,
+        payer: ctx.payer_pk,
+        account_to_close: ctx.bridge_cards_state.pubkey,
+        state: ctx.bridge_cards_state.pubkey,
+        user_token_account: None,
+        rent_recipient: None,
+    };
+    let ix = create_close_account_instruction(&ctx, &accounts, pda::state_seeds(), None);
+    let tx = create_transaction(&ctx, &[ix]);
+    let result = submit_transaction(&mut ctx, tx);
+    assert!(result.is_err(), "close_account should not close the state");
+
+    let state_lamports = ctx
+        .svm
+        .get_account(&ctx.bridge_cards_state.pubkey)
+        .unwrap()
+        .lamports;
+    let admin_kp = ctx.payer_kp.insecure_clone();
+    let meta = close_program_state(&mut ctx, &admin_kp, true).expect("Failed to close state");
+    let event = find_program_decommissioned_event(&meta.logs)
+        .expect("ProgramDecommissioned event not found");
+    assert_eq!(event.admin, ctx.payer_pk);
+    assert_eq!(event.lamports_recovered, state_lamports);
+    assert!(ctx
+        .svm
+        .get_account(&ctx.bridge_cards_state.pubkey)
+        .is_none_or(|account| account.lamports == 0));
+
+    // The program can be initialized again from scratch
+    ctx.svm.expire_blockhash();
+    initialize_bridge_cards(&mut ctx);
+    let account = ctx.svm.get_account(&ctx.bridge_cards_state.pubkey).unwrap();
+    let state = BridgeCardsState::try_deserialize(&mut account.data.as_slice()).unwrap();
+    assert_eq!(state.admin, ctx.payer_pk);
+    assert_eq!(state.version, CURRENT_VERSION);
+    assert_eq!(state.feature_flags, 0);
+}
+
+#[tokio::test]
+async fn test_close_program_state_requires_confirmation() {
+    let mut ctx = setup_and_initialize();
+
+    let admin_kp = ctx.payer_kp.insecure_clone();
+    let err = close_program_state(&mut ctx, &admin_kp, false)
+        .expect_err("Closing the state without confirmation should fail");
+    let expected_message = ErrorCode::DecommissionNotConfirmed.to_string();
+    assert!(
+        err.meta
+            .logs
+            .iter()
+            .any(|log| log.contains(&expected_message)),
+        "Error should contain the expected error message {}, got {}",
+        expected_message,
+        err.meta.logs.join("\n")
+    );
+    assert!(ctx
+        .svm
+        .get_account(&ctx.bridge_cards_state.pubkey)
+        .is_some());
+}
+
+#[tokio::test]
+async fn test_close_program_state_requires_admin() {
+    let mut ctx = setup_and_initialize();
+
+    let non_admin = Keypair::new();
+    let result = close_program_state(&mut ctx, &non_admin, true);
+    assert!(result.is_err(), "A non-admin should not close the state");
+    assert!(ctx
+        .svm
+        .get_account(&ctx.bridge_cards_state.pubkey)
+        .is_some());
+}
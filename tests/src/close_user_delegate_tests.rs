@@ -0,0 +1,112 @@
+use crate::common::*;
+use anchor_lang::prelude::*;
+use bridge_cards::accounts::CloseUserDelegate;
+use bridge_cards::event_parsing::{parse_events, BridgeCardsEvent};
+use bridge_cards::events::UserDelegateClosed;
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer};
+
+const MAX_TRANSFER_LIMIT: u64 = 100_000_000; // $100 per transaction
+const PERIOD_TRANSFER_LIMIT: u64 = 2_000_000_000; // $2000 per day
+
+type TestContext = crate::common::Context;
+
+fn find_user_delegate_closed_event(logs: &[String]) -> Option<UserDelegateClosed> {
+    parse_events(logs)
+        .into_iter()
+        .find_map(|event| match event {
+            BridgeCardsEvent::UserDelegateClosed(event) => Some(event),
+            _ => None,
+        })
+}
+
+fn make_close_user_delegate_accounts(
+    ctx: &TestContext,
+    debit_context: &DebitUserContext,
+    manager: Pubkey,
+) -> CloseUserDelegate {
+    CloseUserDelegate {
+        manager,
+        payer: ctx.payer_pk,
+        manager_state: ctx.merchant_manager_state.pubkey,
+        user_token_account: debit_context.user_token_account,
+        mint: debit_context.mint_pk,
+        user_delegate_account: debit_context.user_delegate_pda,
+    }
+}
+
+#[tokio::test]
+async fn test_close_user_delegate() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    let delegate_lamports = ctx
+        .svm
+        .get_account(&debit_context.user_delegate_pda)
+        .unwrap()
+        .lamports;
+    let payer_lamports_before = ctx.svm.get_balance(&ctx.payer_pk).unwrap();
+
+    let accounts =
+        make_close_user_delegate_accounts(&ctx, &debit_context, ctx.merchant_manager_kp.pubkey());
+    let ix = create_close_user_delegate_instruction(&ctx, &accounts, TEST_MERCHANT_ID);
+    let tx = create_transaction_with_payer_and_signers(
+        &ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &ctx.merchant_manager_kp],
+    );
+
+    let result = submit_transaction(&mut ctx, tx);
+    assert!(
+        result.is_ok(),
+        "Failed to close user delegate: {:?}",
+        result.err()
+    );
+
+    // Verify the UserDelegateClosed event
+    let meta = result.unwrap();
+    let event =
+        find_user_delegate_closed_event(&meta.logs).expect("UserDelegateClosed event not found");
+    assert_eq!(event.merchant_id, TEST_MERCHANT_ID);
+    assert_eq!(event.mint, debit_context.mint_pk);
+    assert_eq!(event.user_ata, debit_context.user_token_account);
+    assert_eq!(event.user_delegate, debit_context.user_delegate_pda);
+
+    // The delegate is gone and its rent went to the payer, net of the transaction fee
+    let closed = ctx.svm.get_account(&debit_context.user_delegate_pda);
+    assert!(closed.is_none() || closed.unwrap().lamports == 0);
+    let payer_lamports_after = ctx.svm.get_balance(&ctx.payer_pk).unwrap();
+    assert!(payer_lamports_after > payer_lamports_before);
+    assert!(payer_lamports_after <= payer_lamports_before + delegate_lamports);
+
+    // Debits through the closed delegate fail
+    assert!(debit_user(&mut ctx, &debit_context, 1).is_err());
+}
+
+#[tokio::test]
+async fn test_non_manager_cannot_close_user_delegate() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    let non_manager = Keypair::new();
+
+    let accounts = make_close_user_delegate_accounts(&ctx, &debit_context, non_manager.pubkey());
+    let ix = create_close_user_delegate_instruction(&ctx, &accounts, TEST_MERCHANT_ID);
+    let tx = create_transaction_with_payer_and_signers(
+        &ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &non_manager],
+    );
+
+    let result = submit_transaction(&mut ctx, tx);
+    assert!(
+        result.is_err(),
+        "Non-manager should not be able to close a user delegate"
+    );
+    assert!(ctx
+        .svm
+        .get_account(&debit_context.user_delegate_pda)
+        .is_some());
+}
@@ -4,19 +4,50 @@ use anchor_lang::InstructionData;
 use anchor_lang::ToAccountMetas;
 use bridge_cards::accounts::DebitUser;
 use bridge_cards::accounts::{
-    AddOrUpdateMerchantDebitor, AddOrUpdateMerchantDestination, AddOrUpdateMerchantManager,
-    AddOrUpdateUserDelegate, Initialize, UpdateAdmin,
+    AddMerchantManager, AddOrUpdateMerchantConfig, AddOrUpdateMerchantDebitor,
+    AddOrUpdateMerchantDestination, AddOrUpdateMerchantManager, AddOrUpdateMerchantSpendTracker,
+    AddOrUpdateRouter, AddOrUpdateUserDelegate, AdminHeartbeat, AdminRaiseDelegateLimits,
+    AdminRecoverUserFunds, BatchDebitUser, CloseMerchantDebitor, CloseMerchantDestination,
+    CloseMerchantManager, CloseProgramState, CloseUserDelegate, CreateUserDelegateFromTemplate,
+    DebitUserSol, DebitUserWithMemo, DebitUserWithSwap, Initialize, IsInitialized,
+    LogUserDelegateState, MigrateAccount, MigrateUserDelegate, ReduceUserDelegateLimits,
+    ReissueDelegate, RemoveMerchantManager, SetFeatureFlags, SetProtocolFee, SimulateDebitUser,
+    TimeUntilReset, UpdateAdmin,
+};
+use bridge_cards::instructions::add_or_update_merchant_config::{
+    MerchantConfigParams, MERCHANT_CONFIG_SEED,
 };
 use bridge_cards::instructions::add_or_update_merchant_debitor::MERCHANT_DEBITOR_SEED;
 use bridge_cards::instructions::add_or_update_merchant_destination::MERCHANT_DESTINATION_SEED;
 use bridge_cards::instructions::add_or_update_merchant_manager::MERCHANT_MANAGER_SEED;
-use bridge_cards::instructions::add_or_update_user_delegate::USER_DELEGATE_SEED;
+use bridge_cards::instructions::add_or_update_merchant_mint::MERCHANT_MINT_SEED;
+use bridge_cards::instructions::add_or_update_merchant_spend_tracker::MERCHANT_SPEND_SEED;
+use bridge_cards::instructions::add_or_update_router::ROUTER_SEED;
+use bridge_cards::instructions::add_or_update_user_delegate::{
+    UserDelegateOptions, USER_DELEGATE_SEED,
+};
+use bridge_cards::instructions::debit_user::DEBIT_RECEIPT_SEED;
+use bridge_cards::instructions::initialize_merchant_registry::MERCHANT_REGISTRY_SEED;
 use litesvm::types::TransactionResult;
 use litesvm::LiteSVM;
 use litesvm_token::*;
 use solana_sdk::signature::{Keypair, Signer};
 use solana_sdk::transaction::Transaction;
-use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use spl_token_2022::extension::transfer_fee::instruction::initialize_transfer_fee_config;
+use spl_token_2022::extension::ExtensionType;
+
+pub const TEST_MERCHANT_ID: u64 = 1u64;
+pub const INITIAL_BALANCE: u64 = 5_000_000_000; // $5000 initial balance
+pub const LIMIT_PERIOD: u32 = 86400; // 1 day in seconds
+
+// SPL Memo v1 is preloaded by LiteSVM and stands in for a swap router: it accepts any
+// accounts without signer checks and fails on non UTF-8 instruction data.
+pub const MOCK_ROUTER_PROGRAM_ID: Pubkey =
+    solana_sdk::pubkey!("Memo1UhkJRfHyvLMcVucJwxXeuD728EqVDDwQDxFMNo");
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum TokenProgram {
@@ -175,6 +206,21 @@ pub fn create_initialize_instruction_with_accounts(
     }
 }
 
+pub fn create_initialize_idempotent_instruction(ctx: &Context, payer: Pubkey) -> Instruction {
+    let accounts = bridge_cards::accounts::InitializeIdempotent {
+        payer,
+        state: ctx.bridge_cards_state.pubkey,
+        program_account: ctx.extra_keypair.pubkey(),
+        system_program: anchor_lang::system_program::ID,
+    };
+    let ix_data = bridge_cards::instruction::InitializeIdempotent {}.data();
+    Instruction {
+        program_id: ctx.program_id,
+        accounts: accounts.to_account_metas(None),
+        data: ix_data,
+    }
+}
+
 pub fn create_update_admin_instruction(ctx: &Context, accounts: UpdateAdmin) -> Instruction {
     let ix_data = bridge_cards::instruction::UpdateAdmin {}.data();
     Instruction {
@@ -199,6 +245,8 @@ pub fn create_transaction_with_payer_and_signers(
     Transaction::new_signed_with_payer(instructions, payer, signers, ctx.svm.latest_blockhash())
 }
 
+// TransactionResult is litesvm's own type, so the large Err variant cannot be boxed here
+#[allow(clippy::result_large_err)]
 pub fn submit_transaction(ctx: &mut Context, tx: Transaction) -> TransactionResult {
     let result = ctx.svm.send_transaction(tx);
     ctx.svm.expire_blockhash();
@@ -227,6 +275,64 @@ pub fn setup_mint_with_program(ctx: &mut Context, token_program: TokenProgram) -
         .unwrap()
 }
 
+/// Create the token-2022 native (wrapped SOL) mint
+pub fn setup_native_mint(ctx: &mut Context) -> Pubkey {
+    CreateNativeMint::new(&mut ctx.svm, &ctx.payer_kp)
+        .send()
+        .unwrap();
+    spl_token::native_mint::ID
+}
+
+/// Create a token-2022 mint with the transfer fee extension
+pub fn setup_transfer_fee_mint(
+    ctx: &mut Context,
+    transfer_fee_basis_points: u16,
+    maximum_fee: u64,
+) -> Pubkey {
+    let mint_kp = Keypair::new();
+    let mint_pk = mint_kp.pubkey();
+    let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&[
+        ExtensionType::TransferFeeConfig,
+    ])
+    .unwrap();
+    let lamports = ctx.svm.minimum_balance_for_rent_exemption(space);
+
+    let instructions = [
+        solana_sdk::system_instruction::create_account(
+            &ctx.payer_pk,
+            &mint_pk,
+            lamports,
+            space as u64,
+            &spl_token_2022::id(),
+        ),
+        initialize_transfer_fee_config(
+            &spl_token_2022::id(),
+            &mint_pk,
+            Some(&ctx.payer_pk),
+            Some(&ctx.payer_pk),
+            transfer_fee_basis_points,
+            maximum_fee,
+        )
+        .unwrap(),
+        spl_token_2022::instruction::initialize_mint2(
+            &spl_token_2022::id(),
+            &mint_pk,
+            &ctx.payer_pk,
+            None,
+            6,
+        )
+        .unwrap(),
+    ];
+    let tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &instructions,
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &mint_kp],
+    );
+    submit_transaction(ctx, tx).unwrap();
+    mint_pk
+}
+
 pub fn make_merchant_debitor_pda(
     merchant_id: u64,
     debitor: &Pubkey,
@@ -322,6 +428,7 @@ pub fn setup_merchant_debitor_and_destination_with_program(
         debitor_state: debitor_pda.pubkey,
         mint: *mint_pk,
         system_program: anchor_lang::system_program::ID,
+        merchant_registry: None,
     };
     let ix = create_add_or_update_merchant_debitor_instruction(
         ctx,
@@ -346,6 +453,7 @@ pub fn setup_merchant_debitor_and_destination_with_program(
         destination_token_account,
         mint: *mint_pk,
         system_program: anchor_lang::system_program::ID,
+        merchant_registry: None,
     };
     let ix = create_add_or_update_merchant_destination_instruction(
         ctx,
@@ -368,10 +476,71 @@ pub fn create_add_or_update_merchant_debitor_instruction(
     accounts: &AddOrUpdateMerchantDebitor,
     merchant_id: u64,
     debitor_allowed: bool,
+) -> Instruction {
+    create_add_or_update_merchant_debitor_instruction_with_limits(
+        ctx,
+        accounts,
+        merchant_id,
+        debitor_allowed,
+        0,
+        0,
+    )
+}
+
+/// Allow or disallow a debitor of the test merchant, signed by the given manager
+#[allow(clippy::result_large_err)]
+pub fn configure_merchant_debitor(
+    ctx: &mut Context,
+    manager: &Keypair,
+    mint: Pubkey,
+    debitor: Pubkey,
+    debitor_allowed: bool,
+) -> TransactionResult {
+    ctx.svm.expire_blockhash();
+    let accounts = AddOrUpdateMerchantDebitor {
+        manager: manager.pubkey(),
+        manager_state: ctx.merchant_manager_state.pubkey,
+        payer: ctx.payer_pk,
+        debitor,
+        debitor_state: make_merchant_debitor_pda(
+            TEST_MERCHANT_ID,
+            &debitor,
+            &mint,
+            &ctx.program_id,
+        )
+        .pubkey,
+        mint,
+        system_program: anchor_lang::system_program::ID,
+        merchant_registry: None,
+    };
+    let ix = create_add_or_update_merchant_debitor_instruction(
+        ctx,
+        &accounts,
+        TEST_MERCHANT_ID,
+        debitor_allowed,
+    );
+    let tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, manager],
+    );
+    submit_transaction(ctx, tx)
+}
+
+pub fn create_add_or_update_merchant_debitor_instruction_with_limits(
+    ctx: &Context,
+    accounts: &AddOrUpdateMerchantDebitor,
+    merchant_id: u64,
+    debitor_allowed: bool,
+    per_call_limit: u64,
+    daily_limit: u64,
 ) -> Instruction {
     let ix_data = bridge_cards::instruction::AddOrUpdateMerchantDebitor {
         merchant_id,
         debitor_allowed,
+        per_call_limit,
+        daily_limit,
     }
     .data();
 
@@ -382,15 +551,67 @@ pub fn create_add_or_update_merchant_debitor_instruction(
     }
 }
 
+/// Set the per-call and daily limits of the debit context's debitor
+pub fn set_debitor_limits(
+    ctx: &mut Context,
+    debit_context: &DebitUserContext,
+    per_call_limit: u64,
+    daily_limit: u64,
+) {
+    ctx.svm.expire_blockhash();
+    let accounts = bridge_cards::accounts::AddOrUpdateMerchantDebitor {
+        manager: ctx.merchant_manager_kp.pubkey(),
+        manager_state: ctx.merchant_manager_state.pubkey,
+        payer: ctx.payer_pk,
+        debitor: debit_context.debitor_pk,
+        debitor_state: debit_context.debitor_state_pda,
+        mint: debit_context.mint_pk,
+        system_program: anchor_lang::system_program::ID,
+        merchant_registry: None,
+    };
+    let ix = create_add_or_update_merchant_debitor_instruction_with_limits(
+        ctx,
+        &accounts,
+        TEST_MERCHANT_ID,
+        true,
+        per_call_limit,
+        daily_limit,
+    );
+    let tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &ctx.merchant_manager_kp],
+    );
+    submit_transaction(ctx, tx).unwrap();
+}
+
 pub fn create_add_or_update_merchant_destination_instruction(
     ctx: &Context,
     accounts: &AddOrUpdateMerchantDestination,
     merchant_id: u64,
     destination_allowed: bool,
+) -> Instruction {
+    create_add_or_update_merchant_destination_instruction_with_primary(
+        ctx,
+        accounts,
+        merchant_id,
+        destination_allowed,
+        false,
+    )
+}
+
+pub fn create_add_or_update_merchant_destination_instruction_with_primary(
+    ctx: &Context,
+    accounts: &AddOrUpdateMerchantDestination,
+    merchant_id: u64,
+    destination_allowed: bool,
+    is_primary: bool,
 ) -> Instruction {
     let ix_data = bridge_cards::instruction::AddOrUpdateMerchantDestination {
         merchant_id,
         destination_allowed,
+        is_primary,
     }
     .data();
 
@@ -426,12 +647,33 @@ pub fn create_add_or_update_user_delegate_instruction(
     max_transfer_limit: u64,
     period_transfer_limit: u64,
     transfer_limit_period: u32,
+) -> Instruction {
+    create_add_or_update_user_delegate_instruction_with_options(
+        ctx,
+        accounts,
+        merchant_id,
+        max_transfer_limit,
+        period_transfer_limit,
+        transfer_limit_period,
+        UserDelegateOptions::default(),
+    )
+}
+
+pub fn create_add_or_update_user_delegate_instruction_with_options(
+    ctx: &Context,
+    accounts: &AddOrUpdateUserDelegate,
+    merchant_id: u64,
+    max_transfer_limit: u64,
+    period_transfer_limit: u64,
+    transfer_limit_period: u32,
+    options: UserDelegateOptions,
 ) -> Instruction {
     let ix_data = bridge_cards::instruction::AddOrUpdateUserDelegate {
         merchant_id,
         max_transfer_limit,
         period_transfer_limit,
         transfer_limit_period,
+        options,
     }
     .data();
 
@@ -442,31 +684,85 @@ pub fn create_add_or_update_user_delegate_instruction(
     }
 }
 
-pub fn create_debit_user_instruction(
+pub fn create_create_user_delegate_from_template_instruction(
     ctx: &Context,
-    accounts: &DebitUser,
+    accounts: &CreateUserDelegateFromTemplate,
     merchant_id: u64,
-    amount: u64,
+    options: UserDelegateOptions,
 ) -> Instruction {
-    create_debit_user_instruction_with_program(
-        ctx,
-        accounts,
+    let ix_data = bridge_cards::instruction::CreateUserDelegateFromTemplate {
         merchant_id,
-        amount,
-        TokenProgram::Token,
-    )
+        options,
+    }
+    .data();
+
+    Instruction {
+        program_id: ctx.program_id,
+        accounts: accounts.to_account_metas(None),
+        data: ix_data,
+    }
 }
 
-pub fn create_debit_user_instruction_with_program(
+pub fn create_admin_raise_delegate_limits_instruction(
     ctx: &Context,
-    accounts: &DebitUser,
+    accounts: &AdminRaiseDelegateLimits,
     merchant_id: u64,
+    per_transfer_limit: u64,
+    period_transfer_limit: u64,
+) -> Instruction {
+    let ix_data = bridge_cards::instruction::AdminRaiseDelegateLimits {
+        merchant_id,
+        per_transfer_limit,
+        period_transfer_limit,
+    }
+    .data();
+
+    Instruction {
+        program_id: ctx.program_id,
+        accounts: accounts.to_account_metas(None),
+        data: ix_data,
+    }
+}
+
+pub fn create_admin_recover_user_funds_instruction(
+    ctx: &Context,
+    accounts: &AdminRecoverUserFunds,
     amount: u64,
-    _token_program: TokenProgram,
 ) -> Instruction {
-    let ix_data = bridge_cards::instruction::DebitUser {
+    let ix_data = bridge_cards::instruction::AdminRecoverUserFunds { amount }.data();
+
+    Instruction {
+        program_id: ctx.program_id,
+        accounts: accounts.to_account_metas(None),
+        data: ix_data,
+    }
+}
+
+pub fn create_close_program_state_instruction(
+    ctx: &Context,
+    accounts: &CloseProgramState,
+    confirm: bool,
+) -> Instruction {
+    let ix_data = bridge_cards::instruction::CloseProgramState { confirm }.data();
+
+    Instruction {
+        program_id: ctx.program_id,
+        accounts: accounts.to_account_metas(None),
+        data: ix_data,
+    }
+}
+
+pub fn create_reduce_user_delegate_limits_instruction(
+    ctx: &Context,
+    accounts: &ReduceUserDelegateLimits,
+    merchant_id: u64,
+    per_transfer_limit: u64,
+    period_transfer_limit: u64,
+) -> Instruction {
+    let ix_data = bridge_cards::instruction::ReduceUserDelegateLimits {
         merchant_id,
-        amount,
+        per_transfer_limit,
+        period_transfer_limit,
     }
     .data();
 
@@ -477,12 +773,128 @@ pub fn create_debit_user_instruction_with_program(
     }
 }
 
-pub fn create_close_account_instruction(
+pub fn create_close_user_delegate_instruction(
     ctx: &Context,
-    accounts: &bridge_cards::accounts::CloseAccount,
-    input_seeds: Vec<Vec<u8>>,
+    accounts: &CloseUserDelegate,
+    merchant_id: u64,
+) -> Instruction {
+    let ix_data = bridge_cards::instruction::CloseUserDelegate { merchant_id }.data();
+
+    Instruction {
+        program_id: ctx.program_id,
+        accounts: accounts.to_account_metas(None),
+        data: ix_data,
+    }
+}
+
+pub fn create_migrate_user_delegate_instruction(
+    ctx: &Context,
+    accounts: &MigrateUserDelegate,
+    merchant_id: u64,
+) -> Instruction {
+    let ix_data = bridge_cards::instruction::MigrateUserDelegate { merchant_id }.data();
+
+    Instruction {
+        program_id: ctx.program_id,
+        accounts: accounts.to_account_metas(None),
+        data: ix_data,
+    }
+}
+
+pub fn create_close_merchant_debitor_instruction(
+    ctx: &Context,
+    accounts: &CloseMerchantDebitor,
+    merchant_id: u64,
+    mint: Pubkey,
+    debitor: Pubkey,
+) -> Instruction {
+    let ix_data = bridge_cards::instruction::CloseMerchantDebitor {
+        merchant_id,
+        mint,
+        debitor,
+    }
+    .data();
+
+    Instruction {
+        program_id: ctx.program_id,
+        accounts: accounts.to_account_metas(None),
+        data: ix_data,
+    }
+}
+
+pub fn create_close_merchant_destination_instruction(
+    ctx: &Context,
+    accounts: &CloseMerchantDestination,
+    merchant_id: u64,
+    mint: Pubkey,
+    destination: Pubkey,
+) -> Instruction {
+    let ix_data = bridge_cards::instruction::CloseMerchantDestination {
+        merchant_id,
+        mint,
+        destination,
+    }
+    .data();
+
+    Instruction {
+        program_id: ctx.program_id,
+        accounts: accounts.to_account_metas(None),
+        data: ix_data,
+    }
+}
+
+pub fn create_close_merchant_manager_instruction(
+    ctx: &Context,
+    accounts: &CloseMerchantManager,
+    merchant_id: u64,
+) -> Instruction {
+    let ix_data = bridge_cards::instruction::CloseMerchantManager { merchant_id }.data();
+
+    Instruction {
+        program_id: ctx.program_id,
+        accounts: accounts.to_account_metas(None),
+        data: ix_data,
+    }
+}
+
+pub fn create_add_merchant_manager_instruction(
+    ctx: &Context,
+    accounts: &AddMerchantManager,
+    merchant_id: u64,
+) -> Instruction {
+    let ix_data = bridge_cards::instruction::AddMerchantManager { merchant_id }.data();
+
+    Instruction {
+        program_id: ctx.program_id,
+        accounts: accounts.to_account_metas(None),
+        data: ix_data,
+    }
+}
+
+pub fn add_merchant_manager(ctx: &mut Context, merchant_id: u64, manager: Pubkey) {
+    ctx.svm.expire_blockhash();
+    let accounts = AddMerchantManager {
+        admin: ctx.payer_pk,
+        state: ctx.bridge_cards_state.pubkey,
+        manager_state: make_manager_pda(merchant_id, &ctx.program_id).pubkey,
+        manager,
+    };
+    let ix = create_add_merchant_manager_instruction(ctx, &accounts, merchant_id);
+    let tx = create_transaction(ctx, &[ix]);
+    submit_transaction(ctx, tx).unwrap();
+}
+
+pub fn create_remove_merchant_manager_instruction(
+    ctx: &Context,
+    accounts: &RemoveMerchantManager,
+    merchant_id: u64,
+    manager: Pubkey,
 ) -> Instruction {
-    let ix_data = bridge_cards::instruction::CloseAccount { input_seeds }.data();
+    let ix_data = bridge_cards::instruction::RemoveMerchantManager {
+        merchant_id,
+        manager,
+    }
+    .data();
 
     Instruction {
         program_id: ctx.program_id,
@@ -490,3 +902,1019 @@ pub fn create_close_account_instruction(
         data: ix_data,
     }
 }
+
+pub fn create_reissue_delegate_instruction(
+    ctx: &Context,
+    accounts: &ReissueDelegate,
+    merchant_id: u64,
+    per_transfer_limit: u64,
+    period_transfer_limit: u64,
+    transfer_limit_period: u32,
+    carry_usage: bool,
+) -> Instruction {
+    let ix_data = bridge_cards::instruction::ReissueDelegate {
+        merchant_id,
+        per_transfer_limit,
+        period_transfer_limit,
+        transfer_limit_period,
+        carry_usage,
+    }
+    .data();
+
+    Instruction {
+        program_id: ctx.program_id,
+        accounts: accounts.to_account_metas(None),
+        data: ix_data,
+    }
+}
+
+pub fn create_admin_heartbeat_instruction(ctx: &Context, accounts: &AdminHeartbeat) -> Instruction {
+    let ix_data = bridge_cards::instruction::AdminHeartbeat {}.data();
+
+    Instruction {
+        program_id: ctx.program_id,
+        accounts: accounts.to_account_metas(None),
+        data: ix_data,
+    }
+}
+
+pub fn create_debit_user_instruction(
+    ctx: &Context,
+    accounts: &DebitUser,
+    merchant_id: u64,
+    amount: u64,
+) -> Instruction {
+    create_debit_user_instruction_with_program(
+        ctx,
+        accounts,
+        merchant_id,
+        amount,
+        TokenProgram::Token,
+    )
+}
+
+pub fn create_debit_user_instruction_with_program(
+    ctx: &Context,
+    accounts: &DebitUser,
+    merchant_id: u64,
+    amount: u64,
+    _token_program: TokenProgram,
+) -> Instruction {
+    let ix_data = bridge_cards::instruction::DebitUser {
+        merchant_id,
+        amount,
+    }
+    .data();
+
+    Instruction {
+        program_id: ctx.program_id,
+        accounts: accounts.to_account_metas(None),
+        data: ix_data,
+    }
+}
+
+pub fn create_simulate_debit_user_instruction(
+    ctx: &Context,
+    accounts: &SimulateDebitUser,
+    merchant_id: u64,
+    amount: u64,
+) -> Instruction {
+    let ix_data = bridge_cards::instruction::SimulateDebitUser {
+        merchant_id,
+        amount,
+    }
+    .data();
+
+    Instruction {
+        program_id: ctx.program_id,
+        accounts: accounts.to_account_metas(None),
+        data: ix_data,
+    }
+}
+
+pub fn create_debit_user_sol_instruction(
+    ctx: &Context,
+    accounts: &DebitUserSol,
+    merchant_id: u64,
+    amount: u64,
+) -> Instruction {
+    let ix_data = bridge_cards::instruction::DebitUserSol {
+        merchant_id,
+        amount,
+    }
+    .data();
+
+    Instruction {
+        program_id: ctx.program_id,
+        accounts: accounts.to_account_metas(None),
+        data: ix_data,
+    }
+}
+
+pub fn create_debit_user_exact_out_instruction(
+    ctx: &Context,
+    accounts: &DebitUser,
+    merchant_id: u64,
+    amount: u64,
+) -> Instruction {
+    let ix_data = bridge_cards::instruction::DebitUserExactOut {
+        merchant_id,
+        amount,
+    }
+    .data();
+
+    Instruction {
+        program_id: ctx.program_id,
+        accounts: accounts.to_account_metas(None),
+        data: ix_data,
+    }
+}
+
+pub fn create_batch_debit_user_instruction(
+    ctx: &Context,
+    accounts: &BatchDebitUser,
+    merchant_id: u64,
+    amounts: Vec<u64>,
+    destinations: &[(Pubkey, Pubkey)],
+) -> Instruction {
+    let ix_data = bridge_cards::instruction::BatchDebitUser {
+        merchant_id,
+        amounts,
+    }
+    .data();
+
+    // Each leg takes its destination token account and destination state PDA
+    let mut account_metas = accounts.to_account_metas(None);
+    for (destination_token_account, destination_state) in destinations {
+        account_metas.push(AccountMeta::new(*destination_token_account, false));
+        account_metas.push(AccountMeta::new(*destination_state, false));
+    }
+
+    Instruction {
+        program_id: ctx.program_id,
+        accounts: account_metas,
+        data: ix_data,
+    }
+}
+
+pub fn create_batch_add_destinations_instruction(
+    ctx: &Context,
+    accounts: &bridge_cards::accounts::BatchAddDestinations,
+    merchant_id: u64,
+    destination_allowed: bool,
+    destinations: &[(Pubkey, Pubkey)],
+) -> Instruction {
+    let ix_data = bridge_cards::instruction::BatchAddDestinations {
+        merchant_id,
+        destination_allowed,
+    }
+    .data();
+
+    // Each destination takes its token account and destination state PDA
+    let mut account_metas = accounts.to_account_metas(None);
+    for (destination_token_account, destination_state) in destinations {
+        account_metas.push(AccountMeta::new_readonly(*destination_token_account, false));
+        account_metas.push(AccountMeta::new(*destination_state, false));
+    }
+
+    Instruction {
+        program_id: ctx.program_id,
+        accounts: account_metas,
+        data: ix_data,
+    }
+}
+
+pub struct DebitUserContext {
+    pub mint_pk: Pubkey,
+    pub debitor_pk: Pubkey,
+    pub debitor_kp: Keypair,
+    pub debitor_state_pda: Pubkey,
+    pub destination_state_pda: Pubkey,
+    pub user_pk: Pubkey,
+    pub user_kp: Keypair,
+    pub user_token_account: Pubkey,
+    pub destination_token_account: Pubkey,
+    pub user_delegate_pda: Pubkey,
+    pub token_program: TokenProgram,
+}
+
+pub fn setup_merchant_and_user_delegate(
+    ctx: &mut Context,
+    max_transfer_limit: u64,
+    period_transfer_limit: u64,
+) -> DebitUserContext {
+    setup_merchant_and_user_delegate_with_program(
+        ctx,
+        max_transfer_limit,
+        period_transfer_limit,
+        TokenProgram::Token,
+    )
+}
+
+pub fn setup_merchant_and_user_delegate_with_program(
+    ctx: &mut Context,
+    max_transfer_limit: u64,
+    period_transfer_limit: u64,
+    token_program: TokenProgram,
+) -> DebitUserContext {
+    setup_merchant_and_user_delegate_with_options(
+        ctx,
+        max_transfer_limit,
+        period_transfer_limit,
+        token_program,
+        UserDelegateOptions::default(),
+    )
+}
+
+pub fn setup_merchant_and_user_delegate_with_options(
+    ctx: &mut Context,
+    max_transfer_limit: u64,
+    period_transfer_limit: u64,
+    token_program: TokenProgram,
+    options: UserDelegateOptions,
+) -> DebitUserContext {
+    // Create a token mint
+    let mint_pk = setup_mint_with_program(ctx, token_program);
+    setup_merchant_and_user_delegate_with_mint(
+        ctx,
+        max_transfer_limit,
+        period_transfer_limit,
+        token_program,
+        mint_pk,
+        options,
+    )
+}
+
+pub fn setup_merchant_and_user_delegate_with_mint(
+    ctx: &mut Context,
+    max_transfer_limit: u64,
+    period_transfer_limit: u64,
+    token_program: TokenProgram,
+    mint_pk: Pubkey,
+    options: UserDelegateOptions,
+) -> DebitUserContext {
+    // Setup merchant
+    let (debitor_kp, debitor_pk) = setup_keypair(ctx);
+    let (_, destination_pk) = setup_keypair(ctx);
+
+    let (debitor_state_pda, destination_state_pda, destination_token_account) =
+        setup_merchant_debitor_and_destination_with_program(
+            ctx,
+            TEST_MERCHANT_ID,
+            debitor_pk,
+            &mint_pk,
+            &destination_pk,
+            token_program,
+        );
+
+    // Create user and their token account
+    let (user_kp, user_pk) = setup_keypair(ctx);
+
+    // Create token account for the user with initial balance
+    let user_token_account =
+        CreateAssociatedTokenAccountIdempotent::new(&mut ctx.svm, &ctx.payer_kp, &mint_pk)
+            .owner(&user_pk)
+            .token_program_id(&token_program.program_id())
+            .send()
+            .unwrap();
+
+    // Fund the user's token account
+    if mint_pk == spl_token::native_mint::ID {
+        // Wrapped SOL is minted by depositing lamports into the token account
+        ctx.svm
+            .airdrop(&user_token_account, INITIAL_BALANCE)
+            .unwrap();
+        SyncNative::new(&mut ctx.svm, &ctx.payer_kp, &user_token_account)
+            .token_program_id(&token_program.program_id())
+            .send()
+            .unwrap();
+    } else {
+        MintTo::new(
+            &mut ctx.svm,
+            &ctx.payer_kp,
+            &mint_pk,
+            &user_token_account,
+            INITIAL_BALANCE,
+        )
+        .token_program_id(&token_program.program_id())
+        .send()
+        .unwrap();
+    }
+
+    // Create the user delegate account
+    let user_delegate_pda = make_user_delegate_pda(
+        TEST_MERCHANT_ID,
+        &mint_pk,
+        &user_token_account,
+        &ctx.program_id,
+    );
+
+    // checked-approve the user delegate pda for the user token account
+    ApproveChecked::new(
+        &mut ctx.svm,
+        &user_kp,
+        &user_delegate_pda.pubkey,
+        &mint_pk,
+        1e18 as u64,
+    )
+    .source(&user_token_account)
+    .token_program_id(&token_program.program_id())
+    .send()
+    .unwrap();
+
+    let user_delegate_accounts = bridge_cards::accounts::AddOrUpdateUserDelegate {
+        manager: ctx.merchant_manager_kp.pubkey(),
+        manager_state: ctx.merchant_manager_state.pubkey,
+        merchant_config: make_merchant_config_pda(TEST_MERCHANT_ID, &ctx.program_id).pubkey,
+        payer: ctx.payer_pk,
+        user_token_account,
+        mint: mint_pk,
+        user_delegate_account: user_delegate_pda.pubkey,
+        system_program: anchor_lang::system_program::ID,
+        merchant_mint: None,
+        merchant_registry: None,
+    };
+
+    let user_delegate_ix = create_add_or_update_user_delegate_instruction_with_options(
+        ctx,
+        &user_delegate_accounts,
+        TEST_MERCHANT_ID,
+        max_transfer_limit,
+        period_transfer_limit,
+        LIMIT_PERIOD,
+        options,
+    );
+    let user_delegate_tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[user_delegate_ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &ctx.merchant_manager_kp],
+    );
+
+    submit_transaction(ctx, user_delegate_tx).unwrap();
+
+    DebitUserContext {
+        mint_pk,
+        debitor_pk,
+        debitor_kp,
+        debitor_state_pda,
+        destination_state_pda,
+        user_pk,
+        user_kp,
+        user_token_account,
+        destination_token_account,
+        user_delegate_pda: user_delegate_pda.pubkey,
+        token_program,
+    }
+}
+
+pub fn make_debit_user_accounts(ctx: &Context, debit_context: &DebitUserContext) -> DebitUser {
+    DebitUser {
+        debitor: debit_context.debitor_pk,
+        payer: ctx.payer_pk,
+        user_delegate_account: debit_context.user_delegate_pda,
+        debitor_state: debit_context.debitor_state_pda,
+        destination_state: debit_context.destination_state_pda,
+        merchant_config: make_merchant_config_pda(TEST_MERCHANT_ID, &ctx.program_id).pubkey,
+        state: ctx.bridge_cards_state.pubkey,
+        user_token_account: debit_context.user_token_account,
+        user_owner: debit_context.user_pk,
+        destination_token_account: debit_context.destination_token_account,
+        mint: debit_context.mint_pk,
+        system_program: anchor_lang::system_program::ID,
+        token_program: debit_context.token_program.program_id(),
+        merchant_spend_tracker: make_merchant_spend_tracker_pda(TEST_MERCHANT_ID, &ctx.program_id)
+            .pubkey,
+        fee_token_account: None,
+        merchant_mint: None,
+        debit_receipt: None,
+    }
+}
+
+pub fn make_simulate_debit_user_accounts(
+    ctx: &Context,
+    debit_context: &DebitUserContext,
+) -> SimulateDebitUser {
+    SimulateDebitUser {
+        user_delegate_account: debit_context.user_delegate_pda,
+        debitor: debit_context.debitor_pk,
+        debitor_state: debit_context.debitor_state_pda,
+        destination_token_account: debit_context.destination_token_account,
+        destination_state: debit_context.destination_state_pda,
+        merchant_config: make_merchant_config_pda(TEST_MERCHANT_ID, &ctx.program_id).pubkey,
+        state: ctx.bridge_cards_state.pubkey,
+        user_token_account: debit_context.user_token_account,
+        user_owner: debit_context.user_pk,
+        mint: debit_context.mint_pk,
+        token_program: debit_context.token_program.program_id(),
+        merchant_spend_tracker: make_merchant_spend_tracker_pda(TEST_MERCHANT_ID, &ctx.program_id)
+            .pubkey,
+        fee_token_account: None,
+        merchant_mint: None,
+    }
+}
+
+/// Debit the user of a DebitUserContext, signed by its debitor
+#[allow(clippy::result_large_err)]
+pub fn debit_user(
+    ctx: &mut Context,
+    debit_context: &DebitUserContext,
+    amount: u64,
+) -> TransactionResult {
+    // Use a fresh blockhash so repeated identical debits aren't rejected as duplicates
+    ctx.svm.expire_blockhash();
+    let debit_accounts = make_debit_user_accounts(ctx, debit_context);
+    let debit_ix = create_debit_user_instruction_with_program(
+        ctx,
+        &debit_accounts,
+        TEST_MERCHANT_ID,
+        amount,
+        debit_context.token_program,
+    );
+    let debit_tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[debit_ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &debit_context.debitor_kp],
+    );
+    submit_transaction(ctx, debit_tx)
+}
+
+pub fn create_debit_user_logged_instruction(
+    ctx: &Context,
+    accounts: &DebitUser,
+    merchant_id: u64,
+    amount: u64,
+) -> Instruction {
+    let ix_data = bridge_cards::instruction::DebitUserLogged {
+        merchant_id,
+        amount,
+    }
+    .data();
+
+    Instruction {
+        program_id: ctx.program_id,
+        accounts: accounts.to_account_metas(None),
+        data: ix_data,
+    }
+}
+
+/// Debit the user, recording a debit rejected by the limits instead of failing
+#[allow(clippy::result_large_err)]
+pub fn debit_user_logged(
+    ctx: &mut Context,
+    debit_context: &DebitUserContext,
+    amount: u64,
+) -> TransactionResult {
+    ctx.svm.expire_blockhash();
+    let debit_accounts = make_debit_user_accounts(ctx, debit_context);
+    let debit_ix =
+        create_debit_user_logged_instruction(ctx, &debit_accounts, TEST_MERCHANT_ID, amount);
+    let debit_tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[debit_ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &debit_context.debitor_kp],
+    );
+    submit_transaction(ctx, debit_tx)
+}
+
+/// Debit the user so that the destination receives exactly the amount
+#[allow(clippy::result_large_err)]
+pub fn debit_user_exact_out(
+    ctx: &mut Context,
+    debit_context: &DebitUserContext,
+    amount: u64,
+) -> TransactionResult {
+    ctx.svm.expire_blockhash();
+    let debit_accounts = make_debit_user_accounts(ctx, debit_context);
+    let debit_ix =
+        create_debit_user_exact_out_instruction(ctx, &debit_accounts, TEST_MERCHANT_ID, amount);
+    let debit_tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[debit_ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &debit_context.debitor_kp],
+    );
+    submit_transaction(ctx, debit_tx)
+}
+
+pub fn create_is_initialized_instruction(ctx: &Context, accounts: &IsInitialized) -> Instruction {
+    let ix_data = bridge_cards::instruction::IsInitialized {}.data();
+
+    Instruction {
+        program_id: ctx.program_id,
+        accounts: accounts.to_account_metas(None),
+        data: ix_data,
+    }
+}
+
+/// Query whether the program is initialized
+pub fn is_initialized(ctx: &mut Context) -> bool {
+    ctx.svm.expire_blockhash();
+    let accounts = IsInitialized {
+        state: ctx.bridge_cards_state.pubkey,
+    };
+    let ix = create_is_initialized_instruction(ctx, &accounts);
+    let tx = create_transaction(ctx, &[ix]);
+    let meta = submit_transaction(ctx, tx).unwrap();
+    assert_eq!(meta.return_data.program_id, ctx.program_id);
+    meta.return_data.data == [1]
+}
+
+pub fn create_set_feature_flags_instruction(
+    ctx: &Context,
+    accounts: &SetFeatureFlags,
+    flags: u64,
+) -> Instruction {
+    let ix_data = bridge_cards::instruction::SetFeatureFlags { flags }.data();
+
+    Instruction {
+        program_id: ctx.program_id,
+        accounts: accounts.to_account_metas(None),
+        data: ix_data,
+    }
+}
+
+pub fn set_feature_flags(ctx: &mut Context, flags: u64) {
+    ctx.svm.expire_blockhash();
+    let accounts = SetFeatureFlags {
+        admin: ctx.payer_pk,
+        state: ctx.bridge_cards_state.pubkey,
+    };
+    let ix = create_set_feature_flags_instruction(ctx, &accounts, flags);
+    let tx = create_transaction(ctx, &[ix]);
+    submit_transaction(ctx, tx).unwrap();
+}
+
+pub fn create_migrate_account_instruction(ctx: &Context, accounts: &MigrateAccount) -> Instruction {
+    let ix_data = bridge_cards::instruction::MigrateAccount {}.data();
+
+    Instruction {
+        program_id: ctx.program_id,
+        accounts: accounts.to_account_metas(None),
+        data: ix_data,
+    }
+}
+
+pub fn create_set_protocol_fee_instruction(
+    ctx: &Context,
+    accounts: &SetProtocolFee,
+    fee_bps: u16,
+    fee_owner: Pubkey,
+) -> Instruction {
+    let ix_data = bridge_cards::instruction::SetProtocolFee { fee_bps, fee_owner }.data();
+
+    Instruction {
+        program_id: ctx.program_id,
+        accounts: accounts.to_account_metas(None),
+        data: ix_data,
+    }
+}
+
+pub fn set_protocol_fee(ctx: &mut Context, fee_bps: u16, fee_owner: Pubkey) {
+    ctx.svm.expire_blockhash();
+    let accounts = SetProtocolFee {
+        admin: ctx.payer_pk,
+        state: ctx.bridge_cards_state.pubkey,
+    };
+    let ix = create_set_protocol_fee_instruction(ctx, &accounts, fee_bps, fee_owner);
+    let tx = create_transaction(ctx, &[ix]);
+    submit_transaction(ctx, tx).unwrap();
+}
+
+pub fn create_time_until_reset_instruction(
+    ctx: &Context,
+    accounts: &TimeUntilReset,
+    merchant_id: u64,
+) -> Instruction {
+    let ix_data = bridge_cards::instruction::TimeUntilReset { merchant_id }.data();
+
+    Instruction {
+        program_id: ctx.program_id,
+        accounts: accounts.to_account_metas(None),
+        data: ix_data,
+    }
+}
+
+/// Query the seconds until the debit context's delegate period resets
+pub fn time_until_reset(ctx: &mut Context, debit_context: &DebitUserContext) -> u64 {
+    ctx.svm.expire_blockhash();
+    let accounts = TimeUntilReset {
+        user_delegate_account: debit_context.user_delegate_pda,
+        mint: debit_context.mint_pk,
+        user_token_account: debit_context.user_token_account,
+    };
+    let ix = create_time_until_reset_instruction(ctx, &accounts, TEST_MERCHANT_ID);
+    let tx = create_transaction(ctx, &[ix]);
+    let meta = submit_transaction(ctx, tx).unwrap();
+    assert_eq!(meta.return_data.program_id, ctx.program_id);
+    u64::from_le_bytes(meta.return_data.data.as_slice().try_into().unwrap())
+}
+
+pub fn create_log_user_delegate_state_instruction(
+    ctx: &Context,
+    accounts: &LogUserDelegateState,
+    merchant_id: u64,
+) -> Instruction {
+    let ix_data = bridge_cards::instruction::LogUserDelegateState { merchant_id }.data();
+
+    Instruction {
+        program_id: ctx.program_id,
+        accounts: accounts.to_account_metas(None),
+        data: ix_data,
+    }
+}
+
+/// Move the clock forward by the given number of slots
+pub fn advance_slots(ctx: &mut Context, slots: u64) {
+    let mut clock = ctx.svm.get_sysvar::<anchor_lang::prelude::Clock>();
+    clock.slot += slots;
+    ctx.svm.set_sysvar(&clock);
+}
+
+/// Helper function to verify token account balance based on token program
+pub fn verify_token_account_balance(
+    ctx: &Context,
+    token_account: &Pubkey,
+    expected_amount: u64,
+    token_program: TokenProgram,
+    error_msg: &str,
+) {
+    match token_program {
+        TokenProgram::Token => {
+            let account_info =
+                get_spl_account::<spl_token::state::Account>(&ctx.svm, token_account).unwrap();
+            assert_eq!(account_info.amount, expected_amount, "{}", error_msg);
+        }
+        TokenProgram::Token2022 => {
+            let account_info =
+                get_spl_account::<spl_token_2022::state::Account>(&ctx.svm, token_account).unwrap();
+            assert_eq!(account_info.amount, expected_amount, "{}", error_msg);
+        }
+    }
+}
+
+pub fn create_close_account_instruction(
+    ctx: &Context,
+    accounts: &bridge_cards::accounts::CloseAccount,
+    input_seeds: Vec<Vec<u8>>,
+    bump: Option<u8>,
+) -> Instruction {
+    let ix_data = bridge_cards::instruction::CloseAccount { input_seeds, bump }.data();
+
+    Instruction {
+        program_id: ctx.program_id,
+        accounts: accounts.to_account_metas(None),
+        data: ix_data,
+    }
+}
+
+pub fn create_close_accounts_instruction(
+    ctx: &Context,
+    accounts: &bridge_cards::accounts::CloseAccounts,
+    input_seeds: Vec<Vec<Vec<u8>>>,
+    accounts_to_close: &[Pubkey],
+) -> Instruction {
+    let ix_data = bridge_cards::instruction::CloseAccounts { input_seeds }.data();
+
+    // The accounts to close follow the fixed accounts, one per seed list
+    let mut account_metas = accounts.to_account_metas(None);
+    for account_to_close in accounts_to_close {
+        account_metas.push(AccountMeta::new(*account_to_close, false));
+    }
+
+    Instruction {
+        program_id: ctx.program_id,
+        accounts: account_metas,
+        data: ix_data,
+    }
+}
+
+pub fn make_merchant_config_pda(merchant_id: u64, program_id: &Pubkey) -> PDAWithBump {
+    make_pda(
+        &[MERCHANT_CONFIG_SEED, &merchant_id.to_le_bytes()],
+        program_id,
+    )
+}
+
+pub fn make_merchant_mint_pda(merchant_id: u64, mint: &Pubkey, program_id: &Pubkey) -> PDAWithBump {
+    make_pda(
+        &[
+            MERCHANT_MINT_SEED,
+            &merchant_id.to_le_bytes(),
+            mint.as_ref(),
+        ],
+        program_id,
+    )
+}
+
+pub fn make_merchant_registry_pda(merchant_id: u64, program_id: &Pubkey) -> PDAWithBump {
+    make_pda(
+        &[MERCHANT_REGISTRY_SEED, &merchant_id.to_le_bytes()],
+        program_id,
+    )
+}
+
+pub fn make_debit_receipt_pda(
+    user_delegate: &Pubkey,
+    debit_nonce: u64,
+    program_id: &Pubkey,
+) -> PDAWithBump {
+    make_pda(
+        &[
+            DEBIT_RECEIPT_SEED,
+            user_delegate.as_ref(),
+            &debit_nonce.to_le_bytes(),
+        ],
+        program_id,
+    )
+}
+
+pub fn make_merchant_spend_tracker_pda(merchant_id: u64, program_id: &Pubkey) -> PDAWithBump {
+    make_pda(
+        &[MERCHANT_SPEND_SEED, &merchant_id.to_le_bytes()],
+        program_id,
+    )
+}
+
+pub fn make_router_pda(router_program: &Pubkey, program_id: &Pubkey) -> PDAWithBump {
+    make_pda(&[ROUTER_SEED, router_program.as_ref()], program_id)
+}
+
+pub fn create_add_or_update_merchant_config_instruction(
+    ctx: &Context,
+    accounts: &AddOrUpdateMerchantConfig,
+    merchant_id: u64,
+    params: MerchantConfigParams,
+) -> Instruction {
+    let ix_data = bridge_cards::instruction::AddOrUpdateMerchantConfig {
+        merchant_id,
+        params,
+    }
+    .data();
+
+    Instruction {
+        program_id: ctx.program_id,
+        accounts: accounts.to_account_metas(None),
+        data: ix_data,
+    }
+}
+
+pub fn setup_merchant_config(
+    ctx: &mut Context,
+    merchant_id: u64,
+    params: MerchantConfigParams,
+) -> Pubkey {
+    let merchant_config_pda = make_merchant_config_pda(merchant_id, &ctx.program_id);
+    let accounts = AddOrUpdateMerchantConfig {
+        admin: ctx.payer_pk,
+        payer: ctx.payer_pk,
+        state: ctx.bridge_cards_state.pubkey,
+        merchant_config: merchant_config_pda.pubkey,
+        system_program: anchor_lang::system_program::ID,
+    };
+    let ix = create_add_or_update_merchant_config_instruction(ctx, &accounts, merchant_id, params);
+    let tx = create_transaction(ctx, &[ix]);
+    submit_transaction(ctx, tx).unwrap();
+    merchant_config_pda.pubkey
+}
+
+pub fn create_add_or_update_merchant_mint_instruction(
+    ctx: &Context,
+    accounts: &bridge_cards::accounts::AddOrUpdateMerchantMint,
+    merchant_id: u64,
+    mint_allowed: bool,
+) -> Instruction {
+    let ix_data = bridge_cards::instruction::AddOrUpdateMerchantMint {
+        merchant_id,
+        mint_allowed,
+    }
+    .data();
+
+    Instruction {
+        program_id: ctx.program_id,
+        accounts: accounts.to_account_metas(None),
+        data: ix_data,
+    }
+}
+
+/// Allowlist (or disallow) a mint for a merchant, returning the merchant mint PDA
+pub fn setup_merchant_mint(
+    ctx: &mut Context,
+    merchant_id: u64,
+    mint: &Pubkey,
+    mint_allowed: bool,
+) -> Pubkey {
+    ctx.svm.expire_blockhash();
+    let merchant_mint_pda = make_merchant_mint_pda(merchant_id, mint, &ctx.program_id);
+    let accounts = bridge_cards::accounts::AddOrUpdateMerchantMint {
+        admin: ctx.payer_pk,
+        payer: ctx.payer_pk,
+        state: ctx.bridge_cards_state.pubkey,
+        mint_state: merchant_mint_pda.pubkey,
+        mint: *mint,
+        system_program: anchor_lang::system_program::ID,
+    };
+    let ix =
+        create_add_or_update_merchant_mint_instruction(ctx, &accounts, merchant_id, mint_allowed);
+    let tx = create_transaction(ctx, &[ix]);
+    submit_transaction(ctx, tx).unwrap();
+    merchant_mint_pda.pubkey
+}
+
+pub fn create_initialize_merchant_registry_instruction(
+    ctx: &Context,
+    accounts: &bridge_cards::accounts::InitializeMerchantRegistry,
+    merchant_id: u64,
+) -> Instruction {
+    let ix_data = bridge_cards::instruction::InitializeMerchantRegistry { merchant_id }.data();
+
+    Instruction {
+        program_id: ctx.program_id,
+        accounts: accounts.to_account_metas(None),
+        data: ix_data,
+    }
+}
+
+/// Create a merchant's registry, returning the merchant registry PDA
+pub fn setup_merchant_registry(ctx: &mut Context, merchant_id: u64) -> Pubkey {
+    ctx.svm.expire_blockhash();
+    let merchant_registry_pda = make_merchant_registry_pda(merchant_id, &ctx.program_id);
+    let accounts = bridge_cards::accounts::InitializeMerchantRegistry {
+        admin: ctx.payer_pk,
+        payer: ctx.payer_pk,
+        state: ctx.bridge_cards_state.pubkey,
+        merchant_registry: merchant_registry_pda.pubkey,
+        system_program: anchor_lang::system_program::ID,
+    };
+    let ix = create_initialize_merchant_registry_instruction(ctx, &accounts, merchant_id);
+    let tx = create_transaction(ctx, &[ix]);
+    submit_transaction(ctx, tx).unwrap();
+    merchant_registry_pda.pubkey
+}
+
+pub fn create_add_or_update_merchant_spend_tracker_instruction(
+    ctx: &Context,
+    accounts: &AddOrUpdateMerchantSpendTracker,
+    merchant_id: u64,
+    period_transfer_limit: u64,
+    transfer_limit_period: u32,
+) -> Instruction {
+    let ix_data = bridge_cards::instruction::AddOrUpdateMerchantSpendTracker {
+        merchant_id,
+        period_transfer_limit,
+        transfer_limit_period,
+    }
+    .data();
+
+    Instruction {
+        program_id: ctx.program_id,
+        accounts: accounts.to_account_metas(None),
+        data: ix_data,
+    }
+}
+
+/// Create or update the merchant spend tracker of TEST_MERCHANT_ID, signed by admin
+#[allow(clippy::result_large_err)]
+pub fn add_or_update_merchant_spend_tracker(
+    ctx: &mut Context,
+    admin: &Keypair,
+    period_transfer_limit: u64,
+) -> TransactionResult {
+    ctx.svm.expire_blockhash();
+    let accounts = AddOrUpdateMerchantSpendTracker {
+        admin: admin.pubkey(),
+        payer: ctx.payer_pk,
+        state: ctx.bridge_cards_state.pubkey,
+        merchant_spend_tracker: make_merchant_spend_tracker_pda(TEST_MERCHANT_ID, &ctx.program_id)
+            .pubkey,
+        system_program: anchor_lang::system_program::ID,
+    };
+    let ix = create_add_or_update_merchant_spend_tracker_instruction(
+        ctx,
+        &accounts,
+        TEST_MERCHANT_ID,
+        period_transfer_limit,
+        LIMIT_PERIOD,
+    );
+    let tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, admin],
+    );
+    submit_transaction(ctx, tx)
+}
+
+pub fn set_merchant_destination_primary(
+    ctx: &mut Context,
+    debit_context: &DebitUserContext,
+    is_primary: bool,
+) {
+    set_merchant_destination(ctx, debit_context, true, is_primary);
+}
+
+pub fn set_merchant_destination(
+    ctx: &mut Context,
+    debit_context: &DebitUserContext,
+    destination_allowed: bool,
+    is_primary: bool,
+) {
+    ctx.svm.expire_blockhash();
+    let accounts = AddOrUpdateMerchantDestination {
+        admin: ctx.payer_pk,
+        payer: ctx.payer_pk,
+        state: ctx.bridge_cards_state.pubkey,
+        destination_state: debit_context.destination_state_pda,
+        destination_token_account: debit_context.destination_token_account,
+        mint: debit_context.mint_pk,
+        system_program: anchor_lang::system_program::ID,
+        merchant_registry: None,
+    };
+    let ix = create_add_or_update_merchant_destination_instruction_with_primary(
+        ctx,
+        &accounts,
+        TEST_MERCHANT_ID,
+        destination_allowed,
+        is_primary,
+    );
+    let tx = create_transaction(ctx, &[ix]);
+    submit_transaction(ctx, tx).unwrap();
+}
+
+pub fn create_add_or_update_router_instruction(
+    ctx: &Context,
+    accounts: &AddOrUpdateRouter,
+    router_allowed: bool,
+) -> Instruction {
+    let ix_data = bridge_cards::instruction::AddOrUpdateRouter { router_allowed }.data();
+
+    Instruction {
+        program_id: ctx.program_id,
+        accounts: accounts.to_account_metas(None),
+        data: ix_data,
+    }
+}
+
+pub fn setup_router(ctx: &mut Context, router_program: &Pubkey, router_allowed: bool) -> Pubkey {
+    let router_pda = make_router_pda(router_program, &ctx.program_id);
+    let accounts = AddOrUpdateRouter {
+        admin: ctx.payer_pk,
+        payer: ctx.payer_pk,
+        state: ctx.bridge_cards_state.pubkey,
+        router_state: router_pda.pubkey,
+        router_program: *router_program,
+        system_program: anchor_lang::system_program::ID,
+    };
+    let ix = create_add_or_update_router_instruction(ctx, &accounts, router_allowed);
+    let tx = create_transaction(ctx, &[ix]);
+    submit_transaction(ctx, tx).unwrap();
+    router_pda.pubkey
+}
+
+pub fn create_debit_user_with_memo_instruction(
+    ctx: &Context,
+    accounts: &DebitUserWithMemo,
+    merchant_id: u64,
+    amount: u64,
+    memo: Vec<u8>,
+) -> Instruction {
+    let ix_data = bridge_cards::instruction::DebitUserWithMemo {
+        merchant_id,
+        amount,
+        memo,
+    }
+    .data();
+
+    Instruction {
+        program_id: ctx.program_id,
+        accounts: accounts.to_account_metas(None),
+        data: ix_data,
+    }
+}
+
+pub fn create_debit_user_with_swap_instruction(
+    ctx: &Context,
+    accounts: &DebitUserWithSwap,
+    merchant_id: u64,
+    amount: u64,
+    route_data: Vec<u8>,
+    route_accounts: &[AccountMeta],
+) -> Instruction {
+    let ix_data = bridge_cards::instruction::DebitUserWithSwap {
+        merchant_id,
+        amount,
+        route_data,
+    }
+    .data();
+
+    let mut account_metas = accounts.to_account_metas(None);
+    account_metas.extend_from_slice(route_accounts);
+
+    Instruction {
+        program_id: ctx.program_id,
+        accounts: account_metas,
+        data: ix_data,
+    }
+}
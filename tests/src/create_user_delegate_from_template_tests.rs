@@ -0,0 +1,244 @@
+use crate::common::*;
+use anchor_lang::prelude::*;
+use bridge_cards::accounts::CreateUserDelegateFromTemplate;
+use bridge_cards::errors::ErrorCode;
+use bridge_cards::event_parsing::{parse_events, BridgeCardsEvent};
+use bridge_cards::events::UserDelegateAddedOrUpdated;
+use bridge_cards::instructions::add_or_update_merchant_config::MerchantConfigParams;
+use bridge_cards::instructions::add_or_update_user_delegate::UserDelegateOptions;
+use bridge_cards::state::{UserDelegateState, CURRENT_VERSION};
+use litesvm::types::TransactionResult;
+use litesvm_token::CreateAssociatedTokenAccountIdempotent;
+use solana_program_test::tokio;
+use solana_sdk::signature::Signer;
+
+const TEMPLATE_PER_TRANSFER_LIMIT: u64 = 25_000_000; // $25 per transaction
+const TEMPLATE_PERIOD_TRANSFER_LIMIT: u64 = 500_000_000; // $500 per week
+const TEMPLATE_PERIOD_SECONDS: u32 = 7 * 86400; // 1 week in seconds
+
+type TestContext = crate::common::Context;
+
+fn find_user_delegate_event(logs: &[String]) -> Option<UserDelegateAddedOrUpdated> {
+    parse_events(logs)
+        .into_iter()
+        .find_map(|event| match event {
+            BridgeCardsEvent::UserDelegateAddedOrUpdated(event) => Some(event),
+            _ => None,
+        })
+}
+
+fn setup_template(ctx: &mut TestContext) {
+    setup_merchant_config(
+        ctx,
+        TEST_MERCHANT_ID,
+        MerchantConfigParams {
+            default_per_transfer_limit: TEMPLATE_PER_TRANSFER_LIMIT,
+            default_period_transfer_limit: TEMPLATE_PERIOD_TRANSFER_LIMIT,
+            default_period_seconds: TEMPLATE_PERIOD_SECONDS,
+            ..Default::default()
+        },
+    );
+}
+
+/// Create a user token account for a new user, returning it and its delegate PDA
+fn setup_user_token_account(ctx: &mut TestContext, mint_pk: &Pubkey) -> (Pubkey, Pubkey) {
+    let (_, user_pk) = setup_keypair(ctx);
+    let user_token_account =
+        CreateAssociatedTokenAccountIdempotent::new(&mut ctx.svm, &ctx.payer_kp, mint_pk)
+            .owner(&user_pk)
+            .send()
+            .unwrap();
+    let user_delegate_pda = make_user_delegate_pda(
+        TEST_MERCHANT_ID,
+        mint_pk,
+        &user_token_account,
+        &ctx.program_id,
+    )
+    .pubkey;
+    (user_token_account, user_delegate_pda)
+}
+
+#[allow(clippy::result_large_err)]
+fn create_delegate_from_template(
+    ctx: &mut TestContext,
+    mint_pk: &Pubkey,
+    user_token_account: &Pubkey,
+    options: UserDelegateOptions,
+) -> TransactionResult {
+    ctx.svm.expire_blockhash();
+    let accounts = CreateUserDelegateFromTemplate {
+        manager: ctx.merchant_manager_kp.pubkey(),
+        payer: ctx.payer_pk,
+        manager_state: ctx.merchant_manager_state.pubkey,
+        merchant_config: make_merchant_config_pda(TEST_MERCHANT_ID, &ctx.program_id).pubkey,
+        user_token_account: *user_token_account,
+        mint: *mint_pk,
+        user_delegate_account: make_user_delegate_pda(
+            TEST_MERCHANT_ID,
+            mint_pk,
+            user_token_account,
+            &ctx.program_id,
+        )
+        .pubkey,
+        system_program: System::id(),
+        merchant_mint: None,
+        merchant_registry: None,
+    };
+    let ix = create_create_user_delegate_from_template_instruction(
+        ctx,
+        &accounts,
+        TEST_MERCHANT_ID,
+        options,
+    );
+    let tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &ctx.merchant_manager_kp],
+    );
+    submit_transaction(ctx, tx)
+}
+
+#[tokio::test]
+async fn test_create_user_delegate_from_template() {
+    let mut ctx = setup_and_initialize();
+    let mint_pk = setup_mint(&mut ctx);
+    setup_template(&mut ctx);
+
+    let metadata_hash = [7; 32];
+    for _ in 0..2 {
+        let (user_token_account, user_delegate_pda) = setup_user_token_account(&mut ctx, &mint_pk);
+        let meta = create_delegate_from_template(
+            &mut ctx,
+            &mint_pk,
+            &user_token_account,
+            UserDelegateOptions {
+                metadata_hash,
+                ..Default::default()
+            },
+        )
+        .expect("Failed to create delegate from template");
+
+        let event =
+            find_user_delegate_event(&meta.logs).expect("UserDelegateAddedOrUpdated not found");
+        assert_eq!(event.user_delegate, user_delegate_pda);
+        assert_eq!(event.previous_per_transfer_limit, 0);
+        assert_eq!(event.new_per_transfer_limit, TEMPLATE_PER_TRANSFER_LIMIT);
+        assert_eq!(event.previous_period_transfer_limit, 0);
+        assert_eq!(
+            event.new_period_transfer_limit,
+            TEMPLATE_PERIOD_TRANSFER_LIMIT
+        );
+
+        // Every delegate gets the template's limits, alongside its own options
+        let account = ctx.svm.get_account(&user_delegate_pda).unwrap();
+        let state = UserDelegateState::try_deserialize(&mut account.data.as_slice()).unwrap();
+        assert_eq!(state.version, CURRENT_VERSION);
+        assert_eq!(state.per_transfer_limit, TEMPLATE_PER_TRANSFER_LIMIT);
+        assert_eq!(state.period_transfer_limit, TEMPLATE_PERIOD_TRANSFER_LIMIT);
+        assert_eq!(state.transfer_limit_period_seconds, TEMPLATE_PERIOD_SECONDS);
+        assert_eq!(state.metadata_hash, metadata_hash);
+        assert_eq!(
+            state.bump,
+            make_user_delegate_pda(
+                TEST_MERCHANT_ID,
+                &mint_pk,
+                &user_token_account,
+                &ctx.program_id
+            )
+            .bump
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_create_user_delegate_from_template_requires_template() {
+    let mut ctx = setup_and_initialize();
+    let mint_pk = setup_mint(&mut ctx);
+    let (user_token_account, _) = setup_user_token_account(&mut ctx, &mint_pk);
+
+    // Neither a missing config nor a config without a template can be used
+    for with_config in [false, true] {
+        if with_config {
+            setup_merchant_config(&mut ctx, TEST_MERCHANT_ID, MerchantConfigParams::default());
+        }
+        let err = create_delegate_from_template(
+            &mut ctx,
+            &mint_pk,
+            &user_token_account,
+            UserDelegateOptions::default(),
+        )
+        .expect_err("Creating a delegate without a template should fail");
+        let expected_message = ErrorCode::MerchantTemplateNotSet.to_string();
+        assert!(
+            err.meta
+                .logs
+                .iter()
+                .any(|log| log.contains(&expected_message)),
+            "Error should contain the expected error message {}, got {}",
+            expected_message,
+            err.meta.logs.join("\n")
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_create_user_delegate_from_template_only_creates() {
+    let mut ctx = setup_and_initialize();
+    let mint_pk = setup_mint(&mut ctx);
+    setup_template(&mut ctx);
+    let (user_token_account, _) = setup_user_token_account(&mut ctx, &mint_pk);
+
+    let result = create_delegate_from_template(
+        &mut ctx,
+        &mint_pk,
+        &user_token_account,
+        UserDelegateOptions::default(),
+    );
+    assert!(result.is_ok(), "Creation failed: {:?}", result.err());
+
+    // An existing delegate is updated through add_or_update_user_delegate instead
+    let result = create_delegate_from_template(
+        &mut ctx,
+        &mint_pk,
+        &user_token_account,
+        UserDelegateOptions::default(),
+    );
+    assert!(result.is_err(), "The delegate should only be created once");
+}
+
+#[tokio::test]
+async fn test_merchant_config_rejects_invalid_template() {
+    let mut ctx = setup_and_initialize();
+    let accounts = bridge_cards::accounts::AddOrUpdateMerchantConfig {
+        admin: ctx.payer_pk,
+        payer: ctx.payer_pk,
+        state: ctx.bridge_cards_state.pubkey,
+        merchant_config: make_merchant_config_pda(TEST_MERCHANT_ID, &ctx.program_id).pubkey,
+        system_program: System::id(),
+    };
+    let ix = create_add_or_update_merchant_config_instruction(
+        &ctx,
+        &accounts,
+        TEST_MERCHANT_ID,
+        MerchantConfigParams {
+            default_per_transfer_limit: TEMPLATE_PERIOD_TRANSFER_LIMIT + 1,
+            default_period_transfer_limit: TEMPLATE_PERIOD_TRANSFER_LIMIT,
+            default_period_seconds: TEMPLATE_PERIOD_SECONDS,
+            ..Default::default()
+        },
+    );
+    let tx = create_transaction(&ctx, &[ix]);
+    let err = submit_transaction(&mut ctx, tx)
+        .expect_err("A period limit below the per-transfer limit should be rejected");
+    let expected_message = ErrorCode::InvalidTransferLimits.to_string();
+    assert!(
+        err.meta
+            .logs
+            .iter()
+            .any(|log| log.contains(&expected_message)),
+        "Error should contain the expected error message {}, got {}",
+        expected_message,
+        err.meta.logs.join("\n")
+    );
+}
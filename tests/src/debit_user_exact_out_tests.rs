@@ -0,0 +1,145 @@
+use crate::common::*;
+use bridge_cards::errors::ErrorCode;
+use bridge_cards::instructions::add_or_update_user_delegate::UserDelegateOptions;
+use bridge_cards::state::feature_flags;
+use solana_program_test::tokio;
+
+const MAX_TRANSFER_LIMIT: u64 = 100_000_000; // $100 per transaction
+const PERIOD_TRANSFER_LIMIT: u64 = 2_000_000_000; // $2000 per day
+const DEBIT_AMOUNT: u64 = 50_000_000; // $50 debit amount
+
+type TestContext = crate::common::Context;
+
+fn setup_transfer_fee_delegate(
+    ctx: &mut TestContext,
+    transfer_fee_basis_points: u16,
+    maximum_fee: u64,
+    max_transfer_limit: u64,
+) -> DebitUserContext {
+    let mint_pk = setup_transfer_fee_mint(ctx, transfer_fee_basis_points, maximum_fee);
+    setup_merchant_and_user_delegate_with_mint(
+        ctx,
+        max_transfer_limit,
+        PERIOD_TRANSFER_LIMIT,
+        TokenProgram::Token2022,
+        mint_pk,
+        UserDelegateOptions::default(),
+    )
+}
+
+#[tokio::test]
+async fn test_debit_user_exact_out_with_transfer_fee() {
+    let mut ctx = setup_and_initialize();
+
+    // 1% transfer fee, withheld in the destination account
+    let debit_context = setup_transfer_fee_delegate(&mut ctx, 100, u64::MAX, MAX_TRANSFER_LIMIT);
+    set_feature_flags(&mut ctx, feature_flags::POST_CONDITION_CHECKS);
+
+    let result = debit_user_exact_out(&mut ctx, &debit_context, DEBIT_AMOUNT);
+    assert!(result.is_ok(), "Exact-out debit failed: {:?}", result.err());
+
+    // ceil(50_000_000 * 10_000 / 9_900) is debited so that the 1% fee leaves exactly the amount
+    let gross_amount = 50_505_051;
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.user_token_account,
+        INITIAL_BALANCE - gross_amount,
+        TokenProgram::Token2022,
+        "User should be debited the grossed-up amount",
+    );
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.destination_token_account,
+        DEBIT_AMOUNT,
+        TokenProgram::Token2022,
+        "Destination should receive exactly the requested amount",
+    );
+}
+
+#[tokio::test]
+async fn test_debit_user_exact_out_with_maximum_fee() {
+    let mut ctx = setup_and_initialize();
+
+    // 10% transfer fee capped at 1_000 tokens
+    let maximum_fee = 1_000;
+    let debit_context =
+        setup_transfer_fee_delegate(&mut ctx, 1_000, maximum_fee, MAX_TRANSFER_LIMIT);
+
+    debit_user_exact_out(&mut ctx, &debit_context, DEBIT_AMOUNT).unwrap();
+
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.user_token_account,
+        INITIAL_BALANCE - DEBIT_AMOUNT - maximum_fee,
+        TokenProgram::Token2022,
+        "User should be debited the amount plus the capped fee",
+    );
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.destination_token_account,
+        DEBIT_AMOUNT,
+        TokenProgram::Token2022,
+        "Destination should receive exactly the requested amount",
+    );
+}
+
+#[tokio::test]
+async fn test_debit_user_exact_out_gross_amount_checked_against_limits() {
+    let mut ctx = setup_and_initialize();
+
+    // The per-transfer limit fits the net amount but not the amount grossed up by the fee
+    let debit_context = setup_transfer_fee_delegate(&mut ctx, 100, u64::MAX, DEBIT_AMOUNT);
+
+    let result = debit_user_exact_out(&mut ctx, &debit_context, DEBIT_AMOUNT);
+    let err = result.expect_err("The grossed-up amount should exceed the per-transfer limit");
+    let expected_message = ErrorCode::ExceedsMaxTransferLimit.to_string();
+    assert!(
+        err.meta
+            .logs
+            .iter()
+            .any(|log| log.contains(&expected_message)),
+        "Error should contain the expected error message {}, got {}",
+        expected_message,
+        err.meta.logs.join("\n")
+    );
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.user_token_account,
+        INITIAL_BALANCE,
+        TokenProgram::Token2022,
+        "User token account balance should remain unchanged",
+    );
+
+    // A plain debit of the same amount is within the limit
+    let result = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT);
+    assert!(result.is_ok(), "Debit failed: {:?}", result.err());
+}
+
+#[tokio::test]
+async fn test_debit_user_exact_out_without_transfer_fee() {
+    let mut ctx = setup_and_initialize();
+    let debit_context = setup_merchant_and_user_delegate_with_program(
+        &mut ctx,
+        MAX_TRANSFER_LIMIT,
+        PERIOD_TRANSFER_LIMIT,
+        TokenProgram::Token2022,
+    );
+
+    debit_user_exact_out(&mut ctx, &debit_context, DEBIT_AMOUNT).unwrap();
+
+    // Without the extension the amount is debited unchanged
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.user_token_account,
+        INITIAL_BALANCE - DEBIT_AMOUNT,
+        TokenProgram::Token2022,
+        "User token account balance incorrect",
+    );
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.destination_token_account,
+        DEBIT_AMOUNT,
+        TokenProgram::Token2022,
+        "Destination token account balance incorrect",
+    );
+}
@@ -0,0 +1,134 @@
+use crate::common::*;
+use anchor_lang::prelude::*;
+use bridge_cards::errors::ErrorCode;
+use bridge_cards::event_parsing::{parse_events, BridgeCardsEvent};
+use bridge_cards::events::DebitRejected;
+use bridge_cards::state::feature_flags;
+use solana_program_test::tokio;
+use solana_sdk::account::Account;
+
+const MAX_TRANSFER_LIMIT: u64 = 100_000_000; // $100 per transaction
+const PERIOD_TRANSFER_LIMIT: u64 = 2_000_000_000; // $2000 per day
+const DEBIT_AMOUNT: u64 = 50_000_000; // $50 debit amount
+
+type TestContext = crate::common::Context;
+
+fn find_debit_rejected_event(logs: &[String]) -> Option<DebitRejected> {
+    parse_events(logs)
+        .into_iter()
+        .find_map(|event| match event {
+            BridgeCardsEvent::DebitRejected(event) => Some(event),
+            _ => None,
+        })
+}
+
+fn has_user_debited_event(logs: &[String]) -> bool {
+    parse_events(logs)
+        .iter()
+        .any(|event| matches!(event, BridgeCardsEvent::UserDebited(_)))
+}
+
+/// Accounts a debit would update
+fn debited_accounts(ctx: &TestContext, debit_context: &DebitUserContext) -> Vec<Account> {
+    [
+        debit_context.user_delegate_pda,
+        debit_context.debitor_state_pda,
+        debit_context.user_token_account,
+        debit_context.destination_token_account,
+    ]
+    .iter()
+    .map(|account| ctx.svm.get_account(account).unwrap())
+    .collect()
+}
+
+fn reason_code(error: ErrorCode) -> u64 {
+    u64::from(u32::from(error))
+}
+
+#[tokio::test]
+async fn test_debit_user_logged_within_limits() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+
+    let meta = debit_user_logged(&mut ctx, &debit_context, DEBIT_AMOUNT)
+        .expect("Debit within the limits failed");
+    assert!(has_user_debited_event(&meta.logs));
+    assert!(find_debit_rejected_event(&meta.logs).is_none());
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.user_token_account,
+        INITIAL_BALANCE - DEBIT_AMOUNT,
+        debit_context.token_program,
+        "User should be debited",
+    );
+}
+
+#[tokio::test]
+async fn test_debit_user_logged_records_rejection() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    let accounts_before = debited_accounts(&ctx, &debit_context);
+
+    let meta = debit_user_logged(&mut ctx, &debit_context, MAX_TRANSFER_LIMIT + 1)
+        .expect("A debit rejected by the limits should still succeed");
+    assert!(!has_user_debited_event(&meta.logs));
+    let event = find_debit_rejected_event(&meta.logs).expect("DebitRejected event not found");
+    let clock = ctx.svm.get_sysvar::<Clock>();
+    assert_eq!(event.merchant_id, TEST_MERCHANT_ID);
+    assert_eq!(event.user_delegate, debit_context.user_delegate_pda);
+    assert_eq!(event.debitor, debit_context.debitor_pk);
+    assert_eq!(event.amount, MAX_TRANSFER_LIMIT + 1);
+    assert_eq!(
+        event.reason_code,
+        reason_code(ErrorCode::ExceedsMaxTransferLimit)
+    );
+    assert_eq!(event.slot, clock.slot);
+    assert_eq!(event.timestamp, clock.unix_timestamp);
+
+    // No tokens moved and no usage was recorded
+    assert_eq!(debited_accounts(&ctx, &debit_context), accounts_before);
+}
+
+#[tokio::test]
+async fn test_debit_user_logged_restores_usage_on_late_rejection() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    set_debitor_limits(&mut ctx, &debit_context, 0, DEBIT_AMOUNT);
+    debit_user_logged(&mut ctx, &debit_context, DEBIT_AMOUNT).unwrap();
+    advance_slots(&mut ctx, 1);
+    let accounts_before = debited_accounts(&ctx, &debit_context);
+
+    // The delegate's limits pass before the debitor's daily limit rejects the debit
+    let meta = debit_user_logged(&mut ctx, &debit_context, DEBIT_AMOUNT)
+        .expect("A debit rejected by the limits should still succeed");
+    let event = find_debit_rejected_event(&meta.logs).expect("DebitRejected event not found");
+    assert_eq!(
+        event.reason_code,
+        reason_code(ErrorCode::ExceedsDebitorDailyLimit)
+    );
+    assert_eq!(debited_accounts(&ctx, &debit_context), accounts_before);
+}
+
+#[tokio::test]
+async fn test_debit_user_logged_other_failures_revert() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    set_feature_flags(&mut ctx, feature_flags::PAUSED);
+
+    let err = debit_user_logged(&mut ctx, &debit_context, MAX_TRANSFER_LIMIT + 1)
+        .expect_err("Debits outside the limit checks should still fail");
+    let expected_message = ErrorCode::ProgramPaused.to_string();
+    assert!(
+        err.meta
+            .logs
+            .iter()
+            .any(|log| log.contains(&expected_message)),
+        "Error should contain the expected error message {}, got {}",
+        expected_message,
+        err.meta.logs.join("\n")
+    );
+}
@@ -0,0 +1,203 @@
+use crate::common::*;
+use anchor_lang::prelude::*;
+use bridge_cards::accounts::DebitUserSol;
+use bridge_cards::errors::ErrorCode;
+use bridge_cards::event_parsing::{parse_events, BridgeCardsEvent};
+use bridge_cards::events::UserDebited;
+use bridge_cards::instructions::add_or_update_user_delegate::UserDelegateOptions;
+use bridge_cards::instructions::debit_user_sol::SOL_DEBIT_SEED;
+use litesvm::types::TransactionResult;
+use litesvm_token::{get_spl_account, spl_token};
+use solana_program_test::tokio;
+
+const MAX_TRANSFER_LIMIT: u64 = 1_000_000_000; // 1 SOL per transaction
+const PERIOD_TRANSFER_LIMIT: u64 = 3_000_000_000; // 3 SOL per day
+
+type TestContext = crate::common::Context;
+
+fn find_user_debited_event(logs: &[String]) -> Option<UserDebited> {
+    parse_events(logs)
+        .into_iter()
+        .find_map(|event| match event {
+            BridgeCardsEvent::UserDebited(event) => Some(event),
+            _ => None,
+        })
+}
+
+/// Set up a merchant and a user delegate on the native mint, funded with wrapped SOL
+fn setup_sol_delegate(ctx: &mut TestContext) -> DebitUserContext {
+    let native_mint = setup_native_mint(ctx);
+    setup_merchant_and_user_delegate_with_mint(
+        ctx,
+        MAX_TRANSFER_LIMIT,
+        PERIOD_TRANSFER_LIMIT,
+        TokenProgram::Token,
+        native_mint,
+        UserDelegateOptions::default(),
+    )
+}
+
+fn make_temporary_token_account_pda(ctx: &TestContext, debit_context: &DebitUserContext) -> Pubkey {
+    make_pda(
+        &[SOL_DEBIT_SEED, debit_context.user_delegate_pda.as_ref()],
+        &ctx.program_id,
+    )
+    .pubkey
+}
+
+/// Owner of the destination token account, who receives the unwrapped SOL
+fn destination_wallet(ctx: &TestContext, debit_context: &DebitUserContext) -> Pubkey {
+    get_spl_account::<spl_token::state::Account>(&ctx.svm, &debit_context.destination_token_account)
+        .unwrap()
+        .owner
+}
+
+#[allow(clippy::result_large_err)]
+fn debit_user_sol(
+    ctx: &mut TestContext,
+    debit_context: &DebitUserContext,
+    amount: u64,
+) -> TransactionResult {
+    ctx.svm.expire_blockhash();
+    let accounts = DebitUserSol {
+        debit: make_debit_user_accounts(ctx, debit_context),
+        rent_payer: ctx.payer_pk,
+        destination_wallet: destination_wallet(ctx, debit_context),
+        temporary_token_account: make_temporary_token_account_pda(ctx, debit_context),
+        system_program: anchor_lang::system_program::ID,
+    };
+    let ix = create_debit_user_sol_instruction(ctx, &accounts, TEST_MERCHANT_ID, amount);
+    let tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &debit_context.debitor_kp],
+    );
+    submit_transaction(ctx, tx)
+}
+
+fn assert_error(result: TransactionResult, expected_message: String) {
+    let err = result.expect_err("Transaction should fail");
+    assert!(
+        err.meta
+            .logs
+            .iter()
+            .any(|log| log.contains(&expected_message)),
+        "Error should contain the expected error message {}, got {}",
+        expected_message,
+        err.meta.logs.join("\n")
+    );
+}
+
+fn lamports(ctx: &TestContext, account: &Pubkey) -> u64 {
+    ctx.svm
+        .get_account(account)
+        .map_or(0, |account| account.lamports)
+}
+
+#[tokio::test]
+async fn test_debit_user_sol() {
+    let mut ctx = setup_and_initialize();
+    let debit_context = setup_sol_delegate(&mut ctx);
+    let wallet = destination_wallet(&ctx, &debit_context);
+    let wallet_lamports_before = lamports(&ctx, &wallet);
+
+    // A debit of exactly the per-transfer limit is allowed
+    let result = debit_user_sol(&mut ctx, &debit_context, MAX_TRANSFER_LIMIT);
+    assert!(result.is_ok(), "SOL debit failed: {:?}", result.err());
+
+    let event =
+        find_user_debited_event(&result.unwrap().logs).expect("UserDebited event not found");
+    assert_eq!(event.mint, debit_context.mint_pk);
+    assert_eq!(event.amount, MAX_TRANSFER_LIMIT);
+    assert_eq!(
+        event.destination_ata,
+        debit_context.destination_token_account
+    );
+
+    // The merchant is paid in SOL and the temporary token account is closed
+    assert_eq!(
+        lamports(&ctx, &wallet),
+        wallet_lamports_before + MAX_TRANSFER_LIMIT
+    );
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.user_token_account,
+        INITIAL_BALANCE - MAX_TRANSFER_LIMIT,
+        debit_context.token_program,
+        "User wrapped SOL balance should be reduced by the debit",
+    );
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.destination_token_account,
+        0,
+        debit_context.token_program,
+        "Destination token account should not receive wrapped SOL",
+    );
+    let temporary_token_account = make_temporary_token_account_pda(&ctx, &debit_context);
+    assert_eq!(lamports(&ctx, &temporary_token_account), 0);
+}
+
+#[tokio::test]
+async fn test_debit_user_sol_exceeds_max_transfer_limit() {
+    let mut ctx = setup_and_initialize();
+    let debit_context = setup_sol_delegate(&mut ctx);
+    let wallet = destination_wallet(&ctx, &debit_context);
+    let wallet_lamports_before = lamports(&ctx, &wallet);
+
+    assert_error(
+        debit_user_sol(&mut ctx, &debit_context, MAX_TRANSFER_LIMIT + 1),
+        ErrorCode::ExceedsMaxTransferLimit.to_string(),
+    );
+
+    // No SOL moved
+    assert_eq!(lamports(&ctx, &wallet), wallet_lamports_before);
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.user_token_account,
+        INITIAL_BALANCE,
+        debit_context.token_program,
+        "User wrapped SOL balance should be unchanged",
+    );
+}
+
+#[tokio::test]
+async fn test_debit_user_sol_exceeds_period_limit() {
+    let mut ctx = setup_and_initialize();
+    let debit_context = setup_sol_delegate(&mut ctx);
+
+    for _ in 0..PERIOD_TRANSFER_LIMIT / MAX_TRANSFER_LIMIT {
+        debit_user_sol(&mut ctx, &debit_context, MAX_TRANSFER_LIMIT).unwrap();
+        advance_slots(&mut ctx, 1);
+    }
+    assert_error(
+        debit_user_sol(&mut ctx, &debit_context, 1),
+        ErrorCode::ExceedsTransferLimitPerPeriod.to_string(),
+    );
+}
+
+#[tokio::test]
+async fn test_debit_user_sol_with_prefunded_temporary_account() {
+    let mut ctx = setup_and_initialize();
+    let debit_context = setup_sol_delegate(&mut ctx);
+
+    // Lamports sent to the temporary account address must not block debits
+    let temporary_token_account = make_temporary_token_account_pda(&ctx, &debit_context);
+    ctx.svm.airdrop(&temporary_token_account, 1_000).unwrap();
+
+    let result = debit_user_sol(&mut ctx, &debit_context, MAX_TRANSFER_LIMIT);
+    assert!(result.is_ok(), "SOL debit failed: {:?}", result.err());
+    assert_eq!(lamports(&ctx, &temporary_token_account), 0);
+}
+
+#[tokio::test]
+async fn test_debit_user_sol_rejects_other_mints() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+
+    assert_error(
+        debit_user_sol(&mut ctx, &debit_context, 1),
+        ErrorCode::NotNativeMint.to_string(),
+    );
+}
@@ -1,22 +1,26 @@
 use crate::common::*;
 use anchor_lang::prelude::*;
-use bridge_cards::accounts::DebitUser;
+use bridge_cards::accounts::{AddOrUpdateMerchantDestination, AddOrUpdateUserDelegate, DebitUser};
 use bridge_cards::errors::ErrorCode;
-use bridge_cards::state::UserDelegateState;
-use litesvm_token::spl_token;
+use bridge_cards::event_parsing::{parse_events, BridgeCardsEvent};
+use bridge_cards::events::{UserDebited, UserDelegatePeriodReset};
+use bridge_cards::instructions::add_or_update_merchant_config::MerchantConfigParams;
+use bridge_cards::instructions::add_or_update_user_delegate::UserDelegateOptions;
+use bridge_cards::limit_check::UNLIMITED_PERIOD_TRANSFER_LIMIT;
+use bridge_cards::state::{
+    feature_flags, DebitReceiptState, DestinationPolicy, MerchantDebitorState,
+    MerchantDestinationState, UserDelegateState, CURRENT_VERSION, MS_PER_SLOT,
+};
 use litesvm_token::CreateAssociatedTokenAccountIdempotent;
 use litesvm_token::*;
 use solana_program_test::tokio;
-use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::signature::Signer;
 
-type TestContext = crate::common::Context;
-
-const TEST_MERCHANT_ID: u64 = 1u64;
 const MAX_TRANSFER_LIMIT: u64 = 100_000_000; // $100 per transaction
 const PERIOD_TRANSFER_LIMIT: u64 = 2_000_000_000; // $2000 per day
-const INITIAL_BALANCE: u64 = 5_000_000_000; // $5000 initial balance
 const DEBIT_AMOUNT: u64 = 50_000_000; // $50 debit amount
-const LIMIT_PERIOD: u32 = 86400; // 1 day in seconds
+
+type TestContext = crate::common::Context;
 
 // Macro to generate parameterized tests for both TOKEN and TOKEN22 programs.
 macro_rules! parameterized_token_test {
@@ -35,155 +39,6 @@ macro_rules! parameterized_token_test {
     };
 }
 
-struct DebitUserContext {
-    mint_pk: Pubkey,
-    debitor_pk: Pubkey,
-    debitor_kp: Keypair,
-    debitor_state_pda: Pubkey,
-    destination_state_pda: Pubkey,
-    user_token_account: Pubkey,
-    destination_token_account: Pubkey,
-    user_delegate_pda: Pubkey,
-    token_program: TokenProgram,
-}
-
-fn setup_merchant_and_user_delegate(
-    ctx: &mut TestContext,
-    max_transfer_limit: u64,
-    period_transfer_limit: u64,
-) -> DebitUserContext {
-    setup_merchant_and_user_delegate_with_program(
-        ctx,
-        max_transfer_limit,
-        period_transfer_limit,
-        TokenProgram::Token,
-    )
-}
-
-fn setup_merchant_and_user_delegate_with_program(
-    ctx: &mut TestContext,
-    max_transfer_limit: u64,
-    period_transfer_limit: u64,
-    token_program: TokenProgram,
-) -> DebitUserContext {
-    // Create a token mint
-    let mint_pk = setup_mint_with_program(ctx, token_program);
-
-    // Setup merchant
-    let (debitor_kp, debitor_pk) = setup_keypair(ctx);
-    let (_, destination_pk) = setup_keypair(ctx);
-
-    let (debitor_state_pda, destination_state_pda, destination_token_account) =
-        setup_merchant_debitor_and_destination(
-            ctx,
-            TEST_MERCHANT_ID,
-            debitor_pk,
-            &mint_pk,
-            &destination_pk,
-        );
-
-    // Create user and their token account
-    let (user_kp, user_pk) = setup_keypair(ctx);
-
-    // Create token account for the user with initial balance
-    let user_token_account =
-        CreateAssociatedTokenAccountIdempotent::new(&mut ctx.svm, &ctx.payer_kp, &mint_pk)
-            .owner(&user_pk)
-            .send()
-            .unwrap();
-
-    // Fund the user's token account
-    MintTo::new(
-        &mut ctx.svm,
-        &ctx.payer_kp,
-        &mint_pk,
-        &user_token_account,
-        INITIAL_BALANCE,
-    )
-    .send()
-    .unwrap();
-
-    // Create the user delegate account
-    let user_delegate_pda = make_user_delegate_pda(
-        TEST_MERCHANT_ID,
-        &mint_pk,
-        &user_token_account,
-        &ctx.program_id,
-    );
-
-    // checked-approve the user delegate pda for the user token account
-    ApproveChecked::new(
-        &mut ctx.svm,
-        &user_kp,
-        &user_delegate_pda.pubkey,
-        &mint_pk,
-        1e18 as u64,
-    )
-    .send()
-    .unwrap();
-
-    let user_delegate_accounts = bridge_cards::accounts::AddOrUpdateUserDelegate {
-        manager: ctx.merchant_manager_kp.pubkey(),
-        manager_state: ctx.merchant_manager_state.pubkey,
-        payer: ctx.payer_pk,
-        user_token_account,
-        mint: mint_pk,
-        user_delegate_account: user_delegate_pda.pubkey,
-        system_program: System::id(),
-    };
-
-    let user_delegate_ix = create_add_or_update_user_delegate_instruction(
-        ctx,
-        &user_delegate_accounts,
-        TEST_MERCHANT_ID,
-        max_transfer_limit,
-        period_transfer_limit,
-        LIMIT_PERIOD,
-    );
-    let user_delegate_tx = create_transaction_with_payer_and_signers(
-        ctx,
-        &[user_delegate_ix],
-        Some(&ctx.payer_pk),
-        &[&ctx.payer_kp, &ctx.merchant_manager_kp],
-    );
-
-    submit_transaction(ctx, user_delegate_tx).unwrap();
-
-    DebitUserContext {
-        mint_pk,
-        debitor_pk,
-        debitor_kp,
-        debitor_state_pda,
-        destination_state_pda,
-        user_token_account,
-        destination_token_account,
-        user_delegate_pda: user_delegate_pda.pubkey,
-        token_program,
-    }
-}
-
-/// Helper function to verify token account balance based on token program
-fn verify_token_account_balance(
-    ctx: &TestContext,
-    token_account: &Pubkey,
-    expected_amount: u64,
-    token_program: TokenProgram,
-    error_msg: &str,
-) {
-    match token_program {
-        TokenProgram::Token => {
-            let account_info =
-                get_spl_account::<spl_token::state::Account>(&ctx.svm, token_account).unwrap();
-            assert_eq!(account_info.amount, expected_amount, "{}", error_msg);
-        }
-        TokenProgram::Token2022 => {
-            let account_info =
-                get_spl_account::<spl_token_2022::state::Account>(&ctx.svm, token_account).unwrap();
-            assert_eq!(account_info.amount, expected_amount, "{}", error_msg);
-        }
-    }
-}
-
 parameterized_token_test!(
     test_debit_user_successful,
     |token_program: TokenProgram| async move {
@@ -204,11 +59,22 @@ parameterized_token_test!(
             user_delegate_account: debit_context.user_delegate_pda,
             debitor_state: debit_context.debitor_state_pda,
             destination_state: debit_context.destination_state_pda,
+            merchant_config: make_merchant_config_pda(TEST_MERCHANT_ID, &ctx.program_id).pubkey,
+            state: ctx.bridge_cards_state.pubkey,
             user_token_account: debit_context.user_token_account,
+            user_owner: debit_context.user_pk,
             destination_token_account: debit_context.destination_token_account,
             mint: debit_context.mint_pk,
             system_program: System::id(),
             token_program: token_program.program_id(),
+            merchant_spend_tracker: make_merchant_spend_tracker_pda(
+                TEST_MERCHANT_ID,
+                &ctx.program_id,
+            )
+            .pubkey,
+            fee_token_account: None,
+            merchant_mint: None,
+            debit_receipt: None,
         };
 
         let debit_ix = create_debit_user_instruction_with_program(
@@ -292,11 +158,22 @@ parameterized_token_test!(
             user_delegate_account: debit_context.user_delegate_pda,
             debitor_state: debit_context.debitor_state_pda,
             destination_state: debit_context.destination_state_pda,
+            merchant_config: make_merchant_config_pda(TEST_MERCHANT_ID, &ctx.program_id).pubkey,
+            state: ctx.bridge_cards_state.pubkey,
             user_token_account: debit_context.user_token_account,
+            user_owner: debit_context.user_pk,
             destination_token_account: debit_context.destination_token_account,
             mint: debit_context.mint_pk,
             system_program: System::id(),
             token_program: token_program.program_id(),
+            merchant_spend_tracker: make_merchant_spend_tracker_pda(
+                TEST_MERCHANT_ID,
+                &ctx.program_id,
+            )
+            .pubkey,
+            fee_token_account: None,
+            merchant_mint: None,
+            debit_receipt: None,
         };
 
         let excessive_amount = MAX_TRANSFER_LIMIT + 1;
@@ -368,11 +245,22 @@ parameterized_token_test!(
             user_delegate_account: debit_context.user_delegate_pda,
             debitor_state: debit_context.debitor_state_pda,
             destination_state: debit_context.destination_state_pda,
+            merchant_config: make_merchant_config_pda(TEST_MERCHANT_ID, &ctx.program_id).pubkey,
+            state: ctx.bridge_cards_state.pubkey,
             user_token_account: debit_context.user_token_account,
+            user_owner: debit_context.user_pk,
             destination_token_account: debit_context.destination_token_account,
             mint: debit_context.mint_pk,
             system_program: System::id(),
             token_program: token_program.program_id(),
+            merchant_spend_tracker: make_merchant_spend_tracker_pda(
+                TEST_MERCHANT_ID,
+                &ctx.program_id,
+            )
+            .pubkey,
+            fee_token_account: None,
+            merchant_mint: None,
+            debit_receipt: None,
         };
 
         let debit_ix = create_debit_user_instruction_with_program(
@@ -427,11 +315,22 @@ parameterized_token_test!(
             user_delegate_account: debit_context.user_delegate_pda,
             debitor_state: debit_context.debitor_state_pda,
             destination_state: debit_context.destination_state_pda,
+            merchant_config: make_merchant_config_pda(TEST_MERCHANT_ID, &ctx.program_id).pubkey,
+            state: ctx.bridge_cards_state.pubkey,
             user_token_account: debit_context.user_token_account,
+            user_owner: debit_context.user_pk,
             destination_token_account: debit_context.destination_token_account,
             mint: debit_context.mint_pk,
             system_program: System::id(),
             token_program: token_program.program_id(),
+            merchant_spend_tracker: make_merchant_spend_tracker_pda(
+                TEST_MERCHANT_ID,
+                &ctx.program_id,
+            )
+            .pubkey,
+            fee_token_account: None,
+            merchant_mint: None,
+            debit_receipt: None,
         };
 
         // First debit
@@ -539,11 +438,22 @@ parameterized_token_test!(
             user_delegate_account: debit_context.user_delegate_pda,
             debitor_state: debit_context.debitor_state_pda,
             destination_state: debit_context.destination_state_pda,
+            merchant_config: make_merchant_config_pda(TEST_MERCHANT_ID, &ctx.program_id).pubkey,
+            state: ctx.bridge_cards_state.pubkey,
             user_token_account: debit_context.user_token_account,
+            user_owner: debit_context.user_pk,
             destination_token_account: debit_context.destination_token_account,
             mint: debit_context.mint_pk,
             system_program: System::id(),
             token_program: token_program.program_id(),
+            merchant_spend_tracker: make_merchant_spend_tracker_pda(
+                TEST_MERCHANT_ID,
+                &ctx.program_id,
+            )
+            .pubkey,
+            fee_token_account: None,
+            merchant_mint: None,
+            debit_receipt: None,
         };
 
         // First debit - half of period limit
@@ -635,11 +545,22 @@ parameterized_token_test!(
             user_delegate_account: debit_context.user_delegate_pda,
             debitor_state: debit_context.debitor_state_pda,
             destination_state: debit_context.destination_state_pda,
+            merchant_config: make_merchant_config_pda(TEST_MERCHANT_ID, &ctx.program_id).pubkey,
+            state: ctx.bridge_cards_state.pubkey,
             user_token_account: debit_context.user_token_account,
+            user_owner: debit_context.user_pk,
             destination_token_account: debit_context.destination_token_account,
             mint: debit_context.mint_pk,
             system_program: System::id(),
             token_program: token_program.program_id(),
+            merchant_spend_tracker: make_merchant_spend_tracker_pda(
+                TEST_MERCHANT_ID,
+                &ctx.program_id,
+            )
+            .pubkey,
+            fee_token_account: None,
+            merchant_mint: None,
+            debit_receipt: None,
         };
 
         // Use incorrect merchant_id (different from TEST_MERCHANT_ID)
@@ -707,11 +628,22 @@ parameterized_token_test!(
             user_delegate_account: debit_context.user_delegate_pda,
             debitor_state: debit_context.debitor_state_pda,
             destination_state: debit_context.destination_state_pda,
+            merchant_config: make_merchant_config_pda(TEST_MERCHANT_ID, &ctx.program_id).pubkey,
+            state: ctx.bridge_cards_state.pubkey,
             user_token_account: debit_context.user_token_account,
+            user_owner: debit_context.user_pk,
             destination_token_account: invalid_destination_token_account, // Wrong destination
             mint: debit_context.mint_pk,
             system_program: System::id(),
             token_program: token_program.program_id(),
+            merchant_spend_tracker: make_merchant_spend_tracker_pda(
+                TEST_MERCHANT_ID,
+                &ctx.program_id,
+            )
+            .pubkey,
+            fee_token_account: None,
+            merchant_mint: None,
+            debit_receipt: None,
         };
 
         let debit_ix = create_debit_user_instruction_with_program(
@@ -816,11 +748,14 @@ parameterized_token_test!(
         let user_delegate_accounts = bridge_cards::accounts::AddOrUpdateUserDelegate {
             manager: ctx.merchant_manager_kp.pubkey(),
             manager_state: ctx.merchant_manager_state.pubkey,
+            merchant_config: make_merchant_config_pda(TEST_MERCHANT_ID, &ctx.program_id).pubkey,
             payer: payer_pk,
             user_token_account,
             mint: mint_pk,
             user_delegate_account: user_delegate_pda.pubkey,
             system_program: System::id(),
+            merchant_mint: None,
+            merchant_registry: None,
         };
 
         let user_delegate_ix = create_add_or_update_user_delegate_instruction(
@@ -847,11 +782,22 @@ parameterized_token_test!(
             user_delegate_account: user_delegate_pda.pubkey,
             debitor_state: debit_context.debitor_state_pda,
             destination_state: debit_context.destination_state_pda,
+            merchant_config: make_merchant_config_pda(TEST_MERCHANT_ID, &ctx.program_id).pubkey,
+            state: ctx.bridge_cards_state.pubkey,
             user_token_account,
+            user_owner: user_pk,
             destination_token_account,
             mint: mint_pk,
             system_program: System::id(),
             token_program: token_program.program_id(),
+            merchant_spend_tracker: make_merchant_spend_tracker_pda(
+                TEST_MERCHANT_ID,
+                &ctx.program_id,
+            )
+            .pubkey,
+            fee_token_account: None,
+            merchant_mint: None,
+            debit_receipt: None,
         };
 
         let debit_ix = create_debit_user_instruction_with_program(
@@ -902,18 +848,75 @@ parameterized_token_test!(
         // Create a different mint
         let different_mint_pk = setup_mint_with_program(&mut ctx, token_program);
 
+        // Authorize the debitor, a destination and a delegate for the different mint, so
+        // only the user token account's mint is wrong
+        let (_, destination_pk) = setup_keypair(&mut ctx);
+        let (debitor_state_pda, destination_state_pda, destination_token_account) =
+            setup_merchant_debitor_and_destination_with_program(
+                &mut ctx,
+                TEST_MERCHANT_ID,
+                debit_context.debitor_pk,
+                &different_mint_pk,
+                &destination_pk,
+                token_program,
+            );
+        let user_delegate_pda = make_user_delegate_pda(
+            TEST_MERCHANT_ID,
+            &different_mint_pk,
+            &debit_context.user_token_account,
+            &ctx.program_id,
+        );
+        let delegate_accounts = AddOrUpdateUserDelegate {
+            manager: ctx.merchant_manager_kp.pubkey(),
+            manager_state: ctx.merchant_manager_state.pubkey,
+            merchant_config: make_merchant_config_pda(TEST_MERCHANT_ID, &ctx.program_id).pubkey,
+            payer: ctx.payer_pk,
+            user_token_account: debit_context.user_token_account,
+            mint: different_mint_pk,
+            user_delegate_account: user_delegate_pda.pubkey,
+            system_program: System::id(),
+            merchant_mint: None,
+            merchant_registry: None,
+        };
+        let delegate_ix = create_add_or_update_user_delegate_instruction(
+            &ctx,
+            &delegate_accounts,
+            TEST_MERCHANT_ID,
+            MAX_TRANSFER_LIMIT,
+            PERIOD_TRANSFER_LIMIT,
+            LIMIT_PERIOD,
+        );
+        let delegate_tx = create_transaction_with_payer_and_signers(
+            &ctx,
+            &[delegate_ix],
+            Some(&ctx.payer_pk),
+            &[&ctx.payer_kp, &ctx.merchant_manager_kp],
+        );
+        submit_transaction(&mut ctx, delegate_tx).unwrap();
+
         // Try to debit with incorrect mint
         let debit_accounts = DebitUser {
             debitor: debit_context.debitor_pk,
             payer: ctx.payer_pk,
-            user_delegate_account: debit_context.user_delegate_pda,
-            debitor_state: debit_context.debitor_state_pda,
-            destination_state: debit_context.destination_state_pda,
+            user_delegate_account: user_delegate_pda.pubkey,
+            debitor_state: debitor_state_pda,
+            destination_state: destination_state_pda,
+            merchant_config: make_merchant_config_pda(TEST_MERCHANT_ID, &ctx.program_id).pubkey,
+            state: ctx.bridge_cards_state.pubkey,
             user_token_account: debit_context.user_token_account,
-            destination_token_account: debit_context.destination_token_account,
+            user_owner: debit_context.user_pk,
+            destination_token_account,
             mint: different_mint_pk, // Wrong mint
             system_program: System::id(),
             token_program: token_program.program_id(),
+            merchant_spend_tracker: make_merchant_spend_tracker_pda(
+                TEST_MERCHANT_ID,
+                &ctx.program_id,
+            )
+            .pubkey,
+            fee_token_account: None,
+            merchant_mint: None,
+            debit_receipt: None,
         };
 
         let debit_ix = create_debit_user_instruction_with_program(
@@ -930,11 +933,18 @@ parameterized_token_test!(
             &[&ctx.payer_kp, &debit_context.debitor_kp],
         );
 
-        // Execute the transaction - should fail with constraint or seed derivation error
+        // Execute the transaction - should fail with MismatchedMint
         let result = submit_transaction(&mut ctx, debit_tx);
+        let err = result.expect_err("Transaction should fail due to incorrect mint");
+        let expected_message = ErrorCode::MismatchedMint.to_string();
         assert!(
-            result.is_err(),
-            "Transaction should fail due to incorrect mint"
+            err.meta
+                .logs
+                .iter()
+                .any(|log| log.contains(&expected_message)),
+            "Error should contain the expected error message {}, got {}",
+            expected_message,
+            err.meta.logs.join("\n")
         );
 
         // Verify user token account balance remains unchanged
@@ -968,11 +978,22 @@ parameterized_token_test!(
             user_delegate_account: debit_context.user_delegate_pda,
             debitor_state: debit_context.debitor_state_pda,
             destination_state: debit_context.destination_state_pda,
+            merchant_config: make_merchant_config_pda(TEST_MERCHANT_ID, &ctx.program_id).pubkey,
+            state: ctx.bridge_cards_state.pubkey,
             user_token_account: debit_context.user_token_account,
+            user_owner: debit_context.user_pk,
             destination_token_account: debit_context.destination_token_account,
             mint: debit_context.mint_pk,
             system_program: System::id(),
             token_program: token_program.program_id(),
+            merchant_spend_tracker: make_merchant_spend_tracker_pda(
+                TEST_MERCHANT_ID,
+                &ctx.program_id,
+            )
+            .pubkey,
+            fee_token_account: None,
+            merchant_mint: None,
+            debit_receipt: None,
         };
 
         // First debit - half of period limit
@@ -1061,11 +1082,22 @@ parameterized_token_test!(
             user_delegate_account: debit_context.user_delegate_pda,
             debitor_state: debit_context.debitor_state_pda,
             destination_state: debit_context.destination_state_pda,
+            merchant_config: make_merchant_config_pda(TEST_MERCHANT_ID, &ctx.program_id).pubkey,
+            state: ctx.bridge_cards_state.pubkey,
             user_token_account: debit_context.user_token_account,
+            user_owner: debit_context.user_pk,
             destination_token_account: debit_context.destination_token_account,
             mint: debit_context.mint_pk,
             system_program: System::id(),
             token_program: token_program.program_id(),
+            merchant_spend_tracker: make_merchant_spend_tracker_pda(
+                TEST_MERCHANT_ID,
+                &ctx.program_id,
+            )
+            .pubkey,
+            fee_token_account: None,
+            merchant_mint: None,
+            debit_receipt: None,
         };
 
         // Perform multiple small debits within the period
@@ -1164,11 +1196,22 @@ parameterized_token_test!(
             user_delegate_account: debit_context.user_delegate_pda,
             debitor_state: debit_context.debitor_state_pda,
             destination_state: debit_context.destination_state_pda,
+            merchant_config: make_merchant_config_pda(TEST_MERCHANT_ID, &ctx.program_id).pubkey,
+            state: ctx.bridge_cards_state.pubkey,
             user_token_account: debit_context.user_token_account,
+            user_owner: debit_context.user_pk,
             destination_token_account: debit_context.destination_token_account,
             mint: debit_context.mint_pk,
             system_program: System::id(),
             token_program: token_program.program_id(),
+            merchant_spend_tracker: make_merchant_spend_tracker_pda(
+                TEST_MERCHANT_ID,
+                &ctx.program_id,
+            )
+            .pubkey,
+            fee_token_account: None,
+            merchant_mint: None,
+            debit_receipt: None,
         };
 
         // First debit
@@ -1269,3 +1312,1857 @@ parameterized_token_test!(
         );
     }
 );
+
+parameterized_token_test!(
+    test_debit_user_wallet_owner_when_system_owned_required,
+    |token_program: TokenProgram| async move {
+        let mut ctx = setup_and_initialize();
+        let debit_context = setup_merchant_and_user_delegate_with_program(
+            &mut ctx,
+            MAX_TRANSFER_LIMIT,
+            PERIOD_TRANSFER_LIMIT,
+            token_program,
+        );
+        setup_merchant_config(
+            &mut ctx,
+            TEST_MERCHANT_ID,
+            MerchantConfigParams {
+                require_user_owner_system_owned: true,
+                ..Default::default()
+            },
+        );
+
+        let debit_accounts = make_debit_user_accounts(&ctx, &debit_context);
+        let debit_ix = create_debit_user_instruction_with_program(
+            &ctx,
+            &debit_accounts,
+            TEST_MERCHANT_ID,
+            DEBIT_AMOUNT,
+            token_program,
+        );
+        let debit_tx = create_transaction_with_payer_and_signers(
+            &ctx,
+            &[debit_ix],
+            Some(&ctx.payer_pk),
+            &[&ctx.payer_kp, &debit_context.debitor_kp],
+        );
+
+        // A regular wallet is owned by the system program
+        let result = submit_transaction(&mut ctx, debit_tx);
+        assert!(result.is_ok(), "Failed to debit user: {:?}", result.err());
+
+        verify_token_account_balance(
+            &ctx,
+            &debit_context.user_token_account,
+            INITIAL_BALANCE - DEBIT_AMOUNT,
+            token_program,
+            "User token account balance incorrect",
+        );
+    }
+);
+
+parameterized_token_test!(
+    test_debit_user_program_owned_owner_when_system_owned_required,
+    |token_program: TokenProgram| async move {
+        let mut ctx = setup_and_initialize();
+        let debit_context = setup_merchant_and_user_delegate_with_program(
+            &mut ctx,
+            MAX_TRANSFER_LIMIT,
+            PERIOD_TRANSFER_LIMIT,
+            token_program,
+        );
+        setup_merchant_config(
+            &mut ctx,
+            TEST_MERCHANT_ID,
+            MerchantConfigParams {
+                require_user_owner_system_owned: true,
+                ..Default::default()
+            },
+        );
+
+        // Reassign the user's wallet to another program, as if it were a program PDA
+        let mut user_owner_account = ctx.svm.get_account(&debit_context.user_pk).unwrap();
+        user_owner_account.owner = MOCK_ROUTER_PROGRAM_ID;
+        ctx.svm
+            .set_account(debit_context.user_pk, user_owner_account)
+            .unwrap();
+
+        let debit_accounts = make_debit_user_accounts(&ctx, &debit_context);
+        let debit_ix = create_debit_user_instruction_with_program(
+            &ctx,
+            &debit_accounts,
+            TEST_MERCHANT_ID,
+            DEBIT_AMOUNT,
+            token_program,
+        );
+        let debit_tx = create_transaction_with_payer_and_signers(
+            &ctx,
+            &[debit_ix],
+            Some(&ctx.payer_pk),
+            &[&ctx.payer_kp, &debit_context.debitor_kp],
+        );
+
+        let result = submit_transaction(&mut ctx, debit_tx);
+        assert!(
+            result.is_err(),
+            "Transaction should fail due to a program-owned token account owner"
+        );
+
+        let err = result.err().unwrap();
+        let expected_message = ErrorCode::UserOwnerNotSystemOwned.to_string();
+        assert!(
+            err.meta
+                .logs
+                .iter()
+                .any(|log| log.contains(&expected_message)),
+            "Error should contain the expected error message {}, got {}",
+            expected_message,
+            err.meta.logs.join("\n")
+        );
+
+        verify_token_account_balance(
+            &ctx,
+            &debit_context.user_token_account,
+            INITIAL_BALANCE,
+            token_program,
+            "User token account balance should remain unchanged",
+        );
+    }
+);
+
+parameterized_token_test!(
+    test_debit_user_merchant_min_slots_between_debits,
+    |token_program: TokenProgram| async move {
+        let mut ctx = setup_and_initialize();
+        let debit_context = setup_merchant_and_user_delegate_with_program(
+            &mut ctx,
+            MAX_TRANSFER_LIMIT,
+            PERIOD_TRANSFER_LIMIT,
+            token_program,
+        );
+        setup_merchant_config(
+            &mut ctx,
+            TEST_MERCHANT_ID,
+            MerchantConfigParams {
+                min_slots_between_debits: 5,
+                ..Default::default()
+            },
+        );
+
+        let result = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT);
+        assert!(result.is_ok(), "First debit failed: {:?}", result.err());
+
+        // Still inside the merchant's slot window
+        advance_slots(&mut ctx, 4);
+        let result = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT);
+        let err = result.expect_err("Debit inside the slot window should fail");
+        let expected_message = ErrorCode::ExceedsMaxTransactionsPerSlot.to_string();
+        assert!(
+            err.meta
+                .logs
+                .iter()
+                .any(|log| log.contains(&expected_message)),
+            "Error should contain the expected error message {}, got {}",
+            expected_message,
+            err.meta.logs.join("\n")
+        );
+
+        // Window elapsed
+        advance_slots(&mut ctx, 1);
+        let result = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT);
+        assert!(
+            result.is_ok(),
+            "Debit after the window failed: {:?}",
+            result.err()
+        );
+
+        verify_token_account_balance(
+            &ctx,
+            &debit_context.user_token_account,
+            INITIAL_BALANCE - DEBIT_AMOUNT * 2,
+            token_program,
+            "User token account balance should reflect two debits",
+        );
+    }
+);
+
+parameterized_token_test!(
+    test_debit_user_delegate_min_slots_overrides_merchant,
+    |token_program: TokenProgram| async move {
+        let mut ctx = setup_and_initialize();
+        let debit_context = setup_merchant_and_user_delegate_with_options(
+            &mut ctx,
+            MAX_TRANSFER_LIMIT,
+            PERIOD_TRANSFER_LIMIT,
+            token_program,
+            UserDelegateOptions {
+                min_slots_between_debits: 2,
+                ..Default::default()
+            },
+        );
+        setup_merchant_config(
+            &mut ctx,
+            TEST_MERCHANT_ID,
+            MerchantConfigParams {
+                min_slots_between_debits: 5,
+                ..Default::default()
+            },
+        );
+
+        let result = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT);
+        assert!(result.is_ok(), "First debit failed: {:?}", result.err());
+
+        // Inside the delegate's window
+        advance_slots(&mut ctx, 1);
+        let result = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT);
+        assert!(
+            result.is_err(),
+            "Debit inside the delegate's slot window should fail"
+        );
+
+        // The delegate's shorter window applies instead of the merchant's
+        advance_slots(&mut ctx, 1);
+        let result = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT);
+        assert!(
+            result.is_ok(),
+            "Debit after the delegate's window failed: {:?}",
+            result.err()
+        );
+    }
+);
+
+parameterized_token_test!(
+    test_debit_user_primary_destination_when_enforced,
+    |token_program: TokenProgram| async move {
+        let mut ctx = setup_and_initialize();
+        let debit_context = setup_merchant_and_user_delegate_with_program(
+            &mut ctx,
+            MAX_TRANSFER_LIMIT,
+            PERIOD_TRANSFER_LIMIT,
+            token_program,
+        );
+        set_merchant_destination_primary(&mut ctx, &debit_context, true);
+        setup_merchant_config(
+            &mut ctx,
+            TEST_MERCHANT_ID,
+            MerchantConfigParams {
+                enforce_primary_destination: true,
+                ..Default::default()
+            },
+        );
+
+        let result = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT);
+        assert!(
+            result.is_ok(),
+            "Debit to the primary destination failed: {:?}",
+            result.err()
+        );
+
+        verify_token_account_balance(
+            &ctx,
+            &debit_context.destination_token_account,
+            DEBIT_AMOUNT,
+            token_program,
+            "Destination token account balance incorrect",
+        );
+    }
+);
+
+parameterized_token_test!(
+    test_debit_user_non_primary_destination_when_enforced,
+    |token_program: TokenProgram| async move {
+        let mut ctx = setup_and_initialize();
+        let debit_context = setup_merchant_and_user_delegate_with_program(
+            &mut ctx,
+            MAX_TRANSFER_LIMIT,
+            PERIOD_TRANSFER_LIMIT,
+            token_program,
+        );
+        setup_merchant_config(
+            &mut ctx,
+            TEST_MERCHANT_ID,
+            MerchantConfigParams {
+                enforce_primary_destination: true,
+                ..Default::default()
+            },
+        );
+
+        let result = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT);
+        let err = result.expect_err("Debit to a non-primary destination should fail");
+        let expected_message = ErrorCode::NonPrimaryDestination.to_string();
+        assert!(
+            err.meta
+                .logs
+                .iter()
+                .any(|log| log.contains(&expected_message)),
+            "Error should contain the expected error message {}, got {}",
+            expected_message,
+            err.meta.logs.join("\n")
+        );
+
+        verify_token_account_balance(
+            &ctx,
+            &debit_context.user_token_account,
+            INITIAL_BALANCE,
+            token_program,
+            "User token account balance should remain unchanged",
+        );
+    }
+);
+
+parameterized_token_test!(
+    test_debit_user_alternate_destination_allowed_by_delegate,
+    |token_program: TokenProgram| async move {
+        let mut ctx = setup_and_initialize();
+        let debit_context = setup_merchant_and_user_delegate_with_options(
+            &mut ctx,
+            MAX_TRANSFER_LIMIT,
+            PERIOD_TRANSFER_LIMIT,
+            token_program,
+            UserDelegateOptions {
+                allow_alternate_destination: true,
+                ..Default::default()
+            },
+        );
+        setup_merchant_config(
+            &mut ctx,
+            TEST_MERCHANT_ID,
+            MerchantConfigParams {
+                enforce_primary_destination: true,
+                ..Default::default()
+            },
+        );
+
+        let result = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT);
+        assert!(
+            result.is_ok(),
+            "Debit to an allowed alternate destination failed: {:?}",
+            result.err()
+        );
+    }
+);
+
+parameterized_token_test!(
+    test_debit_user_cross_program_destination,
+    |token_program: TokenProgram| async move {
+        let mut ctx = setup_and_initialize();
+        let debit_context = setup_merchant_and_user_delegate_with_program(
+            &mut ctx,
+            MAX_TRANSFER_LIMIT,
+            PERIOD_TRANSFER_LIMIT,
+            token_program,
+        );
+
+        // Move the destination under the other token program. The first 165 bytes of a
+        // token account have the same layout in both programs.
+        let other_program = match token_program {
+            TokenProgram::Token => TokenProgram::Token2022,
+            TokenProgram::Token2022 => TokenProgram::Token,
+        };
+        let mut destination_account = ctx
+            .svm
+            .get_account(&debit_context.destination_token_account)
+            .unwrap();
+        destination_account.owner = other_program.program_id();
+        destination_account.data.truncate(165);
+        ctx.svm
+            .set_account(debit_context.destination_token_account, destination_account)
+            .unwrap();
+
+        let result = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT);
+        let err = result.expect_err("Debit across token programs should fail");
+        let expected_message = ErrorCode::CrossProgramTransferNotAllowed.to_string();
+        assert!(
+            err.meta
+                .logs
+                .iter()
+                .any(|log| log.contains(&expected_message)),
+            "Error should contain the expected error message {}, got {}",
+            expected_message,
+            err.meta.logs.join("\n")
+        );
+
+        verify_token_account_balance(
+            &ctx,
+            &debit_context.user_token_account,
+            INITIAL_BALANCE,
+            token_program,
+            "User token account balance should remain unchanged",
+        );
+    }
+);
+
+#[tokio::test]
+async fn test_debit_user_post_condition_with_transfer_fee_mint() {
+    let mut ctx = setup_and_initialize();
+
+    // 1% transfer fee, withheld in the destination account
+    let transfer_fee_basis_points = 100;
+    let mint_pk = setup_transfer_fee_mint(&mut ctx, transfer_fee_basis_points, u64::MAX);
+    let debit_context = setup_merchant_and_user_delegate_with_mint(
+        &mut ctx,
+        MAX_TRANSFER_LIMIT,
+        PERIOD_TRANSFER_LIMIT,
+        TokenProgram::Token2022,
+        mint_pk,
+        UserDelegateOptions::default(),
+    );
+    set_feature_flags(&mut ctx, feature_flags::POST_CONDITION_CHECKS);
+
+    let result = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT);
+    assert!(
+        result.is_ok(),
+        "Debit with post-condition checks failed: {:?}",
+        result.err()
+    );
+
+    // The destination received the amount net of the transfer fee
+    let expected_fee = DEBIT_AMOUNT * transfer_fee_basis_points as u64 / 10_000;
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.user_token_account,
+        INITIAL_BALANCE - DEBIT_AMOUNT,
+        TokenProgram::Token2022,
+        "User token account balance incorrect",
+    );
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.destination_token_account,
+        DEBIT_AMOUNT - expected_fee,
+        TokenProgram::Token2022,
+        "Destination token account should receive the amount net of fees",
+    );
+}
+
+#[tokio::test]
+async fn test_debit_user_merchant_paused() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    setup_merchant_config(
+        &mut ctx,
+        TEST_MERCHANT_ID,
+        MerchantConfigParams {
+            paused: true,
+            ..Default::default()
+        },
+    );
+
+    let result = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT);
+    let err = result.expect_err("Debit should fail while the merchant is paused");
+    let expected_message = ErrorCode::MerchantPaused.to_string();
+    assert!(
+        err.meta
+            .logs
+            .iter()
+            .any(|log| log.contains(&expected_message)),
+        "Error should contain the expected error message {}, got {}",
+        expected_message,
+        err.meta.logs.join("\n")
+    );
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.user_token_account,
+        INITIAL_BALANCE,
+        TokenProgram::Token,
+        "User token account balance should remain unchanged",
+    );
+
+    // Unpausing the merchant lets debits through again
+    ctx.svm.expire_blockhash();
+    setup_merchant_config(
+        &mut ctx,
+        TEST_MERCHANT_ID,
+        MerchantConfigParams {
+            paused: false,
+            ..Default::default()
+        },
+    );
+    let result = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT);
+    assert!(
+        result.is_ok(),
+        "Debit should succeed once the merchant is unpaused: {:?}",
+        result.err()
+    );
+}
+
+fn find_period_reset_event(logs: &[String]) -> Option<UserDelegatePeriodReset> {
+    parse_events(logs)
+        .into_iter()
+        .find_map(|event| match event {
+            BridgeCardsEvent::UserDelegatePeriodReset(event) => Some(event),
+            _ => None,
+        })
+}
+
+#[tokio::test]
+async fn test_debit_user_period_reset_event() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+
+    // The first debit starts the delegate's first period
+    let meta = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT).unwrap();
+    let period_start = ctx.svm.get_sysvar::<Clock>().unix_timestamp as u64;
+    let event = find_period_reset_event(&meta.logs).expect("UserDelegatePeriodReset not found");
+    assert_eq!(event.period_timestamp_last_reset, period_start);
+
+    // At the end of the period the debit still belongs to it
+    let mut clock = ctx.svm.get_sysvar::<Clock>();
+    clock.unix_timestamp += LIMIT_PERIOD as i64;
+    clock.slot += 1;
+    ctx.svm.set_sysvar(&clock);
+    let meta = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT).unwrap();
+    assert!(find_period_reset_event(&meta.logs).is_none());
+
+    // The first debit after the period rolls over
+    let mut clock = ctx.svm.get_sysvar::<Clock>();
+    clock.unix_timestamp += 1;
+    clock.slot += 1;
+    ctx.svm.set_sysvar(&clock);
+    let meta = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT).unwrap();
+    let event = find_period_reset_event(&meta.logs).expect("UserDelegatePeriodReset not found");
+    assert_eq!(event.merchant_id, TEST_MERCHANT_ID);
+    assert_eq!(event.user_delegate, debit_context.user_delegate_pda);
+    assert_eq!(
+        event.period_timestamp_last_reset,
+        clock.unix_timestamp as u64
+    );
+
+    // Later debits in the new period don't reset it again
+    advance_slots(&mut ctx, 1);
+    let meta = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT).unwrap();
+    assert!(find_period_reset_event(&meta.logs).is_none());
+}
+
+#[tokio::test]
+async fn test_debit_user_strict_boundary_fixed_windows() {
+    let mut ctx = setup_and_initialize();
+    // One full debit per period
+    let debit_context = setup_merchant_and_user_delegate_with_options(
+        &mut ctx,
+        MAX_TRANSFER_LIMIT,
+        MAX_TRANSFER_LIMIT,
+        TokenProgram::Token,
+        UserDelegateOptions {
+            strict_boundary: true,
+            ..Default::default()
+        },
+    );
+    let set_time = |ctx: &mut TestContext, unix_timestamp: u64| {
+        let mut clock = ctx.svm.get_sysvar::<Clock>();
+        clock.unix_timestamp = unix_timestamp as i64;
+        clock.slot += 1;
+        ctx.svm.set_sysvar(&clock);
+    };
+    let period = LIMIT_PERIOD as u64;
+
+    // A new delegate's periods are aligned to multiples of the period
+    let meta = debit_user(&mut ctx, &debit_context, MAX_TRANSFER_LIMIT).unwrap();
+    let now = ctx.svm.get_sysvar::<Clock>().unix_timestamp as u64;
+    let event = find_period_reset_event(&meta.logs).expect("UserDelegatePeriodReset not found");
+    let first_boundary = now - now % period;
+    assert_eq!(event.period_timestamp_last_reset, first_boundary);
+
+    // The period's budget is spent until its last second
+    let next_boundary = first_boundary + period;
+    set_time(&mut ctx, next_boundary - 1);
+    assert_debit_error(
+        debit_user(&mut ctx, &debit_context, 1),
+        ErrorCode::ExceedsTransferLimitPerPeriod,
+    );
+
+    // A debit late in the next period doesn't move its start
+    set_time(&mut ctx, next_boundary + period / 2);
+    let meta = debit_user(&mut ctx, &debit_context, MAX_TRANSFER_LIMIT).unwrap();
+    let event = find_period_reset_event(&meta.logs).expect("UserDelegatePeriodReset not found");
+    assert_eq!(event.period_timestamp_last_reset, next_boundary);
+
+    // So a second full debit must wait for the fixed boundary, which starts the new period
+    set_time(&mut ctx, next_boundary + period - 1);
+    assert_debit_error(
+        debit_user(&mut ctx, &debit_context, MAX_TRANSFER_LIMIT),
+        ErrorCode::ExceedsTransferLimitPerPeriod,
+    );
+    set_time(&mut ctx, next_boundary + period);
+    let result = debit_user(&mut ctx, &debit_context, MAX_TRANSFER_LIMIT);
+    assert!(
+        result.is_ok(),
+        "Debit at the fixed boundary should succeed: {:?}",
+        result.err()
+    );
+}
+
+fn find_user_debited_event(logs: &[String]) -> Option<UserDebited> {
+    parse_events(logs)
+        .into_iter()
+        .find_map(|event| match event {
+            BridgeCardsEvent::UserDebited(event) => Some(event),
+            _ => None,
+        })
+}
+
+#[tokio::test]
+async fn test_debit_user_event_slot_and_timestamp() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+
+    let meta = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT).unwrap();
+    let event = find_user_debited_event(&meta.logs).expect("UserDebited event not found");
+
+    let clock = ctx.svm.get_sysvar::<Clock>();
+    assert_eq!(event.amount, DEBIT_AMOUNT);
+    assert_eq!(event.slot, clock.slot);
+    assert_eq!(event.timestamp, clock.unix_timestamp);
+}
+
+#[tokio::test]
+async fn test_debit_user_nonce_increments() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+
+    for expected_nonce in 1..=2 {
+        let meta = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT).unwrap();
+        let event = find_user_debited_event(&meta.logs).expect("UserDebited event not found");
+        assert_eq!(event.debit_nonce, expected_nonce);
+        advance_slots(&mut ctx, 1);
+    }
+
+    // The nonce keeps counting after the period rolls over
+    let mut clock = ctx.svm.get_sysvar::<Clock>();
+    clock.unix_timestamp += LIMIT_PERIOD as i64 + 1;
+    clock.slot += 1;
+    ctx.svm.set_sysvar(&clock);
+    let meta = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT).unwrap();
+    assert!(find_period_reset_event(&meta.logs).is_some());
+    let event = find_user_debited_event(&meta.logs).expect("UserDebited event not found");
+    assert_eq!(event.debit_nonce, 3);
+
+    let user_delegate_account = ctx
+        .svm
+        .get_account(&debit_context.user_delegate_pda)
+        .unwrap();
+    let user_delegate_state =
+        UserDelegateState::try_deserialize(&mut user_delegate_account.data.as_slice()).unwrap();
+    assert_eq!(user_delegate_state.debit_nonce, 3);
+}
+
+/// Debit to a token account of `destination_owner` whose destination state was never created
+#[allow(clippy::result_large_err)]
+fn debit_to_unlisted_owner(
+    ctx: &mut TestContext,
+    debit_context: &DebitUserContext,
+    destination_owner: &Pubkey,
+) -> (Pubkey, litesvm::types::TransactionResult) {
+    let destination_token_account = CreateAssociatedTokenAccountIdempotent::new(
+        &mut ctx.svm,
+        &ctx.payer_kp,
+        &debit_context.mint_pk,
+    )
+    .owner(destination_owner)
+    .send()
+    .unwrap();
+    ctx.svm.expire_blockhash();
+    let destination_state = make_merchant_destination_pda(
+        TEST_MERCHANT_ID,
+        &debit_context.mint_pk,
+        &destination_token_account,
+        &ctx.program_id,
+    );
+    let debit_accounts = DebitUser {
+        destination_token_account,
+        destination_state: destination_state.pubkey,
+        ..make_debit_user_accounts(ctx, debit_context)
+    };
+    let ix = create_debit_user_instruction(ctx, &debit_accounts, TEST_MERCHANT_ID, DEBIT_AMOUNT);
+    let tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &debit_context.debitor_kp],
+    );
+    (destination_token_account, submit_transaction(ctx, tx))
+}
+
+#[tokio::test]
+async fn test_debit_user_allowed_destination_owner() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    let (_, treasury_pk) = setup_keypair(&mut ctx);
+    setup_merchant_config(
+        &mut ctx,
+        TEST_MERCHANT_ID,
+        MerchantConfigParams {
+            allow_any_destination_owner: Some(treasury_pk),
+            ..Default::default()
+        },
+    );
+
+    let (destination_token_account, result) =
+        debit_to_unlisted_owner(&mut ctx, &debit_context, &treasury_pk);
+    assert!(
+        result.is_ok(),
+        "Debit to an account of the allowed owner should succeed: {:?}",
+        result.err()
+    );
+    verify_token_account_balance(
+        &ctx,
+        &destination_token_account,
+        DEBIT_AMOUNT,
+        TokenProgram::Token,
+        "Destination token account balance incorrect",
+    );
+}
+
+#[tokio::test]
+async fn test_debit_user_unlisted_destination_owner_rejected() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    let (_, treasury_pk) = setup_keypair(&mut ctx);
+    let (_, other_pk) = setup_keypair(&mut ctx);
+    setup_merchant_config(
+        &mut ctx,
+        TEST_MERCHANT_ID,
+        MerchantConfigParams {
+            allow_any_destination_owner: Some(treasury_pk),
+            ..Default::default()
+        },
+    );
+
+    let (destination_token_account, result) =
+        debit_to_unlisted_owner(&mut ctx, &debit_context, &other_pk);
+    let err = result.expect_err("Debit to an account of another owner should fail");
+    let expected_message = ErrorCode::DestinationNotAllowed.to_string();
+    assert!(
+        err.meta
+            .logs
+            .iter()
+            .any(|log| log.contains(&expected_message)),
+        "Error should contain the expected error message {}, got {}",
+        expected_message,
+        err.meta.logs.join("\n")
+    );
+    verify_token_account_balance(
+        &ctx,
+        &destination_token_account,
+        0,
+        TokenProgram::Token,
+        "Destination token account balance should remain unchanged",
+    );
+}
+
+/// Replace the user token account's approval with `amount` for `delegate`
+fn approve_delegate(
+    ctx: &mut TestContext,
+    debit_context: &DebitUserContext,
+    delegate: &Pubkey,
+    amount: u64,
+) {
+    ApproveChecked::new(
+        &mut ctx.svm,
+        &debit_context.user_kp,
+        delegate,
+        &debit_context.mint_pk,
+        amount,
+    )
+    .source(&debit_context.user_token_account)
+    .send()
+    .unwrap();
+}
+
+fn assert_debit_error(result: litesvm::types::TransactionResult, error: ErrorCode) {
+    let err = result.expect_err("Debit should fail");
+    let expected_message = error.to_string();
+    assert!(
+        err.meta
+            .logs
+            .iter()
+            .any(|log| log.contains(&expected_message)),
+        "Error should contain the expected error message {}, got {}",
+        expected_message,
+        err.meta.logs.join("\n")
+    );
+}
+
+#[tokio::test]
+async fn test_debit_user_approval_to_other_delegate() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    let (_, other_delegate_pk) = setup_keypair(&mut ctx);
+    approve_delegate(&mut ctx, &debit_context, &other_delegate_pk, 1e18 as u64);
+
+    let result = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT);
+    assert_debit_error(result, ErrorCode::DelegateMismatch);
+}
+
+#[tokio::test]
+async fn test_debit_user_insufficient_delegated_amount() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    let user_delegate_pda = debit_context.user_delegate_pda;
+    approve_delegate(
+        &mut ctx,
+        &debit_context,
+        &user_delegate_pda,
+        DEBIT_AMOUNT - 1,
+    );
+
+    let result = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT);
+    assert_debit_error(result, ErrorCode::InsufficientDelegatedAmount);
+
+    // The exact approved amount can still be debited
+    let result = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT - 1);
+    assert!(
+        result.is_ok(),
+        "Debit within the approval should succeed: {:?}",
+        result.err()
+    );
+}
+
+#[tokio::test]
+async fn test_debit_user_expiry() {
+    let mut ctx = setup_and_initialize();
+    let expires_at = ctx.svm.get_sysvar::<Clock>().unix_timestamp + 3600;
+    let debit_context = setup_merchant_and_user_delegate_with_options(
+        &mut ctx,
+        MAX_TRANSFER_LIMIT,
+        PERIOD_TRANSFER_LIMIT,
+        TokenProgram::Token,
+        UserDelegateOptions {
+            expires_at,
+            ..Default::default()
+        },
+    );
+    let set_time = |ctx: &mut TestContext, unix_timestamp: i64| {
+        let mut clock = ctx.svm.get_sysvar::<Clock>();
+        clock.unix_timestamp = unix_timestamp;
+        clock.slot += 1;
+        ctx.svm.set_sysvar(&clock);
+    };
+
+    // Before expiry
+    set_time(&mut ctx, expires_at - 1);
+    let result = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT);
+    assert!(
+        result.is_ok(),
+        "Debit before expiry should succeed: {:?}",
+        result.err()
+    );
+
+    // expires_at is the last second the delegate can debit
+    set_time(&mut ctx, expires_at);
+    let result = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT);
+    assert!(
+        result.is_ok(),
+        "Debit at expiry should succeed: {:?}",
+        result.err()
+    );
+
+    // After expiry
+    set_time(&mut ctx, expires_at + 1);
+    let result = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT);
+    assert_debit_error(result, ErrorCode::DelegateExpired);
+}
+
+/// Freeze or thaw a token account of a token-2022 mint whose freeze authority is the payer
+fn set_token_account_frozen(
+    ctx: &mut TestContext,
+    debit_context: &DebitUserContext,
+    token_account: &Pubkey,
+    frozen: bool,
+) {
+    let instruction = if frozen {
+        spl_token_2022::instruction::freeze_account
+    } else {
+        spl_token_2022::instruction::thaw_account
+    };
+    let ix = instruction(
+        &spl_token_2022::id(),
+        token_account,
+        &debit_context.mint_pk,
+        &ctx.payer_pk,
+        &[],
+    )
+    .unwrap();
+    let tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp],
+    );
+    submit_transaction(ctx, tx).unwrap();
+}
+
+fn setup_freezable_user_delegate(ctx: &mut TestContext) -> DebitUserContext {
+    let payer_pk = ctx.payer_pk;
+    let mint_pk = CreateMint::new(&mut ctx.svm, &ctx.payer_kp)
+        .token_program_id(&TokenProgram::Token2022.program_id())
+        .freeze_authority(&payer_pk)
+        .decimals(6)
+        .send()
+        .unwrap();
+    setup_merchant_and_user_delegate_with_mint(
+        ctx,
+        MAX_TRANSFER_LIMIT,
+        PERIOD_TRANSFER_LIMIT,
+        TokenProgram::Token2022,
+        mint_pk,
+        UserDelegateOptions::default(),
+    )
+}
+
+#[tokio::test]
+async fn test_debit_user_frozen_user_token_account() {
+    let mut ctx = setup_and_initialize();
+    let debit_context = setup_freezable_user_delegate(&mut ctx);
+    set_token_account_frozen(
+        &mut ctx,
+        &debit_context,
+        &debit_context.user_token_account,
+        true,
+    );
+
+    let result = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT);
+    assert_debit_error(result, ErrorCode::TokenAccountFrozen);
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.user_token_account,
+        INITIAL_BALANCE,
+        TokenProgram::Token2022,
+        "User token account balance should remain unchanged",
+    );
+
+    // Thawing the account lets debits through again
+    set_token_account_frozen(
+        &mut ctx,
+        &debit_context,
+        &debit_context.user_token_account,
+        false,
+    );
+    let result = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT);
+    assert!(
+        result.is_ok(),
+        "Debit should succeed once the account is thawed: {:?}",
+        result.err()
+    );
+}
+
+#[tokio::test]
+async fn test_debit_user_frozen_destination_token_account() {
+    let mut ctx = setup_and_initialize();
+    let debit_context = setup_freezable_user_delegate(&mut ctx);
+    set_token_account_frozen(
+        &mut ctx,
+        &debit_context,
+        &debit_context.destination_token_account,
+        true,
+    );
+
+    let result = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT);
+    assert_debit_error(result, ErrorCode::TokenAccountFrozen);
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.user_token_account,
+        INITIAL_BALANCE,
+        TokenProgram::Token2022,
+        "User token account balance should remain unchanged",
+    );
+}
+
+#[tokio::test]
+async fn test_debit_user_debitor_per_call_limit() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+
+    // The delegate allows DEBIT_AMOUNT but the debitor itself is capped below it
+    let per_call_limit = DEBIT_AMOUNT / 5;
+    set_debitor_limits(&mut ctx, &debit_context, per_call_limit, 0);
+    let debitor_state = MerchantDebitorState::try_deserialize(
+        &mut ctx
+            .svm
+            .get_account(&debit_context.debitor_state_pda)
+            .unwrap()
+            .data
+            .as_slice(),
+    )
+    .unwrap();
+    assert_eq!(debitor_state.per_call_limit, per_call_limit);
+    assert_eq!(debitor_state.daily_limit, 0);
+
+    let result = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT);
+    assert_debit_error(result, ErrorCode::ExceedsDebitorPerCallLimit);
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.user_token_account,
+        INITIAL_BALANCE,
+        TokenProgram::Token,
+        "User token account balance should remain unchanged",
+    );
+
+    let result = debit_user(&mut ctx, &debit_context, per_call_limit);
+    assert!(
+        result.is_ok(),
+        "Debit within the debitor's per-call limit failed: {:?}",
+        result.err()
+    );
+}
+
+#[tokio::test]
+async fn test_debit_user_debitor_daily_limit() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+
+    // The delegate's period limit covers several debits, the debitor's day only one
+    set_debitor_limits(&mut ctx, &debit_context, 0, DEBIT_AMOUNT + DEBIT_AMOUNT / 2);
+    debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT).unwrap();
+
+    advance_slots(&mut ctx, 1);
+    let result = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT);
+    assert_debit_error(result, ErrorCode::ExceedsDebitorDailyLimit);
+
+    // The debitor's budget frees up once the day has passed
+    let mut clock = ctx.svm.get_sysvar::<Clock>();
+    clock.unix_timestamp += MerchantDebitorState::DAILY_LIMIT_PERIOD_SECONDS as i64 + 1;
+    clock.slot += 1;
+    ctx.svm.set_sysvar(&clock);
+    let result = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT);
+    assert!(
+        result.is_ok(),
+        "Debit on the debitor's next day failed: {:?}",
+        result.err()
+    );
+}
+
+/// Debit the context's destination after setting its destination state and the merchant's
+/// destination policy
+#[allow(clippy::result_large_err)]
+fn debit_under_destination_policy(
+    policy: DestinationPolicy,
+    destination_allowed: bool,
+) -> litesvm::types::TransactionResult {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    set_merchant_destination(&mut ctx, &debit_context, destination_allowed, false);
+    setup_merchant_config(
+        &mut ctx,
+        TEST_MERCHANT_ID,
+        MerchantConfigParams {
+            destination_policy: policy as u8,
+            ..Default::default()
+        },
+    );
+    debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT)
+}
+
+#[tokio::test]
+async fn test_debit_user_allowlist_destination_policy() {
+    let result = debit_under_destination_policy(DestinationPolicy::Allowlist, true);
+    assert!(
+        result.is_ok(),
+        "Debit to an allowlisted destination should succeed: {:?}",
+        result.err()
+    );
+
+    let result = debit_under_destination_policy(DestinationPolicy::Allowlist, false);
+    assert_debit_error(result, ErrorCode::DestinationNotAllowed);
+}
+
+#[tokio::test]
+async fn test_debit_user_denylist_destination_policy() {
+    // The same destination state now blocks the destination
+    let result = debit_under_destination_policy(DestinationPolicy::Denylist, true);
+    assert_debit_error(result, ErrorCode::DestinationNotAllowed);
+
+    let result = debit_under_destination_policy(DestinationPolicy::Denylist, false);
+    assert!(
+        result.is_ok(),
+        "Debit to a destination that is not denylisted should succeed: {:?}",
+        result.err()
+    );
+}
+
+#[tokio::test]
+async fn test_debit_user_denylist_unlisted_destination() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    let (_, other_pk) = setup_keypair(&mut ctx);
+    setup_merchant_config(
+        &mut ctx,
+        TEST_MERCHANT_ID,
+        MerchantConfigParams {
+            destination_policy: DestinationPolicy::Denylist as u8,
+            ..Default::default()
+        },
+    );
+
+    let (destination_token_account, result) =
+        debit_to_unlisted_owner(&mut ctx, &debit_context, &other_pk);
+    assert!(
+        result.is_ok(),
+        "Debit to a destination without a destination state should succeed: {:?}",
+        result.err()
+    );
+    verify_token_account_balance(
+        &ctx,
+        &destination_token_account,
+        DEBIT_AMOUNT,
+        TokenProgram::Token,
+        "Destination token account balance incorrect",
+    );
+}
+
+/// Set the protocol fee and debit the context's user, paying the fee into a token account of
+/// the fee owner
+#[allow(clippy::result_large_err)]
+fn debit_with_protocol_fee(
+    ctx: &mut TestContext,
+    debit_context: &DebitUserContext,
+    fee_bps: u16,
+) -> (Pubkey, litesvm::types::TransactionResult) {
+    let (_, fee_owner) = setup_keypair(ctx);
+    set_protocol_fee(ctx, fee_bps, fee_owner);
+    let fee_token_account = CreateAssociatedTokenAccountIdempotent::new(
+        &mut ctx.svm,
+        &ctx.payer_kp,
+        &debit_context.mint_pk,
+    )
+    .owner(&fee_owner)
+    .send()
+    .unwrap();
+    ctx.svm.expire_blockhash();
+    let debit_accounts = DebitUser {
+        fee_token_account: Some(fee_token_account),
+        ..make_debit_user_accounts(ctx, debit_context)
+    };
+    let ix = create_debit_user_instruction(ctx, &debit_accounts, TEST_MERCHANT_ID, DEBIT_AMOUNT);
+    let tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &debit_context.debitor_kp],
+    );
+    (fee_token_account, submit_transaction(ctx, tx))
+}
+
+#[tokio::test]
+async fn test_debit_user_zero_protocol_fee() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+
+    let (fee_token_account, result) = debit_with_protocol_fee(&mut ctx, &debit_context, 0);
+    let meta = result.expect("Debit without a protocol fee should succeed");
+    let event = find_user_debited_event(&meta.logs).expect("UserDebited event not found");
+    assert_eq!(event.amount, DEBIT_AMOUNT);
+    assert_eq!(event.protocol_fee, 0);
+
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.destination_token_account,
+        DEBIT_AMOUNT,
+        TokenProgram::Token,
+        "Destination should receive the whole amount",
+    );
+    verify_token_account_balance(
+        &ctx,
+        &fee_token_account,
+        0,
+        TokenProgram::Token,
+        "Fee token account should receive nothing",
+    );
+}
+
+#[tokio::test]
+async fn test_debit_user_protocol_fee() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+
+    // 2.5% of the debit goes to the fee owner
+    let (fee_token_account, result) = debit_with_protocol_fee(&mut ctx, &debit_context, 250);
+    let meta = result.expect("Debit with a protocol fee should succeed");
+    let protocol_fee = DEBIT_AMOUNT / 40;
+    let event = find_user_debited_event(&meta.logs).expect("UserDebited event not found");
+    assert_eq!(event.amount, DEBIT_AMOUNT);
+    assert_eq!(event.protocol_fee, protocol_fee);
+
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.destination_token_account,
+        DEBIT_AMOUNT - protocol_fee,
+        TokenProgram::Token,
+        "Destination should receive the amount net of the fee",
+    );
+    verify_token_account_balance(
+        &ctx,
+        &fee_token_account,
+        protocol_fee,
+        TokenProgram::Token,
+        "Fee token account should receive the fee",
+    );
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.user_token_account,
+        INITIAL_BALANCE - DEBIT_AMOUNT,
+        TokenProgram::Token,
+        "User should be debited the amount including the fee",
+    );
+
+    // The whole debit, fee included, counts against the delegate's limits
+    let user_delegate_account = ctx
+        .svm
+        .get_account(&debit_context.user_delegate_pda)
+        .unwrap();
+    let user_delegate_state =
+        UserDelegateState::try_deserialize(&mut user_delegate_account.data.as_slice()).unwrap();
+    assert_eq!(user_delegate_state.period_transferred_amount, DEBIT_AMOUNT);
+}
+
+#[tokio::test]
+async fn test_debit_user_protocol_fee_requires_fee_account() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    let (_, fee_owner) = setup_keypair(&mut ctx);
+    set_protocol_fee(&mut ctx, 250, fee_owner);
+
+    let result = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT);
+    assert_debit_error(result, ErrorCode::InvalidProtocolFeeAccount);
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.user_token_account,
+        INITIAL_BALANCE,
+        TokenProgram::Token,
+        "User token account balance should remain unchanged",
+    );
+}
+
+#[tokio::test]
+async fn test_debit_user_rejects_reentrant_token_program() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    let delegate_before = ctx
+        .svm
+        .get_account(&debit_context.user_delegate_pda)
+        .unwrap();
+
+    // A token program that calls back into this program mid-transfer is refused up front
+    let accounts = DebitUser {
+        token_program: ctx.program_id,
+        ..make_debit_user_accounts(&ctx, &debit_context)
+    };
+    let ix = create_debit_user_instruction_with_program(
+        &ctx,
+        &accounts,
+        TEST_MERCHANT_ID,
+        DEBIT_AMOUNT,
+        TokenProgram::Token,
+    );
+    let tx = create_transaction_with_payer_and_signers(
+        &ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &debit_context.debitor_kp],
+    );
+    let err = submit_transaction(&mut ctx, tx).expect_err("Debit should fail");
+    let expected_message = anchor_lang::error::ErrorCode::InvalidProgramId.to_string();
+    assert!(
+        err.meta
+            .logs
+            .iter()
+            .any(|log| log.contains(&expected_message)),
+        "Error should contain the expected error message {}, got {}",
+        expected_message,
+        err.meta.logs.join("\n")
+    );
+    assert_eq!(
+        ctx.svm
+            .get_account(&debit_context.user_delegate_pda)
+            .unwrap(),
+        delegate_before
+    );
+
+    // The usage written before the transfer CPI matches what is left after the debit
+    let result = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT);
+    assert!(result.is_ok(), "Debit failed: {:?}", result.err());
+    let user_delegate_account = ctx
+        .svm
+        .get_account(&debit_context.user_delegate_pda)
+        .unwrap();
+    let user_delegate_state =
+        UserDelegateState::try_deserialize(&mut user_delegate_account.data.as_slice()).unwrap();
+    assert_eq!(user_delegate_state.period_transferred_amount, DEBIT_AMOUNT);
+    assert_eq!(user_delegate_state.debit_nonce, 1);
+}
+
+#[tokio::test]
+async fn test_debit_user_rejects_self_transfer() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+
+    // Even allowlisted, the user's own token account can't be the destination
+    let destination_state = make_merchant_destination_pda(
+        TEST_MERCHANT_ID,
+        &debit_context.mint_pk,
+        &debit_context.user_token_account,
+        &ctx.program_id,
+    );
+    let destination_accounts = AddOrUpdateMerchantDestination {
+        admin: ctx.payer_pk,
+        payer: ctx.payer_pk,
+        state: ctx.bridge_cards_state.pubkey,
+        destination_state: destination_state.pubkey,
+        destination_token_account: debit_context.user_token_account,
+        mint: debit_context.mint_pk,
+        system_program: anchor_lang::system_program::ID,
+        merchant_registry: None,
+    };
+    let ix = create_add_or_update_merchant_destination_instruction(
+        &ctx,
+        &destination_accounts,
+        TEST_MERCHANT_ID,
+        true,
+    );
+    let tx = create_transaction(&ctx, &[ix]);
+    submit_transaction(&mut ctx, tx).unwrap();
+
+    ctx.svm.expire_blockhash();
+    let debit_accounts = DebitUser {
+        destination_token_account: debit_context.user_token_account,
+        destination_state: destination_state.pubkey,
+        ..make_debit_user_accounts(&ctx, &debit_context)
+    };
+    let ix = create_debit_user_instruction(&ctx, &debit_accounts, TEST_MERCHANT_ID, DEBIT_AMOUNT);
+    let tx = create_transaction_with_payer_and_signers(
+        &ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &debit_context.debitor_kp],
+    );
+    assert_debit_error(submit_transaction(&mut ctx, tx), ErrorCode::SelfTransfer);
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.user_token_account,
+        INITIAL_BALANCE,
+        TokenProgram::Token,
+        "User token account balance should remain unchanged",
+    );
+}
+
+/// Update the delegate's options as the merchant manager, keeping its limits
+#[allow(clippy::result_large_err)]
+fn update_delegate_options(
+    ctx: &mut TestContext,
+    debit_context: &DebitUserContext,
+    options: UserDelegateOptions,
+) -> litesvm::types::TransactionResult {
+    ctx.svm.expire_blockhash();
+    let delegate_accounts = AddOrUpdateUserDelegate {
+        manager: ctx.merchant_manager_kp.pubkey(),
+        manager_state: ctx.merchant_manager_state.pubkey,
+        merchant_config: make_merchant_config_pda(TEST_MERCHANT_ID, &ctx.program_id).pubkey,
+        payer: ctx.payer_pk,
+        user_token_account: debit_context.user_token_account,
+        mint: debit_context.mint_pk,
+        user_delegate_account: debit_context.user_delegate_pda,
+        system_program: System::id(),
+        merchant_mint: None,
+        merchant_registry: None,
+    };
+    let ix = create_add_or_update_user_delegate_instruction_with_options(
+        ctx,
+        &delegate_accounts,
+        TEST_MERCHANT_ID,
+        MAX_TRANSFER_LIMIT,
+        PERIOD_TRANSFER_LIMIT,
+        LIMIT_PERIOD,
+        options,
+    );
+    let tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &ctx.merchant_manager_kp],
+    );
+    submit_transaction(ctx, tx)
+}
+
+#[tokio::test]
+async fn test_debit_user_restricted_destination() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    // Any treasury token account is an otherwise allowed destination
+    let (_, treasury_pk) = setup_keypair(&mut ctx);
+    setup_merchant_config(
+        &mut ctx,
+        TEST_MERCHANT_ID,
+        MerchantConfigParams {
+            allow_any_destination_owner: Some(treasury_pk),
+            ..Default::default()
+        },
+    );
+
+    // Bind the delegate to the merchant's destination
+    update_delegate_options(
+        &mut ctx,
+        &debit_context,
+        UserDelegateOptions {
+            restricted_destination: Some(Some(debit_context.destination_token_account)),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    let (_, result) = debit_to_unlisted_owner(&mut ctx, &debit_context, &treasury_pk);
+    assert_debit_error(result, ErrorCode::DestinationNotAllowedForDelegate);
+
+    let result = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT);
+    assert!(
+        result.is_ok(),
+        "Debit to the restricted destination should succeed: {:?}",
+        result.err()
+    );
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.destination_token_account,
+        DEBIT_AMOUNT,
+        TokenProgram::Token,
+        "Restricted destination should receive the debit",
+    );
+}
+
+#[tokio::test]
+async fn test_debit_user_restricted_destination_kept_on_update() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    let (_, treasury_pk) = setup_keypair(&mut ctx);
+    setup_merchant_config(
+        &mut ctx,
+        TEST_MERCHANT_ID,
+        MerchantConfigParams {
+            allow_any_destination_owner: Some(treasury_pk),
+            ..Default::default()
+        },
+    );
+    update_delegate_options(
+        &mut ctx,
+        &debit_context,
+        UserDelegateOptions {
+            restricted_destination: Some(Some(debit_context.destination_token_account)),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    // An update that doesn't mention the restriction keeps it
+    update_delegate_options(&mut ctx, &debit_context, UserDelegateOptions::default()).unwrap();
+    let (_, result) = debit_to_unlisted_owner(&mut ctx, &debit_context, &treasury_pk);
+    assert_debit_error(result, ErrorCode::DestinationNotAllowedForDelegate);
+
+    // Lifting it explicitly allows the other destination again
+    update_delegate_options(
+        &mut ctx,
+        &debit_context,
+        UserDelegateOptions {
+            restricted_destination: Some(None),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let (_, result) = debit_to_unlisted_owner(&mut ctx, &debit_context, &treasury_pk);
+    assert!(
+        result.is_ok(),
+        "Debit after lifting the restriction should succeed: {:?}",
+        result.err()
+    );
+}
+
+/// Debit the user, passing the given debit receipt account
+#[allow(clippy::result_large_err)]
+fn debit_with_receipt(
+    ctx: &mut TestContext,
+    debit_context: &DebitUserContext,
+    debit_receipt: Pubkey,
+) -> litesvm::types::TransactionResult {
+    ctx.svm.expire_blockhash();
+    let debit_accounts = DebitUser {
+        debit_receipt: Some(debit_receipt),
+        ..make_debit_user_accounts(ctx, debit_context)
+    };
+    let ix = create_debit_user_instruction(ctx, &debit_accounts, TEST_MERCHANT_ID, DEBIT_AMOUNT);
+    let tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &debit_context.debitor_kp],
+    );
+    submit_transaction(ctx, tx)
+}
+
+#[tokio::test]
+async fn test_debit_user_writes_receipt() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+
+    // A debit without a receipt still advances the nonce
+    let result = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT);
+    assert!(result.is_ok(), "Debit failed: {:?}", result.err());
+
+    let receipt_pda = make_debit_receipt_pda(&debit_context.user_delegate_pda, 2, &ctx.program_id);
+    let meta = debit_with_receipt(&mut ctx, &debit_context, receipt_pda.pubkey)
+        .expect("Debit with receipt failed");
+    let event = find_user_debited_event(&meta.logs).expect("UserDebited event not found");
+    assert_eq!(event.debit_nonce, 2);
+
+    let account = ctx.svm.get_account(&receipt_pda.pubkey).unwrap();
+    assert_eq!(account.owner, ctx.program_id);
+    let receipt = DebitReceiptState::try_deserialize(&mut account.data.as_slice()).unwrap();
+    assert_eq!(receipt.version, CURRENT_VERSION);
+    assert_eq!(receipt.user_delegate, debit_context.user_delegate_pda);
+    assert_eq!(receipt.debit_nonce, 2);
+    assert_eq!(receipt.amount, DEBIT_AMOUNT);
+    assert_eq!(receipt.destination, debit_context.destination_token_account);
+    assert_eq!(receipt.mint, debit_context.mint_pk);
+    assert_eq!(receipt.timestamp, event.timestamp);
+    assert_eq!(receipt.bump, receipt_pda.bump);
+}
+
+#[tokio::test]
+async fn test_debit_user_receipt_for_other_nonce() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+
+    // The first debit has nonce 1, so the receipt of nonce 2 can't be written yet
+    let receipt_pda = make_debit_receipt_pda(&debit_context.user_delegate_pda, 2, &ctx.program_id);
+    assert_debit_error(
+        debit_with_receipt(&mut ctx, &debit_context, receipt_pda.pubkey),
+        ErrorCode::InvalidPda,
+    );
+    assert!(ctx.svm.get_account(&receipt_pda.pubkey).is_none());
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.user_token_account,
+        INITIAL_BALANCE,
+        TokenProgram::Token,
+        "User token account balance should remain unchanged",
+    );
+}
+
+#[tokio::test]
+async fn test_debit_user_destination_of_other_mint_token22() {
+    let mut ctx = setup_and_initialize();
+    let debit_context = setup_merchant_and_user_delegate_with_program(
+        &mut ctx,
+        MAX_TRANSFER_LIMIT,
+        PERIOD_TRANSFER_LIMIT,
+        TokenProgram::Token2022,
+    );
+
+    // A destination holding another mint, with its state PDA seeded by the delegate's mint
+    let other_mint_pk = setup_mint_with_program(&mut ctx, TokenProgram::Token2022);
+    let (_, destination_owner_pk) = setup_keypair(&mut ctx);
+    let destination_token_account =
+        CreateAssociatedTokenAccountIdempotent::new(&mut ctx.svm, &ctx.payer_kp, &other_mint_pk)
+            .owner(&destination_owner_pk)
+            .token_program_id(&TokenProgram::Token2022.program_id())
+            .send()
+            .unwrap();
+    let destination_state = make_merchant_destination_pda(
+        TEST_MERCHANT_ID,
+        &debit_context.mint_pk,
+        &destination_token_account,
+        &ctx.program_id,
+    );
+
+    ctx.svm.expire_blockhash();
+    let debit_accounts = DebitUser {
+        destination_token_account,
+        destination_state: destination_state.pubkey,
+        ..make_debit_user_accounts(&ctx, &debit_context)
+    };
+    let ix = create_debit_user_instruction_with_program(
+        &ctx,
+        &debit_accounts,
+        TEST_MERCHANT_ID,
+        DEBIT_AMOUNT,
+        TokenProgram::Token2022,
+    );
+    let tx = create_transaction_with_payer_and_signers(
+        &ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &debit_context.debitor_kp],
+    );
+    assert_debit_error(submit_transaction(&mut ctx, tx), ErrorCode::MismatchedMint);
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.user_token_account,
+        INITIAL_BALANCE,
+        TokenProgram::Token2022,
+        "User token account balance should remain unchanged",
+    );
+}
+
+#[tokio::test]
+async fn test_debit_user_creation_cooldown() {
+    let mut ctx = setup_and_initialize();
+    let cooldown_seconds = 3600;
+    let created_at = ctx.svm.get_sysvar::<Clock>().unix_timestamp;
+    let debit_context = setup_merchant_and_user_delegate_with_options(
+        &mut ctx,
+        MAX_TRANSFER_LIMIT,
+        PERIOD_TRANSFER_LIMIT,
+        TokenProgram::Token,
+        UserDelegateOptions {
+            cooldown_seconds,
+            ..Default::default()
+        },
+    );
+    let active_after = created_at + i64::from(cooldown_seconds);
+    let user_delegate_account = ctx
+        .svm
+        .get_account(&debit_context.user_delegate_pda)
+        .unwrap();
+    let user_delegate_state =
+        UserDelegateState::try_deserialize(&mut user_delegate_account.data.as_slice()).unwrap();
+    assert_eq!(user_delegate_state.active_after, active_after);
+
+    let set_time = |ctx: &mut TestContext, unix_timestamp: i64| {
+        let mut clock = ctx.svm.get_sysvar::<Clock>();
+        clock.unix_timestamp = unix_timestamp;
+        clock.slot += 1;
+        ctx.svm.set_sysvar(&clock);
+    };
+
+    // Right after creation and until the last second of the cooldown
+    let result = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT);
+    assert_debit_error(result, ErrorCode::DelegateNotYetActive);
+    set_time(&mut ctx, active_after - 1);
+    let result = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT);
+    assert_debit_error(result, ErrorCode::DelegateNotYetActive);
+
+    // active_after is the first second the delegate can debit
+    set_time(&mut ctx, active_after);
+    let result = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT);
+    assert!(
+        result.is_ok(),
+        "Debit after the cooldown should succeed: {:?}",
+        result.err()
+    );
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.user_token_account,
+        INITIAL_BALANCE - DEBIT_AMOUNT,
+        TokenProgram::Token,
+        "User should be debited once",
+    );
+}
+
+#[tokio::test]
+async fn test_debit_user_unlimited_period_uses_less_compute() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+
+    // Measure a debit within the current period, so neither debit starts a new one
+    debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT).unwrap();
+    advance_slots(&mut ctx, 1);
+    let limited = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT).unwrap();
+
+    let accounts = AddOrUpdateUserDelegate {
+        manager: ctx.merchant_manager_kp.pubkey(),
+        manager_state: ctx.merchant_manager_state.pubkey,
+        merchant_config: make_merchant_config_pda(TEST_MERCHANT_ID, &ctx.program_id).pubkey,
+        payer: ctx.payer_pk,
+        user_token_account: debit_context.user_token_account,
+        mint: debit_context.mint_pk,
+        user_delegate_account: debit_context.user_delegate_pda,
+        system_program: System::id(),
+        merchant_mint: None,
+        merchant_registry: None,
+    };
+    let ix = create_add_or_update_user_delegate_instruction(
+        &ctx,
+        &accounts,
+        TEST_MERCHANT_ID,
+        MAX_TRANSFER_LIMIT,
+        UNLIMITED_PERIOD_TRANSFER_LIMIT,
+        LIMIT_PERIOD,
+    );
+    let tx = create_transaction_with_payer_and_signers(
+        &ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &ctx.merchant_manager_kp],
+    );
+    submit_transaction(&mut ctx, tx).unwrap();
+
+    advance_slots(&mut ctx, 1);
+    let unlimited = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT).unwrap();
+    assert!(
+        unlimited.compute_units_consumed < limited.compute_units_consumed,
+        "An unlimited period should use fewer compute units: {} (unlimited) vs {} (limited)",
+        unlimited.compute_units_consumed,
+        limited.compute_units_consumed
+    );
+
+    // The per-transfer limit still applies
+    advance_slots(&mut ctx, 1);
+    assert_debit_error(
+        debit_user(&mut ctx, &debit_context, MAX_TRANSFER_LIMIT + 1),
+        ErrorCode::ExceedsMaxTransferLimit,
+    );
+}
+
+#[tokio::test]
+async fn test_debit_user_debitor_of_other_mint() {
+    let mut ctx = setup_and_initialize();
+    let mint_a_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    let mint_b_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+
+    // The debitor of mint A has no debitor state for mint B
+    let debitor_of_mint_a = DebitUserContext {
+        debitor_pk: mint_a_context.debitor_pk,
+        debitor_kp: mint_a_context.debitor_kp.insecure_clone(),
+        debitor_state_pda: make_merchant_debitor_pda(
+            TEST_MERCHANT_ID,
+            &mint_a_context.debitor_pk,
+            &mint_b_context.mint_pk,
+            &ctx.program_id,
+        )
+        .pubkey,
+        user_kp: mint_b_context.user_kp.insecure_clone(),
+        ..mint_b_context
+    };
+    assert_debit_error(
+        debit_user(&mut ctx, &debitor_of_mint_a, DEBIT_AMOUNT),
+        ErrorCode::DebitorNotAuthorizedForMint,
+    );
+
+    // Its debitor state for mint A doesn't match the seeds of a mint B debit
+    let debitor_state_of_mint_a = DebitUserContext {
+        debitor_state_pda: mint_a_context.debitor_state_pda,
+        debitor_kp: mint_a_context.debitor_kp.insecure_clone(),
+        user_kp: mint_b_context.user_kp.insecure_clone(),
+        ..debitor_of_mint_a
+    };
+    let err = debit_user(&mut ctx, &debitor_state_of_mint_a, DEBIT_AMOUNT)
+        .expect_err("A debitor state of another mint should be rejected");
+    let expected_message = anchor_lang::error::ErrorCode::ConstraintSeeds.to_string();
+    assert!(
+        err.meta
+            .logs
+            .iter()
+            .any(|log| log.contains(&expected_message)),
+        "Error should contain the expected error message {}, got {}",
+        expected_message,
+        err.meta.logs.join("\n")
+    );
+
+    // The debitor still debits mint A
+    let result = debit_user(&mut ctx, &mint_a_context, DEBIT_AMOUNT);
+    assert!(result.is_ok(), "Debit failed: {:?}", result.err());
+}
+
+#[tokio::test]
+async fn test_debit_user_disallowed_debitor() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    let manager = ctx.merchant_manager_kp.insecure_clone();
+    configure_merchant_debitor(
+        &mut ctx,
+        &manager,
+        debit_context.mint_pk,
+        debit_context.debitor_pk,
+        false,
+    )
+    .unwrap();
+
+    assert_debit_error(
+        debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT),
+        ErrorCode::DebitorNotAuthorizedForMint,
+    );
+}
+
+#[tokio::test]
+async fn test_debit_user_clock_anomaly() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    set_feature_flags(&mut ctx, feature_flags::CLOCK_ANOMALY_CHECKS);
+    debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT).unwrap();
+
+    // A day passes on the clock while a single slot passes
+    let mut clock = ctx.svm.get_sysvar::<Clock>();
+    clock.unix_timestamp += LIMIT_PERIOD as i64;
+    clock.slot += 1;
+    ctx.svm.set_sysvar(&clock);
+    assert_debit_error(
+        debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT),
+        ErrorCode::ClockAnomaly,
+    );
+
+    // Once the slots catch up with the clock the debit goes through
+    advance_slots(&mut ctx, LIMIT_PERIOD as u64 * 1_000 / MS_PER_SLOT);
+    let result = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT);
+    assert!(result.is_ok(), "Debit failed: {:?}", result.err());
+}
+
+#[tokio::test]
+async fn test_debit_user_records_last_received_on_destination() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    let get_destination_state = |ctx: &TestContext| {
+        let account = ctx
+            .svm
+            .get_account(&debit_context.destination_state_pda)
+            .unwrap();
+        MerchantDestinationState::try_deserialize(&mut account.data.as_slice()).unwrap()
+    };
+
+    let destination_state = get_destination_state(&ctx);
+    assert_eq!(destination_state.last_received_amount, 0);
+    assert_eq!(destination_state.last_received_timestamp, 0);
+
+    debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT).unwrap();
+    let destination_state = get_destination_state(&ctx);
+    assert_eq!(destination_state.last_received_amount, DEBIT_AMOUNT);
+    assert_eq!(
+        destination_state.last_received_timestamp,
+        ctx.svm.get_sysvar::<Clock>().unix_timestamp
+    );
+    assert!(destination_state.allowed);
+
+    // Only the most recent debit is recorded
+    let mut clock = ctx.svm.get_sysvar::<Clock>();
+    clock.unix_timestamp += 60;
+    clock.slot += 1;
+    ctx.svm.set_sysvar(&clock);
+    debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT / 2).unwrap();
+    let destination_state = get_destination_state(&ctx);
+    assert_eq!(destination_state.last_received_amount, DEBIT_AMOUNT / 2);
+    assert_eq!(
+        destination_state.last_received_timestamp,
+        clock.unix_timestamp
+    );
+}
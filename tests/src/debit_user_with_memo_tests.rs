@@ -0,0 +1,133 @@
+use crate::common::*;
+use bridge_cards::accounts::DebitUserWithMemo;
+use bridge_cards::errors::ErrorCode;
+use bridge_cards::event_parsing::{parse_events, BridgeCardsEvent};
+use bridge_cards::events::UserDebited;
+use bridge_cards::instructions::debit_user_with_memo::MAX_MEMO_LENGTH;
+use litesvm::types::TransactionResult;
+use solana_program_test::tokio;
+
+const MAX_TRANSFER_LIMIT: u64 = 100_000_000; // $100 per transaction
+const PERIOD_TRANSFER_LIMIT: u64 = 2_000_000_000; // $2000 per day
+const DEBIT_AMOUNT: u64 = 50_000_000; // $50 debit amount
+
+type TestContext = crate::common::Context;
+
+fn find_user_debited_event(logs: &[String]) -> Option<UserDebited> {
+    parse_events(logs)
+        .into_iter()
+        .find_map(|event| match event {
+            BridgeCardsEvent::UserDebited(event) => Some(event),
+            _ => None,
+        })
+}
+
+fn has_memo_log(logs: &[String]) -> bool {
+    logs.iter()
+        .any(|log| log.starts_with("Program log: Memo (len"))
+}
+
+#[allow(clippy::result_large_err)]
+fn debit_user_with_memo(
+    ctx: &mut TestContext,
+    debit_context: &DebitUserContext,
+    memo: &[u8],
+    with_memo_program: bool,
+) -> TransactionResult {
+    ctx.svm.expire_blockhash();
+    let accounts = DebitUserWithMemo {
+        debit: make_debit_user_accounts(ctx, debit_context),
+        memo_program: with_memo_program.then_some(anchor_spl::memo::ID),
+    };
+    let ix = create_debit_user_with_memo_instruction(
+        ctx,
+        &accounts,
+        TEST_MERCHANT_ID,
+        DEBIT_AMOUNT,
+        memo.to_vec(),
+    );
+    let tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &debit_context.debitor_kp],
+    );
+    submit_transaction(ctx, tx)
+}
+
+#[tokio::test]
+async fn test_debit_user_with_memo() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    let memo = b"invoice-2024-0042";
+
+    // Event only
+    let meta = debit_user_with_memo(&mut ctx, &debit_context, memo, false)
+        .expect("Debit with memo failed");
+    let event = find_user_debited_event(&meta.logs).expect("UserDebited event not found");
+    assert_eq!(event.memo, memo.to_vec());
+    assert_eq!(event.amount, DEBIT_AMOUNT);
+    assert!(!has_memo_log(&meta.logs));
+
+    // Event and Memo CPI
+    let meta = debit_user_with_memo(&mut ctx, &debit_context, memo, true)
+        .expect("Debit with memo CPI failed");
+    let event = find_user_debited_event(&meta.logs).expect("UserDebited event not found");
+    assert_eq!(event.memo, memo.to_vec());
+    assert!(has_memo_log(&meta.logs), "Memo program should log the memo");
+    assert!(meta
+        .logs
+        .iter()
+        .any(|log| log.contains("invoice-2024-0042")));
+
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.user_token_account,
+        INITIAL_BALANCE - 2 * DEBIT_AMOUNT,
+        debit_context.token_program,
+        "User should be debited twice",
+    );
+}
+
+#[tokio::test]
+async fn test_debit_user_with_memo_too_long() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+
+    let err = debit_user_with_memo(&mut ctx, &debit_context, &[b'a'; MAX_MEMO_LENGTH + 1], true)
+        .expect_err("Debit with an over-length memo should fail");
+    let expected_message = ErrorCode::MemoTooLong.to_string();
+    assert!(
+        err.meta
+            .logs
+            .iter()
+            .any(|log| log.contains(&expected_message)),
+        "Error should contain the expected error message {}, got {}",
+        expected_message,
+        err.meta.logs.join("\n")
+    );
+
+    // A memo of exactly the maximum length is accepted
+    let result = debit_user_with_memo(&mut ctx, &debit_context, &[b'a'; MAX_MEMO_LENGTH], true);
+    assert!(
+        result.is_ok(),
+        "Debit with a maximum length memo failed: {:?}",
+        result.err()
+    );
+}
+
+#[tokio::test]
+async fn test_debit_user_with_empty_memo() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+
+    // An empty memo debits as debit_user does, without a Memo CPI
+    let meta = debit_user_with_memo(&mut ctx, &debit_context, &[], true)
+        .expect("Debit with an empty memo failed");
+    let event = find_user_debited_event(&meta.logs).expect("UserDebited event not found");
+    assert!(event.memo.is_empty());
+    assert!(!has_memo_log(&meta.logs));
+}
@@ -0,0 +1,244 @@
+use crate::common::*;
+use anchor_lang::prelude::*;
+use bridge_cards::accounts::DebitUserWithSwap;
+use bridge_cards::errors::ErrorCode;
+use bridge_cards::instructions::add_or_update_merchant_config::MerchantConfigParams;
+use solana_program_test::tokio;
+
+type TestContext = crate::common::Context;
+
+const MAX_TRANSFER_LIMIT: u64 = 100_000_000; // $100 per transaction
+const PERIOD_TRANSFER_LIMIT: u64 = 2_000_000_000; // $2000 per day
+const DEBIT_AMOUNT: u64 = 50_000_000; // $50 debit amount
+
+fn make_debit_user_with_swap_accounts(
+    ctx: &TestContext,
+    debit_context: &DebitUserContext,
+    router_program: Pubkey,
+) -> DebitUserWithSwap {
+    DebitUserWithSwap {
+        debit: make_debit_user_accounts(ctx, debit_context),
+        router_state: make_router_pda(&router_program, &ctx.program_id).pubkey,
+        router_program,
+    }
+}
+
+#[tokio::test]
+async fn test_debit_user_with_swap_invokes_router() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    setup_router(&mut ctx, &MOCK_ROUTER_PROGRAM_ID, true);
+    setup_merchant_config(
+        &mut ctx,
+        TEST_MERCHANT_ID,
+        MerchantConfigParams {
+            router_program: MOCK_ROUTER_PROGRAM_ID,
+            ..Default::default()
+        },
+    );
+
+    let accounts = make_debit_user_with_swap_accounts(&ctx, &debit_context, MOCK_ROUTER_PROGRAM_ID);
+    let route_data = b"swap".to_vec();
+    let ix = create_debit_user_with_swap_instruction(
+        &ctx,
+        &accounts,
+        TEST_MERCHANT_ID,
+        DEBIT_AMOUNT,
+        route_data.clone(),
+        &[],
+    );
+    let tx = create_transaction_with_payer_and_signers(
+        &ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &debit_context.debitor_kp],
+    );
+    let account_keys = tx.message.account_keys.clone();
+
+    let result = submit_transaction(&mut ctx, tx);
+    assert!(
+        result.is_ok(),
+        "Failed to debit user with swap: {:?}",
+        result.err()
+    );
+
+    // Verify the router was invoked with the destination account and route data
+    let meta = result.unwrap();
+    let router_ix = meta
+        .inner_instructions
+        .iter()
+        .flatten()
+        .find(|inner| {
+            account_keys[inner.instruction.program_id_index as usize] == MOCK_ROUTER_PROGRAM_ID
+        })
+        .expect("Router CPI not found");
+    let router_accounts: Vec<Pubkey> = router_ix
+        .instruction
+        .accounts
+        .iter()
+        .map(|index| account_keys[*index as usize])
+        .collect();
+    assert_eq!(
+        router_accounts,
+        vec![debit_context.destination_token_account]
+    );
+    assert_eq!(router_ix.instruction.data, route_data);
+
+    // The debit itself must have happened before the router ran
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.user_token_account,
+        INITIAL_BALANCE - DEBIT_AMOUNT,
+        debit_context.token_program,
+        "User token account balance incorrect",
+    );
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.destination_token_account,
+        DEBIT_AMOUNT,
+        debit_context.token_program,
+        "Destination token account balance incorrect",
+    );
+}
+
+#[tokio::test]
+async fn test_debit_user_with_swap_router_failure_reverts_debit() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    setup_router(&mut ctx, &MOCK_ROUTER_PROGRAM_ID, true);
+    setup_merchant_config(
+        &mut ctx,
+        TEST_MERCHANT_ID,
+        MerchantConfigParams {
+            router_program: MOCK_ROUTER_PROGRAM_ID,
+            ..Default::default()
+        },
+    );
+
+    // The mock router rejects invalid UTF-8, simulating a failed swap
+    let accounts = make_debit_user_with_swap_accounts(&ctx, &debit_context, MOCK_ROUTER_PROGRAM_ID);
+    let ix = create_debit_user_with_swap_instruction(
+        &ctx,
+        &accounts,
+        TEST_MERCHANT_ID,
+        DEBIT_AMOUNT,
+        vec![0xff, 0xfe],
+        &[],
+    );
+    let tx = create_transaction_with_payer_and_signers(
+        &ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &debit_context.debitor_kp],
+    );
+
+    let result = submit_transaction(&mut ctx, tx);
+    assert!(result.is_err(), "Failed router CPI should fail the debit");
+
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.user_token_account,
+        INITIAL_BALANCE,
+        debit_context.token_program,
+        "User token account balance should remain unchanged",
+    );
+}
+
+#[tokio::test]
+async fn test_debit_user_with_swap_router_not_configured() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    setup_router(&mut ctx, &MOCK_ROUTER_PROGRAM_ID, true);
+    // Merchant config exists but has no router configured
+    setup_merchant_config(&mut ctx, TEST_MERCHANT_ID, MerchantConfigParams::default());
+
+    let accounts = make_debit_user_with_swap_accounts(&ctx, &debit_context, MOCK_ROUTER_PROGRAM_ID);
+    let ix = create_debit_user_with_swap_instruction(
+        &ctx,
+        &accounts,
+        TEST_MERCHANT_ID,
+        DEBIT_AMOUNT,
+        b"swap".to_vec(),
+        &[],
+    );
+    let tx = create_transaction_with_payer_and_signers(
+        &ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &debit_context.debitor_kp],
+    );
+
+    let result = submit_transaction(&mut ctx, tx);
+    assert!(result.is_err(), "Unconfigured router should be rejected");
+
+    let err = result.err().unwrap();
+    let expected_message = ErrorCode::RouterNotConfigured.to_string();
+    assert!(
+        err.meta
+            .logs
+            .iter()
+            .any(|log| log.contains(&expected_message)),
+        "Error should contain the expected error message {}, got {}",
+        expected_message,
+        err.meta.logs.join(", ")
+    );
+}
+
+#[tokio::test]
+async fn test_debit_user_with_swap_router_not_allowed() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    // Router is configured for the merchant but revoked by the admin
+    setup_router(&mut ctx, &MOCK_ROUTER_PROGRAM_ID, false);
+    setup_merchant_config(
+        &mut ctx,
+        TEST_MERCHANT_ID,
+        MerchantConfigParams {
+            router_program: MOCK_ROUTER_PROGRAM_ID,
+            ..Default::default()
+        },
+    );
+
+    let accounts = make_debit_user_with_swap_accounts(&ctx, &debit_context, MOCK_ROUTER_PROGRAM_ID);
+    let ix = create_debit_user_with_swap_instruction(
+        &ctx,
+        &accounts,
+        TEST_MERCHANT_ID,
+        DEBIT_AMOUNT,
+        b"swap".to_vec(),
+        &[],
+    );
+    let tx = create_transaction_with_payer_and_signers(
+        &ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &debit_context.debitor_kp],
+    );
+
+    let result = submit_transaction(&mut ctx, tx);
+    assert!(result.is_err(), "Revoked router should be rejected");
+
+    let err = result.err().unwrap();
+    let expected_message = ErrorCode::RouterNotAllowed.to_string();
+    assert!(
+        err.meta
+            .logs
+            .iter()
+            .any(|log| log.contains(&expected_message)),
+        "Error should contain the expected error message {}, got {}",
+        expected_message,
+        err.meta.logs.join(", ")
+    );
+
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.user_token_account,
+        INITIAL_BALANCE,
+        debit_context.token_program,
+        "User token account balance should remain unchanged",
+    );
+}
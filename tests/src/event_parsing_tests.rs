@@ -0,0 +1,121 @@
+use crate::common::*;
+use anchor_lang::prelude::*;
+use base64::Engine;
+use bridge_cards::event_parsing::{parse_events, BridgeCardsEvent};
+use bridge_cards::events::{AccountClosed, Heartbeat, UserDebited, UserDelegateClosed};
+use solana_program_test::tokio;
+
+const MAX_TRANSFER_LIMIT: u64 = 100_000_000; // $100 per transaction
+const PERIOD_TRANSFER_LIMIT: u64 = 2_000_000_000; // $2000 per day
+const DEBIT_AMOUNT: u64 = 50_000_000; // $50 debit amount
+
+/// Log line emit! would write for the event
+fn program_data_log<T: Discriminator + AnchorSerialize>(event: &T) -> String {
+    let mut data = T::DISCRIMINATOR.to_vec();
+    event.serialize(&mut data).unwrap();
+    format!(
+        "Program data: {}",
+        base64::engine::general_purpose::STANDARD.encode(data)
+    )
+}
+
+#[tokio::test]
+async fn test_parse_mixed_log_stream() {
+    let user_delegate = Pubkey::new_unique();
+    let recipient = Pubkey::new_unique();
+    let logs = vec![
+        "Program 11111111111111111111111111111111 invoke [1]".to_string(),
+        program_data_log(&Heartbeat {
+            admin: Pubkey::new_unique(),
+            timestamp: 1_700_000_000,
+            feature_flags: 3,
+            fee_bps: 0,
+        }),
+        "Program log: Instruction: CloseUserDelegate".to_string(),
+        program_data_log(&UserDelegateClosed {
+            merchant_id: TEST_MERCHANT_ID,
+            mint: Pubkey::new_unique(),
+            user_ata: Pubkey::new_unique(),
+            user_delegate,
+        }),
+        // Data of another program and data that isn't base64 are skipped
+        format!(
+            "Program data: {}",
+            base64::engine::general_purpose::STANDARD.encode([7u8; 16])
+        ),
+        "Program data: not base64!".to_string(),
+        program_data_log(&AccountClosed {
+            account: user_delegate,
+            lamports_recovered: 1_000,
+            recipient,
+        }),
+    ];
+
+    let events = parse_events(&logs);
+    assert_eq!(events.len(), 3);
+    match &events[0] {
+        BridgeCardsEvent::Heartbeat(event) => {
+            assert_eq!(event.timestamp, 1_700_000_000);
+            assert_eq!(event.feature_flags, 3);
+        }
+        _ => panic!("Expected a Heartbeat event"),
+    }
+    match &events[1] {
+        BridgeCardsEvent::UserDelegateClosed(event) => {
+            assert_eq!(event.merchant_id, TEST_MERCHANT_ID);
+            assert_eq!(event.user_delegate, user_delegate);
+        }
+        _ => panic!("Expected a UserDelegateClosed event"),
+    }
+    match &events[2] {
+        BridgeCardsEvent::AccountClosed(event) => {
+            assert_eq!(event.account, user_delegate);
+            assert_eq!(event.lamports_recovered, 1_000);
+            assert_eq!(event.recipient, recipient);
+        }
+        _ => panic!("Expected an AccountClosed event"),
+    }
+}
+
+#[tokio::test]
+async fn test_parse_truncated_event() {
+    let log = program_data_log(&UserDelegateClosed {
+        merchant_id: TEST_MERCHANT_ID,
+        mint: Pubkey::new_unique(),
+        user_ata: Pubkey::new_unique(),
+        user_delegate: Pubkey::new_unique(),
+    });
+    let data = base64::engine::general_purpose::STANDARD
+        .decode(log.strip_prefix("Program data: ").unwrap())
+        .unwrap();
+    let truncated = format!(
+        "Program data: {}",
+        base64::engine::general_purpose::STANDARD.encode(&data[..data.len() - 1])
+    );
+
+    assert!(parse_events(&[truncated]).is_empty());
+}
+
+#[tokio::test]
+async fn test_parse_debit_events() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+
+    // The first debit starts a period and debits the user
+    let meta = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT).unwrap();
+    let events = parse_events(&meta.logs);
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, BridgeCardsEvent::UserDelegatePeriodReset(_))));
+    let debited: Vec<&UserDebited> = events
+        .iter()
+        .filter_map(|event| match event {
+            BridgeCardsEvent::UserDebited(event) => Some(event),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(debited.len(), 1);
+    assert_eq!(debited[0].amount, DEBIT_AMOUNT);
+    assert_eq!(debited[0].user_delegate, debit_context.user_delegate_pda);
+}
@@ -0,0 +1,334 @@
+use crate::common::*;
+use account_data_trait::AccountData;
+use anchor_lang::prelude::*;
+use bridge_cards::accounts::{
+    AddOrUpdateMerchantDebitor, AddOrUpdateMerchantDestination, AddOrUpdateUserDelegate,
+    BatchAddDestinations, InitializeMerchantRegistry,
+};
+use bridge_cards::event_parsing::{parse_events, BridgeCardsEvent};
+use bridge_cards::events::MerchantRegistryInitialized;
+use bridge_cards::state::{MerchantRegistryState, CURRENT_VERSION};
+use litesvm::types::TransactionResult;
+use litesvm_token::CreateAssociatedTokenAccountIdempotent;
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer};
+
+const MAX_TRANSFER_LIMIT: u64 = 100_000_000; // $100 per transaction
+const PERIOD_TRANSFER_LIMIT: u64 = 2_000_000_000; // $2000 per day
+
+type TestContext = crate::common::Context;
+
+fn find_merchant_registry_event(logs: &[String]) -> Option<MerchantRegistryInitialized> {
+    parse_events(logs)
+        .into_iter()
+        .find_map(|event| match event {
+            BridgeCardsEvent::MerchantRegistryInitialized(event) => Some(event),
+            _ => None,
+        })
+}
+
+fn read_merchant_registry(ctx: &TestContext, merchant_registry: &Pubkey) -> MerchantRegistryState {
+    let account = ctx.svm.get_account(merchant_registry).unwrap();
+    MerchantRegistryState::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+/// Assert the (destination, debitor, delegate) counters of a registry
+fn assert_counts(ctx: &TestContext, merchant_registry: &Pubkey, expected: (u64, u64, u64)) {
+    let registry = read_merchant_registry(ctx, merchant_registry);
+    assert_eq!(
+        (
+            registry.destination_count,
+            registry.debitor_count,
+            registry.delegate_count
+        ),
+        expected
+    );
+}
+
+#[allow(clippy::result_large_err)]
+fn initialize_merchant_registry(ctx: &mut TestContext, admin: &Keypair) -> TransactionResult {
+    ctx.svm.expire_blockhash();
+    let accounts = InitializeMerchantRegistry {
+        admin: admin.pubkey(),
+        payer: ctx.payer_pk,
+        state: ctx.bridge_cards_state.pubkey,
+        merchant_registry: make_merchant_registry_pda(TEST_MERCHANT_ID, &ctx.program_id).pubkey,
+        system_program: anchor_lang::system_program::ID,
+    };
+    let ix = create_initialize_merchant_registry_instruction(ctx, &accounts, TEST_MERCHANT_ID);
+    let tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, admin],
+    );
+    submit_transaction(ctx, tx)
+}
+
+#[allow(clippy::result_large_err)]
+fn add_or_update_debitor(
+    ctx: &mut TestContext,
+    mint_pk: &Pubkey,
+    debitor: &Pubkey,
+    merchant_registry: &Pubkey,
+) -> TransactionResult {
+    ctx.svm.expire_blockhash();
+    let accounts = AddOrUpdateMerchantDebitor {
+        manager: ctx.merchant_manager_kp.pubkey(),
+        manager_state: ctx.merchant_manager_state.pubkey,
+        payer: ctx.payer_pk,
+        debitor: *debitor,
+        debitor_state: make_merchant_debitor_pda(
+            TEST_MERCHANT_ID,
+            debitor,
+            mint_pk,
+            &ctx.program_id,
+        )
+        .pubkey,
+        mint: *mint_pk,
+        system_program: anchor_lang::system_program::ID,
+        merchant_registry: Some(*merchant_registry),
+    };
+    let ix =
+        create_add_or_update_merchant_debitor_instruction(ctx, &accounts, TEST_MERCHANT_ID, true);
+    let tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &ctx.merchant_manager_kp],
+    );
+    submit_transaction(ctx, tx)
+}
+
+#[allow(clippy::result_large_err)]
+fn add_or_update_destination(
+    ctx: &mut TestContext,
+    mint_pk: &Pubkey,
+    destination: &Pubkey,
+    merchant_registry: &Pubkey,
+) -> TransactionResult {
+    ctx.svm.expire_blockhash();
+    let accounts = AddOrUpdateMerchantDestination {
+        admin: ctx.payer_pk,
+        payer: ctx.payer_pk,
+        state: ctx.bridge_cards_state.pubkey,
+        destination_state: make_merchant_destination_pda(
+            TEST_MERCHANT_ID,
+            mint_pk,
+            destination,
+            &ctx.program_id,
+        )
+        .pubkey,
+        destination_token_account: *destination,
+        mint: *mint_pk,
+        system_program: anchor_lang::system_program::ID,
+        merchant_registry: Some(*merchant_registry),
+    };
+    let ix = create_add_or_update_merchant_destination_instruction(
+        ctx,
+        &accounts,
+        TEST_MERCHANT_ID,
+        true,
+    );
+    let tx = create_transaction(ctx, &[ix]);
+    submit_transaction(ctx, tx)
+}
+
+#[allow(clippy::result_large_err)]
+fn add_or_update_user_delegate(
+    ctx: &mut TestContext,
+    mint_pk: &Pubkey,
+    user_token_account: &Pubkey,
+    merchant_registry: &Pubkey,
+) -> TransactionResult {
+    ctx.svm.expire_blockhash();
+    let accounts = AddOrUpdateUserDelegate {
+        manager: ctx.merchant_manager_kp.pubkey(),
+        manager_state: ctx.merchant_manager_state.pubkey,
+        merchant_config: make_merchant_config_pda(TEST_MERCHANT_ID, &ctx.program_id).pubkey,
+        payer: ctx.payer_pk,
+        user_token_account: *user_token_account,
+        mint: *mint_pk,
+        user_delegate_account: make_user_delegate_pda(
+            TEST_MERCHANT_ID,
+            mint_pk,
+            user_token_account,
+            &ctx.program_id,
+        )
+        .pubkey,
+        system_program: anchor_lang::system_program::ID,
+        merchant_mint: None,
+        merchant_registry: Some(*merchant_registry),
+    };
+    let ix = create_add_or_update_user_delegate_instruction(
+        ctx,
+        &accounts,
+        TEST_MERCHANT_ID,
+        MAX_TRANSFER_LIMIT,
+        PERIOD_TRANSFER_LIMIT,
+        LIMIT_PERIOD,
+    );
+    let tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &ctx.merchant_manager_kp],
+    );
+    submit_transaction(ctx, tx)
+}
+
+#[tokio::test]
+async fn test_initialize_merchant_registry() {
+    let mut ctx = setup_and_initialize();
+    let merchant_registry_pda = make_merchant_registry_pda(TEST_MERCHANT_ID, &ctx.program_id);
+    let admin = ctx.payer_kp.insecure_clone();
+
+    let meta =
+        initialize_merchant_registry(&mut ctx, &admin).expect("Merchant registry creation failed");
+    let event =
+        find_merchant_registry_event(&meta.logs).expect("MerchantRegistryInitialized not found");
+    assert_eq!(event.merchant_id, TEST_MERCHANT_ID);
+    assert_eq!(event.state_pda, merchant_registry_pda.pubkey);
+
+    let expected_data = MerchantRegistryState {
+        version: CURRENT_VERSION,
+        destination_count: 0,
+        debitor_count: 0,
+        delegate_count: 0,
+        bump: merchant_registry_pda.bump,
+    }
+    .account_data();
+    let account = ctx.svm.get_account(&merchant_registry_pda.pubkey).unwrap();
+    assert_eq!(account.owner, ctx.program_id);
+    assert_eq!(account.data, expected_data);
+
+    // The registry can't be created again, which would reset its counters
+    let result = initialize_merchant_registry(&mut ctx, &admin);
+    assert!(result.is_err(), "Re-initializing the registry should fail");
+}
+
+#[tokio::test]
+async fn test_initialize_merchant_registry_requires_admin() {
+    let mut ctx = setup_and_initialize();
+    let (not_admin, _) = setup_keypair(&mut ctx);
+
+    let result = initialize_merchant_registry(&mut ctx, &not_admin);
+    assert!(result.is_err(), "Non-admin should not create a registry");
+    let merchant_registry_pda = make_merchant_registry_pda(TEST_MERCHANT_ID, &ctx.program_id);
+    assert!(ctx.svm.get_account(&merchant_registry_pda.pubkey).is_none());
+}
+
+#[tokio::test]
+async fn test_merchant_registry_counts_created_accounts() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    let merchant_registry = setup_merchant_registry(&mut ctx, TEST_MERCHANT_ID);
+    let mint_pk = debit_context.mint_pk;
+
+    // Updating accounts created before the registry doesn't count them
+    let debitor_pk = debit_context.debitor_kp.pubkey();
+    add_or_update_debitor(&mut ctx, &mint_pk, &debitor_pk, &merchant_registry).unwrap();
+    add_or_update_destination(
+        &mut ctx,
+        &mint_pk,
+        &debit_context.destination_token_account,
+        &merchant_registry,
+    )
+    .unwrap();
+    add_or_update_user_delegate(
+        &mut ctx,
+        &mint_pk,
+        &debit_context.user_token_account,
+        &merchant_registry,
+    )
+    .unwrap();
+    assert_counts(&ctx, &merchant_registry, (0, 0, 0));
+
+    // Each creation is counted once
+    let new_debitor = Keypair::new().pubkey();
+    add_or_update_debitor(&mut ctx, &mint_pk, &new_debitor, &merchant_registry).unwrap();
+    assert_counts(&ctx, &merchant_registry, (0, 1, 0));
+    add_or_update_debitor(&mut ctx, &mint_pk, &new_debitor, &merchant_registry).unwrap();
+    assert_counts(&ctx, &merchant_registry, (0, 1, 0));
+
+    let new_destination =
+        CreateAssociatedTokenAccountIdempotent::new(&mut ctx.svm, &ctx.payer_kp, &mint_pk)
+            .owner(&Keypair::new().pubkey())
+            .token_program_id(&debit_context.token_program.program_id())
+            .send()
+            .unwrap();
+    add_or_update_destination(&mut ctx, &mint_pk, &new_destination, &merchant_registry).unwrap();
+    assert_counts(&ctx, &merchant_registry, (1, 1, 0));
+    add_or_update_destination(&mut ctx, &mint_pk, &new_destination, &merchant_registry).unwrap();
+    assert_counts(&ctx, &merchant_registry, (1, 1, 0));
+
+    let new_user_token_account =
+        CreateAssociatedTokenAccountIdempotent::new(&mut ctx.svm, &ctx.payer_kp, &mint_pk)
+            .owner(&Keypair::new().pubkey())
+            .token_program_id(&debit_context.token_program.program_id())
+            .send()
+            .unwrap();
+    add_or_update_user_delegate(
+        &mut ctx,
+        &mint_pk,
+        &new_user_token_account,
+        &merchant_registry,
+    )
+    .unwrap();
+    assert_counts(&ctx, &merchant_registry, (1, 1, 1));
+    add_or_update_user_delegate(
+        &mut ctx,
+        &mint_pk,
+        &new_user_token_account,
+        &merchant_registry,
+    )
+    .unwrap();
+    assert_counts(&ctx, &merchant_registry, (1, 1, 1));
+}
+
+#[tokio::test]
+async fn test_merchant_registry_counts_batch_added_destinations() {
+    let mut ctx = setup_and_initialize();
+    let mint_pk = setup_mint(&mut ctx);
+    let merchant_registry = setup_merchant_registry(&mut ctx, TEST_MERCHANT_ID);
+    let destinations: Vec<(Pubkey, Pubkey)> = (0..3)
+        .map(|_| {
+            let destination =
+                CreateAssociatedTokenAccountIdempotent::new(&mut ctx.svm, &ctx.payer_kp, &mint_pk)
+                    .owner(&Keypair::new().pubkey())
+                    .send()
+                    .unwrap();
+            let destination_state = make_merchant_destination_pda(
+                TEST_MERCHANT_ID,
+                &mint_pk,
+                &destination,
+                &ctx.program_id,
+            );
+            (destination, destination_state.pubkey)
+        })
+        .collect();
+
+    // Re-adding the batch only counts the destinations once
+    for _ in 0..2 {
+        ctx.svm.expire_blockhash();
+        let accounts = BatchAddDestinations {
+            admin: ctx.payer_pk,
+            payer: ctx.payer_pk,
+            state: ctx.bridge_cards_state.pubkey,
+            mint: mint_pk,
+            system_program: System::id(),
+            merchant_registry: Some(merchant_registry),
+        };
+        let ix = create_batch_add_destinations_instruction(
+            &ctx,
+            &accounts,
+            TEST_MERCHANT_ID,
+            true,
+            &destinations,
+        );
+        let tx = create_transaction(&ctx, &[ix]);
+        submit_transaction(&mut ctx, tx).expect("Batch add failed");
+        assert_counts(&ctx, &merchant_registry, (3, 0, 0));
+    }
+}
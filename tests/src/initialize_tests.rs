@@ -1,7 +1,10 @@
 use crate::common::*;
 use account_data_trait::AccountData;
-use bridge_cards::state::BridgeCardsState;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::AccountDeserialize;
+use bridge_cards::state::{BridgeCardsState, CURRENT_VERSION};
 use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer};
 
 #[tokio::test]
 async fn test_initialize() {
@@ -22,8 +25,12 @@ async fn test_initialize() {
 
     // step 4: verify the state
     let expected_state_data = BridgeCardsState {
+        version: CURRENT_VERSION,
         admin: ctx.payer_pk,
         bump: ctx.bridge_cards_state.bump,
+        feature_flags: 0,
+        protocol_fee_bps: 0,
+        protocol_fee_owner: Pubkey::default(),
     }
     .account_data();
 
@@ -62,3 +69,73 @@ async fn test_initialize_twice() {
         "Error processing Instruction 0: custom program error: 0x0"
     );
 }
+
+#[tokio::test]
+async fn test_is_initialized() {
+    let mut ctx = setup();
+    assert!(
+        !is_initialized(&mut ctx),
+        "Program should not be initialized yet"
+    );
+
+    initialize_bridge_cards(&mut ctx);
+    assert!(is_initialized(&mut ctx), "Program should be initialized");
+}
+
+fn initialize_idempotent(ctx: &mut Context, payer: &Keypair) {
+    ctx.svm.expire_blockhash();
+    let ix = create_initialize_idempotent_instruction(ctx, payer.pubkey());
+    let tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[ix],
+        Some(&payer.pubkey()),
+        &[payer, &ctx.extra_keypair],
+    );
+    let result = submit_transaction(ctx, tx);
+    assert!(
+        result.is_ok(),
+        "Failed to initialize idempotently: {:?}",
+        result.err()
+    );
+}
+
+fn state_admin(ctx: &Context) -> Pubkey {
+    let account = ctx.svm.get_account(&ctx.bridge_cards_state.pubkey).unwrap();
+    BridgeCardsState::try_deserialize(&mut account.data.as_slice())
+        .unwrap()
+        .admin
+}
+
+#[tokio::test]
+async fn test_initialize_idempotent_twice() {
+    let mut ctx = setup();
+    let payer = ctx.payer_kp.insecure_clone();
+    initialize_idempotent(&mut ctx, &payer);
+    assert!(is_initialized(&mut ctx), "Program should be initialized");
+    assert_eq!(state_admin(&ctx), ctx.payer_pk);
+
+    // A second call by another payer succeeds and keeps the first admin
+    let (other_payer, _) = setup_keypair(&mut ctx);
+    initialize_idempotent(&mut ctx, &other_payer);
+    assert_eq!(state_admin(&ctx), ctx.payer_pk);
+}
+
+#[tokio::test]
+async fn test_initialize_idempotent_after_initialize() {
+    let mut ctx = setup_and_initialize();
+    let payer = ctx.payer_kp.insecure_clone();
+    let before = ctx
+        .svm
+        .get_account(&ctx.bridge_cards_state.pubkey)
+        .unwrap()
+        .data;
+
+    initialize_idempotent(&mut ctx, &payer);
+    assert_eq!(
+        ctx.svm
+            .get_account(&ctx.bridge_cards_state.pubkey)
+            .unwrap()
+            .data,
+        before
+    );
+}
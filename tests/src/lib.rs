@@ -1,18 +1,88 @@
 #[cfg(test)]
+pub mod add_merchant_manager_tests;
+#[cfg(test)]
+pub mod add_or_update_merchant_config_tests;
+#[cfg(test)]
 pub mod add_or_update_merchant_debitor_tests;
 #[cfg(test)]
 pub mod add_or_update_merchant_destination_tests;
 #[cfg(test)]
 pub mod add_or_update_merchant_manager_tests;
 #[cfg(test)]
+pub mod add_or_update_merchant_mint_tests;
+#[cfg(test)]
+pub mod add_or_update_merchant_spend_tracker_tests;
+#[cfg(test)]
+pub mod add_or_update_router_tests;
+#[cfg(test)]
 pub mod add_or_update_user_delegate_tests;
 #[cfg(test)]
+pub mod admin_heartbeat_tests;
+#[cfg(test)]
+pub mod admin_raise_delegate_limits_tests;
+#[cfg(test)]
+pub mod admin_recover_user_funds_tests;
+#[cfg(test)]
+pub mod batch_add_destinations_tests;
+#[cfg(test)]
+pub mod batch_debit_user_tests;
+#[cfg(test)]
 pub mod close_account_tests;
 #[cfg(test)]
+pub mod close_accounts_tests;
+#[cfg(test)]
+pub mod close_merchant_debitor_tests;
+#[cfg(test)]
+pub mod close_merchant_destination_tests;
+#[cfg(test)]
+pub mod close_merchant_manager_tests;
+#[cfg(test)]
+pub mod close_program_state_tests;
+#[cfg(test)]
+pub mod close_user_delegate_tests;
+#[cfg(test)]
 pub mod common;
 #[cfg(test)]
+pub mod create_user_delegate_from_template_tests;
+#[cfg(test)]
+pub mod debit_user_exact_out_tests;
+#[cfg(test)]
+pub mod debit_user_logged_tests;
+#[cfg(test)]
+pub mod debit_user_sol_tests;
+#[cfg(test)]
 pub mod debit_user_tests;
 #[cfg(test)]
+pub mod debit_user_with_memo_tests;
+#[cfg(test)]
+pub mod debit_user_with_swap_tests;
+#[cfg(test)]
+pub mod event_parsing_tests;
+#[cfg(test)]
+pub mod initialize_merchant_registry_tests;
+#[cfg(test)]
 pub mod initialize_tests;
 #[cfg(test)]
+pub mod log_user_delegate_state_tests;
+#[cfg(test)]
+pub mod migrate_account_tests;
+#[cfg(test)]
+pub mod migrate_user_delegate_tests;
+#[cfg(test)]
+pub mod pda_tests;
+#[cfg(test)]
+pub mod reduce_user_delegate_limits_tests;
+#[cfg(test)]
+pub mod reissue_delegate_tests;
+#[cfg(test)]
+pub mod remove_merchant_manager_tests;
+#[cfg(test)]
+pub mod set_feature_flags_tests;
+#[cfg(test)]
+pub mod set_protocol_fee_tests;
+#[cfg(test)]
+pub mod simulate_debit_user_tests;
+#[cfg(test)]
+pub mod time_until_reset_tests;
+#[cfg(test)]
 pub mod update_admin_tests;
@@ -0,0 +1,111 @@
+use crate::common::*;
+use anchor_lang::prelude::*;
+use bridge_cards::accounts::LogUserDelegateState;
+use bridge_cards::event_parsing::{parse_events, BridgeCardsEvent};
+use bridge_cards::events::UserDelegateStateSnapshot;
+use bridge_cards::instructions::add_or_update_merchant_config::MerchantConfigParams;
+use bridge_cards::state::UserDelegateState;
+use solana_program_test::tokio;
+
+const MAX_TRANSFER_LIMIT: u64 = 100_000_000; // $100 per transaction
+const PERIOD_TRANSFER_LIMIT: u64 = 2_000_000_000; // $2000 per day
+const DEBIT_AMOUNT: u64 = 50_000_000; // $50 debit amount
+
+fn find_snapshot_event(logs: &[String]) -> Option<UserDelegateStateSnapshot> {
+    parse_events(logs)
+        .into_iter()
+        .find_map(|event| match event {
+            BridgeCardsEvent::UserDelegateStateSnapshot(event) => Some(event),
+            _ => None,
+        })
+}
+
+fn log_user_delegate_state(
+    ctx: &mut crate::common::Context,
+    debit_context: &DebitUserContext,
+) -> UserDelegateStateSnapshot {
+    // Anyone can log the state, the payer needs no authority
+    ctx.svm.expire_blockhash();
+    let accounts = LogUserDelegateState {
+        user_delegate_account: debit_context.user_delegate_pda,
+        merchant_config: make_merchant_config_pda(TEST_MERCHANT_ID, &ctx.program_id).pubkey,
+        mint: debit_context.mint_pk,
+        user_token_account: debit_context.user_token_account,
+    };
+    let ix = create_log_user_delegate_state_instruction(ctx, &accounts, TEST_MERCHANT_ID);
+    let tx = create_transaction(ctx, &[ix]);
+    let meta = submit_transaction(ctx, tx).unwrap();
+    find_snapshot_event(&meta.logs).expect("UserDelegateStateSnapshot not found")
+}
+
+#[tokio::test]
+async fn test_log_user_delegate_state() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT).unwrap();
+    let delegate_before = ctx
+        .svm
+        .get_account(&debit_context.user_delegate_pda)
+        .unwrap();
+
+    let event = log_user_delegate_state(&mut ctx, &debit_context);
+    let clock = ctx.svm.get_sysvar::<Clock>();
+    assert_eq!(event.merchant_id, TEST_MERCHANT_ID);
+    assert_eq!(event.mint, debit_context.mint_pk);
+    assert_eq!(event.user_ata, debit_context.user_token_account);
+    assert_eq!(event.user_delegate, debit_context.user_delegate_pda);
+    assert_eq!(event.per_transfer_limit, MAX_TRANSFER_LIMIT);
+    assert_eq!(event.period_transfer_limit, PERIOD_TRANSFER_LIMIT);
+    assert_eq!(event.period_transferred_amount, DEBIT_AMOUNT);
+    assert_eq!(event.transfer_limit_period_seconds, LIMIT_PERIOD);
+    assert_eq!(
+        event.remaining_period_allowance,
+        PERIOD_TRANSFER_LIMIT - DEBIT_AMOUNT
+    );
+    assert_eq!(event.timestamp, clock.unix_timestamp);
+
+    // The delegate account is not modified
+    let delegate_after = ctx
+        .svm
+        .get_account(&debit_context.user_delegate_pda)
+        .unwrap();
+    assert_eq!(delegate_before.data, delegate_after.data);
+}
+
+#[tokio::test]
+async fn test_log_user_delegate_state_follows_merchant_period_rules() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    setup_merchant_config(
+        &mut ctx,
+        TEST_MERCHANT_ID,
+        MerchantConfigParams {
+            reset_at_period_boundary: true,
+            ..Default::default()
+        },
+    );
+    debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT).unwrap();
+    let account = ctx
+        .svm
+        .get_account(&debit_context.user_delegate_pda)
+        .unwrap();
+    let delegate = UserDelegateState::try_deserialize(&mut account.data.as_slice()).unwrap();
+
+    // The merchant resets at the boundary, so the whole limit is available there
+    let mut clock = ctx.svm.get_sysvar::<Clock>();
+    clock.unix_timestamp = (delegate.period_timestamp_last_reset + u64::from(LIMIT_PERIOD)) as i64;
+    ctx.svm.set_sysvar(&clock);
+    let event = log_user_delegate_state(&mut ctx, &debit_context);
+    assert_eq!(event.remaining_period_allowance, PERIOD_TRANSFER_LIMIT);
+
+    // One second earlier the debit still counts
+    clock.unix_timestamp -= 1;
+    ctx.svm.set_sysvar(&clock);
+    let event = log_user_delegate_state(&mut ctx, &debit_context);
+    assert_eq!(
+        event.remaining_period_allowance,
+        PERIOD_TRANSFER_LIMIT - DEBIT_AMOUNT
+    );
+}
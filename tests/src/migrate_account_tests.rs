@@ -0,0 +1,450 @@
+use crate::common::*;
+use anchor_lang::prelude::*;
+use bridge_cards::accounts::MigrateAccount;
+use bridge_cards::event_parsing::{parse_events, BridgeCardsEvent};
+use bridge_cards::events::AccountMigrated;
+use bridge_cards::instructions::add_or_update_merchant_config::MerchantConfigParams;
+use bridge_cards::state::{
+    BridgeCardsState, MerchantDebitorState, MerchantDestinationState, MerchantManagerState,
+    UserDelegateState, CURRENT_VERSION,
+};
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer};
+
+const MAX_TRANSFER_LIMIT: u64 = 100_000_000; // $100 per transaction
+const PERIOD_TRANSFER_LIMIT: u64 = 2_000_000_000; // $2000 per day
+
+type TestContext = crate::common::Context;
+
+fn find_account_migrated_event(logs: &[String]) -> Option<AccountMigrated> {
+    parse_events(logs)
+        .into_iter()
+        .find_map(|event| match event {
+            BridgeCardsEvent::AccountMigrated(event) => Some(event),
+            _ => None,
+        })
+}
+
+/// Rewrite the merchant manager state in the single manager layout of version 1, or of the
+/// unversioned layout when versioned is false
+fn make_single_manager(ctx: &mut TestContext, versioned: bool) -> Vec<u8> {
+    let manager_state_pda = ctx.merchant_manager_state.pubkey;
+    let mut single_manager = ctx.svm.get_account(&manager_state_pda).unwrap();
+    let current_data = single_manager.data.clone();
+    let manager_state =
+        MerchantManagerState::try_deserialize(&mut current_data.as_slice()).unwrap();
+    let mut data = MerchantManagerState::DISCRIMINATOR.to_vec();
+    if versioned {
+        data.push(1);
+    }
+    data.extend_from_slice(manager_state.managers[0].as_ref());
+    data.push(manager_state.bump);
+    single_manager.data = data;
+    ctx.svm
+        .set_account(manager_state_pda, single_manager)
+        .unwrap();
+    current_data
+}
+
+/// Rewrite an account in the original program's layout, created before versioning, keeping
+/// only its original fields. Returns the current data
+fn make_unversioned(ctx: &mut TestContext, account: &Pubkey) -> Vec<u8> {
+    let mut unversioned = ctx.svm.get_account(account).unwrap();
+    let current_data = unversioned.data.clone();
+    let discriminator = &current_data[..8];
+    let mut data = discriminator.to_vec();
+    if discriminator == BridgeCardsState::DISCRIMINATOR {
+        let state = BridgeCardsState::try_deserialize(&mut current_data.as_slice()).unwrap();
+        data.extend_from_slice(state.admin.as_ref());
+        data.push(state.bump);
+    } else if discriminator == UserDelegateState::DISCRIMINATOR {
+        let delegate = UserDelegateState::try_deserialize(&mut current_data.as_slice()).unwrap();
+        for value in [
+            delegate.per_transfer_limit,
+            delegate.period_transfer_limit,
+            delegate.period_transferred_amount,
+            delegate.period_timestamp_last_reset,
+        ] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(&delegate.transfer_limit_period_seconds.to_le_bytes());
+        data.extend_from_slice(&delegate.slot_last_transferred.to_le_bytes());
+        data.push(delegate.bump);
+    } else if discriminator == MerchantDestinationState::DISCRIMINATOR {
+        let destination =
+            MerchantDestinationState::try_deserialize(&mut current_data.as_slice()).unwrap();
+        data.extend_from_slice(&[u8::from(destination.allowed), destination.bump]);
+    } else if discriminator == MerchantDebitorState::DISCRIMINATOR {
+        let debitor = MerchantDebitorState::try_deserialize(&mut current_data.as_slice()).unwrap();
+        data.extend_from_slice(&[u8::from(debitor.allowed), debitor.bump]);
+    } else {
+        panic!("Account type did not exist before versioning");
+    }
+    unversioned.data = data;
+    ctx.svm.set_account(*account, unversioned).unwrap();
+    current_data
+}
+
+#[allow(clippy::result_large_err)]
+fn migrate_account(
+    ctx: &mut TestContext,
+    admin_kp: &Keypair,
+    account: &Pubkey,
+) -> litesvm::types::TransactionResult {
+    ctx.svm.expire_blockhash();
+    let accounts = MigrateAccount {
+        admin: admin_kp.pubkey(),
+        payer: ctx.payer_pk,
+        state: ctx.bridge_cards_state.pubkey,
+        account: *account,
+        system_program: System::id(),
+    };
+    let ix = create_migrate_account_instruction(ctx, &accounts);
+    let tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, admin_kp],
+    );
+    submit_transaction(ctx, tx)
+}
+
+#[tokio::test]
+async fn test_new_accounts_have_current_version() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+
+    let data = |account: &Pubkey| ctx.svm.get_account(account).unwrap().data;
+    let state =
+        BridgeCardsState::try_deserialize(&mut data(&ctx.bridge_cards_state.pubkey).as_slice())
+            .unwrap();
+    assert_eq!(state.version, CURRENT_VERSION);
+    let user_delegate =
+        UserDelegateState::try_deserialize(&mut data(&debit_context.user_delegate_pda).as_slice())
+            .unwrap();
+    assert_eq!(user_delegate.version, CURRENT_VERSION);
+    let debitor = MerchantDebitorState::try_deserialize(
+        &mut data(&debit_context.debitor_state_pda).as_slice(),
+    )
+    .unwrap();
+    assert_eq!(debitor.version, CURRENT_VERSION);
+    let destination = MerchantDestinationState::try_deserialize(
+        &mut data(&debit_context.destination_state_pda).as_slice(),
+    )
+    .unwrap();
+    assert_eq!(destination.version, CURRENT_VERSION);
+}
+
+#[tokio::test]
+async fn test_migrate_account_is_idempotent() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    let mut account = ctx
+        .svm
+        .get_account(&debit_context.user_delegate_pda)
+        .unwrap();
+    let versioned_data = account.data.clone();
+
+    // A version 7 delegate lacks the trailing timestamp_last_transferred, still zero before
+    // the first debit
+    account.data[8] = 7;
+    account.data.truncate(versioned_data.len() - 8);
+    ctx.svm
+        .set_account(debit_context.user_delegate_pda, account)
+        .unwrap();
+
+    let admin_kp = ctx.payer_kp.insecure_clone();
+    let meta = migrate_account(&mut ctx, &admin_kp, &debit_context.user_delegate_pda)
+        .expect("Failed to migrate account");
+    let event = find_account_migrated_event(&meta.logs).expect("AccountMigrated event not found");
+    assert_eq!(event.account, debit_context.user_delegate_pda);
+    assert_eq!(event.previous_version, 7);
+    assert_eq!(event.version, CURRENT_VERSION);
+    let migrated = ctx
+        .svm
+        .get_account(&debit_context.user_delegate_pda)
+        .unwrap();
+    assert_eq!(migrated.data, versioned_data);
+
+    // Migrating again leaves the account unchanged
+    let meta = migrate_account(&mut ctx, &admin_kp, &debit_context.user_delegate_pda)
+        .expect("Migrating a current account should succeed");
+    assert!(find_account_migrated_event(&meta.logs).is_none());
+    let account = ctx
+        .svm
+        .get_account(&debit_context.user_delegate_pda)
+        .unwrap();
+    assert_eq!(account.data, versioned_data);
+    assert_eq!(account.lamports, migrated.lamports);
+}
+
+#[tokio::test]
+async fn test_migrate_unversioned_state() {
+    let mut ctx = setup_and_initialize();
+    let state_pda = ctx.bridge_cards_state.pubkey;
+    let versioned_data = make_unversioned(&mut ctx, &state_pda);
+
+    // The admin is read from the unversioned state
+    let admin_kp = ctx.payer_kp.insecure_clone();
+    let result = migrate_account(&mut ctx, &admin_kp, &state_pda);
+    assert!(
+        result.is_ok(),
+        "Failed to migrate the state: {:?}",
+        result.err()
+    );
+    assert_eq!(
+        ctx.svm.get_account(&state_pda).unwrap().data,
+        versioned_data
+    );
+}
+
+#[tokio::test]
+async fn test_migrate_unversioned_accounts() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    let admin_kp = ctx.payer_kp.insecure_clone();
+    let migrate_unversioned = |ctx: &mut TestContext, account: &Pubkey| {
+        let current_data = make_unversioned(ctx, account);
+        let meta = migrate_account(ctx, &admin_kp, account).expect("Failed to migrate account");
+        let event =
+            find_account_migrated_event(&meta.logs).expect("AccountMigrated event not found");
+        assert_eq!(event.previous_version, 0);
+        assert_eq!(event.version, CURRENT_VERSION);
+        (current_data, ctx.svm.get_account(account).unwrap().data)
+    };
+
+    // The original fields are kept and the fields added since are left at their defaults
+    let (current_data, data) = migrate_unversioned(&mut ctx, &debit_context.user_delegate_pda);
+    let current = UserDelegateState::try_deserialize(&mut current_data.as_slice()).unwrap();
+    let migrated = UserDelegateState::try_deserialize(&mut data.as_slice()).unwrap();
+    assert_eq!(migrated.version, CURRENT_VERSION);
+    assert_eq!(migrated.per_transfer_limit, current.per_transfer_limit);
+    assert_eq!(
+        migrated.period_transfer_limit,
+        current.period_transfer_limit
+    );
+    assert_eq!(
+        migrated.period_transferred_amount,
+        current.period_transferred_amount
+    );
+    assert_eq!(
+        migrated.period_timestamp_last_reset,
+        current.period_timestamp_last_reset
+    );
+    assert_eq!(
+        migrated.transfer_limit_period_seconds,
+        current.transfer_limit_period_seconds
+    );
+    assert_eq!(
+        migrated.slot_last_transferred,
+        current.slot_last_transferred
+    );
+    assert_eq!(migrated.bump, current.bump);
+    assert_eq!(migrated.metadata_hash, [0; 32]);
+    assert_eq!(migrated.expires_at, 0);
+    assert_eq!(migrated.debit_nonce, 0);
+    assert_eq!(migrated.restricted_destination, Pubkey::default());
+    assert_eq!(migrated.active_after, 0);
+
+    let (current_data, data) = migrate_unversioned(&mut ctx, &debit_context.destination_state_pda);
+    let current = MerchantDestinationState::try_deserialize(&mut current_data.as_slice()).unwrap();
+    let migrated = MerchantDestinationState::try_deserialize(&mut data.as_slice()).unwrap();
+    assert_eq!(migrated.version, CURRENT_VERSION);
+    assert_eq!(migrated.allowed, current.allowed);
+    assert!(!migrated.is_primary);
+    assert_eq!(migrated.bump, current.bump);
+    assert_eq!(migrated.last_received_amount, 0);
+    assert_eq!(migrated.last_received_timestamp, 0);
+
+    let (current_data, data) = migrate_unversioned(&mut ctx, &debit_context.debitor_state_pda);
+    let current = MerchantDebitorState::try_deserialize(&mut current_data.as_slice()).unwrap();
+    let migrated = MerchantDebitorState::try_deserialize(&mut data.as_slice()).unwrap();
+    assert_eq!(migrated.version, CURRENT_VERSION);
+    assert_eq!(migrated.allowed, current.allowed);
+    assert_eq!(migrated.per_call_limit, 0);
+    assert_eq!(migrated.daily_limit, 0);
+    assert_eq!(migrated.daily_transferred_amount, 0);
+    assert_eq!(migrated.daily_timestamp_last_reset, 0);
+    assert_eq!(migrated.bump, current.bump);
+}
+
+#[tokio::test]
+async fn test_migrate_older_user_delegate() {
+    for previous_version in [1, 2, 3, 4, 5, 6, 7, 8, 9] {
+        let mut ctx = setup_and_initialize();
+        let debit_context =
+            setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+        let mut account = ctx
+            .svm
+            .get_account(&debit_context.user_delegate_pda)
+            .unwrap();
+        let current_data = account.data.clone();
+
+        // Versions before 8 lack the trailing timestamp_last_transferred, versions before 7
+        // the active_after before it, versions before 6 the restricted_destination before
+        // those, versions before 5 the anchor_to_epoch and last_period_index before those,
+        // and versions 1 and 2 the strict_boundary before them
+        let missing = match previous_version {
+            7..=9 => 8,
+            6 => 16,
+            5 => 48,
+            3 | 4 => 57,
+            _ => 58,
+        };
+        account.data[8] = previous_version;
+        account.data.truncate(current_data.len() - missing);
+        // Migrated delegates are active at once, whenever the delegate was created
+        let mut expected_data = current_data.clone();
+        let active_after_offset = expected_data.len() - 16;
+        expected_data[active_after_offset..].fill(0);
+        ctx.svm
+            .set_account(debit_context.user_delegate_pda, account)
+            .unwrap();
+
+        let admin_kp = ctx.payer_kp.insecure_clone();
+        let meta = migrate_account(&mut ctx, &admin_kp, &debit_context.user_delegate_pda)
+            .expect("Failed to migrate account");
+        let event =
+            find_account_migrated_event(&meta.logs).expect("AccountMigrated event not found");
+        assert_eq!(event.previous_version, previous_version);
+        assert_eq!(
+            ctx.svm
+                .get_account(&debit_context.user_delegate_pda)
+                .unwrap()
+                .data,
+            expected_data
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_migrate_older_merchant_config() {
+    for previous_version in [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11] {
+        let mut ctx = setup_and_initialize();
+        let merchant_config_pda =
+            setup_merchant_config(&mut ctx, TEST_MERCHANT_ID, MerchantConfigParams::default());
+        let mut account = ctx.svm.get_account(&merchant_config_pda).unwrap();
+        let current_data = account.data.clone();
+
+        // Versions before 12 lack the trailing max_splits, versions before 11 the manager
+        // limit caps before it, versions before 10 the delegate limit template before those,
+        // versions before 8 the max_clock_skew_seconds before that, and versions before 4 the
+        // enforce_mint_allowlist before all of them
+        let missing = match previous_version {
+            11 => 1,
+            10 => 17,
+            8 | 9 => 37,
+            4..=7 => 41,
+            _ => 42,
+        };
+        account.data[8] = previous_version;
+        account.data.truncate(current_data.len() - missing);
+        ctx.svm.set_account(merchant_config_pda, account).unwrap();
+
+        let admin_kp = ctx.payer_kp.insecure_clone();
+        let meta = migrate_account(&mut ctx, &admin_kp, &merchant_config_pda)
+            .expect("Failed to migrate account");
+        let event =
+            find_account_migrated_event(&meta.logs).expect("AccountMigrated event not found");
+        assert_eq!(event.previous_version, previous_version);
+        assert_eq!(
+            ctx.svm.get_account(&merchant_config_pda).unwrap().data,
+            current_data
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_migrate_older_merchant_destination() {
+    for previous_version in [1, 2, 3, 4, 5, 6, 7, 8, 9] {
+        let mut ctx = setup_and_initialize();
+        let debit_context =
+            setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+        let mut account = ctx
+            .svm
+            .get_account(&debit_context.destination_state_pda)
+            .unwrap();
+        let current_data = account.data.clone();
+
+        // Versions before 9 lack the trailing last_received_amount and last_received_timestamp
+        account.data[8] = previous_version;
+        account.data.truncate(current_data.len() - 16);
+        ctx.svm
+            .set_account(debit_context.destination_state_pda, account)
+            .unwrap();
+
+        let admin_kp = ctx.payer_kp.insecure_clone();
+        let meta = migrate_account(&mut ctx, &admin_kp, &debit_context.destination_state_pda)
+            .expect("Failed to migrate account");
+        let event =
+            find_account_migrated_event(&meta.logs).expect("AccountMigrated event not found");
+        assert_eq!(event.previous_version, previous_version);
+        assert_eq!(
+            ctx.svm
+                .get_account(&debit_context.destination_state_pda)
+                .unwrap()
+                .data,
+            current_data
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_migrate_single_manager_account() {
+    for (versioned, previous_version) in [(true, 1), (false, 0)] {
+        let mut ctx = setup_and_initialize();
+        let current_data = make_single_manager(&mut ctx, versioned);
+
+        // The single manager becomes the only entry of the managers list
+        let admin_kp = ctx.payer_kp.insecure_clone();
+        let manager_state_pda = ctx.merchant_manager_state.pubkey;
+        let meta = migrate_account(&mut ctx, &admin_kp, &manager_state_pda)
+            .expect("Failed to migrate the manager state");
+        let event =
+            find_account_migrated_event(&meta.logs).expect("AccountMigrated event not found");
+        assert_eq!(event.previous_version, previous_version);
+        assert_eq!(event.version, CURRENT_VERSION);
+        assert_eq!(
+            ctx.svm.get_account(&manager_state_pda).unwrap().data,
+            current_data
+        );
+
+        // The migrated manager can still configure debitors
+        let manager_kp = ctx.merchant_manager_kp.insecure_clone();
+        let mint_pk = setup_mint(&mut ctx);
+        let result =
+            configure_merchant_debitor(&mut ctx, &manager_kp, mint_pk, Pubkey::new_unique(), true);
+        assert!(
+            result.is_ok(),
+            "Migrated manager failed to add a debitor: {:?}",
+            result.err()
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_non_admin_cannot_migrate_account() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    make_unversioned(&mut ctx, &debit_context.user_delegate_pda);
+    let unversioned_data = ctx
+        .svm
+        .get_account(&debit_context.user_delegate_pda)
+        .unwrap()
+        .data;
+
+    let (non_admin_kp, _) = setup_keypair(&mut ctx);
+    let result = migrate_account(&mut ctx, &non_admin_kp, &debit_context.user_delegate_pda);
+    assert!(result.is_err(), "Non-admin should not be able to migrate");
+    assert_eq!(
+        ctx.svm
+            .get_account(&debit_context.user_delegate_pda)
+            .unwrap()
+            .data,
+        unversioned_data
+    );
+}
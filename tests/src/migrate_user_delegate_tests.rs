@@ -0,0 +1,288 @@
+use crate::common::*;
+use account_data_trait::AccountData;
+use anchor_lang::prelude::*;
+use bridge_cards::accounts::MigrateUserDelegate;
+use bridge_cards::errors::ErrorCode;
+use bridge_cards::event_parsing::{parse_events, BridgeCardsEvent};
+use bridge_cards::events::UserDelegateMigrated;
+use bridge_cards::state::UserDelegateState;
+use litesvm::types::TransactionResult;
+use litesvm_token::{ApproveChecked, CreateAccount, Transfer};
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer};
+
+const MAX_TRANSFER_LIMIT: u64 = 100_000_000; // $100 per transaction
+const PERIOD_TRANSFER_LIMIT: u64 = 2_000_000_000; // $2000 per day
+const DEBIT_AMOUNT: u64 = 50_000_000; // $50 debit amount
+
+type TestContext = crate::common::Context;
+
+fn find_user_delegate_migrated_event(logs: &[String]) -> Option<UserDelegateMigrated> {
+    parse_events(logs)
+        .into_iter()
+        .find_map(|event| match event {
+            BridgeCardsEvent::UserDelegateMigrated(event) => Some(event),
+            _ => None,
+        })
+}
+
+fn get_user_delegate_state(ctx: &TestContext, user_delegate_pda: &Pubkey) -> UserDelegateState {
+    let account = ctx.svm.get_account(user_delegate_pda).unwrap();
+    UserDelegateState::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+/// Create a second, non-associated token account of the user
+fn setup_new_user_token_account(ctx: &mut TestContext, debit_context: &DebitUserContext) -> Pubkey {
+    CreateAccount::new(&mut ctx.svm, &ctx.payer_kp, &debit_context.mint_pk)
+        .owner(&debit_context.user_pk)
+        .token_program_id(&debit_context.token_program.program_id())
+        .send()
+        .unwrap()
+}
+
+#[allow(clippy::result_large_err)]
+fn migrate_user_delegate(
+    ctx: &mut TestContext,
+    debit_context: &DebitUserContext,
+    manager: &Keypair,
+    user_owner: &Keypair,
+    new_user_token_account: Pubkey,
+) -> TransactionResult {
+    ctx.svm.expire_blockhash();
+    let accounts = MigrateUserDelegate {
+        manager: manager.pubkey(),
+        payer: ctx.payer_pk,
+        manager_state: ctx.merchant_manager_state.pubkey,
+        old_user_token_account: debit_context.user_token_account,
+        new_user_token_account,
+        user_owner: user_owner.pubkey(),
+        mint: debit_context.mint_pk,
+        old_user_delegate_account: debit_context.user_delegate_pda,
+        new_user_delegate_account: make_user_delegate_pda(
+            TEST_MERCHANT_ID,
+            &debit_context.mint_pk,
+            &new_user_token_account,
+            &ctx.program_id,
+        )
+        .pubkey,
+        system_program: anchor_lang::system_program::ID,
+    };
+    let ix = create_migrate_user_delegate_instruction(ctx, &accounts, TEST_MERCHANT_ID);
+    let tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, manager, user_owner],
+    );
+    submit_transaction(ctx, tx)
+}
+
+fn assert_migrate_error(result: TransactionResult, error: ErrorCode) {
+    let err = result.expect_err("Migration should fail");
+    let expected_message = error.to_string();
+    assert!(
+        err.meta
+            .logs
+            .iter()
+            .any(|log| log.contains(&expected_message)),
+        "Error should contain the expected error message {}, got {}",
+        expected_message,
+        err.meta.logs.join("\n")
+    );
+}
+
+#[tokio::test]
+async fn test_migrate_user_delegate() {
+    for token_program in [TokenProgram::Token, TokenProgram::Token2022] {
+        let mut ctx = setup_and_initialize();
+        let debit_context = setup_merchant_and_user_delegate_with_program(
+            &mut ctx,
+            MAX_TRANSFER_LIMIT,
+            PERIOD_TRANSFER_LIMIT,
+            token_program,
+        );
+        debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT).expect("Debit failed");
+        let old_state = get_user_delegate_state(&ctx, &debit_context.user_delegate_pda);
+        assert_eq!(old_state.period_transferred_amount, DEBIT_AMOUNT);
+
+        let new_user_token_account = setup_new_user_token_account(&mut ctx, &debit_context);
+        let new_user_delegate_pda = make_user_delegate_pda(
+            TEST_MERCHANT_ID,
+            &debit_context.mint_pk,
+            &new_user_token_account,
+            &ctx.program_id,
+        );
+        let manager = ctx.merchant_manager_kp.insecure_clone();
+        let meta = migrate_user_delegate(
+            &mut ctx,
+            &debit_context,
+            &manager,
+            &debit_context.user_kp,
+            new_user_token_account,
+        )
+        .expect("Failed to migrate user delegate");
+
+        let event = find_user_delegate_migrated_event(&meta.logs)
+            .expect("UserDelegateMigrated event not found");
+        assert_eq!(event.merchant_id, TEST_MERCHANT_ID);
+        assert_eq!(event.mint, debit_context.mint_pk);
+        assert_eq!(event.old_user_ata, debit_context.user_token_account);
+        assert_eq!(event.new_user_ata, new_user_token_account);
+        assert_eq!(event.old_user_delegate, debit_context.user_delegate_pda);
+        assert_eq!(event.new_user_delegate, new_user_delegate_pda.pubkey);
+
+        // The old delegate is closed
+        let closed = ctx.svm.get_account(&debit_context.user_delegate_pda);
+        assert!(closed.is_none() || closed.unwrap().lamports == 0);
+
+        // Limits and accumulated spend carry over, only the bump differs
+        let new_state = get_user_delegate_state(&ctx, &new_user_delegate_pda.pubkey);
+        assert_eq!(new_state.period_transferred_amount, DEBIT_AMOUNT);
+        assert_eq!(new_state.debit_nonce, old_state.debit_nonce);
+        let expected_data = UserDelegateState {
+            bump: new_user_delegate_pda.bump,
+            ..old_state
+        }
+        .account_data();
+        let account = ctx.svm.get_account(&new_user_delegate_pda.pubkey).unwrap();
+        assert_eq!(account.owner, ctx.program_id);
+        assert_eq!(account.data, expected_data);
+    }
+}
+
+#[tokio::test]
+async fn test_migrated_user_delegate_keeps_period_spend() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, 3 * DEBIT_AMOUNT);
+    debit_user(&mut ctx, &debit_context, 2 * DEBIT_AMOUNT).expect("Debit failed");
+
+    let new_user_token_account = setup_new_user_token_account(&mut ctx, &debit_context);
+    let manager = ctx.merchant_manager_kp.insecure_clone();
+    migrate_user_delegate(
+        &mut ctx,
+        &debit_context,
+        &manager,
+        &debit_context.user_kp,
+        new_user_token_account,
+    )
+    .expect("Failed to migrate user delegate");
+
+    // The user moves their funds and approves the new delegate
+    let new_debit_context = DebitUserContext {
+        user_token_account: new_user_token_account,
+        user_delegate_pda: make_user_delegate_pda(
+            TEST_MERCHANT_ID,
+            &debit_context.mint_pk,
+            &new_user_token_account,
+            &ctx.program_id,
+        )
+        .pubkey,
+        debitor_kp: debit_context.debitor_kp.insecure_clone(),
+        user_kp: debit_context.user_kp.insecure_clone(),
+        ..debit_context
+    };
+    Transfer::new(
+        &mut ctx.svm,
+        &new_debit_context.user_kp,
+        &new_debit_context.mint_pk,
+        &new_user_token_account,
+        INITIAL_BALANCE - 2 * DEBIT_AMOUNT,
+    )
+    .source(&debit_context.user_token_account)
+    .owner(&new_debit_context.user_kp)
+    .token_program_id(&new_debit_context.token_program.program_id())
+    .send()
+    .unwrap();
+    ApproveChecked::new(
+        &mut ctx.svm,
+        &new_debit_context.user_kp,
+        &new_debit_context.user_delegate_pda,
+        &new_debit_context.mint_pk,
+        1e18 as u64,
+    )
+    .source(&new_user_token_account)
+    .token_program_id(&new_debit_context.token_program.program_id())
+    .send()
+    .unwrap();
+
+    // Only the rest of the period limit can be debited through the new delegate
+    let result = debit_user(&mut ctx, &new_debit_context, 2 * DEBIT_AMOUNT);
+    let err = result.expect_err("Debit over the remaining period limit should fail");
+    let expected_message = ErrorCode::ExceedsTransferLimitPerPeriod.to_string();
+    assert!(
+        err.meta
+            .logs
+            .iter()
+            .any(|log| log.contains(&expected_message)),
+        "Error should contain the expected error message {}, got {}",
+        expected_message,
+        err.meta.logs.join("\n")
+    );
+    debit_user(&mut ctx, &new_debit_context, DEBIT_AMOUNT).expect("Debit failed");
+}
+
+#[tokio::test]
+async fn test_non_manager_cannot_migrate_user_delegate() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    let new_user_token_account = setup_new_user_token_account(&mut ctx, &debit_context);
+    let non_manager = Keypair::new();
+
+    let result = migrate_user_delegate(
+        &mut ctx,
+        &debit_context,
+        &non_manager,
+        &debit_context.user_kp,
+        new_user_token_account,
+    );
+    assert!(
+        result.is_err(),
+        "Non-manager should not be able to migrate a user delegate"
+    );
+    assert!(ctx
+        .svm
+        .get_account(&debit_context.user_delegate_pda)
+        .is_some());
+}
+
+#[tokio::test]
+async fn test_cannot_migrate_user_delegate_to_another_user() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    let (other_user_kp, other_user_pk) = setup_keypair(&mut ctx);
+    let other_user_token_account =
+        CreateAccount::new(&mut ctx.svm, &ctx.payer_kp, &debit_context.mint_pk)
+            .owner(&other_user_pk)
+            .token_program_id(&debit_context.token_program.program_id())
+            .send()
+            .unwrap();
+    let manager = ctx.merchant_manager_kp.insecure_clone();
+
+    // The new token account's owner must sign
+    let result = migrate_user_delegate(
+        &mut ctx,
+        &debit_context,
+        &manager,
+        &debit_context.user_kp,
+        other_user_token_account,
+    );
+    assert_migrate_error(result, ErrorCode::UserTokenAccountOwnerMismatch);
+
+    // Its owner signing doesn't help while the old token account belongs to someone else
+    let result = migrate_user_delegate(
+        &mut ctx,
+        &debit_context,
+        &manager,
+        &other_user_kp,
+        other_user_token_account,
+    );
+    assert_migrate_error(result, ErrorCode::UserTokenAccountOwnerMismatch);
+
+    assert!(ctx
+        .svm
+        .get_account(&debit_context.user_delegate_pda)
+        .is_some_and(|account| account.lamports > 0));
+}
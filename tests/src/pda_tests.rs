@@ -0,0 +1,189 @@
+use crate::common::*;
+use bridge_cards::pda;
+use solana_sdk::pubkey::Pubkey;
+
+fn assert_pda_eq(derived: (Pubkey, u8), expected: PDAWithBump) {
+    assert_eq!(derived.0, expected.pubkey, "PDA address mismatch");
+    assert_eq!(derived.1, expected.bump, "PDA bump mismatch");
+}
+
+#[test]
+fn test_find_state_matches_test_helper() {
+    assert_pda_eq(pda::find_state(), make_pda(&[b"state"], &bridge_cards::ID));
+}
+
+#[test]
+fn test_find_merchant_manager_matches_test_helper() {
+    for merchant_id in [0, TEST_MERCHANT_ID, u64::MAX] {
+        assert_pda_eq(
+            pda::find_merchant_manager(merchant_id),
+            make_manager_pda(merchant_id, &bridge_cards::ID),
+        );
+    }
+}
+
+#[test]
+fn test_find_merchant_debitor_matches_test_helper() {
+    let mint = Pubkey::new_unique();
+    let debitor = Pubkey::new_unique();
+    assert_pda_eq(
+        pda::find_merchant_debitor(TEST_MERCHANT_ID, &mint, &debitor),
+        make_merchant_debitor_pda(TEST_MERCHANT_ID, &debitor, &mint, &bridge_cards::ID),
+    );
+}
+
+#[test]
+fn test_find_merchant_destination_matches_test_helper() {
+    let mint = Pubkey::new_unique();
+    let destination = Pubkey::new_unique();
+    assert_pda_eq(
+        pda::find_merchant_destination(TEST_MERCHANT_ID, &mint, &destination),
+        make_merchant_destination_pda(TEST_MERCHANT_ID, &mint, &destination, &bridge_cards::ID),
+    );
+}
+
+#[test]
+fn test_find_user_delegate_matches_test_helper() {
+    let mint = Pubkey::new_unique();
+    let user_ata = Pubkey::new_unique();
+    assert_pda_eq(
+        pda::find_user_delegate(TEST_MERCHANT_ID, &mint, &user_ata),
+        make_user_delegate_pda(TEST_MERCHANT_ID, &mint, &user_ata, &bridge_cards::ID),
+    );
+}
+
+#[test]
+fn test_find_merchant_config_matches_test_helper() {
+    for merchant_id in [0, TEST_MERCHANT_ID, u64::MAX] {
+        assert_pda_eq(
+            pda::find_merchant_config(merchant_id),
+            make_merchant_config_pda(merchant_id, &bridge_cards::ID),
+        );
+    }
+}
+
+#[test]
+fn test_find_merchant_spend_tracker_matches_test_helper() {
+    for merchant_id in [0, TEST_MERCHANT_ID, u64::MAX] {
+        assert_pda_eq(
+            pda::find_merchant_spend_tracker(merchant_id),
+            make_merchant_spend_tracker_pda(merchant_id, &bridge_cards::ID),
+        );
+    }
+}
+
+/// Derive the canonical PDA from a seed vector, as close_account does without a bump
+fn find_from_seeds(seeds: Vec<Vec<u8>>) -> (Pubkey, u8) {
+    let seeds: Vec<&[u8]> = seeds.iter().map(Vec::as_slice).collect();
+    Pubkey::find_program_address(&seeds, &bridge_cards::ID)
+}
+
+#[test]
+fn test_seeds_derive_to_find_helpers() {
+    let mint = Pubkey::new_unique();
+    let account = Pubkey::new_unique();
+    assert_eq!(find_from_seeds(pda::state_seeds()), pda::find_state());
+    for merchant_id in [0, TEST_MERCHANT_ID, u64::MAX] {
+        assert_eq!(
+            find_from_seeds(pda::merchant_manager_seeds(merchant_id)),
+            pda::find_merchant_manager(merchant_id)
+        );
+        assert_eq!(
+            find_from_seeds(pda::merchant_config_seeds(merchant_id)),
+            pda::find_merchant_config(merchant_id)
+        );
+        assert_eq!(
+            find_from_seeds(pda::merchant_spend_tracker_seeds(merchant_id)),
+            pda::find_merchant_spend_tracker(merchant_id)
+        );
+        assert_eq!(
+            find_from_seeds(pda::merchant_debitor_seeds(merchant_id, &mint, &account)),
+            pda::find_merchant_debitor(merchant_id, &mint, &account)
+        );
+        assert_eq!(
+            find_from_seeds(pda::merchant_destination_seeds(
+                merchant_id,
+                &mint,
+                &account
+            )),
+            pda::find_merchant_destination(merchant_id, &mint, &account)
+        );
+        assert_eq!(
+            find_from_seeds(pda::user_delegate_seeds(merchant_id, &mint, &account)),
+            pda::find_user_delegate(merchant_id, &mint, &account)
+        );
+        assert_eq!(
+            find_from_seeds(pda::merchant_mint_seeds(merchant_id, &mint)),
+            pda::find_merchant_mint(merchant_id, &mint)
+        );
+    }
+    assert_eq!(
+        find_from_seeds(pda::router_seeds(&account)),
+        pda::find_router(&account)
+    );
+    for debit_nonce in [0, 1, u64::MAX] {
+        assert_eq!(
+            find_from_seeds(pda::debit_receipt_seeds(&account, debit_nonce)),
+            pda::find_debit_receipt(&account, debit_nonce)
+        );
+    }
+}
+
+#[test]
+fn test_find_debit_receipt_matches_test_helper() {
+    let user_delegate = Pubkey::new_unique();
+    for debit_nonce in [1, 2, u64::MAX] {
+        assert_pda_eq(
+            pda::find_debit_receipt(&user_delegate, debit_nonce),
+            make_debit_receipt_pda(&user_delegate, debit_nonce, &bridge_cards::ID),
+        );
+    }
+}
+
+#[test]
+fn test_find_router_matches_test_helper() {
+    let router_program = Pubkey::new_unique();
+    assert_pda_eq(
+        pda::find_router(&router_program),
+        make_router_pda(&router_program, &bridge_cards::ID),
+    );
+}
+
+#[test]
+fn test_merchant_id_seed_is_little_endian() {
+    assert_eq!(pda::merchant_id_seed(0), [0; 8]);
+    assert_eq!(
+        pda::merchant_id_seed(0x0102_0304_0506_0708),
+        [8, 7, 6, 5, 4, 3, 2, 1]
+    );
+    for merchant_id in [0, TEST_MERCHANT_ID, u64::MAX] {
+        assert_eq!(
+            pda::merchant_id_seed(merchant_id),
+            merchant_id.to_le_bytes()
+        );
+    }
+}
+
+#[test]
+fn test_merchant_scoped_pdas_differ_by_merchant() {
+    // The merchant id must actually reach every merchant-scoped derivation
+    let mint = Pubkey::new_unique();
+    let account = Pubkey::new_unique();
+    let derive_all = |merchant_id: u64| {
+        [
+            pda::find_merchant_manager(merchant_id).0,
+            pda::find_merchant_config(merchant_id).0,
+            pda::find_merchant_spend_tracker(merchant_id).0,
+            pda::find_merchant_debitor(merchant_id, &mint, &account).0,
+            pda::find_merchant_destination(merchant_id, &mint, &account).0,
+            pda::find_user_delegate(merchant_id, &mint, &account).0,
+        ]
+    };
+    let (first, second) = (
+        derive_all(TEST_MERCHANT_ID),
+        derive_all(TEST_MERCHANT_ID + 1),
+    );
+    for (first, second) in first.iter().zip(second.iter()) {
+        assert_ne!(first, second);
+    }
+}
@@ -0,0 +1,148 @@
+use crate::common::*;
+use anchor_lang::prelude::*;
+use bridge_cards::accounts::ReduceUserDelegateLimits;
+use bridge_cards::errors::ErrorCode;
+use bridge_cards::event_parsing::{parse_events, BridgeCardsEvent};
+use bridge_cards::events::UserDelegateAddedOrUpdated;
+use bridge_cards::state::UserDelegateState;
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer};
+
+const MAX_TRANSFER_LIMIT: u64 = 100_000_000; // $100 per transaction
+const PERIOD_TRANSFER_LIMIT: u64 = 2_000_000_000; // $2000 per day
+
+type TestContext = crate::common::Context;
+
+fn find_user_delegate_event(logs: &[String]) -> Option<UserDelegateAddedOrUpdated> {
+    parse_events(logs)
+        .into_iter()
+        .find_map(|event| match event {
+            BridgeCardsEvent::UserDelegateAddedOrUpdated(event) => Some(event),
+            _ => None,
+        })
+}
+
+fn get_user_delegate_state(
+    ctx: &TestContext,
+    debit_context: &DebitUserContext,
+) -> UserDelegateState {
+    let account = ctx
+        .svm
+        .get_account(&debit_context.user_delegate_pda)
+        .unwrap();
+    UserDelegateState::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+#[allow(clippy::result_large_err)]
+fn reduce_limits(
+    ctx: &mut TestContext,
+    debit_context: &DebitUserContext,
+    user_kp: &Keypair,
+    per_transfer_limit: u64,
+    period_transfer_limit: u64,
+) -> litesvm::types::TransactionResult {
+    let accounts = ReduceUserDelegateLimits {
+        user: user_kp.pubkey(),
+        user_token_account: debit_context.user_token_account,
+        mint: debit_context.mint_pk,
+        user_delegate_account: debit_context.user_delegate_pda,
+    };
+    let ix = create_reduce_user_delegate_limits_instruction(
+        ctx,
+        &accounts,
+        TEST_MERCHANT_ID,
+        per_transfer_limit,
+        period_transfer_limit,
+    );
+    let tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, user_kp],
+    );
+    submit_transaction(ctx, tx)
+}
+
+#[tokio::test]
+async fn test_user_reduces_own_limits() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+
+    let result = reduce_limits(
+        &mut ctx,
+        &debit_context,
+        &debit_context.user_kp,
+        MAX_TRANSFER_LIMIT / 2,
+        PERIOD_TRANSFER_LIMIT / 2,
+    );
+    assert!(
+        result.is_ok(),
+        "Failed to reduce delegate limits: {:?}",
+        result.err()
+    );
+
+    let event = find_user_delegate_event(&result.unwrap().logs)
+        .expect("UserDelegateAddedOrUpdated event not found");
+    assert_eq!(event.merchant_id, TEST_MERCHANT_ID);
+    assert_eq!(event.mint, debit_context.mint_pk);
+    assert_eq!(event.user_ata, debit_context.user_token_account);
+    assert_eq!(event.user_delegate, debit_context.user_delegate_pda);
+    assert_eq!(event.previous_per_transfer_limit, MAX_TRANSFER_LIMIT);
+    assert_eq!(event.new_per_transfer_limit, MAX_TRANSFER_LIMIT / 2);
+    assert_eq!(event.previous_period_transfer_limit, PERIOD_TRANSFER_LIMIT);
+    assert_eq!(event.new_period_transfer_limit, PERIOD_TRANSFER_LIMIT / 2);
+
+    let state = get_user_delegate_state(&ctx, &debit_context);
+    assert_eq!(state.per_transfer_limit, MAX_TRANSFER_LIMIT / 2);
+    assert_eq!(state.period_transfer_limit, PERIOD_TRANSFER_LIMIT / 2);
+}
+
+#[tokio::test]
+async fn test_user_cannot_increase_own_limits() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+
+    // Lowering one limit does not allow raising the other
+    let result = reduce_limits(
+        &mut ctx,
+        &debit_context,
+        &debit_context.user_kp,
+        MAX_TRANSFER_LIMIT / 2,
+        PERIOD_TRANSFER_LIMIT + 1,
+    );
+    let err = result.expect_err("Increasing the period limit should fail");
+    let expected_message = ErrorCode::CannotIncreaseOwnLimit.to_string();
+    assert!(
+        err.meta
+            .logs
+            .iter()
+            .any(|log| log.contains(&expected_message)),
+        "Error should contain the expected error message {}, got {}",
+        expected_message,
+        err.meta.logs.join("\n")
+    );
+
+    let state = get_user_delegate_state(&ctx, &debit_context);
+    assert_eq!(state.per_transfer_limit, MAX_TRANSFER_LIMIT);
+    assert_eq!(state.period_transfer_limit, PERIOD_TRANSFER_LIMIT);
+}
+
+#[tokio::test]
+async fn test_reduce_limits_requires_token_account_owner() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+
+    let (other_kp, _) = setup_keypair(&mut ctx);
+    let result = reduce_limits(&mut ctx, &debit_context, &other_kp, 0, 0);
+    assert!(
+        result.is_err(),
+        "Only the token account owner should be able to reduce the limits"
+    );
+
+    let state = get_user_delegate_state(&ctx, &debit_context);
+    assert_eq!(state.per_transfer_limit, MAX_TRANSFER_LIMIT);
+    assert_eq!(state.period_transfer_limit, PERIOD_TRANSFER_LIMIT);
+}
@@ -0,0 +1,320 @@
+use crate::common::*;
+use anchor_lang::prelude::*;
+use bridge_cards::accounts::ReissueDelegate;
+use bridge_cards::errors::ErrorCode;
+use bridge_cards::event_parsing::{parse_events, BridgeCardsEvent};
+use bridge_cards::events::UserDelegateReissued;
+use bridge_cards::instructions::add_or_update_merchant_config::MerchantConfigParams;
+use bridge_cards::state::UserDelegateState;
+use litesvm::types::TransactionResult;
+use solana_program_test::tokio;
+use solana_sdk::signature::Signer;
+
+const MAX_TRANSFER_LIMIT: u64 = 100_000_000; // $100 per transaction
+const PERIOD_TRANSFER_LIMIT: u64 = 2_000_000_000; // $2000 per day
+const DEBIT_AMOUNT: u64 = 50_000_000; // $50
+
+type TestContext = crate::common::Context;
+
+fn find_reissued_event(logs: &[String]) -> Option<UserDelegateReissued> {
+    parse_events(logs)
+        .into_iter()
+        .find_map(|event| match event {
+            BridgeCardsEvent::UserDelegateReissued(event) => Some(event),
+            _ => None,
+        })
+}
+
+fn get_user_delegate_state(
+    ctx: &TestContext,
+    debit_context: &DebitUserContext,
+) -> UserDelegateState {
+    let account = ctx
+        .svm
+        .get_account(&debit_context.user_delegate_pda)
+        .unwrap();
+    UserDelegateState::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+#[allow(clippy::result_large_err)]
+fn try_reissue_delegate(
+    ctx: &mut TestContext,
+    debit_context: &DebitUserContext,
+    per_transfer_limit: u64,
+    period_transfer_limit: u64,
+    transfer_limit_period: u32,
+    carry_usage: bool,
+) -> TransactionResult {
+    ctx.svm.expire_blockhash();
+    let accounts = ReissueDelegate {
+        manager: ctx.merchant_manager_kp.pubkey(),
+        manager_state: ctx.merchant_manager_state.pubkey,
+        merchant_config: make_merchant_config_pda(TEST_MERCHANT_ID, &ctx.program_id).pubkey,
+        user_token_account: debit_context.user_token_account,
+        mint: debit_context.mint_pk,
+        user_delegate_account: debit_context.user_delegate_pda,
+    };
+    let ix = create_reissue_delegate_instruction(
+        ctx,
+        &accounts,
+        TEST_MERCHANT_ID,
+        per_transfer_limit,
+        period_transfer_limit,
+        transfer_limit_period,
+        carry_usage,
+    );
+    let tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &ctx.merchant_manager_kp],
+    );
+    submit_transaction(ctx, tx)
+}
+
+fn reissue_delegate(
+    ctx: &mut TestContext,
+    debit_context: &DebitUserContext,
+    carry_usage: bool,
+) -> UserDelegateReissued {
+    let result = try_reissue_delegate(
+        ctx,
+        debit_context,
+        MAX_TRANSFER_LIMIT / 2,
+        PERIOD_TRANSFER_LIMIT / 2,
+        LIMIT_PERIOD,
+        carry_usage,
+    );
+    assert!(
+        result.is_ok(),
+        "Failed to reissue delegate: {:?}",
+        result.err()
+    );
+    find_reissued_event(&result.unwrap().logs).expect("UserDelegateReissued event not found")
+}
+
+fn assert_reissue_error(result: TransactionResult, error: ErrorCode) {
+    let err = result.expect_err("Reissue should fail");
+    let expected_message = error.to_string();
+    assert!(
+        err.meta
+            .logs
+            .iter()
+            .any(|log| log.contains(&expected_message)),
+        "Error should contain the expected error message {}, got {}",
+        expected_message,
+        err.meta.logs.join("\n")
+    );
+}
+
+#[tokio::test]
+async fn test_reissue_delegate_carries_usage() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT).unwrap();
+    let before = get_user_delegate_state(&ctx, &debit_context);
+
+    let event = reissue_delegate(&mut ctx, &debit_context, true);
+    assert_eq!(event.merchant_id, TEST_MERCHANT_ID);
+    assert_eq!(event.user_delegate, debit_context.user_delegate_pda);
+    assert_eq!(event.per_transfer_limit, MAX_TRANSFER_LIMIT / 2);
+    assert_eq!(event.period_transfer_limit, PERIOD_TRANSFER_LIMIT / 2);
+    assert!(event.carry_usage);
+    assert_eq!(event.period_transferred_amount, DEBIT_AMOUNT);
+    assert_eq!(event.reissue_count, 1);
+
+    // Limits change, usage is preserved
+    let state = get_user_delegate_state(&ctx, &debit_context);
+    assert_eq!(state.per_transfer_limit, MAX_TRANSFER_LIMIT / 2);
+    assert_eq!(state.period_transfer_limit, PERIOD_TRANSFER_LIMIT / 2);
+    assert_eq!(state.period_transferred_amount, DEBIT_AMOUNT);
+    assert_eq!(
+        state.period_timestamp_last_reset,
+        before.period_timestamp_last_reset
+    );
+    assert_eq!(state.reissue_count, 1);
+}
+
+#[tokio::test]
+async fn test_reissue_delegate_resets_usage() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT).unwrap();
+
+    let event = reissue_delegate(&mut ctx, &debit_context, false);
+    assert!(!event.carry_usage);
+    assert_eq!(event.period_transferred_amount, 0);
+    assert_eq!(event.reissue_count, 1);
+
+    let state = get_user_delegate_state(&ctx, &debit_context);
+    assert_eq!(state.per_transfer_limit, MAX_TRANSFER_LIMIT / 2);
+    assert_eq!(state.period_transferred_amount, 0);
+    assert_eq!(state.period_timestamp_last_reset, 0);
+}
+
+#[tokio::test]
+async fn test_reissue_delegate_increments_count() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+
+    assert_eq!(
+        reissue_delegate(&mut ctx, &debit_context, true).reissue_count,
+        1
+    );
+    ctx.svm.expire_blockhash();
+    assert_eq!(
+        reissue_delegate(&mut ctx, &debit_context, false).reissue_count,
+        2
+    );
+    ctx.svm.expire_blockhash();
+    assert_eq!(
+        reissue_delegate(&mut ctx, &debit_context, true).reissue_count,
+        3
+    );
+
+    let state = get_user_delegate_state(&ctx, &debit_context);
+    assert_eq!(state.reissue_count, 3);
+}
+
+#[tokio::test]
+async fn test_reissue_delegate_limits_above_merchant_cap_rejected() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    setup_merchant_config(
+        &mut ctx,
+        TEST_MERCHANT_ID,
+        MerchantConfigParams {
+            max_allowed_per_transfer: MAX_TRANSFER_LIMIT,
+            max_allowed_period_limit: PERIOD_TRANSFER_LIMIT,
+            ..Default::default()
+        },
+    );
+
+    let result = try_reissue_delegate(
+        &mut ctx,
+        &debit_context,
+        MAX_TRANSFER_LIMIT + 1,
+        PERIOD_TRANSFER_LIMIT,
+        LIMIT_PERIOD,
+        true,
+    );
+    assert_reissue_error(result, ErrorCode::LimitExceedsMerchantCap);
+    let result = try_reissue_delegate(
+        &mut ctx,
+        &debit_context,
+        MAX_TRANSFER_LIMIT,
+        PERIOD_TRANSFER_LIMIT + 1,
+        LIMIT_PERIOD,
+        true,
+    );
+    assert_reissue_error(result, ErrorCode::LimitExceedsMerchantCap);
+
+    // Limits at the caps are accepted
+    let result = try_reissue_delegate(
+        &mut ctx,
+        &debit_context,
+        MAX_TRANSFER_LIMIT,
+        PERIOD_TRANSFER_LIMIT,
+        LIMIT_PERIOD,
+        true,
+    );
+    assert!(result.is_ok(), "Reissue failed: {:?}", result.err());
+}
+
+#[tokio::test]
+async fn test_reissue_delegate_rejects_overflowing_period_end() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    let mut state = get_user_delegate_state(&ctx, &debit_context);
+    state.period_timestamp_last_reset = u64::MAX - u64::from(LIMIT_PERIOD) + 1;
+    let mut account = ctx
+        .svm
+        .get_account(&debit_context.user_delegate_pda)
+        .unwrap();
+    state
+        .try_serialize(&mut account.data.as_mut_slice())
+        .unwrap();
+    ctx.svm
+        .set_account(debit_context.user_delegate_pda, account)
+        .unwrap();
+
+    // Carrying the usage keeps a period start the new period can't follow
+    let result = try_reissue_delegate(
+        &mut ctx,
+        &debit_context,
+        MAX_TRANSFER_LIMIT,
+        PERIOD_TRANSFER_LIMIT,
+        LIMIT_PERIOD,
+        true,
+    );
+    assert_reissue_error(result, ErrorCode::InvalidPeriod);
+
+    // Clearing the usage restarts the period, so the same limits are accepted
+    let result = try_reissue_delegate(
+        &mut ctx,
+        &debit_context,
+        MAX_TRANSFER_LIMIT,
+        PERIOD_TRANSFER_LIMIT,
+        LIMIT_PERIOD,
+        false,
+    );
+    assert!(result.is_ok(), "Reissue failed: {:?}", result.err());
+}
+
+#[tokio::test]
+async fn test_reissue_delegate_cannot_loosen_when_lower_only() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    setup_merchant_config(
+        &mut ctx,
+        TEST_MERCHANT_ID,
+        MerchantConfigParams {
+            managers_can_only_lower_limits: true,
+            ..Default::default()
+        },
+    );
+    debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT).unwrap();
+
+    // Clearing the usage frees up the whole period limit
+    let result = try_reissue_delegate(
+        &mut ctx,
+        &debit_context,
+        MAX_TRANSFER_LIMIT,
+        PERIOD_TRANSFER_LIMIT,
+        LIMIT_PERIOD,
+        false,
+    );
+    assert_reissue_error(result, ErrorCode::LimitRaiseRequiresAdmin);
+
+    // A shorter period frees up the period limit sooner
+    let result = try_reissue_delegate(
+        &mut ctx,
+        &debit_context,
+        MAX_TRANSFER_LIMIT,
+        PERIOD_TRANSFER_LIMIT,
+        LIMIT_PERIOD - 1,
+        true,
+    );
+    assert_reissue_error(result, ErrorCode::LimitRaiseRequiresAdmin);
+
+    let state = get_user_delegate_state(&ctx, &debit_context);
+    assert_eq!(state.period_transferred_amount, DEBIT_AMOUNT);
+    assert_eq!(state.transfer_limit_period_seconds, LIMIT_PERIOD);
+
+    // Lower limits carrying the usage are still allowed
+    let result = try_reissue_delegate(
+        &mut ctx,
+        &debit_context,
+        MAX_TRANSFER_LIMIT / 2,
+        PERIOD_TRANSFER_LIMIT / 2,
+        LIMIT_PERIOD,
+        true,
+    );
+    assert!(result.is_ok(), "Reissue failed: {:?}", result.err());
+}
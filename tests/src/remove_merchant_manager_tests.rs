@@ -0,0 +1,143 @@
+use crate::common::*;
+use anchor_lang::error::ErrorCode as AnchorErrorCode;
+use anchor_lang::prelude::*;
+use bridge_cards::accounts::RemoveMerchantManager;
+use bridge_cards::errors::ErrorCode;
+use bridge_cards::event_parsing::{parse_events, BridgeCardsEvent};
+use bridge_cards::events::MerchantManagerRemoved;
+use bridge_cards::state::MerchantManagerState;
+use litesvm::types::TransactionResult;
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer};
+
+type TestContext = crate::common::Context;
+
+fn find_merchant_manager_removed_event(logs: &[String]) -> Option<MerchantManagerRemoved> {
+    parse_events(logs)
+        .into_iter()
+        .find_map(|event| match event {
+            BridgeCardsEvent::MerchantManagerRemoved(event) => Some(event),
+            _ => None,
+        })
+}
+
+#[allow(clippy::result_large_err)]
+fn remove_manager(ctx: &mut TestContext, admin: &Keypair, manager: Pubkey) -> TransactionResult {
+    ctx.svm.expire_blockhash();
+    let accounts = RemoveMerchantManager {
+        admin: admin.pubkey(),
+        state: ctx.bridge_cards_state.pubkey,
+        manager_state: ctx.merchant_manager_state.pubkey,
+    };
+    let ix = create_remove_merchant_manager_instruction(ctx, &accounts, TEST_MERCHANT_ID, manager);
+    let tx = create_transaction_with_payer_and_signers(
+        ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, admin],
+    );
+    submit_transaction(ctx, tx)
+}
+
+fn assert_error(result: TransactionResult, expected_message: String) {
+    let err = result.expect_err("Transaction should fail");
+    assert!(
+        err.meta
+            .logs
+            .iter()
+            .any(|log| log.contains(&expected_message)),
+        "Error should contain the expected error message {}, got {}",
+        expected_message,
+        err.meta.logs.join("\n")
+    );
+}
+
+fn read_manager_state(ctx: &TestContext) -> MerchantManagerState {
+    let account = ctx
+        .svm
+        .get_account(&ctx.merchant_manager_state.pubkey)
+        .unwrap();
+    MerchantManagerState::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+#[tokio::test]
+async fn test_remove_merchant_manager() {
+    let mut ctx = setup_and_initialize();
+    let admin = ctx.payer_kp.insecure_clone();
+    let old_manager = ctx.merchant_manager_kp.insecure_clone();
+    let new_manager = Keypair::new();
+    add_merchant_manager(&mut ctx, TEST_MERCHANT_ID, new_manager.pubkey());
+
+    // Rotate the manager key: the new manager was added first, so the merchant always has one
+    let result = remove_manager(&mut ctx, &admin, old_manager.pubkey());
+    assert!(
+        result.is_ok(),
+        "Failed to remove merchant manager: {:?}",
+        result.err()
+    );
+
+    let event = find_merchant_manager_removed_event(&result.unwrap().logs)
+        .expect("MerchantManagerRemoved event not found");
+    assert_eq!(event.merchant_id, TEST_MERCHANT_ID);
+    assert_eq!(event.manager, old_manager.pubkey());
+    assert_eq!(event.active_count, 1);
+    assert_eq!(
+        read_manager_state(&ctx).active_managers(),
+        &[new_manager.pubkey()]
+    );
+
+    // Only the remaining manager can configure debitors
+    let mint_pk = setup_mint(&mut ctx);
+    let debitor = Pubkey::new_unique();
+    assert_error(
+        configure_merchant_debitor(&mut ctx, &old_manager, mint_pk, debitor, true),
+        AnchorErrorCode::ConstraintRaw.to_string(),
+    );
+    let result = configure_merchant_debitor(&mut ctx, &new_manager, mint_pk, debitor, true);
+    assert!(
+        result.is_ok(),
+        "Remaining manager failed to add a debitor: {:?}",
+        result.err()
+    );
+}
+
+#[tokio::test]
+async fn test_cannot_remove_last_merchant_manager() {
+    let mut ctx = setup_and_initialize();
+    let admin = ctx.payer_kp.insecure_clone();
+
+    let manager = ctx.merchant_manager_kp.pubkey();
+    assert_error(
+        remove_manager(&mut ctx, &admin, manager),
+        ErrorCode::CannotRemoveLastManager.to_string(),
+    );
+    assert_eq!(read_manager_state(&ctx).active_managers(), &[manager]);
+}
+
+#[tokio::test]
+async fn test_remove_unknown_merchant_manager() {
+    let mut ctx = setup_and_initialize();
+    let admin = ctx.payer_kp.insecure_clone();
+    add_merchant_manager(&mut ctx, TEST_MERCHANT_ID, Pubkey::new_unique());
+
+    assert_error(
+        remove_manager(&mut ctx, &admin, Pubkey::new_unique()),
+        ErrorCode::ManagerNotFound.to_string(),
+    );
+    assert_eq!(read_manager_state(&ctx).active_count, 2);
+}
+
+#[tokio::test]
+async fn test_non_admin_cannot_remove_merchant_manager() {
+    let mut ctx = setup_and_initialize();
+    let second_manager = Pubkey::new_unique();
+    add_merchant_manager(&mut ctx, TEST_MERCHANT_ID, second_manager);
+
+    let non_admin = Keypair::new();
+    let result = remove_manager(&mut ctx, &non_admin, second_manager);
+    assert!(
+        result.is_err(),
+        "Non-admin should not be able to remove a merchant manager"
+    );
+    assert_eq!(read_manager_state(&ctx).active_count, 2);
+}
@@ -0,0 +1,222 @@
+use crate::common::*;
+use account_data_trait::AccountData;
+use anchor_lang::prelude::*;
+use bridge_cards::accounts::{DebitUserWithSwap, SetFeatureFlags, UpdateAdmin};
+use bridge_cards::errors::ErrorCode;
+use bridge_cards::event_parsing::{parse_events, BridgeCardsEvent};
+use bridge_cards::events::FeatureFlagsUpdated;
+use bridge_cards::instructions::add_or_update_merchant_config::MerchantConfigParams;
+use bridge_cards::state::{feature_flags, BridgeCardsState, CURRENT_VERSION};
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer};
+
+const MAX_TRANSFER_LIMIT: u64 = 100_000_000; // $100 per transaction
+const PERIOD_TRANSFER_LIMIT: u64 = 2_000_000_000; // $2000 per day
+const DEBIT_AMOUNT: u64 = 50_000_000; // $50 debit amount
+
+fn find_feature_flags_event(logs: &[String]) -> Option<FeatureFlagsUpdated> {
+    parse_events(logs)
+        .into_iter()
+        .find_map(|event| match event {
+            BridgeCardsEvent::FeatureFlagsUpdated(event) => Some(event),
+            _ => None,
+        })
+}
+
+fn assert_error(logs: &[String], error: ErrorCode) {
+    let expected_message = error.to_string();
+    assert!(
+        logs.iter().any(|log| log.contains(&expected_message)),
+        "Error should contain the expected error message {}, got {}",
+        expected_message,
+        logs.join("\n")
+    );
+}
+
+#[tokio::test]
+async fn test_set_feature_flags() {
+    let mut ctx = setup_and_initialize();
+
+    let accounts = SetFeatureFlags {
+        admin: ctx.payer_pk,
+        state: ctx.bridge_cards_state.pubkey,
+    };
+    let flags = feature_flags::PAUSED | feature_flags::SWAPS_DISABLED;
+    let ix = create_set_feature_flags_instruction(&ctx, &accounts, flags);
+    let tx = create_transaction(&ctx, &[ix]);
+
+    let result = submit_transaction(&mut ctx, tx);
+    assert!(
+        result.is_ok(),
+        "Failed to set feature flags: {:?}",
+        result.err()
+    );
+
+    // Verify the FeatureFlagsUpdated event
+    let event = find_feature_flags_event(&result.unwrap().logs)
+        .expect("FeatureFlagsUpdated event not found");
+    assert_eq!(event.previous_flags, 0);
+    assert_eq!(event.new_flags, flags);
+
+    // Verify the state
+    let expected_state_data = BridgeCardsState {
+        version: CURRENT_VERSION,
+        admin: ctx.payer_pk,
+        bump: ctx.bridge_cards_state.bump,
+        feature_flags: flags,
+        protocol_fee_bps: 0,
+        protocol_fee_owner: Pubkey::default(),
+    }
+    .account_data();
+    let state_account = ctx.svm.get_account(&ctx.bridge_cards_state.pubkey).unwrap();
+    assert_eq!(
+        state_account.data, expected_state_data,
+        "State account data doesn't match expected data"
+    );
+}
+
+#[tokio::test]
+async fn test_non_admin_cannot_set_feature_flags() {
+    let mut ctx = setup_and_initialize();
+    let (non_admin_kp, non_admin_pk) = setup_keypair(&mut ctx);
+
+    let accounts = SetFeatureFlags {
+        admin: non_admin_pk,
+        state: ctx.bridge_cards_state.pubkey,
+    };
+    let ix = create_set_feature_flags_instruction(&ctx, &accounts, feature_flags::PAUSED);
+    let tx = create_transaction_with_payer_and_signers(
+        &ctx,
+        &[ix],
+        Some(&non_admin_pk),
+        &[&non_admin_kp],
+    );
+
+    let result = submit_transaction(&mut ctx, tx);
+    assert!(
+        result.is_err(),
+        "Non-admin should not be able to set feature flags"
+    );
+}
+
+#[tokio::test]
+async fn test_paused_flag_blocks_debits() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+
+    set_feature_flags(&mut ctx, feature_flags::PAUSED);
+    let err = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT)
+        .expect_err("Debit should fail while paused");
+    assert_error(&err.meta.logs, ErrorCode::ProgramPaused);
+
+    // Clearing the flag resumes debits
+    set_feature_flags(&mut ctx, 0);
+    let result = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT);
+    assert!(
+        result.is_ok(),
+        "Debit after unpausing failed: {:?}",
+        result.err()
+    );
+}
+
+#[tokio::test]
+async fn test_paused_flag_does_not_block_admin_recovery() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    set_feature_flags(&mut ctx, feature_flags::PAUSED);
+
+    // The admin can still be handed over while paused
+    let new_admin = Keypair::new();
+    let accounts = UpdateAdmin {
+        admin: ctx.payer_pk,
+        payer: ctx.payer_pk,
+        state: ctx.bridge_cards_state.pubkey,
+        new_admin: new_admin.pubkey(),
+    };
+    let ix = create_update_admin_instruction(&ctx, accounts);
+    let tx = create_transaction_with_payer_and_signers(
+        &ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &new_admin],
+    );
+    let result = submit_transaction(&mut ctx, tx);
+    assert!(
+        result.is_ok(),
+        "Updating the admin while paused failed: {:?}",
+        result.err()
+    );
+
+    // And the new admin can unpause
+    ctx.svm.expire_blockhash();
+    let accounts = SetFeatureFlags {
+        admin: new_admin.pubkey(),
+        state: ctx.bridge_cards_state.pubkey,
+    };
+    let ix = create_set_feature_flags_instruction(&ctx, &accounts, 0);
+    let tx = create_transaction_with_payer_and_signers(
+        &ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &new_admin],
+    );
+    let result = submit_transaction(&mut ctx, tx);
+    assert!(result.is_ok(), "Unpausing failed: {:?}", result.err());
+
+    let result = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT);
+    assert!(
+        result.is_ok(),
+        "Debit after unpausing failed: {:?}",
+        result.err()
+    );
+}
+
+#[tokio::test]
+async fn test_swaps_disabled_flag_blocks_only_swaps() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    setup_router(&mut ctx, &MOCK_ROUTER_PROGRAM_ID, true);
+    setup_merchant_config(
+        &mut ctx,
+        TEST_MERCHANT_ID,
+        MerchantConfigParams {
+            router_program: MOCK_ROUTER_PROGRAM_ID,
+            ..Default::default()
+        },
+    );
+
+    set_feature_flags(&mut ctx, feature_flags::SWAPS_DISABLED);
+
+    let accounts = DebitUserWithSwap {
+        debit: make_debit_user_accounts(&ctx, &debit_context),
+        router_state: make_router_pda(&MOCK_ROUTER_PROGRAM_ID, &ctx.program_id).pubkey,
+        router_program: MOCK_ROUTER_PROGRAM_ID,
+    };
+    let ix = create_debit_user_with_swap_instruction(
+        &ctx,
+        &accounts,
+        TEST_MERCHANT_ID,
+        DEBIT_AMOUNT,
+        b"swap".to_vec(),
+        &[],
+    );
+    let tx = create_transaction_with_payer_and_signers(
+        &ctx,
+        &[ix],
+        Some(&ctx.payer_pk),
+        &[&ctx.payer_kp, &debit_context.debitor_kp],
+    );
+    let err = submit_transaction(&mut ctx, tx).expect_err("Swap should fail while disabled");
+    assert_error(&err.meta.logs, ErrorCode::SwapsDisabled);
+
+    // Plain debits are unaffected
+    let result = debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT);
+    assert!(
+        result.is_ok(),
+        "Debit with swaps disabled failed: {:?}",
+        result.err()
+    );
+}
@@ -0,0 +1,138 @@
+use crate::common::*;
+use anchor_lang::prelude::*;
+use bridge_cards::accounts::SetProtocolFee;
+use bridge_cards::errors::ErrorCode;
+use bridge_cards::event_parsing::{parse_events, BridgeCardsEvent};
+use bridge_cards::events::ProtocolFeeUpdated;
+use bridge_cards::state::BridgeCardsState;
+use litesvm::types::TransactionResult;
+use solana_program_test::tokio;
+
+fn find_protocol_fee_event(logs: &[String]) -> Option<ProtocolFeeUpdated> {
+    parse_events(logs)
+        .into_iter()
+        .find_map(|event| match event {
+            BridgeCardsEvent::ProtocolFeeUpdated(event) => Some(event),
+            _ => None,
+        })
+}
+
+fn get_state(ctx: &crate::common::Context) -> BridgeCardsState {
+    let account = ctx.svm.get_account(&ctx.bridge_cards_state.pubkey).unwrap();
+    BridgeCardsState::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+#[allow(clippy::result_large_err)]
+fn set_protocol_fee(
+    ctx: &mut crate::common::Context,
+    fee_bps: u16,
+    fee_owner: Pubkey,
+) -> TransactionResult {
+    let accounts = SetProtocolFee {
+        admin: ctx.payer_pk,
+        state: ctx.bridge_cards_state.pubkey,
+    };
+    let ix = create_set_protocol_fee_instruction(ctx, &accounts, fee_bps, fee_owner);
+    let tx = create_transaction(ctx, &[ix]);
+    submit_transaction(ctx, tx)
+}
+
+#[tokio::test]
+async fn test_set_protocol_fee() {
+    let mut ctx = setup_and_initialize();
+    let (_, fee_owner) = setup_keypair(&mut ctx);
+
+    let accounts = SetProtocolFee {
+        admin: ctx.payer_pk,
+        state: ctx.bridge_cards_state.pubkey,
+    };
+    let ix = create_set_protocol_fee_instruction(&ctx, &accounts, 250, fee_owner);
+    let tx = create_transaction(&ctx, &[ix]);
+
+    let result = submit_transaction(&mut ctx, tx);
+    assert!(
+        result.is_ok(),
+        "Failed to set protocol fee: {:?}",
+        result.err()
+    );
+
+    let event =
+        find_protocol_fee_event(&result.unwrap().logs).expect("ProtocolFeeUpdated event not found");
+    assert_eq!(event.previous_fee_bps, 0);
+    assert_eq!(event.fee_bps, 250);
+    assert_eq!(event.fee_owner, fee_owner);
+
+    let state = get_state(&ctx);
+    assert_eq!(state.protocol_fee_bps, 250);
+    assert_eq!(state.protocol_fee_owner, fee_owner);
+}
+
+#[tokio::test]
+async fn test_set_protocol_fee_up_to_max() {
+    let mut ctx = setup_and_initialize();
+    let (_, fee_owner) = setup_keypair(&mut ctx);
+
+    for fee_bps in [
+        BridgeCardsState::MAX_PROTOCOL_FEE_BPS - 1,
+        BridgeCardsState::MAX_PROTOCOL_FEE_BPS,
+    ] {
+        ctx.svm.expire_blockhash();
+        let result = set_protocol_fee(&mut ctx, fee_bps, fee_owner);
+        assert!(
+            result.is_ok(),
+            "Failed to set protocol fee {}: {:?}",
+            fee_bps,
+            result.err()
+        );
+        assert_eq!(get_state(&ctx).protocol_fee_bps, fee_bps);
+    }
+}
+
+#[tokio::test]
+async fn test_set_protocol_fee_above_max_rejected() {
+    let mut ctx = setup_and_initialize();
+    let (_, fee_owner) = setup_keypair(&mut ctx);
+
+    let err = set_protocol_fee(
+        &mut ctx,
+        BridgeCardsState::MAX_PROTOCOL_FEE_BPS + 1,
+        fee_owner,
+    )
+    .expect_err("Fee above the maximum accepted");
+    let expected_message = ErrorCode::FeeTooHigh.to_string();
+    assert!(
+        err.meta
+            .logs
+            .iter()
+            .any(|log| log.contains(&expected_message)),
+        "Error should contain the expected error message {}, got {}",
+        expected_message,
+        err.meta.logs.join("\n")
+    );
+    assert_eq!(get_state(&ctx).protocol_fee_bps, 0);
+}
+
+#[tokio::test]
+async fn test_non_admin_cannot_set_protocol_fee() {
+    let mut ctx = setup_and_initialize();
+    let (non_admin_kp, non_admin_pk) = setup_keypair(&mut ctx);
+
+    let accounts = SetProtocolFee {
+        admin: non_admin_pk,
+        state: ctx.bridge_cards_state.pubkey,
+    };
+    let ix = create_set_protocol_fee_instruction(&ctx, &accounts, 250, non_admin_pk);
+    let tx = create_transaction_with_payer_and_signers(
+        &ctx,
+        &[ix],
+        Some(&non_admin_pk),
+        &[&non_admin_kp],
+    );
+
+    let result = submit_transaction(&mut ctx, tx);
+    assert!(
+        result.is_err(),
+        "Non-admin should not be able to set the protocol fee"
+    );
+    assert_eq!(get_state(&ctx).protocol_fee_bps, 0);
+}
@@ -0,0 +1,201 @@
+use crate::common::*;
+use anchor_lang::prelude::*;
+use bridge_cards::errors::ErrorCode;
+use bridge_cards::event_parsing::{parse_events, BridgeCardsEvent};
+use bridge_cards::events::DebitSimulationResult;
+use litesvm_token::CreateAssociatedTokenAccountIdempotent;
+use solana_program_test::tokio;
+use solana_sdk::account::Account;
+use solana_sdk::signature::{Keypair, Signer};
+
+const MAX_TRANSFER_LIMIT: u64 = 100_000_000; // $100 per transaction
+const PERIOD_TRANSFER_LIMIT: u64 = 2_000_000_000; // $2000 per day
+
+type TestContext = crate::common::Context;
+
+fn find_debit_simulation_result_event(logs: &[String]) -> Option<DebitSimulationResult> {
+    parse_events(logs)
+        .into_iter()
+        .find_map(|event| match event {
+            BridgeCardsEvent::DebitSimulationResult(event) => Some(event),
+            _ => None,
+        })
+}
+
+/// Simulate a debit, signed by the payer only, and return the reported outcome
+fn simulate_debit_user(
+    ctx: &mut TestContext,
+    debit_context: &DebitUserContext,
+    destination_token_account: Pubkey,
+    amount: u64,
+) -> DebitSimulationResult {
+    ctx.svm.expire_blockhash();
+    let mut accounts = make_simulate_debit_user_accounts(ctx, debit_context);
+    accounts.destination_token_account = destination_token_account;
+    accounts.destination_state = make_merchant_destination_pda(
+        TEST_MERCHANT_ID,
+        &debit_context.mint_pk,
+        &destination_token_account,
+        &ctx.program_id,
+    )
+    .pubkey;
+    let ix = create_simulate_debit_user_instruction(ctx, &accounts, TEST_MERCHANT_ID, amount);
+    let tx = create_transaction(ctx, &[ix]);
+    let meta = submit_transaction(ctx, tx).expect("Simulating a debit should not fail");
+    find_debit_simulation_result_event(&meta.logs).expect("DebitSimulationResult event not found")
+}
+
+/// Accounts a debit would update
+fn debited_accounts(ctx: &TestContext, debit_context: &DebitUserContext) -> Vec<Account> {
+    [
+        debit_context.user_delegate_pda,
+        debit_context.debitor_state_pda,
+        debit_context.user_token_account,
+        debit_context.destination_token_account,
+    ]
+    .iter()
+    .map(|account| ctx.svm.get_account(account).unwrap())
+    .collect()
+}
+
+fn reason_code(error: ErrorCode) -> u64 {
+    u64::from(u32::from(error))
+}
+
+#[tokio::test]
+async fn test_simulate_debit_within_limits() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    let accounts_before = debited_accounts(&ctx, &debit_context);
+
+    let destination = debit_context.destination_token_account;
+    let result = simulate_debit_user(&mut ctx, &debit_context, destination, MAX_TRANSFER_LIMIT);
+    assert!(result.success);
+    assert_eq!(result.reason_code, 0);
+    assert_eq!(result.merchant_id, TEST_MERCHANT_ID);
+    assert_eq!(result.user_delegate, debit_context.user_delegate_pda);
+    assert_eq!(result.debitor, debit_context.debitor_pk);
+    assert_eq!(result.destination_ata, destination);
+    assert_eq!(result.amount, MAX_TRANSFER_LIMIT);
+
+    // Nothing moved, and the debit itself still goes through
+    assert_eq!(debited_accounts(&ctx, &debit_context), accounts_before);
+    let result = debit_user(&mut ctx, &debit_context, MAX_TRANSFER_LIMIT);
+    assert!(result.is_ok(), "Debit failed: {:?}", result.err());
+}
+
+#[tokio::test]
+async fn test_simulate_debit_over_limit_reports_failure() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    let accounts_before = debited_accounts(&ctx, &debit_context);
+
+    let destination = debit_context.destination_token_account;
+    let result = simulate_debit_user(
+        &mut ctx,
+        &debit_context,
+        destination,
+        MAX_TRANSFER_LIMIT + 1,
+    );
+    assert!(!result.success);
+    assert_eq!(
+        result.reason_code,
+        reason_code(ErrorCode::ExceedsMaxTransferLimit)
+    );
+
+    // The failed check left the delegate, debitor and token accounts untouched
+    assert_eq!(debited_accounts(&ctx, &debit_context), accounts_before);
+    verify_token_account_balance(
+        &ctx,
+        &debit_context.user_token_account,
+        INITIAL_BALANCE,
+        debit_context.token_program,
+        "User balance should be unchanged",
+    );
+}
+
+#[tokio::test]
+async fn test_simulate_debit_counts_period_usage() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, MAX_TRANSFER_LIMIT);
+    debit_user(&mut ctx, &debit_context, MAX_TRANSFER_LIMIT / 2).unwrap();
+    advance_slots(&mut ctx, 1);
+
+    // The amount is within the per-transfer limit but not what is left of the period
+    let destination = debit_context.destination_token_account;
+    let result = simulate_debit_user(&mut ctx, &debit_context, destination, MAX_TRANSFER_LIMIT);
+    assert!(!result.success);
+    assert_eq!(
+        result.reason_code,
+        reason_code(ErrorCode::ExceedsTransferLimitPerPeriod)
+    );
+    let result = simulate_debit_user(
+        &mut ctx,
+        &debit_context,
+        destination,
+        MAX_TRANSFER_LIMIT / 2,
+    );
+    assert!(result.success);
+}
+
+#[tokio::test]
+async fn test_simulate_debit_unlisted_destination_reports_failure() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+
+    let unlisted_destination = CreateAssociatedTokenAccountIdempotent::new(
+        &mut ctx.svm,
+        &ctx.payer_kp,
+        &debit_context.mint_pk,
+    )
+    .owner(&Keypair::new().pubkey())
+    .send()
+    .unwrap();
+    let result = simulate_debit_user(&mut ctx, &debit_context, unlisted_destination, 1);
+    assert!(!result.success);
+    assert_eq!(
+        result.reason_code,
+        reason_code(ErrorCode::DestinationNotAllowed)
+    );
+}
+
+#[tokio::test]
+async fn test_simulate_debit_disallowed_debitor_reports_failure() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+    let manager = ctx.merchant_manager_kp.insecure_clone();
+    configure_merchant_debitor(
+        &mut ctx,
+        &manager,
+        debit_context.mint_pk,
+        debit_context.debitor_pk,
+        false,
+    )
+    .unwrap();
+
+    let destination = debit_context.destination_token_account;
+    let result = simulate_debit_user(&mut ctx, &debit_context, destination, 1);
+    assert!(!result.success);
+    assert_eq!(
+        result.reason_code,
+        reason_code(ErrorCode::DebitorNotAuthorizedForMint)
+    );
+}
+
+#[tokio::test]
+async fn test_simulate_debit_to_user_token_account_reports_failure() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+
+    // debit_user rejects the user's own account as a destination, so the simulation does too
+    let destination = debit_context.user_token_account;
+    let result = simulate_debit_user(&mut ctx, &debit_context, destination, MAX_TRANSFER_LIMIT);
+    assert!(!result.success);
+    assert_eq!(result.reason_code, reason_code(ErrorCode::SelfTransfer));
+}
@@ -0,0 +1,60 @@
+use crate::common::*;
+use anchor_lang::prelude::*;
+use solana_program_test::tokio;
+
+type TestContext = crate::common::Context;
+
+const MAX_TRANSFER_LIMIT: u64 = 100_000_000; // $100 per transaction
+const PERIOD_TRANSFER_LIMIT: u64 = 2_000_000_000; // $2000 per day
+const DEBIT_AMOUNT: u64 = 50_000_000; // $50 debit amount
+
+fn advance_time(ctx: &mut TestContext, seconds: i64) {
+    let mut clock = ctx.svm.get_sysvar::<Clock>();
+    clock.unix_timestamp += seconds;
+    clock.slot += 1;
+    ctx.svm.set_sysvar(&clock);
+}
+
+#[tokio::test]
+async fn test_time_until_reset_within_period() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+
+    // The first debit starts a new period at the current time
+    advance_time(&mut ctx, LIMIT_PERIOD as i64 + 1);
+    debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT).unwrap();
+    assert_eq!(
+        time_until_reset(&mut ctx, &debit_context),
+        LIMIT_PERIOD as u64
+    );
+
+    // One hour into the period
+    advance_time(&mut ctx, 3600);
+    assert_eq!(
+        time_until_reset(&mut ctx, &debit_context),
+        LIMIT_PERIOD as u64 - 3600
+    );
+
+    // One second before the period ends
+    advance_time(&mut ctx, LIMIT_PERIOD as i64 - 3601);
+    assert_eq!(time_until_reset(&mut ctx, &debit_context), 1);
+}
+
+#[tokio::test]
+async fn test_time_until_reset_after_period_elapsed() {
+    let mut ctx = setup_and_initialize();
+    let debit_context =
+        setup_merchant_and_user_delegate(&mut ctx, MAX_TRANSFER_LIMIT, PERIOD_TRANSFER_LIMIT);
+
+    advance_time(&mut ctx, LIMIT_PERIOD as i64 + 1);
+    debit_user(&mut ctx, &debit_context, DEBIT_AMOUNT).unwrap();
+
+    // Exactly at the end of the period
+    advance_time(&mut ctx, LIMIT_PERIOD as i64);
+    assert_eq!(time_until_reset(&mut ctx, &debit_context), 0);
+
+    // Well past the end of the period
+    advance_time(&mut ctx, LIMIT_PERIOD as i64 * 3);
+    assert_eq!(time_until_reset(&mut ctx, &debit_context), 0);
+}
@@ -1,9 +1,12 @@
 use crate::common::*;
 use account_data_trait::AccountData;
 use anchor_lang::error::ErrorCode;
-use anchor_lang::{prelude::*, Event};
-use base64;
-use bridge_cards::{accounts::UpdateAdmin, events::AdminUpdated, state::BridgeCardsState};
+use anchor_lang::prelude::*;
+use bridge_cards::{
+    accounts::UpdateAdmin,
+    event_parsing::{parse_events, BridgeCardsEvent},
+    state::{BridgeCardsState, CURRENT_VERSION},
+};
 use solana_program_test::tokio;
 use solana_sdk::signature::Keypair;
 use solana_sdk::signature::Signer;
@@ -38,21 +41,14 @@ async fn test_update_admin() {
     let meta = result.unwrap();
 
     let mut event_found = false;
-    for log in meta.logs.iter() {
-        if let Some(data_str) = log.strip_prefix("Program data: ") {
-            if let Ok(log_bytes) = base64::decode(data_str) {
-                if log_bytes.len() > 8 {
-                    let event_data = &log_bytes[8..];
-                    if let Ok(parsed_event) = AdminUpdated::try_from_slice(event_data) {
-                        assert_eq!(
-                            parsed_event.admin, new_admin_pk,
-                            "Updated admin does not match"
-                        );
-                        event_found = true;
-                        break;
-                    }
-                }
-            }
+    for event in parse_events(&meta.logs) {
+        if let BridgeCardsEvent::AdminUpdated(parsed_event) = event {
+            assert_eq!(
+                parsed_event.admin, new_admin_pk,
+                "Updated admin does not match"
+            );
+            event_found = true;
+            break;
         }
     }
     assert!(
@@ -63,8 +59,12 @@ async fn test_update_admin() {
 
     // step 4: verify the state
     let expected_state_data = BridgeCardsState {
+        version: CURRENT_VERSION,
         admin: new_admin_pk,
         bump: ctx.bridge_cards_state.bump,
+        feature_flags: 0,
+        protocol_fee_bps: 0,
+        protocol_fee_owner: Pubkey::default(),
     }
     .account_data();
 